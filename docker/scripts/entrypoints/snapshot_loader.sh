@@ -17,7 +17,7 @@ case "${snapshot_mode}" in
     echo "Snapshot loader disabled (SNAPSHOT_MODE=none)"
     exit 0
     ;;
-  balance-history)
+  balance-history|balance-history-peer)
     ;;
   *)
     echo "Unsupported SNAPSHOT_MODE=${snapshot_mode}" >&2
@@ -25,10 +25,20 @@ case "${snapshot_mode}" in
     ;;
 esac
 
+# In peer mode the marker records the peer URL of the snapshot; the manifest comes from the peer.
+snapshot_file="${BH_SNAPSHOT_FILE:-}"
+snapshot_manifest="${BH_SNAPSHOT_MANIFEST:-}"
+snapshot_peer_url="${BH_SNAPSHOT_PEER_URL:-}"
+if [[ "${snapshot_mode}" == "balance-history-peer" ]]; then
+  marker_file="${snapshot_peer_url%/}/snapshots/${snapshot_file}"
+  marker_manifest=""
+else
+  marker_file="${snapshot_file}"
+  marker_manifest="${snapshot_manifest}"
+fi
+
 if [[ -d "${db_dir}" ]] && find "${db_dir}" -mindepth 1 -print -quit | grep -q .; then
-  snapshot_file="${BH_SNAPSHOT_FILE:-}"
-  snapshot_manifest="${BH_SNAPSHOT_MANIFEST:-}"
-  if snapshot_marker_matches "${marker_path}" "${snapshot_mode}" "${snapshot_file}" "${snapshot_manifest}"; then
+  if snapshot_marker_matches "${marker_path}" "${snapshot_mode}" "${marker_file}" "${marker_manifest}"; then
     echo "Existing balance-history DB and matching snapshot marker detected under ${root_dir}; skipping snapshot install"
     exit 0
   fi
@@ -36,28 +46,40 @@ if [[ -d "${db_dir}" ]] && find "${db_dir}" -mindepth 1 -print -quit | grep -q .
   exit 1
 fi
 
-snapshot_file="${BH_SNAPSHOT_FILE:-}"
 if [[ -z "${snapshot_file}" ]]; then
-  echo "SNAPSHOT_MODE=balance-history requires BH_SNAPSHOT_FILE" >&2
+  echo "SNAPSHOT_MODE=${snapshot_mode} requires BH_SNAPSHOT_FILE" >&2
   exit 1
 fi
 
-if [[ ! -f "${snapshot_file}" ]]; then
+if [[ "${snapshot_mode}" == "balance-history-peer" ]]; then
+  if [[ -z "${snapshot_peer_url}" ]]; then
+    echo "SNAPSHOT_MODE=balance-history-peer requires BH_SNAPSHOT_PEER_URL" >&2
+    exit 1
+  fi
+  if [[ "${snapshot_file}" == */* ]]; then
+    echo "SNAPSHOT_MODE=balance-history-peer expects BH_SNAPSHOT_FILE to be a file name offered by the peer: ${snapshot_file}" >&2
+    exit 1
+  fi
+elif [[ ! -f "${snapshot_file}" ]]; then
   echo "Snapshot file does not exist: ${snapshot_file}" >&2
   exit 1
 fi
 
-snapshot_manifest="${BH_SNAPSHOT_MANIFEST:-}"
 if [[ -f "${marker_path}" ]]; then
   echo "Removing stale snapshot marker at ${marker_path}" >&2
   rm -f "${marker_path}"
 fi
 
-args=(--root-dir "${root_dir}" install-snapshot --file "${snapshot_file}")
-if [[ -n "${snapshot_manifest}" ]]; then
-  args+=(--manifest "${snapshot_manifest}")
+if [[ "${snapshot_mode}" == "balance-history-peer" ]]; then
+  # Downloads resume from ${root_dir}/snapshots if the loader is restarted.
+  args=(--root-dir "${root_dir}" fetch-snapshot --peer "${snapshot_peer_url}" --file "${snapshot_file}")
+else
+  args=(--root-dir "${root_dir}" install-snapshot --file "${snapshot_file}")
+  if [[ -n "${snapshot_manifest}" ]]; then
+    args+=(--manifest "${snapshot_manifest}")
+  fi
 fi
 
 balance-history "${args[@]}"
-snapshot_marker_write "${marker_path}" "${snapshot_mode}" "${snapshot_file}" "${snapshot_manifest}"
+snapshot_marker_write "${marker_path}" "${snapshot_mode}" "${marker_file}" "${marker_manifest}"
 echo "Snapshot install completed and marker written to ${marker_path}"
@@ -70,6 +70,8 @@ max_sync_block_height = ${BH_SYNC_MAX_SYNC_BLOCK_HEIGHT:-4294967295}
 [rpc_server]
 host = "${BH_RPC_HOST:-0.0.0.0}"
 port = ${BH_RPC_PORT:-28010}
+ws_port = ${BH_WS_PORT:-28011}
+snapshot_port = ${BH_SNAPSHOT_HTTP_PORT:-0}
 
 [snapshot]
 trust_mode = "${BH_SNAPSHOT_TRUST_MODE:-dev}"
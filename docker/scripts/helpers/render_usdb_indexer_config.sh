@@ -67,6 +67,8 @@ cat >>"${output_path}" <<EOF
     "rpc_server_host": "${USDB_INDEXER_RPC_HOST:-0.0.0.0}",
     "rpc_server_port": ${USDB_INDEXER_RPC_PORT:-28020},
     "rpc_server_enabled": ${USDB_RPC_SERVER_ENABLED:-true},
+    "ws_server_port": ${USDB_INDEXER_WS_PORT:-28021},
+    "ws_server_enabled": ${USDB_WS_SERVER_ENABLED:-true},
     "pass_energy_leaderboard_cache_enabled": ${PASS_ENERGY_LEADERBOARD_CACHE_ENABLED:-true},
     "pass_energy_leaderboard_cache_top_k": ${PASS_ENERGY_LEADERBOARD_CACHE_TOP_K:-1000}
   }
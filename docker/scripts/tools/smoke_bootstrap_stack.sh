@@ -166,12 +166,16 @@ BTC_P2P_BIND_PORT=0
 BH_ROOT_DIR=/data/balance-history
 BH_RPC_PORT=28110
 BH_BIND_PORT=0
+BH_WS_PORT=28111
+BH_WS_BIND_PORT=0
 BH_SNAPSHOT_TRUST_MODE=dev
 WAIT_FOR_BTC_TIMEOUT_SECS=120
 
 USDB_INDEXER_ROOT_DIR=/data/usdb-indexer
 USDB_INDEXER_RPC_PORT=28120
 USDB_INDEXER_BIND_PORT=0
+USDB_INDEXER_WS_PORT=28121
+USDB_INDEXER_WS_BIND_PORT=0
 USDB_GENESIS_BLOCK_HEIGHT=1
 INSCRIPTION_SOURCE=bitcoind
 
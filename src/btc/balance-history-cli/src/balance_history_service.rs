@@ -48,6 +48,19 @@ impl BalanceHistoryService {
                 println!("Sending stop command to the service...");
                 self.client.stop().await?;
             }
+            Commands::CreateCheckpoint => {
+                println!("Creating checkpoint...");
+                let info = self.client.create_checkpoint().await?;
+                println!(
+                    "Checkpoint created at height {}: {}",
+                    info.manifest.state_ref.block_height, info.checkpoint_dir
+                );
+                println!(
+                    "block_commit={}",
+                    info.manifest.state_ref.latest_block_commit
+                );
+                println!("manifest_file={}", info.manifest_file);
+            }
             Commands::Balance { user, mut position } => {
                 // Handle Balance command
                 let user_id = match UserId::from_str(&user) {
@@ -32,6 +32,9 @@ pub enum Commands {
     /// Stop the balance history service
     Stop,
 
+    /// Take a hot RocksDB checkpoint of the running service
+    CreateCheckpoint,
+
     /// Get balance history for one script_hash
     Balance {
         #[arg(value_name = "USER_ID")]
@@ -200,6 +200,7 @@ max_sync_block_height = 4294967295
 
 [rpc_server]
 port = ${BH_RPC_PORT}
+ws_port = 0
 EOF
 }
 
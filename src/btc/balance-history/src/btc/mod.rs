@@ -2,10 +2,12 @@ mod client;
 mod file_indexer;
 mod local_loader;
 mod rpc;
+mod zmq;
 
 pub use client::*;
 pub use file_indexer::*;
 pub use local_loader::*;
+pub use zmq::*;
 
 use crate::config::BalanceHistoryConfigRef;
 use crate::db::BalanceHistoryDBRef;
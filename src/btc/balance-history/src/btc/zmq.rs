@@ -0,0 +1,166 @@
+use super::client::{BTCClient, BTCClientRef, BTCClientType};
+use bitcoincore_rpc::bitcoin::{Amount, Block, BlockHash, OutPoint, ScriptBuf};
+use std::sync::Arc;
+use usdb_util::ZmqBlockSubscriberRef;
+
+// RPC client decorator that serves blocks already pushed over bitcoind ZMQ.
+// Near the tip this saves the `getblock` round trip; anything else falls through.
+pub struct ZmqHintedBTCClient {
+    inner: BTCClientRef,
+    block_notifier: ZmqBlockSubscriberRef,
+}
+
+impl ZmqHintedBTCClient {
+    pub fn new(inner: BTCClientRef, block_notifier: ZmqBlockSubscriberRef) -> Self {
+        Self {
+            inner,
+            block_notifier,
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl BTCClient for ZmqHintedBTCClient {
+    fn get_type(&self) -> BTCClientType {
+        self.inner.get_type()
+    }
+
+    fn init(&self) -> Result<(), String> {
+        self.inner.init()
+    }
+
+    fn stop(&self) -> Result<(), String> {
+        self.inner.stop()
+    }
+
+    fn on_sync_complete(&self, block_height: u32) -> Result<(), String> {
+        self.inner.on_sync_complete(block_height)
+    }
+
+    fn get_latest_block_height(&self) -> Result<u32, String> {
+        self.inner.get_latest_block_height()
+    }
+
+    fn get_block_hash(&self, block_height: u32) -> Result<BlockHash, String> {
+        self.inner.get_block_hash(block_height)
+    }
+
+    fn get_block_by_hash(&self, block_hash: &BlockHash) -> Result<Block, String> {
+        if let Some(block) = self.block_notifier.get_block(block_hash) {
+            return Ok(block.as_ref().clone());
+        }
+
+        self.inner.get_block_by_hash(block_hash)
+    }
+
+    fn get_block_by_height(&self, block_height: u32) -> Result<Block, String> {
+        // Resolving the hash first keeps the lookup reorg-safe: a cached body is only
+        // used when it is the canonical block at this height.
+        let block_hash = self.inner.get_block_hash(block_height)?;
+        self.get_block_by_hash(&block_hash)
+    }
+
+    async fn get_blocks(&self, start_height: u32, end_height: u32) -> Result<Vec<Block>, String> {
+        self.inner.get_blocks(start_height, end_height).await
+    }
+
+    fn get_utxo(&self, outpoint: &OutPoint) -> Result<(ScriptBuf, Amount), String> {
+        self.inner.get_utxo(outpoint)
+    }
+}
+
+pub fn create_zmq_hinted_btc_client(
+    inner: BTCClientRef,
+    block_notifier: ZmqBlockSubscriberRef,
+) -> BTCClientRef {
+    Arc::new(Box::new(ZmqHintedBTCClient::new(inner, block_notifier)) as Box<dyn BTCClient>)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bitcoincore_rpc::bitcoin::Network;
+    use bitcoincore_rpc::bitcoin::blockdata::constants::genesis_block;
+    use usdb_util::{ZmqBlockEvent, ZmqBlockSubscriber};
+
+    // Knows the canonical hash at height 0 but cannot serve block bodies.
+    struct HashOnlyBTCClient {
+        tip_hash: BlockHash,
+    }
+
+    #[async_trait::async_trait]
+    impl BTCClient for HashOnlyBTCClient {
+        fn get_type(&self) -> BTCClientType {
+            BTCClientType::RPC
+        }
+
+        fn init(&self) -> Result<(), String> {
+            Ok(())
+        }
+
+        fn stop(&self) -> Result<(), String> {
+            Ok(())
+        }
+
+        fn on_sync_complete(&self, _block_height: u32) -> Result<(), String> {
+            Ok(())
+        }
+
+        fn get_latest_block_height(&self) -> Result<u32, String> {
+            Ok(0)
+        }
+
+        fn get_block_hash(&self, block_height: u32) -> Result<BlockHash, String> {
+            match block_height {
+                0 => Ok(self.tip_hash),
+                _ => Err(format!("Missing block hash at height {}", block_height)),
+            }
+        }
+
+        fn get_block_by_hash(&self, block_hash: &BlockHash) -> Result<Block, String> {
+            Err(format!("Block body not available: {}", block_hash))
+        }
+
+        fn get_block_by_height(&self, block_height: u32) -> Result<Block, String> {
+            Err(format!("Block body not available at {}", block_height))
+        }
+
+        async fn get_blocks(
+            &self,
+            _start_height: u32,
+            _end_height: u32,
+        ) -> Result<Vec<Block>, String> {
+            Err("not implemented in HashOnlyBTCClient".to_string())
+        }
+
+        fn get_utxo(&self, _outpoint: &OutPoint) -> Result<(ScriptBuf, Amount), String> {
+            Err("not implemented in HashOnlyBTCClient".to_string())
+        }
+    }
+
+    #[test]
+    fn test_zmq_hinted_client_serves_canonical_pushed_block() {
+        let block = genesis_block(Network::Regtest);
+        let inner: BTCClientRef = Arc::new(Box::new(HashOnlyBTCClient {
+            tip_hash: block.block_hash(),
+        }) as Box<dyn BTCClient>);
+        let notifier = Arc::new(ZmqBlockSubscriber::new("tcp://127.0.0.1:28332"));
+        let client = create_zmq_hinted_btc_client(inner, notifier.clone());
+
+        // Nothing pushed yet: the request falls through to the inner client.
+        assert!(client.get_block_by_height(0).is_err());
+
+        // A pushed block that is not canonical at the height is never served.
+        let mut stale = block.clone();
+        stale.header.nonce += 1;
+        notifier.on_event(ZmqBlockEvent::RawBlock(stale));
+        assert!(client.get_block_by_height(0).is_err());
+
+        notifier.on_event(ZmqBlockEvent::RawBlock(block.clone()));
+        assert_eq!(client.get_block_by_height(0).unwrap(), block);
+        assert_eq!(
+            client.get_block_by_hash(&block.block_hash()).unwrap(),
+            block
+        );
+    }
+}
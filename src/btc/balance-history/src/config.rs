@@ -50,6 +50,10 @@ fn default_undo_cleanup_interval_blocks() -> u32 {
     16
 }
 
+fn default_history_prune_interval_blocks() -> u32 {
+    1008
+}
+
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct IndexConfig {
     /// Threshold of blocks behind to switch to LocalLoader client
@@ -82,6 +86,33 @@ pub struct IndexConfig {
     /// Block interval used to trigger low-frequency undo journal pruning.
     #[serde(default = "default_undo_cleanup_interval_blocks")]
     pub undo_cleanup_interval_blocks: u32,
+
+    /// Maintain a bitcoind-compatible MuHash of the UTXO set per height.
+    /// Costs one 32-byte digest per UTXO plus a 768-byte state per height, and must be
+    /// enabled before syncing from genesis.
+    #[serde(default)]
+    pub utxo_set_hash: bool,
+
+    /// Maintain a per-address transaction index for `get_address_transactions`.
+    /// Only blocks indexed while enabled are covered; earlier heights stay unavailable.
+    #[serde(default)]
+    pub address_transactions: bool,
+
+    /// Keep the created and spent height of every UTXO for `get_utxo_at_height` and
+    /// historical UTXO snapshots. Spent outputs are retained, so storage grows with chain
+    /// history. Heights before the first block indexed while enabled stay unavailable.
+    #[serde(default)]
+    pub utxo_history: bool,
+
+    /// Number of recent blocks whose full balance history is retained. Older rows are
+    /// compacted down to the latest balance per script hash and balance queries below the
+    /// floor return `STATE_NOT_RETAINED`. Zero keeps the full history.
+    #[serde(default)]
+    pub history_retention_blocks: u32,
+
+    /// Minimum number of blocks the retention floor must advance before pruning runs again.
+    #[serde(default = "default_history_prune_interval_blocks")]
+    pub history_prune_interval_blocks: u32,
 }
 
 // By default, no limit on max sync block height
@@ -101,6 +132,11 @@ impl Default for IndexConfig {
             max_sync_block_height: default_max_sync_block_height(),
             undo_retention_blocks: default_undo_retention_blocks(),
             undo_cleanup_interval_blocks: default_undo_cleanup_interval_blocks(),
+            utxo_set_hash: false,
+            address_transactions: false,
+            utxo_history: false,
+            history_retention_blocks: 0,
+            history_prune_interval_blocks: default_history_prune_interval_blocks(),
         }
     }
 }
@@ -112,6 +148,21 @@ pub struct RpcServer {
 
     #[serde(default = "default_rpc_port")]
     pub port: u16,
+
+    /// WebSocket subscription server port, sharing `host`. Zero, the default, disables it.
+    /// The conventional port is `BALANCE_HISTORY_SERVICE_WS_PORT`.
+    #[serde(default)]
+    pub ws_port: u16,
+
+    /// Electrum protocol TCP server port, sharing `host`. Zero, the default, disables it.
+    /// `get_history` needs `sync.address_transactions`.
+    #[serde(default)]
+    pub electrum_port: u16,
+
+    /// Snapshot distribution HTTP server port, sharing `host`. Serves `${root}/snapshots`
+    /// read-only for `fetch-snapshot`. Zero, the default, disables it.
+    #[serde(default)]
+    pub snapshot_port: u16,
 }
 
 fn default_rpc_host() -> String {
@@ -167,6 +218,38 @@ impl Default for RpcServer {
         RpcServer {
             host: default_rpc_host(),
             port: default_rpc_port(),
+            ws_port: 0,
+            electrum_port: 0,
+            snapshot_port: 0,
+        }
+    }
+}
+
+fn default_mempool_poll_interval_secs() -> u64 {
+    5
+}
+
+/// Optional mempool tracker behind `get_address_pending_balance`.
+///
+/// Pending deltas are read from the upstream bitcoind mempool and never feed
+/// block commits, state refs or snapshots. The upstream node needs `txindex=1`, since
+/// inputs spending outputs of blocks not indexed locally yet are resolved with
+/// `getrawtransaction` on confirmed transactions.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MempoolConfig {
+    #[serde(default)]
+    pub enabled: bool,
+
+    /// Interval between two `getrawmempool` polls.
+    #[serde(default = "default_mempool_poll_interval_secs")]
+    pub poll_interval_secs: u64,
+}
+
+impl Default for MempoolConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            poll_interval_secs: default_mempool_poll_interval_secs(),
         }
     }
 }
@@ -189,6 +272,8 @@ pub struct BalanceHistoryConfig {
     pub rpc_server: RpcServer,
     #[serde(default)]
     pub snapshot: SnapshotConfig,
+    #[serde(default)]
+    pub mempool: MempoolConfig,
 }
 
 impl Default for BalanceHistoryConfig {
@@ -201,6 +286,7 @@ impl Default for BalanceHistoryConfig {
             sync: IndexConfig::default(),
             rpc_server: RpcServer::default(),
             snapshot: SnapshotConfig::default(),
+            mempool: MempoolConfig::default(),
         }
     }
 }
@@ -247,6 +333,10 @@ impl BalanceHistoryConfig {
         self.root_dir.join("snapshots")
     }
 
+    pub fn checkpoint_dir(&self) -> PathBuf {
+        self.root_dir.join("checkpoints")
+    }
+
     /// Resolves a service-local path against `root_dir` when the input is relative.
     pub fn resolve_service_path(&self, path: &Path) -> PathBuf {
         if path.is_absolute() {
@@ -1,17 +1,24 @@
 use super::helper::get_approx_cf_key_count;
-use crate::config::BalanceHistoryConfigRef;
+use crate::config::{BalanceHistoryConfig, BalanceHistoryConfigRef};
 use crate::snapshot_provenance::SnapshotInstallProvenance;
 use bitcoincore_rpc::bitcoin::hashes::Hash;
-use bitcoincore_rpc::bitcoin::{BlockHash, OutPoint, ScriptBuf, Txid};
+use bitcoincore_rpc::bitcoin::{BlockHash, Network, OutPoint, ScriptBuf, Txid};
 use rocksdb::{
-    ColumnFamilyDescriptor, DB, Direction, IteratorMode, Options, ReadOptions, WriteBatch,
-    WriteOptions,
+    ColumnFamily, ColumnFamilyDescriptor, DB, Direction, IteratorMode, Options, ReadOptions,
+    WriteBatch, WriteOptions,
 };
+use rust_rocksdb::checkpoint::Checkpoint;
 use rust_rocksdb::{self as rocksdb};
-use std::collections::HashMap;
-use std::path::PathBuf;
+use std::borrow::Borrow;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::path::{Path, PathBuf};
 use std::sync::Mutex;
-use usdb_util::USDBScriptHash;
+use usdb_util::MuHash3072;
+use usdb_util::{
+    BALANCE_STATE_EMPTY_HASH, BalanceDeltaRootRule, USDBScriptHash,
+    balance_state_root_activation_height, build_balance_state_subtree_siblings,
+    compute_balance_state_subtree_root, hash_balance_state_node, is_balance_state_root_active,
+};
 use usdb_util::{BalanceHistoryData, OutPointRef, UTXOEntry, UTXOEntryRef, UTXOValue};
 
 // Column family names
@@ -27,6 +34,32 @@ pub const BLOCK_UNDO_META_CF: &str = "block_undo_meta";
 pub const BLOCK_UNDO_CREATED_UTXOS_CF: &str = "block_undo_created_utxos";
 pub const BLOCK_UNDO_SPENT_UTXOS_CF: &str = "block_undo_spent_utxos";
 pub const BLOCK_UNDO_BALANCE_INDEX_CF: &str = "block_undo_balance_index";
+// BLOCK_BALANCE_INDEX_CF lists the script hashes written by each Merkle-rule block so
+// balance proofs can rebuild the block's sorted leaf set. Unlike undo data it is never pruned.
+pub const BLOCK_BALANCE_INDEX_CF: &str = "block_balance_index";
+// BALANCE_STATE_LEAVES_CF holds the current non-zero balance of every script, i.e. the leaves of
+// the balance state tree. BALANCE_STATE_NODES_CF caches the tree levels above
+// BALANCE_STATE_BUCKET_DEPTH; deeper levels are recomputed from the leaves of one bucket.
+pub const BALANCE_STATE_LEAVES_CF: &str = "balance_state_leaves";
+pub const BALANCE_STATE_NODES_CF: &str = "balance_state_nodes";
+// BALANCE_STATE_ROOTS_CF stores the balance state root per height, keyed like BLOCK_COMMITS_CF.
+pub const BALANCE_STATE_ROOTS_CF: &str = "balance_state_roots";
+// The UTXO set hash CFs are only written when `sync.utxo_set_hash` is enabled.
+// UTXO_MUHASH_DIGESTS_CF maps every unspent outpoint to its MuHash element digest,
+// UTXO_SET_HASH_STATES_CF stores the MuHash accumulator after each height, and
+// UTXO_MUHASH_UNDO_CF keeps the digests spent by undo-retained blocks for rollback.
+pub const UTXO_MUHASH_DIGESTS_CF: &str = "utxo_muhash_digests";
+pub const UTXO_SET_HASH_STATES_CF: &str = "utxo_set_hash_states";
+pub const UTXO_MUHASH_UNDO_CF: &str = "utxo_muhash_undo";
+// ADDRESS_UTXO_INDEX_CF maps script_hash + outpoint to the UTXO value, so the live UTXOs of
+// one script can be listed without scanning UTXO_CF. It mirrors UTXO_CF on every write path.
+pub const ADDRESS_UTXO_INDEX_CF: &str = "address_utxo_index";
+// ADDRESS_TX_INDEX_CF is only written when `sync.address_transactions` is enabled. It maps
+// script_hash + block_height + tx_index to the txid and the value the tx moved for the script.
+pub const ADDRESS_TX_INDEX_CF: &str = "address_tx_index";
+// UTXO_HISTORY_CF is only written when `sync.utxo_history` is enabled. It maps every outpoint
+// created or spent since enabling to its created height, spent height, owner and value.
+pub const UTXO_HISTORY_CF: &str = "utxo_history";
 
 // Mete key names
 pub const META_KEY_BTC_BLOCK_HEIGHT: &str = "btc_block_height";
@@ -39,6 +72,12 @@ pub const META_KEY_UNDO_RETAINED_FROM_HEIGHT: &str = "undo_retained_from_height"
 pub const META_KEY_SNAPSHOT_INSTALL_USED: &str = "snapshot_install_used";
 pub const META_KEY_SNAPSHOT_INSTALL_MANIFEST_VERIFIED: &str = "snapshot_install_manifest_verified";
 pub const META_KEY_SNAPSHOT_INSTALL_PROVENANCE: &str = "snapshot_install_provenance";
+pub const META_KEY_ADDRESS_UTXO_INDEX_READY: &str = "address_utxo_index_ready";
+pub const META_KEY_ADDRESS_TX_INDEX_FROM_HEIGHT: &str = "address_tx_index_from_height";
+pub const META_KEY_UTXO_HISTORY_FROM_HEIGHT: &str = "utxo_history_from_height";
+pub const META_KEY_BALANCE_HISTORY_RETAINED_FROM_HEIGHT: &str =
+    "balance_history_retained_from_height";
+pub const META_KEY_BALANCE_HISTORY_PRUNE_CURSOR: &str = "balance_history_prune_cursor";
 
 pub const BALANCE_HISTORY_KEY_LEN: usize = USDBScriptHash::LEN + 4; // USDBScriptHash (32 bytes) + block_height (4 bytes)
 pub const UTXO_KEY_LEN: usize = Txid::LEN + 4; // OutPoint: txid (32 bytes) + vout (4 bytes)
@@ -51,6 +90,22 @@ pub const BLOCK_UNDO_META_VALUE_LEN: usize = 2 + BlockHash::LEN + 4 + 4 + 4;
 pub const BLOCK_UNDO_UTXO_KEY_LEN: usize = 4 + 4;
 pub const BLOCK_UNDO_UTXO_VALUE_LEN: usize = UTXO_KEY_LEN + USDBScriptHash::LEN + 8;
 pub const BLOCK_UNDO_BALANCE_INDEX_KEY_LEN: usize = 4 + USDBScriptHash::LEN;
+pub const UTXO_MUHASH_UNDO_KEY_LEN: usize = 4 + UTXO_KEY_LEN;
+pub const ADDRESS_UTXO_INDEX_KEY_LEN: usize = USDBScriptHash::LEN + UTXO_KEY_LEN;
+pub const ADDRESS_TX_INDEX_KEY_LEN: usize = USDBScriptHash::LEN + 4 + 4;
+pub const ADDRESS_TX_INDEX_VALUE_LEN: usize = Txid::LEN + 8 + 8;
+pub const UTXO_HISTORY_VALUE_LEN: usize = 4 + 4 + USDBScriptHash::LEN + 8;
+// Stored in place of a created or spent height that is unknown or has not happened yet.
+const UTXO_HISTORY_NO_HEIGHT: u32 = u32::MAX;
+// DBs created before the address UTXO index are backfilled from UTXO_CF in chunks on open.
+const ADDRESS_UTXO_INDEX_BACKFILL_CHUNK: usize = 64 * 1024;
+// Buckets are keyed by the first three script hash bytes.
+const BALANCE_STATE_BUCKET_DEPTH: usize = 24;
+// Snapshot installs apply balances to the state tree in chunks to bound overlay memory.
+const BALANCE_STATE_UPDATE_CHUNK: usize = 16 * 1024;
+
+// Deletes issued per write batch while pruning balance history below the retention floor.
+const BALANCE_HISTORY_PRUNE_BATCH: usize = 64 * 1024;
 
 #[derive(Debug, Clone)]
 pub struct BalanceHistoryEntry {
@@ -143,6 +198,87 @@ pub struct BlockStateUpdateBatch<'a> {
     pub block_commits: &'a [BlockCommitEntry],
     pub script_registry_entries: &'a [ScriptRegistryEntry],
     pub undo_bundles: &'a [BlockUndoBundle],
+    // Present only when the UTXO set hash is maintained; digests of `remove_utxos` are
+    // deleted together with the UTXOs.
+    pub utxo_set_hash: Option<&'a UtxoSetHashUpdate>,
+    // Present only when the address transaction index is maintained.
+    pub address_transactions: Option<&'a AddressTxIndexUpdate>,
+    // Present only when the UTXO history index is maintained.
+    pub utxo_history: Option<&'a UtxoHistoryUpdate>,
+}
+
+// Position of an interrupted balance history prune pass, stored as JSON meta.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+struct BalanceHistoryPruneCursor {
+    // Floor the interrupted pass was pruning to.
+    retained_from_height: u32,
+    // First script hash the pass had not finished.
+    next_script_hash: String,
+}
+
+/// MuHash UTXO set hash writes of one block-state batch.
+#[derive(Debug, Clone, Default)]
+pub struct UtxoSetHashUpdate {
+    // Digests of the batch's new UTXOs that bitcoind keeps in its UTXO set.
+    pub new_digests: Vec<(OutPointRef, [u8; 32])>,
+    // Accumulator after each block of the batch.
+    pub states: Vec<(u32, MuHash3072)>,
+    // Digests of coins spent by undo-retained blocks, restored on rollback.
+    pub undo_digests: Vec<(u32, OutPointRef, [u8; 32])>,
+}
+
+/// Value one transaction moved for one script hash.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AddressTxIndexEntry {
+    pub script_hash: USDBScriptHash,
+    pub block_height: u32,
+    // Position of the transaction inside its block.
+    pub tx_index: u32,
+    pub txid: Txid,
+    // Sum of the script's UTXOs spent by the transaction inputs.
+    pub input_value: u64,
+    // Sum of the transaction outputs paying to the script.
+    pub output_value: u64,
+}
+
+/// Lifetime of one UTXO as recorded by the UTXO history index.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UtxoHistoryEntry {
+    pub outpoint: OutPoint,
+    pub script_hash: USDBScriptHash,
+    pub value: u64,
+    // None when the UTXO was created before the index coverage start.
+    pub created_height: Option<u32>,
+    // None while the UTXO is unspent.
+    pub spent_height: Option<u32>,
+}
+
+impl UtxoHistoryEntry {
+    /// Whether the UTXO was unspent once `block_height` was applied. Callers must ensure
+    /// `block_height` is inside the index coverage.
+    pub fn is_live_at(&self, block_height: u32) -> bool {
+        self.created_height
+            .is_none_or(|created_height| created_height <= block_height)
+            && self
+                .spent_height
+                .is_none_or(|spent_height| spent_height > block_height)
+    }
+}
+
+/// UTXO history index writes of one block-state batch.
+#[derive(Debug, Clone, Default)]
+pub struct UtxoHistoryUpdate {
+    // First block height of the batch, recorded as index coverage start on the first write.
+    pub first_block_height: u32,
+    pub entries: Vec<UtxoHistoryEntry>,
+}
+
+/// Address transaction index writes of one block-state batch.
+#[derive(Debug, Clone, Default)]
+pub struct AddressTxIndexUpdate {
+    // First block height of the batch, recorded as index coverage start on the first write.
+    pub first_block_height: u32,
+    pub entries: Vec<AddressTxIndexEntry>,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -151,6 +287,14 @@ pub enum BalanceHistoryDBMode {
     Normal,
 }
 
+// Balance state tree writes of one write batch that are not persisted yet.
+// A zero balance or an empty node hash means the key is deleted.
+#[derive(Default)]
+struct BalanceStateOverlay {
+    leaves: BTreeMap<USDBScriptHash, u64>,
+    nodes: HashMap<(usize, u32), [u8; 32]>,
+}
+
 pub struct BalanceHistoryDB {
     config: BalanceHistoryConfigRef,
     mode: Mutex<BalanceHistoryDBMode>,
@@ -176,7 +320,7 @@ impl BalanceHistoryDB {
             })?;
         }
 
-        let file = db_dir.join("balance_history");
+        let file = Self::db_path(&config);
         info!("Opening RocksDB at {}, mode {:?}", file.display(), mode);
 
         // Default options
@@ -190,14 +334,83 @@ impl BalanceHistoryDB {
             msg
         })?;
 
-        Ok(BalanceHistoryDB {
+        let ret = BalanceHistoryDB {
             file,
             db,
             config,
             mode: Mutex::new(mode),
+        };
+        ret.ensure_address_utxo_index()?;
+
+        Ok(ret)
+    }
+
+    /// Location of the RocksDB directory under the configured `db_dir`.
+    pub fn db_path(config: &BalanceHistoryConfig) -> PathBuf {
+        config.db_dir().join("balance_history")
+    }
+
+    /// Opens a RocksDB checkpoint directory read-only, e.g. to inspect a backup before
+    /// restoring it. No migration or index backfill runs on the checkpoint.
+    pub fn open_checkpoint(
+        config: BalanceHistoryConfigRef,
+        checkpoint_dir: &Path,
+    ) -> Result<Self, String> {
+        info!("Opening RocksDB checkpoint at {}", checkpoint_dir.display());
+
+        let mode = BalanceHistoryDBMode::Normal;
+        let options = Self::get_options_on_mode(mode);
+        let cf_descriptors = Self::get_cf_descriptors_on_mode(mode);
+        let db = DB::open_cf_descriptors_read_only(&options, checkpoint_dir, cf_descriptors, false)
+            .map_err(|e| {
+                let msg = format!(
+                    "Failed to open RocksDB checkpoint at {}: {}",
+                    checkpoint_dir.display(),
+                    e
+                );
+                error!("{}", msg);
+                msg
+            })?;
+
+        Ok(BalanceHistoryDB {
+            file: checkpoint_dir.to_path_buf(),
+            db,
+            config,
+            mode: Mutex::new(mode),
         })
     }
 
+    /// Writes a consistent point-in-time copy of every column family to `checkpoint_dir`,
+    /// which must not exist yet. SST files are hard-linked when the directory is on the
+    /// same filesystem, so this is cheap and does not block concurrent writers.
+    pub fn create_checkpoint(&self, checkpoint_dir: &Path) -> Result<(), String> {
+        let checkpoint = Checkpoint::new(&self.db).map_err(|e| {
+            let msg = format!("Failed to create RocksDB checkpoint object: {}", e);
+            error!("{}", msg);
+            msg
+        })?;
+        checkpoint.create_checkpoint(checkpoint_dir).map_err(|e| {
+            let msg = format!(
+                "Failed to create RocksDB checkpoint at {}: {}",
+                checkpoint_dir.display(),
+                e
+            );
+            error!("{}", msg);
+            msg
+        })?;
+
+        info!(
+            "Created RocksDB checkpoint of {} at {}",
+            self.file.display(),
+            checkpoint_dir.display()
+        );
+        Ok(())
+    }
+
+    pub fn network(&self) -> Network {
+        self.config.btc.network()
+    }
+
     pub fn get_mode(&self) -> BalanceHistoryDBMode {
         let guard = self.mode.lock().unwrap();
         *guard
@@ -327,6 +540,25 @@ impl BalanceHistoryDB {
                 BLOCK_UNDO_BALANCE_INDEX_CF,
                 Self::get_block_undo_height_cf_opts(),
             ),
+            ColumnFamilyDescriptor::new(
+                BLOCK_BALANCE_INDEX_CF,
+                Self::get_block_undo_height_cf_opts(),
+            ),
+            ColumnFamilyDescriptor::new(BALANCE_STATE_LEAVES_CF, Options::default()),
+            ColumnFamilyDescriptor::new(BALANCE_STATE_NODES_CF, Options::default()),
+            ColumnFamilyDescriptor::new(BALANCE_STATE_ROOTS_CF, Options::default()),
+            ColumnFamilyDescriptor::new(UTXO_MUHASH_DIGESTS_CF, Self::normal_utxo_cf_opts()),
+            ColumnFamilyDescriptor::new(UTXO_SET_HASH_STATES_CF, Options::default()),
+            ColumnFamilyDescriptor::new(UTXO_MUHASH_UNDO_CF, Self::get_block_undo_height_cf_opts()),
+            ColumnFamilyDescriptor::new(
+                ADDRESS_UTXO_INDEX_CF,
+                Self::get_script_hash_prefix_cf_opts(),
+            ),
+            ColumnFamilyDescriptor::new(
+                ADDRESS_TX_INDEX_CF,
+                Self::get_script_hash_prefix_cf_opts(),
+            ),
+            ColumnFamilyDescriptor::new(UTXO_HISTORY_CF, Self::normal_utxo_cf_opts()),
         ]
     }
 
@@ -337,6 +569,16 @@ impl BalanceHistoryDB {
         opts
     }
 
+    fn get_script_hash_prefix_cf_opts() -> Options {
+        let mut opts = Options::default();
+        opts.create_if_missing(true);
+        opts.set_prefix_extractor(rocksdb::SliceTransform::create_fixed_prefix(
+            USDBScriptHash::LEN,
+        ));
+        opts.set_compression_type(rocksdb::DBCompressionType::Lz4);
+        opts
+    }
+
     fn get_balance_history_cf_opts_on_mode(mode: BalanceHistoryDBMode) -> Options {
         match mode {
             BalanceHistoryDBMode::BestEffort => Self::best_balance_history_cf_opts(),
@@ -471,6 +713,16 @@ impl BalanceHistoryDB {
             BLOCK_UNDO_CREATED_UTXOS_CF,
             BLOCK_UNDO_SPENT_UTXOS_CF,
             BLOCK_UNDO_BALANCE_INDEX_CF,
+            BLOCK_BALANCE_INDEX_CF,
+            BALANCE_STATE_LEAVES_CF,
+            BALANCE_STATE_NODES_CF,
+            BALANCE_STATE_ROOTS_CF,
+            UTXO_MUHASH_DIGESTS_CF,
+            UTXO_SET_HASH_STATES_CF,
+            UTXO_MUHASH_UNDO_CF,
+            ADDRESS_UTXO_INDEX_CF,
+            ADDRESS_TX_INDEX_CF,
+            UTXO_HISTORY_CF,
         ];
         let db = DB::open_cf_as_secondary(&opts, &file, &tmp_dir, cf_descriptors_names).map_err(
             |e| {
@@ -493,6 +745,43 @@ impl BalanceHistoryDB {
         info!("Closed RocksDB at {}", self.file.display());
     }
 
+    /// Create a consistent RocksDB checkpoint of this DB under `db_dir`, laid out the same
+    /// way as `config.db_dir()` so the directory can be opened with a config rooted elsewhere.
+    ///
+    /// SST files are hard-linked where possible, so this is cheap even for large DBs.
+    pub fn create_checkpoint(&self, db_dir: &Path) -> Result<(), String> {
+        std::fs::create_dir_all(db_dir).map_err(|e| {
+            let msg = format!(
+                "Could not create checkpoint directory at {}: {}",
+                db_dir.display(),
+                e
+            );
+            error!("{}", msg);
+            msg
+        })?;
+
+        let target = db_dir.join("balance_history");
+        let checkpoint = rocksdb::checkpoint::Checkpoint::new(&self.db).map_err(|e| {
+            let msg = format!(
+                "Failed to initialize RocksDB checkpoint for {}: {}",
+                self.file.display(),
+                e
+            );
+            error!("{}", msg);
+            msg
+        })?;
+        checkpoint.create_checkpoint(&target).map_err(|e| {
+            let msg = format!(
+                "Failed to create RocksDB checkpoint of {} at {}: {}",
+                self.file.display(),
+                target.display(),
+                e
+            );
+            error!("{}", msg);
+            msg
+        })
+    }
+
     // Flush secondary DB to catch up with primary in read-only mode
     pub fn flush_with_primary(&self) -> Result<(), String> {
         self.db.try_catch_up_with_primary().map_err(|e| {
@@ -711,7 +1000,8 @@ impl BalanceHistoryDB {
         })
     }
 
-    fn make_block_undo_balance_index_key(
+    // Key layout shared by BLOCK_UNDO_BALANCE_INDEX_CF and BLOCK_BALANCE_INDEX_CF.
+    fn make_block_script_hash_key(
         block_height: u32,
         script_hash: &USDBScriptHash,
     ) -> [u8; BLOCK_UNDO_BALANCE_INDEX_KEY_LEN] {
@@ -721,6 +1011,39 @@ impl BalanceHistoryDB {
         key
     }
 
+    fn make_utxo_muhash_undo_key(
+        block_height: u32,
+        outpoint: &OutPoint,
+    ) -> [u8; UTXO_MUHASH_UNDO_KEY_LEN] {
+        let mut key = [0u8; UTXO_MUHASH_UNDO_KEY_LEN];
+        key[..4].copy_from_slice(&block_height.to_be_bytes());
+        key[4..].copy_from_slice(&Self::make_utxo_key(outpoint));
+        key
+    }
+
+    fn make_address_utxo_index_key(
+        script_hash: &USDBScriptHash,
+        outpoint: &OutPoint,
+    ) -> [u8; ADDRESS_UTXO_INDEX_KEY_LEN] {
+        let mut key = [0u8; ADDRESS_UTXO_INDEX_KEY_LEN];
+        key[..USDBScriptHash::LEN].copy_from_slice(script_hash.as_ref() as &[u8]);
+        key[USDBScriptHash::LEN..].copy_from_slice(&Self::make_utxo_key(outpoint));
+        key
+    }
+
+    fn make_address_tx_index_key(
+        script_hash: &USDBScriptHash,
+        block_height: u32,
+        tx_index: u32,
+    ) -> [u8; ADDRESS_TX_INDEX_KEY_LEN] {
+        let mut key = [0u8; ADDRESS_TX_INDEX_KEY_LEN];
+        key[..USDBScriptHash::LEN].copy_from_slice(script_hash.as_ref() as &[u8]);
+        key[USDBScriptHash::LEN..USDBScriptHash::LEN + 4]
+            .copy_from_slice(&block_height.to_be_bytes());
+        key[USDBScriptHash::LEN + 4..].copy_from_slice(&tx_index.to_be_bytes());
+        key
+    }
+
     fn block_height_prefix_matches(key: &[u8], block_height: u32) -> bool {
         key.len() >= 4 && key[..4] == block_height.to_be_bytes()
     }
@@ -954,6 +1277,9 @@ impl BalanceHistoryDB {
             block_commits,
             script_registry_entries: &[],
             undo_bundles,
+            utxo_set_hash: None,
+            address_transactions: None,
+            utxo_history: None,
         })
     }
 
@@ -969,6 +1295,16 @@ impl BalanceHistoryDB {
             msg
         })?;
 
+        // Index removals look up the owning script in UTXO_CF, so they go before the deletes.
+        self.append_address_utxo_index_removals_to_batch(&mut batch, update.remove_utxos)?;
+        self.append_address_utxo_index_puts_to_batch(
+            &mut batch,
+            update
+                .new_utxos
+                .iter()
+                .map(|(outpoint, utxo)| (outpoint.as_ref(), &utxo.script_hash, utxo.value)),
+        )?;
+
         for (outpoint, utxo) in update.new_utxos {
             let value = UTXOValue::encode(&utxo.script_hash, utxo.value);
             let key = Self::make_utxo_key(outpoint);
@@ -996,6 +1332,9 @@ impl BalanceHistoryDB {
             batch.put_cf(balance_cf, key, value);
         }
 
+        self.append_block_balance_index_to_batch(&mut batch, update.entries_list)?;
+        self.append_balance_state_to_batch(&mut batch, update.entries_list, update.block_commits)?;
+
         let block_commit_cf = self.db.cf_handle(BLOCK_COMMITS_CF).ok_or_else(|| {
             let msg = format!("Column family {} not found", BLOCK_COMMITS_CF);
             error!("{}", msg);
@@ -1018,6 +1357,15 @@ impl BalanceHistoryDB {
 
         self.append_script_registry_entries_to_batch(&mut batch, update.script_registry_entries)?;
         self.append_block_undo_bundles_to_batch(&mut batch, update.undo_bundles)?;
+        if let Some(utxo_set_hash) = update.utxo_set_hash {
+            self.append_utxo_set_hash_to_batch(&mut batch, update.remove_utxos, utxo_set_hash)?;
+        }
+        if let Some(address_transactions) = update.address_transactions {
+            self.append_address_tx_index_to_batch(&mut batch, address_transactions)?;
+        }
+        if let Some(utxo_history) = update.utxo_history {
+            self.append_utxo_history_to_batch(&mut batch, utxo_history)?;
+        }
 
         if !update.undo_bundles.is_empty() {
             let first_undo_height = update
@@ -1055,118 +1403,1137 @@ impl BalanceHistoryDB {
         Ok(())
     }
 
-    fn append_script_registry_entries_to_batch(
+    // Index the script hashes of Merkle-rule blocks; flat-rule blocks cannot serve proofs.
+    fn append_block_balance_index_to_batch(
         &self,
         batch: &mut WriteBatch,
-        entries: &[ScriptRegistryEntry],
+        entries: &[BalanceHistoryEntry],
     ) -> Result<(), String> {
-        if entries.is_empty() {
-            return Ok(());
-        }
-
-        let cf = self.db.cf_handle(SCRIPT_REGISTRY_CF).ok_or_else(|| {
-            let msg = format!("Column family {} not found", SCRIPT_REGISTRY_CF);
+        let network = self.network();
+        let cf = self.db.cf_handle(BLOCK_BALANCE_INDEX_CF).ok_or_else(|| {
+            let msg = format!("Column family {} not found", BLOCK_BALANCE_INDEX_CF);
             error!("{}", msg);
             msg
         })?;
 
         for entry in entries {
+            if BalanceDeltaRootRule::at_height(network, entry.block_height)
+                != BalanceDeltaRootRule::MerkleV2
+            {
+                continue;
+            }
             batch.put_cf(
                 cf,
-                entry.script_hash.as_ref() as &[u8],
-                entry.script_pubkey.as_bytes(),
+                Self::make_block_script_hash_key(entry.block_height, &entry.script_hash),
+                [1u8; 1],
             );
         }
 
         Ok(())
     }
 
-    fn append_block_undo_bundles_to_batch(
+    pub fn is_balance_state_tree_enabled(&self) -> bool {
+        balance_state_root_activation_height(self.network()).is_some()
+    }
+
+    fn balance_state_bucket(script_hash: &USDBScriptHash) -> u32 {
+        let bytes: &[u8] = script_hash.as_ref();
+        u32::from_be_bytes([0, bytes[0], bytes[1], bytes[2]])
+    }
+
+    // Inclusive script hash range of one bucket.
+    fn balance_state_bucket_bounds(bucket: u32) -> (USDBScriptHash, USDBScriptHash) {
+        let prefix = &bucket.to_be_bytes()[1..];
+        let mut start = [0u8; USDBScriptHash::LEN];
+        let mut end = [0xFFu8; USDBScriptHash::LEN];
+        start[..3].copy_from_slice(prefix);
+        end[..3].copy_from_slice(prefix);
+        (
+            USDBScriptHash::from_byte_array(start),
+            USDBScriptHash::from_byte_array(end),
+        )
+    }
+
+    fn make_balance_state_node_key(depth: usize, prefix: u32) -> [u8; 5] {
+        let mut key = [0u8; 5];
+        key[0] = depth as u8;
+        key[1..].copy_from_slice(&prefix.to_be_bytes());
+        key
+    }
+
+    fn parse_balance_state_hash(cf_name: &str, value: &[u8]) -> Result<[u8; 32], String> {
+        value.try_into().map_err(|_| {
+            let msg = format!("Invalid {} value length {}", cf_name, value.len());
+            error!("{}", msg);
+            msg
+        })
+    }
+
+    fn get_balance_state_node(
         &self,
-        batch: &mut WriteBatch,
-        undo_bundles: &[BlockUndoBundle],
-    ) -> Result<(), String> {
-        if undo_bundles.is_empty() {
-            return Ok(());
+        overlay: &BalanceStateOverlay,
+        depth: usize,
+        prefix: u32,
+    ) -> Result<[u8; 32], String> {
+        if let Some(hash) = overlay.nodes.get(&(depth, prefix)) {
+            return Ok(*hash);
         }
 
-        let meta_cf = self.db.cf_handle(BLOCK_UNDO_META_CF).ok_or_else(|| {
-            let msg = format!("Column family {} not found", BLOCK_UNDO_META_CF);
+        let cf = self.db.cf_handle(BALANCE_STATE_NODES_CF).ok_or_else(|| {
+            let msg = format!("Column family {} not found", BALANCE_STATE_NODES_CF);
             error!("{}", msg);
             msg
         })?;
-        let created_cf = self
+        let value = self
             .db
-            .cf_handle(BLOCK_UNDO_CREATED_UTXOS_CF)
-            .ok_or_else(|| {
-                let msg = format!("Column family {} not found", BLOCK_UNDO_CREATED_UTXOS_CF);
+            .get_cf(cf, Self::make_balance_state_node_key(depth, prefix))
+            .map_err(|e| {
+                let msg = format!(
+                    "Failed to get balance state node at depth {} prefix {}: {}",
+                    depth, prefix, e
+                );
                 error!("{}", msg);
                 msg
             })?;
-        let spent_cf = self
+
+        match value {
+            Some(value) => Self::parse_balance_state_hash(BALANCE_STATE_NODES_CF, &value),
+            None => Ok(BALANCE_STATE_EMPTY_HASH),
+        }
+    }
+
+    // Current non-zero leaves of one bucket sorted by script hash, with overlay writes applied.
+    fn load_balance_state_bucket_leaves(
+        &self,
+        overlay: &BalanceStateOverlay,
+        bucket: u32,
+    ) -> Result<Vec<(USDBScriptHash, u64)>, String> {
+        let cf = self.db.cf_handle(BALANCE_STATE_LEAVES_CF).ok_or_else(|| {
+            let msg = format!("Column family {} not found", BALANCE_STATE_LEAVES_CF);
+            error!("{}", msg);
+            msg
+        })?;
+        let (start, end) = Self::balance_state_bucket_bounds(bucket);
+
+        let start_key: &[u8] = start.as_ref();
+        let end_key: &[u8] = end.as_ref();
+
+        let mut leaves = BTreeMap::new();
+        let iter = self
             .db
-            .cf_handle(BLOCK_UNDO_SPENT_UTXOS_CF)
-            .ok_or_else(|| {
-                let msg = format!("Column family {} not found", BLOCK_UNDO_SPENT_UTXOS_CF);
+            .iterator_cf(cf, IteratorMode::From(start_key, Direction::Forward));
+        for item in iter {
+            let (key, value) = item.map_err(|e| {
+                let msg = format!(
+                    "Iterator error when reading balance state bucket {}: {}",
+                    bucket, e
+                );
                 error!("{}", msg);
                 msg
             })?;
-        let balance_cf = self
-            .db
-            .cf_handle(BLOCK_UNDO_BALANCE_INDEX_CF)
-            .ok_or_else(|| {
-                let msg = format!("Column family {} not found", BLOCK_UNDO_BALANCE_INDEX_CF);
+            if &key[..] > end_key {
+                break;
+            }
+            let script_hash = USDBScriptHash::from_slice(&key).map_err(|e| {
+                let msg = format!("Invalid balance state leaf key: {}", e);
+                error!("{}", msg);
+                msg
+            })?;
+            let value: [u8; 8] = value.as_ref().try_into().map_err(|_| {
+                let msg = format!(
+                    "Invalid balance state leaf value length {} for {}",
+                    value.len(),
+                    script_hash
+                );
                 error!("{}", msg);
                 msg
             })?;
+            leaves.insert(script_hash, u64::from_be_bytes(value));
+        }
+        for (script_hash, balance) in overlay.leaves.range(start..=end) {
+            leaves.insert(*script_hash, *balance);
+        }
 
-        for bundle in undo_bundles {
-            let meta = BlockUndoMetaEntry {
-                block_height: bundle.block_height,
-                format_version: 1,
-                btc_block_hash: bundle.btc_block_hash,
-                created_utxo_count: bundle.created_utxos.len() as u32,
-                spent_utxo_count: bundle.spent_utxos.len() as u32,
-                balance_entry_count: bundle.touched_script_hashes.len() as u32,
-            };
+        Ok(leaves
+            .into_iter()
+            .filter(|(_, balance)| *balance > 0)
+            .collect())
+    }
 
-            batch.put_cf(
-                meta_cf,
-                Self::make_block_undo_meta_key(bundle.block_height),
-                Self::serialize_block_undo_meta_value(&meta),
+    // Apply balance updates to the tree through `overlay` and return the resulting root.
+    fn apply_balance_state_updates(
+        &self,
+        overlay: &mut BalanceStateOverlay,
+        updates: &[(USDBScriptHash, u64)],
+    ) -> Result<[u8; 32], String> {
+        let mut dirty = BTreeSet::new();
+        for (script_hash, balance) in updates {
+            overlay.leaves.insert(*script_hash, *balance);
+            dirty.insert(Self::balance_state_bucket(script_hash));
+        }
+
+        for bucket in &dirty {
+            let leaves = self.load_balance_state_bucket_leaves(overlay, *bucket)?;
+            overlay.nodes.insert(
+                (BALANCE_STATE_BUCKET_DEPTH, *bucket),
+                compute_balance_state_subtree_root(BALANCE_STATE_BUCKET_DEPTH, &leaves),
             );
+        }
 
-            for (seq, entry) in bundle.created_utxos.iter().enumerate() {
-                batch.put_cf(
-                    created_cf,
-                    Self::make_block_undo_utxo_key(bundle.block_height, seq as u32),
-                    Self::serialize_block_undo_utxo_value(entry),
-                );
+        for depth in (0..BALANCE_STATE_BUCKET_DEPTH).rev() {
+            dirty = dirty.into_iter().map(|prefix| prefix >> 1).collect();
+            for prefix in &dirty {
+                let left = self.get_balance_state_node(overlay, depth + 1, prefix << 1)?;
+                let right = self.get_balance_state_node(overlay, depth + 1, (prefix << 1) | 1)?;
+                overlay
+                    .nodes
+                    .insert((depth, *prefix), hash_balance_state_node(&left, &right));
             }
+        }
 
-            for (seq, entry) in bundle.spent_utxos.iter().enumerate() {
-                batch.put_cf(
-                    spent_cf,
-                    Self::make_block_undo_utxo_key(bundle.block_height, seq as u32),
-                    Self::serialize_block_undo_utxo_value(entry),
-                );
-            }
+        self.get_balance_state_node(overlay, 0, 0)
+    }
 
-            for script_hash in &bundle.touched_script_hashes {
+    fn append_balance_state_overlay_to_batch(
+        &self,
+        batch: &mut WriteBatch,
+        overlay: &BalanceStateOverlay,
+    ) -> Result<(), String> {
+        let leaves_cf = self.db.cf_handle(BALANCE_STATE_LEAVES_CF).ok_or_else(|| {
+            let msg = format!("Column family {} not found", BALANCE_STATE_LEAVES_CF);
+            error!("{}", msg);
+            msg
+        })?;
+        let nodes_cf = self.db.cf_handle(BALANCE_STATE_NODES_CF).ok_or_else(|| {
+            let msg = format!("Column family {} not found", BALANCE_STATE_NODES_CF);
+            error!("{}", msg);
+            msg
+        })?;
+
+        for (script_hash, balance) in &overlay.leaves {
+            if *balance == 0 {
+                batch.delete_cf(leaves_cf, script_hash.as_byte_array());
+            } else {
                 batch.put_cf(
-                    balance_cf,
-                    Self::make_block_undo_balance_index_key(bundle.block_height, script_hash),
-                    [1u8; 1],
+                    leaves_cf,
+                    script_hash.as_byte_array(),
+                    balance.to_be_bytes(),
                 );
             }
         }
+        for ((depth, prefix), hash) in &overlay.nodes {
+            let key = Self::make_balance_state_node_key(*depth, *prefix);
+            if *hash == BALANCE_STATE_EMPTY_HASH {
+                batch.delete_cf(nodes_cf, key);
+            } else {
+                batch.put_cf(nodes_cf, key, hash);
+            }
+        }
 
         Ok(())
     }
 
-    pub fn get_block_undo_meta(
+    // Advance the balance state tree height by height through one block-state batch and record
+    // the root of every committed height at or above the activation height.
+    fn append_balance_state_to_batch(
         &self,
-        block_height: u32,
+        batch: &mut WriteBatch,
+        entries: &[BalanceHistoryEntry],
+        block_commits: &[BlockCommitEntry],
+    ) -> Result<(), String> {
+        if !self.is_balance_state_tree_enabled() {
+            return Ok(());
+        }
+
+        let roots_cf = self.db.cf_handle(BALANCE_STATE_ROOTS_CF).ok_or_else(|| {
+            let msg = format!("Column family {} not found", BALANCE_STATE_ROOTS_CF);
+            error!("{}", msg);
+            msg
+        })?;
+
+        let mut updates_by_height: BTreeMap<u32, Vec<(USDBScriptHash, u64)>> = BTreeMap::new();
+        for entry in entries {
+            updates_by_height
+                .entry(entry.block_height)
+                .or_default()
+                .push((entry.script_hash, entry.balance));
+        }
+        for commit in block_commits {
+            updates_by_height.entry(commit.block_height).or_default();
+        }
+
+        let network = self.network();
+        let mut overlay = BalanceStateOverlay::default();
+        for (block_height, updates) in &updates_by_height {
+            let root = self.apply_balance_state_updates(&mut overlay, updates)?;
+            let committed = block_commits
+                .iter()
+                .any(|commit| commit.block_height == *block_height);
+            if committed && is_balance_state_root_active(network, *block_height) {
+                batch.put_cf(roots_cf, Self::make_block_commit_key(*block_height), root);
+            }
+        }
+
+        self.append_balance_state_overlay_to_batch(batch, &overlay)
+    }
+
+    // Restore the tree leaves touched by a rolled back block to their balances at the previous
+    // height and drop the block's root.
+    fn append_balance_state_rollback_to_batch(
+        &self,
+        batch: &mut WriteBatch,
+        block_height: u32,
+        touched_script_hashes: &[USDBScriptHash],
+    ) -> Result<(), String> {
+        if !self.is_balance_state_tree_enabled() {
+            return Ok(());
+        }
+
+        let mut updates = Vec::with_capacity(touched_script_hashes.len());
+        for script_hash in touched_script_hashes {
+            let balance = match block_height.checked_sub(1) {
+                Some(previous_height) => {
+                    self.get_balance_at_block_height(script_hash, previous_height)?
+                        .balance
+                }
+                None => 0,
+            };
+            updates.push((*script_hash, balance));
+        }
+
+        let mut overlay = BalanceStateOverlay::default();
+        self.apply_balance_state_updates(&mut overlay, &updates)?;
+        self.append_balance_state_overlay_to_batch(batch, &overlay)?;
+
+        let roots_cf = self.db.cf_handle(BALANCE_STATE_ROOTS_CF).ok_or_else(|| {
+            let msg = format!("Column family {} not found", BALANCE_STATE_ROOTS_CF);
+            error!("{}", msg);
+            msg
+        })?;
+        batch.delete_cf(roots_cf, Self::make_block_commit_key(block_height));
+
+        Ok(())
+    }
+
+    // Apply installed snapshot balances to the balance state tree, one write per chunk.
+    pub fn update_balance_state_async(
+        &self,
+        entries: &[BalanceHistoryEntry],
+    ) -> Result<(), String> {
+        if !self.is_balance_state_tree_enabled() {
+            return Ok(());
+        }
+
+        for chunk in entries.chunks(BALANCE_STATE_UPDATE_CHUNK) {
+            let updates: Vec<(USDBScriptHash, u64)> = chunk
+                .iter()
+                .map(|entry| (entry.script_hash, entry.balance))
+                .collect();
+            let mut overlay = BalanceStateOverlay::default();
+            self.apply_balance_state_updates(&mut overlay, &updates)?;
+
+            let mut batch = WriteBatch::default();
+            self.append_balance_state_overlay_to_batch(&mut batch, &overlay)?;
+            let mut write_options = WriteOptions::default();
+            write_options.set_sync(false);
+            self.db.write_opt(&batch, &write_options).map_err(|e| {
+                let msg = format!("Failed to write balance state tree batch to DB: {}", e);
+                error!("{}", msg);
+                msg
+            })?;
+        }
+
+        Ok(())
+    }
+
+    // Record the current tree root as the balance state root of `block_height`.
+    pub fn commit_balance_state_root(&self, block_height: u32) -> Result<(), String> {
+        if !is_balance_state_root_active(self.network(), block_height) {
+            return Ok(());
+        }
+
+        let root = self.get_balance_state_node(&BalanceStateOverlay::default(), 0, 0)?;
+        let cf = self.db.cf_handle(BALANCE_STATE_ROOTS_CF).ok_or_else(|| {
+            let msg = format!("Column family {} not found", BALANCE_STATE_ROOTS_CF);
+            error!("{}", msg);
+            msg
+        })?;
+        self.db
+            .put_cf(cf, Self::make_block_commit_key(block_height), root)
+            .map_err(|e| {
+                let msg = format!(
+                    "Failed to put balance state root at height {}: {}",
+                    block_height, e
+                );
+                error!("{}", msg);
+                msg
+            })
+    }
+
+    pub fn get_balance_state_root(&self, block_height: u32) -> Result<Option<[u8; 32]>, String> {
+        let cf = self.db.cf_handle(BALANCE_STATE_ROOTS_CF).ok_or_else(|| {
+            let msg = format!("Column family {} not found", BALANCE_STATE_ROOTS_CF);
+            error!("{}", msg);
+            msg
+        })?;
+        let value = self
+            .db
+            .get_cf(cf, Self::make_block_commit_key(block_height))
+            .map_err(|e| {
+                let msg = format!(
+                    "Failed to get balance state root at height {}: {}",
+                    block_height, e
+                );
+                error!("{}", msg);
+                msg
+            })?;
+
+        value
+            .map(|value| Self::parse_balance_state_hash(BALANCE_STATE_ROOTS_CF, &value))
+            .transpose()
+    }
+
+    pub fn is_utxo_set_hash_enabled(&self) -> bool {
+        self.config.sync.utxo_set_hash
+    }
+
+    fn append_utxo_set_hash_to_batch(
+        &self,
+        batch: &mut WriteBatch,
+        remove_utxos: &[OutPointRef],
+        update: &UtxoSetHashUpdate,
+    ) -> Result<(), String> {
+        let digests_cf = self.db.cf_handle(UTXO_MUHASH_DIGESTS_CF).ok_or_else(|| {
+            let msg = format!("Column family {} not found", UTXO_MUHASH_DIGESTS_CF);
+            error!("{}", msg);
+            msg
+        })?;
+        let states_cf = self.db.cf_handle(UTXO_SET_HASH_STATES_CF).ok_or_else(|| {
+            let msg = format!("Column family {} not found", UTXO_SET_HASH_STATES_CF);
+            error!("{}", msg);
+            msg
+        })?;
+        let undo_cf = self.db.cf_handle(UTXO_MUHASH_UNDO_CF).ok_or_else(|| {
+            let msg = format!("Column family {} not found", UTXO_MUHASH_UNDO_CF);
+            error!("{}", msg);
+            msg
+        })?;
+
+        for (outpoint, digest) in &update.new_digests {
+            batch.put_cf(digests_cf, Self::make_utxo_key(outpoint), digest);
+        }
+        for outpoint in remove_utxos {
+            batch.delete_cf(digests_cf, Self::make_utxo_key(outpoint));
+        }
+        for (block_height, state) in &update.states {
+            batch.put_cf(
+                states_cf,
+                Self::make_block_commit_key(*block_height),
+                state.to_bytes(),
+            );
+        }
+        for (block_height, outpoint, digest) in &update.undo_digests {
+            batch.put_cf(
+                undo_cf,
+                Self::make_utxo_muhash_undo_key(*block_height, outpoint),
+                digest,
+            );
+        }
+
+        Ok(())
+    }
+
+    // Restores the digests spent by `block_height` and drops the ones it created. The
+    // accumulator of the previous height is still stored, so only this height's state goes.
+    fn append_utxo_set_hash_rollback_to_batch(
+        &self,
+        batch: &mut WriteBatch,
+        block_height: u32,
+        created_utxos: &[BlockUndoUtxoEntry],
+    ) -> Result<(), String> {
+        if !self.is_utxo_set_hash_enabled() {
+            return Ok(());
+        }
+
+        let digests_cf = self.db.cf_handle(UTXO_MUHASH_DIGESTS_CF).ok_or_else(|| {
+            let msg = format!("Column family {} not found", UTXO_MUHASH_DIGESTS_CF);
+            error!("{}", msg);
+            msg
+        })?;
+        let states_cf = self.db.cf_handle(UTXO_SET_HASH_STATES_CF).ok_or_else(|| {
+            let msg = format!("Column family {} not found", UTXO_SET_HASH_STATES_CF);
+            error!("{}", msg);
+            msg
+        })?;
+
+        for created in created_utxos {
+            batch.delete_cf(digests_cf, Self::make_utxo_key(&created.outpoint));
+        }
+        for (outpoint, digest) in self.get_utxo_muhash_undo_digests(block_height)? {
+            batch.put_cf(digests_cf, Self::make_utxo_key(&outpoint), digest);
+        }
+        batch.delete_cf(states_cf, Self::make_block_commit_key(block_height));
+
+        Ok(())
+    }
+
+    fn get_utxo_muhash_undo_digests(
+        &self,
+        block_height: u32,
+    ) -> Result<Vec<(OutPoint, [u8; 32])>, String> {
+        let cf = self.db.cf_handle(UTXO_MUHASH_UNDO_CF).ok_or_else(|| {
+            let msg = format!("Column family {} not found", UTXO_MUHASH_UNDO_CF);
+            error!("{}", msg);
+            msg
+        })?;
+        let start_key = block_height.to_be_bytes();
+        let mut read_opts = ReadOptions::default();
+        read_opts.set_prefix_same_as_start(true);
+        read_opts.set_total_order_seek(false);
+        let iter = self.db.iterator_cf_opt(
+            cf,
+            read_opts,
+            IteratorMode::From(&start_key, Direction::Forward),
+        );
+
+        let mut entries = Vec::new();
+        for item in iter {
+            let (key, value) = item.map_err(|e| {
+                let msg = format!(
+                    "Iterator error when reading UTXO muhash undo at height {}: {}",
+                    block_height, e
+                );
+                error!("{}", msg);
+                msg
+            })?;
+            if !Self::block_height_prefix_matches(&key, block_height) {
+                break;
+            }
+            let outpoint = usdb_util::OutPointCodec::decode(&key[4..]).map_err(|e| {
+                let msg = format!("Failed to decode UTXO muhash undo outpoint: {}", e);
+                error!("{}", msg);
+                msg
+            })?;
+            entries.push((
+                outpoint,
+                Self::parse_balance_state_hash(UTXO_MUHASH_UNDO_CF, &value)?,
+            ));
+        }
+
+        Ok(entries)
+    }
+
+    /// Element digests of the given unspent outpoints; `None` for outpoints without one.
+    pub fn get_utxo_muhash_digests_bulk(
+        &self,
+        outpoints: &[OutPointRef],
+    ) -> Result<Vec<Option<[u8; 32]>>, String> {
+        let cf = self.db.cf_handle(UTXO_MUHASH_DIGESTS_CF).ok_or_else(|| {
+            let msg = format!("Column family {} not found", UTXO_MUHASH_DIGESTS_CF);
+            error!("{}", msg);
+            msg
+        })?;
+
+        let keys: Vec<[u8; UTXO_KEY_LEN]> = outpoints
+            .iter()
+            .map(|outpoint| Self::make_utxo_key(outpoint))
+            .collect();
+        let results = self
+            .db
+            .multi_get_pinned_cf(keys.iter().map(|k| (cf, k.as_slice())));
+
+        let mut digests = Vec::with_capacity(outpoints.len());
+        for res in results {
+            match res {
+                Ok(Some(value)) => digests.push(Some(Self::parse_balance_state_hash(
+                    UTXO_MUHASH_DIGESTS_CF,
+                    &value,
+                )?)),
+                Ok(None) => digests.push(None),
+                Err(e) => {
+                    let msg = format!("Failed to get UTXO muhash digests in bulk: {}", e);
+                    error!("{}", msg);
+                    return Err(msg);
+                }
+            }
+        }
+
+        Ok(digests)
+    }
+
+    /// MuHash accumulator of the UTXO set after `block_height`, if it was maintained.
+    pub fn get_utxo_set_hash_state(&self, block_height: u32) -> Result<Option<MuHash3072>, String> {
+        let cf = self.db.cf_handle(UTXO_SET_HASH_STATES_CF).ok_or_else(|| {
+            let msg = format!("Column family {} not found", UTXO_SET_HASH_STATES_CF);
+            error!("{}", msg);
+            msg
+        })?;
+        let value = self
+            .db
+            .get_cf(cf, Self::make_block_commit_key(block_height))
+            .map_err(|e| {
+                let msg = format!(
+                    "Failed to get UTXO set hash state at height {}: {}",
+                    block_height, e
+                );
+                error!("{}", msg);
+                msg
+            })?;
+
+        value
+            .map(|value| MuHash3072::from_bytes(&value))
+            .transpose()
+    }
+
+    /// Finalized MuHash of the UTXO set after `block_height`, comparable with
+    /// bitcoind's `gettxoutsetinfo muhash`.
+    pub fn get_utxo_set_hash(&self, block_height: u32) -> Result<Option<[u8; 32]>, String> {
+        Ok(self
+            .get_utxo_set_hash_state(block_height)?
+            .map(|state| state.finalize()))
+    }
+
+    /// Fails when `sync.utxo_set_hash` is enabled but the accumulator of the synced height
+    /// is missing, e.g. on a DB populated by snapshot install or synced with the flag off.
+    /// MuHash state can only be built by replaying every block from genesis.
+    pub fn ensure_utxo_set_hash_state_available(&self) -> Result<(), String> {
+        if !self.is_utxo_set_hash_enabled() {
+            return Ok(());
+        }
+
+        let block_height = self.get_btc_block_height()?;
+        if block_height == 0 || self.get_utxo_set_hash_state(block_height)?.is_some() {
+            return Ok(());
+        }
+
+        let origin = if self.get_snapshot_install_provenance()?.is_some() {
+            "this DB was populated by snapshot install, which carries no MuHash state"
+        } else {
+            "this DB was synced with sync.utxo_set_hash disabled"
+        };
+        let msg = format!(
+            "Cannot enable sync.utxo_set_hash: missing UTXO set hash state at synced height {}, {}. Disable sync.utxo_set_hash or resync from genesis with it enabled.",
+            block_height, origin
+        );
+        error!("{}", msg);
+        Err(msg)
+    }
+
+    pub fn is_address_tx_index_enabled(&self) -> bool {
+        self.config.sync.address_transactions
+    }
+
+    /// First block height covered by the address transaction index, if it was ever written.
+    pub fn get_address_tx_index_from_height(&self) -> Result<Option<u32>, String> {
+        self.get_u32_meta(META_KEY_ADDRESS_TX_INDEX_FROM_HEIGHT)
+    }
+
+    fn append_address_tx_index_to_batch(
+        &self,
+        batch: &mut WriteBatch,
+        update: &AddressTxIndexUpdate,
+    ) -> Result<(), String> {
+        let cf = self.db.cf_handle(ADDRESS_TX_INDEX_CF).ok_or_else(|| {
+            let msg = format!("Column family {} not found", ADDRESS_TX_INDEX_CF);
+            error!("{}", msg);
+            msg
+        })?;
+
+        for entry in &update.entries {
+            let mut value = [0u8; ADDRESS_TX_INDEX_VALUE_LEN];
+            value[..Txid::LEN].copy_from_slice(entry.txid.as_byte_array());
+            value[Txid::LEN..Txid::LEN + 8].copy_from_slice(&entry.input_value.to_be_bytes());
+            value[Txid::LEN + 8..].copy_from_slice(&entry.output_value.to_be_bytes());
+            batch.put_cf(
+                cf,
+                Self::make_address_tx_index_key(
+                    &entry.script_hash,
+                    entry.block_height,
+                    entry.tx_index,
+                ),
+                value,
+            );
+        }
+
+        if self.get_address_tx_index_from_height()?.is_none() {
+            let meta_cf = self.db.cf_handle(META_CF).ok_or_else(|| {
+                let msg = format!("Column family {} not found", META_CF);
+                error!("{}", msg);
+                msg
+            })?;
+            batch.put_cf(
+                meta_cf,
+                META_KEY_ADDRESS_TX_INDEX_FROM_HEIGHT,
+                update.first_block_height.to_be_bytes(),
+            );
+        }
+
+        Ok(())
+    }
+
+    // The undo bundle touches every script that owned a created or spent UTXO, so deleting
+    // their rows at `block_height` removes all rows the block wrote.
+    fn append_address_tx_index_rollback_to_batch(
+        &self,
+        batch: &mut WriteBatch,
+        block_height: u32,
+        touched_script_hashes: &[USDBScriptHash],
+    ) -> Result<(), String> {
+        if !self.is_address_tx_index_enabled() {
+            return Ok(());
+        }
+
+        let cf = self.db.cf_handle(ADDRESS_TX_INDEX_CF).ok_or_else(|| {
+            let msg = format!("Column family {} not found", ADDRESS_TX_INDEX_CF);
+            error!("{}", msg);
+            msg
+        })?;
+        for script_hash in touched_script_hashes {
+            batch.delete_range_cf(
+                cf,
+                Self::make_address_tx_index_key(script_hash, block_height, 0),
+                Self::make_address_tx_index_key(script_hash, block_height + 1, 0),
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Transactions that moved value for `script_hash` inside the half-open height range,
+    /// ordered by height and position in block, skipping `offset` rows and returning at
+    /// most `limit`.
+    pub fn get_address_transactions(
+        &self,
+        script_hash: &USDBScriptHash,
+        block_range: std::ops::Range<u32>,
+        offset: usize,
+        limit: usize,
+    ) -> Result<Vec<AddressTxIndexEntry>, String> {
+        let cf = self.db.cf_handle(ADDRESS_TX_INDEX_CF).ok_or_else(|| {
+            let msg = format!("Column family {} not found", ADDRESS_TX_INDEX_CF);
+            error!("{}", msg);
+            msg
+        })?;
+
+        let start_key = Self::make_address_tx_index_key(script_hash, block_range.start, 0);
+        let mut read_opts = ReadOptions::default();
+        read_opts.set_prefix_same_as_start(true);
+        read_opts.set_total_order_seek(false);
+        let iter = self.db.iterator_cf_opt(
+            cf,
+            read_opts,
+            IteratorMode::From(&start_key, Direction::Forward),
+        );
+
+        let prefix: &[u8] = script_hash.as_ref();
+        let mut entries = Vec::with_capacity(limit.min(1024));
+        for item in iter.skip(offset) {
+            if entries.len() >= limit {
+                break;
+            }
+
+            let (key, value) = item.map_err(|e| {
+                let msg = format!(
+                    "Iterator error when reading address transactions of {}: {}",
+                    script_hash, e
+                );
+                error!("{}", msg);
+                msg
+            })?;
+            if key.len() != ADDRESS_TX_INDEX_KEY_LEN || !key.starts_with(prefix) {
+                break;
+            }
+            let block_height =
+                Self::parse_u32_be_key(&key[USDBScriptHash::LEN..USDBScriptHash::LEN + 4])?;
+            if block_height >= block_range.end {
+                break;
+            }
+            if value.len() != ADDRESS_TX_INDEX_VALUE_LEN {
+                let msg = format!(
+                    "Invalid {} value length {}",
+                    ADDRESS_TX_INDEX_CF,
+                    value.len()
+                );
+                error!("{}", msg);
+                return Err(msg);
+            }
+
+            entries.push(AddressTxIndexEntry {
+                script_hash: *script_hash,
+                block_height,
+                tx_index: Self::parse_u32_be_key(&key[USDBScriptHash::LEN + 4..])?,
+                txid: Txid::from_slice(&value[..Txid::LEN]).unwrap(),
+                input_value: u64::from_be_bytes(
+                    value[Txid::LEN..Txid::LEN + 8].try_into().unwrap(),
+                ),
+                output_value: u64::from_be_bytes(value[Txid::LEN + 8..].try_into().unwrap()),
+            });
+        }
+
+        Ok(entries)
+    }
+
+    fn encode_utxo_history_value(entry: &UtxoHistoryEntry) -> [u8; UTXO_HISTORY_VALUE_LEN] {
+        let mut value = [0u8; UTXO_HISTORY_VALUE_LEN];
+        value[..4].copy_from_slice(
+            &entry
+                .created_height
+                .unwrap_or(UTXO_HISTORY_NO_HEIGHT)
+                .to_be_bytes(),
+        );
+        value[4..8].copy_from_slice(
+            &entry
+                .spent_height
+                .unwrap_or(UTXO_HISTORY_NO_HEIGHT)
+                .to_be_bytes(),
+        );
+        value[8..8 + USDBScriptHash::LEN].copy_from_slice(entry.script_hash.as_ref() as &[u8]);
+        value[8 + USDBScriptHash::LEN..].copy_from_slice(&entry.value.to_be_bytes());
+        value
+    }
+
+    fn parse_utxo_history_value(
+        outpoint: &OutPoint,
+        value: &[u8],
+    ) -> Result<UtxoHistoryEntry, String> {
+        if value.len() != UTXO_HISTORY_VALUE_LEN {
+            let msg = format!("Invalid {} value length {}", UTXO_HISTORY_CF, value.len());
+            error!("{}", msg);
+            return Err(msg);
+        }
+
+        let height = |bytes: &[u8]| {
+            let height = u32::from_be_bytes(bytes.try_into().unwrap());
+            (height != UTXO_HISTORY_NO_HEIGHT).then_some(height)
+        };
+        Ok(UtxoHistoryEntry {
+            outpoint: *outpoint,
+            script_hash: USDBScriptHash::from_byte_array(
+                value[8..8 + USDBScriptHash::LEN].try_into().unwrap(),
+            ),
+            value: u64::from_be_bytes(value[8 + USDBScriptHash::LEN..].try_into().unwrap()),
+            created_height: height(&value[..4]),
+            spent_height: height(&value[4..8]),
+        })
+    }
+
+    pub fn is_utxo_history_enabled(&self) -> bool {
+        self.config.sync.utxo_history
+    }
+
+    /// First block height covered by the UTXO history index, if it was ever written.
+    pub fn get_utxo_history_from_height(&self) -> Result<Option<u32>, String> {
+        self.get_u32_meta(META_KEY_UTXO_HISTORY_FROM_HEIGHT)
+    }
+
+    fn append_utxo_history_to_batch(
+        &self,
+        batch: &mut WriteBatch,
+        update: &UtxoHistoryUpdate,
+    ) -> Result<(), String> {
+        let cf = self.db.cf_handle(UTXO_HISTORY_CF).ok_or_else(|| {
+            let msg = format!("Column family {} not found", UTXO_HISTORY_CF);
+            error!("{}", msg);
+            msg
+        })?;
+
+        for entry in &update.entries {
+            batch.put_cf(
+                cf,
+                Self::make_utxo_key(&entry.outpoint),
+                Self::encode_utxo_history_value(entry),
+            );
+        }
+
+        if self.get_utxo_history_from_height()?.is_none() {
+            let meta_cf = self.db.cf_handle(META_CF).ok_or_else(|| {
+                let msg = format!("Column family {} not found", META_CF);
+                error!("{}", msg);
+                msg
+            })?;
+            batch.put_cf(
+                meta_cf,
+                META_KEY_UTXO_HISTORY_FROM_HEIGHT,
+                update.first_block_height.to_be_bytes(),
+            );
+        }
+
+        Ok(())
+    }
+
+    // Outputs created by the block are forgotten and outputs it spent become unspent again.
+    // A spent row without created height only existed to record the spend, so it is removed
+    // and the output falls back to the live UTXO set.
+    fn append_utxo_history_rollback_to_batch(
+        &self,
+        batch: &mut WriteBatch,
+        created_utxos: &[BlockUndoUtxoEntry],
+        spent_utxos: &[BlockUndoUtxoEntry],
+    ) -> Result<(), String> {
+        if !self.is_utxo_history_enabled() {
+            return Ok(());
+        }
+
+        let cf = self.db.cf_handle(UTXO_HISTORY_CF).ok_or_else(|| {
+            let msg = format!("Column family {} not found", UTXO_HISTORY_CF);
+            error!("{}", msg);
+            msg
+        })?;
+
+        let spent_outpoints: Vec<OutPoint> =
+            spent_utxos.iter().map(|entry| entry.outpoint).collect();
+        for entry in self
+            .get_utxo_history_bulk(&spent_outpoints)?
+            .into_iter()
+            .flatten()
+        {
+            let key = Self::make_utxo_key(&entry.outpoint);
+            if entry.created_height.is_none() {
+                batch.delete_cf(cf, key);
+            } else {
+                let entry = UtxoHistoryEntry {
+                    spent_height: None,
+                    ..entry
+                };
+                batch.put_cf(cf, key, Self::encode_utxo_history_value(&entry));
+            }
+        }
+
+        // Deleted last so outputs created and spent inside the block are removed entirely.
+        for entry in created_utxos {
+            batch.delete_cf(cf, Self::make_utxo_key(&entry.outpoint));
+        }
+
+        Ok(())
+    }
+
+    pub fn get_utxo_history_bulk<T: Borrow<OutPoint>>(
+        &self,
+        outpoints: &[T],
+    ) -> Result<Vec<Option<UtxoHistoryEntry>>, String> {
+        let cf = self.db.cf_handle(UTXO_HISTORY_CF).ok_or_else(|| {
+            let msg = format!("Column family {} not found", UTXO_HISTORY_CF);
+            error!("{}", msg);
+            msg
+        })?;
+
+        let keys: Vec<[u8; UTXO_KEY_LEN]> = outpoints
+            .iter()
+            .map(|outpoint| Self::make_utxo_key(outpoint.borrow()))
+            .collect();
+        let results = self
+            .db
+            .multi_get_pinned_cf(keys.iter().map(|key| (cf, key.as_slice())));
+
+        let mut entries = Vec::with_capacity(outpoints.len());
+        for (outpoint, res) in outpoints.iter().zip(results) {
+            match res {
+                Ok(Some(value)) => entries.push(Some(Self::parse_utxo_history_value(
+                    outpoint.borrow(),
+                    &value,
+                )?)),
+                Ok(None) => entries.push(None),
+                Err(e) => {
+                    let msg = format!("Failed to get UTXO history in bulk: {}", e);
+                    error!("{}", msg);
+                    return Err(msg);
+                }
+            }
+        }
+
+        Ok(entries)
+    }
+
+    /// The UTXO at `outpoint` if it was unspent once `block_height` was applied.
+    ///
+    /// Outpoints without a history row were never created or spent while the index was
+    /// enabled, so they are answered from the live UTXO set. The caller must ensure
+    /// `block_height` is covered by the index and not above the current DB height.
+    pub fn get_utxo_at_height(
+        &self,
+        outpoint: &OutPoint,
+        block_height: u32,
+    ) -> Result<Option<UtxoHistoryEntry>, String> {
+        let history_cf = self.db.cf_handle(UTXO_HISTORY_CF).ok_or_else(|| {
+            let msg = format!("Column family {} not found", UTXO_HISTORY_CF);
+            error!("{}", msg);
+            msg
+        })?;
+        let utxo_cf = self.db.cf_handle(UTXO_CF).ok_or_else(|| {
+            let msg = format!("Column family {} not found", UTXO_CF);
+            error!("{}", msg);
+            msg
+        })?;
+
+        let key = Self::make_utxo_key(outpoint);
+        let snapshot = self.db.snapshot();
+        let history = snapshot.get_cf(history_cf, key).map_err(|e| {
+            let msg = format!("Failed to get UTXO history of {}: {}", outpoint, e);
+            error!("{}", msg);
+            msg
+        })?;
+        let entry = match history {
+            Some(value) => Self::parse_utxo_history_value(outpoint, &value)?,
+            None => {
+                let live = snapshot.get_cf(utxo_cf, key).map_err(|e| {
+                    let msg = format!("Failed to get UTXO: {}", e);
+                    error!("{}", msg);
+                    msg
+                })?;
+                let Some(value) = live else {
+                    return Ok(None);
+                };
+                let utxo = Self::parse_utxo_from_value(&value);
+                UtxoHistoryEntry {
+                    outpoint: *outpoint,
+                    script_hash: utxo.script_hash,
+                    value: utxo.value,
+                    created_height: None,
+                    spent_height: None,
+                }
+            }
+        };
+
+        Ok(entry.is_live_at(block_height).then_some(entry))
+    }
+
+    // Balance and full sibling path of `script_hash` in the tree at the current DB height,
+    // from the leaf level upwards. A missing script yields balance 0 and a non-membership path.
+    pub fn get_balance_state_path(
+        &self,
+        script_hash: &USDBScriptHash,
+    ) -> Result<(u64, Vec<[u8; 32]>), String> {
+        let overlay = BalanceStateOverlay::default();
+        let bucket = Self::balance_state_bucket(script_hash);
+        let leaves = self.load_balance_state_bucket_leaves(&overlay, bucket)?;
+        let balance = leaves
+            .binary_search_by(|(key, _)| key.cmp(script_hash))
+            .map(|index| leaves[index].1)
+            .unwrap_or(0);
+
+        let mut siblings =
+            build_balance_state_subtree_siblings(BALANCE_STATE_BUCKET_DEPTH, &leaves, script_hash);
+        let mut prefix = bucket;
+        for depth in (1..=BALANCE_STATE_BUCKET_DEPTH).rev() {
+            siblings.push(self.get_balance_state_node(&overlay, depth, prefix ^ 1)?);
+            prefix >>= 1;
+        }
+
+        Ok((balance, siblings))
+    }
+
+    fn append_script_registry_entries_to_batch(
+        &self,
+        batch: &mut WriteBatch,
+        entries: &[ScriptRegistryEntry],
+    ) -> Result<(), String> {
+        if entries.is_empty() {
+            return Ok(());
+        }
+
+        let cf = self.db.cf_handle(SCRIPT_REGISTRY_CF).ok_or_else(|| {
+            let msg = format!("Column family {} not found", SCRIPT_REGISTRY_CF);
+            error!("{}", msg);
+            msg
+        })?;
+
+        for entry in entries {
+            batch.put_cf(
+                cf,
+                entry.script_hash.as_ref() as &[u8],
+                entry.script_pubkey.as_bytes(),
+            );
+        }
+
+        Ok(())
+    }
+
+    fn append_block_undo_bundles_to_batch(
+        &self,
+        batch: &mut WriteBatch,
+        undo_bundles: &[BlockUndoBundle],
+    ) -> Result<(), String> {
+        if undo_bundles.is_empty() {
+            return Ok(());
+        }
+
+        let meta_cf = self.db.cf_handle(BLOCK_UNDO_META_CF).ok_or_else(|| {
+            let msg = format!("Column family {} not found", BLOCK_UNDO_META_CF);
+            error!("{}", msg);
+            msg
+        })?;
+        let created_cf = self
+            .db
+            .cf_handle(BLOCK_UNDO_CREATED_UTXOS_CF)
+            .ok_or_else(|| {
+                let msg = format!("Column family {} not found", BLOCK_UNDO_CREATED_UTXOS_CF);
+                error!("{}", msg);
+                msg
+            })?;
+        let spent_cf = self
+            .db
+            .cf_handle(BLOCK_UNDO_SPENT_UTXOS_CF)
+            .ok_or_else(|| {
+                let msg = format!("Column family {} not found", BLOCK_UNDO_SPENT_UTXOS_CF);
+                error!("{}", msg);
+                msg
+            })?;
+        let balance_cf = self
+            .db
+            .cf_handle(BLOCK_UNDO_BALANCE_INDEX_CF)
+            .ok_or_else(|| {
+                let msg = format!("Column family {} not found", BLOCK_UNDO_BALANCE_INDEX_CF);
+                error!("{}", msg);
+                msg
+            })?;
+
+        for bundle in undo_bundles {
+            let meta = BlockUndoMetaEntry {
+                block_height: bundle.block_height,
+                format_version: 1,
+                btc_block_hash: bundle.btc_block_hash,
+                created_utxo_count: bundle.created_utxos.len() as u32,
+                spent_utxo_count: bundle.spent_utxos.len() as u32,
+                balance_entry_count: bundle.touched_script_hashes.len() as u32,
+            };
+
+            batch.put_cf(
+                meta_cf,
+                Self::make_block_undo_meta_key(bundle.block_height),
+                Self::serialize_block_undo_meta_value(&meta),
+            );
+
+            for (seq, entry) in bundle.created_utxos.iter().enumerate() {
+                batch.put_cf(
+                    created_cf,
+                    Self::make_block_undo_utxo_key(bundle.block_height, seq as u32),
+                    Self::serialize_block_undo_utxo_value(entry),
+                );
+            }
+
+            for (seq, entry) in bundle.spent_utxos.iter().enumerate() {
+                batch.put_cf(
+                    spent_cf,
+                    Self::make_block_undo_utxo_key(bundle.block_height, seq as u32),
+                    Self::serialize_block_undo_utxo_value(entry),
+                );
+            }
+
+            for script_hash in &bundle.touched_script_hashes {
+                batch.put_cf(
+                    balance_cf,
+                    Self::make_block_script_hash_key(bundle.block_height, script_hash),
+                    [1u8; 1],
+                );
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn get_block_undo_meta(
+        &self,
+        block_height: u32,
     ) -> Result<Option<BlockUndoMetaEntry>, String> {
         let cf = self.db.cf_handle(BLOCK_UNDO_META_CF).ok_or_else(|| {
             let msg = format!("Column family {} not found", BLOCK_UNDO_META_CF);
@@ -1250,15 +2617,28 @@ impl BalanceHistoryDB {
         &self,
         block_height: u32,
     ) -> Result<Vec<USDBScriptHash>, String> {
-        let cf = self
-            .db
-            .cf_handle(BLOCK_UNDO_BALANCE_INDEX_CF)
-            .ok_or_else(|| {
-                let msg = format!("Column family {} not found", BLOCK_UNDO_BALANCE_INDEX_CF);
-                error!("{}", msg);
-                msg
-            })?;
-        let start_key = Self::make_block_undo_balance_index_key(
+        self.get_block_script_hashes_from_cf(BLOCK_UNDO_BALANCE_INDEX_CF, block_height)
+    }
+
+    // Script hashes written by one Merkle-rule block, sorted like the block's leaves.
+    pub fn get_block_balance_index_script_hashes(
+        &self,
+        block_height: u32,
+    ) -> Result<Vec<USDBScriptHash>, String> {
+        self.get_block_script_hashes_from_cf(BLOCK_BALANCE_INDEX_CF, block_height)
+    }
+
+    fn get_block_script_hashes_from_cf(
+        &self,
+        cf_name: &str,
+        block_height: u32,
+    ) -> Result<Vec<USDBScriptHash>, String> {
+        let cf = self.db.cf_handle(cf_name).ok_or_else(|| {
+            let msg = format!("Column family {} not found", cf_name);
+            error!("{}", msg);
+            msg
+        })?;
+        let start_key = Self::make_block_script_hash_key(
             block_height,
             &USDBScriptHash::from_byte_array([0u8; USDBScriptHash::LEN]),
         );
@@ -1275,8 +2655,8 @@ impl BalanceHistoryDB {
         for item in iter {
             let (key, _value) = item.map_err(|e| {
                 let msg = format!(
-                    "Iterator error when reading block undo balance index at height {}: {}",
-                    block_height, e
+                    "Iterator error when reading {} at height {}: {}",
+                    cf_name, block_height, e
                 );
                 error!("{}", msg);
                 msg
@@ -1367,7 +2747,19 @@ impl BalanceHistoryDB {
         for script_hash in touched {
             batch.delete_cf(
                 balance_cf,
-                Self::make_block_undo_balance_index_key(block_height, &script_hash),
+                Self::make_block_script_hash_key(block_height, &script_hash),
+            );
+        }
+
+        let muhash_undo_cf = self.db.cf_handle(UTXO_MUHASH_UNDO_CF).ok_or_else(|| {
+            let msg = format!("Column family {} not found", UTXO_MUHASH_UNDO_CF);
+            error!("{}", msg);
+            msg
+        })?;
+        for (outpoint, _digest) in self.get_utxo_muhash_undo_digests(block_height)? {
+            batch.delete_cf(
+                muhash_undo_cf,
+                Self::make_utxo_muhash_undo_key(block_height, &outpoint),
             );
         }
 
@@ -1414,8 +2806,18 @@ impl BalanceHistoryDB {
 
         let mut batch = WriteBatch::default();
 
+        let address_utxo_cf = self.db.cf_handle(ADDRESS_UTXO_INDEX_CF).ok_or_else(|| {
+            let msg = format!("Column family {} not found", ADDRESS_UTXO_INDEX_CF);
+            error!("{}", msg);
+            msg
+        })?;
+
         for created in &bundle.created_utxos {
             batch.delete_cf(utxo_cf, Self::make_utxo_key(&created.outpoint));
+            batch.delete_cf(
+                address_utxo_cf,
+                Self::make_address_utxo_index_key(&created.script_hash, &created.outpoint),
+            );
         }
 
         for spent in &bundle.spent_utxos {
@@ -1424,16 +2826,51 @@ impl BalanceHistoryDB {
                 Self::make_utxo_key(&spent.outpoint),
                 UTXOValue::encode(&spent.script_hash, spent.value),
             );
+            batch.put_cf(
+                address_utxo_cf,
+                Self::make_address_utxo_index_key(&spent.script_hash, &spent.outpoint),
+                spent.value.to_be_bytes(),
+            );
         }
 
+        let balance_index_cf = self.db.cf_handle(BLOCK_BALANCE_INDEX_CF).ok_or_else(|| {
+            let msg = format!("Column family {} not found", BLOCK_BALANCE_INDEX_CF);
+            error!("{}", msg);
+            msg
+        })?;
         for script_hash in &bundle.touched_script_hashes {
             batch.delete_cf(
                 balance_cf,
                 Self::make_balance_history_key(script_hash, block_height),
             );
+            batch.delete_cf(
+                balance_index_cf,
+                Self::make_block_script_hash_key(block_height, script_hash),
+            );
         }
 
         batch.delete_cf(block_commit_cf, Self::make_block_commit_key(block_height));
+        self.append_balance_state_rollback_to_batch(
+            &mut batch,
+            block_height,
+            &bundle.touched_script_hashes,
+        )?;
+
+        self.append_utxo_set_hash_rollback_to_batch(
+            &mut batch,
+            block_height,
+            &bundle.created_utxos,
+        )?;
+        self.append_address_tx_index_rollback_to_batch(
+            &mut batch,
+            block_height,
+            &bundle.touched_script_hashes,
+        )?;
+        self.append_utxo_history_rollback_to_batch(
+            &mut batch,
+            &bundle.created_utxos,
+            &bundle.spent_utxos,
+        )?;
 
         self.append_delete_block_undo_bundle_to_batch(&mut batch, block_height)?;
 
@@ -1596,68 +3033,321 @@ impl BalanceHistoryDB {
                 msg
             })?;
 
-            let block_height = Self::parse_u32_be_key(&key)?;
-            if block_height >= effective_min_retained_height {
-                break;
-            }
-            heights_to_prune.push(block_height);
+            let block_height = Self::parse_u32_be_key(&key)?;
+            if block_height >= effective_min_retained_height {
+                break;
+            }
+            heights_to_prune.push(block_height);
+        }
+
+        if heights_to_prune.is_empty() {
+            self.put_undo_retained_from_height(effective_min_retained_height)?;
+            return Ok(0);
+        }
+
+        let mut batch = WriteBatch::default();
+        for block_height in &heights_to_prune {
+            self.append_delete_block_undo_bundle_to_batch(&mut batch, *block_height)?;
+        }
+
+        let meta_cf = self.db.cf_handle(META_CF).ok_or_else(|| {
+            let msg = format!("Column family {} not found", META_CF);
+            error!("{}", msg);
+            msg
+        })?;
+        batch.put_cf(
+            meta_cf,
+            META_KEY_UNDO_RETAINED_FROM_HEIGHT,
+            effective_min_retained_height.to_be_bytes(),
+        );
+
+        let mut write_options = WriteOptions::default();
+        write_options.set_sync(false);
+        self.db.write_opt(&batch, &write_options).map_err(|e| {
+            let msg = format!(
+                "Failed to prune undo journal before height {}: {}",
+                effective_min_retained_height, e
+            );
+            error!("{}", msg);
+            msg
+        })?;
+
+        info!(
+            "Pruned undo journal entries: removed_blocks={}, undo_retained_from_height={}",
+            heights_to_prune.len(),
+            effective_min_retained_height
+        );
+
+        Ok(heights_to_prune.len())
+    }
+
+    pub fn put_undo_retained_from_height(&self, height: u32) -> Result<(), String> {
+        self.put_u32_meta(META_KEY_UNDO_RETAINED_FROM_HEIGHT, height)
+    }
+
+    pub fn get_undo_retained_from_height(&self) -> Result<Option<u32>, String> {
+        self.get_u32_meta(META_KEY_UNDO_RETAINED_FROM_HEIGHT)
+    }
+
+    pub fn put_rollback_supported_from_height(&self, height: u32) -> Result<(), String> {
+        self.put_u32_meta(META_KEY_ROLLBACK_SUPPORTED_FROM_HEIGHT, height)
+    }
+
+    pub fn get_rollback_supported_from_height(&self) -> Result<Option<u32>, String> {
+        self.get_u32_meta(META_KEY_ROLLBACK_SUPPORTED_FROM_HEIGHT)
+    }
+
+    /// Lowest height whose balance history is fully retained, if the DB was ever pruned.
+    pub fn get_balance_history_retained_from_height(&self) -> Result<Option<u32>, String> {
+        self.get_u32_meta(META_KEY_BALANCE_HISTORY_RETAINED_FROM_HEIGHT)
+    }
+
+    /// Returns the retention floor when the rows at `block_height` may already be pruned.
+    ///
+    /// Below the floor only the latest row per script survives, which still resolves
+    /// balances but no longer holds every row a block wrote. Snapshot generation, rollback
+    /// and the RPC queries all go through this check so they agree on what is retained.
+    pub fn get_balance_history_pruned_floor(
+        &self,
+        block_height: u32,
+    ) -> Result<Option<u32>, String> {
+        Ok(self
+            .get_balance_history_retained_from_height()?
+            .filter(|retained_from_height| block_height < *retained_from_height))
+    }
+
+    /// Drops balance history rows below `retained_from_height`.
+    ///
+    /// Per script hash the latest row below the floor is kept, because it carries the
+    /// balance every height from the floor up to the next row resolves to. That row is
+    /// dropped too when its balance is zero and nothing older survives, matching how
+    /// snapshots omit empty scripts. When the address transaction index starts below the
+    /// floor, the latest row before its coverage start is also kept so callers can still
+    /// tell whether a script had activity the index does not cover.
+    ///
+    /// Each script is only read up to the floor and then skipped with a seek, so a pass
+    /// costs one seek per script plus the rows below the floor, not the retained window.
+    ///
+    /// The floor is recorded before any row is deleted, so readers never observe a height
+    /// as retained while its rows are being removed. A pass interrupted by `should_stop`
+    /// records a cursor at the script it was scanning, and the next call at the same or a
+    /// higher floor resumes from there.
+    pub fn prune_balance_history_before_height(
+        &self,
+        retained_from_height: u32,
+        should_stop: &dyn Fn() -> bool,
+    ) -> Result<usize, String> {
+        if self.is_rollback_in_progress()? {
+            warn!(
+                "Skipping balance history prune while rollback is in progress: retained_from_height={}",
+                retained_from_height
+            );
+            return Ok(0);
+        }
+
+        let current_floor = self.get_balance_history_retained_from_height()?;
+        if current_floor.is_some_and(|floor| floor > retained_from_height) {
+            let msg = format!(
+                "Balance history retention floor cannot move backwards: current={}, requested={}",
+                current_floor.unwrap(),
+                retained_from_height
+            );
+            error!("{}", msg);
+            return Err(msg);
+        }
+
+        let synced_height = self.get_btc_block_height()?;
+        if retained_from_height > synced_height {
+            let msg = format!(
+                "Balance history retention floor {} is above synced height {}",
+                retained_from_height, synced_height
+            );
+            error!("{}", msg);
+            return Err(msg);
+        }
+
+        self.put_u32_meta(
+            META_KEY_BALANCE_HISTORY_RETAINED_FROM_HEIGHT,
+            retained_from_height,
+        )?;
+
+        let tx_index_from_height = if self.is_address_tx_index_enabled() {
+            self.get_address_tx_index_from_height()?
+                .filter(|height| *height < retained_from_height)
+        } else {
+            None
+        };
+
+        let cf = self.db.cf_handle(BALANCE_HISTORY_CF).ok_or_else(|| {
+            let msg = format!("Column family {} not found", BALANCE_HISTORY_CF);
+            error!("{}", msg);
+            msg
+        })?;
+
+        let mut write_options = WriteOptions::default();
+        write_options.set_sync(false);
+        let flush = |batch: &mut WriteBatch| -> Result<(), String> {
+            self.db.write_opt(&*batch, &write_options).map_err(|e| {
+                let msg = format!(
+                    "Failed to delete balance history rows below height {}: {}",
+                    retained_from_height, e
+                );
+                error!("{}", msg);
+                msg
+            })?;
+            batch.clear();
+            Ok(())
+        };
+
+        // Rows below the floor of the script currently being scanned.
+        struct PendingAnchors {
+            script_hash: Vec<u8>,
+            // Latest row before the address transaction index coverage start.
+            tx_index_anchor: Option<Box<[u8]>>,
+            // Latest row below the floor and its balance.
+            floor_anchor: Option<(Box<[u8]>, u64)>,
         }
 
-        if heights_to_prune.is_empty() {
-            self.put_undo_retained_from_height(effective_min_retained_height)?;
-            return Ok(0);
+        // A zero-balance floor anchor is only dropped when no older row survives, otherwise
+        // lookups above the floor would resolve to that older row instead.
+        fn finish_script(pending: PendingAnchors, batch: &mut WriteBatch, cf: &ColumnFamily) {
+            if let Some((key, balance)) = pending.floor_anchor {
+                if balance == 0 && pending.tx_index_anchor.is_none() {
+                    batch.delete_cf(cf, key);
+                }
+            }
         }
 
+        // An interrupted pass left a cursor at the script it was scanning. Scripts before it
+        // were pruned at the cursor's floor; if the floor has moved since, their rows between
+        // the two floors stay until the next full pass, which only wastes space.
+        let cursor: Option<BalanceHistoryPruneCursor> =
+            self.get_json_meta(META_KEY_BALANCE_HISTORY_PRUNE_CURSOR)?;
+        let start_key = match &cursor {
+            Some(cursor) => {
+                let script_hash =
+                    usdb_util::parse_script_hash(&cursor.next_script_hash).map_err(|e| {
+                        let msg = format!("Invalid balance history prune cursor: {}", e);
+                        error!("{}", msg);
+                        msg
+                    })?;
+                info!(
+                    "Resuming balance history prune: retained_from_height={}, cursor_floor={}, next_script_hash={}",
+                    retained_from_height, cursor.retained_from_height, cursor.next_script_hash
+                );
+                Self::make_balance_history_key(&script_hash, 0).to_vec()
+            }
+            None => Vec::new(),
+        };
+
         let mut batch = WriteBatch::default();
-        for block_height in &heights_to_prune {
-            self.append_delete_block_undo_bundle_to_batch(&mut batch, *block_height)?;
-        }
+        let mut pruned = 0usize;
+        let mut pending: Option<PendingAnchors> = None;
+        let mut iter = self.db.raw_iterator_cf(cf);
+        iter.seek(&start_key);
+        while iter.valid() {
+            let key: Box<[u8]> = Box::from(iter.key().unwrap());
+            if key.len() != BALANCE_HISTORY_KEY_LEN {
+                iter.next();
+                continue;
+            }
 
-        let meta_cf = self.db.cf_handle(META_CF).ok_or_else(|| {
-            let msg = format!("Column family {} not found", META_CF);
-            error!("{}", msg);
-            msg
-        })?;
-        batch.put_cf(
-            meta_cf,
-            META_KEY_UNDO_RETAINED_FROM_HEIGHT,
-            effective_min_retained_height.to_be_bytes(),
-        );
+            let script_hash = &key[..USDBScriptHash::LEN];
+            if pending
+                .as_ref()
+                .is_none_or(|pending| pending.script_hash.as_slice() != script_hash)
+            {
+                if let Some(done) = pending.take() {
+                    let before = batch.len();
+                    finish_script(done, &mut batch, cf);
+                    pruned += batch.len() - before;
+                }
+                pending = Some(PendingAnchors {
+                    script_hash: script_hash.to_vec(),
+                    tx_index_anchor: None,
+                    floor_anchor: None,
+                });
+            }
 
-        let mut write_options = WriteOptions::default();
-        write_options.set_sync(false);
-        self.db.write_opt(&batch, &write_options).map_err(|e| {
-            let msg = format!(
-                "Failed to prune undo journal before height {}: {}",
-                effective_min_retained_height, e
-            );
+            let block_height = Self::parse_block_height_from_key(&key);
+            if block_height >= retained_from_height {
+                // The rest of this script is retained, so skip straight to the next one
+                // instead of reading every row inside the retention window.
+                let mut next_script_key = [0xFFu8; BALANCE_HISTORY_KEY_LEN];
+                next_script_key[..USDBScriptHash::LEN].copy_from_slice(script_hash);
+                iter.seek(next_script_key);
+                if iter.key() == Some(&next_script_key[..]) {
+                    iter.next();
+                }
+                continue;
+            }
+
+            let (_, balance) = Self::parse_balance_from_value(iter.value().unwrap());
+            let anchors = pending.as_mut().unwrap();
+            let superseded = anchors.floor_anchor.replace((key.clone(), balance));
+            if tx_index_from_height.is_some_and(|from_height| block_height < from_height) {
+                // Everything seen so far is older than this row, including the previous
+                // transaction index anchor, which is the superseded floor anchor.
+                anchors.tx_index_anchor = Some(key);
+                if let Some((superseded_key, _)) = superseded {
+                    batch.delete_cf(cf, superseded_key);
+                    pruned += 1;
+                }
+            } else if let Some((superseded_key, _)) = superseded {
+                if anchors.tx_index_anchor.as_ref() != Some(&superseded_key) {
+                    batch.delete_cf(cf, superseded_key);
+                    pruned += 1;
+                }
+            }
+
+            if batch.len() >= BALANCE_HISTORY_PRUNE_BATCH {
+                flush(&mut batch)?;
+                if should_stop() {
+                    // The current script is scanned again on resume, since its anchors
+                    // are only settled once all of its rows below the floor are seen.
+                    let script_hash =
+                        USDBScriptHash::from_slice(&pending.as_ref().unwrap().script_hash).unwrap();
+                    self.put_json_meta(
+                        META_KEY_BALANCE_HISTORY_PRUNE_CURSOR,
+                        &BalanceHistoryPruneCursor {
+                            retained_from_height,
+                            next_script_hash: script_hash.to_string(),
+                        },
+                    )?;
+                    info!(
+                        "Balance history prune interrupted: retained_from_height={}, pruned_rows={}, next_script_hash={}",
+                        retained_from_height, pruned, script_hash
+                    );
+                    return Ok(pruned);
+                }
+            }
+
+            iter.next();
+        }
+        iter.status().map_err(|e| {
+            let msg = format!("Iterator error when pruning balance history: {}", e);
             error!("{}", msg);
             msg
         })?;
 
-        info!(
-            "Pruned undo journal entries: removed_blocks={}, undo_retained_from_height={}",
-            heights_to_prune.len(),
-            effective_min_retained_height
-        );
-
-        Ok(heights_to_prune.len())
-    }
-
-    pub fn put_undo_retained_from_height(&self, height: u32) -> Result<(), String> {
-        self.put_u32_meta(META_KEY_UNDO_RETAINED_FROM_HEIGHT, height)
-    }
+        if let Some(done) = pending.take() {
+            let before = batch.len();
+            finish_script(done, &mut batch, cf);
+            pruned += batch.len() - before;
+        }
+        flush(&mut batch)?;
+        if cursor.is_some() {
+            self.delete_meta_key(META_KEY_BALANCE_HISTORY_PRUNE_CURSOR)?;
+        }
 
-    pub fn get_undo_retained_from_height(&self) -> Result<Option<u32>, String> {
-        self.get_u32_meta(META_KEY_UNDO_RETAINED_FROM_HEIGHT)
-    }
+        self.db.compact_range_cf(cf, None::<&[u8]>, None::<&[u8]>);
 
-    pub fn put_rollback_supported_from_height(&self, height: u32) -> Result<(), String> {
-        self.put_u32_meta(META_KEY_ROLLBACK_SUPPORTED_FROM_HEIGHT, height)
-    }
+        info!(
+            "Pruned balance history: retained_from_height={}, pruned_rows={}",
+            retained_from_height, pruned
+        );
 
-    pub fn get_rollback_supported_from_height(&self) -> Result<Option<u32>, String> {
-        self.get_u32_meta(META_KEY_ROLLBACK_SUPPORTED_FROM_HEIGHT)
+        Ok(pruned)
     }
 
     fn ensure_rollback_undo_available(
@@ -1669,6 +3359,19 @@ impl BalanceHistoryDB {
             return Ok(());
         }
 
+        // Rollback removes every row above the target, so those rows must still be retained;
+        // the anchors left below the floor describe the balances at the target itself.
+        if let Some(retained_from_height) =
+            self.get_balance_history_pruned_floor(target_height.saturating_add(1))?
+        {
+            let msg = format!(
+                "Rollback from height {} to {} crosses the balance history retention floor {}. Reset balance-history or install a matching snapshot before continuing.",
+                current_height, target_height, retained_from_height
+            );
+            error!("{}", msg);
+            return Err(msg);
+        }
+
         let first_required_height = target_height.saturating_add(1);
         if let Some(supported_from_height) = self.get_rollback_supported_from_height()? {
             if first_required_height < supported_from_height {
@@ -2246,7 +3949,14 @@ impl BalanceHistoryDB {
 
         let key = Self::make_utxo_key(outpoint);
 
-        self.db.put_cf_opt(cf, key, value, &ops).map_err(|e| {
+        let mut batch = WriteBatch::default();
+        batch.put_cf(cf, key, value);
+        self.append_address_utxo_index_puts_to_batch(
+            &mut batch,
+            std::iter::once((outpoint, script_hash, amount)),
+        )?;
+
+        self.db.write_opt(&batch, &ops).map_err(|e| {
             let msg = format!("Failed to put UTXO: {}", e);
             error!("{}", msg);
             msg
@@ -2271,6 +3981,12 @@ impl BalanceHistoryDB {
 
             batch.put_cf(cf, key, value);
         }
+        self.append_address_utxo_index_puts_to_batch(
+            &mut batch,
+            utxos
+                .iter()
+                .map(|utxo| (&utxo.outpoint, &utxo.script_hash, utxo.value)),
+        )?;
 
         let mut write_options = WriteOptions::default();
         write_options.set_sync(false);
@@ -2283,6 +3999,30 @@ impl BalanceHistoryDB {
         Ok(())
     }
 
+    pub fn delete_utxos(&self, outpoints: &[OutPoint]) -> Result<(), String> {
+        let cf = self.db.cf_handle(UTXO_CF).ok_or_else(|| {
+            let msg = format!("Column family {} not found", UTXO_CF);
+            error!("{}", msg);
+            msg
+        })?;
+
+        let mut batch = WriteBatch::default();
+        self.append_address_utxo_index_removals_to_batch(&mut batch, outpoints)?;
+        for outpoint in outpoints {
+            batch.delete_cf(cf, Self::make_utxo_key(outpoint));
+        }
+
+        let mut write_options = WriteOptions::default();
+        write_options.set_sync(false);
+        self.db.write_opt(&batch, &write_options).map_err(|e| {
+            let msg = format!("Failed to delete UTXO batch from DB: {}", e);
+            error!("{}", msg);
+            msg
+        })?;
+
+        Ok(())
+    }
+
     pub fn update_utxos_async(
         &self,
         new_utxos: &Vec<(OutPointRef, UTXOEntryRef)>,
@@ -2295,6 +4035,13 @@ impl BalanceHistoryDB {
         })?;
 
         let mut batch = WriteBatch::default();
+        self.append_address_utxo_index_removals_to_batch(&mut batch, remove_utxos)?;
+        self.append_address_utxo_index_puts_to_batch(
+            &mut batch,
+            new_utxos
+                .iter()
+                .map(|(outpoint, utxo)| (outpoint.as_ref(), &utxo.script_hash, utxo.value)),
+        )?;
 
         for (outpoint, utxo) in new_utxos {
             // Value format: USDBScriptHash (32 bytes) + amount (u64)
@@ -2362,24 +4109,254 @@ impl BalanceHistoryDB {
         // Convert to iterator of (cf, key)
         let cf_keys = keys.iter().map(|k| (cf, k.as_slice()));
 
-        // Execute batch read
-        let results = self.db.multi_get_pinned_cf(cf_keys);
+        // Execute batch read
+        let results = self.db.multi_get_pinned_cf(cf_keys);
+
+        // Parse results
+        let mut entries = Vec::with_capacity(outpoints.len());
+        for res in results {
+            match res {
+                Ok(Some(value)) => entries.push(Some(Self::parse_utxo_from_value(&value))),
+                Ok(None) => entries.push(None),
+                Err(e) => {
+                    let msg = format!("Failed to get UTXO in bulk: {}", e);
+                    error!("{}", msg);
+                    return Err(msg);
+                }
+            }
+        }
+
+        Ok(entries)
+    }
+
+    fn append_address_utxo_index_puts_to_batch<'e>(
+        &self,
+        batch: &mut WriteBatch,
+        utxos: impl Iterator<Item = (&'e OutPoint, &'e USDBScriptHash, u64)>,
+    ) -> Result<(), String> {
+        let cf = self.db.cf_handle(ADDRESS_UTXO_INDEX_CF).ok_or_else(|| {
+            let msg = format!("Column family {} not found", ADDRESS_UTXO_INDEX_CF);
+            error!("{}", msg);
+            msg
+        })?;
+
+        for (outpoint, script_hash, value) in utxos {
+            batch.put_cf(
+                cf,
+                Self::make_address_utxo_index_key(script_hash, outpoint),
+                value.to_be_bytes(),
+            );
+        }
+
+        Ok(())
+    }
+
+    // Callers only pass outpoints, so the owning scripts are read back from UTXO_CF. This must
+    // run before the same batch deletes the outpoints; missing outpoints have no index entry.
+    fn append_address_utxo_index_removals_to_batch<T: Borrow<OutPoint>>(
+        &self,
+        batch: &mut WriteBatch,
+        outpoints: &[T],
+    ) -> Result<(), String> {
+        if outpoints.is_empty() {
+            return Ok(());
+        }
+
+        let utxo_cf = self.db.cf_handle(UTXO_CF).ok_or_else(|| {
+            let msg = format!("Column family {} not found", UTXO_CF);
+            error!("{}", msg);
+            msg
+        })?;
+        let cf = self.db.cf_handle(ADDRESS_UTXO_INDEX_CF).ok_or_else(|| {
+            let msg = format!("Column family {} not found", ADDRESS_UTXO_INDEX_CF);
+            error!("{}", msg);
+            msg
+        })?;
+
+        let keys: Vec<[u8; UTXO_KEY_LEN]> = outpoints
+            .iter()
+            .map(|outpoint| Self::make_utxo_key(outpoint.borrow()))
+            .collect();
+        let results = self
+            .db
+            .multi_get_pinned_cf(keys.iter().map(|k| (utxo_cf, k.as_slice())));
+
+        for (outpoint, res) in outpoints.iter().zip(results) {
+            match res {
+                Ok(Some(value)) => {
+                    let utxo = Self::parse_utxo_from_value(&value);
+                    batch.delete_cf(
+                        cf,
+                        Self::make_address_utxo_index_key(&utxo.script_hash, outpoint.borrow()),
+                    );
+                }
+                Ok(None) => {}
+                Err(e) => {
+                    let msg = format!("Failed to get UTXO owners for address index: {}", e);
+                    error!("{}", msg);
+                    return Err(msg);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    // Backfill the address UTXO index once for DBs whose UTXO set predates it.
+    fn ensure_address_utxo_index(&self) -> Result<(), String> {
+        let meta_cf = self.db.cf_handle(META_CF).ok_or_else(|| {
+            let msg = format!("Column family {} not found", META_CF);
+            error!("{}", msg);
+            msg
+        })?;
+        let ready = self
+            .db
+            .get_cf(meta_cf, META_KEY_ADDRESS_UTXO_INDEX_READY)
+            .map_err(|e| {
+                let msg = format!("Failed to get address UTXO index state: {}", e);
+                error!("{}", msg);
+                msg
+            })?;
+        if ready.is_some() {
+            return Ok(());
+        }
+
+        let utxo_cf = self.db.cf_handle(UTXO_CF).ok_or_else(|| {
+            let msg = format!("Column family {} not found", UTXO_CF);
+            error!("{}", msg);
+            msg
+        })?;
+        let index_cf = self.db.cf_handle(ADDRESS_UTXO_INDEX_CF).ok_or_else(|| {
+            let msg = format!("Column family {} not found", ADDRESS_UTXO_INDEX_CF);
+            error!("{}", msg);
+            msg
+        })?;
+
+        let mut write_options = WriteOptions::default();
+        write_options.set_sync(false);
+
+        let mut batch = WriteBatch::default();
+        let mut indexed = 0usize;
+        for item in self.db.iterator_cf(utxo_cf, IteratorMode::Start) {
+            let (key, value) = item.map_err(|e| {
+                let msg = format!("Iterator error while backfilling address UTXO index: {}", e);
+                error!("{}", msg);
+                msg
+            })?;
+            if key.len() != UTXO_KEY_LEN {
+                continue;
+            }
+
+            let outpoint = usdb_util::OutPointCodec::decode(&key)?;
+            let utxo = Self::parse_utxo_from_value(&value);
+            batch.put_cf(
+                index_cf,
+                Self::make_address_utxo_index_key(&utxo.script_hash, &outpoint),
+                utxo.value.to_be_bytes(),
+            );
+            indexed += 1;
+
+            if batch.len() >= ADDRESS_UTXO_INDEX_BACKFILL_CHUNK {
+                self.db.write_opt(&batch, &write_options).map_err(|e| {
+                    let msg = format!("Failed to write address UTXO index batch: {}", e);
+                    error!("{}", msg);
+                    msg
+                })?;
+                batch = WriteBatch::default();
+                info!("Address UTXO index backfill progress: {} UTXOs", indexed);
+            }
+        }
+
+        batch.put_cf(meta_cf, META_KEY_ADDRESS_UTXO_INDEX_READY, [1u8; 1]);
+        write_options.set_sync(true);
+        self.db.write_opt(&batch, &write_options).map_err(|e| {
+            let msg = format!("Failed to finish address UTXO index backfill: {}", e);
+            error!("{}", msg);
+            msg
+        })?;
+
+        if indexed > 0 {
+            info!("Address UTXO index backfilled: utxos={}", indexed);
+        }
+        Ok(())
+    }
+
+    /// Live UTXOs owned by `script_hash` in outpoint order, skipping `offset` entries and
+    /// returning at most `limit`.
+    ///
+    /// The listing and the returned BTC height are read from one RocksDB snapshot, so the
+    /// page is exactly the UTXO set at that height.
+    pub fn get_address_utxos(
+        &self,
+        script_hash: &USDBScriptHash,
+        offset: usize,
+        limit: usize,
+    ) -> Result<(u32, Vec<(OutPoint, u64)>), String> {
+        let meta_cf = self.db.cf_handle(META_CF).ok_or_else(|| {
+            let msg = format!("Column family {} not found", META_CF);
+            error!("{}", msg);
+            msg
+        })?;
+        let cf = self.db.cf_handle(ADDRESS_UTXO_INDEX_CF).ok_or_else(|| {
+            let msg = format!("Column family {} not found", ADDRESS_UTXO_INDEX_CF);
+            error!("{}", msg);
+            msg
+        })?;
+
+        let snapshot = self.db.snapshot();
+        let block_height = match snapshot
+            .get_cf(meta_cf, META_KEY_BTC_BLOCK_HEIGHT)
+            .map_err(|e| {
+                let msg = format!("Failed to get BTC block height: {}", e);
+                error!("{}", msg);
+                msg
+            })? {
+            Some(value) => Self::parse_u32_be_key(&value)?,
+            None => 0,
+        };
+
+        let prefix: &[u8] = script_hash.as_ref();
+        let mut read_opts = ReadOptions::default();
+        read_opts.set_prefix_same_as_start(true);
+        read_opts.set_total_order_seek(false);
+        let iter = snapshot.iterator_cf_opt(
+            cf,
+            read_opts,
+            IteratorMode::From(prefix, Direction::Forward),
+        );
+
+        let mut entries = Vec::with_capacity(limit.min(1024));
+        for item in iter.skip(offset) {
+            if entries.len() >= limit {
+                break;
+            }
 
-        // Parse results
-        let mut entries = Vec::with_capacity(outpoints.len());
-        for res in results {
-            match res {
-                Ok(Some(value)) => entries.push(Some(Self::parse_utxo_from_value(&value))),
-                Ok(None) => entries.push(None),
-                Err(e) => {
-                    let msg = format!("Failed to get UTXO in bulk: {}", e);
-                    error!("{}", msg);
-                    return Err(msg);
-                }
+            let (key, value) = item.map_err(|e| {
+                let msg = format!(
+                    "Iterator error when reading address UTXOs of {}: {}",
+                    script_hash, e
+                );
+                error!("{}", msg);
+                msg
+            })?;
+            if key.len() != ADDRESS_UTXO_INDEX_KEY_LEN || !key.starts_with(prefix) {
+                break;
             }
+
+            let outpoint = usdb_util::OutPointCodec::decode(&key[USDBScriptHash::LEN..])?;
+            let value: [u8; 8] = value.as_ref().try_into().map_err(|_| {
+                let msg = format!(
+                    "Invalid {} value length {}",
+                    ADDRESS_UTXO_INDEX_CF,
+                    value.len()
+                );
+                error!("{}", msg);
+                msg
+            })?;
+            entries.push((outpoint, u64::from_be_bytes(value)));
         }
 
-        Ok(entries)
+        Ok((block_height, entries))
     }
 
     /// Persist auxiliary script registry entries in one batch.
@@ -2659,6 +4636,127 @@ impl BalanceHistoryDB {
         Ok(())
     }
 
+    // Emit, for every script_hash, the latest entry in (base_block_height, target_block_height].
+    // A zero-balance latest entry is only emitted when the base snapshot exported a positive
+    // balance for the script, because full snapshots skip zero balances and the delta row must
+    // override the base row on install.
+    fn generate_balance_history_delta_snapshot_sharded(
+        &self,
+        base_block_height: u32,
+        target_block_height: u32,
+        shard_index: u8,
+        batch_size: usize,
+        cb: SnapshotCallbackRef,
+    ) -> Result<(), String> {
+        let cf = self.db.cf_handle(BALANCE_HISTORY_CF).ok_or_else(|| {
+            let msg = format!("Column family {} not found", BALANCE_HISTORY_CF);
+            error!("{}", msg);
+            msg
+        })?;
+
+        let mut seek_key = vec![shard_index];
+        seek_key.resize(USDBScriptHash::LEN, 0xFF); // max USDBScriptHash
+        let seek_key = Self::make_balance_history_key(
+            &USDBScriptHash::from_slice(&seek_key).unwrap(),
+            u32::MAX,
+        );
+
+        let mut iter = self
+            .db
+            .full_iterator_cf(&cf, IteratorMode::From(&seek_key, Direction::Reverse));
+
+        let mut current_script_hash: Option<USDBScriptHash> = None;
+        let mut current_founded = false;
+        // Latest zero-balance entry of the current script, waiting for its base-side row.
+        let mut pending_zero_entry: Option<BalanceHistoryEntry> = None;
+        let mut snapshot = Vec::with_capacity(batch_size);
+        let mut entries_processed = 0u64;
+
+        while let Some(Ok((key, value))) = iter.next() {
+            if key.len() != BALANCE_HISTORY_KEY_LEN {
+                continue;
+            }
+
+            if key[0] != shard_index {
+                // Moved to a new shard
+                break;
+            }
+
+            entries_processed += 1;
+
+            let script_hash = USDBScriptHash::from_slice(&key[0..USDBScriptHash::LEN]).unwrap();
+            let height = u32::from_be_bytes(
+                key[USDBScriptHash::LEN..USDBScriptHash::LEN + 4]
+                    .try_into()
+                    .unwrap(),
+            );
+
+            if current_script_hash.as_ref() != Some(&script_hash) {
+                // Moved to a new script_hash
+                current_script_hash = Some(script_hash);
+                current_founded = false;
+                pending_zero_entry = None;
+            }
+
+            if current_founded || height > target_block_height {
+                continue;
+            }
+
+            let (delta, balance) = Self::parse_balance_from_value(&value);
+            let emit = if let Some(entry) = pending_zero_entry.take() {
+                if height > base_block_height {
+                    // Older row still inside the delta range, keep looking for the base row
+                    pending_zero_entry = Some(entry);
+                    None
+                } else {
+                    current_founded = true;
+                    if balance > 0 { Some(entry) } else { None }
+                }
+            } else if height <= base_block_height {
+                // Unchanged since the base snapshot
+                current_founded = true;
+                None
+            } else {
+                let entry = BalanceHistoryEntry {
+                    script_hash,
+                    block_height: height,
+                    delta,
+                    balance,
+                };
+                if balance > 0 {
+                    current_founded = true;
+                    Some(entry)
+                } else {
+                    pending_zero_entry = Some(entry);
+                    None
+                }
+            };
+
+            if let Some(entry) = emit {
+                snapshot.push(entry);
+
+                if snapshot.len() >= batch_size {
+                    // Flush snapshot batch
+                    cb.on_balance_history_entries(&snapshot, entries_processed)?;
+                    snapshot.clear();
+                    entries_processed = 0;
+                }
+            }
+        }
+
+        // Flush remaining snapshot entries
+        if !snapshot.is_empty() {
+            cb.on_balance_history_entries(&snapshot, entries_processed)?;
+        }
+
+        info!(
+            "Balance history delta shard {:0x} processed complete",
+            shard_index
+        );
+
+        Ok(())
+    }
+
     fn generate_utxo_snapshot_sharded(
         &self,
         shard_index: u8,
@@ -2749,6 +4847,37 @@ impl BalanceHistoryDB {
         Ok(())
     }
 
+    pub fn generate_balance_history_delta_snapshot_parallel(
+        &self,
+        base_block_height: u32,
+        target_block_height: u32,
+        cb: SnapshotCallbackRef,
+    ) -> Result<(), String> {
+        use rayon::prelude::*;
+
+        const SHARD_COUNT: u8 = 255;
+        const BATCH_SIZE: usize = 1024 * 64;
+
+        (0u8..=SHARD_COUNT)
+            .into_par_iter()
+            .try_for_each(|shard_index| {
+                self.generate_balance_history_delta_snapshot_sharded(
+                    base_block_height,
+                    target_block_height,
+                    shard_index,
+                    BATCH_SIZE,
+                    cb.clone(),
+                )
+            })?;
+
+        info!(
+            "Balance history delta snapshot generation complete: base_block_height={}, target_block_height={}",
+            base_block_height, target_block_height
+        );
+
+        Ok(())
+    }
+
     pub fn generate_utxo_snapshot_parallel(&self, cb: SnapshotCallbackRef) -> Result<(), String> {
         use rayon::prelude::*;
 
@@ -2766,6 +4895,170 @@ impl BalanceHistoryDB {
         Ok(())
     }
 
+    // UTXOs unspent at `target_block_height` with txid first byte `shard_index`: history rows
+    // live at that height plus live UTXOs that have no history row.
+    fn generate_historical_utxo_snapshot_sharded(
+        &self,
+        target_block_height: u32,
+        shard_index: u8,
+        batch_size: usize,
+        cb: SnapshotCallbackRef,
+    ) -> Result<(), String> {
+        let history_cf = self.db.cf_handle(UTXO_HISTORY_CF).ok_or_else(|| {
+            let msg = format!("Column family {} not found", UTXO_HISTORY_CF);
+            error!("{}", msg);
+            msg
+        })?;
+        let utxo_cf = self.db.cf_handle(UTXO_CF).ok_or_else(|| {
+            let msg = format!("Column family {} not found", UTXO_CF);
+            error!("{}", msg);
+            msg
+        })?;
+
+        let snapshot = self.db.snapshot();
+        let mut entries = Vec::with_capacity(batch_size);
+        let mut entries_processed = 0u64;
+
+        let seek_key = [shard_index];
+        for item in snapshot.iterator_cf(
+            history_cf,
+            IteratorMode::From(&seek_key, Direction::Forward),
+        ) {
+            let (key, value) = item.map_err(|e| {
+                let msg = format!("Iterator error when reading UTXO history: {}", e);
+                error!("{}", msg);
+                msg
+            })?;
+            if key[0] != shard_index {
+                break;
+            }
+            if key.len() != UTXO_KEY_LEN {
+                continue;
+            }
+
+            entries_processed += 1;
+            let outpoint = usdb_util::OutPointCodec::decode(&key)?;
+            let entry = Self::parse_utxo_history_value(&outpoint, &value)?;
+            if entry.is_live_at(target_block_height) {
+                entries.push(UTXOEntry {
+                    outpoint,
+                    script_hash: entry.script_hash,
+                    value: entry.value,
+                });
+            }
+
+            if entries.len() >= batch_size {
+                cb.on_utxo_entries(&entries, entries_processed)?;
+                entries.clear();
+                entries_processed = 0;
+            }
+        }
+
+        // Flush the history pass first so the second pass starts from an empty batch.
+        if !entries.is_empty() {
+            cb.on_utxo_entries(&entries, entries_processed)?;
+            entries.clear();
+            entries_processed = 0;
+        }
+
+        let mut live = Vec::with_capacity(batch_size);
+        for item in snapshot.iterator_cf(utxo_cf, IteratorMode::From(&seek_key, Direction::Forward))
+        {
+            let (key, value) = item.map_err(|e| {
+                let msg = format!("Iterator error when reading UTXOs: {}", e);
+                error!("{}", msg);
+                msg
+            })?;
+            if key[0] != shard_index {
+                break;
+            }
+            if key.len() != UTXO_KEY_LEN {
+                continue;
+            }
+
+            entries_processed += 1;
+            live.push((key, value));
+            if live.len() >= batch_size {
+                let entries = Self::filter_utxos_without_history(&snapshot, history_cf, &mut live)?;
+                cb.on_utxo_entries(&entries, entries_processed)?;
+                entries_processed = 0;
+            }
+        }
+        if !live.is_empty() {
+            let entries = Self::filter_utxos_without_history(&snapshot, history_cf, &mut live)?;
+            cb.on_utxo_entries(&entries, entries_processed)?;
+        }
+
+        info!(
+            "Historical UTXO shard {:0x} at height {} processed complete",
+            shard_index, target_block_height
+        );
+
+        Ok(())
+    }
+
+    // Drain raw UTXO_CF rows, keeping those the history index has no row for.
+    fn filter_utxos_without_history(
+        snapshot: &rocksdb::Snapshot<'_>,
+        history_cf: &rocksdb::ColumnFamily,
+        live: &mut Vec<(Box<[u8]>, Box<[u8]>)>,
+    ) -> Result<Vec<UTXOEntry>, String> {
+        let histories =
+            snapshot.multi_get_cf(live.iter().map(|(key, _)| (history_cf, key.as_ref())));
+
+        let mut entries = Vec::new();
+        for ((key, value), history) in live.drain(..).zip(histories) {
+            let history = history.map_err(|e| {
+                let msg = format!("Failed to get UTXO history in bulk: {}", e);
+                error!("{}", msg);
+                msg
+            })?;
+            if history.is_some() {
+                continue;
+            }
+
+            let utxo_value = Self::parse_utxo_from_value(&value);
+            entries.push(UTXOEntry {
+                outpoint: usdb_util::OutPointCodec::decode(&key)?,
+                script_hash: utxo_value.script_hash,
+                value: utxo_value.value,
+            });
+        }
+
+        Ok(entries)
+    }
+
+    /// Export the UTXO set as it was once `target_block_height` was applied, rebuilt from the
+    /// UTXO history index. The caller must ensure the index covers `target_block_height`.
+    pub fn generate_historical_utxo_snapshot_parallel(
+        &self,
+        target_block_height: u32,
+        cb: SnapshotCallbackRef,
+    ) -> Result<(), String> {
+        use rayon::prelude::*;
+
+        const SHARD_COUNT: u8 = 255;
+        const BATCH_SIZE: usize = 1024 * 64;
+
+        (0u8..=SHARD_COUNT)
+            .into_par_iter()
+            .try_for_each(|shard_index| {
+                self.generate_historical_utxo_snapshot_sharded(
+                    target_block_height,
+                    shard_index,
+                    BATCH_SIZE,
+                    cb.clone(),
+                )
+            })?;
+
+        info!(
+            "Historical UTXO snapshot generation complete: target_block_height={}",
+            target_block_height
+        );
+
+        Ok(())
+    }
+
     fn generate_script_registry_snapshot_sharded(
         &self,
         shard_index: u8,
@@ -2844,11 +5137,34 @@ impl BalanceHistoryDB {
 
         info!("Script registry snapshot generation complete");
 
-        Ok(())
+        Ok(())
+    }
+
+    pub fn generate_block_commit_snapshot(
+        &self,
+        target_block_height: u32,
+        cb: SnapshotCallbackRef,
+    ) -> Result<(), String> {
+        self.generate_block_commit_snapshot_from(0, target_block_height, cb)
+    }
+
+    /// Export block commits in (base_block_height, target_block_height] for a delta snapshot.
+    pub fn generate_block_commit_delta_snapshot(
+        &self,
+        base_block_height: u32,
+        target_block_height: u32,
+        cb: SnapshotCallbackRef,
+    ) -> Result<(), String> {
+        self.generate_block_commit_snapshot_from(
+            base_block_height.saturating_add(1),
+            target_block_height,
+            cb,
+        )
     }
 
-    pub fn generate_block_commit_snapshot(
+    fn generate_block_commit_snapshot_from(
         &self,
+        start_block_height: u32,
         target_block_height: u32,
         cb: SnapshotCallbackRef,
     ) -> Result<(), String> {
@@ -2860,7 +5176,10 @@ impl BalanceHistoryDB {
             msg
         })?;
 
-        let iter = self.db.iterator_cf(&cf, IteratorMode::Start);
+        let start_key = Self::make_block_commit_key(start_block_height);
+        let iter = self
+            .db
+            .iterator_cf(&cf, IteratorMode::From(&start_key, Direction::Forward));
         let mut snapshot = Vec::with_capacity(BATCH_SIZE);
         let mut entries_processed = 0u64;
 
@@ -2898,8 +5217,8 @@ impl BalanceHistoryDB {
         }
 
         info!(
-            "Block commit snapshot generation complete: target_block_height={}",
-            target_block_height
+            "Block commit snapshot generation complete: start_block_height={}, target_block_height={}",
+            start_block_height, target_block_height
         );
 
         Ok(())
@@ -3013,6 +5332,44 @@ impl BalanceHistoryDB {
         Ok(())
     }
 
+    /// Returns the latest entry of the first script hash at or below `start_script_hash` in
+    /// key order, i.e. the entry `traverse_latest(Some(start), ..)` would yield first.
+    pub fn find_latest_balance_entry(
+        &self,
+        start_script_hash: &USDBScriptHash,
+    ) -> Result<Option<BalanceHistoryEntry>, String> {
+        let cf = self.db.cf_handle(BALANCE_HISTORY_CF).ok_or_else(|| {
+            let msg = format!("Column family {} not found", BALANCE_HISTORY_CF);
+            error!("{}", msg);
+            msg
+        })?;
+
+        let seek_key = Self::make_balance_history_key(start_script_hash, u32::MAX);
+        let iter = self
+            .db
+            .full_iterator_cf(&cf, IteratorMode::From(&seek_key, Direction::Reverse));
+        for item in iter {
+            let (key, value) = item.map_err(|e| {
+                let msg = format!("Iterator error: {} {}", start_script_hash, e);
+                error!("{}", msg);
+                msg
+            })?;
+            if key.len() != BALANCE_HISTORY_KEY_LEN {
+                continue;
+            }
+
+            let (delta, balance) = Self::parse_balance_from_value(&value);
+            return Ok(Some(BalanceHistoryEntry {
+                script_hash: USDBScriptHash::from_slice(&key[0..USDBScriptHash::LEN]).unwrap(),
+                block_height: Self::parse_block_height_from_key(&key),
+                delta,
+                balance,
+            }));
+        }
+
+        Ok(None)
+    }
+
     pub fn traverse_at_height<F>(
         &self,
         start_script_hash: Option<USDBScriptHash>,
@@ -3312,6 +5669,8 @@ impl BalanceHistoryDB {
         let removed_blocks = self.clear_column_family(BLOCKS_CF)?;
         let removed_heights = self.clear_column_family(BLOCK_HEIGHTS_CF)?;
         let removed_commits = self.clear_column_family(BLOCK_COMMITS_CF)?;
+        self.clear_column_family(BALANCE_STATE_ROOTS_CF)?;
+        self.clear_column_family(UTXO_SET_HASH_STATES_CF)?;
 
         if self.get_last_block_file_index()?.is_some() {
             let msg = "Block index clear verification failed: last_block_file_index still exists"
@@ -3376,7 +5735,7 @@ mod tests {
     use bitcoincore_rpc::bitcoin::ScriptBuf;
     use bitcoincore_rpc::bitcoin::hashes::Hash;
     use std::sync::{Arc, Mutex};
-    use usdb_util::ToUSDBScriptHash;
+    use usdb_util::{ToUSDBScriptHash, compute_balance_state_root_from_siblings};
 
     #[test]
     fn test_make_and_parse_key() {
@@ -4138,6 +6497,224 @@ mod tests {
         assert_eq!(db.get_btc_block_height().unwrap(), 11);
     }
 
+    #[test]
+    fn test_address_utxo_index_tracks_blocks_rollback_and_backfill() {
+        let mut config = BalanceHistoryConfig::default();
+
+        let temp_dir = std::env::temp_dir().join("balance_history_address_utxo_index_test");
+        let _ = std::fs::remove_dir_all(&temp_dir);
+        std::fs::create_dir_all(&temp_dir).unwrap();
+        config.root_dir = temp_dir;
+        let config = std::sync::Arc::new(config);
+
+        let db = BalanceHistoryDB::open(config.clone(), BalanceHistoryDBMode::Normal).unwrap();
+
+        let owner = ScriptBuf::from(vec![4u8; 32]).to_usdb_script_hash();
+        let other = ScriptBuf::from(vec![5u8; 32]).to_usdb_script_hash();
+        let outpoint = |seed: u8, vout: u32| OutPoint {
+            txid: Txid::from_slice(&[seed; 32]).unwrap(),
+            vout,
+        };
+        db.put_utxo(&outpoint(1, 0), &owner, 100).unwrap();
+        db.put_utxo(&outpoint(2, 1), &owner, 200).unwrap();
+        db.put_utxo(&outpoint(3, 0), &other, 300).unwrap();
+        db.put_btc_block_height(11).unwrap();
+
+        let (height, utxos) = db.get_address_utxos(&owner, 0, 10).unwrap();
+        assert_eq!(height, 11);
+        assert_eq!(utxos, vec![(outpoint(1, 0), 100), (outpoint(2, 1), 200)]);
+        assert_eq!(
+            db.get_address_utxos(&owner, 1, 10).unwrap().1,
+            vec![(outpoint(2, 1), 200)]
+        );
+
+        let commit = BlockCommitEntry {
+            block_height: 12,
+            btc_block_hash: BlockHash::from_slice(&[6u8; 32]).unwrap(),
+            balance_delta_root: [7u8; 32],
+            block_commit: [8u8; 32],
+        };
+        let undo_bundle = BlockUndoBundle {
+            block_height: 12,
+            btc_block_hash: commit.btc_block_hash,
+            created_utxos: vec![BlockUndoUtxoEntry {
+                outpoint: outpoint(4, 0),
+                script_hash: other,
+                value: 100,
+            }],
+            spent_utxos: vec![BlockUndoUtxoEntry {
+                outpoint: outpoint(1, 0),
+                script_hash: owner,
+                value: 100,
+            }],
+            touched_script_hashes: vec![owner, other],
+        };
+        db.update_block_state_with_undo_async(
+            &[(
+                Arc::new(outpoint(4, 0)),
+                Arc::new(UTXOValue {
+                    script_hash: other,
+                    value: 100,
+                }),
+            )],
+            &[Arc::new(outpoint(1, 0))],
+            &[],
+            12,
+            &[commit],
+            &[undo_bundle],
+        )
+        .unwrap();
+
+        assert_eq!(
+            db.get_address_utxos(&owner, 0, 10).unwrap(),
+            (12, vec![(outpoint(2, 1), 200)])
+        );
+        assert_eq!(
+            db.get_address_utxos(&other, 0, 10).unwrap().1,
+            vec![(outpoint(3, 0), 300), (outpoint(4, 0), 100)]
+        );
+
+        db.rollback_one_block(12).unwrap();
+        assert_eq!(
+            db.get_address_utxos(&owner, 0, 10).unwrap(),
+            (11, vec![(outpoint(1, 0), 100), (outpoint(2, 1), 200)])
+        );
+        assert_eq!(
+            db.get_address_utxos(&other, 0, 10).unwrap().1,
+            vec![(outpoint(3, 0), 300)]
+        );
+
+        db.delete_utxos(&[outpoint(3, 0)]).unwrap();
+        assert!(db.get_address_utxos(&other, 0, 10).unwrap().1.is_empty());
+
+        // A DB without the ready marker is backfilled from the UTXO set.
+        db.clear_column_family(ADDRESS_UTXO_INDEX_CF).unwrap();
+        db.delete_meta_key(META_KEY_ADDRESS_UTXO_INDEX_READY)
+            .unwrap();
+        db.ensure_address_utxo_index().unwrap();
+        assert_eq!(
+            db.get_address_utxos(&owner, 0, 10).unwrap().1,
+            vec![(outpoint(1, 0), 100), (outpoint(2, 1), 200)]
+        );
+    }
+
+    #[test]
+    fn test_balance_state_root_tracks_blocks_and_rollback() {
+        let mut config = BalanceHistoryConfig::default();
+        config.btc.network = Network::Regtest;
+
+        let temp_dir = std::env::temp_dir().join("balance_history_balance_state_root_test");
+        let _ = std::fs::remove_dir_all(&temp_dir);
+        std::fs::create_dir_all(&temp_dir).unwrap();
+        config.root_dir = temp_dir;
+        let config = std::sync::Arc::new(config);
+
+        let db = BalanceHistoryDB::open(config.clone(), BalanceHistoryDBMode::Normal).unwrap();
+        assert!(db.is_balance_state_tree_enabled());
+
+        // script_a and script_b share one bucket, script_c lives in another one.
+        let script_a = USDBScriptHash::from_byte_array([1u8; 32]);
+        let mut bytes_b = [1u8; 32];
+        bytes_b[31] = 2;
+        let script_b = USDBScriptHash::from_byte_array(bytes_b);
+        let script_c = USDBScriptHash::from_byte_array([9u8; 32]);
+
+        let write_block = |block_height: u32, entries: &[BalanceHistoryEntry]| {
+            let commit = BlockCommitEntry {
+                block_height,
+                btc_block_hash: BlockHash::from_slice(&[block_height as u8; 32]).unwrap(),
+                balance_delta_root: [block_height as u8; 32],
+                block_commit: [block_height as u8; 32],
+            };
+            let undo_bundle = BlockUndoBundle {
+                block_height,
+                btc_block_hash: commit.btc_block_hash,
+                created_utxos: Vec::new(),
+                spent_utxos: Vec::new(),
+                touched_script_hashes: entries.iter().map(|entry| entry.script_hash).collect(),
+            };
+            db.update_block_state_with_undo_async(
+                &[],
+                &[],
+                entries,
+                block_height,
+                &[commit],
+                &[undo_bundle],
+            )
+            .unwrap();
+        };
+
+        write_block(
+            1,
+            &[
+                BalanceHistoryEntry {
+                    script_hash: script_a,
+                    block_height: 1,
+                    delta: 10,
+                    balance: 10,
+                },
+                BalanceHistoryEntry {
+                    script_hash: script_b,
+                    block_height: 1,
+                    delta: 20,
+                    balance: 20,
+                },
+            ],
+        );
+        let root_1 = compute_balance_state_subtree_root(0, &[(script_a, 10), (script_b, 20)]);
+        assert_eq!(db.get_balance_state_root(1).unwrap(), Some(root_1));
+
+        write_block(
+            2,
+            &[
+                BalanceHistoryEntry {
+                    script_hash: script_a,
+                    block_height: 2,
+                    delta: -10,
+                    balance: 0,
+                },
+                BalanceHistoryEntry {
+                    script_hash: script_c,
+                    block_height: 2,
+                    delta: 5,
+                    balance: 5,
+                },
+            ],
+        );
+        let root_2 = compute_balance_state_subtree_root(0, &[(script_b, 20), (script_c, 5)]);
+        assert_eq!(db.get_balance_state_root(2).unwrap(), Some(root_2));
+
+        // A drained script proves an empty leaf under the new root.
+        let (balance, siblings) = db.get_balance_state_path(&script_a).unwrap();
+        assert_eq!(balance, 0);
+        assert_eq!(
+            compute_balance_state_root_from_siblings(&script_a, balance, &siblings),
+            Some(root_2)
+        );
+        let (balance, siblings) = db.get_balance_state_path(&script_b).unwrap();
+        assert_eq!(balance, 20);
+        assert_eq!(
+            compute_balance_state_root_from_siblings(&script_b, balance, &siblings),
+            Some(root_2)
+        );
+
+        db.rollback_one_block(2).unwrap();
+        assert!(db.get_balance_state_root(2).unwrap().is_none());
+        assert_eq!(db.get_balance_state_root(1).unwrap(), Some(root_1));
+        let (balance, siblings) = db.get_balance_state_path(&script_a).unwrap();
+        assert_eq!(balance, 10);
+        assert_eq!(
+            compute_balance_state_root_from_siblings(&script_a, balance, &siblings),
+            Some(root_1)
+        );
+        let (balance, siblings) = db.get_balance_state_path(&script_c).unwrap();
+        assert_eq!(balance, 0);
+        assert_eq!(
+            compute_balance_state_root_from_siblings(&script_c, balance, &siblings),
+            Some(root_1)
+        );
+    }
+
     #[test]
     fn test_rollback_to_block_height_reverts_multiple_blocks_and_clears_meta_state() {
         let mut config = BalanceHistoryConfig::default();
@@ -4434,6 +7011,246 @@ mod tests {
         assert_eq!(db.get_undo_retained_from_height().unwrap(), Some(12));
     }
 
+    #[test]
+    fn test_prune_balance_history_keeps_floor_anchors() {
+        let mut config = BalanceHistoryConfig::default();
+
+        let temp_dir = std::env::temp_dir().join("balance_history_prune_history_test");
+        let _ = std::fs::remove_dir_all(&temp_dir);
+        std::fs::create_dir_all(&temp_dir).unwrap();
+        config.root_dir = temp_dir;
+        let config = std::sync::Arc::new(config);
+
+        let db = BalanceHistoryDB::open(config.clone(), BalanceHistoryDBMode::Normal).unwrap();
+
+        let active = ScriptBuf::from(vec![1u8; 32]).to_usdb_script_hash();
+        let emptied = ScriptBuf::from(vec![2u8; 32]).to_usdb_script_hash();
+        let entries = vec![
+            BalanceHistoryEntry {
+                script_hash: active,
+                block_height: 10,
+                delta: 100,
+                balance: 100,
+            },
+            BalanceHistoryEntry {
+                script_hash: active,
+                block_height: 12,
+                delta: 50,
+                balance: 150,
+            },
+            BalanceHistoryEntry {
+                script_hash: active,
+                block_height: 20,
+                delta: -30,
+                balance: 120,
+            },
+            BalanceHistoryEntry {
+                script_hash: emptied,
+                block_height: 11,
+                delta: 70,
+                balance: 70,
+            },
+            BalanceHistoryEntry {
+                script_hash: emptied,
+                block_height: 13,
+                delta: -70,
+                balance: 0,
+            },
+        ];
+        db.put_address_history_async(&entries).unwrap();
+        db.put_btc_block_height(20).unwrap();
+
+        let pruned = db
+            .prune_balance_history_before_height(15, &|| false)
+            .unwrap();
+        assert_eq!(pruned, 3);
+        assert_eq!(
+            db.get_balance_history_retained_from_height().unwrap(),
+            Some(15)
+        );
+        assert_eq!(db.get_balance_history_pruned_floor(14).unwrap(), Some(15));
+        assert_eq!(db.get_balance_history_pruned_floor(15).unwrap(), None);
+
+        // The latest row below the floor still answers lookups at and above it.
+        let at_floor = db.get_balance_at_block_height(&active, 15).unwrap();
+        assert_eq!(at_floor.block_height, 12);
+        assert_eq!(at_floor.balance, 150);
+        assert_eq!(
+            db.get_balance_at_block_height(&active, 20).unwrap().balance,
+            120
+        );
+        assert_eq!(
+            db.get_balance_at_block_height(&active, 10).unwrap().balance,
+            0
+        );
+
+        // Zero-balance anchors carry no information and are dropped entirely.
+        assert_eq!(
+            db.get_balance_at_block_height(&emptied, 15)
+                .unwrap()
+                .balance,
+            0
+        );
+        assert_eq!(
+            db.get_balance_at_block_height(&emptied, 11)
+                .unwrap()
+                .balance,
+            0
+        );
+
+        // Pruning again at the same floor is a no-op; the floor never moves backwards.
+        assert_eq!(
+            db.prune_balance_history_before_height(15, &|| false)
+                .unwrap(),
+            0
+        );
+        assert!(
+            db.prune_balance_history_before_height(14, &|| false)
+                .is_err()
+        );
+        assert!(
+            db.prune_balance_history_before_height(21, &|| false)
+                .is_err()
+        );
+
+        let error = db.rollback_to_block_height(13).unwrap_err();
+        assert!(error.contains("15"));
+    }
+
+    #[test]
+    fn test_find_latest_balance_entry_returns_latest_row_at_or_below_seek() {
+        let mut config = BalanceHistoryConfig::default();
+
+        let temp_dir = std::env::temp_dir().join("balance_history_find_latest_entry_test");
+        let _ = std::fs::remove_dir_all(&temp_dir);
+        std::fs::create_dir_all(&temp_dir).unwrap();
+        config.root_dir = temp_dir;
+        let config = std::sync::Arc::new(config);
+
+        let db = BalanceHistoryDB::open(config.clone(), BalanceHistoryDBMode::Normal).unwrap();
+        let low = USDBScriptHash::from_byte_array([0x10; 32]);
+        let high = USDBScriptHash::from_byte_array([0x80; 32]);
+        assert!(db.find_latest_balance_entry(&high).unwrap().is_none());
+
+        let entries = vec![
+            BalanceHistoryEntry {
+                script_hash: low,
+                block_height: 5,
+                delta: 10,
+                balance: 10,
+            },
+            BalanceHistoryEntry {
+                script_hash: low,
+                block_height: 9,
+                delta: 5,
+                balance: 15,
+            },
+            BalanceHistoryEntry {
+                script_hash: high,
+                block_height: 7,
+                delta: 20,
+                balance: 20,
+            },
+        ];
+        db.put_address_history_async(&entries).unwrap();
+
+        let find = |seek: u8| {
+            db.find_latest_balance_entry(&USDBScriptHash::from_byte_array([seek; 32]))
+                .unwrap()
+                .map(|entry| (entry.script_hash, entry.block_height, entry.balance))
+        };
+        assert_eq!(find(0x10), Some((low, 9, 15)));
+        assert_eq!(find(0x50), Some((low, 9, 15)));
+        assert_eq!(find(0x80), Some((high, 7, 20)));
+        assert_eq!(find(0xFF), Some((high, 7, 20)));
+        assert_eq!(find(0x05), None);
+    }
+
+    #[test]
+    fn test_prune_balance_history_resumes_from_cursor() {
+        let mut config = BalanceHistoryConfig::default();
+
+        let temp_dir = std::env::temp_dir().join("balance_history_prune_cursor_test");
+        let _ = std::fs::remove_dir_all(&temp_dir);
+        std::fs::create_dir_all(&temp_dir).unwrap();
+        config.root_dir = temp_dir;
+        let config = std::sync::Arc::new(config);
+
+        let db = BalanceHistoryDB::open(config.clone(), BalanceHistoryDBMode::Normal).unwrap();
+
+        let mut scripts = [
+            ScriptBuf::from(vec![1u8; 32]).to_usdb_script_hash(),
+            ScriptBuf::from(vec![2u8; 32]).to_usdb_script_hash(),
+        ];
+        scripts.sort();
+        let [first, second] = scripts;
+        let mut entries = Vec::new();
+        for script_hash in scripts {
+            entries.push(BalanceHistoryEntry {
+                script_hash,
+                block_height: 10,
+                delta: 100,
+                balance: 100,
+            });
+            entries.push(BalanceHistoryEntry {
+                script_hash,
+                block_height: 12,
+                delta: 50,
+                balance: 150,
+            });
+        }
+        db.put_address_history_async(&entries).unwrap();
+        db.put_btc_block_height(20).unwrap();
+
+        // Simulate a pass interrupted after the first script.
+        db.put_json_meta(
+            META_KEY_BALANCE_HISTORY_PRUNE_CURSOR,
+            &BalanceHistoryPruneCursor {
+                retained_from_height: 15,
+                next_script_hash: second.to_string(),
+            },
+        )
+        .unwrap();
+
+        assert_eq!(
+            db.prune_balance_history_before_height(15, &|| false)
+                .unwrap(),
+            1
+        );
+        assert_eq!(
+            db.get_balance_at_block_height(&first, 10).unwrap().balance,
+            100
+        );
+        assert_eq!(
+            db.get_balance_at_block_height(&second, 10).unwrap().balance,
+            0
+        );
+        assert_eq!(
+            db.get_balance_at_block_height(&second, 15).unwrap().balance,
+            150
+        );
+        assert!(
+            db.get_json_meta::<BalanceHistoryPruneCursor>(META_KEY_BALANCE_HISTORY_PRUNE_CURSOR)
+                .unwrap()
+                .is_none()
+        );
+
+        // The next pass starts from the beginning again and catches the skipped script.
+        assert_eq!(
+            db.prune_balance_history_before_height(15, &|| false)
+                .unwrap(),
+            1
+        );
+        assert_eq!(
+            db.get_balance_at_block_height(&first, 10).unwrap().balance,
+            0
+        );
+        assert_eq!(
+            db.get_balance_at_block_height(&first, 15).unwrap().balance,
+            150
+        );
+    }
+
     #[test]
     fn test_resume_rollback_if_needed_continues_from_meta_state() {
         let mut config = BalanceHistoryConfig::default();
@@ -6,7 +6,9 @@ CREATE TABLE IF NOT EXISTS meta (
     block_commit_count INTEGER NOT NULL,
     script_registry_count INTEGER NOT NULL DEFAULT 0,
     generated_at    INTEGER NOT NULL,
-    version         INTEGER NOT NULL DEFAULT 1
+    version         INTEGER NOT NULL DEFAULT 1,
+    base_block_height INTEGER,                    -- set only for delta snapshots
+    utxo_removal_count INTEGER NOT NULL DEFAULT 0
 );
 
 CREATE TABLE IF NOT EXISTS balance_history (
@@ -33,3 +35,8 @@ CREATE TABLE IF NOT EXISTS script_registry (
     script_hash     BLOB    NOT NULL PRIMARY KEY, -- 32 bytes
     script_pubkey   BLOB    NOT NULL              -- raw BTC locking script
 );
+
+-- Delta snapshots only: outpoints spent between the base height and the target height.
+CREATE TABLE IF NOT EXISTS utxo_removals (
+    outpoint       BLOB    NOT NULL PRIMARY KEY   -- 36 bytes
+);
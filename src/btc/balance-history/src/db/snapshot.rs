@@ -7,7 +7,7 @@ use std::time::{SystemTime, UNIX_EPOCH};
 use usdb_util::{OutPointCodec, USDBScriptHash, UTXOEntry};
 
 // The version of the snapshot database schema.
-pub const SNAPSHOT_DB_VERSION: u32 = 2;
+pub const SNAPSHOT_DB_VERSION: u32 = 3;
 
 #[derive(Debug, Clone)]
 pub struct SnapshotMeta {
@@ -18,6 +18,10 @@ pub struct SnapshotMeta {
     pub script_registry_count: u64,
     pub generated_at: u64, // UNIX timestamp
     pub version: u32,
+    // Height of the base snapshot a delta snapshot applies on top of, None for full snapshots.
+    pub base_block_height: Option<u32>,
+    // Number of outpoints in the utxo_removals table (delta snapshots only).
+    pub utxo_removal_count: u64,
 }
 
 impl SnapshotMeta {
@@ -35,8 +39,20 @@ impl SnapshotMeta {
             script_registry_count: 0,
             generated_at: since_the_epoch.as_secs(),
             version: SNAPSHOT_DB_VERSION,
+            base_block_height: None,
+            utxo_removal_count: 0,
         }
     }
+
+    pub fn new_delta(base_block_height: u32, block_height: u32) -> Self {
+        let mut meta = Self::new(block_height);
+        meta.base_block_height = Some(base_block_height);
+        meta
+    }
+
+    pub fn is_delta(&self) -> bool {
+        self.base_block_height.is_some()
+    }
 }
 
 pub struct SnapshotHash;
@@ -72,6 +88,50 @@ impl SnapshotHash {
         let hash_result = hasher.finalize();
         Ok(format!("{:x}", hash_result))
     }
+
+    /// SHA256 of every `chunk_size` slice of the file, the last one possibly shorter.
+    pub fn calc_chunk_hashes(path: &Path, chunk_size: u64) -> Result<Vec<String>, String> {
+        use sha2::{Digest, Sha256};
+        use std::fs::File;
+        use std::io::{BufReader, Read};
+
+        let file = File::open(path).map_err(|e| {
+            let msg = format!("Failed to open snapshot file for chunk hashing: {}", e);
+            error!("{}", msg);
+            msg
+        })?;
+        let mut reader = BufReader::new(file);
+        let mut hashes = Vec::new();
+        let mut buffer = [0; 1024 * 64];
+
+        loop {
+            let mut hasher = Sha256::new();
+            let mut chunk_len = 0u64;
+            while chunk_len < chunk_size {
+                let limit = (chunk_size - chunk_len).min(buffer.len() as u64) as usize;
+                let n = reader.read(&mut buffer[..limit]).map_err(|e| {
+                    let msg = format!("Failed to read snapshot file for chunk hashing: {}", e);
+                    error!("{}", msg);
+                    msg
+                })?;
+                if n == 0 {
+                    break;
+                }
+                hasher.update(&buffer[..n]);
+                chunk_len += n as u64;
+            }
+
+            if chunk_len == 0 {
+                break;
+            }
+            hashes.push(format!("{:x}", hasher.finalize()));
+            if chunk_len < chunk_size {
+                break;
+            }
+        }
+
+        Ok(hashes)
+    }
 }
 
 /// Snapshot Manager
@@ -134,6 +194,33 @@ impl SnapshotDB {
             })?;
         }
 
+        if !Self::table_has_column(conn, "meta", "base_block_height")? {
+            conn.execute("ALTER TABLE meta ADD COLUMN base_block_height INTEGER", [])
+                .map_err(|e| {
+                    let msg = format!(
+                        "Failed to add base_block_height column to snapshot meta: {}",
+                        e
+                    );
+                    error!("{}", msg);
+                    msg
+                })?;
+        }
+
+        if !Self::table_has_column(conn, "meta", "utxo_removal_count")? {
+            conn.execute(
+                "ALTER TABLE meta ADD COLUMN utxo_removal_count INTEGER NOT NULL DEFAULT 0",
+                [],
+            )
+            .map_err(|e| {
+                let msg = format!(
+                    "Failed to add utxo_removal_count column to snapshot meta: {}",
+                    e
+                );
+                error!("{}", msg);
+                msg
+            })?;
+        }
+
         Ok(())
     }
 
@@ -173,6 +260,28 @@ impl SnapshotDB {
         root_dir: &Path,
         block_height: u32,
         create_new: bool,
+    ) -> Result<Self, String> {
+        Self::open_in_snapshot_dir(root_dir, &format!("snapshot_{}", block_height), create_new)
+    }
+
+    /// Create or open the delta snapshot covering (base_block_height, block_height].
+    pub fn open_delta_by_heights(
+        root_dir: &Path,
+        base_block_height: u32,
+        block_height: u32,
+        create_new: bool,
+    ) -> Result<Self, String> {
+        Self::open_in_snapshot_dir(
+            root_dir,
+            &format!("snapshot_delta_{}_{}", base_block_height, block_height),
+            create_new,
+        )
+    }
+
+    fn open_in_snapshot_dir(
+        root_dir: &Path,
+        file_stem: &str,
+        create_new: bool,
     ) -> Result<Self, String> {
         let snapshot_dir = root_dir.join("snapshots");
         std::fs::create_dir_all(&snapshot_dir).map_err(|e| {
@@ -184,7 +293,7 @@ impl SnapshotDB {
             msg
         })?;
 
-        let db_path = snapshot_dir.join(format!("snapshot_{}.db", block_height));
+        let db_path = snapshot_dir.join(format!("{}.db", file_stem));
         if create_new {
             if db_path.exists() {
                 let msg = format!("Snapshot database {:?} already exists", db_path);
@@ -192,8 +301,8 @@ impl SnapshotDB {
 
                 // For safety, rename existing snapshot to old file
                 let old_db_path = snapshot_dir.join(format!(
-                    "snapshot_{}_{}.db",
-                    block_height,
+                    "{}_{}.db",
+                    file_stem,
                     SystemTime::now()
                         .duration_since(UNIX_EPOCH)
                         .unwrap()
@@ -276,7 +385,7 @@ impl SnapshotDB {
     pub fn update_meta(&self, meta: &SnapshotMeta) -> Result<(), String> {
         self.conn
             .execute(
-                "INSERT INTO meta (block_height, balance_history_count, utxo_count, block_commit_count, script_registry_count, generated_at, version) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                "INSERT INTO meta (block_height, balance_history_count, utxo_count, block_commit_count, script_registry_count, generated_at, version, base_block_height, utxo_removal_count) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
                 (
                     meta.block_height as i64,
                     meta.balance_history_count as i64,
@@ -285,6 +394,8 @@ impl SnapshotDB {
                     meta.script_registry_count as i64,
                     meta.generated_at as i64,
                     meta.version as i64,
+                    meta.base_block_height.map(|height| height as i64),
+                    meta.utxo_removal_count as i64,
                 ),
             )
             .map_err(|e| {
@@ -300,7 +411,7 @@ impl SnapshotDB {
         let mut stmt = self
             .conn
             .prepare(
-                "SELECT block_height, balance_history_count, utxo_count, block_commit_count, script_registry_count, generated_at, version FROM meta ORDER BY generated_at DESC LIMIT 1",
+                "SELECT block_height, balance_history_count, utxo_count, block_commit_count, script_registry_count, generated_at, version, base_block_height, utxo_removal_count FROM meta ORDER BY generated_at DESC LIMIT 1",
             )
             .map_err(|e| {
                 let msg = format!("Failed to prepare statement: {}", e);
@@ -318,6 +429,8 @@ impl SnapshotDB {
                     script_registry_count: row.get::<_, i64>(4).map(|v| v as u64)?,
                     generated_at: row.get::<_, i64>(5).map(|v| v as u64)?,
                     version: row.get::<_, i64>(6).map(|v| v as u32)?,
+                    base_block_height: row.get::<_, Option<i64>>(7).map(|v| v.map(|v| v as u32))?,
+                    utxo_removal_count: row.get::<_, i64>(8).map(|v| v as u64)?,
                 })
             })
             .map_err(|e| {
@@ -411,6 +524,41 @@ impl SnapshotDB {
         Ok(())
     }
 
+    pub fn put_utxo_removals(&mut self, outpoints: &[OutPoint]) -> Result<(), String> {
+        let tx = self.conn.transaction().map_err(|e| {
+            let msg = format!("Failed to start transaction: {}", e);
+            error!("{}", msg);
+            msg
+        })?;
+
+        {
+            let mut stmt = tx
+                .prepare("INSERT INTO utxo_removals (outpoint) VALUES (?1)")
+                .map_err(|e| {
+                    let msg = format!("Failed to prepare statement: {}", e);
+                    error!("{}", msg);
+                    msg
+                })?;
+
+            for outpoint in outpoints {
+                stmt.execute([OutPointCodec::encode(outpoint)])
+                    .map_err(|e| {
+                        let msg = format!("Failed to insert UTXO removal entry: {}", e);
+                        error!("{}", msg);
+                        msg
+                    })?;
+            }
+        }
+
+        tx.commit().map_err(|e| {
+            let msg = format!("Failed to commit transaction: {}", e);
+            error!("{}", msg);
+            msg
+        })?;
+
+        Ok(())
+    }
+
     pub fn put_block_commit_entries(&mut self, entries: &[BlockCommitEntry]) -> Result<(), String> {
         let tx = self.conn.transaction().map_err(|e| {
             let msg = format!("Failed to start transaction: {}", e);
@@ -1001,6 +1149,89 @@ impl SnapshotDB {
         Ok(entries)
     }
 
+    pub fn get_utxo_removals(
+        &self,
+        page_size: u32,
+        last_outpoint: Option<&OutPoint>,
+    ) -> Result<Vec<OutPoint>, String> {
+        let sql = match last_outpoint {
+            Some(_) => {
+                "
+                SELECT outpoint
+                FROM utxo_removals
+                WHERE outpoint > ?1
+                ORDER BY outpoint ASC
+                LIMIT ?2
+                "
+            }
+            None => {
+                "
+                SELECT outpoint
+                FROM utxo_removals
+                ORDER BY outpoint ASC
+                LIMIT ?1
+                "
+            }
+        };
+
+        let params = match last_outpoint {
+            Some(last_outpoint) => {
+                rusqlite::params![OutPointCodec::encode(last_outpoint), page_size as i64,]
+            }
+            None => rusqlite::params![page_size as i64],
+        };
+
+        let mut stmt = self.conn.prepare(sql).map_err(|e| {
+            let msg = format!("Failed to prepare statement: {}", e);
+            error!("{}", msg);
+            msg
+        })?;
+        let mut entries_iter = stmt.query(params).map_err(|e| {
+            let msg = format!("Failed to query map: {}", e);
+            error!("{}", msg);
+            msg
+        })?;
+
+        let mut outpoints = Vec::with_capacity(page_size as usize);
+        while let Some(row) = entries_iter.next().map_err(|e| {
+            let msg = format!("Failed to get next row: {}", e);
+            error!("{}", msg);
+            msg
+        })? {
+            let blob: Vec<u8> = row.get(0).map_err(|e| {
+                let msg = format!("Failed to get outpoint blob: {}", e);
+                error!("{}", msg);
+                msg
+            })?;
+
+            outpoints.push(OutPointCodec::decode(&blob).map_err(|e| {
+                let msg = format!("Failed to convert outpoint blob: {}", e);
+                error!("{}", msg);
+                msg
+            })?);
+        }
+
+        Ok(outpoints)
+    }
+
+    /// Returns true when the snapshot already carries a script registry row for script_hash.
+    pub fn has_script_registry_entry(&self, script_hash: &USDBScriptHash) -> Result<bool, String> {
+        let mut stmt = self
+            .conn
+            .prepare_cached("SELECT 1 FROM script_registry WHERE script_hash = ?1")
+            .map_err(|e| {
+                let msg = format!("Failed to prepare statement: {}", e);
+                error!("{}", msg);
+                msg
+            })?;
+
+        stmt.exists([script_hash.as_ref() as &[u8]]).map_err(|e| {
+            let msg = format!("Failed to query script registry entry: {}", e);
+            error!("{}", msg);
+            msg
+        })
+    }
+
     pub fn get_script_registry_entries(
         &self,
         page_size: u32,
@@ -2,18 +2,24 @@ use crate::bench::{BatchBlockBenchMark, BatchBlockBenchMarkRef};
 use crate::btc::BTCClientRef;
 use crate::cache::{AddressBalanceCacheRef, UTXOCacheRef};
 use crate::db::{
-    BalanceHistoryDBRef, BalanceHistoryEntry, BlockCommitEntry, BlockStateUpdateBatch,
-    BlockUndoBundle, BlockUndoUtxoEntry, ScriptRegistryEntry,
+    AddressTxIndexEntry, AddressTxIndexUpdate, BalanceHistoryDBRef, BalanceHistoryEntry,
+    BlockCommitEntry, BlockStateUpdateBatch, BlockUndoBundle, BlockUndoUtxoEntry,
+    ScriptRegistryEntry, UtxoHistoryEntry, UtxoHistoryUpdate, UtxoSetHashUpdate,
 };
 use bitcoincore_rpc::bitcoin::hashes::Hash;
-use bitcoincore_rpc::bitcoin::{Block, BlockHash, OutPoint, Txid};
+use bitcoincore_rpc::bitcoin::{Block, BlockHash, Network, OutPoint, Txid};
 use dashmap::DashMap;
 use rayon::slice::ParallelSliceMut;
 use sha2::{Digest, Sha256};
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::sync::{Arc, Mutex, RwLock};
+use usdb_util::{
+    BalanceDeltaLeaf, BalanceDeltaRootRule, MuHash3072, ToUSDBScriptHash, USDBScriptHash,
+    compute_balance_delta_merkle_root, compute_balance_delta_root_v2,
+    compute_balance_history_block_commit, hash_balance_delta_leaf, hash_utxo_for_muhash,
+    is_muhash_tracked_script,
+};
 use usdb_util::{BalanceHistoryData, OutPointRef, UTXOEntryRef, UTXOValue};
-use usdb_util::{ToUSDBScriptHash, USDBScriptHash};
 
 // EMPTY_COMMIT_HASH is the genesis previous-commit value used when the batch
 // starts from block height 0 and there is no earlier committed block.
@@ -29,6 +35,7 @@ struct VOutUtxoInfo {
     item: UTXOEntryRef,
     block_height: u32,
     spend: bool, // Whether this UTXO is spent in the batch
+    muhash_digest: Option<[u8; 32]>,
 }
 
 impl VOutUtxoInfo {
@@ -41,11 +48,16 @@ pub struct PreloadVIn {
     pub outpoint: OutPointRef,
     pub cache_tx_out: Option<UTXOEntryRef>,
     pub need_flush: bool,
+    // MuHash element digest of the spent coin, loaded only when the UTXO set hash is enabled.
+    pub muhash_digest: Option<[u8; 32]>,
 }
 
 pub struct PreloadVOut {
     pub outpoint: OutPointRef,
     pub cache_tx_out: UTXOEntryRef,
+    // MuHash element digest of the coin, None when disabled or when bitcoind does not
+    // keep the output in its UTXO set.
+    pub muhash_digest: Option<[u8; 32]>,
 }
 
 pub struct PreloadTx {
@@ -116,9 +128,17 @@ impl BatchBlockData {
     }
 }
 
-// compute_balance_delta_root hashes the canonical logical balance result of one block.
+// compute_balance_delta_root hashes the canonical logical balance result of one block
+// under the delta root rule active at its height.
 // The hash only covers balance-history state, not UTXO cache state.
-fn compute_balance_delta_root(block: &BlockBalanceDelta) -> [u8; 32] {
+fn compute_balance_delta_root(block: &BlockBalanceDelta, rule: BalanceDeltaRootRule) -> [u8; 32] {
+    match rule {
+        BalanceDeltaRootRule::FlatV1 => compute_flat_balance_delta_root(block),
+        BalanceDeltaRootRule::MerkleV2 => compute_merkle_balance_delta_root(block),
+    }
+}
+
+fn compute_flat_balance_delta_root(block: &BlockBalanceDelta) -> [u8; 32] {
     let mut hasher = Sha256::new();
     hasher.update(b"balance-history:block-delta-root:v1");
     hasher.update(block.block_height.to_be_bytes());
@@ -134,20 +154,27 @@ fn compute_balance_delta_root(block: &BlockBalanceDelta) -> [u8; 32] {
     hasher.finalize().into()
 }
 
-// compute_block_commit links one block's logical result to the previous committed block.
-fn compute_block_commit(
-    block_height: u32,
-    block_hash: &BlockHash,
-    balance_delta_root: &[u8; 32],
-    prev_block_commit: &[u8; 32],
-) -> [u8; 32] {
-    let mut hasher = Sha256::new();
-    hasher.update(b"balance-history:block-commit:v1");
-    hasher.update(block_height.to_be_bytes());
-    hasher.update(block_hash.as_ref() as &[u8]);
-    hasher.update(balance_delta_root);
-    hasher.update(prev_block_commit);
-    hasher.finalize().into()
+// The Merkle rule relies on block.entries being sorted by script_hash, which is the
+// same order the proof RPC rebuilds from BLOCK_BALANCE_INDEX_CF.
+fn compute_merkle_balance_delta_root(block: &BlockBalanceDelta) -> [u8; 32] {
+    let leaf_hashes: Vec<[u8; 32]> = block
+        .entries
+        .iter()
+        .map(|entry| {
+            hash_balance_delta_leaf(&BalanceDeltaLeaf {
+                script_hash: entry.script_hash,
+                delta: entry.delta,
+                balance: entry.balance,
+            })
+        })
+        .collect();
+    let merkle_root = compute_balance_delta_merkle_root(&leaf_hashes);
+    compute_balance_delta_root_v2(
+        block.block_height,
+        &block.block_hash,
+        leaf_hashes.len() as u32,
+        &merkle_root,
+    )
 }
 
 // build_block_commits computes a contiguous commit chain for a fully ordered batch.
@@ -155,11 +182,13 @@ fn compute_block_commit(
 fn build_block_commits(
     blocks: &[BlockBalanceDelta],
     mut prev_block_commit: [u8; 32],
+    network: Network,
 ) -> Vec<BlockCommitEntry> {
     let mut commits = Vec::with_capacity(blocks.len());
     for block in blocks {
-        let balance_delta_root = compute_balance_delta_root(block);
-        let block_commit = compute_block_commit(
+        let rule = BalanceDeltaRootRule::at_height(network, block.block_height);
+        let balance_delta_root = compute_balance_delta_root(block, rule);
+        let block_commit = compute_balance_history_block_commit(
             block.block_height,
             &block.block_hash,
             &balance_delta_root,
@@ -363,6 +392,8 @@ impl BatchBlockPreloader {
             txdata: Vec::with_capacity(block.txdata.len()),
         };
         let mut script_registry_entries = Vec::new();
+        // bitcoind never adds the genesis outputs to its UTXO set.
+        let track_utxo_set_hash = self.db.is_utxo_set_hash_enabled() && block_height > 0;
 
         // Load all vins' UTXOs into cache
         // Here we do not use rayon because we already used rayon to process blocks in higher level
@@ -385,11 +416,20 @@ impl BatchBlockPreloader {
                             outpoint: Arc::new(outpoint.clone()),
                             cache_tx_out: None,
                             need_flush: true,
+                            muhash_digest: None,
                         };
                         preload_tx.vin.push(preload_vin);
                     }
                 }
 
+                // The first copies of the two BIP30 duplicate coinbases were overwritten and
+                // are skipped by bitcoind's coinstats index as well.
+                let track_tx_outputs = track_utxo_set_hash
+                    && !(tx.is_coinbase()
+                        && self
+                            .utxo_cache
+                            .check_black_list_coinbase_tx(block_height as u64, &preload_tx.txid));
+
                 for (n, vout) in tx.output.iter().enumerate() {
                     // Skip outputs that cannot be spent
                     if vout.script_pubkey.is_op_return() {
@@ -411,9 +451,22 @@ impl BatchBlockPreloader {
                         script_hash,
                     };
 
+                    let muhash_digest = (track_tx_outputs
+                        && is_muhash_tracked_script(&vout.script_pubkey))
+                    .then(|| {
+                        hash_utxo_for_muhash(
+                            &outpoint,
+                            block_height,
+                            tx.is_coinbase(),
+                            cache_tx_out.value,
+                            &vout.script_pubkey,
+                        )
+                    });
+
                     let preload_vout = PreloadVOut {
                         outpoint: Arc::new(outpoint),
                         cache_tx_out: Arc::new(cache_tx_out),
+                        muhash_digest,
                     };
                     preload_tx.vout.push(preload_vout);
                 }
@@ -446,6 +499,7 @@ impl BatchBlockPreloader {
                         item: vout.cache_tx_out.clone(),
                         block_height,
                         spend: false,
+                        muhash_digest: vout.muhash_digest,
                     },
                 );
             }
@@ -477,6 +531,7 @@ impl BatchBlockPreloader {
                         vout_utxo_info.spend = true;
 
                         vin.cache_tx_out.replace(vout_utxo_info.item.clone());
+                        vin.muhash_digest = vout_utxo_info.muhash_digest;
                         // Same-block spends never existed before the rollback boundary, but
                         // earlier-block spends inside this batch must be restorable if a later
                         // block is rolled back.
@@ -505,6 +560,10 @@ impl BatchBlockPreloader {
             }
         }
 
+        if self.db.is_utxo_set_hash_enabled() {
+            self.preload_muhash_digests(preload_block)?;
+        }
+
         if outpoints_to_load.is_empty() {
             return Ok(());
         }
@@ -542,6 +601,44 @@ impl BatchBlockPreloader {
         Ok(())
     }
 
+    // Spent coins created before this batch keep their digest only in RocksDB, the UTXO
+    // cache does not carry it.
+    fn preload_muhash_digests(&self, preload_block: &mut PreloadBlock) -> Result<(), String> {
+        let mut outpoints = Vec::new();
+        let mut positions = Vec::new();
+        for (tx_index, tx) in preload_block.txdata.iter().enumerate() {
+            for (vin_index, vin) in tx.vin.iter().enumerate() {
+                if vin.muhash_digest.is_none() {
+                    outpoints.push(vin.outpoint.clone());
+                    positions.push(VInPosition {
+                        tx_index,
+                        vin_index,
+                    });
+                }
+            }
+        }
+
+        if outpoints.is_empty() {
+            return Ok(());
+        }
+
+        let digests = self.db.get_utxo_muhash_digests_bulk(&outpoints)?;
+        for (pos, digest) in positions.into_iter().zip(digests.into_iter()) {
+            let vin = &mut preload_block.txdata[pos.tx_index].vin[pos.vin_index];
+            let digest = digest.ok_or_else(|| {
+                let msg = format!(
+                    "Missing UTXO muhash digest for spent outpoint {} at block height {}. The UTXO set hash must be enabled from genesis; resync or disable sync.utxo_set_hash.",
+                    vin.outpoint, preload_block.height
+                );
+                error!("{}", msg);
+                msg
+            })?;
+            vin.muhash_digest = Some(digest);
+        }
+
+        Ok(())
+    }
+
     fn fetch_utxos(&self, outpoints: &[OutPointRef]) -> Result<Vec<UTXOEntryRef>, String> {
         // First try to get from db by bulk
         let all = self.db.get_utxos_bulk(outpoints)?;
@@ -665,6 +762,9 @@ impl BatchBlockFlusher {
             self.collect_balance_updates(data)?;
         let script_registry_entries = self.collect_script_registry_updates(data)?;
         let undo_bundles = self.collect_undo_bundles(data)?;
+        let utxo_set_hash = self.collect_utxo_set_hash_update(data)?;
+        let address_transactions = self.collect_address_tx_index_update(data)?;
+        let utxo_history = self.collect_utxo_history_update(data)?;
 
         let begin = std::time::Instant::now();
         self.db
@@ -676,6 +776,9 @@ impl BatchBlockFlusher {
                 block_commits: &block_commits,
                 script_registry_entries: &script_registry_entries,
                 undo_bundles: &undo_bundles,
+                utxo_set_hash: utxo_set_hash.as_ref(),
+                address_transactions: address_transactions.as_ref(),
+                utxo_history: utxo_history.as_ref(),
             })?;
         let duration = begin.elapsed();
 
@@ -708,7 +811,8 @@ impl BatchBlockFlusher {
         let last_block_height = data.block_range.end - 1;
         let all = data.balance_history.lock().unwrap().clone();
         let block_balance_deltas = data.block_balance_deltas.lock().unwrap();
-        let block_commits = build_block_commits(&block_balance_deltas, previous_commit);
+        let block_commits =
+            build_block_commits(&block_balance_deltas, previous_commit, self.db.network());
 
         data.bench_mark
             .batch_put_balance_counts
@@ -858,6 +962,232 @@ impl BatchBlockFlusher {
         Ok(bundles)
     }
 
+    // Fold every block of the batch into the MuHash accumulator of the previous height.
+    // Per-block products are computed in parallel, only the chaining is sequential.
+    fn collect_utxo_set_hash_update(
+        &self,
+        data: &BatchBlockDataRef,
+    ) -> Result<Option<UtxoSetHashUpdate>, String> {
+        use rayon::prelude::*;
+
+        if !self.db.is_utxo_set_hash_enabled() {
+            return Ok(None);
+        }
+
+        let previous_height = data.block_range.start.checked_sub(1);
+        let previous_state = match previous_height {
+            Some(height) => self.db.get_utxo_set_hash_state(height)?,
+            None => None,
+        };
+        let mut state = match (previous_height, previous_state) {
+            (_, Some(state)) => state,
+            (None | Some(0), None) => MuHash3072::new(),
+            (Some(height), None) => {
+                let msg = format!(
+                    "Missing UTXO set hash state at height {}. The UTXO set hash must be enabled from genesis; resync or disable sync.utxo_set_hash.",
+                    height
+                );
+                error!("{}", msg);
+                return Err(msg);
+            }
+        };
+
+        let blocks = data.blocks.lock().unwrap();
+        let block_deltas: Vec<Result<MuHash3072, String>> = blocks
+            .par_iter()
+            .map(|block| {
+                let mut created = Vec::new();
+                let mut spent = Vec::new();
+                for tx in &block.txdata {
+                    created.extend(tx.vout.iter().filter_map(|vout| vout.muhash_digest));
+                    for vin in &tx.vin {
+                        let digest = vin.muhash_digest.ok_or_else(|| {
+                            let msg = format!(
+                                "Missing UTXO muhash digest for spent outpoint {} at block height {}",
+                                vin.outpoint, block.height
+                            );
+                            error!("{}", msg);
+                            msg
+                        })?;
+                        spent.push(digest);
+                    }
+                }
+                Ok(MuHash3072::from_digests(&created, &spent))
+            })
+            .collect();
+
+        let mut update = UtxoSetHashUpdate::default();
+        for (block, delta) in blocks.iter().zip(block_deltas.into_iter()) {
+            state.combine(&delta?);
+            update.states.push((block.height, state.clone()));
+
+            if !should_persist_undo_for_block(
+                block.height,
+                self.latest_btc_height,
+                self.undo_retention_blocks,
+            ) {
+                continue;
+            }
+            for tx in &block.txdata {
+                for vin in tx.vin.iter().filter(|vin| vin.need_flush) {
+                    if let Some(digest) = vin.muhash_digest {
+                        update
+                            .undo_digests
+                            .push((block.height, vin.outpoint.clone(), digest));
+                    }
+                }
+            }
+        }
+
+        let vout_utxos = data.vout_utxos.read().unwrap();
+        for (outpoint, vout_utxo_info) in vout_utxos.iter() {
+            if vout_utxo_info.spend {
+                continue;
+            }
+            if let Some(digest) = vout_utxo_info.muhash_digest {
+                update.new_digests.push((outpoint.clone(), digest));
+            }
+        }
+
+        Ok(Some(update))
+    }
+
+    // Aggregate, per transaction, the value each script spent through inputs and received
+    // through outputs.
+    fn collect_address_tx_index_update(
+        &self,
+        data: &BatchBlockDataRef,
+    ) -> Result<Option<AddressTxIndexUpdate>, String> {
+        use rayon::prelude::*;
+
+        if !self.db.is_address_tx_index_enabled() {
+            return Ok(None);
+        }
+
+        let blocks = data.blocks.lock().unwrap();
+        let block_entries: Vec<Result<Vec<AddressTxIndexEntry>, String>> = blocks
+            .par_iter()
+            .map(|block| {
+                let mut entries = Vec::new();
+                for (tx_index, tx) in block.txdata.iter().enumerate() {
+                    // (input_value, output_value) per script, sorted for a stable write order.
+                    let mut moved: BTreeMap<USDBScriptHash, (u64, u64)> = BTreeMap::new();
+                    for vin in &tx.vin {
+                        let spent = vin.cache_tx_out.as_ref().ok_or_else(|| {
+                            let msg = format!(
+                                "Missing cached spent UTXO when collecting address transactions: block_height={}, outpoint={}",
+                                block.height, vin.outpoint
+                            );
+                            error!("{}", msg);
+                            msg
+                        })?;
+                        moved.entry(spent.script_hash).or_default().0 += spent.value;
+                    }
+                    for vout in &tx.vout {
+                        moved.entry(vout.cache_tx_out.script_hash).or_default().1 +=
+                            vout.cache_tx_out.value;
+                    }
+
+                    entries.extend(moved.into_iter().map(
+                        |(script_hash, (input_value, output_value))| AddressTxIndexEntry {
+                            script_hash,
+                            block_height: block.height,
+                            tx_index: tx_index as u32,
+                            txid: tx.txid,
+                            input_value,
+                            output_value,
+                        },
+                    ));
+                }
+                Ok(entries)
+            })
+            .collect();
+
+        let mut update = AddressTxIndexUpdate {
+            first_block_height: data.block_range.start,
+            entries: Vec::new(),
+        };
+        for entries in block_entries {
+            update.entries.extend(entries?);
+        }
+
+        Ok(Some(update))
+    }
+
+    // Record the created and spent height of every output the batch touched. Blocks are
+    // walked in order so spends of outputs created earlier in the batch update the same row.
+    fn collect_utxo_history_update(
+        &self,
+        data: &BatchBlockDataRef,
+    ) -> Result<Option<UtxoHistoryUpdate>, String> {
+        if !self.db.is_utxo_history_enabled() {
+            return Ok(None);
+        }
+
+        let mut entries: BTreeMap<OutPoint, UtxoHistoryEntry> = BTreeMap::new();
+        let mut spent_before_batch = Vec::new();
+        {
+            let blocks = data.blocks.lock().unwrap();
+            for block in blocks.iter() {
+                for tx in &block.txdata {
+                    for vin in &tx.vin {
+                        if let Some(entry) = entries.get_mut(vin.outpoint.as_ref()) {
+                            entry.spent_height = Some(block.height);
+                            continue;
+                        }
+
+                        let spent = vin.cache_tx_out.as_ref().ok_or_else(|| {
+                            let msg = format!(
+                                "Missing cached spent UTXO when collecting UTXO history: block_height={}, outpoint={}",
+                                block.height, vin.outpoint
+                            );
+                            error!("{}", msg);
+                            msg
+                        })?;
+                        entries.insert(
+                            *vin.outpoint,
+                            UtxoHistoryEntry {
+                                outpoint: *vin.outpoint,
+                                script_hash: spent.script_hash,
+                                value: spent.value,
+                                created_height: None,
+                                spent_height: Some(block.height),
+                            },
+                        );
+                        spent_before_batch.push(*vin.outpoint);
+                    }
+
+                    for vout in &tx.vout {
+                        entries.insert(
+                            *vout.outpoint,
+                            UtxoHistoryEntry {
+                                outpoint: *vout.outpoint,
+                                script_hash: vout.cache_tx_out.script_hash,
+                                value: vout.cache_tx_out.value,
+                                created_height: Some(block.height),
+                                spent_height: None,
+                            },
+                        );
+                    }
+                }
+            }
+        }
+
+        // Outputs created before the batch keep the created height already recorded for them;
+        // outputs older than the index coverage have none.
+        let existing = self.db.get_utxo_history_bulk(&spent_before_batch)?;
+        for (outpoint, existing) in spent_before_batch.iter().zip(existing) {
+            if let Some(existing) = existing {
+                entries.get_mut(outpoint).unwrap().created_height = existing.created_height;
+            }
+        }
+
+        Ok(Some(UtxoHistoryUpdate {
+            first_block_height: data.block_range.start,
+            entries: entries.into_values().collect(),
+        }))
+    }
+
     fn collect_utxo_updates(
         &self,
         data: &BatchBlockDataRef,
@@ -1013,8 +1343,8 @@ mod block_commit_tests {
         };
 
         assert_eq!(
-            compute_balance_delta_root(&block),
-            compute_balance_delta_root(&block)
+            compute_balance_delta_root(&block, BalanceDeltaRootRule::FlatV1),
+            compute_balance_delta_root(&block, BalanceDeltaRootRule::FlatV1)
         );
     }
 
@@ -1033,8 +1363,8 @@ mod block_commit_tests {
         };
 
         assert_ne!(
-            compute_balance_delta_root(&original),
-            compute_balance_delta_root(&changed)
+            compute_balance_delta_root(&original, BalanceDeltaRootRule::FlatV1),
+            compute_balance_delta_root(&changed, BalanceDeltaRootRule::FlatV1)
         );
     }
 
@@ -1051,16 +1381,24 @@ mod block_commit_tests {
             entries: vec![make_entry(2, 2, 5, 15)],
         };
 
-        let commits = build_block_commits(&[first.clone(), second.clone()], EMPTY_COMMIT_HASH);
+        let commits = build_block_commits(
+            &[first.clone(), second.clone()],
+            EMPTY_COMMIT_HASH,
+            Network::Bitcoin,
+        );
         assert_eq!(commits.len(), 2);
 
-        let first_root = compute_balance_delta_root(&first);
-        let expected_first =
-            compute_block_commit(1, &first.block_hash, &first_root, &EMPTY_COMMIT_HASH);
+        let first_root = compute_balance_delta_root(&first, BalanceDeltaRootRule::FlatV1);
+        let expected_first = compute_balance_history_block_commit(
+            1,
+            &first.block_hash,
+            &first_root,
+            &EMPTY_COMMIT_HASH,
+        );
         assert_eq!(commits[0].block_commit, expected_first);
 
-        let second_root = compute_balance_delta_root(&second);
-        let expected_second = compute_block_commit(
+        let second_root = compute_balance_delta_root(&second, BalanceDeltaRootRule::FlatV1);
+        let expected_second = compute_balance_history_block_commit(
             2,
             &second.block_hash,
             &second_root,
@@ -1069,6 +1407,27 @@ mod block_commit_tests {
         assert_eq!(commits[1].block_commit, expected_second);
     }
 
+    #[test]
+    fn test_build_block_commits_switches_to_merkle_root_on_activated_network() {
+        let block = BlockBalanceDelta {
+            block_height: 3,
+            block_hash: BlockHash::from_slice(&[3u8; 32]).unwrap(),
+            entries: vec![make_entry(1, 3, 5, 5), make_entry(2, 3, -2, 8)],
+        };
+
+        let flat = build_block_commits(&[block.clone()], EMPTY_COMMIT_HASH, Network::Bitcoin);
+        let merkle = build_block_commits(&[block.clone()], EMPTY_COMMIT_HASH, Network::Regtest);
+        assert_eq!(
+            flat[0].balance_delta_root,
+            compute_balance_delta_root(&block, BalanceDeltaRootRule::FlatV1)
+        );
+        assert_eq!(
+            merkle[0].balance_delta_root,
+            compute_balance_delta_root(&block, BalanceDeltaRootRule::MerkleV2)
+        );
+        assert_ne!(flat[0].block_commit, merkle[0].block_commit);
+    }
+
     #[test]
     fn test_build_block_commits_depends_on_previous_commit() {
         let block = BlockBalanceDelta {
@@ -1077,8 +1436,8 @@ mod block_commit_tests {
             entries: vec![make_entry(4, 11, 3, 33)],
         };
 
-        let left = build_block_commits(&[block.clone()], [1u8; 32]);
-        let right = build_block_commits(&[block], [2u8; 32]);
+        let left = build_block_commits(&[block.clone()], [1u8; 32], Network::Bitcoin);
+        let right = build_block_commits(&[block], [2u8; 32], Network::Bitcoin);
         assert_ne!(left[0].block_commit, right[0].block_commit);
     }
 
@@ -1131,7 +1490,7 @@ mod block_commit_tests {
         assert_eq!(balance_entries[0].balance, block.entries[0].balance);
         assert_eq!(
             block_commits,
-            build_block_commits(&[block], EMPTY_COMMIT_HASH)
+            build_block_commits(&[block], EMPTY_COMMIT_HASH, Network::Bitcoin)
         );
     }
 
@@ -1215,6 +1574,7 @@ mod block_commit_tests {
                 vout: vec![PreloadVOut {
                     outpoint,
                     cache_tx_out: utxo,
+                    muhash_digest: None,
                 }],
             }],
         }];
@@ -1255,6 +1615,7 @@ mod block_commit_tests {
                             value: 30,
                         })),
                         need_flush: true,
+                        muhash_digest: None,
                     },
                     PreloadVIn {
                         outpoint: Arc::new(OutPoint {
@@ -1266,6 +1627,7 @@ mod block_commit_tests {
                             value: 40,
                         })),
                         need_flush: true,
+                        muhash_digest: None,
                     },
                 ],
                 vout: vec![
@@ -1278,6 +1640,7 @@ mod block_commit_tests {
                             script_hash: script_b,
                             value: 50,
                         }),
+                        muhash_digest: None,
                     },
                     PreloadVOut {
                         outpoint: Arc::new(OutPoint {
@@ -1288,6 +1651,7 @@ mod block_commit_tests {
                             script_hash: script_a,
                             value: 60,
                         }),
+                        muhash_digest: None,
                     },
                 ],
             }],
@@ -1383,6 +1747,7 @@ mod block_commit_tests {
                             value: 40,
                         })),
                         need_flush: true,
+                        muhash_digest: None,
                     },
                     PreloadVIn {
                         outpoint: Arc::new(OutPoint {
@@ -1394,6 +1759,7 @@ mod block_commit_tests {
                             value: 30,
                         })),
                         need_flush: true,
+                        muhash_digest: None,
                     },
                 ],
                 vout: vec![
@@ -1406,6 +1772,7 @@ mod block_commit_tests {
                             script_hash: script_a,
                             value: 60,
                         }),
+                        muhash_digest: None,
                     },
                     PreloadVOut {
                         outpoint: Arc::new(OutPoint {
@@ -1416,6 +1783,7 @@ mod block_commit_tests {
                             script_hash: script_b,
                             value: 50,
                         }),
+                        muhash_digest: None,
                     },
                 ],
             }],
@@ -1468,6 +1836,329 @@ mod block_commit_tests {
         assert_eq!(bundles[0].touched_script_hashes, expected_touched);
     }
 
+    fn make_muhash_batch(
+        block_height: u32,
+        spent: &[(OutPoint, [u8; 32])],
+        created: &[(OutPoint, [u8; 32])],
+    ) -> BatchBlockDataRef {
+        let utxo = Arc::new(UTXOValue {
+            script_hash: ScriptBuf::from(vec![0x51]).to_usdb_script_hash(),
+            value: 10,
+        });
+        let mut data = BatchBlockData::new();
+        data.block_range = block_height..block_height + 1;
+        *data.blocks.lock().unwrap() = vec![PreloadBlock {
+            height: block_height,
+            block_hash: BlockHash::from_slice(&[block_height as u8; 32]).unwrap(),
+            txdata: vec![PreloadTx {
+                txid: Txid::from_slice(&[block_height as u8; 32]).unwrap(),
+                vin: spent
+                    .iter()
+                    .map(|(outpoint, digest)| PreloadVIn {
+                        outpoint: Arc::new(*outpoint),
+                        cache_tx_out: Some(utxo.clone()),
+                        need_flush: true,
+                        muhash_digest: Some(*digest),
+                    })
+                    .collect(),
+                vout: created
+                    .iter()
+                    .map(|(outpoint, digest)| PreloadVOut {
+                        outpoint: Arc::new(*outpoint),
+                        cache_tx_out: utxo.clone(),
+                        muhash_digest: Some(*digest),
+                    })
+                    .collect(),
+            }],
+        }];
+        *data.vout_utxos.write().unwrap() = created
+            .iter()
+            .map(|(outpoint, digest)| {
+                (
+                    Arc::new(*outpoint),
+                    VOutUtxoInfo {
+                        item: utxo.clone(),
+                        block_height,
+                        spend: false,
+                        muhash_digest: Some(*digest),
+                    },
+                )
+            })
+            .collect();
+        *data.block_balance_deltas.lock().unwrap() = vec![BlockBalanceDelta {
+            block_height,
+            block_hash: BlockHash::from_slice(&[block_height as u8; 32]).unwrap(),
+            entries: Vec::new(),
+        }];
+        Arc::new(data)
+    }
+
+    #[test]
+    fn test_flusher_maintains_utxo_set_hash_across_rollback() {
+        let mut config = BalanceHistoryConfig::default();
+        config.sync.utxo_set_hash = true;
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_nanos();
+        let temp_dir =
+            std::env::temp_dir().join(format!("balance_history_utxo_set_hash_{}", nanos));
+        std::fs::create_dir_all(&temp_dir).unwrap();
+        config.root_dir = temp_dir;
+        let db: BalanceHistoryDBRef = Arc::new(
+            BalanceHistoryDB::open(Arc::new(config), BalanceHistoryDBMode::Normal).unwrap(),
+        );
+        let flusher = test_flusher_with_undo(db.clone(), 2, 10);
+
+        let coin = |seed: u8| {
+            (
+                OutPoint {
+                    txid: Txid::from_slice(&[seed; 32]).unwrap(),
+                    vout: 0,
+                },
+                [seed; 32],
+            )
+        };
+        let (a, b, c) = (coin(0xa1), coin(0xb2), coin(0xc3));
+
+        flusher.flush(&make_muhash_batch(1, &[], &[a, b])).unwrap();
+        let hash_at_1 = MuHash3072::from_digests(&[a.1, b.1], &[]).finalize();
+        assert_eq!(db.get_utxo_set_hash(1).unwrap(), Some(hash_at_1));
+
+        flusher.flush(&make_muhash_batch(2, &[a], &[c])).unwrap();
+        assert_eq!(
+            db.get_utxo_set_hash(2).unwrap(),
+            Some(MuHash3072::from_digests(&[b.1, c.1], &[]).finalize())
+        );
+        let outpoints = [Arc::new(a.0), Arc::new(c.0)];
+        assert_eq!(
+            db.get_utxo_muhash_digests_bulk(&outpoints).unwrap(),
+            vec![None, Some(c.1)]
+        );
+
+        db.rollback_one_block(2).unwrap();
+        assert_eq!(db.get_utxo_set_hash(2).unwrap(), None);
+        assert_eq!(db.get_utxo_set_hash(1).unwrap(), Some(hash_at_1));
+        assert_eq!(
+            db.get_utxo_muhash_digests_bulk(&outpoints).unwrap(),
+            vec![Some(a.1), None]
+        );
+
+        // Replaying the block after the rollback continues from the restored digests.
+        flusher.flush(&make_muhash_batch(2, &[a], &[c])).unwrap();
+        assert_eq!(
+            db.get_utxo_set_hash(2).unwrap(),
+            Some(MuHash3072::from_digests(&[b.1, c.1], &[]).finalize())
+        );
+        db.ensure_utxo_set_hash_state_available().unwrap();
+
+        // A synced height without accumulator, as left by a snapshot install, is refused.
+        db.put_btc_block_height(3).unwrap();
+        let err = db.ensure_utxo_set_hash_state_available().unwrap_err();
+        assert!(err.contains("synced height 3"), "{}", err);
+    }
+
+    #[test]
+    fn test_flusher_indexes_address_transactions_across_rollback() {
+        let mut config = BalanceHistoryConfig::default();
+        config.sync.address_transactions = true;
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_nanos();
+        let temp_dir =
+            std::env::temp_dir().join(format!("balance_history_address_transactions_{}", nanos));
+        std::fs::create_dir_all(&temp_dir).unwrap();
+        config.root_dir = temp_dir;
+        let db: BalanceHistoryDBRef = Arc::new(
+            BalanceHistoryDB::open(Arc::new(config), BalanceHistoryDBMode::Normal).unwrap(),
+        );
+        let flusher = test_flusher_with_undo(db.clone(), 2, 10);
+        let script_hash = ScriptBuf::from(vec![0x51]).to_usdb_script_hash();
+
+        let coin = |seed: u8| {
+            (
+                OutPoint {
+                    txid: Txid::from_slice(&[seed; 32]).unwrap(),
+                    vout: 0,
+                },
+                [seed; 32],
+            )
+        };
+        let (a, b, c) = (coin(0xa1), coin(0xb2), coin(0xc3));
+
+        flusher.flush(&make_muhash_batch(1, &[], &[a, b])).unwrap();
+        flusher.flush(&make_muhash_batch(2, &[a], &[c])).unwrap();
+        assert_eq!(db.get_address_tx_index_from_height().unwrap(), Some(1));
+
+        let entries = db
+            .get_address_transactions(&script_hash, 0..3, 0, 10)
+            .unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(
+            (
+                entries[0].block_height,
+                entries[0].input_value,
+                entries[0].output_value
+            ),
+            (1, 0, 20)
+        );
+        assert_eq!(
+            (
+                entries[1].block_height,
+                entries[1].input_value,
+                entries[1].output_value
+            ),
+            (2, 10, 10)
+        );
+        assert_eq!(entries[1].txid, Txid::from_slice(&[2u8; 32]).unwrap());
+        assert_eq!(
+            db.get_address_transactions(&script_hash, 0..3, 1, 10)
+                .unwrap()
+                .len(),
+            1
+        );
+        assert_eq!(
+            db.get_address_transactions(&script_hash, 2..3, 0, 10)
+                .unwrap()
+                .len(),
+            1
+        );
+
+        db.rollback_one_block(2).unwrap();
+        let entries = db
+            .get_address_transactions(&script_hash, 0..3, 0, 10)
+            .unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].block_height, 1);
+    }
+
+    #[derive(Default)]
+    struct UtxoCollector {
+        outpoints: Mutex<Vec<OutPoint>>,
+    }
+
+    impl crate::db::SnapshotCallback for Arc<UtxoCollector> {
+        fn on_balance_history_entries(
+            &self,
+            _entries: &[BalanceHistoryEntry],
+            _entries_processed: u64,
+        ) -> Result<(), String> {
+            Ok(())
+        }
+
+        fn on_utxo_entries(
+            &self,
+            entries: &[usdb_util::UTXOEntry],
+            _entries_processed: u64,
+        ) -> Result<(), String> {
+            let mut outpoints = self.outpoints.lock().unwrap();
+            outpoints.extend(entries.iter().map(|entry| entry.outpoint));
+            Ok(())
+        }
+
+        fn on_block_commit_entries(
+            &self,
+            _entries: &[BlockCommitEntry],
+            _entries_processed: u64,
+        ) -> Result<(), String> {
+            Ok(())
+        }
+    }
+
+    fn historical_utxo_outpoints(db: &BalanceHistoryDB, block_height: u32) -> Vec<OutPoint> {
+        let collector = Arc::new(UtxoCollector::default());
+        db.generate_historical_utxo_snapshot_parallel(
+            block_height,
+            Arc::new(Box::new(collector.clone()) as Box<dyn crate::db::SnapshotCallback>),
+        )
+        .unwrap();
+        let mut outpoints = collector.outpoints.lock().unwrap().clone();
+        outpoints.sort();
+        outpoints
+    }
+
+    #[test]
+    fn test_flusher_tracks_utxo_history_across_rollback() {
+        let mut config = BalanceHistoryConfig::default();
+        config.sync.utxo_history = true;
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_nanos();
+        let temp_dir = std::env::temp_dir().join(format!("balance_history_utxo_history_{}", nanos));
+        std::fs::create_dir_all(&temp_dir).unwrap();
+        config.root_dir = temp_dir;
+        let db: BalanceHistoryDBRef = Arc::new(
+            BalanceHistoryDB::open(Arc::new(config), BalanceHistoryDBMode::Normal).unwrap(),
+        );
+        let flusher = test_flusher_with_undo(db.clone(), 2, 10);
+        let script_hash = ScriptBuf::from(vec![0x51]).to_usdb_script_hash();
+
+        let coin = |seed: u8| {
+            (
+                OutPoint {
+                    txid: Txid::from_slice(&[seed; 32]).unwrap(),
+                    vout: 0,
+                },
+                [seed; 32],
+            )
+        };
+        let (a, b, c, old) = (coin(0xa1), coin(0xb2), coin(0xc3), coin(0x0d));
+        // Created before the index coverage starts.
+        db.put_utxo(&old.0, &script_hash, 10).unwrap();
+
+        flusher.flush(&make_muhash_batch(1, &[], &[a, b])).unwrap();
+        flusher
+            .flush(&make_muhash_batch(2, &[a, old], &[c]))
+            .unwrap();
+        assert_eq!(db.get_utxo_history_from_height().unwrap(), Some(1));
+
+        let a_at_1 = db.get_utxo_at_height(&a.0, 1).unwrap().unwrap();
+        assert_eq!(
+            (a_at_1.created_height, a_at_1.spent_height, a_at_1.value),
+            (Some(1), Some(2), 10)
+        );
+        assert!(db.get_utxo_at_height(&a.0, 2).unwrap().is_none());
+        assert!(db.get_utxo_at_height(&c.0, 1).unwrap().is_none());
+        assert_eq!(
+            db.get_utxo_at_height(&c.0, 2)
+                .unwrap()
+                .unwrap()
+                .created_height,
+            Some(2)
+        );
+        let old_at_1 = db.get_utxo_at_height(&old.0, 1).unwrap().unwrap();
+        assert_eq!(
+            (old_at_1.created_height, old_at_1.spent_height),
+            (None, Some(2))
+        );
+        assert!(db.get_utxo_at_height(&old.0, 2).unwrap().is_none());
+
+        let mut expected = vec![a.0, b.0, old.0];
+        expected.sort();
+        assert_eq!(historical_utxo_outpoints(&db, 1), expected);
+        let mut expected = vec![b.0, c.0];
+        expected.sort();
+        assert_eq!(historical_utxo_outpoints(&db, 2), expected);
+
+        db.rollback_one_block(2).unwrap();
+        let a_at_1 = db.get_utxo_at_height(&a.0, 1).unwrap().unwrap();
+        assert_eq!(a_at_1.spent_height, None);
+        assert!(db.get_utxo_at_height(&c.0, 1).unwrap().is_none());
+        let old_at_1 = db.get_utxo_at_height(&old.0, 1).unwrap().unwrap();
+        assert_eq!(
+            (old_at_1.created_height, old_at_1.spent_height),
+            (None, None)
+        );
+        assert!(
+            db.get_utxo_history_bulk(&[old.0, c.0])
+                .unwrap()
+                .iter()
+                .all(Option::is_none)
+        );
+    }
+
     #[test]
     fn test_should_persist_undo_only_inside_hot_window() {
         assert!(!should_persist_undo_for_block(100, 150, 0));
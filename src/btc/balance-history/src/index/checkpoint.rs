@@ -0,0 +1,491 @@
+use crate::config::BalanceHistoryConfigRef;
+use crate::db::{BalanceHistoryDB, BalanceHistoryDBMode};
+use crate::service::{HistoricalSnapshotStateRef, build_historical_state_ref_at_height};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Version tag for the checkpoint sidecar manifest format.
+pub const CHECKPOINT_MANIFEST_VERSION: &str = "balance-history-checkpoint-manifest:v1";
+
+// Serializes checkpoint creation so concurrent requests never share a staging directory.
+static CHECKPOINT_LOCK: Mutex<()> = Mutex::new(());
+
+/// Sidecar manifest describing the state captured by one RocksDB checkpoint.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct CheckpointManifest {
+    /// Version tag of the checkpoint manifest schema.
+    pub manifest_version: String,
+    /// Checkpoint directory basename described by this manifest.
+    pub checkpoint_name: String,
+    /// Stable state captured by the checkpoint, including the block commit at that height.
+    pub state_ref: HistoricalSnapshotStateRef,
+    /// Lowest height whose undo journal is retained, i.e. how deep a restored node can roll back.
+    #[serde(default)]
+    pub undo_retained_from_height: Option<u32>,
+    /// Balance history retention floor when the source node runs in pruned mode.
+    #[serde(default)]
+    pub balance_history_retained_from_height: Option<u32>,
+    /// Unix timestamp when the checkpoint was taken.
+    pub created_at: u64,
+}
+
+/// Result of one `create_checkpoint` call.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct CheckpointInfo {
+    /// Checkpoint directory holding the RocksDB files.
+    pub checkpoint_dir: String,
+    /// Sidecar manifest path next to the checkpoint directory.
+    pub manifest_file: String,
+    pub manifest: CheckpointManifest,
+}
+
+impl CheckpointManifest {
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let data = std::fs::read_to_string(path).map_err(|e| {
+            let msg = format!(
+                "Failed to read checkpoint manifest {}: {}",
+                path.display(),
+                e
+            );
+            error!("{}", msg);
+            msg
+        })?;
+        let manifest: CheckpointManifest = serde_json::from_str(&data).map_err(|e| {
+            let msg = format!(
+                "Failed to parse checkpoint manifest {} as JSON: {}",
+                path.display(),
+                e
+            );
+            error!("{}", msg);
+            msg
+        })?;
+        if manifest.manifest_version != CHECKPOINT_MANIFEST_VERSION {
+            let msg = format!(
+                "Unsupported checkpoint manifest version {} in {} (expected {})",
+                manifest.manifest_version,
+                path.display(),
+                CHECKPOINT_MANIFEST_VERSION
+            );
+            error!("{}", msg);
+            return Err(msg);
+        }
+        Ok(manifest)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        let data = serde_json::to_string_pretty(self).map_err(|e| {
+            let msg = format!(
+                "Failed to serialize checkpoint manifest {}: {}",
+                path.display(),
+                e
+            );
+            error!("{}", msg);
+            msg
+        })?;
+        std::fs::write(path, data).map_err(|e| {
+            let msg = format!(
+                "Failed to write checkpoint manifest {}: {}",
+                path.display(),
+                e
+            );
+            error!("{}", msg);
+            msg
+        })
+    }
+}
+
+/// Returns the sidecar manifest path for one checkpoint directory.
+pub fn manifest_path_for_checkpoint_dir(dir: &Path) -> PathBuf {
+    let mut path = dir.as_os_str().to_owned();
+    path.push(".manifest.json");
+    PathBuf::from(path)
+}
+
+// Reads the committed state of a checkpoint and rebuilds its state ref from the
+// checkpoint itself, never from the live DB, which may already have moved on.
+fn inspect_checkpoint(
+    config: &BalanceHistoryConfigRef,
+    checkpoint_dir: &Path,
+    checkpoint_name: &str,
+    created_at: u64,
+) -> Result<CheckpointManifest, String> {
+    let db = BalanceHistoryDB::open_checkpoint(config.clone(), checkpoint_dir)?;
+    if db.is_rollback_in_progress()? {
+        let msg = format!(
+            "Checkpoint {} was taken while a rollback was in progress",
+            checkpoint_dir.display()
+        );
+        error!("{}", msg);
+        return Err(msg);
+    }
+
+    let block_height = db.get_btc_block_height()?;
+    let state_ref =
+        build_historical_state_ref_at_height(config, &db, block_height)?.ok_or_else(|| {
+            let msg = format!(
+                "Checkpoint {} has no block commit at its synced height {}",
+                checkpoint_dir.display(),
+                block_height
+            );
+            error!("{}", msg);
+            msg
+        })?;
+
+    Ok(CheckpointManifest {
+        manifest_version: CHECKPOINT_MANIFEST_VERSION.to_string(),
+        checkpoint_name: checkpoint_name.to_string(),
+        state_ref,
+        undo_retained_from_height: db.get_undo_retained_from_height()?,
+        balance_history_retained_from_height: db.get_balance_history_retained_from_height()?,
+        created_at,
+    })
+}
+
+/// Takes a consistent checkpoint of the live DB under `checkpoint_dir()` and writes its
+/// sidecar manifest. The indexer keeps running while the checkpoint is taken.
+pub fn create_checkpoint(
+    config: &BalanceHistoryConfigRef,
+    db: &BalanceHistoryDB,
+) -> Result<CheckpointInfo, String> {
+    let _guard = CHECKPOINT_LOCK.lock().unwrap();
+
+    let root = config.checkpoint_dir();
+    std::fs::create_dir_all(&root).map_err(|e| {
+        let msg = format!(
+            "Failed to create checkpoint directory {}: {}",
+            root.display(),
+            e
+        );
+        error!("{}", msg);
+        msg
+    })?;
+
+    let created_at = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+
+    // The height is only known once the checkpoint exists, so it is staged first and
+    // renamed after inspection.
+    let staging_dir = root.join(format!(".checkpoint_{}.tmp", created_at));
+    if staging_dir.exists() {
+        std::fs::remove_dir_all(&staging_dir).map_err(|e| {
+            let msg = format!(
+                "Failed to remove stale checkpoint staging directory {}: {}",
+                staging_dir.display(),
+                e
+            );
+            error!("{}", msg);
+            msg
+        })?;
+    }
+    db.create_checkpoint(&staging_dir)?;
+
+    let manifest = match inspect_checkpoint(config, &staging_dir, "", created_at) {
+        Ok(manifest) => manifest,
+        Err(e) => {
+            let _ = std::fs::remove_dir_all(&staging_dir);
+            return Err(e);
+        }
+    };
+
+    // Two checkpoints of the same height within one second would share a name, so a
+    // counter suffix is appended until the name is free. The lock keeps this race-free.
+    let base_name = format!(
+        "checkpoint_{}_{}",
+        manifest.state_ref.block_height, created_at
+    );
+    let mut checkpoint_name = base_name.clone();
+    let mut suffix = 0u32;
+    while root.join(&checkpoint_name).exists()
+        || manifest_path_for_checkpoint_dir(&root.join(&checkpoint_name)).exists()
+    {
+        suffix += 1;
+        checkpoint_name = format!("{}_{}", base_name, suffix);
+    }
+    let checkpoint_dir = root.join(&checkpoint_name);
+    std::fs::rename(&staging_dir, &checkpoint_dir).map_err(|e| {
+        let msg = format!(
+            "Failed to move checkpoint {} to {}: {}",
+            staging_dir.display(),
+            checkpoint_dir.display(),
+            e
+        );
+        error!("{}", msg);
+        msg
+    })?;
+
+    let manifest = CheckpointManifest {
+        checkpoint_name,
+        ..manifest
+    };
+    let manifest_file = manifest_path_for_checkpoint_dir(&checkpoint_dir);
+    manifest.save(&manifest_file)?;
+
+    info!(
+        "Checkpoint created: dir={}, block_height={}, block_commit={}",
+        checkpoint_dir.display(),
+        manifest.state_ref.block_height,
+        manifest.state_ref.latest_block_commit
+    );
+
+    Ok(CheckpointInfo {
+        checkpoint_dir: checkpoint_dir.display().to_string(),
+        manifest_file: manifest_file.display().to_string(),
+        manifest,
+    })
+}
+
+/// Replaces the service DB with a checkpoint after checking it against its manifest.
+/// The service must be stopped; an existing DB is only replaced when `force` is set.
+pub fn restore_checkpoint(
+    config: &BalanceHistoryConfigRef,
+    checkpoint_dir: &Path,
+    manifest_file: Option<&Path>,
+    force: bool,
+) -> Result<CheckpointManifest, String> {
+    let manifest_file = manifest_file
+        .map(Path::to_path_buf)
+        .unwrap_or_else(|| manifest_path_for_checkpoint_dir(checkpoint_dir));
+    let manifest = CheckpointManifest::load(&manifest_file)?;
+
+    let actual = inspect_checkpoint(
+        config,
+        checkpoint_dir,
+        &manifest.checkpoint_name,
+        manifest.created_at,
+    )?;
+    if actual != manifest {
+        let msg = format!(
+            "Checkpoint {} does not match manifest {}: expected height {} commit {}, found height {} commit {}",
+            checkpoint_dir.display(),
+            manifest_file.display(),
+            manifest.state_ref.block_height,
+            manifest.state_ref.latest_block_commit,
+            actual.state_ref.block_height,
+            actual.state_ref.latest_block_commit
+        );
+        error!("{}", msg);
+        return Err(msg);
+    }
+
+    let db_path = BalanceHistoryDB::db_path(config);
+    if db_path.exists() {
+        if !force {
+            let msg = format!(
+                "Refusing to overwrite existing database {}; rerun with force enabled",
+                db_path.display()
+            );
+            error!("{}", msg);
+            return Err(msg);
+        }
+
+        // Opening takes the RocksDB lock, which fails while the service is still running.
+        drop(BalanceHistoryDB::open(
+            config.clone(),
+            BalanceHistoryDBMode::Normal,
+        )?);
+    }
+
+    // Copy next to the target first so a failed copy never leaves a half-restored DB.
+    let staging_path = db_path.with_extension("restore.tmp");
+    if staging_path.exists() {
+        remove_dir(&staging_path)?;
+    }
+    copy_checkpoint_files(checkpoint_dir, &staging_path)?;
+
+    if db_path.exists() {
+        remove_dir(&db_path)?;
+    }
+    std::fs::rename(&staging_path, &db_path).map_err(|e| {
+        let msg = format!(
+            "Failed to move restored database {} to {}: {}",
+            staging_path.display(),
+            db_path.display(),
+            e
+        );
+        error!("{}", msg);
+        msg
+    })?;
+
+    info!(
+        "Checkpoint restored: checkpoint={}, block_height={}, db={}",
+        checkpoint_dir.display(),
+        manifest.state_ref.block_height,
+        db_path.display()
+    );
+    Ok(manifest)
+}
+
+fn remove_dir(dir: &Path) -> Result<(), String> {
+    std::fs::remove_dir_all(dir).map_err(|e| {
+        let msg = format!("Failed to remove directory {}: {}", dir.display(), e);
+        error!("{}", msg);
+        msg
+    })
+}
+
+// Checkpoints are flat directories of SST, manifest, options and WAL files.
+fn copy_checkpoint_files(from: &Path, to: &Path) -> Result<(), String> {
+    std::fs::create_dir_all(to).map_err(|e| {
+        let msg = format!("Failed to create directory {}: {}", to.display(), e);
+        error!("{}", msg);
+        msg
+    })?;
+
+    let entries = std::fs::read_dir(from).map_err(|e| {
+        let msg = format!(
+            "Failed to read checkpoint directory {}: {}",
+            from.display(),
+            e
+        );
+        error!("{}", msg);
+        msg
+    })?;
+    for entry in entries {
+        let entry = entry.map_err(|e| {
+            let msg = format!(
+                "Failed to read checkpoint directory {}: {}",
+                from.display(),
+                e
+            );
+            error!("{}", msg);
+            msg
+        })?;
+        let source = entry.path();
+        if !source.is_file() {
+            let msg = format!("Unexpected entry in checkpoint: {}", source.display());
+            error!("{}", msg);
+            return Err(msg);
+        }
+        let target = to.join(entry.file_name());
+        std::fs::copy(&source, &target).map_err(|e| {
+            let msg = format!(
+                "Failed to copy checkpoint file {} to {}: {}",
+                source.display(),
+                target.display(),
+                e
+            );
+            error!("{}", msg);
+            msg
+        })?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::BalanceHistoryConfig;
+    use crate::db::{BalanceHistoryEntry, BlockCommitEntry};
+    use bitcoincore_rpc::bitcoin::BlockHash;
+    use bitcoincore_rpc::bitcoin::hashes::Hash;
+    use std::sync::Arc;
+    use usdb_util::USDBScriptHash;
+
+    fn make_config(tag: &str) -> BalanceHistoryConfigRef {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_nanos();
+        let mut config = BalanceHistoryConfig::default();
+        config.root_dir =
+            std::env::temp_dir().join(format!("balance_history_checkpoint_{}_{}", tag, nanos));
+        std::fs::create_dir_all(&config.root_dir).unwrap();
+        Arc::new(config)
+    }
+
+    fn commit_block(db: &BalanceHistoryDB, height: u32, balance: u64) {
+        let entry = BalanceHistoryEntry {
+            script_hash: USDBScriptHash::from_byte_array([1u8; 32]),
+            block_height: height,
+            delta: balance as i64,
+            balance,
+        };
+        let commit = BlockCommitEntry {
+            block_height: height,
+            btc_block_hash: BlockHash::from_slice(&[height as u8; 32]).unwrap(),
+            balance_delta_root: [height as u8; 32],
+            block_commit: [height as u8 + 1; 32],
+        };
+        db.update_address_history_with_block_commits_async(&vec![entry], height, &[commit])
+            .unwrap();
+    }
+
+    #[test]
+    fn test_create_and_restore_checkpoint_round_trip() {
+        let source_config = make_config("source");
+        let db =
+            BalanceHistoryDB::open(source_config.clone(), BalanceHistoryDBMode::Normal).unwrap();
+        commit_block(&db, 10, 100);
+
+        let info = create_checkpoint(&source_config, &db).unwrap();
+        assert_eq!(info.manifest.state_ref.block_height, 10);
+        assert!(Path::new(&info.manifest_file).exists());
+
+        // Later writes to the live DB are not part of the checkpoint.
+        commit_block(&db, 11, 200);
+        drop(db);
+
+        let target_config = make_config("target");
+        let checkpoint_dir = PathBuf::from(&info.checkpoint_dir);
+        let manifest = restore_checkpoint(&target_config, &checkpoint_dir, None, false).unwrap();
+        assert_eq!(manifest, info.manifest);
+
+        let restored =
+            BalanceHistoryDB::open(target_config.clone(), BalanceHistoryDBMode::Normal).unwrap();
+        assert_eq!(restored.get_btc_block_height().unwrap(), 10);
+        let balance = restored
+            .get_balance_at_block_height(&USDBScriptHash::from_byte_array([1u8; 32]), 11)
+            .unwrap();
+        assert_eq!(balance.balance, 100);
+        drop(restored);
+
+        let err = restore_checkpoint(&target_config, &checkpoint_dir, None, false).unwrap_err();
+        assert!(err.contains("Refusing to overwrite"));
+        restore_checkpoint(&target_config, &checkpoint_dir, None, true).unwrap();
+    }
+
+    #[test]
+    fn test_create_checkpoint_twice_in_one_second_uses_distinct_names() {
+        let config = make_config("same_second");
+        let db = BalanceHistoryDB::open(config.clone(), BalanceHistoryDBMode::Normal).unwrap();
+        commit_block(&db, 10, 100);
+
+        let first = create_checkpoint(&config, &db).unwrap();
+        let second = create_checkpoint(&config, &db).unwrap();
+        assert_ne!(first.checkpoint_dir, second.checkpoint_dir);
+        assert_ne!(
+            first.manifest.checkpoint_name,
+            second.manifest.checkpoint_name
+        );
+        for info in [&first, &second] {
+            assert!(Path::new(&info.checkpoint_dir).is_dir());
+            let manifest = CheckpointManifest::load(Path::new(&info.manifest_file)).unwrap();
+            assert_eq!(manifest, info.manifest);
+        }
+    }
+
+    #[test]
+    fn test_restore_checkpoint_rejects_mismatched_manifest() {
+        let config = make_config("mismatch");
+        let db = BalanceHistoryDB::open(config.clone(), BalanceHistoryDBMode::Normal).unwrap();
+        commit_block(&db, 10, 100);
+        let info = create_checkpoint(&config, &db).unwrap();
+        drop(db);
+
+        let manifest_file = PathBuf::from(&info.manifest_file);
+        let mut manifest = CheckpointManifest::load(&manifest_file).unwrap();
+        manifest.state_ref.latest_block_commit = "00".repeat(32);
+        manifest.save(&manifest_file).unwrap();
+
+        let target_config = make_config("mismatch_target");
+        let err = restore_checkpoint(&target_config, Path::new(&info.checkpoint_dir), None, false)
+            .unwrap_err();
+        assert!(err.contains("does not match manifest"));
+        assert!(!BalanceHistoryDB::db_path(&target_config).exists());
+    }
+}
@@ -1,5 +1,8 @@
 use super::block::BatchBlockProcessor;
-use crate::btc::{BTCClientRef, BTCClientType, create_btc_rpc_client, create_local_btc_client};
+use crate::btc::{
+    BTCClientRef, BTCClientType, create_btc_rpc_client, create_local_btc_client,
+    create_zmq_hinted_btc_client,
+};
 use crate::cache::{
     AddressBalanceCache, AddressBalanceCacheRef, MemoryCacheMonitor, MemoryCacheMonitorRef,
 };
@@ -7,11 +10,14 @@ use crate::cache::{UTXOCache, UTXOCacheRef};
 use crate::config::BalanceHistoryConfigRef;
 use crate::db::{BalanceHistoryDB, BalanceHistoryDBMode, BalanceHistoryDBRef, BalanceHistoryEntry};
 use crate::output::IndexOutputRef;
-use crate::service::BALANCE_HISTORY_STABLE_LAG;
+use crate::service::{
+    BALANCE_HISTORY_STABLE_LAG, SUBSCRIBE_NEW_STABLE_BLOCK, StableBlockEvent,
+    build_block_commit_info,
+};
 use std::collections::HashMap;
 use std::sync::{Arc, Mutex};
 use tokio::sync::oneshot;
-use usdb_util::USDBScriptHash;
+use usdb_util::{SubscriptionHubRef, USDBScriptHash, ZmqBlockSubscriberRef};
 
 // Use to keep the balance history result for a block
 type BlockHistoryResult = HashMap<USDBScriptHash, BalanceHistoryEntry>;
@@ -122,6 +128,8 @@ pub struct BalanceHistoryIndexer {
     db: BalanceHistoryDBRef,
     batch_block_processor: BatchBlockProcessor,
     output: IndexOutputRef,
+    subscription_hub: Option<SubscriptionHubRef>,
+    block_notifier: Option<ZmqBlockSubscriberRef>,
     shutdown_tx: Arc<Mutex<Option<oneshot::Sender<()>>>>,
     shutdown_rx: Arc<Mutex<Option<oneshot::Receiver<()>>>>,
 }
@@ -140,6 +148,7 @@ impl BalanceHistoryIndexer {
             }
         };
         output.println("Database initialized.");
+        db.ensure_utxo_set_hash_state_available()?;
 
         // Check synced block height
         let last_synced_block_height = db.get_btc_block_height()?;
@@ -239,6 +248,8 @@ impl BalanceHistoryIndexer {
             db,
             batch_block_processor,
             output,
+            subscription_hub: None,
+            block_notifier: None,
             shutdown_tx: Arc::new(Mutex::new(None)),
             shutdown_rx: Arc::new(Mutex::new(None)),
         })
@@ -265,6 +276,7 @@ impl BalanceHistoryIndexer {
             }
         };
         output.println("Database initialized.");
+        db.ensure_utxo_set_hash_state_available()?;
 
         let cache_strategy = match btc_client.get_type() {
             BTCClientType::LocalLoader => crate::cache::CacheStrategy::BestEffort,
@@ -293,11 +305,37 @@ impl BalanceHistoryIndexer {
             db,
             batch_block_processor,
             output,
+            subscription_hub: None,
+            block_notifier: None,
             shutdown_tx: Arc::new(Mutex::new(None)),
             shutdown_rx: Arc::new(Mutex::new(None)),
         })
     }
 
+    /// Attaches the hub that receives `subscribe_new_stable_block` events.
+    pub fn with_subscription_hub(mut self, hub: SubscriptionHubRef) -> Self {
+        self.subscription_hub = Some(hub);
+        self
+    }
+
+    /// Attaches a bitcoind ZMQ subscriber that wakes the idle loop on new blocks.
+    ///
+    /// With the RPC client, pushed `rawblock` bodies are also reused instead of refetched.
+    pub fn with_block_notifier(mut self, notifier: ZmqBlockSubscriberRef) -> Self {
+        if self.btc_client.get_type() == BTCClientType::RPC {
+            self.btc_client =
+                create_zmq_hinted_btc_client(self.btc_client.clone(), notifier.clone());
+            self.batch_block_processor = BatchBlockProcessor::new(
+                self.btc_client.clone(),
+                self.db.clone(),
+                self.utxo_cache.clone(),
+                self.balance_cache.clone(),
+            );
+        }
+        self.block_notifier = Some(notifier);
+        self
+    }
+
     pub fn get_latest_block_height(&self) -> Result<u32, String> {
         let rpc_latest_block_height = self
             .btc_client
@@ -545,6 +583,11 @@ impl BalanceHistoryIndexer {
     }
 
     fn wait_for_new_blocks(&self, last_height: u32) -> Result<u32, String> {
+        let mut seen_notification = self
+            .block_notifier
+            .as_ref()
+            .map(|notifier| notifier.notification_sequence())
+            .unwrap_or(0);
         loop {
             let latest_height = self.get_latest_block_height()?;
             if should_wake_for_chain_update(&self.db, &self.btc_client, last_height, latest_height)?
@@ -556,7 +599,16 @@ impl BalanceHistoryIndexer {
                 return Ok(latest_height);
             }
 
-            std::thread::sleep(std::time::Duration::from_secs(1));
+            // A ZMQ notification cuts the wait short; the timeout keeps polling as the fallback.
+            match &self.block_notifier {
+                Some(notifier) => {
+                    seen_notification = notifier.wait_for_notification(
+                        seen_notification,
+                        std::time::Duration::from_secs(1),
+                    );
+                }
+                None => std::thread::sleep(std::time::Duration::from_secs(1)),
+            }
 
             // Check for shutdown signal while waiting
             if self.check_shutdown() {
@@ -599,6 +651,11 @@ impl BalanceHistoryIndexer {
         self.utxo_cache.clear();
         self.balance_cache.clear();
 
+        self.publish_stable_block_event(&StableBlockEvent::Rollback {
+            from_height: current_height,
+            to_height: ancestor_height,
+        });
+
         Ok(ancestor_height)
     }
 
@@ -702,6 +759,7 @@ impl BalanceHistoryIndexer {
         let last_height = height_range.end - 1;
 
         self.prune_undo_journal_if_needed(batch_start_height, last_height)?;
+        self.publish_new_stable_blocks(height_range.clone());
 
         self.output.update_current_height(last_height as u64);
 
@@ -713,6 +771,49 @@ impl BalanceHistoryIndexer {
         Ok(last_height)
     }
 
+    // Push one event per persisted block. Commits are only read back when someone is
+    // listening, so catch-up sync without subscribers pays nothing.
+    fn publish_new_stable_blocks(&self, height_range: std::ops::Range<u32>) {
+        let Some(hub) = &self.subscription_hub else {
+            return;
+        };
+        if hub.subscriber_count(SUBSCRIBE_NEW_STABLE_BLOCK) == 0 {
+            return;
+        }
+
+        for block_height in height_range {
+            match self.db.get_block_commit(block_height) {
+                Ok(Some(commit)) => {
+                    self.publish_stable_block_event(&StableBlockEvent::NewStableBlock(
+                        build_block_commit_info(self.config.btc.network(), &commit),
+                    ))
+                }
+                Ok(None) => {
+                    warn!(
+                        "Missing block commit at height {} after batch persisted, skip publishing",
+                        block_height
+                    );
+                }
+                Err(e) => {
+                    warn!(
+                        "Failed to load block commit at height {} for publishing: {}",
+                        block_height, e
+                    );
+                }
+            }
+        }
+    }
+
+    // Subscription delivery is best effort and must never fail indexing.
+    fn publish_stable_block_event(&self, event: &StableBlockEvent) {
+        let Some(hub) = &self.subscription_hub else {
+            return;
+        };
+        if let Err(e) = hub.publish(SUBSCRIBE_NEW_STABLE_BLOCK, event) {
+            warn!("Failed to publish stable block event: {}", e);
+        }
+    }
+
     fn prune_undo_journal_if_needed(
         &self,
         batch_start_height: u32,
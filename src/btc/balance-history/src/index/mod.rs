@@ -1,10 +1,16 @@
 mod address;
 mod block;
+mod checkpoint;
 mod indexer;
+mod prune;
 mod snapshot;
 mod verify;
+mod verify_bitcoind;
 
 pub use address::*;
+pub use checkpoint::*;
 pub use indexer::*;
+pub use prune::*;
 pub use snapshot::*;
 pub use verify::*;
+pub use verify_bitcoind::*;
@@ -0,0 +1,147 @@
+use crate::config::BalanceHistoryConfigRef;
+use crate::db::BalanceHistoryDBRef;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+const HISTORY_PRUNE_POLL_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Background worker of the pruned mode: keeps `BALANCE_HISTORY_CF` compacted below a
+/// retention floor that trails the synced height by `history_retention_blocks`.
+pub struct HistoryPruner {
+    config: BalanceHistoryConfigRef,
+    db: BalanceHistoryDBRef,
+    stopped: AtomicBool,
+}
+
+impl HistoryPruner {
+    pub fn new(config: BalanceHistoryConfigRef, db: BalanceHistoryDBRef) -> Self {
+        Self {
+            config,
+            db,
+            stopped: AtomicBool::new(false),
+        }
+    }
+
+    /// Effective retention window. Never shorter than the undo window, so every rollback the
+    /// undo journal allows stays above the floor.
+    pub fn retention_blocks(&self) -> u32 {
+        self.config
+            .sync
+            .history_retention_blocks
+            .max(self.config.sync.undo_retention_blocks)
+    }
+
+    pub fn start(self: &Arc<Self>) {
+        let pruner = self.clone();
+        std::thread::spawn(move || {
+            while !pruner.stopped.load(Ordering::SeqCst) {
+                if let Err(e) = pruner.prune_if_needed() {
+                    warn!("Failed to prune balance history: {}", e);
+                }
+                std::thread::sleep(HISTORY_PRUNE_POLL_INTERVAL);
+            }
+            info!("Balance history pruner stopped.");
+        });
+    }
+
+    pub fn stop(&self) {
+        self.stopped.store(true, Ordering::SeqCst);
+    }
+
+    /// Advances the retention floor once it trails the target by at least the prune
+    /// interval. Returns the number of rows removed.
+    pub fn prune_if_needed(&self) -> Result<usize, String> {
+        let synced_height = self.db.get_btc_block_height()?;
+        let target_floor = synced_height
+            .saturating_add(1)
+            .saturating_sub(self.retention_blocks());
+        if target_floor == 0 {
+            return Ok(0);
+        }
+
+        let interval = self.config.sync.history_prune_interval_blocks.max(1);
+        let current_floor = self
+            .db
+            .get_balance_history_retained_from_height()?
+            .unwrap_or(0);
+        if target_floor < current_floor.saturating_add(interval) {
+            return Ok(0);
+        }
+
+        info!(
+            "Pruning balance history below height {}: synced_height={}, previous_floor={}",
+            target_floor, synced_height, current_floor
+        );
+        self.db
+            .prune_balance_history_before_height(target_floor, &|| {
+                self.stopped.load(Ordering::SeqCst)
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::BalanceHistoryConfig;
+    use crate::db::{BalanceHistoryDB, BalanceHistoryDBMode, BalanceHistoryEntry};
+    use bitcoincore_rpc::bitcoin::ScriptBuf;
+    use usdb_util::ToUSDBScriptHash;
+
+    #[test]
+    fn test_history_pruner_advances_floor_by_interval() {
+        let mut config = BalanceHistoryConfig::default();
+        let temp_dir = std::env::temp_dir().join("balance_history_history_pruner_test");
+        let _ = std::fs::remove_dir_all(&temp_dir);
+        std::fs::create_dir_all(&temp_dir).unwrap();
+        config.root_dir = temp_dir;
+        config.sync.undo_retention_blocks = 4;
+        config.sync.history_retention_blocks = 10;
+        config.sync.history_prune_interval_blocks = 5;
+        let config = Arc::new(config);
+
+        let db =
+            Arc::new(BalanceHistoryDB::open(config.clone(), BalanceHistoryDBMode::Normal).unwrap());
+        let script_hash = ScriptBuf::from(vec![7u8; 32]).to_usdb_script_hash();
+        let entries: Vec<BalanceHistoryEntry> = (1..=30u32)
+            .map(|height| BalanceHistoryEntry {
+                script_hash,
+                block_height: height,
+                delta: 1,
+                balance: height as u64,
+            })
+            .collect();
+        db.put_address_history_async(&entries).unwrap();
+
+        let pruner = HistoryPruner::new(config.clone(), db.clone());
+
+        // The floor would only be at height 4, short of the first interval.
+        db.put_btc_block_height(13).unwrap();
+        assert_eq!(pruner.prune_if_needed().unwrap(), 0);
+        assert_eq!(db.get_balance_history_retained_from_height().unwrap(), None);
+
+        db.put_btc_block_height(20).unwrap();
+        assert_eq!(pruner.prune_if_needed().unwrap(), 9);
+        assert_eq!(
+            db.get_balance_history_retained_from_height().unwrap(),
+            Some(11)
+        );
+
+        // Three more blocks do not reach the next interval.
+        db.put_btc_block_height(23).unwrap();
+        assert_eq!(pruner.prune_if_needed().unwrap(), 0);
+
+        db.put_btc_block_height(25).unwrap();
+        assert_eq!(pruner.prune_if_needed().unwrap(), 5);
+        assert_eq!(
+            db.get_balance_history_retained_from_height().unwrap(),
+            Some(16)
+        );
+        assert_eq!(
+            db.get_balance_at_block_height(&script_hash, 16)
+                .unwrap()
+                .balance,
+            16
+        );
+    }
+}
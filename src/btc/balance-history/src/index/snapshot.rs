@@ -10,8 +10,10 @@ use crate::snapshot_provenance::{
     SnapshotInstallOrigin, SnapshotInstallProvenance, SnapshotVerificationState,
 };
 use base64::Engine as _;
+use bitcoincore_rpc::bitcoin::OutPoint;
 use ed25519_dalek::{Signature, Signer, SigningKey, Verifier, VerifyingKey};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::path::{Path, PathBuf};
 use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::{Arc, Mutex};
@@ -20,8 +22,13 @@ use usdb_util::{USDBScriptHash, UTXOEntry};
 
 /// Version tag for the first external snapshot manifest sidecar format.
 pub const SNAPSHOT_MANIFEST_VERSION: &str = "balance-history-snapshot-manifest:v1";
+/// Version tag for delta snapshot manifests. Kept distinct from the full manifest version so
+/// installers without delta support reject delta files instead of installing partial state.
+pub const SNAPSHOT_DELTA_MANIFEST_VERSION: &str = "balance-history-snapshot-manifest:delta-v1";
 /// Detached signature scheme name used by signed snapshot manifests.
 pub const SNAPSHOT_SIGNATURE_SCHEME_ED25519: &str = "ed25519";
+/// Chunk size recorded in new manifests for verified ranged downloads.
+pub const SNAPSHOT_CHUNK_SIZE: u64 = 8 * 1024 * 1024;
 
 pub struct SnapshotIndexer {
     config: BalanceHistoryConfigRef,
@@ -55,17 +62,28 @@ impl SnapshotIndexer {
             return Err(msg);
         }
 
+        self.ensure_history_retained(target_block_height)?;
+
         // Historical balance snapshots are supported because the DB stores point-in-time
         // balance rows by height. UTXO snapshots are different: the current UTXO CF only
-        // contains live tip state, so exporting UTXOs for an older height would silently
-        // mix a historical block commit/state_ref with a tip UTXO view.
-        if with_utxo && target_block_height != last_synced_height {
-            let msg = format!(
-                "Historical UTXO snapshots are not supported: target block height {} must match last synced BTC block height {} when with_utxo=true",
-                target_block_height, last_synced_height
-            );
-            self.output.eprintln(&msg);
-            return Err(msg);
+        // contains live tip state, so an older height can only be exported when the UTXO
+        // history index covers it. Otherwise we would silently mix a historical block
+        // commit/state_ref with a tip UTXO view.
+        let historical_utxo = with_utxo && target_block_height != last_synced_height;
+        if historical_utxo {
+            let from_height = if self.db.is_utxo_history_enabled() {
+                self.db.get_utxo_history_from_height()?
+            } else {
+                None
+            };
+            if !from_height.is_some_and(|from_height| from_height <= target_block_height) {
+                let msg = format!(
+                    "Historical UTXO snapshots are not supported without UTXO history: target block height {} differs from last synced BTC block height {} and is not covered by sync.utxo_history (covered from {:?})",
+                    target_block_height, last_synced_height, from_height
+                );
+                self.output.eprintln(&msg);
+                return Err(msg);
+            }
         }
 
         self.output.start_load(0);
@@ -124,7 +142,12 @@ impl SnapshotIndexer {
 
             let generator = SnapshotGenerator::new(snapshot_db.clone(), self.output.clone());
             let cb = Arc::new(Box::new(generator.clone()) as Box<dyn SnapshotCallback>);
-            self.db.generate_utxo_snapshot_parallel(cb)?;
+            if historical_utxo {
+                self.db
+                    .generate_historical_utxo_snapshot_parallel(target_block_height, cb)?;
+            } else {
+                self.db.generate_utxo_snapshot_parallel(cb)?;
+            }
 
             let total_count = generator.utxo_count.load(Ordering::SeqCst);
             snapshot_meta.utxo_count = total_count;
@@ -189,7 +212,329 @@ impl SnapshotIndexer {
         }
 
         // Finally, update snapshot meta with counts
-        snapshot_db.lock().unwrap().update_meta(&snapshot_meta)?;
+        let db_path = self.finalize_snapshot_db(snapshot_db, &snapshot_meta)?;
+        self.write_snapshot_manifest(&db_path, target_block_height, None)
+    }
+
+    // Uses the same retention rule as the RPC queries, so a height the server reports as
+    // not retained is never exported either.
+    fn ensure_history_retained(&self, block_height: u32) -> Result<(), String> {
+        if let Some(retained_from_height) =
+            self.db.get_balance_history_pruned_floor(block_height)?
+        {
+            let msg = format!(
+                "Balance history at height {} is not retained: pruned below height {}",
+                block_height, retained_from_height
+            );
+            self.output.eprintln(&msg);
+            return Err(msg);
+        }
+
+        Ok(())
+    }
+
+    /// Generate a delta snapshot containing only the state changes between the base snapshot
+    /// height and target_block_height.
+    ///
+    /// The base must be a full snapshot produced from the same chain. The delta mirrors the
+    /// base's UTXO mode: when the base carries UTXOs, the UTXO changes are rebuilt from the
+    /// retained block undo bundles, so every height in the delta range must still have undo.
+    pub fn run_delta(&self, base: SnapshotData, target_block_height: u32) -> Result<(), String> {
+        info!(
+            "Starting delta snapshot generation from base {} up to block height {}",
+            base.file.display(),
+            target_block_height
+        );
+
+        let last_synced_height = self.db.get_btc_block_height()?;
+        if target_block_height > last_synced_height {
+            let msg = format!(
+                "Target block height {} is greater than last synced BTC block height {}",
+                target_block_height, last_synced_height
+            );
+            self.output.eprintln(&msg);
+            return Err(msg);
+        }
+
+        if !base.file.exists() {
+            let msg = format!("Base snapshot file {:?} does not exist", base.file);
+            self.output.eprintln(&msg);
+            return Err(msg);
+        }
+
+        // Hash before opening: SnapshotDB::open may touch the file through schema migrations.
+        let base_file_sha256 = SnapshotHash::calc_hash(&base.file)?;
+        let base_file_name = base
+            .file
+            .file_name()
+            .and_then(|value| value.to_str())
+            .ok_or_else(|| {
+                let msg = format!(
+                    "Failed to derive base snapshot file name from path {}",
+                    base.file.display()
+                );
+                self.output.eprintln(&msg);
+                msg
+            })?
+            .to_string();
+        let base_db = SnapshotDB::open(&base.file)?;
+        let base_meta = base_db.get_meta()?;
+        if base_meta.is_delta() {
+            let msg = format!(
+                "Base snapshot {} is itself a delta snapshot; delta snapshots must be generated against a full snapshot",
+                base.file.display()
+            );
+            self.output.eprintln(&msg);
+            return Err(msg);
+        }
+
+        let base_block_height = base_meta.block_height;
+        if target_block_height <= base_block_height {
+            let msg = format!(
+                "Target block height {} must be greater than base snapshot height {}",
+                target_block_height, base_block_height
+            );
+            self.output.eprintln(&msg);
+            return Err(msg);
+        }
+
+        // The delta carries every balance row in (base, target], so none of them may have
+        // been pruned.
+        self.ensure_history_retained(base_block_height)?;
+
+        let base_state_ref = build_historical_state_ref_at_height(
+            &self.config,
+            self.db.as_ref(),
+            base_block_height,
+        )?
+        .ok_or_else(|| {
+            let msg = format!(
+                "Failed to build historical state ref for base snapshot height {}",
+                base_block_height
+            );
+            self.output.eprintln(&msg);
+            msg
+        })?;
+        if let Some(manifest_file) = base.manifest_file.as_ref() {
+            let base_manifest = SnapshotManifest::load(manifest_file)?;
+            if base_manifest.is_delta()
+                || base_manifest.file_name != base_file_name
+                || base_manifest.file_sha256.to_ascii_lowercase()
+                    != base_file_sha256.to_ascii_lowercase()
+                || base_manifest.state_ref != base_state_ref
+            {
+                let msg = format!(
+                    "Base snapshot manifest {} does not describe base snapshot {} on the local chain",
+                    manifest_file.display(),
+                    base.file.display()
+                );
+                self.output.eprintln(&msg);
+                return Err(msg);
+            }
+        }
+
+        let with_utxo = base_meta.utxo_count > 0;
+        let utxo_delta = if with_utxo {
+            Some(self.collect_utxo_delta(base_block_height, target_block_height)?)
+        } else {
+            None
+        };
+
+        self.output.start_load(0);
+        self.output.println(&format!(
+            "Creating delta snapshot database at {} for heights ({}, {}], with_utxo={}",
+            self.config.root_dir.display(),
+            base_block_height,
+            target_block_height,
+            with_utxo
+        ));
+        let snapshot_db = SnapshotDB::open_delta_by_heights(
+            &self.config.root_dir,
+            base_block_height,
+            target_block_height,
+            true,
+        )
+        .map_err(|e| {
+            let msg = format!("Failed to create delta snapshot database: {}", e);
+            self.output.eprintln(&msg);
+            msg
+        })?;
+        let snapshot_db = Arc::new(Mutex::new(snapshot_db));
+        let mut snapshot_meta = SnapshotMeta::new_delta(base_block_height, target_block_height);
+
+        {
+            let total = self.db.get_history_balance_count()?;
+            self.output.update_load_total_count(total);
+            self.output.println(&format!(
+                "Will scan {} balance history entries for changes in ({}, {}]",
+                total, base_block_height, target_block_height
+            ));
+
+            let generator = SnapshotGenerator::new(snapshot_db.clone(), self.output.clone());
+            let cb = Arc::new(Box::new(generator.clone()) as Box<dyn SnapshotCallback>);
+            self.db.generate_balance_history_delta_snapshot_parallel(
+                base_block_height,
+                target_block_height,
+                cb,
+            )?;
+
+            snapshot_meta.balance_history_count =
+                generator.balance_history_count.load(Ordering::SeqCst);
+            self.output.println(&format!(
+                "Completed balance history delta generation, total entries: {}",
+                snapshot_meta.balance_history_count
+            ));
+        }
+
+        if let Some((added, removed)) = utxo_delta {
+            const BATCH_SIZE: usize = 1024 * 64;
+
+            let mut db = snapshot_db.lock().unwrap();
+            for chunk in added.chunks(BATCH_SIZE) {
+                db.put_utxo_entries(chunk)?;
+            }
+            for chunk in removed.chunks(BATCH_SIZE) {
+                db.put_utxo_removals(chunk)?;
+            }
+            snapshot_meta.utxo_count = added.len() as u64;
+            snapshot_meta.utxo_removal_count = removed.len() as u64;
+            self.output.println(&format!(
+                "Completed UTXO delta generation, added: {}, removed: {}",
+                snapshot_meta.utxo_count, snapshot_meta.utxo_removal_count
+            ));
+        }
+
+        {
+            let generator = SnapshotGenerator::new(snapshot_db.clone(), self.output.clone());
+            let cb = Arc::new(Box::new(generator.clone()) as Box<dyn SnapshotCallback>);
+            self.db.generate_block_commit_delta_snapshot(
+                base_block_height,
+                target_block_height,
+                cb,
+            )?;
+
+            snapshot_meta.block_commit_count = generator.block_commit_count.load(Ordering::SeqCst);
+            let expected = u64::from(target_block_height - base_block_height);
+            if snapshot_meta.block_commit_count != expected {
+                let msg = format!(
+                    "Delta snapshot expects {} block commits in ({}, {}], but found {}",
+                    expected,
+                    base_block_height,
+                    target_block_height,
+                    snapshot_meta.block_commit_count
+                );
+                self.output.eprintln(&msg);
+                return Err(msg);
+            }
+        }
+
+        // Script registry has no per-height state, so the delta carries every live entry the
+        // base snapshot does not already have.
+        {
+            let total = self.db.get_script_registry_count()?;
+            self.output.update_load_total_count(total);
+            self.output.println(&format!(
+                "Will diff {} script registry entries against the base snapshot",
+                total
+            ));
+
+            let generator = SnapshotGenerator::new(snapshot_db.clone(), self.output.clone());
+            let filter = ScriptRegistryDeltaFilter {
+                base_db: Mutex::new(base_db),
+                inner: generator.clone(),
+            };
+            let cb = Arc::new(Box::new(filter) as Box<dyn SnapshotCallback>);
+            self.db.generate_script_registry_snapshot_parallel(cb)?;
+
+            snapshot_meta.script_registry_count =
+                generator.script_registry_count.load(Ordering::SeqCst);
+            self.output.println(&format!(
+                "Completed script registry delta generation, new entries: {}",
+                snapshot_meta.script_registry_count
+            ));
+        }
+
+        let db_path = self.finalize_snapshot_db(snapshot_db, &snapshot_meta)?;
+        self.write_snapshot_manifest(
+            &db_path,
+            target_block_height,
+            Some(SnapshotManifestBase {
+                file_name: base_file_name,
+                file_sha256: base_file_sha256,
+                state_ref: base_state_ref,
+            }),
+        )
+    }
+
+    // Fold the block undo bundles in (base, target] into the net UTXO change set: outputs
+    // that exist at target but not at base, and base outputs spent by target.
+    fn collect_utxo_delta(
+        &self,
+        base_block_height: u32,
+        target_block_height: u32,
+    ) -> Result<(Vec<UTXOEntry>, Vec<OutPoint>), String> {
+        let first_required_height = base_block_height + 1;
+        match self.db.get_undo_retained_from_height()? {
+            Some(retained_from_height) if retained_from_height <= first_required_height => {}
+            retained_from_height => {
+                let msg = format!(
+                    "UTXO delta from height {} requires block undo from height {}, but undo is only retained from {:?}",
+                    base_block_height, first_required_height, retained_from_height
+                );
+                self.output.eprintln(&msg);
+                return Err(msg);
+            }
+        }
+
+        let mut changes: BTreeMap<OutPoint, Option<UTXOEntry>> = BTreeMap::new();
+        for height in first_required_height..=target_block_height {
+            let bundle = self.db.get_block_undo_bundle(height)?.ok_or_else(|| {
+                let msg = format!(
+                    "UTXO delta requires block undo bundle at height {}, but it is missing",
+                    height
+                );
+                self.output.eprintln(&msg);
+                msg
+            })?;
+
+            // Created before spent, so outputs created and spent inside the range cancel out.
+            for entry in bundle.created_utxos {
+                changes.insert(
+                    entry.outpoint,
+                    Some(UTXOEntry {
+                        outpoint: entry.outpoint,
+                        script_hash: entry.script_hash,
+                        value: entry.value,
+                    }),
+                );
+            }
+            for entry in bundle.spent_utxos {
+                if let Some(Some(_)) = changes.get(&entry.outpoint) {
+                    changes.remove(&entry.outpoint);
+                } else {
+                    changes.insert(entry.outpoint, None);
+                }
+            }
+        }
+
+        let mut added = Vec::new();
+        let mut removed = Vec::new();
+        for (outpoint, change) in changes {
+            match change {
+                Some(entry) => added.push(entry),
+                None => removed.push(outpoint),
+            }
+        }
+
+        Ok((added, removed))
+    }
+
+    fn finalize_snapshot_db(
+        &self,
+        snapshot_db: Arc<Mutex<SnapshotDB>>,
+        snapshot_meta: &SnapshotMeta,
+    ) -> Result<PathBuf, String> {
+        snapshot_db.lock().unwrap().update_meta(snapshot_meta)?;
         let snapshot_db = Arc::try_unwrap(snapshot_db).map_err(|_| {
             let msg =
                 "Failed to acquire exclusive ownership of generated snapshot DB before finalization"
@@ -215,7 +560,16 @@ impl SnapshotIndexer {
             db_path.display()
         ));
 
-        let file_hash = SnapshotHash::calc_hash(&db_path).map_err(|e| {
+        Ok(db_path)
+    }
+
+    fn write_snapshot_manifest(
+        &self,
+        db_path: &Path,
+        target_block_height: u32,
+        base: Option<SnapshotManifestBase>,
+    ) -> Result<(), String> {
+        let file_hash = SnapshotHash::calc_hash(db_path).map_err(|e| {
             let msg = format!(
                 "Failed to calculate snapshot hash for {}: {}",
                 db_path.display(),
@@ -242,24 +596,36 @@ impl SnapshotIndexer {
             .snapshot_signing_key_path()
             .map(|path| SnapshotSigningKeyFile::load(&path))
             .transpose()?;
-        let manifest = SnapshotManifest::build(
-            db_path
-                .file_name()
-                .and_then(|value| value.to_str())
-                .ok_or_else(|| {
-                    let msg = format!(
-                        "Failed to derive snapshot file name from path {}",
-                        db_path.display()
-                    );
-                    self.output.eprintln(&msg);
-                    msg
-                })?
-                .to_string(),
-            file_hash,
-            state_ref,
-            signing_key.as_ref().map(|key| key.key_id.clone()),
-        );
-        let manifest_path = manifest_path_for_snapshot_file(&db_path);
+        let file_name = db_path
+            .file_name()
+            .and_then(|value| value.to_str())
+            .ok_or_else(|| {
+                let msg = format!(
+                    "Failed to derive snapshot file name from path {}",
+                    db_path.display()
+                );
+                self.output.eprintln(&msg);
+                msg
+            })?
+            .to_string();
+        let signing_key_id = signing_key.as_ref().map(|key| key.key_id.clone());
+        let chunks = SnapshotChunkList::calc(db_path, SNAPSHOT_CHUNK_SIZE).map_err(|e| {
+            let msg = format!(
+                "Failed to calculate snapshot chunk hashes for {}: {}",
+                db_path.display(),
+                e
+            );
+            self.output.eprintln(&msg);
+            msg
+        })?;
+        let manifest = match base {
+            Some(base) => {
+                SnapshotManifest::build_delta(file_name, file_hash, state_ref, base, signing_key_id)
+            }
+            None => SnapshotManifest::build(file_name, file_hash, state_ref, signing_key_id),
+        }
+        .with_chunks(chunks);
+        let manifest_path = manifest_path_for_snapshot_file(db_path);
         manifest.save(&manifest_path).map_err(|e| {
             let msg = format!(
                 "Failed to write snapshot manifest {}: {}",
@@ -412,6 +778,55 @@ impl SnapshotCallback for SnapshotGenerator {
     }
 }
 
+// Forwards only the script registry entries missing from the base snapshot.
+struct ScriptRegistryDeltaFilter {
+    base_db: Mutex<SnapshotDB>,
+    inner: SnapshotGenerator,
+}
+
+impl SnapshotCallback for ScriptRegistryDeltaFilter {
+    fn on_balance_history_entries(
+        &self,
+        entries: &[BalanceHistoryEntry],
+        entries_processed: u64,
+    ) -> Result<(), String> {
+        self.inner
+            .on_balance_history_entries(entries, entries_processed)
+    }
+
+    fn on_utxo_entries(&self, entries: &[UTXOEntry], entries_processed: u64) -> Result<(), String> {
+        self.inner.on_utxo_entries(entries, entries_processed)
+    }
+
+    fn on_block_commit_entries(
+        &self,
+        entries: &[BlockCommitEntry],
+        entries_processed: u64,
+    ) -> Result<(), String> {
+        self.inner
+            .on_block_commit_entries(entries, entries_processed)
+    }
+
+    fn on_script_registry_entries(
+        &self,
+        entries: &[ScriptRegistryEntry],
+        entries_processed: u64,
+    ) -> Result<(), String> {
+        let mut missing = Vec::new();
+        {
+            let base_db = self.base_db.lock().unwrap();
+            for entry in entries {
+                if !base_db.has_script_registry_entry(&entry.script_hash)? {
+                    missing.push(entry.clone());
+                }
+            }
+        }
+
+        self.inner
+            .on_script_registry_entries(&missing, entries_processed)
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct SnapshotData {
     /// Path to the snapshot DB file to be installed.
@@ -431,6 +846,12 @@ pub struct SnapshotManifest {
     pub file_sha256: String,
     /// Exact historical consensus state expected after installation.
     pub state_ref: HistoricalSnapshotStateRef,
+    /// Base snapshot a delta snapshot must be applied on top of, absent for full snapshots.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub base: Option<SnapshotManifestBase>,
+    /// Per-chunk hashes used to verify ranged downloads, absent in older manifests.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub chunks: Option<SnapshotChunkList>,
     /// Detached signature scheme used for the optional sidecar signature file.
     #[serde(default)]
     pub signature_scheme: Option<String>,
@@ -442,6 +863,26 @@ pub struct SnapshotManifest {
     pub generated_at: Option<u64>,
 }
 
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct SnapshotManifestBase {
+    /// Base snapshot DB file basename the delta was generated against.
+    pub file_name: String,
+    /// Canonical SHA256 of the base snapshot DB file.
+    pub file_sha256: String,
+    /// Historical consensus state the installed DB must be at before the delta is applied.
+    pub state_ref: HistoricalSnapshotStateRef,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct SnapshotChunkList {
+    /// Size in bytes of every chunk except possibly the last one.
+    pub chunk_size: u64,
+    /// Total size in bytes of the snapshot DB file.
+    pub file_size: u64,
+    /// SHA256 of each chunk in file order.
+    pub sha256: Vec<String>,
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
 pub struct SnapshotSigningKeyFile {
     /// Logical signer identifier stored in manifests and matched during install.
@@ -452,8 +893,18 @@ pub struct SnapshotSigningKeyFile {
 
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
 pub struct SnapshotTrustedKeySet {
+    /// Number of distinct trusted signers a manifest needs. Older files without it mean 1.
+    #[serde(default = "default_snapshot_signature_threshold")]
+    pub threshold: u32,
     /// Trusted public keys accepted for signed snapshot manifests.
     pub keys: Vec<SnapshotTrustedPublicKey>,
+    /// Signers that must no longer be accepted, even if still listed in `keys`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub revoked_keys: Vec<SnapshotRevokedKey>,
+}
+
+fn default_snapshot_signature_threshold() -> u32 {
+    1
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
@@ -462,6 +913,60 @@ pub struct SnapshotTrustedPublicKey {
     pub key_id: String,
     /// Ed25519 public key encoded as base64(raw 32-byte bytes).
     pub public_key_base64: String,
+    /// First manifest `state_ref.block_height` this key may sign, inclusive.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub valid_from_height: Option<u32>,
+    /// Last manifest `state_ref.block_height` this key may sign, inclusive.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub valid_until_height: Option<u32>,
+    /// Start of the key's time window (unix seconds), inclusive. Both the manifest
+    /// `generated_at` and the installer's clock must be inside the window.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub valid_from_time: Option<u64>,
+    /// End of the key's time window (unix seconds), inclusive.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub valid_until_time: Option<u64>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct SnapshotRevokedKey {
+    /// Revoked signer identifier.
+    pub key_id: String,
+    /// Operator note on why the key was revoked.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
+/// Multi-signer detached signature sidecar. Manifests signed by one key at creation keep the
+/// plain base64 sidecar; co-signing converts it to this JSON form.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct SnapshotSignatureSet {
+    pub signatures: Vec<SnapshotSignatureEntry>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct SnapshotSignatureEntry {
+    /// Signer identifier matched against the trusted key set.
+    pub key_id: String,
+    /// Ed25519 signature over the manifest canonical bytes, base64(raw 64 bytes).
+    pub signature_base64: String,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct SnapshotRejectedSigner {
+    pub key_id: String,
+    pub reason: String,
+}
+
+/// Per-signer outcome of checking a signature sidecar against a trusted key set.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct SnapshotSignatureReport {
+    /// Distinct trusted signers required.
+    pub threshold: u32,
+    /// Signers whose signatures were accepted, in sidecar order.
+    pub accepted_signers: Vec<String>,
+    /// Signatures that did not count towards the threshold, with the reason.
+    pub rejected_signers: Vec<SnapshotRejectedSigner>,
 }
 
 impl SnapshotManifest {
@@ -476,6 +981,8 @@ impl SnapshotManifest {
             file_name,
             file_sha256,
             state_ref,
+            base: None,
+            chunks: None,
             signature_scheme: signing_key_id
                 .as_ref()
                 .map(|_| SNAPSHOT_SIGNATURE_SCHEME_ED25519.to_string()),
@@ -489,10 +996,32 @@ impl SnapshotManifest {
         }
     }
 
-    /// Returns the canonical JSON bytes covered by the detached signature.
-    pub fn canonical_bytes(&self) -> Result<Vec<u8>, String> {
-        serde_json::to_vec(self).map_err(|e| {
-            let msg = format!(
+    pub fn build_delta(
+        file_name: String,
+        file_sha256: String,
+        state_ref: HistoricalSnapshotStateRef,
+        base: SnapshotManifestBase,
+        signing_key_id: Option<String>,
+    ) -> Self {
+        let mut manifest = Self::build(file_name, file_sha256, state_ref, signing_key_id);
+        manifest.manifest_version = SNAPSHOT_DELTA_MANIFEST_VERSION.to_string();
+        manifest.base = Some(base);
+        manifest
+    }
+
+    pub fn is_delta(&self) -> bool {
+        self.base.is_some()
+    }
+
+    pub fn with_chunks(mut self, chunks: SnapshotChunkList) -> Self {
+        self.chunks = Some(chunks);
+        self
+    }
+
+    /// Returns the canonical JSON bytes covered by the detached signature.
+    pub fn canonical_bytes(&self) -> Result<Vec<u8>, String> {
+        serde_json::to_vec(self).map_err(|e| {
+            let msg = format!(
                 "Failed to serialize canonical snapshot manifest {} for signing: {}",
                 self.file_name, e
             );
@@ -516,12 +1045,17 @@ impl SnapshotManifest {
             error!("{}", msg);
             msg
         })?;
-        if manifest.manifest_version != SNAPSHOT_MANIFEST_VERSION {
+        let expected_version = if manifest.is_delta() {
+            SNAPSHOT_DELTA_MANIFEST_VERSION
+        } else {
+            SNAPSHOT_MANIFEST_VERSION
+        };
+        if manifest.manifest_version != expected_version {
             let msg = format!(
                 "Unsupported snapshot manifest version {} in {} (expected {})",
                 manifest.manifest_version,
                 path.display(),
-                SNAPSHOT_MANIFEST_VERSION
+                expected_version
             );
             error!("{}", msg);
             return Err(msg);
@@ -551,6 +1085,57 @@ impl SnapshotManifest {
     }
 }
 
+impl SnapshotChunkList {
+    /// Hashes `file` in `chunk_size` slices.
+    pub fn calc(file: &Path, chunk_size: u64) -> Result<Self, String> {
+        let file_size = std::fs::metadata(file)
+            .map_err(|e| {
+                let msg = format!(
+                    "Failed to read snapshot file metadata {}: {}",
+                    file.display(),
+                    e
+                );
+                error!("{}", msg);
+                msg
+            })?
+            .len();
+        let sha256 = SnapshotHash::calc_chunk_hashes(file, chunk_size)?;
+
+        Ok(Self {
+            chunk_size,
+            file_size,
+            sha256,
+        })
+    }
+
+    /// Byte range `[start, end)` covered by chunk `index`.
+    pub fn chunk_range(&self, index: usize) -> (u64, u64) {
+        let start = index as u64 * self.chunk_size;
+        let end = (start + self.chunk_size).min(self.file_size);
+        (start, end)
+    }
+
+    /// Rejects lists whose chunk count does not cover `file_size` exactly.
+    pub fn validate(&self) -> Result<(), String> {
+        let expected = if self.chunk_size == 0 {
+            None
+        } else {
+            Some(self.file_size.div_ceil(self.chunk_size))
+        };
+        if expected != Some(self.sha256.len() as u64) {
+            let msg = format!(
+                "Invalid snapshot chunk list: chunk_size={}, file_size={}, chunks={}",
+                self.chunk_size,
+                self.file_size,
+                self.sha256.len()
+            );
+            error!("{}", msg);
+            return Err(msg);
+        }
+        Ok(())
+    }
+}
+
 impl SnapshotSigningKeyFile {
     pub fn load(path: &Path) -> Result<Self, String> {
         let data = std::fs::read_to_string(path).map_err(|e| {
@@ -608,7 +1193,7 @@ impl SnapshotTrustedKeySet {
             error!("{}", msg);
             msg
         })?;
-        serde_json::from_str(&data).map_err(|e| {
+        let key_set: Self = serde_json::from_str(&data).map_err(|e| {
             let msg = format!(
                 "Failed to parse trusted snapshot keys {} as JSON: {}",
                 path.display(),
@@ -616,18 +1201,218 @@ impl SnapshotTrustedKeySet {
             );
             error!("{}", msg);
             msg
-        })
+        })?;
+        let signer_count = key_set.validate().map_err(|e| {
+            let msg = format!("Invalid trusted snapshot keys {}: {}", path.display(), e);
+            error!("{}", msg);
+            msg
+        })?;
+        info!(
+            "Loaded trusted snapshot keys {}: {}-of-{} signers required, {} revoked",
+            path.display(),
+            key_set.threshold,
+            signer_count,
+            key_set.revoked_keys.len()
+        );
+
+        Ok(key_set)
+    }
+
+    /// Rejects policies no manifest can satisfy. Returns the number of distinct
+    /// non-revoked public keys, i.e. the `n` of the k-of-n policy.
+    pub fn validate(&self) -> Result<usize, String> {
+        if self.threshold == 0 {
+            return Err("threshold must be at least 1".to_string());
+        }
+        let mut public_keys = self
+            .keys
+            .iter()
+            .filter(|entry| !self.is_revoked(&entry.key_id))
+            .map(|entry| entry.public_key_base64.as_str())
+            .collect::<Vec<_>>();
+        public_keys.sort_unstable();
+        public_keys.dedup();
+        if self.threshold as usize > public_keys.len() {
+            return Err(format!(
+                "threshold {} exceeds the {} distinct trusted public keys",
+                self.threshold,
+                public_keys.len()
+            ));
+        }
+
+        Ok(public_keys.len())
     }
 
+    /// Finds a trusted key by id. Revoked keys are never returned.
     pub fn find_verifying_key(&self, key_id: &str) -> Result<Option<VerifyingKey>, String> {
+        if self.is_revoked(key_id) {
+            return Ok(None);
+        }
         let Some(entry) = self.keys.iter().find(|entry| entry.key_id == key_id) else {
             return Ok(None);
         };
         entry.to_verifying_key().map(Some)
     }
+
+    pub fn is_revoked(&self, key_id: &str) -> bool {
+        self.revoked_keys.iter().any(|entry| entry.key_id == key_id)
+    }
+
+    /// Checks every signature over `payload` and reports which signers count towards the
+    /// threshold. Signers are counted once per distinct public key, so one key listed under
+    /// two ids cannot satisfy a 2-of-n policy alone. `now` is the installer's clock in unix
+    /// seconds, checked against key time windows together with `generated_at`.
+    pub fn verify_signatures(
+        &self,
+        payload: &[u8],
+        block_height: u32,
+        generated_at: Option<u64>,
+        now: u64,
+        signatures: &[(String, Signature)],
+    ) -> SnapshotSignatureReport {
+        let mut report = SnapshotSignatureReport {
+            threshold: self.threshold,
+            ..Default::default()
+        };
+        let mut accepted_keys = Vec::new();
+        for (key_id, signature) in signatures {
+            let ret = self.verify_one(
+                key_id,
+                signature,
+                payload,
+                block_height,
+                generated_at,
+                now,
+                &accepted_keys,
+            );
+            match ret {
+                Ok(verifying_key) => {
+                    accepted_keys.push(verifying_key);
+                    report.accepted_signers.push(key_id.clone());
+                }
+                Err(reason) => report.rejected_signers.push(SnapshotRejectedSigner {
+                    key_id: key_id.clone(),
+                    reason,
+                }),
+            }
+        }
+        report
+    }
+
+    fn verify_one(
+        &self,
+        key_id: &str,
+        signature: &Signature,
+        payload: &[u8],
+        block_height: u32,
+        generated_at: Option<u64>,
+        now: u64,
+        accepted_keys: &[VerifyingKey],
+    ) -> Result<VerifyingKey, String> {
+        if let Some(entry) = self
+            .revoked_keys
+            .iter()
+            .find(|entry| entry.key_id == key_id)
+        {
+            return Err(match &entry.reason {
+                Some(reason) => format!("key revoked: {}", reason),
+                None => "key revoked".to_string(),
+            });
+        }
+        let entry = self
+            .keys
+            .iter()
+            .find(|entry| entry.key_id == key_id)
+            .ok_or_else(|| "key not trusted".to_string())?;
+        entry.check_validity(block_height, generated_at, now)?;
+        let verifying_key = entry.to_verifying_key()?;
+        if accepted_keys.contains(&verifying_key) {
+            return Err("public key already counted for another signer".to_string());
+        }
+        verifying_key
+            .verify(payload, signature)
+            .map_err(|e| format!("signature verification failed: {}", e))?;
+        Ok(verifying_key)
+    }
+}
+
+impl SnapshotSignatureReport {
+    pub fn is_satisfied(&self) -> bool {
+        self.accepted_signers.len() as u64 >= self.threshold as u64
+    }
+
+    pub fn ensure_satisfied(&self, subject: &str) -> Result<(), String> {
+        if self.is_satisfied() {
+            return Ok(());
+        }
+        let rejected = self
+            .rejected_signers
+            .iter()
+            .map(|signer| format!("{} ({})", signer.key_id, signer.reason))
+            .collect::<Vec<_>>();
+        let msg = format!(
+            "Snapshot signature threshold not met for {}: accepted {}/{} signers {:?}, rejected {:?}",
+            subject,
+            self.accepted_signers.len(),
+            self.threshold,
+            self.accepted_signers,
+            rejected
+        );
+        error!("{}", msg);
+        Err(msg)
+    }
 }
 
 impl SnapshotTrustedPublicKey {
+    /// Rejects manifests outside this key's validity window. A time window needs
+    /// `generated_at` in the manifest and is also checked against the installer's clock
+    /// `now`, because the signer chooses `generated_at` and an expired key could otherwise
+    /// keep signing backdated manifests.
+    pub fn check_validity(
+        &self,
+        block_height: u32,
+        generated_at: Option<u64>,
+        now: u64,
+    ) -> Result<(), String> {
+        if self
+            .valid_from_height
+            .is_some_and(|from| block_height < from)
+            || self
+                .valid_until_height
+                .is_some_and(|until| block_height > until)
+        {
+            return Err(format!(
+                "block height {} outside key validity window {:?}..={:?}",
+                block_height, self.valid_from_height, self.valid_until_height
+            ));
+        }
+        if self.valid_from_time.is_none() && self.valid_until_time.is_none() {
+            return Ok(());
+        }
+        if self.valid_from_time.is_some_and(|from| now < from)
+            || self.valid_until_time.is_some_and(|until| now > until)
+        {
+            return Err(format!(
+                "install time {} outside key validity window {:?}..={:?}",
+                now, self.valid_from_time, self.valid_until_time
+            ));
+        }
+        let Some(generated_at) = generated_at else {
+            return Err("manifest generated_at is required by key validity window".to_string());
+        };
+        if self.valid_from_time.is_some_and(|from| generated_at < from)
+            || self
+                .valid_until_time
+                .is_some_and(|until| generated_at > until)
+        {
+            return Err(format!(
+                "generated_at {} outside key validity window {:?}..={:?}",
+                generated_at, self.valid_from_time, self.valid_until_time
+            ));
+        }
+        Ok(())
+    }
+
     pub fn to_verifying_key(&self) -> Result<VerifyingKey, String> {
         let raw = base64::engine::general_purpose::STANDARD
             .decode(self.public_key_base64.as_bytes())
@@ -673,7 +1458,8 @@ pub fn signature_path_for_manifest_file(file: &Path) -> PathBuf {
     path
 }
 
-fn save_signature_file(path: &Path, signature: &Signature) -> Result<(), String> {
+/// Writes one detached Ed25519 signature sidecar as base64(raw 64-byte signature).
+pub fn save_signature_file(path: &Path, signature: &Signature) -> Result<(), String> {
     let encoded = base64::engine::general_purpose::STANDARD.encode(signature.to_bytes());
     std::fs::write(path, encoded).map_err(|e| {
         let msg = format!(
@@ -686,16 +1472,137 @@ fn save_signature_file(path: &Path, signature: &Signature) -> Result<(), String>
     })
 }
 
-fn load_signature_file(path: &Path) -> Result<Signature, String> {
-    let data = std::fs::read_to_string(path).map_err(|e| {
+/// Loads one detached Ed25519 signature sidecar written by `save_signature_file`.
+pub fn load_signature_file(path: &Path) -> Result<Signature, String> {
+    let data = read_signature_sidecar(path)?;
+    decode_signature(&data, path)
+}
+
+/// Loads a signature sidecar in either form as `(key_id, signature)` pairs. The plain form
+/// carries no signer, so it is attributed to `default_key_id` (the manifest `signing_key_id`).
+pub fn load_signature_set_file(
+    path: &Path,
+    default_key_id: Option<&str>,
+) -> Result<Vec<(String, Signature)>, String> {
+    let data = read_signature_sidecar(path)?;
+    if !data.trim_start().starts_with('{') {
+        let key_id = default_key_id.ok_or_else(|| {
+            let msg = format!(
+                "Snapshot signature sidecar {} has no signer and the manifest has no signing_key_id",
+                path.display()
+            );
+            error!("{}", msg);
+            msg
+        })?;
+        return Ok(vec![(key_id.to_string(), decode_signature(&data, path)?)]);
+    }
+
+    let set: SnapshotSignatureSet = serde_json::from_str(&data).map_err(|e| {
         let msg = format!(
-            "Failed to read snapshot signature sidecar {}: {}",
+            "Failed to parse snapshot signature set {} as JSON: {}",
             path.display(),
             e
         );
         error!("{}", msg);
         msg
     })?;
+    set.signatures
+        .iter()
+        .map(|entry| {
+            decode_signature(&entry.signature_base64, path)
+                .map(|signature| (entry.key_id.clone(), signature))
+        })
+        .collect()
+}
+
+/// Adds or replaces `key_id`'s signature in the sidecar and returns the signature count.
+/// The sidecar is always written in the multi-signer JSON form.
+pub fn add_signature_to_file(
+    path: &Path,
+    default_key_id: Option<&str>,
+    key_id: &str,
+    signature: &Signature,
+) -> Result<usize, String> {
+    let mut signatures = if path.exists() {
+        load_signature_set_file(path, default_key_id)?
+    } else {
+        Vec::new()
+    };
+    signatures.retain(|(existing, _)| existing != key_id);
+    signatures.push((key_id.to_string(), *signature));
+
+    let set = SnapshotSignatureSet {
+        signatures: signatures
+            .iter()
+            .map(|(key_id, signature)| SnapshotSignatureEntry {
+                key_id: key_id.clone(),
+                signature_base64: base64::engine::general_purpose::STANDARD
+                    .encode(signature.to_bytes()),
+            })
+            .collect(),
+    };
+    let data = serde_json::to_string_pretty(&set).map_err(|e| {
+        let msg = format!(
+            "Failed to serialize snapshot signature set {}: {}",
+            path.display(),
+            e
+        );
+        error!("{}", msg);
+        msg
+    })?;
+    std::fs::write(path, data).map_err(|e| {
+        let msg = format!(
+            "Failed to write snapshot signature sidecar {}: {}",
+            path.display(),
+            e
+        );
+        error!("{}", msg);
+        msg
+    })?;
+    Ok(set.signatures.len())
+}
+
+/// Co-signs an existing snapshot manifest with `signing_key`, keeping earlier signatures.
+/// Returns the number of signatures now in the sidecar.
+pub fn cosign_snapshot_manifest(
+    manifest_path: &Path,
+    signing_key: &SnapshotSigningKeyFile,
+) -> Result<usize, String> {
+    let manifest = SnapshotManifest::load(manifest_path)?;
+    if manifest.signature_scheme.as_deref() != Some(SNAPSHOT_SIGNATURE_SCHEME_ED25519) {
+        let msg = format!(
+            "Snapshot manifest {} declares signature scheme {:?}, only {} manifests can be co-signed",
+            manifest_path.display(),
+            manifest.signature_scheme,
+            SNAPSHOT_SIGNATURE_SCHEME_ED25519
+        );
+        error!("{}", msg);
+        return Err(msg);
+    }
+    let signature = signing_key
+        .to_signing_key()?
+        .sign(&manifest.canonical_bytes()?);
+    add_signature_to_file(
+        &signature_path_for_manifest_file(manifest_path),
+        manifest.signing_key_id.as_deref(),
+        &signing_key.key_id,
+        &signature,
+    )
+}
+
+fn read_signature_sidecar(path: &Path) -> Result<String, String> {
+    std::fs::read_to_string(path).map_err(|e| {
+        let msg = format!(
+            "Failed to read snapshot signature sidecar {}: {}",
+            path.display(),
+            e
+        );
+        error!("{}", msg);
+        msg
+    })
+}
+
+fn decode_signature(data: &str, path: &Path) -> Result<Signature, String> {
     let raw = base64::engine::general_purpose::STANDARD
         .decode(data.trim().as_bytes())
         .map_err(|e| {
@@ -719,6 +1626,52 @@ fn load_signature_file(path: &Path) -> Result<Signature, String> {
     Ok(Signature::from_bytes(&signature_bytes))
 }
 
+// Outcome of the manifest/signature/file-hash checks shared by full and delta installs.
+struct VerifiedSnapshotSource {
+    trust_mode: SnapshotTrustMode,
+    manifest: Option<SnapshotManifest>,
+    signature_present: bool,
+    // Set only once the signature threshold was met in signed mode.
+    signature_report: Option<SnapshotSignatureReport>,
+}
+
+impl VerifiedSnapshotSource {
+    fn to_provenance(&self, meta: &SnapshotMeta) -> SnapshotInstallProvenance {
+        let manifest = self.manifest.as_ref();
+        SnapshotInstallProvenance {
+            origin: SnapshotInstallOrigin::SnapshotInstall,
+            trust_mode: self.trust_mode.clone(),
+            verification_state: if self.signature_report.is_some() {
+                SnapshotVerificationState::SignatureVerified
+            } else if manifest.is_some() {
+                SnapshotVerificationState::ManifestVerified
+            } else {
+                SnapshotVerificationState::ManifestMissing
+            },
+            manifest_present: manifest.is_some(),
+            manifest_verified: manifest.is_some(),
+            signature_present: self.signature_present,
+            signature_verified: self.signature_report.is_some(),
+            manifest_version: manifest.map(|value| value.manifest_version.clone()),
+            signature_scheme: manifest.and_then(|value| value.signature_scheme.clone()),
+            signing_key_id: manifest.and_then(|value| value.signing_key_id.clone()),
+            accepted_signing_key_ids: self
+                .signature_report
+                .as_ref()
+                .map(|report| report.accepted_signers.clone())
+                .unwrap_or_default(),
+            signature_threshold: self
+                .signature_report
+                .as_ref()
+                .map(|report| report.threshold),
+            snapshot_file_sha256: manifest.map(|value| value.file_sha256.clone()),
+            snapshot_id: manifest.map(|value| value.state_ref.snapshot_id.clone()),
+            installed_block_height: meta.block_height,
+            base_block_height: meta.base_block_height,
+        }
+    }
+}
+
 pub struct SnapshotInstaller {
     config: BalanceHistoryConfigRef,
     db: BalanceHistoryDBRef,
@@ -737,7 +1690,81 @@ impl SnapshotInstaller {
     pub fn install(self, data: SnapshotData) -> Result<(), String> {
         info!("Starting snapshot installation from {:?}", data,);
 
+        // Snapshots carry neither the per-coin MuHash digests nor the accumulator, and they
+        // cannot be rebuilt from script hashes and values, so the next block would fail.
+        if self.db.is_utxo_set_hash_enabled() {
+            let msg = format!(
+                "Snapshot install does not support sync.utxo_set_hash: {} carries no UTXO set hash state. Disable sync.utxo_set_hash or sync from genesis with it enabled.",
+                data.file.display()
+            );
+            error!("{}", msg);
+            return Err(msg);
+        }
+
         self.output.start_load(0);
+        let source = self.verify_snapshot_source(&data)?;
+
+        let snapshot_db = SnapshotDB::open(&data.file).map_err(|e| {
+            let msg = format!("Failed to open snapshot database: {}", e);
+            error!("{}", msg);
+            msg
+        })?;
+
+        let meta = snapshot_db.get_meta().map_err(|e| {
+            let msg = format!("Failed to read snapshot metadata: {}", e);
+            error!("{}", msg);
+            msg
+        })?;
+
+        if let Some(manifest) = source.manifest.as_ref() {
+            if manifest.state_ref.block_height != meta.block_height {
+                let msg = format!(
+                    "Snapshot manifest block height mismatch: manifest expects {}, snapshot meta reports {}",
+                    manifest.state_ref.block_height, meta.block_height
+                );
+                error!("{}", msg);
+                return Err(msg);
+            }
+
+            let manifest_base_height = manifest
+                .base
+                .as_ref()
+                .map(|base| base.state_ref.block_height);
+            if manifest_base_height != meta.base_block_height {
+                let msg = format!(
+                    "Snapshot manifest base height mismatch: manifest expects {:?}, snapshot meta reports {:?}",
+                    manifest_base_height, meta.base_block_height
+                );
+                error!("{}", msg);
+                return Err(msg);
+            }
+        }
+
+        info!("Snapshot metadata: {:?}", meta);
+        self.output.println(&format!(
+            "Snapshot generated at block height {}, base height {:?}, balance history entries: {}, UTXO entries: {}, UTXO removals: {}, block commits: {}, script registry entries: {}",
+            meta.block_height,
+            meta.base_block_height,
+            meta.balance_history_count,
+            meta.utxo_count,
+            meta.utxo_removal_count,
+            meta.block_commit_count,
+            meta.script_registry_count
+        ));
+
+        if meta.is_delta() {
+            self.install_delta(snapshot_db, meta, source)
+        } else {
+            self.install_full(snapshot_db, meta, source)
+        }
+    }
+
+    // Check trust mode requirements, the detached signature and the snapshot file hash
+    // before any snapshot content is read.
+    fn verify_snapshot_source(
+        &self,
+        data: &SnapshotData,
+    ) -> Result<VerifiedSnapshotSource, String> {
         let trust_mode = self.config.snapshot.trust_mode.clone();
 
         let manifest = if let Some(manifest_file) = data.manifest_file.as_ref() {
@@ -773,7 +1800,7 @@ impl SnapshotInstaller {
             .as_ref()
             .map(|path| path.exists())
             .unwrap_or(false);
-        let signature_verified = if matches!(trust_mode, SnapshotTrustMode::Signed) {
+        let signature_report = if matches!(trust_mode, SnapshotTrustMode::Signed) {
             let manifest = manifest.as_ref().ok_or_else(|| {
                 let msg = format!(
                     "Signed snapshot install requires a manifest for {}",
@@ -800,14 +1827,6 @@ impl SnapshotInstaller {
                 error!("{}", msg);
                 return Err(msg);
             }
-            let signing_key_id = manifest.signing_key_id.as_deref().ok_or_else(|| {
-                let msg = format!(
-                    "Signed snapshot install requires manifest.signing_key_id for {}",
-                    data.file.display()
-                );
-                error!("{}", msg);
-                msg
-            })?;
             let signature_path = signature_path.as_ref().ok_or_else(|| {
                 let msg = format!(
                     "Signed snapshot install requires a signature sidecar path for {}",
@@ -833,36 +1852,35 @@ impl SnapshotInstaller {
                 msg
             })?;
             let trusted_keys = SnapshotTrustedKeySet::load(&trusted_keys_path)?;
-            let verifying_key = trusted_keys
-                .find_verifying_key(signing_key_id)?
-                .ok_or_else(|| {
-                    let msg = format!(
-                        "Snapshot signer {} is not trusted by {}",
-                        signing_key_id,
-                        trusted_keys_path.display()
-                    );
-                    error!("{}", msg);
-                    msg
-                })?;
-            let signature = load_signature_file(signature_path)?;
-            verifying_key
-                .verify(&manifest.canonical_bytes()?, &signature)
-                .map_err(|e| {
-                    let msg = format!(
-                        "Snapshot signature verification failed for manifest {} signed by {}: {}",
-                        data.manifest_file
-                            .as_ref()
-                            .map(|path| path.display().to_string())
-                            .unwrap_or_else(|| "<unknown>".to_string()),
-                        signing_key_id,
-                        e
-                    );
-                    error!("{}", msg);
-                    msg
-                })?;
-            true
+            let signatures =
+                load_signature_set_file(signature_path, manifest.signing_key_id.as_deref())?;
+            let now = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs();
+            let report = trusted_keys.verify_signatures(
+                &manifest.canonical_bytes()?,
+                manifest.state_ref.block_height,
+                manifest.generated_at,
+                now,
+                &signatures,
+            );
+            for signer in &report.rejected_signers {
+                warn!(
+                    "Snapshot signer {} rejected for {}: {}",
+                    signer.key_id,
+                    signature_path.display(),
+                    signer.reason
+                );
+            }
+            report.ensure_satisfied(&format!(
+                "manifest {} with trusted keys {}",
+                signature_path.display(),
+                trusted_keys_path.display()
+            ))?;
+            Some(report)
         } else {
-            false
+            None
         };
 
         // First check hash is correct
@@ -915,51 +1933,175 @@ impl SnapshotInstaller {
                 .println("No snapshot file hash provided, skipping verification");
         }
 
-        let snapshot_db = SnapshotDB::open(&data.file).map_err(|e| {
-            let msg = format!("Failed to open snapshot database: {}", e);
+        Ok(VerifiedSnapshotSource {
+            trust_mode,
+            manifest,
+            signature_present,
+            signature_report,
+        })
+    }
+
+    fn install_full(
+        self,
+        snapshot_db: SnapshotDB,
+        meta: SnapshotMeta,
+        source: VerifiedSnapshotSource,
+    ) -> Result<(), String> {
+        let staging_root = self.prepare_staging_root()?;
+        let staging_config = self.make_staging_config(staging_root.clone());
+        let staging_db = BalanceHistoryDB::open(staging_config, BalanceHistoryDBMode::BestEffort)
+            .map_err(|e| {
+            let msg = format!("Failed to initialize staging database: {}", e);
+            self.output.println(&msg);
+            msg
+        })?;
+
+        // Install into staging DB first, then atomically switch the live DB directory.
+        self.install_balance_history_snapshot(&staging_db, &snapshot_db, &meta)?;
+        self.install_utxo_snapshot(&staging_db, &snapshot_db, &meta)?;
+        self.install_block_commit_snapshot(&staging_db, &snapshot_db, &meta)?;
+        self.install_script_registry_snapshot(&staging_db, &snapshot_db, &meta)?;
+
+        staging_db
+            .put_btc_block_height(meta.block_height)
+            .map_err(|e| {
+                let msg = format!("Failed to update BTC block height: {}", e);
+                self.output.println(&msg);
+                msg
+            })?;
+        // The staged root lets validate_staged_manifest check the balance state without replay.
+        staging_db
+            .commit_balance_state_root(meta.block_height)
+            .map_err(|e| {
+                let msg = format!("Failed to commit balance state root: {}", e);
+                self.output.println(&msg);
+                msg
+            })?;
+        staging_db.flush_all().map_err(|e| {
+            let msg = format!("Failed to flush staging database: {}", e);
+            self.output.println(&msg);
+            msg
+        })?;
+
+        if let Some(manifest) = source.manifest.as_ref() {
+            self.validate_staged_manifest(
+                &staging_db,
+                &meta,
+                manifest,
+                source.signature_report.as_ref(),
+            )?;
+        }
+        let provenance = source.to_provenance(&meta);
+        staging_db
+            .put_snapshot_install_provenance(&provenance)
+            .map_err(|e| {
+                let msg = format!(
+                    "Failed to persist snapshot install provenance at block height {}: {}",
+                    meta.block_height, e
+                );
+                error!("{}", msg);
+                msg
+            })?;
+        staging_db.flush_all().map_err(|e| {
+            let msg = format!(
+                "Failed to flush snapshot install provenance metadata at block height {}: {}",
+                meta.block_height, e
+            );
             error!("{}", msg);
             msg
         })?;
 
-        let meta = snapshot_db.get_meta().map_err(|e| {
-            let msg = format!("Failed to read snapshot metadata: {}", e);
+        let output = self.output.clone();
+        self.swap_staging_db_into_place(staging_db, staging_root)?;
+
+        output.println(&format!(
+            "Completed snapshot installation up to block height {}",
+            meta.block_height
+        ));
+        output.finish_load();
+
+        Ok(())
+    }
+
+    // Apply a delta snapshot on top of the live DB. The live DB is checkpointed into a staging
+    // directory first, so a failed apply or validation never touches the live DB.
+    fn install_delta(
+        self,
+        snapshot_db: SnapshotDB,
+        meta: SnapshotMeta,
+        source: VerifiedSnapshotSource,
+    ) -> Result<(), String> {
+        let base_block_height = meta.base_block_height.ok_or_else(|| {
+            let msg =
+                "Delta snapshot install requires a base block height in snapshot meta".to_string();
             error!("{}", msg);
             msg
         })?;
 
-        if let Some(manifest) = manifest.as_ref() {
-            if manifest.state_ref.block_height != meta.block_height {
+        let live_height = self.db.get_btc_block_height()?;
+        if live_height != base_block_height {
+            let msg = format!(
+                "Delta snapshot applies on top of block height {}, but the live DB is at block height {}",
+                base_block_height, live_height
+            );
+            error!("{}", msg);
+            return Err(msg);
+        }
+
+        if let Some(manifest) = source.manifest.as_ref() {
+            let base = manifest.base.as_ref().ok_or_else(|| {
                 let msg = format!(
-                    "Snapshot manifest block height mismatch: manifest expects {}, snapshot meta reports {}",
-                    manifest.state_ref.block_height, meta.block_height
+                    "Delta snapshot manifest {} does not describe its base snapshot",
+                    manifest.file_name
+                );
+                error!("{}", msg);
+                msg
+            })?;
+            let live_state_ref = build_historical_state_ref_at_height(
+                &self.config,
+                self.db.as_ref(),
+                base_block_height,
+            )?;
+            if live_state_ref.as_ref() != Some(&base.state_ref) {
+                let msg = format!(
+                    "Live DB state ref at base height {} does not match delta snapshot base {}: expected {:?}, got {:?}",
+                    base_block_height, base.file_name, base.state_ref, live_state_ref
                 );
                 error!("{}", msg);
                 return Err(msg);
             }
-        }
 
-        info!("Snapshot metadata: {:?}", meta);
-        self.output.println(&format!(
-            "Snapshot generated at block height {}, balance history entries: {}, UTXO entries: {}, block commits: {}, script registry entries: {}",
-            meta.block_height,
-            meta.balance_history_count,
-            meta.utxo_count,
-            meta.block_commit_count,
-            meta.script_registry_count
-        ));
+            // A verified delta must not lift an unverified snapshot install to verified state.
+            if let Some(provenance) = self.db.get_snapshot_install_provenance()? {
+                if !provenance.is_consensus_verified() {
+                    let msg = format!(
+                        "Live DB was installed from an unverified snapshot at height {}; reinstall a verified base before applying delta {}",
+                        provenance.installed_block_height, manifest.file_name
+                    );
+                    error!("{}", msg);
+                    return Err(msg);
+                }
+            }
+        }
 
         let staging_root = self.prepare_staging_root()?;
         let staging_config = self.make_staging_config(staging_root.clone());
+        self.output.println(&format!(
+            "Checkpointing live DB at block height {} into {}",
+            base_block_height,
+            staging_config.db_dir().display()
+        ));
+        self.db.create_checkpoint(&staging_config.db_dir())?;
         let staging_db = BalanceHistoryDB::open(staging_config, BalanceHistoryDBMode::BestEffort)
             .map_err(|e| {
-            let msg = format!("Failed to initialize staging database: {}", e);
+            let msg = format!("Failed to open staging database checkpoint: {}", e);
             self.output.println(&msg);
             msg
         })?;
 
-        // Install into staging DB first, then atomically switch the live DB directory.
         self.install_balance_history_snapshot(&staging_db, &snapshot_db, &meta)?;
         self.install_utxo_snapshot(&staging_db, &snapshot_db, &meta)?;
+        self.install_utxo_removals(&staging_db, &snapshot_db, &meta)?;
         self.install_block_commit_snapshot(&staging_db, &snapshot_db, &meta)?;
         self.install_script_registry_snapshot(&staging_db, &snapshot_db, &meta)?;
 
@@ -970,49 +2112,42 @@ impl SnapshotInstaller {
                 self.output.println(&msg);
                 msg
             })?;
+        // The delta carries no undo bundles, so rollback below the delta height is unsupported.
+        staging_db
+            .put_rollback_supported_from_height(meta.block_height + 1)
+            .map_err(|e| {
+                let msg = format!("Failed to update rollback boundary: {}", e);
+                self.output.println(&msg);
+                msg
+            })?;
+        // The staged root lets validate_staged_manifest check the balance state without replay.
+        staging_db
+            .commit_balance_state_root(meta.block_height)
+            .map_err(|e| {
+                let msg = format!("Failed to commit balance state root: {}", e);
+                self.output.println(&msg);
+                msg
+            })?;
         staging_db.flush_all().map_err(|e| {
             let msg = format!("Failed to flush staging database: {}", e);
             self.output.println(&msg);
             msg
         })?;
 
-        if let Some(manifest) = manifest.as_ref() {
-            self.validate_staged_manifest(&staging_db, &meta, manifest)?;
+        if let Some(manifest) = source.manifest.as_ref() {
+            self.validate_staged_manifest(
+                &staging_db,
+                &meta,
+                manifest,
+                source.signature_report.as_ref(),
+            )?;
         }
-        let provenance = SnapshotInstallProvenance {
-            origin: SnapshotInstallOrigin::SnapshotInstall,
-            trust_mode,
-            verification_state: if signature_verified {
-                SnapshotVerificationState::SignatureVerified
-            } else if manifest.is_some() {
-                SnapshotVerificationState::ManifestVerified
-            } else {
-                SnapshotVerificationState::ManifestMissing
-            },
-            manifest_present: manifest.is_some(),
-            manifest_verified: manifest.is_some(),
-            signature_present,
-            signature_verified,
-            manifest_version: manifest
-                .as_ref()
-                .map(|value| value.manifest_version.clone()),
-            signature_scheme: manifest
-                .as_ref()
-                .and_then(|value| value.signature_scheme.clone()),
-            signing_key_id: manifest
-                .as_ref()
-                .and_then(|value| value.signing_key_id.clone()),
-            snapshot_file_sha256: manifest.as_ref().map(|value| value.file_sha256.clone()),
-            snapshot_id: manifest
-                .as_ref()
-                .map(|value| value.state_ref.snapshot_id.clone()),
-            installed_block_height: meta.block_height,
-        };
+        let provenance = source.to_provenance(&meta);
         staging_db
             .put_snapshot_install_provenance(&provenance)
             .map_err(|e| {
                 let msg = format!(
-                    "Failed to persist snapshot install provenance at block height {}: {}",
+                    "Failed to persist delta snapshot install provenance at block height {}: {}",
                     meta.block_height, e
                 );
                 error!("{}", msg);
@@ -1020,7 +2155,7 @@ impl SnapshotInstaller {
             })?;
         staging_db.flush_all().map_err(|e| {
             let msg = format!(
-                "Failed to flush snapshot install provenance metadata at block height {}: {}",
+                "Failed to flush delta snapshot install provenance metadata at block height {}: {}",
                 meta.block_height, e
             );
             error!("{}", msg);
@@ -1031,8 +2166,8 @@ impl SnapshotInstaller {
         self.swap_staging_db_into_place(staging_db, staging_root)?;
 
         output.println(&format!(
-            "Completed snapshot installation up to block height {}",
-            meta.block_height
+            "Completed delta snapshot installation from block height {} up to {}",
+            base_block_height, meta.block_height
         ));
         output.finish_load();
 
@@ -1044,6 +2179,7 @@ impl SnapshotInstaller {
         staging_db: &BalanceHistoryDB,
         meta: &SnapshotMeta,
         manifest: &SnapshotManifest,
+        signature_report: Option<&SnapshotSignatureReport>,
     ) -> Result<(), String> {
         self.output.println(&format!(
             "Validating staged snapshot state against manifest for block height {}",
@@ -1072,62 +2208,152 @@ impl SnapshotInstaller {
 
         self.output
             .println("Staged snapshot state matches manifest expectations");
+        if let Some(report) = signature_report {
+            self.output.println(&format!(
+                "Manifest accepted with {}/{} trusted signers: {}",
+                report.accepted_signers.len(),
+                report.threshold,
+                report.accepted_signers.join(", ")
+            ));
+            for signer in &report.rejected_signers {
+                self.output.println(&format!(
+                    "Ignored snapshot signer {}: {}",
+                    signer.key_id, signer.reason
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    fn install_balance_history_snapshot(
+        &self,
+        target_db: &BalanceHistoryDB,
+        snapshot_db: &SnapshotDB,
+        meta: &SnapshotMeta,
+    ) -> Result<(), String> {
+        let total = meta.balance_history_count;
+        if total == 0 {
+            self.output
+                .println("No balance history entries in snapshot, skipping installation");
+            return Ok(());
+        }
+
+        self.output.update_load_total_count(total);
+        self.output.println(&format!(
+            "Installing balance history snapshot with {} entries up to block height {}",
+            total, meta.block_height
+        ));
+
+        // Load balance by batch
+        let page_size = 1024 * 256; // 256k entries per batch
+        let mut last_script_hash: Option<USDBScriptHash> = None;
+        let mut installed_total = 0u64;
+        loop {
+            let entries = snapshot_db
+                .get_balance_history_entries(page_size, last_script_hash.as_ref())
+                .map_err(|e| {
+                    let msg = format!("Failed to read snapshot entries: {}", e);
+                    self.output.println(&msg);
+                    msg
+                })?;
+
+            target_db.put_address_history_async(&entries).map_err(|e| {
+                let msg = format!("Failed to write snapshot entries to database: {}", e);
+                self.output.println(&msg);
+                msg
+            })?;
+            target_db
+                .update_balance_state_async(&entries)
+                .map_err(|e| {
+                    let msg = format!("Failed to update balance state tree: {}", e);
+                    self.output.println(&msg);
+                    msg
+                })?;
+            installed_total += entries.len() as u64;
+
+            if let Some(last_entry) = entries.last() {
+                last_script_hash = Some(last_entry.script_hash.clone());
+            }
+
+            self.output.update_load_current_count(installed_total);
+
+            if entries.len() < page_size as usize {
+                break;
+            }
+        }
+
+        assert!(
+            installed_total == total,
+            "Installed total {} does not match expected total {}",
+            installed_total,
+            total
+        );
+
+        target_db.flush_all().map_err(|e| {
+            let msg = format!("Failed to flush database: {}", e);
+            self.output.println(&msg);
+            msg
+        })?;
+
+        self.output
+            .println("Balance history snapshot installation completed");
+
         Ok(())
     }
 
-    fn install_balance_history_snapshot(
+    fn install_utxo_snapshot(
         &self,
         target_db: &BalanceHistoryDB,
         snapshot_db: &SnapshotDB,
         meta: &SnapshotMeta,
     ) -> Result<(), String> {
-        let total = meta.balance_history_count;
+        let total = meta.utxo_count;
         if total == 0 {
             self.output
-                .println("No balance history entries in snapshot, skipping installation");
+                .println("No UTXO entries in snapshot, skipping installation");
             return Ok(());
         }
 
         self.output.update_load_total_count(total);
         self.output.println(&format!(
-            "Installing balance history snapshot with {} entries up to block height {}",
+            "Installing UTXO snapshot with {} entries up to block height {}",
             total, meta.block_height
         ));
 
-        // Load balance by batch
+        // Load UTXO by batch
         let page_size = 1024 * 256; // 256k entries per batch
-        let mut last_script_hash: Option<USDBScriptHash> = None;
+        let mut last_outpoint = None;
         let mut installed_total = 0u64;
         loop {
-            let entries = snapshot_db
-                .get_balance_history_entries(page_size, last_script_hash.as_ref())
+            let utxos = snapshot_db
+                .get_utxo_entries(page_size, last_outpoint.as_ref())
                 .map_err(|e| {
-                    let msg = format!("Failed to read snapshot entries: {}", e);
+                    let msg = format!("Failed to read snapshot UTXOs: {}", e);
                     self.output.println(&msg);
                     msg
                 })?;
 
-            target_db.put_address_history_async(&entries).map_err(|e| {
-                let msg = format!("Failed to write snapshot entries to database: {}", e);
+            target_db.put_utxos(&utxos).map_err(|e| {
+                let msg = format!("Failed to write snapshot UTXOs to database: {}", e);
                 self.output.println(&msg);
                 msg
             })?;
-            installed_total += entries.len() as u64;
+            installed_total += utxos.len() as u64;
 
-            if let Some(last_entry) = entries.last() {
-                last_script_hash = Some(last_entry.script_hash.clone());
+            if let Some(last_utxo) = utxos.last() {
+                last_outpoint = Some(last_utxo.outpoint);
             }
 
             self.output.update_load_current_count(installed_total);
 
-            if entries.len() < page_size as usize {
+            if utxos.len() < page_size as usize {
                 break;
             }
         }
 
         assert!(
             installed_total == total,
-            "Installed total {} does not match expected total {}",
+            "Installed UTXOs total {} does not match expected total {}",
             installed_total,
             total
         );
@@ -1138,65 +2364,63 @@ impl SnapshotInstaller {
             msg
         })?;
 
-        self.output
-            .println("Balance history snapshot installation completed");
+        self.output.println("UTXO snapshot installation completed");
 
         Ok(())
     }
 
-    fn install_utxo_snapshot(
+    fn install_utxo_removals(
         &self,
         target_db: &BalanceHistoryDB,
         snapshot_db: &SnapshotDB,
         meta: &SnapshotMeta,
     ) -> Result<(), String> {
-        let total = meta.utxo_count;
+        let total = meta.utxo_removal_count;
         if total == 0 {
             self.output
-                .println("No UTXO entries in snapshot, skipping installation");
+                .println("No UTXO removals in snapshot, skipping installation");
             return Ok(());
         }
 
         self.output.update_load_total_count(total);
         self.output.println(&format!(
-            "Installing UTXO snapshot with {} entries up to block height {}",
+            "Removing {} UTXOs spent up to block height {}",
             total, meta.block_height
         ));
 
-        // Load UTXO by batch
-        let page_size = 1024 * 256; // 256k entries per batch
+        let page_size = 1024 * 256;
         let mut last_outpoint = None;
         let mut installed_total = 0u64;
         loop {
-            let utxos = snapshot_db
-                .get_utxo_entries(page_size, last_outpoint.as_ref())
+            let outpoints = snapshot_db
+                .get_utxo_removals(page_size, last_outpoint.as_ref())
                 .map_err(|e| {
-                    let msg = format!("Failed to read snapshot UTXOs: {}", e);
+                    let msg = format!("Failed to read snapshot UTXO removals: {}", e);
                     self.output.println(&msg);
                     msg
                 })?;
 
-            target_db.put_utxos(&utxos).map_err(|e| {
-                let msg = format!("Failed to write snapshot UTXOs to database: {}", e);
+            target_db.delete_utxos(&outpoints).map_err(|e| {
+                let msg = format!("Failed to remove snapshot UTXOs from database: {}", e);
                 self.output.println(&msg);
                 msg
             })?;
-            installed_total += utxos.len() as u64;
+            installed_total += outpoints.len() as u64;
 
-            if let Some(last_utxo) = utxos.last() {
-                last_outpoint = Some(last_utxo.outpoint);
+            if let Some(last) = outpoints.last() {
+                last_outpoint = Some(*last);
             }
 
             self.output.update_load_current_count(installed_total);
 
-            if utxos.len() < page_size as usize {
+            if outpoints.len() < page_size as usize {
                 break;
             }
         }
 
         assert!(
             installed_total == total,
-            "Installed UTXOs total {} does not match expected total {}",
+            "Removed UTXOs total {} does not match expected total {}",
             installed_total,
             total
         );
@@ -1207,7 +2431,7 @@ impl SnapshotInstaller {
             msg
         })?;
 
-        self.output.println("UTXO snapshot installation completed");
+        self.output.println("UTXO removal installation completed");
 
         Ok(())
     }
@@ -1520,7 +2744,7 @@ mod tests {
     ) -> SnapshotManifest {
         let stable_block_hash = format!("{:x}", commit.btc_block_hash);
         let consensus_identity =
-            build_consensus_snapshot_identity(config, block_height, &stable_block_hash);
+            build_consensus_snapshot_identity(config, block_height, &stable_block_hash, None);
         let snapshot_id = build_consensus_snapshot_id(&consensus_identity);
         let state_ref = HistoricalSnapshotStateRef {
             block_height,
@@ -1546,6 +2770,18 @@ mod tests {
         )
     }
 
+    fn trusted_public_key(key_id: &str, signing_key: &SigningKey) -> SnapshotTrustedPublicKey {
+        SnapshotTrustedPublicKey {
+            key_id: key_id.to_string(),
+            public_key_base64: base64::engine::general_purpose::STANDARD
+                .encode(signing_key.verifying_key().to_bytes()),
+            valid_from_height: None,
+            valid_until_height: None,
+            valid_from_time: None,
+            valid_until_time: None,
+        }
+    }
+
     fn write_signing_material(
         root_dir: &Path,
         key_id: &str,
@@ -1560,11 +2796,9 @@ mod tests {
                 .encode(signing_key.to_bytes()),
         };
         let trusted_keys_file = SnapshotTrustedKeySet {
-            keys: vec![SnapshotTrustedPublicKey {
-                key_id: key_id.to_string(),
-                public_key_base64: base64::engine::general_purpose::STANDARD
-                    .encode(signing_key.verifying_key().to_bytes()),
-            }],
+            threshold: 1,
+            keys: vec![trusted_public_key(key_id, &signing_key)],
+            revoked_keys: Vec::new(),
         };
 
         std::fs::write(
@@ -2031,6 +3265,264 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_trusted_key_set_threshold_windows_and_revocation() {
+        let keys: Vec<SigningKey> = (1..=4u8)
+            .map(|seed| SigningKey::from_bytes(&[seed; 32]))
+            .collect();
+        let mut limited = trusted_public_key("signer-3", &keys[2]);
+        limited.valid_until_height = Some(99);
+        let mut timed = trusted_public_key("signer-4", &keys[3]);
+        timed.valid_from_time = Some(1_000);
+        let trusted = SnapshotTrustedKeySet {
+            threshold: 2,
+            keys: vec![
+                trusted_public_key("signer-1", &keys[0]),
+                trusted_public_key("signer-2", &keys[1]),
+                limited,
+                timed,
+                trusted_public_key("signer-1-alias", &keys[0]),
+            ],
+            revoked_keys: vec![SnapshotRevokedKey {
+                key_id: "signer-2".to_string(),
+                reason: Some("lost".to_string()),
+            }],
+        };
+        let payload = b"manifest";
+        let sign = |key_id: &str, index: usize| (key_id.to_string(), keys[index].sign(payload));
+
+        // Revoked, out-of-window and aliased keys never count.
+        let report = trusted.verify_signatures(
+            payload,
+            100,
+            Some(500),
+            2_000,
+            &[
+                sign("signer-1", 0),
+                sign("signer-2", 1),
+                sign("signer-3", 2),
+                sign("signer-4", 3),
+                sign("signer-1-alias", 0),
+            ],
+        );
+        assert_eq!(report.accepted_signers, vec!["signer-1".to_string()]);
+        assert_eq!(
+            report
+                .rejected_signers
+                .iter()
+                .map(|signer| signer.key_id.as_str())
+                .collect::<Vec<_>>(),
+            vec!["signer-2", "signer-3", "signer-4", "signer-1-alias"]
+        );
+        assert!(report.rejected_signers[0].reason.contains("revoked: lost"));
+        assert!(!report.is_satisfied());
+        assert!(report.ensure_satisfied("test").is_err());
+        assert!(trusted.find_verifying_key("signer-2").unwrap().is_none());
+
+        let report = trusted.verify_signatures(
+            payload,
+            99,
+            Some(1_000),
+            2_000,
+            &[
+                sign("signer-1", 0),
+                sign("signer-3", 2),
+                sign("signer-4", 1),
+            ],
+        );
+        assert_eq!(
+            report.accepted_signers,
+            vec!["signer-1".to_string(), "signer-3".to_string()]
+        );
+        assert!(
+            report.rejected_signers[0]
+                .reason
+                .contains("verification failed")
+        );
+        assert!(report.is_satisfied());
+
+        // A time window cannot be checked without generated_at.
+        let report = trusted.verify_signatures(payload, 99, None, 2_000, &[sign("signer-4", 3)]);
+        assert!(report.rejected_signers[0].reason.contains("generated_at"));
+
+        // A backdated manifest inside the window does not revive an expired key.
+        let mut expiring = trusted_public_key("signer-5", &keys[3]);
+        expiring.valid_until_time = Some(1_500);
+        let expiring = SnapshotTrustedKeySet {
+            threshold: 1,
+            keys: vec![expiring],
+            revoked_keys: Vec::new(),
+        };
+        let report =
+            expiring.verify_signatures(payload, 99, Some(1_200), 2_000, &[sign("signer-5", 3)]);
+        assert!(report.rejected_signers[0].reason.contains("install time"));
+        assert!(!report.is_satisfied());
+        let report =
+            expiring.verify_signatures(payload, 99, Some(1_200), 1_400, &[sign("signer-5", 3)]);
+        assert!(report.is_satisfied());
+
+        // Older files without a threshold keep the single-signer behaviour.
+        let legacy: SnapshotTrustedKeySet = serde_json::from_str(r#"{"keys": []}"#).unwrap();
+        assert_eq!(legacy.threshold, 1);
+        assert!(legacy.revoked_keys.is_empty());
+    }
+
+    #[test]
+    fn test_trusted_key_set_load_rejects_unreachable_threshold() {
+        let root_dir = temp_root("trusted_keys_threshold");
+        let keys: Vec<SigningKey> = (1..=2u8)
+            .map(|seed| SigningKey::from_bytes(&[seed; 32]))
+            .collect();
+        let path = root_dir.join("trusted_keys.json");
+        let write_and_load = |threshold: u32, revoked: &[&str]| {
+            let key_set = SnapshotTrustedKeySet {
+                threshold,
+                keys: vec![
+                    trusted_public_key("signer-1", &keys[0]),
+                    trusted_public_key("signer-2", &keys[1]),
+                    trusted_public_key("signer-1-alias", &keys[0]),
+                ],
+                revoked_keys: revoked
+                    .iter()
+                    .map(|key_id| SnapshotRevokedKey {
+                        key_id: key_id.to_string(),
+                        reason: None,
+                    })
+                    .collect(),
+            };
+            std::fs::write(&path, serde_json::to_vec_pretty(&key_set).unwrap()).unwrap();
+            SnapshotTrustedKeySet::load(&path)
+        };
+
+        assert!(write_and_load(0, &[]).unwrap_err().contains("at least 1"));
+        // The alias shares a public key, so only two distinct signers exist.
+        assert_eq!(write_and_load(2, &[]).unwrap().validate().unwrap(), 2);
+        assert!(write_and_load(3, &[]).unwrap_err().contains("exceeds"));
+        assert!(
+            write_and_load(2, &["signer-2"])
+                .unwrap_err()
+                .contains("exceeds")
+        );
+    }
+
+    #[test]
+    fn test_install_requires_signature_threshold_and_records_signers() {
+        let root_dir = temp_root("install_manifest_threshold");
+        let mut config = BalanceHistoryConfig::default();
+        config.root_dir = root_dir.clone();
+        config.snapshot.trust_mode = SnapshotTrustMode::Signed;
+        let (signing_key_path, trusted_keys_path, _) =
+            write_signing_material(&root_dir, "snapshot-signer-1", 42);
+        let cosigner = SigningKey::from_bytes(&[43u8; 32]);
+        let cosigner_key_path = root_dir.join("cosigner_signing_key.json");
+        std::fs::write(
+            &cosigner_key_path,
+            serde_json::to_vec_pretty(&SnapshotSigningKeyFile {
+                key_id: "snapshot-signer-2".to_string(),
+                secret_key_base64: base64::engine::general_purpose::STANDARD
+                    .encode(cosigner.to_bytes()),
+            })
+            .unwrap(),
+        )
+        .unwrap();
+        let mut trusted_keys = SnapshotTrustedKeySet::load(&trusted_keys_path).unwrap();
+        trusted_keys.threshold = 2;
+        trusted_keys
+            .keys
+            .push(trusted_public_key("snapshot-signer-2", &cosigner));
+        std::fs::write(
+            &trusted_keys_path,
+            serde_json::to_vec_pretty(&trusted_keys).unwrap(),
+        )
+        .unwrap();
+        config.snapshot.trusted_keys_file = Some(trusted_keys_path);
+        let config = Arc::new(config);
+
+        let live_db = BalanceHistoryDB::open(config.clone(), BalanceHistoryDBMode::Normal).unwrap();
+        live_db.put_btc_block_height(3).unwrap();
+        let live_db = Arc::new(live_db);
+
+        let snapshot_path = root_dir.join("install_source_snapshot_threshold.db");
+        let manifest_path = manifest_path_for_snapshot_file(&snapshot_path);
+        let signature_path = signature_path_for_manifest_file(&manifest_path);
+        let new_commit = BlockCommitEntry {
+            block_height: 10,
+            btc_block_hash: BlockHash::from_slice(&[10u8; 32]).unwrap(),
+            balance_delta_root: [11u8; 32],
+            block_commit: [12u8; 32],
+        };
+
+        {
+            let mut snapshot_db = SnapshotDB::open(&snapshot_path).unwrap();
+            snapshot_db
+                .put_block_commit_entries(std::slice::from_ref(&new_commit))
+                .unwrap();
+
+            let mut meta = SnapshotMeta::new(10);
+            meta.block_commit_count = 1;
+            snapshot_db.update_meta(&meta).unwrap();
+        }
+
+        let mut manifest =
+            build_manifest_for_snapshot(config.as_ref(), &snapshot_path, 10, &new_commit);
+        manifest.signature_scheme = Some(SNAPSHOT_SIGNATURE_SCHEME_ED25519.to_string());
+        manifest.signing_key_id = Some("snapshot-signer-1".to_string());
+        manifest.save(&manifest_path).unwrap();
+        let signing_key = SnapshotSigningKeyFile::load(&signing_key_path).unwrap();
+        let signature = signing_key
+            .to_signing_key()
+            .unwrap()
+            .sign(&manifest.canonical_bytes().unwrap());
+        save_signature_file(&signature_path, &signature).unwrap();
+
+        let status = Arc::new(SyncStatusManager::new());
+        let output = Arc::new(IndexOutput::new(status));
+        let err = SnapshotInstaller::new(config.clone(), live_db.clone(), output.clone())
+            .install(SnapshotData {
+                file: snapshot_path.clone(),
+                manifest_file: Some(manifest_path.clone()),
+            })
+            .unwrap_err();
+        assert!(err.contains("threshold not met"), "{}", err);
+
+        // Co-signing keeps the creator's plain signature and adds the second signer.
+        let cosigner_key = SnapshotSigningKeyFile::load(&cosigner_key_path).unwrap();
+        assert_eq!(
+            cosign_snapshot_manifest(&manifest_path, &cosigner_key).unwrap(),
+            2
+        );
+        assert_eq!(
+            cosign_snapshot_manifest(&manifest_path, &cosigner_key).unwrap(),
+            2
+        );
+
+        SnapshotInstaller::new(config.clone(), live_db, output)
+            .install(SnapshotData {
+                file: snapshot_path,
+                manifest_file: Some(manifest_path),
+            })
+            .unwrap();
+
+        let reopened_db =
+            BalanceHistoryDB::open(config.clone(), BalanceHistoryDBMode::Normal).unwrap();
+        let provenance = reopened_db
+            .get_snapshot_install_provenance()
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            provenance.verification_state,
+            SnapshotVerificationState::SignatureVerified
+        );
+        assert_eq!(
+            provenance.accepted_signing_key_ids,
+            vec![
+                "snapshot-signer-1".to_string(),
+                "snapshot-signer-2".to_string()
+            ]
+        );
+        assert_eq!(provenance.signature_threshold, Some(2));
+    }
+
     #[test]
     fn test_install_rejects_signed_mode_without_signature_sidecar() {
         let root_dir = temp_root("install_manifest_signed_missing_sig");
@@ -2083,6 +3575,30 @@ mod tests {
         assert!(err.contains("requires signature sidecar"));
     }
 
+    #[test]
+    fn test_install_rejects_utxo_set_hash_enabled() {
+        let root_dir = temp_root("install_utxo_set_hash_rejected");
+        let mut config = BalanceHistoryConfig::default();
+        config.root_dir = root_dir.clone();
+        config.sync.utxo_set_hash = true;
+        let config = Arc::new(config);
+
+        let live_db = BalanceHistoryDB::open(config.clone(), BalanceHistoryDBMode::Normal).unwrap();
+        let status = Arc::new(SyncStatusManager::new());
+        let output = Arc::new(IndexOutput::new(status));
+        let err = SnapshotInstaller::new(config.clone(), Arc::new(live_db), output)
+            .install(SnapshotData {
+                file: root_dir.join("install_source_snapshot_utxo_set_hash.db"),
+                manifest_file: None,
+            })
+            .unwrap_err();
+        assert!(
+            err.contains("does not support sync.utxo_set_hash"),
+            "{}",
+            err
+        );
+    }
+
     #[test]
     fn test_create_snapshot_rejects_historical_utxo_export() {
         let root_dir = temp_root("snapshot_historical_utxo_rejected");
@@ -2146,6 +3662,12 @@ mod tests {
         let manifest = SnapshotManifest::load(&manifest_path).unwrap();
         let actual_hash = SnapshotHash::calc_hash(&snapshot_path).unwrap();
         assert_eq!(manifest.file_sha256, actual_hash);
+        let chunks = manifest.chunks.as_ref().unwrap();
+        chunks.validate().unwrap();
+        assert_eq!(
+            chunks,
+            &SnapshotChunkList::calc(&snapshot_path, SNAPSHOT_CHUNK_SIZE).unwrap()
+        );
         assert!(
             !snapshot_path.with_extension("db-wal").exists(),
             "finalized snapshot should not leave sqlite wal sidecar behind"
@@ -2155,4 +3677,228 @@ mod tests {
             "finalized snapshot should not leave sqlite shm sidecar behind"
         );
     }
+
+    fn make_test_commit(block_height: u32) -> BlockCommitEntry {
+        let seed = block_height as u8;
+        BlockCommitEntry {
+            block_height,
+            btc_block_hash: BlockHash::from_slice(&[seed; 32]).unwrap(),
+            balance_delta_root: [seed.wrapping_add(100); 32],
+            block_commit: [seed.wrapping_add(200); 32],
+        }
+    }
+
+    // Producer DB at height 3 (base) and 5 (delta target): script 1 is emptied at 4, script 3
+    // first appears at 5, script 2 stays untouched.
+    fn build_delta_producer(
+        root_dir: &Path,
+    ) -> (
+        BalanceHistoryConfigRef,
+        PathBuf,
+        PathBuf,
+        [(ScriptBuf, USDBScriptHash); 3],
+    ) {
+        let mut config = BalanceHistoryConfig::default();
+        config.root_dir = root_dir.to_path_buf();
+        let config = Arc::new(config);
+        let scripts = [1u8, 2, 3].map(|seed| {
+            let script = ScriptBuf::from(vec![seed; 32]);
+            let script_hash = script.to_usdb_script_hash();
+            (script, script_hash)
+        });
+
+        let db =
+            Arc::new(BalanceHistoryDB::open(config.clone(), BalanceHistoryDBMode::Normal).unwrap());
+        db.put_block_commits_async(&(1..=3).map(make_test_commit).collect::<Vec<_>>())
+            .unwrap();
+        db.put_address_history_async(&vec![
+            BalanceHistoryEntry {
+                script_hash: scripts[0].1,
+                block_height: 2,
+                delta: 50,
+                balance: 50,
+            },
+            BalanceHistoryEntry {
+                script_hash: scripts[1].1,
+                block_height: 2,
+                delta: 30,
+                balance: 30,
+            },
+        ])
+        .unwrap();
+        db.put_script_registry_entries(
+            &scripts[..2]
+                .iter()
+                .map(|(script, script_hash)| ScriptRegistryEntry {
+                    script_hash: *script_hash,
+                    script_pubkey: script.clone(),
+                })
+                .collect::<Vec<_>>(),
+        )
+        .unwrap();
+        db.put_btc_block_height(3).unwrap();
+        db.flush_all().unwrap();
+
+        let status = Arc::new(SyncStatusManager::new());
+        let output = Arc::new(IndexOutput::new(status));
+        let indexer = SnapshotIndexer::new(config.clone(), db.clone(), output);
+        indexer.run(3, false).unwrap();
+        let base_path = config.snapshot_dir().join("snapshot_3.db");
+
+        db.put_block_commits_async(&(4..=5).map(make_test_commit).collect::<Vec<_>>())
+            .unwrap();
+        db.put_address_history_async(&vec![
+            BalanceHistoryEntry {
+                script_hash: scripts[0].1,
+                block_height: 4,
+                delta: -50,
+                balance: 0,
+            },
+            BalanceHistoryEntry {
+                script_hash: scripts[2].1,
+                block_height: 5,
+                delta: 20,
+                balance: 20,
+            },
+        ])
+        .unwrap();
+        db.put_script_registry_entries(&[ScriptRegistryEntry {
+            script_hash: scripts[2].1,
+            script_pubkey: scripts[2].0.clone(),
+        }])
+        .unwrap();
+        db.put_btc_block_height(5).unwrap();
+        db.flush_all().unwrap();
+
+        indexer
+            .run_delta(
+                SnapshotData {
+                    file: base_path.clone(),
+                    manifest_file: Some(manifest_path_for_snapshot_file(&base_path)),
+                },
+                5,
+            )
+            .unwrap();
+        let delta_path = config.snapshot_dir().join("snapshot_delta_3_5.db");
+
+        (config, base_path, delta_path, scripts)
+    }
+
+    #[test]
+    fn test_delta_snapshot_applies_on_top_of_installed_base() {
+        let producer_root = temp_root("delta_producer");
+        let (_producer_config, base_path, delta_path, scripts) =
+            build_delta_producer(&producer_root);
+
+        let delta_manifest =
+            SnapshotManifest::load(&manifest_path_for_snapshot_file(&delta_path)).unwrap();
+        assert_eq!(
+            delta_manifest.manifest_version,
+            SNAPSHOT_DELTA_MANIFEST_VERSION
+        );
+        let base = delta_manifest.base.as_ref().unwrap();
+        assert_eq!(base.file_name, "snapshot_3.db");
+        assert_eq!(
+            base.file_sha256,
+            SnapshotHash::calc_hash(&base_path).unwrap()
+        );
+        assert_eq!(base.state_ref.block_height, 3);
+
+        {
+            let delta_db = SnapshotDB::open(&delta_path).unwrap();
+            let meta = delta_db.get_meta().unwrap();
+            assert_eq!(meta.base_block_height, Some(3));
+            assert_eq!(meta.block_height, 5);
+            // Script 1 zero row plus script 3; untouched script 2 is not repeated.
+            assert_eq!(meta.balance_history_count, 2);
+            assert_eq!(meta.block_commit_count, 2);
+            assert_eq!(meta.script_registry_count, 1);
+            assert_eq!(meta.utxo_count, 0);
+        }
+
+        let consumer_root = temp_root("delta_consumer");
+        let mut config = BalanceHistoryConfig::default();
+        config.root_dir = consumer_root.clone();
+        let config = Arc::new(config);
+
+        let install = |data: SnapshotData| {
+            let live_db =
+                BalanceHistoryDB::open(config.clone(), BalanceHistoryDBMode::Normal).unwrap();
+            let status = Arc::new(SyncStatusManager::new());
+            let output = Arc::new(IndexOutput::new(status));
+            SnapshotInstaller::new(config.clone(), Arc::new(live_db), output).install(data)
+        };
+        install(SnapshotData {
+            manifest_file: Some(manifest_path_for_snapshot_file(&base_path)),
+            file: base_path,
+        })
+        .unwrap();
+        install(SnapshotData {
+            manifest_file: Some(manifest_path_for_snapshot_file(&delta_path)),
+            file: delta_path,
+        })
+        .unwrap();
+
+        let db = BalanceHistoryDB::open(config.clone(), BalanceHistoryDBMode::Normal).unwrap();
+        assert_eq!(db.get_btc_block_height().unwrap(), 5);
+        assert_eq!(db.get_latest_balance(&scripts[0].1).unwrap().balance, 0);
+        assert_eq!(
+            db.get_balance_at_block_height(&scripts[0].1, 3)
+                .unwrap()
+                .balance,
+            50
+        );
+        assert_eq!(db.get_latest_balance(&scripts[1].1).unwrap().balance, 30);
+        assert_eq!(db.get_latest_balance(&scripts[2].1).unwrap().balance, 20);
+        assert_eq!(db.get_block_commit(5).unwrap(), Some(make_test_commit(5)));
+        assert_eq!(
+            db.get_script_registry_entry(&scripts[2].1).unwrap(),
+            Some(scripts[2].0.clone())
+        );
+        assert_eq!(db.get_rollback_supported_from_height().unwrap(), Some(6));
+
+        let provenance = db.get_snapshot_install_provenance().unwrap().unwrap();
+        assert_eq!(provenance.installed_block_height, 5);
+        assert_eq!(provenance.base_block_height, Some(3));
+        assert_eq!(
+            provenance.verification_state,
+            SnapshotVerificationState::ManifestVerified
+        );
+    }
+
+    #[test]
+    fn test_install_delta_rejects_live_db_not_at_base_height() {
+        let producer_root = temp_root("delta_producer_mismatch");
+        let (_producer_config, _base_path, delta_path, _scripts) =
+            build_delta_producer(&producer_root);
+
+        let consumer_root = temp_root("delta_consumer_mismatch");
+        let mut config = BalanceHistoryConfig::default();
+        config.root_dir = consumer_root.clone();
+        let config = Arc::new(config);
+
+        let live_db = BalanceHistoryDB::open(config.clone(), BalanceHistoryDBMode::Normal).unwrap();
+        live_db.put_btc_block_height(2).unwrap();
+        let status = Arc::new(SyncStatusManager::new());
+        let output = Arc::new(IndexOutput::new(status));
+        let error = SnapshotInstaller::new(config.clone(), Arc::new(live_db), output)
+            .install(SnapshotData {
+                manifest_file: Some(manifest_path_for_snapshot_file(&delta_path)),
+                file: delta_path,
+            })
+            .unwrap_err();
+        assert!(error.contains("live DB is at block height 2"), "{}", error);
+
+        let staging_dirs = std::fs::read_dir(&consumer_root)
+            .unwrap()
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| {
+                entry
+                    .file_name()
+                    .to_string_lossy()
+                    .starts_with("snapshot_install_staging_")
+            })
+            .count();
+        assert_eq!(staging_dirs, 0, "height mismatch must fail before staging");
+    }
 }
@@ -3,7 +3,47 @@ use crate::db::{AddressDBRef, BalanceHistoryDBRef, SnapshotDBRef};
 use crate::output::IndexOutputRef;
 use bitcoincore_rpc::bitcoin::ScriptBuf;
 use bitcoincore_rpc::bitcoin::address::Address;
-use usdb_util::{ElectrsClientRef, ToUSDBScriptHash, USDBScriptHash};
+use usdb_util::{
+    BTCRpcClient, ElectrsClientRef, ToUSDBScriptHash, USDBScriptHash, utxo_set_hash_to_hex,
+};
+
+// Compare the local UTXO set hash at `block_height` with bitcoind's `gettxoutsetinfo muhash`
+// and return the matching hex hash.
+pub fn verify_utxo_set_hash(
+    db: &BalanceHistoryDBRef,
+    btc_client: &BTCRpcClient,
+    block_height: u32,
+) -> Result<String, String> {
+    if !db.is_utxo_set_hash_enabled() {
+        let msg = "UTXO set hash is disabled, enable sync.utxo_set_hash and resync from genesis"
+            .to_string();
+        error!("{}", msg);
+        return Err(msg);
+    }
+
+    let local = db.get_utxo_set_hash(block_height)?.ok_or_else(|| {
+        let msg = format!("No UTXO set hash persisted at height {}", block_height);
+        error!("{}", msg);
+        msg
+    })?;
+    let local = utxo_set_hash_to_hex(&local);
+
+    let remote = btc_client.get_utxo_set_muhash(block_height)?;
+    if local != remote {
+        let msg = format!(
+            "UTXO set hash mismatch at height {}: local={}, bitcoind={}",
+            block_height, local, remote
+        );
+        error!("{}", msg);
+        return Err(msg);
+    }
+
+    info!(
+        "UTXO set hash verified at height {}: {}",
+        block_height, local
+    );
+    Ok(local)
+}
 
 pub struct BalanceHistoryVerifier {
     config: BalanceHistoryConfigRef,
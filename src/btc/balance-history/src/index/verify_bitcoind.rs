@@ -0,0 +1,608 @@
+use crate::config::BalanceHistoryConfigRef;
+use crate::db::BalanceHistoryDBRef;
+use crate::output::IndexOutputRef;
+use bitcoincore_rpc::bitcoin::ScriptBuf;
+use bitcoincore_rpc::bitcoin::address::Address;
+use std::collections::HashMap;
+use usdb_util::{BTCRpcClientRef, BlockTxOutFlow, ToUSDBScriptHash, USDBScriptHash};
+
+const SAMPLE_TRAVERSE_BATCH_SIZE: usize = 1024;
+const SAMPLE_PROGRESS_INTERVAL: u64 = 1_000_000;
+const REPLAY_PROGRESS_INTERVAL: u32 = 100;
+
+#[derive(Debug, Clone)]
+pub struct BalanceSampleMismatch {
+    pub script_hash: USDBScriptHash,
+    pub address: String,
+    pub local_balance: u64,
+    pub bitcoind_balance: u64,
+}
+
+/// Outcome of a random-sample verification against bitcoind.
+#[derive(Debug, Clone)]
+pub struct BalanceSampleReport {
+    pub block_height: u32,
+    pub requested: usize,
+    // Sampled addresses whose balance was compared.
+    pub checked: usize,
+    // Sampled addresses whose script could not be recovered, so they were skipped.
+    pub unresolved: usize,
+    pub mismatches: Vec<BalanceSampleMismatch>,
+    pub confidence: f64,
+}
+
+impl BalanceSampleReport {
+    pub fn matched(&self) -> usize {
+        self.checked - self.mismatches.len()
+    }
+
+    /// Upper bound of the DB-wide mismatch rate at `confidence` when every checked sample
+    /// matched (exact binomial bound, roughly 3/n at 95%). The sample is uniform and drawn
+    /// without replacement, for which the binomial bound is conservative.
+    pub fn mismatch_rate_upper_bound(&self) -> Option<f64> {
+        if self.checked == 0 || !self.mismatches.is_empty() {
+            return None;
+        }
+
+        Some(1.0 - (1.0 - self.confidence).powf(1.0 / self.checked as f64))
+    }
+
+    pub fn summary(&self) -> String {
+        let mut summary = format!(
+            "Sampled {} of {} requested addresses at block height {}: {} matched, {} mismatched, {} unresolved",
+            self.checked,
+            self.requested,
+            self.block_height,
+            self.matched(),
+            self.mismatches.len(),
+            self.unresolved
+        );
+        match self.mismatch_rate_upper_bound() {
+            Some(bound) => summary.push_str(&format!(
+                "; mismatch rate < {:.4}% at {:.1}% confidence",
+                bound * 100.0,
+                self.confidence * 100.0
+            )),
+            None if self.checked > 0 => summary.push_str(&format!(
+                "; observed mismatch rate {:.4}%",
+                self.mismatches.len() as f64 * 100.0 / self.checked as f64
+            )),
+            None => {}
+        }
+
+        summary
+    }
+}
+
+// Verifies local balances against bitcoind alone, for deployments without electrs.
+// Balances at bitcoind's tip come from `scantxoutset`; older heights are reached by
+// replaying the blocks in between with their prevouts and subtracting the deltas.
+pub struct BitcoindBalanceVerifier {
+    config: BalanceHistoryConfigRef,
+    btc_client: BTCRpcClientRef,
+    db: BalanceHistoryDBRef,
+    output: IndexOutputRef,
+}
+
+impl BitcoindBalanceVerifier {
+    pub fn new(
+        config: BalanceHistoryConfigRef,
+        btc_client: BTCRpcClientRef,
+        db: BalanceHistoryDBRef,
+        output: IndexOutputRef,
+    ) -> Self {
+        Self {
+            config,
+            btc_client,
+            db,
+            output,
+        }
+    }
+
+    pub fn verify_address_latest(&self, script_hash: &USDBScriptHash) -> Result<(), String> {
+        let stable_height = self.db.get_btc_block_height()?;
+        self.verify_address_at_height(script_hash, stable_height)
+    }
+
+    pub fn verify_address_at_height(
+        &self,
+        script_hash: &USDBScriptHash,
+        block_height: u32,
+    ) -> Result<(), String> {
+        self.check_target_height(block_height)?;
+
+        let script = self.resolve_script(script_hash)?;
+        let bitcoind_balance = self.fetch_balances(std::slice::from_ref(&script), block_height)?[0];
+        let local_balance = self
+            .db
+            .get_balance_at_block_height(script_hash, block_height)?
+            .balance;
+
+        if local_balance != bitcoind_balance {
+            let msg = format!(
+                "Balance mismatch for script_hash {} at block height {}: expected {}, got {} from bitcoind, address {}",
+                script_hash,
+                block_height,
+                local_balance,
+                bitcoind_balance,
+                self.describe_script(&script)
+            );
+            error!("{}", msg);
+            return Err(msg);
+        }
+
+        info!(
+            "Balance verified against bitcoind for script_hash {} at block height {}: balance={}, address={}",
+            script_hash,
+            block_height,
+            local_balance,
+            self.describe_script(&script)
+        );
+        Ok(())
+    }
+
+    /// Compares `sample_size` randomly chosen addresses at `block_height` (the stable height
+    /// if omitted). Mismatches are reported, not returned as errors.
+    pub fn verify_sample(
+        &self,
+        sample_size: usize,
+        block_height: Option<u32>,
+        confidence: f64,
+    ) -> Result<BalanceSampleReport, String> {
+        if sample_size == 0 || !(confidence > 0.0 && confidence < 1.0) {
+            let msg = format!(
+                "Invalid sample parameters: sample_size={}, confidence={}",
+                sample_size, confidence
+            );
+            error!("{}", msg);
+            return Err(msg);
+        }
+
+        let block_height = match block_height {
+            Some(height) => height,
+            None => self.db.get_btc_block_height()?,
+        };
+        self.check_target_height(block_height)?;
+
+        let script_hashes = self.sample_script_hashes(sample_size)?;
+        self.output.println(&format!(
+            "Sampled {} addresses, resolving their scripts...",
+            script_hashes.len()
+        ));
+
+        let mut resolved = Vec::with_capacity(script_hashes.len());
+        let mut unresolved = 0;
+        for script_hash in script_hashes {
+            match self.resolve_script(&script_hash) {
+                Ok(script) => resolved.push((script_hash, script)),
+                Err(e) => {
+                    warn!("Skipping sampled script_hash {}: {}", script_hash, e);
+                    unresolved += 1;
+                }
+            }
+        }
+
+        let scripts: Vec<ScriptBuf> = resolved.iter().map(|(_, script)| script.clone()).collect();
+        let bitcoind_balances = if scripts.is_empty() {
+            Vec::new()
+        } else {
+            self.fetch_balances(&scripts, block_height)?
+        };
+
+        let mut mismatches = Vec::new();
+        for ((script_hash, script), bitcoind_balance) in resolved.iter().zip(bitcoind_balances) {
+            let local_balance = self
+                .db
+                .get_balance_at_block_height(script_hash, block_height)?
+                .balance;
+            if local_balance != bitcoind_balance {
+                let address = self.describe_script(script);
+                error!(
+                    "Balance mismatch for script_hash {} at block height {}: expected {}, got {} from bitcoind, address {}",
+                    script_hash, block_height, local_balance, bitcoind_balance, address
+                );
+                mismatches.push(BalanceSampleMismatch {
+                    script_hash: *script_hash,
+                    address,
+                    local_balance,
+                    bitcoind_balance,
+                });
+            }
+        }
+
+        let report = BalanceSampleReport {
+            block_height,
+            requested: sample_size,
+            checked: resolved.len(),
+            unresolved,
+            mismatches,
+            confidence,
+        };
+        info!("{}", report.summary());
+        Ok(report)
+    }
+
+    fn check_target_height(&self, block_height: u32) -> Result<(), String> {
+        let stable_height = self.db.get_btc_block_height()?;
+        if block_height > stable_height {
+            let msg = format!(
+                "Target block height {} is above the stable height {}",
+                block_height, stable_height
+            );
+            error!("{}", msg);
+            return Err(msg);
+        }
+
+        // Below the retention floor only the latest row per script survives, so local
+        // balances there are not the historical ones and must not be compared.
+        if let Some(retained_from_height) =
+            self.db.get_balance_history_pruned_floor(block_height)?
+        {
+            let msg = format!(
+                "Balance history at height {} is not retained: pruned below height {}",
+                block_height, retained_from_height
+            );
+            error!("{}", msg);
+            self.output.eprintln(&msg);
+            return Err(msg);
+        }
+
+        Ok(())
+    }
+
+    // Uniform sample over every script hash in the DB, drawn by reservoir sampling in one
+    // pass over the latest balance entry of each script.
+    fn sample_script_hashes(&self, sample_size: usize) -> Result<Vec<USDBScriptHash>, String> {
+        let mut reservoir = ReservoirSample::new(sample_size);
+        self.db
+            .traverse_latest(None, SAMPLE_TRAVERSE_BATCH_SIZE, |entries| {
+                for entry in entries {
+                    let random = getrandom::u64().map_err(|e| {
+                        let msg = format!("Failed to generate random sample position: {}", e);
+                        error!("{}", msg);
+                        msg
+                    })?;
+                    reservoir.offer(entry.script_hash, random);
+                    if reservoir.seen() % SAMPLE_PROGRESS_INTERVAL == 0 {
+                        self.output.println(&format!(
+                            "Scanned {} addresses for sampling...",
+                            reservoir.seen()
+                        ));
+                    }
+                }
+                Ok(())
+            })?;
+
+        info!(
+            "Sampled {} of {} addresses in the DB",
+            reservoir.len(),
+            reservoir.seen()
+        );
+        Ok(reservoir.into_items())
+    }
+
+    // The script registry is checked against the hash, so a corrupt entry cannot hide a
+    // mismatch. Without one, the script is recovered from the first block that paid it.
+    fn resolve_script(&self, script_hash: &USDBScriptHash) -> Result<ScriptBuf, String> {
+        if let Some(script) = self
+            .db
+            .get_script_registry_entry(script_hash)?
+            .filter(|script| script.to_usdb_script_hash() == *script_hash)
+        {
+            return Ok(script);
+        }
+
+        let history = self.db.get_all_balance(script_hash)?;
+        let entry = history
+            .iter()
+            .find(|entry| entry.delta > 0)
+            .ok_or_else(|| {
+                let msg = format!(
+                    "No incoming balance record to recover the script of script_hash {}",
+                    script_hash
+                );
+                error!("{}", msg);
+                msg
+            })?;
+
+        let block = self.btc_client.get_block(entry.block_height)?;
+        block
+            .txdata
+            .iter()
+            .flat_map(|tx| tx.output.iter())
+            .find(|vout| vout.script_pubkey.to_usdb_script_hash() == *script_hash)
+            .map(|vout| vout.script_pubkey.clone())
+            .ok_or_else(|| {
+                let msg = format!(
+                    "No output paying script_hash {} in block {}",
+                    script_hash, entry.block_height
+                );
+                error!("{}", msg);
+                msg
+            })
+    }
+
+    fn fetch_balances(&self, scripts: &[ScriptBuf], block_height: u32) -> Result<Vec<u64>, String> {
+        // Balances on two different chains are not comparable, so the local block at the
+        // target height must be the one bitcoind has there.
+        let bitcoind_hash = self.btc_client.get_block_hash(block_height)?;
+        match self.db.get_block_commit(block_height)? {
+            Some(commit) if commit.btc_block_hash != bitcoind_hash => {
+                let msg = format!(
+                    "Local block at height {} is {}, but bitcoind has {}; the DB is on a different chain",
+                    block_height, commit.btc_block_hash, bitcoind_hash
+                );
+                error!("{}", msg);
+                return Err(msg);
+            }
+            Some(_) => {}
+            None => {
+                let msg = format!(
+                    "No block commit recorded at height {}, cannot confirm the local chain matches bitcoind {}",
+                    block_height, bitcoind_hash
+                );
+                warn!("{}", msg);
+                self.output.eprintln(&msg);
+            }
+        }
+
+        self.output.println(&format!(
+            "Scanning bitcoind UTXO set for {} scripts, this may take several minutes...",
+            scripts.len()
+        ));
+        let scan = self.btc_client.scan_script_balances(scripts)?;
+        if scan.block_height < block_height {
+            let msg = format!(
+                "bitcoind tip {} is below the target block height {}",
+                scan.block_height, block_height
+            );
+            error!("{}", msg);
+            return Err(msg);
+        }
+
+        let index: HashMap<&ScriptBuf, usize> = scripts
+            .iter()
+            .enumerate()
+            .map(|(i, script)| (script, i))
+            .collect();
+        let mut deltas = vec![0i64; scripts.len()];
+        if scan.block_height > block_height {
+            self.output.println(&format!(
+                "Replaying blocks [{} - {}] to rewind balances from bitcoind tip to height {}",
+                block_height + 1,
+                scan.block_height,
+                block_height
+            ));
+        }
+        for height in block_height + 1..=scan.block_height {
+            let block_hash = self.btc_client.get_block_hash(height)?;
+            let flow = self.btc_client.get_block_tx_out_flow(&block_hash)?;
+            accumulate_block_deltas(&flow, &index, &mut deltas);
+
+            if (height - block_height) % REPLAY_PROGRESS_INTERVAL == 0 {
+                info!("Replayed blocks up to {} of {}", height, scan.block_height);
+            }
+        }
+
+        // A reorg during the replay would mix blocks from two chains.
+        let tip_hash = self.btc_client.get_block_hash(scan.block_height)?;
+        if tip_hash != scan.block_hash {
+            let msg = format!(
+                "bitcoind block at height {} changed during verification: scanned {}, now {}",
+                scan.block_height, scan.block_hash, tip_hash
+            );
+            error!("{}", msg);
+            return Err(msg);
+        }
+
+        scan.balances
+            .iter()
+            .zip(deltas)
+            .zip(scripts)
+            .map(|((&tip_balance, delta), script)| {
+                rewind_balance(tip_balance, delta).ok_or_else(|| {
+                    let msg = format!(
+                        "Negative balance for {} after rewinding from {} to {}: tip_balance={}, delta={}",
+                        self.describe_script(script),
+                        scan.block_height,
+                        block_height,
+                        tip_balance,
+                        delta
+                    );
+                    error!("{}", msg);
+                    msg
+                })
+            })
+            .collect()
+    }
+
+    fn describe_script(&self, script: &ScriptBuf) -> String {
+        Address::from_script(script, self.config.btc.network())
+            .map(|address| address.to_string())
+            .unwrap_or_else(|_| script.to_hex_string())
+    }
+}
+
+// Uniform sample of up to `capacity` items from a stream of unknown length (Algorithm R).
+// Every offered item ends up in the sample with probability capacity / seen.
+struct ReservoirSample<T> {
+    capacity: usize,
+    seen: u64,
+    items: Vec<T>,
+}
+
+impl<T> ReservoirSample<T> {
+    fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            seen: 0,
+            items: Vec::with_capacity(capacity),
+        }
+    }
+
+    // `random` must be uniform over u64; the modulo bias is below seen / 2^64.
+    fn offer(&mut self, item: T, random: u64) {
+        self.seen += 1;
+        if self.items.len() < self.capacity {
+            self.items.push(item);
+            return;
+        }
+
+        let slot = random % self.seen;
+        if slot < self.capacity as u64 {
+            self.items[slot as usize] = item;
+        }
+    }
+
+    fn seen(&self) -> u64 {
+        self.seen
+    }
+
+    fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    fn into_items(self) -> Vec<T> {
+        self.items
+    }
+}
+
+// Adds each tracked script's net change in the block to `deltas`.
+fn accumulate_block_deltas(
+    flow: &BlockTxOutFlow,
+    index: &HashMap<&ScriptBuf, usize>,
+    deltas: &mut [i64],
+) {
+    for vout in &flow.created {
+        if let Some(&i) = index.get(&vout.script_pubkey) {
+            deltas[i] += vout.value.to_sat() as i64;
+        }
+    }
+    for prevout in &flow.spent {
+        if let Some(&i) = index.get(&prevout.script_pubkey) {
+            deltas[i] -= prevout.value.to_sat() as i64;
+        }
+    }
+}
+
+fn rewind_balance(tip_balance: u64, delta: i64) -> Option<u64> {
+    u64::try_from(tip_balance as i64 - delta).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bitcoincore_rpc::bitcoin::hashes::Hash;
+    use bitcoincore_rpc::bitcoin::{Amount, TxOut};
+
+    fn tx_out(script: &ScriptBuf, value: u64) -> TxOut {
+        TxOut {
+            value: Amount::from_sat(value),
+            script_pubkey: script.clone(),
+        }
+    }
+
+    #[test]
+    fn test_block_replay_rewinds_tip_balance() {
+        let tracked = ScriptBuf::from(vec![0x51]);
+        let other = ScriptBuf::from(vec![0x52]);
+        let scripts = [tracked.clone()];
+        let index: HashMap<&ScriptBuf, usize> =
+            scripts.iter().enumerate().map(|(i, s)| (s, i)).collect();
+
+        // Block A pays 700 to the script, block B spends 500 of it and pays back 100.
+        let blocks = [
+            BlockTxOutFlow {
+                created: vec![tx_out(&tracked, 700), tx_out(&other, 5)],
+                spent: vec![tx_out(&other, 705)],
+            },
+            BlockTxOutFlow {
+                created: vec![tx_out(&tracked, 100)],
+                spent: vec![tx_out(&tracked, 500)],
+            },
+        ];
+        let mut deltas = vec![0i64];
+        for flow in &blocks {
+            accumulate_block_deltas(flow, &index, &mut deltas);
+        }
+        assert_eq!(deltas, vec![300]);
+
+        // 1000 at the tip means 700 before both blocks.
+        assert_eq!(rewind_balance(1000, deltas[0]), Some(700));
+        assert_eq!(rewind_balance(200, deltas[0]), None);
+    }
+
+    #[test]
+    fn test_reservoir_sample_replaces_by_slot() {
+        let mut reservoir = ReservoirSample::new(2);
+        reservoir.offer('a', 0);
+        reservoir.offer('b', 0);
+        assert_eq!(reservoir.items, vec!['a', 'b']);
+
+        // Third item: slot = random % 3, kept only for slots 0 and 1.
+        reservoir.offer('c', 5);
+        assert_eq!(reservoir.items, vec!['a', 'b']);
+        reservoir.offer('d', 1);
+        assert_eq!(reservoir.items, vec!['a', 'd']);
+        assert_eq!(reservoir.seen(), 4);
+
+        let mut small = ReservoirSample::new(10);
+        small.offer(1u8, 7);
+        assert_eq!(small.into_items(), vec![1u8]);
+    }
+
+    #[test]
+    fn test_reservoir_sample_is_uniform() {
+        // xorshift64 keeps the test deterministic.
+        let mut state = 0x9E37_79B9_7F4A_7C15u64;
+        let mut next = || {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state
+        };
+
+        let trials = 20_000;
+        let mut counts = [0u32; 10];
+        for _ in 0..trials {
+            let mut reservoir = ReservoirSample::new(3);
+            for item in 0..10usize {
+                reservoir.offer(item, next());
+            }
+            for item in reservoir.into_items() {
+                counts[item] += 1;
+            }
+        }
+
+        // Each item is expected in 30% of the samples.
+        for count in counts {
+            let rate = count as f64 / trials as f64;
+            assert!((rate - 0.3).abs() < 0.02, "{:?}", counts);
+        }
+    }
+
+    #[test]
+    fn test_sample_report_confidence_bound() {
+        let mut report = BalanceSampleReport {
+            block_height: 100,
+            requested: 300,
+            checked: 300,
+            unresolved: 0,
+            mismatches: Vec::new(),
+            confidence: 0.95,
+        };
+        let bound = report.mismatch_rate_upper_bound().unwrap();
+        assert!((bound - 0.00994).abs() < 0.0001, "{}", bound);
+        assert!(report.summary().contains("mismatch rate < 0.99"));
+
+        report.mismatches.push(BalanceSampleMismatch {
+            script_hash: USDBScriptHash::from_byte_array([1u8; 32]),
+            address: "addr".to_string(),
+            local_balance: 1,
+            bitcoind_balance: 2,
+        });
+        assert_eq!(report.matched(), 299);
+        assert!(report.mismatch_rate_upper_bound().is_none());
+        assert!(report.summary().contains("observed mismatch rate 0.3333%"));
+    }
+}
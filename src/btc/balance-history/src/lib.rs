@@ -7,6 +7,7 @@ pub mod cache;
 pub mod config;
 pub mod db;
 pub mod index;
+pub mod mempool;
 pub mod output;
 pub mod runtime;
 pub mod service;
@@ -8,6 +8,7 @@ use balance_history::db::{self, BalanceHistoryDB};
 use balance_history::index;
 use balance_history::output::IndexOutput;
 use balance_history::runtime::run_service;
+use balance_history::service;
 use balance_history::{status, tool, web_server};
 use clap::{Parser, Subcommand};
 use std::path::PathBuf;
@@ -55,6 +56,14 @@ struct InstallSnapshotSource {
     block_height: Option<u32>,
 }
 
+#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+enum VerifyBackend {
+    /// Query balances from electrs
+    Electrs,
+    /// Recompute balances from bitcoind with scantxoutset and block replay
+    Bitcoind,
+}
+
 #[derive(Args, Debug, Clone)]
 struct SnapshotKeygenArgs {
     /// Logical signer identifier written into manifest.signing_key_id.
@@ -91,6 +100,22 @@ enum BalanceHistoryCommands {
         with_utxo: bool,
     },
 
+    /// Create a delta snapshot containing only the changes since a base snapshot
+    CreateDeltaSnapshot {
+        /// Full base snapshot file, if the file is relative, it is relative to ${root}/snapshots/
+        #[arg(long)]
+        base: String,
+
+        /// Optional manifest of the base snapshot. If omitted, the `<base>.manifest.json`
+        /// next to the base snapshot is used when present.
+        #[arg(long)]
+        base_manifest: Option<String>,
+
+        /// Specify the target block height for the delta snapshot
+        #[arg(short, long)]
+        block_height: u32,
+    },
+
     VerifySnapshot {},
 
     InstallSnapshot {
@@ -103,12 +128,61 @@ enum BalanceHistoryCommands {
         manifest: Option<String>,
     },
 
+    /// Download a snapshot from a peer's snapshot HTTP server into ${root}/snapshots/ and install it
+    FetchSnapshot {
+        /// Base URL of the peer snapshot server, e.g. http://10.0.0.2:28012
+        #[arg(long)]
+        peer: String,
+
+        #[clap(flatten)]
+        source: InstallSnapshotSource,
+
+        /// Download a snapshot the peer offers without a manifest. Its contents can then
+        /// only be checked against the peer's own chunk list.
+        #[arg(long, default_value_t = false)]
+        allow_missing_manifest: bool,
+
+        /// Only download and verify the snapshot files, skip installation.
+        #[arg(long, default_value_t = false)]
+        download_only: bool,
+    },
+
+    /// Replace the database with a checkpoint taken by `create_checkpoint`.
+    /// The service must be stopped.
+    RestoreCheckpoint {
+        /// Checkpoint directory, if the path is relative, it is relative to ${root}/checkpoints/
+        #[arg(long)]
+        checkpoint: String,
+
+        /// Optional checkpoint manifest. If omitted, the `<checkpoint>.manifest.json`
+        /// next to the checkpoint directory is used.
+        #[arg(long)]
+        manifest: Option<String>,
+
+        /// Replace an existing database.
+        #[arg(long, default_value_t = false)]
+        force: bool,
+    },
+
     /// Generate one snapshot signing key and matching public-key export files.
     SnapshotKeygen {
         #[clap(flatten)]
         args: SnapshotKeygenArgs,
     },
 
+    /// Add a signature to an existing snapshot manifest, keeping the signatures already
+    /// in its `.sig` sidecar. Used to collect k-of-n signatures from independent signers.
+    SignSnapshotManifest {
+        /// Manifest file, if the path is relative, it is relative to ${root}/snapshots/
+        #[arg(long)]
+        manifest: String,
+
+        /// Signing key file. Defaults to snapshot.signing_key_file from config.
+        /// Relative paths are resolved against root_dir.
+        #[arg(long)]
+        key_file: Option<PathBuf>,
+    },
+
     Verify {
         /// Specify the target address to verify
         #[arg(short, long)]
@@ -125,6 +199,26 @@ enum BalanceHistoryCommands {
         /// Specify the starting address or script hash to verify from
         #[arg(long, alias = "start")]
         from: Option<String>,
+
+        /// Where the reference balances come from. The bitcoind backend needs an address,
+        /// a script hash or --sample, since every check scans the whole UTXO set.
+        #[arg(long, value_enum, default_value_t = VerifyBackend::Electrs)]
+        backend: VerifyBackend,
+
+        /// Verify this many randomly sampled addresses and print a confidence report (bitcoind backend)
+        #[arg(long)]
+        sample: Option<usize>,
+
+        /// Confidence level of the sample report
+        #[arg(long, default_value_t = 0.95)]
+        confidence: f64,
+    },
+
+    /// Compare the local UTXO set MuHash with bitcoind's `gettxoutsetinfo muhash`
+    VerifyUtxoSetHash {
+        /// Specify the target block height. If omitted, verify at the current stable height in balance-history DB.
+        #[arg(short, long)]
+        block_height: Option<u32>,
     },
 
     /// Serve balance-history browser static web files
@@ -260,6 +354,83 @@ async fn main() {
             println!("Snapshot generated successfully.");
             return;
         }
+        Some(BalanceHistoryCommands::CreateDeltaSnapshot {
+            base,
+            base_manifest,
+            block_height,
+        }) => {
+            // Init file logging
+            let file_name = format!("{}_snapshot", usdb_util::BALANCE_HISTORY_SERVICE_NAME);
+            let config = LogConfig::new(usdb_util::BALANCE_HISTORY_SERVICE_NAME)
+                .with_service_root_dir(root_dir.clone())
+                .with_file_name(&file_name)
+                .enable_console(false);
+            usdb_util::init_log(config);
+
+            println!("Generating delta snapshot in directory: {:?}", root_dir);
+            let config = match BalanceHistoryConfig::load(&root_dir) {
+                Ok(cfg) => cfg,
+                Err(e) => {
+                    error!("Failed to load config: {}", e);
+                    println!("Failed to load config: {}", e);
+                    std::process::exit(1);
+                }
+            };
+
+            let mut base_file = PathBuf::from(&base);
+            if base_file.is_relative() {
+                base_file = config.snapshot_dir().join(&base);
+                println!("Resolved relative base snapshot path to: {:?}", base_file);
+            }
+            let base_manifest_file = match base_manifest {
+                Some(manifest) => {
+                    let path = PathBuf::from(&manifest);
+                    Some(if path.is_relative() {
+                        config.snapshot_dir().join(manifest)
+                    } else {
+                        path
+                    })
+                }
+                None => {
+                    let auto_manifest = index::manifest_path_for_snapshot_file(&base_file);
+                    auto_manifest.exists().then_some(auto_manifest)
+                }
+            };
+
+            let config = Arc::new(config);
+            let status = status::SyncStatusManager::new();
+            let status = Arc::new(status);
+            let output = IndexOutput::new(status);
+            let output = Arc::new(output);
+
+            let db = match BalanceHistoryDB::open(
+                config.clone(),
+                db::BalanceHistoryDBMode::BestEffort,
+            ) {
+                Ok(database) => database,
+                Err(e) => {
+                    error!("Failed to initialize database: {}", e);
+                    output.println(&format!("Failed to initialize database: {}", e));
+                    std::process::exit(1);
+                }
+            };
+            let db = Arc::new(db);
+
+            let snapshot_indexer =
+                index::SnapshotIndexer::new(config.clone(), db.clone(), output.clone());
+            let base = index::SnapshotData {
+                file: base_file,
+                manifest_file: base_manifest_file,
+            };
+            if let Err(e) = snapshot_indexer.run_delta(base, block_height) {
+                error!("Failed to generate delta snapshot: {}", e);
+                output.println(&format!("Failed to generate delta snapshot: {}", e));
+                std::process::exit(1);
+            }
+
+            println!("Delta snapshot generated successfully.");
+            return;
+        }
         Some(BalanceHistoryCommands::InstallSnapshot { source, manifest }) => {
             // Init file logging
             let file_name = format!(
@@ -372,6 +543,158 @@ async fn main() {
             println!("Snapshot installed successfully.");
             return;
         }
+        Some(BalanceHistoryCommands::FetchSnapshot {
+            peer,
+            source,
+            allow_missing_manifest,
+            download_only,
+        }) => {
+            // Init file logging
+            let file_name = format!("{}_fetch_snapshot", usdb_util::BALANCE_HISTORY_SERVICE_NAME);
+            let config = LogConfig::new(usdb_util::BALANCE_HISTORY_SERVICE_NAME)
+                .with_service_root_dir(root_dir.clone())
+                .with_file_name(&file_name)
+                .enable_console(false);
+            usdb_util::init_log(config);
+
+            println!(
+                "Fetching snapshot from {} into directory: {:?}",
+                peer, root_dir
+            );
+            let config = match BalanceHistoryConfig::load(&root_dir) {
+                Ok(cfg) => cfg,
+                Err(e) => {
+                    error!("Failed to load config: {}", e);
+                    println!("Failed to load config: {}", e);
+                    std::process::exit(1);
+                }
+            };
+
+            let snapshot_file = match (source.file, source.block_height) {
+                (Some(file), _) => file,
+                (None, Some(block_height)) => format!("snapshot_{}.db", block_height),
+                (None, None) => {
+                    error!("No snapshot file or block height specified for fetch.");
+                    println!("No snapshot file or block height specified for fetch.");
+                    std::process::exit(1);
+                }
+            };
+
+            let fetcher = match service::SnapshotFetcher::new(&peer, config.snapshot_dir()) {
+                Ok(fetcher) => fetcher.with_allow_missing_manifest(allow_missing_manifest),
+                Err(e) => {
+                    println!("Failed to create snapshot fetcher: {}", e);
+                    std::process::exit(1);
+                }
+            };
+            let data = match fetcher.fetch(&snapshot_file).await {
+                Ok(data) => data,
+                Err(e) => {
+                    error!("Failed to fetch snapshot: {}", e);
+                    println!("Failed to fetch snapshot: {}", e);
+                    std::process::exit(1);
+                }
+            };
+            println!("Snapshot downloaded to {:?}", data.file);
+            match &data.manifest_file {
+                Some(manifest_file) => println!("Snapshot manifest saved to {:?}", manifest_file),
+                None => println!(
+                    "WARNING: peer offers no manifest for this snapshot; its state cannot be verified against an expected state ref"
+                ),
+            }
+            if download_only {
+                return;
+            }
+
+            let config = Arc::new(config);
+            let status = status::SyncStatusManager::new();
+            let status = Arc::new(status);
+            let output = IndexOutput::new(status);
+            let output = Arc::new(output);
+
+            let db = match BalanceHistoryDB::open(
+                config.clone(),
+                db::BalanceHistoryDBMode::BestEffort,
+            ) {
+                Ok(database) => database,
+                Err(e) => {
+                    output.eprintln(&format!("Failed to initialize database: {}", e));
+                    std::process::exit(1);
+                }
+            };
+            let db = Arc::new(db);
+
+            let snapshot_installer =
+                index::SnapshotInstaller::new(config.clone(), db, output.clone());
+            if let Err(e) = snapshot_installer.install(data) {
+                output.eprintln(&format!("Failed to install snapshot: {}", e));
+                std::process::exit(1);
+            }
+
+            println!("Snapshot installed successfully.");
+            return;
+        }
+        Some(BalanceHistoryCommands::RestoreCheckpoint {
+            checkpoint,
+            manifest,
+            force,
+        }) => {
+            // Init file logging
+            let file_name = format!(
+                "{}_restore_checkpoint",
+                usdb_util::BALANCE_HISTORY_SERVICE_NAME
+            );
+            let config = LogConfig::new(usdb_util::BALANCE_HISTORY_SERVICE_NAME)
+                .with_service_root_dir(root_dir.clone())
+                .with_file_name(&file_name)
+                .enable_console(true);
+            usdb_util::init_log(config);
+
+            println!("Restoring checkpoint in directory: {:?}", root_dir);
+            let config = match BalanceHistoryConfig::load(&root_dir) {
+                Ok(cfg) => cfg,
+                Err(e) => {
+                    error!("Failed to load config: {}", e);
+                    println!("Failed to load config: {}", e);
+                    std::process::exit(1);
+                }
+            };
+
+            let mut checkpoint_dir = PathBuf::from(&checkpoint);
+            if checkpoint_dir.is_relative() {
+                checkpoint_dir = config.checkpoint_dir().join(&checkpoint);
+                println!("Resolved relative checkpoint path to: {:?}", checkpoint_dir);
+            }
+            let manifest_file = manifest.map(|manifest| {
+                let path = PathBuf::from(&manifest);
+                if path.is_relative() {
+                    config.checkpoint_dir().join(manifest)
+                } else {
+                    path
+                }
+            });
+
+            let config = Arc::new(config);
+            match index::restore_checkpoint(
+                &config,
+                &checkpoint_dir,
+                manifest_file.as_deref(),
+                force,
+            ) {
+                Ok(manifest) => {
+                    println!(
+                        "Checkpoint restored at height {}, block_commit={}",
+                        manifest.state_ref.block_height, manifest.state_ref.latest_block_commit
+                    );
+                }
+                Err(e) => {
+                    error!("Failed to restore checkpoint: {}", e);
+                    println!("Failed to restore checkpoint: {}", e);
+                    std::process::exit(1);
+                }
+            }
+            return;
+        }
         Some(BalanceHistoryCommands::SnapshotKeygen { args }) => {
             let file_name = format!(
                 "{}_snapshot_keygen",
@@ -406,6 +729,66 @@ async fn main() {
             println!("trusted_keys_file={}", output.trusted_keys_file.display());
             return;
         }
+        Some(BalanceHistoryCommands::SignSnapshotManifest { manifest, key_file }) => {
+            let file_name = format!(
+                "{}_sign_snapshot_manifest",
+                usdb_util::BALANCE_HISTORY_SERVICE_NAME
+            );
+            let config = LogConfig::new(usdb_util::BALANCE_HISTORY_SERVICE_NAME)
+                .with_service_root_dir(root_dir.clone())
+                .with_file_name(&file_name)
+                .enable_console(true);
+            usdb_util::init_log(config);
+
+            let config = match BalanceHistoryConfig::load(&root_dir) {
+                Ok(cfg) => cfg,
+                Err(e) => {
+                    error!("Failed to load config: {}", e);
+                    println!("Failed to load config: {}", e);
+                    std::process::exit(1);
+                }
+            };
+
+            let mut manifest_path = PathBuf::from(&manifest);
+            if manifest_path.is_relative() {
+                manifest_path = config.snapshot_dir().join(&manifest);
+                println!("Resolved relative manifest path to: {:?}", manifest_path);
+            }
+            let key_path = match key_file {
+                Some(path) if path.is_relative() => root_dir.join(path),
+                Some(path) => path,
+                None => match config.snapshot_signing_key_path() {
+                    Some(path) => path,
+                    None => {
+                        println!(
+                            "No --key-file given and snapshot.signing_key_file is not configured"
+                        );
+                        std::process::exit(1);
+                    }
+                },
+            };
+
+            let ret = index::SnapshotSigningKeyFile::load(&key_path).and_then(|signing_key| {
+                index::cosign_snapshot_manifest(&manifest_path, &signing_key)
+                    .map(|count| (signing_key.key_id, count))
+            });
+            match ret {
+                Ok((key_id, count)) => {
+                    println!(
+                        "Snapshot manifest signed by {}, sidecar now holds {} signatures: {:?}",
+                        key_id,
+                        count,
+                        index::signature_path_for_manifest_file(&manifest_path)
+                    );
+                }
+                Err(e) => {
+                    error!("Failed to sign snapshot manifest: {}", e);
+                    println!("Failed to sign snapshot manifest: {}", e);
+                    std::process::exit(1);
+                }
+            }
+            return;
+        }
         Some(BalanceHistoryCommands::VerifySnapshot {}) => {
             // Init file logging
             let file_name = format!(
@@ -481,6 +864,9 @@ async fn main() {
             script_hash,
             height,
             from,
+            backend,
+            sample,
+            confidence,
         }) => {
             // Init file logging
             let file_name = format!("{}_verify", usdb_util::BALANCE_HISTORY_SERVICE_NAME);
@@ -518,22 +904,6 @@ async fn main() {
             };
             let db = Arc::new(db);
 
-            let electrs_client = match usdb_util::ElectrsClient::new(&config.electrs.rpc_url()) {
-                Ok(client) => client,
-                Err(e) => {
-                    output.eprintln(&format!("Failed to create electrs client: {}", e));
-                    std::process::exit(1);
-                }
-            };
-            let electrs_client = Arc::new(electrs_client);
-
-            let verifier = index::BalanceHistoryVerifier::new(
-                config.clone(),
-                electrs_client,
-                db,
-                output.clone(),
-            );
-
             let script_hash = if let Some(addr_str) = address {
                 match usdb_util::address_string_to_script_hash(&addr_str, &config.btc.network) {
                     Ok(sh) => Some(sh),
@@ -570,6 +940,88 @@ async fn main() {
                 None
             };
 
+            if backend == VerifyBackend::Bitcoind {
+                let btc_client =
+                    match usdb_util::BTCRpcClient::new(config.btc.rpc_url(), config.btc.auth()) {
+                        Ok(client) => client,
+                        Err(e) => {
+                            output.eprintln(&format!("Failed to create BTC RPC client: {}", e));
+                            std::process::exit(1);
+                        }
+                    };
+                let verifier = index::BitcoindBalanceVerifier::new(
+                    config.clone(),
+                    Arc::new(btc_client),
+                    db,
+                    output.clone(),
+                );
+
+                let ret = tokio::task::spawn_blocking(move || {
+                    if let Some(script_hash) = script_hash {
+                        return match height {
+                            Some(height) => verifier.verify_address_at_height(&script_hash, height),
+                            None => verifier.verify_address_latest(&script_hash),
+                        };
+                    }
+
+                    let Some(sample_size) = sample else {
+                        return Err(
+                            "The bitcoind backend needs --address, --script-hash or --sample"
+                                .to_string(),
+                        );
+                    };
+                    let report = verifier.verify_sample(sample_size, height, confidence)?;
+                    output.println(&report.summary());
+                    for mismatch in &report.mismatches {
+                        output.println(&format!(
+                            "Mismatch: script_hash={}, address={}, local={}, bitcoind={}",
+                            mismatch.script_hash,
+                            mismatch.address,
+                            mismatch.local_balance,
+                            mismatch.bitcoind_balance
+                        ));
+                    }
+                    if !report.mismatches.is_empty() {
+                        return Err(format!(
+                            "{} of {} sampled addresses mismatched",
+                            report.mismatches.len(),
+                            report.checked
+                        ));
+                    }
+                    Ok(())
+                })
+                .await
+                .unwrap();
+
+                if let Err(e) = ret {
+                    println!("Failed to verify balance history: {}", e);
+                    std::process::exit(1);
+                }
+                println!("Balance history verified successfully against bitcoind.");
+                return;
+            }
+
+            if sample.is_some() {
+                output.eprintln("--sample is only supported with --backend bitcoind");
+                std::process::exit(1);
+            }
+
+            let electrs_client = match usdb_util::ElectrsClient::new(&config.electrs.rpc_url()) {
+                Ok(client) => client,
+                Err(e) => {
+                    output.eprintln(&format!("Failed to create electrs client: {}", e));
+                    std::process::exit(1);
+                }
+            };
+            let electrs_client = Arc::new(electrs_client);
+
+            let verifier = index::BalanceHistoryVerifier::new(
+                config.clone(),
+                electrs_client,
+                db,
+                output.clone(),
+            );
+
             tokio::task::spawn_blocking(move || {
                 if script_hash.is_some() {
                     if let Some(height) = height {
@@ -625,6 +1077,74 @@ async fn main() {
             println!("Balance history verified successfully.");
             return;
         }
+        Some(BalanceHistoryCommands::VerifyUtxoSetHash { block_height }) => {
+            // Init file logging
+            let file_name = format!(
+                "{}_verify_utxo_set_hash",
+                usdb_util::BALANCE_HISTORY_SERVICE_NAME
+            );
+            let config = LogConfig::new(usdb_util::BALANCE_HISTORY_SERVICE_NAME)
+                .with_service_root_dir(root_dir.clone())
+                .with_file_name(&file_name)
+                .enable_console(true);
+            usdb_util::init_log(config);
+
+            let config = match BalanceHistoryConfig::load(&root_dir) {
+                Ok(cfg) => cfg,
+                Err(e) => {
+                    error!("Failed to load config: {}", e);
+                    println!("Failed to load config: {}", e);
+                    std::process::exit(1);
+                }
+            };
+            let config = Arc::new(config);
+
+            let db = match BalanceHistoryDB::open_for_read(
+                config.clone(),
+                db::BalanceHistoryDBMode::BestEffort,
+            ) {
+                Ok(database) => database,
+                Err(e) => {
+                    println!("Failed to initialize database: {}", e);
+                    std::process::exit(1);
+                }
+            };
+            let db = Arc::new(db);
+
+            let btc_client =
+                match usdb_util::BTCRpcClient::new(config.btc.rpc_url(), config.btc.auth()) {
+                    Ok(client) => client,
+                    Err(e) => {
+                        println!("Failed to create BTC RPC client: {}", e);
+                        std::process::exit(1);
+                    }
+                };
+
+            let ret = tokio::task::spawn_blocking(move || {
+                let block_height = match block_height {
+                    Some(height) => height,
+                    None => db.get_btc_block_height()?,
+                };
+                index::verify_utxo_set_hash(&db, &btc_client, block_height)
+                    .map(|hash| (block_height, hash))
+            })
+            .await
+            .unwrap();
+
+            match ret {
+                Ok((block_height, hash)) => {
+                    println!(
+                        "UTXO set hash verified successfully at height {}: {}",
+                        block_height, hash
+                    );
+                }
+                Err(e) => {
+                    println!("Failed to verify UTXO set hash: {}", e);
+                    std::process::exit(1);
+                }
+            }
+            return;
+        }
         Some(BalanceHistoryCommands::ServeWeb { port, web_root }) => {
             let file_name = format!("{}_web", usdb_util::BALANCE_HISTORY_SERVICE_NAME);
             let config = LogConfig::new(usdb_util::BALANCE_HISTORY_SERVICE_NAME)
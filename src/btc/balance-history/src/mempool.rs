@@ -0,0 +1,536 @@
+use crate::config::BalanceHistoryConfigRef;
+use crate::db::BalanceHistoryDBRef;
+use bitcoincore_rpc::bitcoin::{OutPoint, Transaction, Txid};
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
+use std::thread::JoinHandle;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use usdb_util::{BTCRpcClient, ToUSDBScriptHash, USDBScriptHash, UTXOValue};
+
+/// Upstream mempool view consumed by `MempoolTracker`.
+pub trait MempoolSource: Send + Sync {
+    fn get_raw_mempool(&self) -> Result<Vec<Txid>, String>;
+
+    // Fails once the tx has left the mempool, e.g. mined, replaced or evicted.
+    fn check_mempool_entry(&self, txid: &Txid) -> Result<(), String>;
+
+    fn get_transaction(&self, txid: &Txid) -> Result<Transaction, String>;
+}
+
+impl MempoolSource for BTCRpcClient {
+    fn get_raw_mempool(&self) -> Result<Vec<Txid>, String> {
+        self.get_raw_mempool()
+    }
+
+    fn check_mempool_entry(&self, txid: &Txid) -> Result<(), String> {
+        self.get_mempool_entry(txid).map(|_| ())
+    }
+
+    fn get_transaction(&self, txid: &Txid) -> Result<Transaction, String> {
+        self.get_transaction(txid)
+    }
+}
+
+/// Sum of the unconfirmed value moved for one script hash.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PendingDelta {
+    /// Value paid to the script by mempool transactions, in satoshi.
+    pub incoming: u64,
+    /// Value of the script's outputs spent by mempool transactions, in satoshi.
+    pub outgoing: u64,
+    /// Number of mempool transactions touching the script.
+    pub tx_count: u32,
+}
+
+impl PendingDelta {
+    fn is_empty(&self) -> bool {
+        self.tx_count == 0
+    }
+}
+
+/// Pending delta of one script hash plus the tracker state it was read from.
+#[derive(Debug, Clone, Default)]
+pub struct PendingBalanceView {
+    pub delta: PendingDelta,
+    /// Number of mempool transactions currently tracked.
+    pub mempool_tx_count: usize,
+    /// Unix time of the last successful refresh, `None` before the first one.
+    pub refreshed_at: Option<u64>,
+}
+
+#[derive(Debug, Clone)]
+struct PendingTx {
+    // Prevouts spent by the tx, resolved to their owner and value.
+    spent: Vec<UTXOValue>,
+    outputs: Vec<(OutPoint, UTXOValue)>,
+}
+
+impl PendingTx {
+    // Aggregates the tx per script hash as (incoming, outgoing).
+    fn script_values(&self) -> HashMap<USDBScriptHash, (u64, u64)> {
+        let mut values: HashMap<USDBScriptHash, (u64, u64)> = HashMap::new();
+        for (_, output) in &self.outputs {
+            values.entry(output.script_hash).or_default().0 += output.value;
+        }
+        for input in &self.spent {
+            values.entry(input.script_hash).or_default().1 += input.value;
+        }
+        values
+    }
+}
+
+#[derive(Default)]
+struct MempoolState {
+    txs: HashMap<Txid, PendingTx>,
+    // Outputs created by tracked txs, so chained unconfirmed spends resolve locally.
+    outputs: HashMap<OutPoint, UTXOValue>,
+    deltas: HashMap<USDBScriptHash, PendingDelta>,
+    refreshed_at: Option<u64>,
+}
+
+impl MempoolState {
+    fn insert_tx(&mut self, txid: Txid, tx: PendingTx) {
+        for (script_hash, (incoming, outgoing)) in tx.script_values() {
+            let delta = self.deltas.entry(script_hash).or_default();
+            delta.incoming += incoming;
+            delta.outgoing += outgoing;
+            delta.tx_count += 1;
+        }
+        for (outpoint, output) in &tx.outputs {
+            self.outputs.insert(*outpoint, output.clone());
+        }
+        self.txs.insert(txid, tx);
+    }
+
+    fn remove_tx(&mut self, txid: &Txid) {
+        let Some(tx) = self.txs.remove(txid) else {
+            return;
+        };
+
+        for (script_hash, (incoming, outgoing)) in tx.script_values() {
+            if let Some(delta) = self.deltas.get_mut(&script_hash) {
+                delta.incoming -= incoming;
+                delta.outgoing -= outgoing;
+                delta.tx_count -= 1;
+                if delta.is_empty() {
+                    self.deltas.remove(&script_hash);
+                }
+            }
+        }
+        for (outpoint, _) in &tx.outputs {
+            self.outputs.remove(outpoint);
+        }
+    }
+}
+
+/// Best-effort tracker of unconfirmed balance changes per script hash.
+///
+/// The tracker polls the upstream node mempool and keeps its state in memory only.
+/// It never writes to the DB, so pending deltas cannot leak into block commits,
+/// state refs or snapshots. Around a new block the view may briefly count a
+/// transaction twice or not at all, until the indexer and the next poll catch up.
+pub struct MempoolTracker {
+    config: BalanceHistoryConfigRef,
+    source: Arc<dyn MempoolSource>,
+    db: BalanceHistoryDBRef,
+    state: RwLock<MempoolState>,
+    stopped: AtomicBool,
+    worker: Mutex<Option<JoinHandle<()>>>,
+}
+
+impl MempoolTracker {
+    pub fn new(
+        config: BalanceHistoryConfigRef,
+        source: Arc<dyn MempoolSource>,
+        db: BalanceHistoryDBRef,
+    ) -> Self {
+        Self {
+            config,
+            source,
+            db,
+            state: RwLock::new(MempoolState::default()),
+            stopped: AtomicBool::new(false),
+            worker: Mutex::new(None),
+        }
+    }
+
+    pub fn start(self: &Arc<Self>) {
+        let tracker = self.clone();
+        let interval = Duration::from_secs(self.config.mempool.poll_interval_secs.max(1));
+        let worker = std::thread::spawn(move || {
+            while !tracker.stopped.load(Ordering::SeqCst) {
+                if let Err(e) = tracker.refresh() {
+                    warn!("Failed to refresh mempool tracker: {}", e);
+                }
+                // Woken early by stop(); a spurious wakeup only polls sooner.
+                std::thread::park_timeout(interval);
+            }
+            info!("Mempool tracker stopped.");
+        });
+        *self.worker.lock().unwrap() = Some(worker);
+    }
+
+    /// Stops polling and waits for an in-flight refresh to finish.
+    pub fn stop(&self) {
+        self.stopped.store(true, Ordering::SeqCst);
+        let worker = self.worker.lock().unwrap().take();
+        if let Some(worker) = worker {
+            worker.thread().unpark();
+            if worker.join().is_err() {
+                warn!("Mempool tracker thread panicked.");
+            }
+        }
+    }
+
+    pub fn get_pending_balance(&self, script_hash: &USDBScriptHash) -> PendingBalanceView {
+        let state = self.state.read().unwrap();
+        PendingBalanceView {
+            delta: state.deltas.get(script_hash).copied().unwrap_or_default(),
+            mempool_tx_count: state.txs.len(),
+            refreshed_at: state.refreshed_at,
+        }
+    }
+
+    /// Polls the mempool once, dropping txs that left it and resolving new ones.
+    pub fn refresh(&self) -> Result<(), String> {
+        let txids = self.source.get_raw_mempool()?;
+        let current: HashSet<Txid> = txids.iter().copied().collect();
+
+        let new_txids: Vec<Txid> = {
+            let state = self.state.read().unwrap();
+            txids
+                .into_iter()
+                .filter(|txid| !state.txs.contains_key(txid))
+                .collect()
+        };
+
+        // Fetch new txs without holding the state lock. Txs that leave the mempool
+        // between the two calls are skipped and dropped on the next poll anyway.
+        let mut fetched = Vec::with_capacity(new_txids.len());
+        for txid in new_txids {
+            if self.source.check_mempool_entry(&txid).is_err() {
+                continue;
+            }
+            match self.source.get_transaction(&txid) {
+                Ok(tx) => fetched.push((txid, tx)),
+                Err(e) => warn!("Skip mempool tx {} this round: {}", txid, e),
+            }
+        }
+
+        // Outputs of the new batch first, so a child fetched before its parent resolves.
+        let new_outputs: HashMap<OutPoint, UTXOValue> = fetched
+            .iter()
+            .flat_map(|(txid, tx)| Self::tx_outputs(txid, tx))
+            .collect();
+
+        let mut resolved = Vec::with_capacity(fetched.len());
+        for (txid, tx) in fetched {
+            match self.resolve_inputs(&tx, &new_outputs) {
+                Ok(spent) => resolved.push((
+                    txid,
+                    PendingTx {
+                        spent,
+                        outputs: Self::tx_outputs(&txid, &tx),
+                    },
+                )),
+                Err(e) => warn!("Skip mempool tx {} this round: {}", txid, e),
+            }
+        }
+
+        let mut state = self.state.write().unwrap();
+        let gone: Vec<Txid> = state
+            .txs
+            .keys()
+            .filter(|txid| !current.contains(*txid))
+            .copied()
+            .collect();
+        for txid in &gone {
+            state.remove_tx(txid);
+        }
+
+        let added = resolved.len();
+        for (txid, tx) in resolved {
+            state.insert_tx(txid, tx);
+        }
+        state.refreshed_at = Some(
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs(),
+        );
+
+        debug!(
+            "Mempool tracker refreshed: added={}, removed={}, tracked={}",
+            added,
+            gone.len(),
+            state.txs.len()
+        );
+
+        Ok(())
+    }
+
+    fn tx_outputs(txid: &Txid, tx: &Transaction) -> Vec<(OutPoint, UTXOValue)> {
+        tx.output
+            .iter()
+            .enumerate()
+            .map(|(vout, output)| {
+                (
+                    OutPoint::new(*txid, vout as u32),
+                    UTXOValue {
+                        script_hash: output.script_pubkey.to_usdb_script_hash(),
+                        value: output.value.to_sat(),
+                    },
+                )
+            })
+            .collect()
+    }
+
+    // Resolves prevouts from unconfirmed parents first, then the local UTXO set, and
+    // finally bitcoind for outputs of blocks the indexer has not committed yet.
+    fn resolve_inputs(
+        &self,
+        tx: &Transaction,
+        new_outputs: &HashMap<OutPoint, UTXOValue>,
+    ) -> Result<Vec<UTXOValue>, String> {
+        let mut spent = Vec::with_capacity(tx.input.len());
+        for input in &tx.input {
+            let prevout = &input.previous_output;
+            if let Some(value) = new_outputs.get(prevout) {
+                spent.push(value.clone());
+                continue;
+            }
+            if let Some(value) = self.state.read().unwrap().outputs.get(prevout) {
+                spent.push(value.clone());
+                continue;
+            }
+            if let Some(value) = self.db.get_utxo(prevout)? {
+                spent.push(value);
+                continue;
+            }
+
+            // The parent is confirmed here, so bitcoind can only return it with txindex=1.
+            let prev_tx = self.source.get_transaction(&prevout.txid)?;
+            let output = prev_tx.output.get(prevout.vout as usize).ok_or_else(|| {
+                let msg = format!("Invalid vout index for prevout {}", prevout);
+                warn!("{}", msg);
+                msg
+            })?;
+            spent.push(UTXOValue {
+                script_hash: output.script_pubkey.to_usdb_script_hash(),
+                value: output.value.to_sat(),
+            });
+        }
+
+        Ok(spent)
+    }
+}
+
+pub type MempoolTrackerRef = Arc<MempoolTracker>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::BalanceHistoryConfig;
+    use crate::db::{BalanceHistoryDB, BalanceHistoryDBMode};
+    use bitcoincore_rpc::bitcoin::absolute::LockTime;
+    use bitcoincore_rpc::bitcoin::hashes::Hash;
+    use bitcoincore_rpc::bitcoin::transaction::Version;
+    use bitcoincore_rpc::bitcoin::{Amount, ScriptBuf, Sequence, TxIn, TxOut, Witness};
+
+    #[derive(Default)]
+    struct MockMempool {
+        mempool: Mutex<Vec<Txid>>,
+        txs: Mutex<HashMap<Txid, Transaction>>,
+    }
+
+    impl MockMempool {
+        fn add_tx(&self, tx: Transaction, in_mempool: bool) -> Txid {
+            let txid = tx.compute_txid();
+            self.txs.lock().unwrap().insert(txid, tx);
+            if in_mempool {
+                self.mempool.lock().unwrap().push(txid);
+            }
+            txid
+        }
+
+        fn drop_from_mempool(&self, txid: &Txid) {
+            self.mempool.lock().unwrap().retain(|item| item != txid);
+        }
+    }
+
+    impl MempoolSource for MockMempool {
+        fn get_raw_mempool(&self) -> Result<Vec<Txid>, String> {
+            Ok(self.mempool.lock().unwrap().clone())
+        }
+
+        fn check_mempool_entry(&self, txid: &Txid) -> Result<(), String> {
+            if self.mempool.lock().unwrap().contains(txid) {
+                Ok(())
+            } else {
+                Err(format!("Transaction {} not in mempool", txid))
+            }
+        }
+
+        fn get_transaction(&self, txid: &Txid) -> Result<Transaction, String> {
+            self.txs
+                .lock()
+                .unwrap()
+                .get(txid)
+                .cloned()
+                .ok_or_else(|| format!("Transaction {} not found", txid))
+        }
+    }
+
+    fn make_script(byte: u8) -> ScriptBuf {
+        ScriptBuf::from_bytes(vec![byte; 22])
+    }
+
+    fn make_tx(inputs: &[OutPoint], outputs: &[(u8, u64)]) -> Transaction {
+        Transaction {
+            version: Version::TWO,
+            lock_time: LockTime::ZERO,
+            input: inputs
+                .iter()
+                .map(|outpoint| TxIn {
+                    previous_output: *outpoint,
+                    script_sig: ScriptBuf::new(),
+                    sequence: Sequence::MAX,
+                    witness: Witness::new(),
+                })
+                .collect(),
+            output: outputs
+                .iter()
+                .map(|(byte, value)| TxOut {
+                    value: Amount::from_sat(*value),
+                    script_pubkey: make_script(*byte),
+                })
+                .collect(),
+        }
+    }
+
+    fn make_tracker(tag: &str, source: Arc<MockMempool>) -> MempoolTracker {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_nanos();
+        let mut config = BalanceHistoryConfig::default();
+        config.root_dir =
+            std::env::temp_dir().join(format!("balance_history_mempool_{}_{}", tag, nanos));
+        std::fs::create_dir_all(&config.root_dir).unwrap();
+        let config = Arc::new(config);
+        let db =
+            Arc::new(BalanceHistoryDB::open(config.clone(), BalanceHistoryDBMode::Normal).unwrap());
+
+        MempoolTracker::new(config, source, db)
+    }
+
+    #[test]
+    fn test_stop_joins_worker_without_waiting_for_poll_interval() {
+        let tracker = Arc::new(make_tracker("stop", Arc::new(MockMempool::default())));
+        tracker.start();
+
+        let begin = std::time::Instant::now();
+        tracker.stop();
+        assert!(tracker.worker.lock().unwrap().is_none());
+        assert!(begin.elapsed() < Duration::from_secs(tracker.config.mempool.poll_interval_secs));
+    }
+
+    #[test]
+    fn test_refresh_tracks_chained_spends_and_evictions() {
+        let source = Arc::new(MockMempool::default());
+        let alice = make_script(1).to_usdb_script_hash();
+        let bob = make_script(2).to_usdb_script_hash();
+
+        // Confirmed upstream but not yet indexed locally, resolved through the source.
+        let funding = source.add_tx(
+            make_tx(&[OutPoint::new(Txid::all_zeros(), 0)], &[(1, 500_000)]),
+            false,
+        );
+        let pay = source.add_tx(
+            make_tx(&[OutPoint::new(funding, 0)], &[(2, 300_000), (1, 199_000)]),
+            true,
+        );
+        let forward = source.add_tx(make_tx(&[OutPoint::new(pay, 0)], &[(1, 299_000)]), true);
+
+        let tracker = make_tracker("chain", source.clone());
+        assert!(tracker.get_pending_balance(&alice).refreshed_at.is_none());
+        tracker.refresh().unwrap();
+
+        let view = tracker.get_pending_balance(&alice);
+        assert_eq!(view.mempool_tx_count, 2);
+        assert!(view.refreshed_at.is_some());
+        assert_eq!(
+            view.delta,
+            PendingDelta {
+                incoming: 199_000 + 299_000,
+                outgoing: 500_000,
+                tx_count: 2,
+            }
+        );
+        assert_eq!(
+            tracker.get_pending_balance(&bob).delta,
+            PendingDelta {
+                incoming: 300_000,
+                outgoing: 300_000,
+                tx_count: 2,
+            }
+        );
+
+        // The child gets replaced, so only the parent stays pending.
+        source.drop_from_mempool(&forward);
+        tracker.refresh().unwrap();
+
+        let view = tracker.get_pending_balance(&bob);
+        assert_eq!(view.mempool_tx_count, 1);
+        assert_eq!(
+            view.delta,
+            PendingDelta {
+                incoming: 300_000,
+                outgoing: 0,
+                tx_count: 1,
+            }
+        );
+
+        source.drop_from_mempool(&pay);
+        tracker.refresh().unwrap();
+        assert_eq!(
+            tracker.get_pending_balance(&alice).delta,
+            PendingDelta::default()
+        );
+        assert!(tracker.state.read().unwrap().outputs.is_empty());
+    }
+
+    #[test]
+    fn test_refresh_retries_unresolved_inputs() {
+        let source = Arc::new(MockMempool::default());
+        let alice = make_script(1).to_usdb_script_hash();
+
+        // The parent is unknown to both the local DB and the source on the first poll.
+        let parent = make_tx(&[OutPoint::new(Txid::all_zeros(), 0)], &[(3, 2_000)]);
+        let parent_txid = parent.compute_txid();
+        source.add_tx(
+            make_tx(&[OutPoint::new(parent_txid, 0)], &[(1, 1_000)]),
+            true,
+        );
+
+        let tracker = make_tracker("retry", source.clone());
+        tracker.refresh().unwrap();
+        let view = tracker.get_pending_balance(&alice);
+        assert_eq!(view.mempool_tx_count, 0);
+        assert_eq!(view.delta, PendingDelta::default());
+
+        source.add_tx(parent, false);
+        tracker.refresh().unwrap();
+        let view = tracker.get_pending_balance(&alice);
+        assert_eq!(view.mempool_tx_count, 1);
+        assert_eq!(view.delta.incoming, 1_000);
+        assert_eq!(
+            tracker
+                .get_pending_balance(&make_script(3).to_usdb_script_hash())
+                .delta
+                .outgoing,
+            2_000
+        );
+    }
+}
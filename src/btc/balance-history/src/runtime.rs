@@ -1,10 +1,14 @@
 use crate::config::BalanceHistoryConfig;
-use crate::index::BalanceHistoryIndexer;
+use crate::index::{BalanceHistoryIndexer, HistoryPruner};
+use crate::mempool::MempoolTracker;
 use crate::output::IndexOutput;
-use crate::service::BalanceHistoryRpcServer;
+use crate::service::{
+    BalanceHistoryRpcServer, ElectrumHandler, ElectrumServer, SUBSCRIBE_NEW_STABLE_BLOCK,
+    SnapshotHttpHandler, SnapshotHttpServer,
+};
 use std::path::PathBuf;
 use std::sync::Arc;
-use usdb_util::LogConfig;
+use usdb_util::{LogConfig, SubscriptionHub, WsSubscriptionServer, ZmqBlockSubscriber};
 
 /// Runs the balance-history indexing service until an external shutdown signal
 /// or an indexer error stops the process.
@@ -63,8 +67,32 @@ pub async fn run_service(
 
     let config = Arc::new(config);
 
+    let subscription_hub = match SubscriptionHub::new(&[SUBSCRIBE_NEW_STABLE_BLOCK]) {
+        Ok(hub) => Arc::new(hub),
+        Err(e) => {
+            output.eprintln(&format!("Failed to create subscription hub: {}", e));
+            std::process::exit(1);
+        }
+    };
+
+    let block_notifier = config.btc.zmq_block_url.as_ref().map(|url| {
+        let notifier = Arc::new(ZmqBlockSubscriber::new(url));
+        notifier.start();
+        output.println(&format!(
+            "Subscribed to bitcoind ZMQ block notifications at {}",
+            url
+        ));
+        notifier
+    });
+
     let indexer = match BalanceHistoryIndexer::new(config.clone(), output.clone()) {
-        Ok(idx) => idx,
+        Ok(idx) => {
+            let idx = idx.with_subscription_hub(subscription_hub.clone());
+            match &block_notifier {
+                Some(notifier) => idx.with_block_notifier(notifier.clone()),
+                None => idx,
+            }
+        }
         Err(e) => {
             output.eprintln(&format!("Failed to initialize indexer: {}", e));
             std::process::exit(1);
@@ -72,12 +100,47 @@ pub async fn run_service(
     };
     output.println("Starting indexer...");
 
+    let mempool = if config.mempool.enabled {
+        let ret = usdb_util::BTCRpcClient::new(config.btc.rpc_url(), config.btc.auth());
+        match ret {
+            Ok(client) => {
+                let tracker = Arc::new(MempoolTracker::new(
+                    config.clone(),
+                    Arc::new(client),
+                    indexer.db().clone(),
+                ));
+                tracker.start();
+                output.println("Mempool tracker started.");
+                Some(tracker)
+            }
+            Err(e) => {
+                output.eprintln(&format!("Failed to create mempool BTC client: {}", e));
+                std::process::exit(1);
+            }
+        }
+    } else {
+        None
+    };
+
+    let history_pruner = if config.sync.history_retention_blocks > 0 {
+        let pruner = Arc::new(HistoryPruner::new(config.clone(), indexer.db().clone()));
+        pruner.start();
+        output.println(&format!(
+            "Pruned mode enabled, retaining balance history for the last {} blocks.",
+            pruner.retention_blocks()
+        ));
+        Some(pruner)
+    } else {
+        None
+    };
+
     let (shutdown_tx, mut shutdown_rx) = tokio::sync::watch::channel(());
 
     let ret = BalanceHistoryRpcServer::start(
         config.clone(),
         output.status().clone(),
         indexer.db().clone(),
+        mempool.clone(),
         shutdown_tx,
     );
     if let Err(e) = &ret {
@@ -91,6 +154,91 @@ pub async fn run_service(
         rpc_server.get_listen_url()
     ));
 
+    let ws_server = if config.rpc_server.ws_port == 0 {
+        output.println("WebSocket subscription server disabled.");
+        None
+    } else {
+        let ret = match format!("{}:{}", config.rpc_server.host, config.rpc_server.ws_port)
+            .parse::<std::net::SocketAddr>()
+        {
+            Ok(addr) => WsSubscriptionServer::start(addr, subscription_hub.clone()).await,
+            Err(e) => Err(format!("Failed to parse WebSocket server address: {}", e)),
+        };
+        match ret {
+            Ok(server) => {
+                output.println(&format!(
+                    "WebSocket subscription server started at {}",
+                    server.get_listen_url()
+                ));
+                Some(server)
+            }
+            Err(e) => {
+                output.eprintln(&format!("Failed to start WebSocket server: {}", e));
+                std::process::exit(1);
+            }
+        }
+    };
+
+    let electrum_server = if config.rpc_server.electrum_port == 0 {
+        None
+    } else {
+        let ret = usdb_util::BTCRpcClient::new(config.btc.rpc_url(), config.btc.auth())
+            .map_err(|e| format!("Failed to create Electrum BTC client: {}", e))
+            .and_then(|client| {
+                let mut handler = ElectrumHandler::new(indexer.db().clone(), Arc::new(client));
+                if let Some(mempool) = &mempool {
+                    handler = handler.with_mempool_tracker(mempool.clone());
+                }
+
+                format!(
+                    "{}:{}",
+                    config.rpc_server.host, config.rpc_server.electrum_port
+                )
+                .parse::<std::net::SocketAddr>()
+                .map_err(|e| format!("Failed to parse Electrum server address: {}", e))
+                .and_then(|addr| ElectrumServer::start(addr, Arc::new(handler)))
+            });
+        match ret {
+            Ok(server) => {
+                output.println(&format!(
+                    "Electrum server started at {}",
+                    server.get_listen_url()
+                ));
+                Some(server)
+            }
+            Err(e) => {
+                output.eprintln(&format!("Failed to start Electrum server: {}", e));
+                std::process::exit(1);
+            }
+        }
+    };
+
+    let snapshot_http_server = if config.rpc_server.snapshot_port == 0 {
+        None
+    } else {
+        let handler = SnapshotHttpHandler::new(config.snapshot_dir());
+        let ret = format!(
+            "{}:{}",
+            config.rpc_server.host, config.rpc_server.snapshot_port
+        )
+        .parse::<std::net::SocketAddr>()
+        .map_err(|e| format!("Failed to parse snapshot HTTP server address: {}", e))
+        .and_then(|addr| SnapshotHttpServer::start(addr, Arc::new(handler)));
+        match ret {
+            Ok(server) => {
+                output.println(&format!(
+                    "Snapshot HTTP server started at {}",
+                    server.get_listen_url()
+                ));
+                Some(server)
+            }
+            Err(e) => {
+                output.eprintln(&format!("Failed to start snapshot HTTP server: {}", e));
+                std::process::exit(1);
+            }
+        }
+    };
+
     use tokio::signal;
     let sigint = signal::ctrl_c();
 
@@ -128,15 +276,35 @@ pub async fn run_service(
         }
     }
 
+    if let Some(mempool) = &mempool {
+        mempool.stop();
+    }
+    if let Some(history_pruner) = &history_pruner {
+        history_pruner.stop();
+    }
+
     output.println("Shutting down indexer...");
     indexer.shutdown().await;
     output.println("Shutdown indexer complete.");
 
+    if let Some(block_notifier) = &block_notifier {
+        block_notifier.close();
+    }
+
     indexer.db().flush_all().unwrap_or_else(|e| {
         error!("Failed to flush database on shutdown: {}", e);
     });
 
     rpc_server.close().await;
+    if let Some(ws_server) = &ws_server {
+        ws_server.close().await;
+    }
+    if let Some(electrum_server) = &electrum_server {
+        electrum_server.close().await;
+    }
+    if let Some(snapshot_http_server) = &snapshot_http_server {
+        snapshot_http_server.close().await;
+    }
 
     println!("Shutdown complete.");
 
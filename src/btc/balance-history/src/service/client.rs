@@ -1,8 +1,13 @@
 use super::rpc::{
     AddressBalance, AddressBalanceSummary, AddressBalanceTimeseriesPoint, AddressFlowBucket,
-    BlockCommitInfo, GetStateRefAtHeightParams, HistoricalSnapshotStateRef, ReadinessInfo,
+    AddressPendingBalance, AddressTransactionPage, AddressUtxoPage, BlockCommitInfo,
+    DescriptorBalanceSummary, GetAddressBalanceProofParams, GetAddressPendingBalanceParams,
+    GetAddressTransactionsParams, GetAddressUtxosParams, GetBalanceStateProofParams,
+    GetDescriptorBalanceBucketsParams, GetDescriptorBalanceSummaryParams,
+    GetStateRefAtHeightParams, HistoricalSnapshotStateRef, ReadinessInfo,
     ResolveScriptHashesParams, ScriptHashResolutionResponse, SnapshotInfo, UtxoInfo,
 };
+use crate::index::CheckpointInfo;
 use crate::snapshot_provenance::SnapshotInstallProvenance;
 use crate::status::SyncStatus;
 use bitcoincore_rpc::bitcoin::OutPoint;
@@ -10,7 +15,10 @@ use reqwest::Client;
 use serde::Deserialize;
 use serde_json::{Value, json};
 use std::ops::Range;
-use usdb_util::{ConsensusQueryContext, ConsensusRpcErrorData, USDBScriptHash};
+use usdb_util::{
+    BalanceDeltaProof, BalanceStateProof, ConsensusQueryContext, ConsensusRpcErrorData,
+    USDBScriptHash,
+};
 
 pub struct RpcClient {
     url: String,
@@ -119,6 +127,11 @@ impl RpcClient {
         .await
     }
 
+    pub async fn get_utxo_set_hash(&self, block_height: u32) -> Result<Option<String>, String> {
+        self.rpc_call::<Option<String>>(&self.url, "get_utxo_set_hash", json!([block_height]))
+            .await
+    }
+
     // Query the current live UTXO view persisted by balance-history itself.
     pub async fn get_live_utxo(&self, outpoint: OutPoint) -> Result<Option<UtxoInfo>, String> {
         let params = json!([outpoint]);
@@ -126,6 +139,64 @@ impl RpcClient {
             .await
     }
 
+    // Query one outpoint as it was at a historical height, from the UTXO history index.
+    pub async fn get_utxo_at_height(
+        &self,
+        outpoint: OutPoint,
+        block_height: u32,
+    ) -> Result<Option<HistoricalUtxoInfo>, String> {
+        let params = GetUtxoAtHeightParams {
+            outpoint,
+            block_height,
+        };
+        self.rpc_call::<Option<HistoricalUtxoInfo>>(
+            &self.url,
+            "get_utxo_at_height",
+            json!([params]),
+        )
+        .await
+    }
+
+    // List one page of the live UTXOs owned by a script hash.
+    pub async fn get_address_utxos(
+        &self,
+        params: GetAddressUtxosParams,
+    ) -> Result<AddressUtxoPage, String> {
+        self.rpc_call::<AddressUtxoPage>(&self.url, "get_address_utxos", json!([params]))
+            .await
+    }
+
+    // Get the confirmed balance adjusted by unconfirmed mempool transactions.
+    pub async fn get_address_pending_balance(
+        &self,
+        params: GetAddressPendingBalanceParams,
+    ) -> Result<AddressPendingBalance, String> {
+        self.rpc_call::<AddressPendingBalance>(
+            &self.url,
+            "get_address_pending_balance",
+            json!([params]),
+        )
+        .await
+    }
+
+    // List one page of the transactions that moved value for a script hash.
+    pub async fn get_address_transactions(
+        &self,
+        params: GetAddressTransactionsParams,
+    ) -> Result<AddressTransactionPage, String> {
+        self.rpc_call::<AddressTransactionPage>(
+            &self.url,
+            "get_address_transactions",
+            json!([params]),
+        )
+        .await
+    }
+
+    pub async fn create_checkpoint(&self) -> Result<CheckpointInfo, String> {
+        self.rpc_call::<CheckpointInfo>(&self.url, "create_checkpoint", json!([]))
+            .await
+    }
+
     pub async fn stop(&self) -> Result<(), String> {
         self.rpc_call::<()>(&self.url, "stop", json!([])).await
     }
@@ -246,6 +317,74 @@ impl RpcClient {
             .await
     }
 
+    pub async fn get_descriptor_balance_summary(
+        &self,
+        params: GetDescriptorBalanceSummaryParams,
+    ) -> Result<DescriptorBalanceSummary, String> {
+        self.rpc_call::<DescriptorBalanceSummary>(
+            &self.url,
+            "get_descriptor_balance_summary",
+            json!([params]),
+        )
+        .await
+    }
+
+    pub async fn get_descriptor_balance_timeseries(
+        &self,
+        params: GetDescriptorBalanceBucketsParams,
+    ) -> Result<Vec<AddressBalanceTimeseriesPoint>, String> {
+        self.rpc_call::<Vec<AddressBalanceTimeseriesPoint>>(
+            &self.url,
+            "get_descriptor_balance_timeseries",
+            json!([params]),
+        )
+        .await
+    }
+
+    pub async fn get_descriptor_flow_buckets(
+        &self,
+        params: GetDescriptorBalanceBucketsParams,
+    ) -> Result<Vec<AddressFlowBucket>, String> {
+        self.rpc_call::<Vec<AddressFlowBucket>>(
+            &self.url,
+            "get_descriptor_flow_buckets",
+            json!([params]),
+        )
+        .await
+    }
+
+    pub async fn get_address_balance_proof(
+        &self,
+        script_hash: USDBScriptHash,
+        block_height: u32,
+    ) -> Result<BalanceDeltaProof, String> {
+        self.rpc_call::<BalanceDeltaProof>(
+            &self.url,
+            "get_address_balance_proof",
+            json!([GetAddressBalanceProofParams {
+                script_hash,
+                block_height,
+            }]),
+        )
+        .await
+    }
+
+    pub async fn get_address_balance_state_proof(
+        &self,
+        script_hash: USDBScriptHash,
+        block_height: u32,
+    ) -> Result<BalanceStateProof, String> {
+        self.rpc_call::<BalanceStateProof>(
+            &self.url,
+            "get_address_balance_state_proof",
+            json!([GetBalanceStateProofParams {
+                script_hash,
+                block_height,
+            }]),
+        )
+        .await
+    }
+
     pub async fn resolve_script_hashes(
         &self,
         script_hashes: Vec<USDBScriptHash>,
@@ -0,0 +1,870 @@
+use crate::db::BalanceHistoryDBRef;
+use crate::mempool::MempoolTrackerRef;
+use bitcoincore_rpc::bitcoin::block::Header;
+use bitcoincore_rpc::bitcoin::consensus::encode::serialize_hex;
+use bitcoincore_rpc::bitcoin::{BlockHash, OutPoint, Txid};
+use serde::Serialize;
+use serde_json::{Value, json};
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::{mpsc, watch};
+use tokio::task::JoinHandle;
+use usdb_util::{BTCRpcClient, USDBScriptHash, parse_script_hash};
+
+// Electrum protocol subset served from the local balance-history DB.
+//
+// Requests are newline-delimited JSON-RPC objects or batches over plain TCP, as spoken by
+// Electrum wallets and electrs. Only confirmed data that balance-history already indexes is
+// served; `get_history` and `listunspent` do not report mempool transactions, while
+// `get_balance` takes its unconfirmed part from the optional mempool tracker.
+
+/// Protocol version reported by `server.version`.
+pub const ELECTRUM_PROTOCOL_VERSION: &str = "1.4";
+/// Maximum rows returned by `get_history` and `listunspent` for one script hash.
+pub const MAX_ELECTRUM_SCRIPT_ITEMS: usize = 10_000;
+
+const MAX_ELECTRUM_REQUEST_SIZE: u64 = 1024 * 1024;
+const ELECTRUM_OUTBOUND_CAPACITY: usize = 256;
+const ELECTRUM_TIP_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+const JSONRPC_PARSE_ERROR: i64 = -32700;
+const JSONRPC_INVALID_REQUEST: i64 = -32600;
+const JSONRPC_METHOD_NOT_FOUND: i64 = -32601;
+const JSONRPC_INVALID_PARAMS: i64 = -32602;
+const JSONRPC_INTERNAL_ERROR: i64 = -32603;
+
+/// Block header lookup used for `blockchain.headers.subscribe`.
+pub trait HeaderSource: Send + Sync {
+    fn get_block_header(&self, block_hash: &BlockHash) -> Result<Header, String>;
+}
+
+impl HeaderSource for BTCRpcClient {
+    fn get_block_header(&self, block_hash: &BlockHash) -> Result<Header, String> {
+        self.get_block_header(block_hash)
+    }
+}
+
+/// Tip notification payload of `blockchain.headers.subscribe`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ElectrumHeader {
+    pub height: u32,
+    /// Consensus-serialized 80-byte header, hex encoded.
+    pub hex: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct ElectrumError {
+    code: i64,
+    message: String,
+}
+
+impl ElectrumError {
+    fn invalid_params(message: String) -> Self {
+        Self {
+            code: JSONRPC_INVALID_PARAMS,
+            message,
+        }
+    }
+
+    fn internal(message: String) -> Self {
+        error!("{}", message);
+        Self {
+            code: JSONRPC_INTERNAL_ERROR,
+            message,
+        }
+    }
+}
+
+/// Answers Electrum requests from the balance-history DB.
+pub struct ElectrumHandler {
+    db: BalanceHistoryDBRef,
+    headers: Arc<dyn HeaderSource>,
+    mempool: Option<MempoolTrackerRef>,
+}
+
+pub type ElectrumHandlerRef = Arc<ElectrumHandler>;
+
+impl ElectrumHandler {
+    pub fn new(db: BalanceHistoryDBRef, headers: Arc<dyn HeaderSource>) -> Self {
+        Self {
+            db,
+            headers,
+            mempool: None,
+        }
+    }
+
+    pub fn with_mempool_tracker(mut self, mempool: MempoolTrackerRef) -> Self {
+        self.mempool = Some(mempool);
+        self
+    }
+
+    /// Header of the latest committed block, `None` before the first commit.
+    pub fn get_tip_header(&self) -> Result<Option<ElectrumHeader>, String> {
+        let height = self.db.get_btc_block_height()?;
+        // Read the hash from our own commit so the header matches the indexed chain.
+        let Some(commit) = self.db.get_block_commit(height)? else {
+            return Ok(None);
+        };
+        let header = self.headers.get_block_header(&commit.btc_block_hash)?;
+
+        Ok(Some(ElectrumHeader {
+            height,
+            hex: serialize_hex(&header),
+        }))
+    }
+
+    fn handle(&self, method: &str, params: &[Value]) -> Result<Value, ElectrumError> {
+        match method {
+            "server.version" => Ok(json!([
+                format!("balance-history {}", env!("CARGO_PKG_VERSION")),
+                ELECTRUM_PROTOCOL_VERSION
+            ])),
+            "server.ping" => Ok(Value::Null),
+            "blockchain.headers.subscribe" => match self.get_tip_header() {
+                Ok(Some(header)) => Ok(json!(header)),
+                Ok(None) => Err(ElectrumError::internal(
+                    "No block has been indexed yet".to_string(),
+                )),
+                Err(e) => Err(ElectrumError::internal(format!(
+                    "Failed to get tip header: {}",
+                    e
+                ))),
+            },
+            "blockchain.scripthash.get_balance" => {
+                self.get_balance(&Self::parse_script_hash_param(params)?)
+            }
+            "blockchain.scripthash.get_history" => {
+                self.get_history(&Self::parse_script_hash_param(params)?)
+            }
+            "blockchain.scripthash.listunspent" => {
+                self.list_unspent(&Self::parse_script_hash_param(params)?)
+            }
+            _ => Err(ElectrumError {
+                code: JSONRPC_METHOD_NOT_FOUND,
+                message: format!("Method not found: {}", method),
+            }),
+        }
+    }
+
+    fn parse_script_hash_param(params: &[Value]) -> Result<USDBScriptHash, ElectrumError> {
+        let value = params.first().and_then(Value::as_str).ok_or_else(|| {
+            ElectrumError::invalid_params("Invalid params: expected [scripthash]".to_string())
+        })?;
+        parse_script_hash(value).map_err(ElectrumError::invalid_params)
+    }
+
+    fn get_balance(&self, script_hash: &USDBScriptHash) -> Result<Value, ElectrumError> {
+        let confirmed = self.db.get_latest_balance(script_hash).map_err(|e| {
+            ElectrumError::internal(format!(
+                "Failed to get balance of script hash {}: {}",
+                script_hash, e
+            ))
+        })?;
+        let unconfirmed = match &self.mempool {
+            Some(mempool) => {
+                let delta = mempool.get_pending_balance(script_hash).delta;
+                delta.incoming as i64 - delta.outgoing as i64
+            }
+            None => 0,
+        };
+
+        Ok(json!({
+            "confirmed": confirmed.balance,
+            "unconfirmed": unconfirmed,
+        }))
+    }
+
+    // Confirmed transactions of the script ordered by height and position in block.
+    fn load_history(
+        &self,
+        script_hash: &USDBScriptHash,
+    ) -> Result<Vec<(u32, Txid)>, ElectrumError> {
+        let from_height = if self.db.is_address_tx_index_enabled() {
+            self.db.get_address_tx_index_from_height().map_err(|e| {
+                ElectrumError::internal(format!(
+                    "Failed to get address transaction index coverage: {}",
+                    e
+                ))
+            })?
+        } else {
+            None
+        };
+        let Some(from_height) = from_height else {
+            return Err(ElectrumError::invalid_params(
+                "Address transaction index is not enabled on this node".to_string(),
+            ));
+        };
+
+        // The index misses transactions before its coverage start, which is only
+        // harmless when the script had no balance record before it.
+        if from_height > 0 {
+            let before = self
+                .db
+                .get_balance_at_block_height(script_hash, from_height - 1)
+                .map_err(|e| {
+                    ElectrumError::internal(format!(
+                        "Failed to get balance of script hash {} before height {}: {}",
+                        script_hash, from_height, e
+                    ))
+                })?;
+            if before.block_height != 0 || before.delta != 0 || before.balance != 0 {
+                return Err(ElectrumError::invalid_params(format!(
+                    "History of script hash {} starts before the address transaction index at height {}",
+                    script_hash, from_height
+                )));
+            }
+        }
+
+        let entries = self
+            .db
+            .get_address_transactions(script_hash, 0..u32::MAX, 0, MAX_ELECTRUM_SCRIPT_ITEMS + 1)
+            .map_err(|e| {
+                ElectrumError::internal(format!(
+                    "Failed to get transactions of script hash {}: {}",
+                    script_hash, e
+                ))
+            })?;
+        if entries.len() > MAX_ELECTRUM_SCRIPT_ITEMS {
+            return Err(ElectrumError::invalid_params(format!(
+                "History of script hash {} exceeds {} transactions",
+                script_hash, MAX_ELECTRUM_SCRIPT_ITEMS
+            )));
+        }
+
+        Ok(entries
+            .into_iter()
+            .map(|entry| (entry.block_height, entry.txid))
+            .collect())
+    }
+
+    fn get_history(&self, script_hash: &USDBScriptHash) -> Result<Value, ElectrumError> {
+        let history = self.load_history(script_hash)?;
+        Ok(Value::Array(
+            history
+                .into_iter()
+                .map(|(height, txid)| {
+                    json!({
+                        "height": height,
+                        "tx_hash": txid.to_string(),
+                    })
+                })
+                .collect(),
+        ))
+    }
+
+    fn list_unspent(&self, script_hash: &USDBScriptHash) -> Result<Value, ElectrumError> {
+        let (_, utxos) = self
+            .db
+            .get_address_utxos(script_hash, 0, MAX_ELECTRUM_SCRIPT_ITEMS + 1)
+            .map_err(|e| {
+                ElectrumError::internal(format!(
+                    "Failed to get UTXOs of script hash {}: {}",
+                    script_hash, e
+                ))
+            })?;
+        if utxos.len() > MAX_ELECTRUM_SCRIPT_ITEMS {
+            return Err(ElectrumError::invalid_params(format!(
+                "UTXOs of script hash {} exceed {} items",
+                script_hash, MAX_ELECTRUM_SCRIPT_ITEMS
+            )));
+        }
+
+        let outpoints: Vec<OutPoint> = utxos.iter().map(|(outpoint, _)| *outpoint).collect();
+        let heights = self.resolve_utxo_heights(script_hash, &outpoints)?;
+        let mut items: Vec<(u32, OutPoint, u64)> = utxos
+            .into_iter()
+            .zip(heights)
+            .map(|((outpoint, value), height)| (height, outpoint, value))
+            .collect();
+        items.sort_by_key(|(height, outpoint, _)| (*height, outpoint.txid, outpoint.vout));
+
+        Ok(Value::Array(
+            items
+                .into_iter()
+                .map(|(height, outpoint, value)| {
+                    json!({
+                        "height": height,
+                        "tx_pos": outpoint.vout,
+                        "tx_hash": outpoint.txid.to_string(),
+                        "value": value,
+                    })
+                })
+                .collect(),
+        ))
+    }
+
+    // The UTXO index does not keep confirmation heights, so they come from the UTXO
+    // history index when enabled and otherwise from the address transaction index.
+    fn resolve_utxo_heights(
+        &self,
+        script_hash: &USDBScriptHash,
+        outpoints: &[OutPoint],
+    ) -> Result<Vec<u32>, ElectrumError> {
+        let mut heights = vec![None; outpoints.len()];
+        if self.db.is_utxo_history_enabled() {
+            let entries = self.db.get_utxo_history_bulk(outpoints).map_err(|e| {
+                ElectrumError::internal(format!("Failed to get UTXO history: {}", e))
+            })?;
+            for (height, entry) in heights.iter_mut().zip(entries) {
+                *height = entry.and_then(|entry| entry.created_height);
+            }
+        }
+
+        if heights.iter().any(Option::is_none) {
+            let tx_heights: HashMap<Txid, u32> = self
+                .load_history(script_hash)?
+                .into_iter()
+                .map(|(height, txid)| (txid, height))
+                .collect();
+            for (height, outpoint) in heights.iter_mut().zip(outpoints) {
+                if height.is_none() {
+                    *height = tx_heights.get(&outpoint.txid).copied();
+                }
+            }
+        }
+
+        heights
+            .into_iter()
+            .zip(outpoints)
+            .map(|(height, outpoint)| {
+                height.ok_or_else(|| {
+                    ElectrumError::internal(format!(
+                        "Confirmation height of UTXO {} is not indexed",
+                        outpoint
+                    ))
+                })
+            })
+            .collect()
+    }
+}
+
+/// Electrum protocol TCP listener backed by an `ElectrumHandler`.
+pub struct ElectrumServer {
+    addr: SocketAddr,
+    shutdown_tx: watch::Sender<bool>,
+    tasks: Mutex<Vec<JoinHandle<()>>>,
+}
+
+impl ElectrumServer {
+    /// Binds `addr` and starts accepting connections on the current tokio runtime.
+    pub fn start(addr: SocketAddr, handler: ElectrumHandlerRef) -> Result<Self, String> {
+        let listener = std::net::TcpListener::bind(addr)
+            .and_then(|listener| {
+                listener.set_nonblocking(true)?;
+                Ok(listener)
+            })
+            .and_then(TcpListener::from_std)
+            .map_err(|e| {
+                let msg = format!("Unable to start Electrum server on {}: {}", addr, e);
+                error!("{}", msg);
+                msg
+            })?;
+        let addr = listener.local_addr().map_err(|e| {
+            let msg = format!("Failed to get Electrum server address: {}", e);
+            error!("{}", msg);
+            msg
+        })?;
+
+        let (shutdown_tx, shutdown_rx) = watch::channel(false);
+        let (tip_tx, tip_rx) = watch::channel(None);
+        let tasks = vec![
+            tokio::spawn(Self::poll_tip_loop(
+                handler.clone(),
+                tip_tx,
+                shutdown_rx.clone(),
+            )),
+            tokio::spawn(Self::accept_loop(listener, handler, tip_rx, shutdown_rx)),
+        ];
+        info!("Electrum server listening on {}", addr);
+
+        Ok(Self {
+            addr,
+            shutdown_tx,
+            tasks: Mutex::new(tasks),
+        })
+    }
+
+    pub fn local_addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    pub fn get_listen_url(&self) -> String {
+        format!("tcp://{}", self.addr)
+    }
+
+    /// Stops accepting connections and closes every open connection.
+    pub async fn close(&self) {
+        let _ = self.shutdown_tx.send(true);
+        let tasks = std::mem::take(&mut *self.tasks.lock().unwrap());
+        if tasks.is_empty() {
+            return;
+        }
+
+        for task in tasks {
+            if let Err(e) = task.await {
+                warn!("Electrum server task exited abnormally: {}", e);
+            }
+        }
+        info!("Electrum server closed.");
+    }
+
+    async fn poll_tip_loop(
+        handler: ElectrumHandlerRef,
+        tip_tx: watch::Sender<Option<ElectrumHeader>>,
+        mut shutdown_rx: watch::Receiver<bool>,
+    ) {
+        let mut interval = tokio::time::interval(ELECTRUM_TIP_POLL_INTERVAL);
+        loop {
+            tokio::select! {
+                _ = shutdown_rx.changed() => break,
+                _ = interval.tick() => {
+                    let handler = handler.clone();
+                    match tokio::task::spawn_blocking(move || handler.get_tip_header()).await {
+                        Ok(Ok(Some(header))) => {
+                            tip_tx.send_if_modified(|tip| {
+                                if tip.as_ref() == Some(&header) {
+                                    return false;
+                                }
+                                *tip = Some(header);
+                                true
+                            });
+                        }
+                        Ok(Ok(None)) => {}
+                        Ok(Err(e)) => warn!("Failed to refresh Electrum tip header: {}", e),
+                        Err(e) => warn!("Electrum tip refresh task failed: {}", e),
+                    }
+                }
+            }
+        }
+    }
+
+    async fn accept_loop(
+        listener: TcpListener,
+        handler: ElectrumHandlerRef,
+        tip_rx: watch::Receiver<Option<ElectrumHeader>>,
+        mut shutdown_rx: watch::Receiver<bool>,
+    ) {
+        let mut connections = Vec::new();
+        loop {
+            tokio::select! {
+                _ = shutdown_rx.changed() => break,
+                ret = listener.accept() => match ret {
+                    Ok((stream, peer)) => {
+                        connections.retain(|task: &JoinHandle<()>| !task.is_finished());
+                        let handler = handler.clone();
+                        let tip_rx = tip_rx.clone();
+                        let shutdown_rx = shutdown_rx.clone();
+                        connections.push(tokio::spawn(async move {
+                            if let Err(e) = handle_connection(stream, handler, tip_rx, shutdown_rx).await {
+                                debug!("Electrum connection closed with error: peer={}, error={}", peer, e);
+                            }
+                        }));
+                    }
+                    Err(e) => {
+                        warn!("Failed to accept Electrum connection: {}", e);
+                    }
+                },
+            }
+        }
+
+        for task in connections {
+            let _ = task.await;
+        }
+    }
+}
+
+fn result_response(id: Value, result: Value) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "result": result,
+    })
+}
+
+fn error_response(id: Value, code: i64, message: &str) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "error": {
+            "code": code,
+            "message": message,
+        },
+    })
+}
+
+struct ConnectionState {
+    handler: ElectrumHandlerRef,
+    tip_rx: watch::Receiver<Option<ElectrumHeader>>,
+    headers_subscribed: bool,
+}
+
+impl ConnectionState {
+    async fn handle_line(&mut self, line: &[u8]) -> Value {
+        match serde_json::from_slice::<Value>(line) {
+            Ok(Value::Array(requests)) if !requests.is_empty() => {
+                let mut responses = Vec::with_capacity(requests.len());
+                for request in requests {
+                    responses.push(self.handle_request(request).await);
+                }
+                Value::Array(responses)
+            }
+            Ok(request) => self.handle_request(request).await,
+            Err(e) => error_response(
+                Value::Null,
+                JSONRPC_PARSE_ERROR,
+                &format!("Parse error: {}", e),
+            ),
+        }
+    }
+
+    async fn handle_request(&mut self, request: Value) -> Value {
+        let id = request.get("id").cloned().unwrap_or(Value::Null);
+        let Some(method) = request.get("method").and_then(Value::as_str) else {
+            return error_response(
+                id,
+                JSONRPC_INVALID_REQUEST,
+                "Invalid request: missing method",
+            );
+        };
+        let method = method.to_string();
+        let params = match request.get("params") {
+            None | Some(Value::Null) => Vec::new(),
+            Some(Value::Array(params)) => params.clone(),
+            Some(_) => {
+                return error_response(
+                    id,
+                    JSONRPC_INVALID_PARAMS,
+                    "Invalid params: expected an array",
+                );
+            }
+        };
+
+        if method == "blockchain.headers.subscribe" {
+            // Mark the current tip as seen so the next notification is a new block.
+            self.tip_rx.mark_unchanged();
+            self.headers_subscribed = true;
+        }
+
+        let handler = self.handler.clone();
+        let ret = tokio::task::spawn_blocking(move || handler.handle(&method, &params)).await;
+        match ret {
+            Ok(Ok(result)) => result_response(id, result),
+            Ok(Err(e)) => error_response(id, e.code, &e.message),
+            Err(e) => error_response(
+                id,
+                JSONRPC_INTERNAL_ERROR,
+                &format!("Request task failed: {}", e),
+            ),
+        }
+    }
+}
+
+async fn handle_connection(
+    stream: TcpStream,
+    handler: ElectrumHandlerRef,
+    tip_rx: watch::Receiver<Option<ElectrumHeader>>,
+    mut shutdown_rx: watch::Receiver<bool>,
+) -> Result<(), String> {
+    let (reader, mut writer) = stream.into_split();
+
+    // Lines are read on a separate task so a tip notification never interrupts a
+    // partially read request.
+    let (line_tx, mut line_rx) =
+        mpsc::channel::<Result<Vec<u8>, String>>(ELECTRUM_OUTBOUND_CAPACITY);
+    let reader_task = tokio::spawn(async move {
+        let mut reader = BufReader::new(reader);
+        loop {
+            let mut line = Vec::new();
+            let ret = (&mut reader)
+                .take(MAX_ELECTRUM_REQUEST_SIZE + 1)
+                .read_until(b'\n', &mut line)
+                .await;
+            let item = match ret {
+                Ok(0) => break,
+                Ok(_)
+                    if line.last() != Some(&b'\n')
+                        && line.len() as u64 > MAX_ELECTRUM_REQUEST_SIZE =>
+                {
+                    Err(format!(
+                        "Request exceeds {} bytes",
+                        MAX_ELECTRUM_REQUEST_SIZE
+                    ))
+                }
+                Ok(_) => Ok(line),
+                Err(e) => Err(format!("Electrum read failed: {}", e)),
+            };
+            let stop = item.is_err();
+            if line_tx.send(item).await.is_err() || stop {
+                break;
+            }
+        }
+    });
+
+    let mut state = ConnectionState {
+        handler,
+        tip_rx,
+        headers_subscribed: false,
+    };
+    let result = loop {
+        let message = tokio::select! {
+            _ = shutdown_rx.changed() => break Ok(()),
+            ret = state.tip_rx.changed(), if state.headers_subscribed => {
+                if ret.is_err() {
+                    break Ok(());
+                }
+                let Some(header) = state.tip_rx.borrow_and_update().clone() else {
+                    continue;
+                };
+                json!({
+                    "jsonrpc": "2.0",
+                    "method": "blockchain.headers.subscribe",
+                    "params": [header],
+                })
+            }
+            line = line_rx.recv() => match line {
+                None => break Ok(()),
+                Some(Err(e)) => break Err(e),
+                Some(Ok(line)) => {
+                    if line.iter().all(u8::is_ascii_whitespace) {
+                        continue;
+                    }
+                    state.handle_line(&line).await
+                }
+            },
+        };
+
+        let mut text = message.to_string();
+        text.push('\n');
+        if let Err(e) = writer.write_all(text.as_bytes()).await {
+            break Err(format!("Electrum write failed: {}", e));
+        }
+    };
+
+    reader_task.abort();
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::BalanceHistoryConfig;
+    use crate::db::{
+        AddressTxIndexEntry, AddressTxIndexUpdate, BalanceHistoryDB, BalanceHistoryDBMode,
+        BalanceHistoryEntry, BlockCommitEntry, BlockStateUpdateBatch,
+    };
+    use bitcoincore_rpc::bitcoin::hashes::Hash;
+    use bitcoincore_rpc::bitcoin::{CompactTarget, TxMerkleNode};
+    use std::time::{SystemTime, UNIX_EPOCH};
+    use usdb_util::UTXOValue;
+
+    struct MockHeaders;
+
+    impl HeaderSource for MockHeaders {
+        fn get_block_header(&self, block_hash: &BlockHash) -> Result<Header, String> {
+            Ok(Header {
+                version: bitcoincore_rpc::bitcoin::block::Version::TWO,
+                prev_blockhash: *block_hash,
+                merkle_root: TxMerkleNode::all_zeros(),
+                time: 1_700_000_000,
+                bits: CompactTarget::from_consensus(0x1d00ffff),
+                nonce: 7,
+            })
+        }
+    }
+
+    fn make_handler(tag: &str, address_transactions: bool) -> ElectrumHandler {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_nanos();
+        let mut config = BalanceHistoryConfig::default();
+        config.root_dir =
+            std::env::temp_dir().join(format!("balance_history_electrum_{}_{}", tag, nanos));
+        config.sync.address_transactions = address_transactions;
+        std::fs::create_dir_all(&config.root_dir).unwrap();
+        let db = Arc::new(
+            BalanceHistoryDB::open(Arc::new(config), BalanceHistoryDBMode::Normal).unwrap(),
+        );
+
+        ElectrumHandler::new(db, Arc::new(MockHeaders))
+    }
+
+    fn seed_commit(handler: &ElectrumHandler, block_height: u32) {
+        let commit = BlockCommitEntry {
+            block_height,
+            btc_block_hash: BlockHash::from_slice(&[9; 32]).unwrap(),
+            balance_delta_root: [1; 32],
+            block_commit: [2; 32],
+        };
+        handler
+            .db
+            .update_address_history_with_block_commits_async(&Vec::new(), block_height, &[commit])
+            .unwrap();
+    }
+
+    #[test]
+    fn test_get_balance_and_history_require_tx_index() {
+        let handler = make_handler("balance", false);
+        let script_hash = USDBScriptHash::from_byte_array([4; 32]);
+        handler
+            .db
+            .put_address_history_async(&vec![BalanceHistoryEntry {
+                script_hash,
+                block_height: 5,
+                delta: 700,
+                balance: 700,
+            }])
+            .unwrap();
+
+        let params = [json!(format!("{:x}", script_hash))];
+        let balance = handler
+            .handle("blockchain.scripthash.get_balance", &params)
+            .unwrap();
+        assert_eq!(balance, json!({"confirmed": 700, "unconfirmed": 0}));
+
+        let err = handler
+            .handle("blockchain.scripthash.get_history", &params)
+            .unwrap_err();
+        assert_eq!(err.code, JSONRPC_INVALID_PARAMS);
+        assert!(err.message.contains("not enabled"));
+
+        let err = handler
+            .handle("blockchain.scripthash.get_balance", &[json!("zz")])
+            .unwrap_err();
+        assert_eq!(err.code, JSONRPC_INVALID_PARAMS);
+        let err = handler.handle("blockchain.block.header", &[]).unwrap_err();
+        assert_eq!(err.code, JSONRPC_METHOD_NOT_FOUND);
+    }
+
+    #[test]
+    fn test_get_history_and_listunspent_from_tx_index() {
+        let handler = make_handler("history", true);
+        let script_hash = USDBScriptHash::from_byte_array([6; 32]);
+        let funding = Txid::from_byte_array([1; 32]);
+        let change = Txid::from_byte_array([2; 32]);
+        let tx_index = AddressTxIndexUpdate {
+            first_block_height: 0,
+            entries: vec![
+                AddressTxIndexEntry {
+                    script_hash,
+                    block_height: 3,
+                    tx_index: 1,
+                    txid: funding,
+                    input_value: 0,
+                    output_value: 1_000,
+                },
+                AddressTxIndexEntry {
+                    script_hash,
+                    block_height: 8,
+                    tx_index: 4,
+                    txid: change,
+                    input_value: 1_000,
+                    output_value: 600,
+                },
+            ],
+        };
+        handler
+            .db
+            .update_block_state_batch_async(BlockStateUpdateBatch {
+                new_utxos: &[(
+                    Arc::new(OutPoint::new(change, 1)),
+                    Arc::new(UTXOValue {
+                        script_hash,
+                        value: 600,
+                    }),
+                )],
+                remove_utxos: &[],
+                entries_list: &[],
+                block_height: 8,
+                block_commits: &[],
+                script_registry_entries: &[],
+                undo_bundles: &[],
+                utxo_set_hash: None,
+                address_transactions: Some(&tx_index),
+                utxo_history: None,
+            })
+            .unwrap();
+
+        let params = [json!(format!("{:x}", script_hash))];
+        let history = handler
+            .handle("blockchain.scripthash.get_history", &params)
+            .unwrap();
+        assert_eq!(
+            history,
+            json!([
+                {"height": 3, "tx_hash": funding.to_string()},
+                {"height": 8, "tx_hash": change.to_string()},
+            ])
+        );
+
+        let unspent = handler
+            .handle("blockchain.scripthash.listunspent", &params)
+            .unwrap();
+        assert_eq!(
+            unspent,
+            json!([
+                {"height": 8, "tx_pos": 1, "tx_hash": change.to_string(), "value": 600},
+            ])
+        );
+    }
+
+    #[tokio::test]
+    async fn test_electrum_server_roundtrip_and_header_notification() {
+        let handler = Arc::new(make_handler("server", false));
+        seed_commit(&handler, 10);
+        let server =
+            ElectrumServer::start("127.0.0.1:0".parse().unwrap(), handler.clone()).unwrap();
+
+        let stream = TcpStream::connect(server.local_addr()).await.unwrap();
+        let (reader, mut writer) = stream.into_split();
+        let mut lines = BufReader::new(reader).lines();
+        let request = |body: Value| {
+            let mut text = body.to_string();
+            text.push('\n');
+            text
+        };
+
+        writer
+            .write_all(
+                request(json!({"id": 1, "method": "server.version", "params": ["test", "1.4"]}))
+                    .as_bytes(),
+            )
+            .await
+            .unwrap();
+        let response: Value =
+            serde_json::from_str(&lines.next_line().await.unwrap().unwrap()).unwrap();
+        assert_eq!(response["id"], 1);
+        assert_eq!(response["result"][1], ELECTRUM_PROTOCOL_VERSION);
+
+        writer
+            .write_all(request(json!([{"id": 2, "method": "blockchain.headers.subscribe"}, {"id": 3, "method": "server.ping"}])).as_bytes())
+            .await
+            .unwrap();
+        let response: Value =
+            serde_json::from_str(&lines.next_line().await.unwrap().unwrap()).unwrap();
+        assert_eq!(response[0]["result"]["height"], 10);
+        assert_eq!(response[0]["result"]["hex"].as_str().unwrap().len(), 160);
+        assert_eq!(response[1]["id"], 3);
+
+        seed_commit(&handler, 11);
+        let notification: Value = tokio::time::timeout(Duration::from_secs(10), async {
+            loop {
+                let line = lines.next_line().await.unwrap().unwrap();
+                let value: Value = serde_json::from_str(&line).unwrap();
+                if value["params"][0]["height"] == 11 {
+                    break value;
+                }
+            }
+        })
+        .await
+        .unwrap();
+        assert_eq!(notification["method"], "blockchain.headers.subscribe");
+
+        server.close().await;
+        assert!(lines.next_line().await.unwrap().is_none());
+    }
+}
@@ -1,10 +1,14 @@
 mod client;
+mod electrum;
 mod rpc;
 mod server;
+mod snapshot_http;
 mod state_ref;
 
 #[allow(unused_imports)]
 pub use client::*;
+pub use electrum::*;
 pub use rpc::*;
 pub use server::*;
+pub use snapshot_http::*;
 pub use state_ref::*;
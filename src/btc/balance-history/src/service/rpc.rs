@@ -1,3 +1,4 @@
+use crate::index::CheckpointInfo;
 use crate::snapshot_provenance::{
     SnapshotInstallOrigin, SnapshotInstallProvenance, SnapshotVerificationState,
 };
@@ -8,7 +9,8 @@ use jsonrpc_derive::rpc;
 use serde::{Deserialize, Serialize};
 use std::ops::Range;
 use usdb_util::{
-    ConsensusQueryContext, ConsensusSnapshotIdentity, ConsensusStateReference, USDBScriptHash,
+    BalanceDeltaProof, BalanceStateProof, ConsensusQueryContext, ConsensusSnapshotIdentity,
+    ConsensusStateReference, USDBScriptHash,
 };
 
 /// Public RPC/API version of balance-history.
@@ -173,6 +175,67 @@ pub struct AddressFlowBucket {
     pub change_count: u64,
 }
 
+/// Query parameters for one wallet-level aggregate over output descriptors.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GetDescriptorBalanceSummaryParams {
+    /// Output descriptors of the wallet, such as `wpkh([fp/84h/0h/0h]xpub.../<0;1>/*)`.
+    ///
+    /// Supported forms are `wpkh`, `sh(wpkh)` and key-path only `tr` over one
+    /// xpub/tpub. A trailing `#checksum` is verified when present.
+    pub descriptors: Vec<String>,
+
+    /// Consecutive unused scripts that end the scan of a ranged descriptor.
+    /// Defaults to 20.
+    pub gap_limit: Option<u32>,
+
+    /// Half-open range `[start, end)` to summarize.
+    pub block_range: Range<u32>,
+}
+
+/// Query parameters for bucketed wallet-level aggregates over output descriptors.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GetDescriptorBalanceBucketsParams {
+    /// Output descriptors of the wallet, same format as the summary query.
+    pub descriptors: Vec<String>,
+
+    /// Consecutive unused scripts that end the scan of a ranged descriptor.
+    /// Defaults to 20.
+    pub gap_limit: Option<u32>,
+
+    /// Half-open range `[start, end)` to aggregate.
+    pub block_range: Range<u32>,
+
+    /// Number of blocks covered by each bucket.
+    pub bucket_size: u32,
+}
+
+/// One used script derived from a wallet descriptor.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DescriptorScriptInfo {
+    /// Canonical descriptor branch the script was derived from, without key origin.
+    pub descriptor: String,
+    /// Child index for ranged descriptors, `None` for fixed ones.
+    pub index: Option<u32>,
+    /// Script hash in balance-history's canonical internal format.
+    pub script_hash: String,
+    /// BTC address for the service network.
+    pub address: Option<String>,
+    /// Balance at or before `range_end - 1`, in satoshi.
+    pub end_balance: u64,
+}
+
+/// Wallet-level balance summary over all used scripts of a set of descriptors.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DescriptorBalanceSummary {
+    /// Aggregate over the wallet. Movements are merged per block, so transfers
+    /// between scripts of the same wallet inside one block net out.
+    pub summary: AddressBalanceSummary,
+    /// Number of scripts derived while scanning, including unused ones.
+    pub scanned_script_count: u64,
+    /// Scripts with at least one record at or before `range_end - 1`.
+    pub scripts: Vec<DescriptorScriptInfo>,
+}
+
 /// Stable snapshot metadata exposed to downstream consumers.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SnapshotInfo {
@@ -200,6 +263,9 @@ pub struct SnapshotInfo {
     pub commit_protocol_version: String,
     /// Hash algorithm used to build `latest_block_commit`.
     pub commit_hash_algo: String,
+    /// Full balance state root at `stable_height`, once activated for the network.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub balance_state_root: Option<String>,
 }
 
 /// Normalized inputs required to derive one current `ConsensusStateReference`
@@ -233,6 +299,7 @@ impl From<SnapshotStateReferenceSeed> for ConsensusStateReference {
                         .clone(),
                     usdb_index_formula_version: usdb_util::USDB_INDEX_FORMULA_VERSION.to_string(),
                     usdb_index_protocol_version: usdb_util::USDB_INDEX_PROTOCOL_VERSION.to_string(),
+                    balance_state_root: seed.snapshot.balance_state_root.clone(),
                 };
                 usdb_util::build_consensus_snapshot_id(&identity)
             });
@@ -265,6 +332,7 @@ impl From<HistoricalSnapshotStateRef> for SnapshotInfo {
                 .balance_history_semantics_version,
             commit_protocol_version: state_ref.commit_protocol_version,
             commit_hash_algo: state_ref.commit_hash_algo,
+            balance_state_root: state_ref.consensus_identity.balance_state_root,
         }
     }
 }
@@ -436,6 +504,28 @@ pub struct BlockCommitInfo {
     pub commit_hash_algo: String,
 }
 
+/// WebSocket subscription method streaming `StableBlockEvent` notifications.
+pub const SUBSCRIBE_NEW_STABLE_BLOCK: &str = "subscribe_new_stable_block";
+
+/// Event pushed to `subscribe_new_stable_block` subscribers.
+///
+/// Events are published only after the block batch is persisted. Subscribers
+/// that miss events (reconnect or lag) must resync through `get_block_commit`
+/// and `get_readiness`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum StableBlockEvent {
+    /// A new stable block was committed; fields mirror `BlockCommitInfo`.
+    NewStableBlock(BlockCommitInfo),
+    /// Local state was rolled back by a BTC reorg.
+    Rollback {
+        /// Local synced height before the rollback.
+        from_height: u32,
+        /// Common ancestor height that is now the local synced height.
+        to_height: u32,
+    },
+}
+
 /// One currently-live UTXO entry stored by balance-history.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct UtxoInfo {
@@ -449,6 +539,150 @@ pub struct UtxoInfo {
     pub value: u64,
 }
 
+/// Query parameters for looking up one outpoint at a historical height.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GetUtxoAtHeightParams {
+    /// Outpoint to look up.
+    pub outpoint: OutPoint,
+    /// BTC block height whose post-block UTXO set should be queried.
+    pub block_height: u32,
+}
+
+/// One UTXO that was unspent at the requested height.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistoricalUtxoInfo {
+    /// Outpoint, owner and value of the UTXO.
+    #[serde(flatten)]
+    pub utxo: UtxoInfo,
+    /// Height of the block that created the output, or `None` when it was created
+    /// before the UTXO history index coverage started.
+    pub created_height: Option<u32>,
+    /// Height of the block that spent the output, or `None` while it is still
+    /// unspent. May be above the requested height.
+    pub spent_height: Option<u32>,
+}
+
+/// Query parameters for one page of the live UTXOs owned by a script hash.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GetAddressUtxosParams {
+    /// Target script hash in balance-history's canonical internal format.
+    pub script_hash: USDBScriptHash,
+    /// Zero-based page index.
+    pub page: usize,
+    /// Number of rows per page.
+    pub page_size: usize,
+}
+
+/// One page of live UTXOs owned by a script hash.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AddressUtxoPage {
+    /// Stable height of the UTXO set that this page was read from.
+    pub stable_height: u32,
+    /// Live UTXOs in the requested page, ordered by outpoint.
+    pub items: Vec<UtxoInfo>,
+    /// Whether more UTXOs follow this page at `stable_height`.
+    pub has_more: bool,
+}
+
+/// Query parameters for the mempool-adjusted balance of one script hash.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GetAddressPendingBalanceParams {
+    /// Target script hash in balance-history's canonical internal format.
+    pub script_hash: USDBScriptHash,
+}
+
+/// Confirmed balance of one script hash plus the effect of unconfirmed transactions.
+///
+/// This is a best-effort, non-consensus view read from the upstream mempool. It
+/// changes without new blocks and must never be used to derive state refs.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AddressPendingBalance {
+    /// Machine-readable policy describing the pending view semantics.
+    pub policy: String,
+    /// Stable height that `confirmed_balance` was read at.
+    pub stable_height: u32,
+    /// Latest confirmed balance at or before `stable_height`, in satoshi.
+    pub confirmed_balance: u64,
+    /// Value paid to the script by mempool transactions, in satoshi.
+    pub pending_incoming: u64,
+    /// Value of the script's outputs spent by mempool transactions, in satoshi.
+    pub pending_outgoing: u64,
+    /// `confirmed_balance + pending_incoming - pending_outgoing`, floored at zero.
+    pub pending_balance: u64,
+    /// Number of mempool transactions touching the script.
+    pub pending_tx_count: u32,
+    /// Number of mempool transactions currently tracked by the service.
+    pub mempool_tx_count: u64,
+    /// Unix time of the last successful mempool poll, `None` before the first one.
+    pub mempool_refreshed_at: Option<u64>,
+}
+
+/// Query parameters for one page of the transactions that moved value for a script hash.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GetAddressTransactionsParams {
+    /// Target script hash in balance-history's canonical internal format.
+    pub script_hash: USDBScriptHash,
+
+    /// Optional half-open range `[start, end)` of block heights.
+    ///
+    /// When omitted, the range covers every indexed height up to the current
+    /// stable height.
+    pub block_range: Option<Range<u32>>,
+
+    /// Zero-based page index.
+    pub page: usize,
+
+    /// Number of rows per page.
+    pub page_size: usize,
+}
+
+/// One transaction that spent from or paid to a script hash.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AddressTransaction {
+    /// Height of the block containing the transaction.
+    pub block_height: u32,
+    /// Position of the transaction inside its block.
+    pub tx_index: u32,
+    /// Transaction id, lowercase hex.
+    pub txid: String,
+    /// Total value of the script's UTXOs spent by the transaction inputs, in satoshi.
+    pub input_value: u64,
+    /// Total value of the transaction outputs paying to the script, in satoshi.
+    pub output_value: u64,
+}
+
+/// One page of transactions for a script hash.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AddressTransactionPage {
+    /// Stable height that the requested range was validated against.
+    pub stable_height: u32,
+    /// Transactions in the requested page, ordered by height and position in block.
+    pub items: Vec<AddressTransaction>,
+    /// Whether more transactions follow this page inside the requested range.
+    pub has_more: bool,
+}
+
+/// Query parameters for one balance inclusion proof.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GetAddressBalanceProofParams {
+    /// Target script hash in balance-history's canonical internal format.
+    pub script_hash: USDBScriptHash,
+
+    /// Query height with at-or-before semantics: the proof covers the latest
+    /// persisted balance record whose block height is `<= block_height`.
+    pub block_height: u32,
+}
+
+/// Query parameters for one full balance state proof.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GetBalanceStateProofParams {
+    /// Target script hash in balance-history's canonical internal format.
+    pub script_hash: USDBScriptHash,
+
+    /// Exact query height. Only the current stable height is served.
+    pub block_height: u32,
+}
+
 /// Parameters for resolving stored script hashes into display-oriented BTC script metadata.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ResolveScriptHashesParams {
@@ -552,6 +786,14 @@ pub trait BalanceHistoryRpc {
     #[rpc(name = "get_block_commit")]
     fn get_block_commit(&self, block_height: u32) -> JsonResult<Option<BlockCommitInfo>>;
 
+    /// Returns the MuHash3072 of the UTXO set after one exact BTC block height.
+    ///
+    /// The hex value uses bitcoind's byte order, so it can be compared directly with
+    /// `gettxoutsetinfo muhash <height>`. Returns `None` when `sync.utxo_set_hash` is
+    /// disabled or no UTXO set hash has been persisted for the requested height.
+    #[rpc(name = "get_utxo_set_hash")]
+    fn get_utxo_set_hash(&self, block_height: u32) -> JsonResult<Option<String>>;
+
     /// Returns balance records for one script hash.
     ///
     /// Semantics depend on the selector in `params`:
@@ -634,6 +876,75 @@ pub trait BalanceHistoryRpc {
         params: GetAddressBalanceBucketsParams,
     ) -> JsonResult<Vec<AddressFlowBucket>>;
 
+    /// Returns the wallet-level balance summary of a set of output descriptors.
+    ///
+    /// Ranged descriptors are scanned from index 0 until `gap_limit` consecutive
+    /// scripts have no record at or before `range_end - 1`. Used scripts are then
+    /// aggregated like one address, with per-script end balances in `scripts`.
+    ///
+    /// Returns `InvalidParams` for unsupported descriptors, keys of another
+    /// network, or when the scan exceeds the server script limit.
+    #[rpc(name = "get_descriptor_balance_summary")]
+    fn get_descriptor_balance_summary(
+        &self,
+        params: GetDescriptorBalanceSummaryParams,
+    ) -> JsonResult<DescriptorBalanceSummary>;
+
+    /// Returns downsampled wallet balance points over fixed block buckets.
+    ///
+    /// Uses the same descriptor scan as `get_descriptor_balance_summary` and the
+    /// same bucket layout as `get_address_balance_timeseries`.
+    #[rpc(name = "get_descriptor_balance_timeseries")]
+    fn get_descriptor_balance_timeseries(
+        &self,
+        params: GetDescriptorBalanceBucketsParams,
+    ) -> JsonResult<Vec<AddressBalanceTimeseriesPoint>>;
+
+    /// Returns bucketed wallet inflow/outflow aggregates.
+    ///
+    /// Uses the same descriptor scan as `get_descriptor_balance_summary` and the
+    /// same bucket layout as `get_address_flow_buckets`.
+    #[rpc(name = "get_descriptor_flow_buckets")]
+    fn get_descriptor_flow_buckets(
+        &self,
+        params: GetDescriptorBalanceBucketsParams,
+    ) -> JsonResult<Vec<AddressFlowBucket>>;
+
+    /// Returns a Merkle inclusion proof of one script's balance record.
+    ///
+    /// The proven leaf is the latest record at or before `params.block_height`,
+    /// so the returned `block_height` may be lower than the query height. The
+    /// proof binds the leaf to the block commit at that height; callers check it with
+    /// `usdb_util::verify_balance_delta_proof` and then compare `block_commit`
+    /// with a commit they already trust.
+    ///
+    /// Returns shared consensus error `NO_RECORD` when the script has no record
+    /// at or before the height, and `HISTORY_NOT_AVAILABLE` when the leaf block
+    /// was committed under the flat v1 rule or its leaf index is not present
+    /// locally, for example below a snapshot install height.
+    #[rpc(name = "get_address_balance_proof")]
+    fn get_address_balance_proof(
+        &self,
+        params: GetAddressBalanceProofParams,
+    ) -> JsonResult<BalanceDeltaProof>;
+
+    /// Returns a sparse Merkle proof of one script's balance in the full balance state.
+    ///
+    /// Unlike `get_address_balance_proof`, the proof also covers scripts whose balance
+    /// is zero or that never appeared, proving the leaf is empty. Callers check it with
+    /// `usdb_util::verify_balance_state_proof` and then compare `balance_state_root`
+    /// with the `balance_state_root` of a snapshot identity they already trust.
+    ///
+    /// Only the tree at the current stable height is kept, so `params.block_height` must
+    /// equal it. Returns shared consensus error `HISTORY_NOT_AVAILABLE` for any other
+    /// height, before the state root is activated, or when the state advanced while the
+    /// proof was being built.
+    #[rpc(name = "get_address_balance_state_proof")]
+    fn get_address_balance_state_proof(
+        &self,
+        params: GetBalanceStateProofParams,
+    ) -> JsonResult<BalanceStateProof>;
+
     /// Gets one currently-live UTXO from balance-history's persisted UTXO view.
     ///
     /// This endpoint only reads the service's own DB state and returns `None`
@@ -645,6 +956,62 @@ pub trait BalanceHistoryRpc {
     #[rpc(name = "get_live_utxo")]
     fn get_live_utxo(&self, outpoint: OutPoint) -> JsonResult<Option<UtxoInfo>>;
 
+    /// Gets one UTXO as it was once block `block_height` was applied.
+    ///
+    /// Returns `None` when the outpoint did not exist or was already spent at that
+    /// height. Requires `sync.utxo_history`; the index only covers heights synced
+    /// while it was enabled.
+    ///
+    /// Returns shared consensus error `HEIGHT_NOT_SYNCED` when `block_height` exceeds
+    /// the current stable height, and `HISTORY_NOT_AVAILABLE` when the index is
+    /// disabled or `block_height` is below the first indexed height.
+    #[rpc(name = "get_utxo_at_height")]
+    fn get_utxo_at_height(
+        &self,
+        params: GetUtxoAtHeightParams,
+    ) -> JsonResult<Option<HistoricalUtxoInfo>>;
+
+    /// Lists one page of the transactions that moved value for a script hash.
+    ///
+    /// Each item aggregates one transaction's inputs and outputs for the script, so
+    /// a self-transfer appears once with both values set. Requires
+    /// `sync.address_transactions`; the index only covers heights synced while it
+    /// was enabled.
+    ///
+    /// Returns shared consensus error `HEIGHT_NOT_SYNCED` when the requested range
+    /// exceeds the current stable height, and `HISTORY_NOT_AVAILABLE` when the index
+    /// is disabled or the range starts below the first indexed height.
+    #[rpc(name = "get_address_transactions")]
+    fn get_address_transactions(
+        &self,
+        params: GetAddressTransactionsParams,
+    ) -> JsonResult<AddressTransactionPage>;
+
+    /// Lists one page of the currently-live UTXOs owned by a script hash.
+    ///
+    /// Items are ordered by outpoint and read from one consistent view of the local
+    /// UTXO set at `stable_height`. Like `get_live_utxo`, this only reads the
+    /// service's own DB state and never falls back to bitcoind RPC. Pages are offset
+    /// based, so callers paging across a height change should restart from page 0
+    /// when `stable_height` moves.
+    ///
+    /// Returns `InvalidParams` when `page_size` is zero or above the server limit.
+    #[rpc(name = "get_address_utxos")]
+    fn get_address_utxos(&self, params: GetAddressUtxosParams) -> JsonResult<AddressUtxoPage>;
+
+    /// Gets the confirmed balance of a script hash adjusted by unconfirmed mempool txs.
+    ///
+    /// The pending part comes from the optional in-memory mempool tracker and is
+    /// not part of any consensus commit or state ref; it can also briefly double
+    /// count or miss a transaction around a new block. Requires `mempool.enabled`.
+    ///
+    /// Returns `InvalidRequest` when the mempool tracker is disabled.
+    #[rpc(name = "get_address_pending_balance")]
+    fn get_address_pending_balance(
+        &self,
+        params: GetAddressPendingBalanceParams,
+    ) -> JsonResult<AddressPendingBalance>;
+
     /// Resolves script hashes through balance-history's auxiliary script registry.
     ///
     /// This endpoint is for display and diagnostics only. It does not alter
@@ -657,6 +1024,15 @@ pub trait BalanceHistoryRpc {
         params: ResolveScriptHashesParams,
     ) -> JsonResult<ScriptHashResolutionResponse>;
 
+    /// Takes a consistent RocksDB checkpoint of every column family while indexing
+    /// continues, and writes a sidecar manifest with the captured stable height and
+    /// block commit.
+    ///
+    /// Checkpoints are written under `${root}/checkpoints/` and restored offline with
+    /// `balance-history restore-checkpoint`.
+    #[rpc(name = "create_checkpoint")]
+    fn create_checkpoint(&self) -> JsonResult<CheckpointInfo>;
+
     /// Requests graceful shutdown of the balance-history process.
     ///
     /// The service sends its internal shutdown signal and starts tearing down the
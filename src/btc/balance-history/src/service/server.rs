@@ -1,30 +1,56 @@
 use super::rpc::*;
 use super::{
-    COMMIT_HASH_ALGO, COMMIT_PROTOCOL_VERSION,
+    COMMIT_HASH_ALGO, build_block_commit_info,
     build_consensus_snapshot_identity as shared_build_consensus_snapshot_identity,
-    build_historical_state_ref_at_height, encode_commit_hex as encode_hex,
+    build_historical_state_ref_at_height, commit_protocol_version_at_height,
+    encode_commit_hex as encode_hex,
 };
 use crate::config::BalanceHistoryConfigRef;
 use crate::db::BalanceHistoryDBRef;
+use crate::index::{CheckpointInfo, create_checkpoint};
+use crate::mempool::MempoolTrackerRef;
 use crate::snapshot_provenance::SnapshotInstallProvenance;
 use crate::status::{SyncStatus, SyncStatusManagerRef};
-use bitcoincore_rpc::bitcoin::{Address, OutPoint, Script};
+use bitcoincore_rpc::bitcoin::{Address, NetworkKind, OutPoint, Script};
 use jsonrpc_core::IoHandler;
 use jsonrpc_core::{Error as JsonError, ErrorCode, Result as JsonResult};
 use jsonrpc_http_server::{AccessControlAllowOrigin, DomainsValidation, ServerBuilder};
+use std::collections::{BTreeMap, HashSet};
 use std::sync::{Arc, Mutex};
 use std::time::Duration;
 use tokio::sync::watch;
 use usdb_util::{
-    BALANCE_HISTORY_SERVICE_NAME, BalanceHistoryData, CONSENSUS_SNAPSHOT_ID_HASH_ALGO,
+    BALANCE_HISTORY_SERVICE_NAME, BalanceDeltaLeaf, BalanceDeltaProof, BalanceDeltaRootRule,
+    BalanceHistoryData, BalanceStateProof, CONSENSUS_SNAPSHOT_ID_HASH_ALGO,
     CONSENSUS_SNAPSHOT_ID_VERSION, ConsensusQueryContext, ConsensusRpcErrorCode,
-    ConsensusRpcErrorData, ConsensusSnapshotIdentity, ConsensusStateReference,
-    build_consensus_snapshot_id,
+    ConsensusRpcErrorData, ConsensusSnapshotIdentity, ConsensusStateReference, ToUSDBScriptHash,
+    USDBScriptHash, WalletDescriptor, build_balance_delta_proof, build_balance_state_proof,
+    build_consensus_snapshot_id, compute_balance_state_root_from_siblings,
+    is_balance_state_root_active, utxo_set_hash_to_hex,
 };
 
 const MAX_ADDRESS_AGGREGATE_BUCKETS: u64 = 2_000;
 const MAX_SCRIPT_RESOLUTION_ITEMS: usize = 1_000;
+const MAX_RPC_PAGE_SIZE: usize = 1_000;
 const SCRIPT_REGISTRY_POLICY: &str = "auxiliary_seen_scripts_non_consensus_v1";
+const PENDING_BALANCE_POLICY: &str = "mempool_pending_non_consensus_v1";
+const MAX_DESCRIPTORS: usize = 16;
+const DEFAULT_DESCRIPTOR_GAP_LIMIT: u32 = 20;
+const MAX_DESCRIPTOR_GAP_LIMIT: u32 = 1_000;
+const MAX_DESCRIPTOR_SCAN_SCRIPTS: u64 = 10_000;
+
+struct DescriptorScript {
+    descriptor: String,
+    index: Option<u32>,
+    address: Option<String>,
+    script_hash: USDBScriptHash,
+    end_balance: u64,
+}
+
+struct DescriptorScan {
+    scanned_script_count: u64,
+    scripts: Vec<DescriptorScript>,
+}
 
 #[derive(Clone)]
 pub struct BalanceHistoryRpcServer {
@@ -32,6 +58,7 @@ pub struct BalanceHistoryRpcServer {
     addr: std::net::SocketAddr,
     status: SyncStatusManagerRef,
     db: BalanceHistoryDBRef,
+    mempool: Option<MempoolTrackerRef>,
     shutdown_tx: watch::Sender<()>,
     server_handle: Arc<Mutex<Option<jsonrpc_http_server::CloseHandle>>>,
 }
@@ -49,11 +76,18 @@ impl BalanceHistoryRpcServer {
             addr,
             status,
             db,
+            mempool: None,
             shutdown_tx,
             server_handle: Arc::new(Mutex::new(None)),
         }
     }
 
+    /// Serves `get_address_pending_balance` from the given mempool tracker.
+    pub fn with_mempool_tracker(mut self, mempool: MempoolTrackerRef) -> Self {
+        self.mempool = Some(mempool);
+        self
+    }
+
     pub fn get_listen_url(&self) -> String {
         format!("http://{}", self.addr)
     }
@@ -62,6 +96,7 @@ impl BalanceHistoryRpcServer {
         config: BalanceHistoryConfigRef,
         status: SyncStatusManagerRef,
         db: BalanceHistoryDBRef,
+        mempool: Option<MempoolTrackerRef>,
         shutdown_tx: watch::Sender<()>,
     ) -> Result<Self, String> {
         let addr = format!("{}:{}", config.rpc_server.host, config.rpc_server.port)
@@ -72,7 +107,10 @@ impl BalanceHistoryRpcServer {
                 msg
             })?;
 
-        let ret = Self::new(config.clone(), addr, status, db, shutdown_tx.clone());
+        let mut ret = Self::new(config.clone(), addr, status, db, shutdown_tx.clone());
+        if let Some(mempool) = mempool {
+            ret = ret.with_mempool_tracker(mempool);
+        }
 
         let mut io = IoHandler::new();
         io.extend_with(ret.clone().to_delegate());
@@ -165,17 +203,49 @@ impl BalanceHistoryRpcServer {
             stable_lag: BALANCE_HISTORY_STABLE_LAG,
             balance_history_api_version: BALANCE_HISTORY_API_VERSION.to_string(),
             balance_history_semantics_version: BALANCE_HISTORY_SEMANTICS_VERSION.to_string(),
-            commit_protocol_version: COMMIT_PROTOCOL_VERSION.to_string(),
+            commit_protocol_version: commit_protocol_version_at_height(
+                self.config.btc.network(),
+                stable_height,
+            )
+            .to_string(),
             commit_hash_algo: COMMIT_HASH_ALGO.to_string(),
+            balance_state_root: self
+                .db
+                .get_balance_state_root(stable_height)?
+                .map(|root| encode_hex(&root)),
         })
     }
 
+    // Mirrors build_historical_state_ref_at_height: once the state root is active for the
+    // height, an unreadable or missing root is an error rather than a root-less identity.
     fn build_consensus_snapshot_identity(
         &self,
         stable_height: u32,
         stable_block_hash: &str,
-    ) -> ConsensusSnapshotIdentity {
-        shared_build_consensus_snapshot_identity(&self.config, stable_height, stable_block_hash)
+    ) -> Result<ConsensusSnapshotIdentity, String> {
+        let network = self.config.btc.network();
+        let balance_state_root = if is_balance_state_root_active(network, stable_height) {
+            match self.db.get_balance_state_root(stable_height)? {
+                Some(root) => Some(encode_hex(&root)),
+                None => {
+                    let msg = format!(
+                        "Missing balance state root at height {} while building consensus snapshot identity",
+                        stable_height
+                    );
+                    error!("{}", msg);
+                    return Err(msg);
+                }
+            }
+        } else {
+            None
+        };
+
+        Ok(shared_build_consensus_snapshot_identity(
+            &self.config,
+            stable_height,
+            stable_block_hash,
+            balance_state_root,
+        ))
     }
 
     fn build_state_ref_at_height(
@@ -479,6 +549,73 @@ impl BalanceHistoryRpcServer {
         Ok(snapshot)
     }
 
+    // Balance history below the retention floor of a pruned node only keeps the latest row
+    // per script hash, so balance queries there must not be answered from what is left.
+    fn ensure_history_retained(
+        &self,
+        requested_height: u32,
+        snapshot: Option<&SnapshotInfo>,
+    ) -> Result<(), JsonError> {
+        let retained_from_height = self
+            .db
+            .get_balance_history_pruned_floor(requested_height)
+            .map_err(|e| {
+                Self::to_internal_error(format!(
+                    "Failed to get balance history retention floor: {}",
+                    e
+                ))
+            })?;
+        if let Some(retained_from_height) = retained_from_height {
+            return Err(Self::to_consensus_error(
+                ConsensusRpcErrorCode::StateNotRetained,
+                self.build_consensus_error_data(
+                    Some(requested_height),
+                    snapshot,
+                    Some(format!(
+                        "Balance history is only retained from height {}",
+                        retained_from_height
+                    )),
+                ),
+            ));
+        }
+
+        Ok(())
+    }
+
+    fn validate_retained_height(&self, requested_height: u32) -> Result<SnapshotInfo, JsonError> {
+        let snapshot = self.validate_requested_height(requested_height)?;
+        self.ensure_history_retained(requested_height, Some(&snapshot))?;
+        Ok(snapshot)
+    }
+
+    fn validate_retained_range(
+        &self,
+        range: &std::ops::Range<u32>,
+    ) -> Result<SnapshotInfo, JsonError> {
+        let snapshot = self.validate_requested_range(range)?;
+        if !range.is_empty() {
+            self.ensure_history_retained(range.start, Some(&snapshot))?;
+        }
+        Ok(snapshot)
+    }
+
+    // Returns the row offset of a validated page.
+    fn validate_page(page: usize, page_size: usize) -> Result<usize, JsonError> {
+        if page_size == 0 || page_size > MAX_RPC_PAGE_SIZE {
+            return Err(Self::to_invalid_params(format!(
+                "page_size must be between 1 and {}, got {}",
+                MAX_RPC_PAGE_SIZE, page_size
+            )));
+        }
+
+        page.checked_mul(page_size).ok_or_else(|| {
+            Self::to_invalid_params(format!(
+                "Page offset overflows: page={}, page_size={}",
+                page, page_size
+            ))
+        })
+    }
+
     fn validate_aggregate_range(
         &self,
         range: &std::ops::Range<u32>,
@@ -490,7 +627,7 @@ impl BalanceHistoryRpcServer {
             )));
         }
 
-        self.validate_requested_range(range)
+        self.validate_retained_range(range)
     }
 
     fn validate_bucket_params(
@@ -612,20 +749,35 @@ impl BalanceHistoryRpcServer {
     ) -> Result<AddressBalanceSummary, JsonError> {
         let start = self.get_balance_before_range(script_hash, range.start)?;
         let end = self.get_balance_at_range_end(script_hash, range.end)?;
+        Ok(Self::summarize_balance_rows(
+            range,
+            start.balance,
+            end.balance,
+            rows,
+        ))
+    }
+
+    // Shared by address and descriptor summaries; `rows` must be ordered by height.
+    fn summarize_balance_rows(
+        range: &std::ops::Range<u32>,
+        start_balance: u64,
+        end_balance: u64,
+        rows: &[BalanceHistoryData],
+    ) -> AddressBalanceSummary {
         let mut summary = AddressBalanceSummary {
             range_start: range.start,
             range_end: range.end,
-            start_balance: start.balance,
-            end_balance: end.balance,
+            start_balance,
+            end_balance,
             change_count: 0,
             total_inflow: 0,
             total_outflow: 0,
             net_delta: 0,
             first_movement_height: None,
             latest_movement_height: None,
-            peak_balance: start.balance,
+            peak_balance: start_balance,
             peak_height: range.start,
-            low_balance: start.balance,
+            low_balance: start_balance,
             low_height: range.start,
         };
 
@@ -650,7 +802,214 @@ impl BalanceHistoryRpcServer {
             }
         }
 
-        Ok(summary)
+        summary
+    }
+
+    fn build_balance_timeseries(
+        range: &std::ops::Range<u32>,
+        bucket_size: u32,
+        start_balance: u64,
+        rows: &[BalanceHistoryData],
+    ) -> Vec<AddressBalanceTimeseriesPoint> {
+        let mut result = Vec::new();
+        let mut row_index = 0usize;
+        let mut bucket_start = range.start;
+        let mut current_balance = start_balance;
+        while bucket_start < range.end {
+            let bucket_end = bucket_start.saturating_add(bucket_size).min(range.end);
+            let mut net_delta = 0i64;
+            let mut change_count = 0u64;
+            let mut latest_movement_height = None;
+
+            while row_index < rows.len() && rows[row_index].block_height < bucket_end {
+                let row = &rows[row_index];
+                debug_assert!(row.block_height >= bucket_start);
+                current_balance = row.balance;
+                net_delta += row.delta;
+                change_count += 1;
+                latest_movement_height = Some(row.block_height);
+                row_index += 1;
+            }
+
+            result.push(AddressBalanceTimeseriesPoint {
+                bucket_start,
+                bucket_end,
+                balance: current_balance,
+                net_delta,
+                change_count,
+                latest_movement_height,
+            });
+
+            bucket_start = bucket_end;
+        }
+
+        result
+    }
+
+    fn build_flow_buckets(
+        range: &std::ops::Range<u32>,
+        bucket_size: u32,
+        rows: &[BalanceHistoryData],
+    ) -> Vec<AddressFlowBucket> {
+        let mut result = Vec::new();
+        let mut row_index = 0usize;
+        let mut bucket_start = range.start;
+        while bucket_start < range.end {
+            let bucket_end = bucket_start.saturating_add(bucket_size).min(range.end);
+            let mut inflow = 0u64;
+            let mut outflow = 0u64;
+            let mut net_delta = 0i64;
+            let mut change_count = 0u64;
+
+            while row_index < rows.len() && rows[row_index].block_height < bucket_end {
+                let row = &rows[row_index];
+                debug_assert!(row.block_height >= bucket_start);
+                if row.delta >= 0 {
+                    inflow += row.delta as u64;
+                } else {
+                    outflow += (-row.delta) as u64;
+                }
+                net_delta += row.delta;
+                change_count += 1;
+                row_index += 1;
+            }
+
+            result.push(AddressFlowBucket {
+                bucket_start,
+                bucket_end,
+                inflow,
+                outflow,
+                net_delta,
+                change_count,
+            });
+
+            bucket_start = bucket_end;
+        }
+
+        result
+    }
+
+    // Derives the scripts of every descriptor and keeps the ones with a record at or
+    // before `range.end - 1`. Ranged descriptors stop after `gap_limit` unused scripts.
+    fn scan_descriptor_scripts(
+        &self,
+        descriptors: &[String],
+        gap_limit: Option<u32>,
+        range: &std::ops::Range<u32>,
+    ) -> Result<DescriptorScan, JsonError> {
+        if descriptors.is_empty() || descriptors.len() > MAX_DESCRIPTORS {
+            return Err(Self::to_invalid_params(format!(
+                "descriptors length must be between 1 and {}, got {}",
+                MAX_DESCRIPTORS,
+                descriptors.len()
+            )));
+        }
+
+        let gap_limit = gap_limit.unwrap_or(DEFAULT_DESCRIPTOR_GAP_LIMIT);
+        if gap_limit == 0 || gap_limit > MAX_DESCRIPTOR_GAP_LIMIT {
+            return Err(Self::to_invalid_params(format!(
+                "gap_limit must be between 1 and {}, got {}",
+                MAX_DESCRIPTOR_GAP_LIMIT, gap_limit
+            )));
+        }
+
+        let network = self.config.btc.network();
+        let mut scan = DescriptorScan {
+            scanned_script_count: 0,
+            scripts: Vec::new(),
+        };
+        let mut seen = HashSet::new();
+        for text in descriptors {
+            for descriptor in WalletDescriptor::parse(text).map_err(Self::to_invalid_params)? {
+                if descriptor.network_kind() != NetworkKind::from(network) {
+                    return Err(Self::to_invalid_params(format!(
+                        "Descriptor {} does not match service network {}",
+                        descriptor, network
+                    )));
+                }
+
+                let name = descriptor.to_string();
+                let mut index = 0u32;
+                let mut unused_count = 0u32;
+                loop {
+                    if scan.scanned_script_count >= MAX_DESCRIPTOR_SCAN_SCRIPTS {
+                        return Err(Self::to_invalid_params(format!(
+                            "Descriptor scan exceeds maximum {} scripts; lower gap_limit or split the query",
+                            MAX_DESCRIPTOR_SCAN_SCRIPTS
+                        )));
+                    }
+
+                    let script = descriptor
+                        .derive_script(index)
+                        .map_err(Self::to_invalid_params)?;
+                    scan.scanned_script_count += 1;
+
+                    let script_hash = script.to_usdb_script_hash();
+                    let end = self.get_balance_at_range_end(&script_hash, range.end)?;
+                    // The db returns an all-zero record when the script has no history.
+                    let used = end.block_height != 0 || end.delta != 0 || end.balance != 0;
+                    if used {
+                        unused_count = 0;
+                        if seen.insert(script_hash) {
+                            scan.scripts.push(DescriptorScript {
+                                descriptor: name.clone(),
+                                index: descriptor.is_ranged().then_some(index),
+                                address: Address::from_script(&script, network)
+                                    .ok()
+                                    .map(|address| address.to_string()),
+                                script_hash,
+                                end_balance: end.balance,
+                            });
+                        }
+                    } else {
+                        unused_count += 1;
+                    }
+
+                    if !descriptor.is_ranged() || unused_count >= gap_limit {
+                        break;
+                    }
+                    index += 1;
+                }
+            }
+        }
+
+        Ok(scan)
+    }
+
+    // Merges the movements of all wallet scripts per block height, so transfers between
+    // wallet scripts inside one block net out. Returns the wallet balance before the
+    // range together with the merged rows.
+    fn build_descriptor_balance_rows(
+        &self,
+        scan: &DescriptorScan,
+        range: &std::ops::Range<u32>,
+    ) -> Result<(u64, Vec<BalanceHistoryData>), JsonError> {
+        let mut start_balance = 0u64;
+        let mut deltas = BTreeMap::<u32, i64>::new();
+        for script in &scan.scripts {
+            start_balance += self
+                .get_balance_before_range(&script.script_hash, range.start)?
+                .balance;
+            for row in self.get_range_balance_rows(&script.script_hash, range)? {
+                *deltas.entry(row.block_height).or_default() += row.delta;
+            }
+        }
+
+        let mut balance = start_balance as i64;
+        let rows = deltas
+            .into_iter()
+            .filter(|(_, delta)| *delta != 0)
+            .map(|(block_height, delta)| {
+                balance += delta;
+                BalanceHistoryData {
+                    block_height,
+                    delta,
+                    balance: balance as u64,
+                }
+            })
+            .collect();
+
+        Ok((start_balance, rows))
     }
 
     fn readiness_info(&self) -> Result<ReadinessInfo, String> {
@@ -771,63 +1130,355 @@ impl BalanceHistoryRpcServer {
     fn snapshot_provenance(&self) -> Result<Option<SnapshotInstallProvenance>, String> {
         self.db.get_snapshot_install_provenance()
     }
-}
-
-impl BalanceHistoryRpc for BalanceHistoryRpcServer {
-    fn stop(&self) -> JsonResult<()> {
-        info!("Received stop command via RPC.");
-        self.status.set_shutdown_requested(true);
-        if let Err(e) = self.shutdown_tx.send(()) {
-            let msg = format!("Failed to send shutdown signal: {}", e);
-            log::error!("{}", msg);
-            return Err(JsonError {
-                code: ErrorCode::InternalError,
-                message: msg,
-                data: None,
-            });
-        }
-
-        if let Some(handle) = self.server_handle.lock().unwrap().take() {
-            info!("Closing RPC server.");
-            tokio::task::spawn_blocking(move || {
-                std::thread::sleep(Duration::from_millis(500));
-                handle.close();
-            });
-        } else {
-            warn!("RPC server handle not found.");
-        }
 
-        Ok(())
+    fn proof_history_not_available(
+        &self,
+        block_height: u32,
+        snapshot: &SnapshotInfo,
+        detail: String,
+    ) -> JsonError {
+        Self::to_consensus_error(
+            ConsensusRpcErrorCode::HistoryNotAvailable,
+            self.build_consensus_error_data(Some(block_height), Some(snapshot), Some(detail)),
+        )
     }
 
-    fn get_network_type(&self) -> JsonResult<String> {
-        let network = self.config.btc.network();
+    // Rebuild the sorted leaf set of the block that wrote the script's latest record at or
+    // before block_height, and only serve the proof if it reproduces the persisted commit.
+    fn build_address_balance_proof(
+        &self,
+        params: &GetAddressBalanceProofParams,
+    ) -> Result<BalanceDeltaProof, JsonError> {
+        let snapshot = self.validate_retained_height(params.block_height)?;
+        let script_hash = params.script_hash;
 
-        Ok(network.to_string())
-    }
+        let record = self
+            .db
+            .get_balance_at_block_height(&script_hash, params.block_height)
+            .map_err(|e| {
+                Self::to_internal_error(format!(
+                    "Failed to get balance at block height {}: {}",
+                    params.block_height, e
+                ))
+            })?;
+        let leaf_height = record.block_height;
+        let exact = self
+            .db
+            .get_balance_delta_at_block_height(&script_hash, leaf_height)
+            .map_err(|e| {
+                Self::to_internal_error(format!(
+                    "Failed to get balance delta at block height {}: {}",
+                    leaf_height, e
+                ))
+            })?;
+        if exact.is_none() {
+            return Err(Self::to_consensus_error(
+                ConsensusRpcErrorCode::NoRecord,
+                self.build_consensus_error_data(
+                    Some(params.block_height),
+                    Some(&snapshot),
+                    Some(format!(
+                        "No balance record for script hash {} at or before height {}",
+                        script_hash, params.block_height
+                    )),
+                ),
+            ));
+        }
 
-    fn get_block_height(&self) -> JsonResult<u64> {
-        let height = self.db.get_btc_block_height().map_err(|e| JsonError {
-            code: ErrorCode::InternalError,
-            message: format!("Failed to get block height: {}", e),
-            data: None,
-        })?;
+        // The latest record may be a pruned anchor whose sibling leaves are gone.
+        self.ensure_history_retained(leaf_height, Some(&snapshot))?;
 
-        Ok(height as u64)
-    }
+        // A snapshot install collapses history into one record per script at the installed
+        // height and carries no block balance index, so nothing up to that height is provable.
+        let installed_height = self
+            .snapshot_provenance()
+            .map_err(|e| {
+                Self::to_internal_error(format!("Failed to get snapshot provenance: {}", e))
+            })?
+            .map(|provenance| provenance.installed_block_height);
+        if let Some(installed_height) = installed_height.filter(|height| leaf_height <= *height) {
+            return Err(self.proof_history_not_available(
+                leaf_height,
+                &snapshot,
+                format!(
+                    "Balance proofs are only available above snapshot install height {}",
+                    installed_height
+                ),
+            ));
+        }
 
-    fn get_sync_status(&self) -> JsonResult<SyncStatus> {
-        let status = self.status.get_status();
-        Ok(status)
-    }
+        let network = self.config.btc.network();
+        if BalanceDeltaRootRule::at_height(network, leaf_height) != BalanceDeltaRootRule::MerkleV2 {
+            return Err(self.proof_history_not_available(
+                leaf_height,
+                &snapshot,
+                format!(
+                    "Block {} was committed under commit protocol {}, which has no inclusion proofs",
+                    leaf_height,
+                    commit_protocol_version_at_height(network, leaf_height)
+                ),
+            ));
+        }
 
-    fn get_snapshot_info(&self) -> JsonResult<SnapshotInfo> {
-        self.resolve_queryable_snapshot()
-    }
+        let commit = self
+            .db
+            .get_block_commit(leaf_height)
+            .map_err(|e| {
+                Self::to_internal_error(format!(
+                    "Failed to get block commit at height {}: {}",
+                    leaf_height, e
+                ))
+            })?
+            .ok_or_else(|| {
+                self.proof_history_not_available(
+                    leaf_height,
+                    &snapshot,
+                    format!("Missing block commit at height {}", leaf_height),
+                )
+            })?;
+        let prev_block_commit = match leaf_height.checked_sub(1) {
+            Some(prev_height) => self
+                .db
+                .get_block_commit(prev_height)
+                .map_err(|e| {
+                    Self::to_internal_error(format!(
+                        "Failed to get block commit at height {}: {}",
+                        prev_height, e
+                    ))
+                })?
+                .map(|entry| entry.block_commit),
+            None => None,
+        };
+        // Mirror the indexer: a missing commit is only allowed right above genesis.
+        let prev_block_commit = match prev_block_commit {
+            Some(value) => value,
+            None if leaf_height <= 1 => [0u8; 32],
+            None => {
+                return Err(self.proof_history_not_available(
+                    leaf_height,
+                    &snapshot,
+                    format!(
+                        "Missing previous block commit at height {}",
+                        leaf_height - 1
+                    ),
+                ));
+            }
+        };
 
-    fn get_readiness(&self) -> JsonResult<ReadinessInfo> {
-        self.readiness_info().map_err(|e| JsonError {
-            code: ErrorCode::InternalError,
+        let script_hashes = self
+            .db
+            .get_block_balance_index_script_hashes(leaf_height)
+            .map_err(|e| {
+                Self::to_internal_error(format!(
+                    "Failed to get block balance index at height {}: {}",
+                    leaf_height, e
+                ))
+            })?;
+        let Ok(leaf_index) = script_hashes.binary_search(&script_hash) else {
+            return Err(self.proof_history_not_available(
+                leaf_height,
+                &snapshot,
+                format!(
+                    "Block balance index is not available at height {}",
+                    leaf_height
+                ),
+            ));
+        };
+
+        let mut leaves = Vec::with_capacity(script_hashes.len());
+        for indexed_script_hash in &script_hashes {
+            let entry = self
+                .db
+                .get_balance_delta_at_block_height(indexed_script_hash, leaf_height)
+                .map_err(|e| {
+                    Self::to_internal_error(format!(
+                        "Failed to get balance delta at block height {}: {}",
+                        leaf_height, e
+                    ))
+                })?
+                .ok_or_else(|| {
+                    self.proof_history_not_available(
+                        leaf_height,
+                        &snapshot,
+                        format!(
+                            "Block balance index at height {} references missing record {}",
+                            leaf_height, indexed_script_hash
+                        ),
+                    )
+                })?;
+            leaves.push(BalanceDeltaLeaf {
+                script_hash: *indexed_script_hash,
+                delta: entry.delta,
+                balance: entry.balance,
+            });
+        }
+
+        let proof = build_balance_delta_proof(
+            leaf_height,
+            &commit.btc_block_hash,
+            &leaves,
+            leaf_index,
+            &prev_block_commit,
+        )
+        .ok_or_else(|| {
+            Self::to_internal_error(format!(
+                "Failed to build balance proof at height {}",
+                leaf_height
+            ))
+        })?;
+        if proof.block_commit != encode_hex(&commit.block_commit) {
+            return Err(self.proof_history_not_available(
+                leaf_height,
+                &snapshot,
+                format!(
+                    "Block balance index at height {} does not reproduce the persisted block commit",
+                    leaf_height
+                ),
+            ));
+        }
+
+        Ok(proof)
+    }
+
+    // The tree is only kept at the tip, so the proof is read from the live DB and then
+    // checked against the root stored for the requested height.
+    fn build_address_balance_state_proof(
+        &self,
+        params: &GetBalanceStateProofParams,
+    ) -> Result<BalanceStateProof, JsonError> {
+        let snapshot = self.validate_requested_height(params.block_height)?;
+        let block_height = params.block_height;
+        if !is_balance_state_root_active(self.config.btc.network(), block_height) {
+            return Err(self.proof_history_not_available(
+                block_height,
+                &snapshot,
+                format!(
+                    "Balance state root is not active at height {}",
+                    block_height
+                ),
+            ));
+        }
+        if block_height != snapshot.stable_height {
+            return Err(self.proof_history_not_available(
+                block_height,
+                &snapshot,
+                format!(
+                    "Balance state proofs are only served at current stable height {}",
+                    snapshot.stable_height
+                ),
+            ));
+        }
+
+        let root = self
+            .db
+            .get_balance_state_root(block_height)
+            .map_err(|e| {
+                Self::to_internal_error(format!(
+                    "Failed to get balance state root at height {}: {}",
+                    block_height, e
+                ))
+            })?
+            .ok_or_else(|| {
+                self.proof_history_not_available(
+                    block_height,
+                    &snapshot,
+                    format!("Missing balance state root at height {}", block_height),
+                )
+            })?;
+        let (balance, siblings) = self
+            .db
+            .get_balance_state_path(&params.script_hash)
+            .map_err(|e| {
+                Self::to_internal_error(format!(
+                    "Failed to get balance state path for script hash {}: {}",
+                    params.script_hash, e
+                ))
+            })?;
+
+        if compute_balance_state_root_from_siblings(&params.script_hash, balance, &siblings)
+            != Some(root)
+        {
+            return Err(self.proof_history_not_available(
+                block_height,
+                &snapshot,
+                format!(
+                    "Balance state advanced past height {} while building the proof, retry",
+                    block_height
+                ),
+            ));
+        }
+
+        Ok(build_balance_state_proof(
+            block_height,
+            &params.script_hash,
+            balance,
+            &siblings,
+            &root,
+        ))
+    }
+}
+
+impl BalanceHistoryRpc for BalanceHistoryRpcServer {
+    fn create_checkpoint(&self) -> JsonResult<CheckpointInfo> {
+        info!("Received create_checkpoint command via RPC.");
+        create_checkpoint(&self.config, &self.db)
+            .map_err(|e| Self::to_internal_error(format!("Failed to create checkpoint: {}", e)))
+    }
+
+    fn stop(&self) -> JsonResult<()> {
+        info!("Received stop command via RPC.");
+        self.status.set_shutdown_requested(true);
+        if let Err(e) = self.shutdown_tx.send(()) {
+            let msg = format!("Failed to send shutdown signal: {}", e);
+            log::error!("{}", msg);
+            return Err(JsonError {
+                code: ErrorCode::InternalError,
+                message: msg,
+                data: None,
+            });
+        }
+
+        if let Some(handle) = self.server_handle.lock().unwrap().take() {
+            info!("Closing RPC server.");
+            tokio::task::spawn_blocking(move || {
+                std::thread::sleep(Duration::from_millis(500));
+                handle.close();
+            });
+        } else {
+            warn!("RPC server handle not found.");
+        }
+
+        Ok(())
+    }
+
+    fn get_network_type(&self) -> JsonResult<String> {
+        let network = self.config.btc.network();
+
+        Ok(network.to_string())
+    }
+
+    fn get_block_height(&self) -> JsonResult<u64> {
+        let height = self.db.get_btc_block_height().map_err(|e| JsonError {
+            code: ErrorCode::InternalError,
+            message: format!("Failed to get block height: {}", e),
+            data: None,
+        })?;
+
+        Ok(height as u64)
+    }
+
+    fn get_sync_status(&self) -> JsonResult<SyncStatus> {
+        let status = self.status.get_status();
+        Ok(status)
+    }
+
+    fn get_snapshot_info(&self) -> JsonResult<SnapshotInfo> {
+        self.resolve_queryable_snapshot()
+    }
+
+    fn get_readiness(&self) -> JsonResult<ReadinessInfo> {
+        self.readiness_info().map_err(|e| JsonError {
+            code: ErrorCode::InternalError,
             message: format!("Failed to build readiness info: {}", e),
             data: None,
         })
@@ -869,19 +1520,29 @@ impl BalanceHistoryRpc for BalanceHistoryRpcServer {
                 data: None,
             })?;
 
-        Ok(commit.map(|entry| BlockCommitInfo {
-            block_height: entry.block_height,
-            btc_block_hash: format!("{:x}", entry.btc_block_hash),
-            balance_delta_root: encode_hex(&entry.balance_delta_root),
-            block_commit: encode_hex(&entry.block_commit),
-            commit_protocol_version: COMMIT_PROTOCOL_VERSION.to_string(),
-            commit_hash_algo: COMMIT_HASH_ALGO.to_string(),
-        }))
+        Ok(commit
+            .as_ref()
+            .map(|entry| build_block_commit_info(self.config.btc.network(), entry)))
+    }
+
+    fn get_utxo_set_hash(&self, block_height: u32) -> JsonResult<Option<String>> {
+        if !self.db.is_utxo_set_hash_enabled() {
+            return Ok(None);
+        }
+
+        let hash = self.db.get_utxo_set_hash(block_height).map_err(|e| {
+            Self::to_internal_error(format!(
+                "Failed to get UTXO set hash at height {}: {}",
+                block_height, e
+            ))
+        })?;
+
+        Ok(hash.as_ref().map(utxo_set_hash_to_hex))
     }
 
     fn get_address_balance(&self, params: GetBalanceParams) -> JsonResult<Vec<AddressBalance>> {
         if let Some(height) = params.block_height {
-            self.validate_requested_height(height)?;
+            self.validate_retained_height(height)?;
             // This endpoint uses at-or-before semantics:
             // return the latest balance record with block_height <= query height.
             // Callers that need exact block delta should use get_address_balance_delta.
@@ -908,7 +1569,7 @@ impl BalanceHistoryRpc for BalanceHistoryRpcServer {
                 return Ok(Vec::new());
             }
 
-            self.validate_requested_range(&range)?;
+            self.validate_retained_range(&range)?;
 
             let ret = self
                 .db
@@ -972,6 +1633,7 @@ impl BalanceHistoryRpc for BalanceHistoryRpcServer {
         params: GetBalanceParams,
     ) -> JsonResult<Vec<Option<AddressBalance>>> {
         if let Some(height) = params.block_height {
+            self.ensure_history_retained(height, None)?;
             let ret = self
                 .db
                 .get_balance_delta_at_block_height(&params.script_hash, height)
@@ -997,6 +1659,7 @@ impl BalanceHistoryRpc for BalanceHistoryRpcServer {
                 return Ok(Vec::new());
             }
 
+            self.ensure_history_retained(range.start, None)?;
             let ret = self
                 .db
                 .get_balance_in_range(&params.script_hash, range.start, range.end)
@@ -1071,41 +1734,12 @@ impl BalanceHistoryRpc for BalanceHistoryRpcServer {
         let start_balance =
             self.get_balance_before_range(&params.script_hash, params.block_range.start)?;
 
-        let mut result = Vec::new();
-        let mut row_index = 0usize;
-        let mut bucket_start = params.block_range.start;
-        let mut current_balance = start_balance.balance;
-        while bucket_start < params.block_range.end {
-            let bucket_end = bucket_start
-                .saturating_add(params.bucket_size)
-                .min(params.block_range.end);
-            let mut net_delta = 0i64;
-            let mut change_count = 0u64;
-            let mut latest_movement_height = None;
-
-            while row_index < rows.len() && rows[row_index].block_height < bucket_end {
-                let row = &rows[row_index];
-                debug_assert!(row.block_height >= bucket_start);
-                current_balance = row.balance;
-                net_delta += row.delta;
-                change_count += 1;
-                latest_movement_height = Some(row.block_height);
-                row_index += 1;
-            }
-
-            result.push(AddressBalanceTimeseriesPoint {
-                bucket_start,
-                bucket_end,
-                balance: current_balance,
-                net_delta,
-                change_count,
-                latest_movement_height,
-            });
-
-            bucket_start = bucket_end;
-        }
-
-        Ok(result)
+        Ok(Self::build_balance_timeseries(
+            &params.block_range,
+            params.bucket_size,
+            start_balance.balance,
+            &rows,
+        ))
     }
 
     fn get_address_flow_buckets(
@@ -1116,44 +1750,103 @@ impl BalanceHistoryRpc for BalanceHistoryRpcServer {
         self.validate_bucket_params(&params.block_range, params.bucket_size)?;
         let rows = self.get_range_balance_rows(&params.script_hash, &params.block_range)?;
 
-        let mut result = Vec::new();
-        let mut row_index = 0usize;
-        let mut bucket_start = params.block_range.start;
-        while bucket_start < params.block_range.end {
-            let bucket_end = bucket_start
-                .saturating_add(params.bucket_size)
-                .min(params.block_range.end);
-            let mut inflow = 0u64;
-            let mut outflow = 0u64;
-            let mut net_delta = 0i64;
-            let mut change_count = 0u64;
+        Ok(Self::build_flow_buckets(
+            &params.block_range,
+            params.bucket_size,
+            &rows,
+        ))
+    }
 
-            while row_index < rows.len() && rows[row_index].block_height < bucket_end {
-                let row = &rows[row_index];
-                debug_assert!(row.block_height >= bucket_start);
-                if row.delta >= 0 {
-                    inflow += row.delta as u64;
-                } else {
-                    outflow += (-row.delta) as u64;
-                }
-                net_delta += row.delta;
-                change_count += 1;
-                row_index += 1;
-            }
+    fn get_descriptor_balance_summary(
+        &self,
+        params: GetDescriptorBalanceSummaryParams,
+    ) -> JsonResult<DescriptorBalanceSummary> {
+        self.validate_aggregate_range(&params.block_range)?;
+        let scan = self.scan_descriptor_scripts(
+            &params.descriptors,
+            params.gap_limit,
+            &params.block_range,
+        )?;
+        let (start_balance, rows) =
+            self.build_descriptor_balance_rows(&scan, &params.block_range)?;
+        let end_balance = scan.scripts.iter().map(|script| script.end_balance).sum();
+
+        Ok(DescriptorBalanceSummary {
+            summary: Self::summarize_balance_rows(
+                &params.block_range,
+                start_balance,
+                end_balance,
+                &rows,
+            ),
+            scanned_script_count: scan.scanned_script_count,
+            scripts: scan
+                .scripts
+                .into_iter()
+                .map(|script| DescriptorScriptInfo {
+                    descriptor: script.descriptor,
+                    index: script.index,
+                    script_hash: script.script_hash.to_string(),
+                    address: script.address,
+                    end_balance: script.end_balance,
+                })
+                .collect(),
+        })
+    }
 
-            result.push(AddressFlowBucket {
-                bucket_start,
-                bucket_end,
-                inflow,
-                outflow,
-                net_delta,
-                change_count,
-            });
+    fn get_descriptor_balance_timeseries(
+        &self,
+        params: GetDescriptorBalanceBucketsParams,
+    ) -> JsonResult<Vec<AddressBalanceTimeseriesPoint>> {
+        self.validate_aggregate_range(&params.block_range)?;
+        self.validate_bucket_params(&params.block_range, params.bucket_size)?;
+        let scan = self.scan_descriptor_scripts(
+            &params.descriptors,
+            params.gap_limit,
+            &params.block_range,
+        )?;
+        let (start_balance, rows) =
+            self.build_descriptor_balance_rows(&scan, &params.block_range)?;
 
-            bucket_start = bucket_end;
-        }
+        Ok(Self::build_balance_timeseries(
+            &params.block_range,
+            params.bucket_size,
+            start_balance,
+            &rows,
+        ))
+    }
+
+    fn get_descriptor_flow_buckets(
+        &self,
+        params: GetDescriptorBalanceBucketsParams,
+    ) -> JsonResult<Vec<AddressFlowBucket>> {
+        self.validate_aggregate_range(&params.block_range)?;
+        self.validate_bucket_params(&params.block_range, params.bucket_size)?;
+        let scan = self.scan_descriptor_scripts(
+            &params.descriptors,
+            params.gap_limit,
+            &params.block_range,
+        )?;
+        let (_, rows) = self.build_descriptor_balance_rows(&scan, &params.block_range)?;
+
+        Ok(Self::build_flow_buckets(
+            &params.block_range,
+            params.bucket_size,
+            &rows,
+        ))
+    }
+
+    fn get_address_balance_proof(
+        &self,
+        params: GetAddressBalanceProofParams,
+    ) -> JsonResult<BalanceDeltaProof> {
+        self.build_address_balance_proof(&params)
+    }
 
-        Ok(result)
+    fn get_address_balance_state_proof(
+        &self,
+        params: GetBalanceStateProofParams,
+    ) -> JsonResult<BalanceStateProof> {
+        self.build_address_balance_state_proof(&params)
     }
 
     fn get_live_utxo(&self, outpoint: OutPoint) -> JsonResult<Option<UtxoInfo>> {
@@ -1174,36 +1867,253 @@ impl BalanceHistoryRpc for BalanceHistoryRpcServer {
         }))
     }
 
-    fn resolve_script_hashes(
+    fn get_utxo_at_height(
         &self,
-        params: ResolveScriptHashesParams,
-    ) -> JsonResult<ScriptHashResolutionResponse> {
-        self.validate_script_resolution_params(&params)?;
-        let include_script_pubkey = params.include_script_pubkey.unwrap_or(false);
-        let network = self.config.btc.network();
-        let entries = self
+        params: GetUtxoAtHeightParams,
+    ) -> JsonResult<Option<HistoricalUtxoInfo>> {
+        let snapshot = self.validate_requested_height(params.block_height)?;
+
+        let from_height = if self.db.is_utxo_history_enabled() {
+            self.db.get_utxo_history_from_height().map_err(|e| {
+                Self::to_internal_error(format!("Failed to get UTXO history coverage: {}", e))
+            })?
+        } else {
+            None
+        };
+        let Some(from_height) = from_height else {
+            return Err(Self::to_consensus_error(
+                ConsensusRpcErrorCode::HistoryNotAvailable,
+                self.build_consensus_error_data(
+                    Some(params.block_height),
+                    Some(&snapshot),
+                    Some("UTXO history index is not enabled on this node".to_string()),
+                ),
+            ));
+        };
+        if params.block_height < from_height {
+            return Err(Self::to_consensus_error(
+                ConsensusRpcErrorCode::HistoryNotAvailable,
+                self.build_consensus_error_data(
+                    Some(params.block_height),
+                    Some(&snapshot),
+                    Some(format!(
+                        "UTXO history is only indexed from height {}",
+                        from_height
+                    )),
+                ),
+            ));
+        }
+
+        let outpoint = params.outpoint;
+        let entry = self
             .db
-            .get_script_registry_entries(&params.script_hashes)
+            .get_utxo_at_height(&outpoint, params.block_height)
             .map_err(|e| {
-                Self::to_internal_error(format!("Failed to resolve script hashes: {}", e))
+                Self::to_internal_error(format!(
+                    "Failed to get utxo {}:{} at height {}: {}",
+                    outpoint.txid, outpoint.vout, params.block_height, e
+                ))
             })?;
 
-        let items = params
-            .script_hashes
-            .iter()
-            .zip(entries)
-            .map(|(script_hash, script_pubkey)| {
-                let Some(script_pubkey) = script_pubkey else {
-                    return ScriptHashResolution {
-                        script_hash: script_hash.to_string(),
-                        found: false,
-                        script_pubkey: None,
-                        address: None,
-                        address_type: None,
-                        standard: false,
-                    };
-                };
-
+        Ok(entry.map(|entry| HistoricalUtxoInfo {
+            utxo: UtxoInfo {
+                txid: outpoint.txid.to_string(),
+                vout: outpoint.vout,
+                script_hash: format!("{:x}", entry.script_hash),
+                value: entry.value,
+            },
+            created_height: entry.created_height,
+            spent_height: entry.spent_height,
+        }))
+    }
+
+    fn get_address_transactions(
+        &self,
+        params: GetAddressTransactionsParams,
+    ) -> JsonResult<AddressTransactionPage> {
+        let offset = Self::validate_page(params.page, params.page_size)?;
+        let snapshot = match params.block_range.as_ref() {
+            Some(range) => self.validate_requested_range(range)?,
+            None => self.resolve_queryable_snapshot()?,
+        };
+
+        let from_height = if self.db.is_address_tx_index_enabled() {
+            self.db.get_address_tx_index_from_height().map_err(|e| {
+                Self::to_internal_error(format!(
+                    "Failed to get address transaction index coverage: {}",
+                    e
+                ))
+            })?
+        } else {
+            None
+        };
+        let Some(from_height) = from_height else {
+            return Err(Self::to_consensus_error(
+                ConsensusRpcErrorCode::HistoryNotAvailable,
+                self.build_consensus_error_data(
+                    None,
+                    Some(&snapshot),
+                    Some("Address transaction index is not enabled on this node".to_string()),
+                ),
+            ));
+        };
+
+        let range = params
+            .block_range
+            .unwrap_or(from_height..snapshot.stable_height.saturating_add(1));
+        if range.is_empty() {
+            return Ok(AddressTransactionPage {
+                stable_height: snapshot.stable_height,
+                items: Vec::new(),
+                has_more: false,
+            });
+        }
+        if range.start < from_height {
+            return Err(Self::to_consensus_error(
+                ConsensusRpcErrorCode::HistoryNotAvailable,
+                self.build_consensus_error_data(
+                    Some(range.start),
+                    Some(&snapshot),
+                    Some(format!(
+                        "Address transactions are only indexed from height {}",
+                        from_height
+                    )),
+                ),
+            ));
+        }
+
+        // Read one extra row to tell whether another page follows.
+        let mut entries = self
+            .db
+            .get_address_transactions(&params.script_hash, range, offset, params.page_size + 1)
+            .map_err(|e| {
+                Self::to_internal_error(format!(
+                    "Failed to get transactions of script hash {}: {}",
+                    params.script_hash, e
+                ))
+            })?;
+        let has_more = entries.len() > params.page_size;
+        entries.truncate(params.page_size);
+
+        Ok(AddressTransactionPage {
+            stable_height: snapshot.stable_height,
+            items: entries
+                .into_iter()
+                .map(|entry| AddressTransaction {
+                    block_height: entry.block_height,
+                    tx_index: entry.tx_index,
+                    txid: entry.txid.to_string(),
+                    input_value: entry.input_value,
+                    output_value: entry.output_value,
+                })
+                .collect(),
+            has_more,
+        })
+    }
+
+    fn get_address_utxos(&self, params: GetAddressUtxosParams) -> JsonResult<AddressUtxoPage> {
+        let offset = Self::validate_page(params.page, params.page_size)?;
+
+        // Read one extra row to tell whether another page follows.
+        let (stable_height, mut utxos) = self
+            .db
+            .get_address_utxos(&params.script_hash, offset, params.page_size + 1)
+            .map_err(|e| {
+                Self::to_internal_error(format!(
+                    "Failed to get UTXOs of script hash {}: {}",
+                    params.script_hash, e
+                ))
+            })?;
+        let has_more = utxos.len() > params.page_size;
+        utxos.truncate(params.page_size);
+
+        let script_hash = format!("{:x}", params.script_hash);
+        Ok(AddressUtxoPage {
+            stable_height,
+            items: utxos
+                .into_iter()
+                .map(|(outpoint, value)| UtxoInfo {
+                    txid: outpoint.txid.to_string(),
+                    vout: outpoint.vout,
+                    script_hash: script_hash.clone(),
+                    value,
+                })
+                .collect(),
+            has_more,
+        })
+    }
+
+    fn get_address_pending_balance(
+        &self,
+        params: GetAddressPendingBalanceParams,
+    ) -> JsonResult<AddressPendingBalance> {
+        let Some(mempool) = self.mempool.as_ref() else {
+            return Err(JsonError {
+                code: ErrorCode::InvalidRequest,
+                message: "Mempool tracker is not enabled on this node".to_string(),
+                data: None,
+            });
+        };
+
+        let stable_height = self
+            .db
+            .get_btc_block_height()
+            .map_err(|e| Self::to_internal_error(format!("Failed to get stable height: {}", e)))?;
+        let confirmed = self
+            .db
+            .get_balance_at_block_height(&params.script_hash, stable_height)
+            .map_err(|e| {
+                Self::to_internal_error(format!(
+                    "Failed to get balance at block height {}: {}",
+                    stable_height, e
+                ))
+            })?;
+        let view = mempool.get_pending_balance(&params.script_hash);
+
+        Ok(AddressPendingBalance {
+            policy: PENDING_BALANCE_POLICY.to_string(),
+            stable_height,
+            confirmed_balance: confirmed.balance,
+            pending_incoming: view.delta.incoming,
+            pending_outgoing: view.delta.outgoing,
+            pending_balance: (confirmed.balance + view.delta.incoming)
+                .saturating_sub(view.delta.outgoing),
+            pending_tx_count: view.delta.tx_count,
+            mempool_tx_count: view.mempool_tx_count as u64,
+            mempool_refreshed_at: view.refreshed_at,
+        })
+    }
+
+    fn resolve_script_hashes(
+        &self,
+        params: ResolveScriptHashesParams,
+    ) -> JsonResult<ScriptHashResolutionResponse> {
+        self.validate_script_resolution_params(&params)?;
+        let include_script_pubkey = params.include_script_pubkey.unwrap_or(false);
+        let network = self.config.btc.network();
+        let entries = self
+            .db
+            .get_script_registry_entries(&params.script_hashes)
+            .map_err(|e| {
+                Self::to_internal_error(format!("Failed to resolve script hashes: {}", e))
+            })?;
+
+        let items = params
+            .script_hashes
+            .iter()
+            .zip(entries)
+            .map(|(script_hash, script_pubkey)| {
+                let Some(script_pubkey) = script_pubkey else {
+                    return ScriptHashResolution {
+                        script_hash: script_hash.to_string(),
+                        found: false,
+                        script_pubkey: None,
+                        address: None,
+                        address_type: None,
+                        standard: false,
+                    };
+                };
+
                 let address = Address::from_script(&script_pubkey, network)
                     .ok()
                     .map(|address| address.to_string());
@@ -1234,23 +2144,35 @@ mod tests {
     use crate::config::BalanceHistoryConfig;
     use crate::db::{
         BalanceHistoryDB, BalanceHistoryDBMode, BalanceHistoryEntry, BlockCommitEntry,
-        ScriptRegistryEntry,
+        BlockStateUpdateBatch, ScriptRegistryEntry,
     };
+    use crate::mempool::{MempoolSource, MempoolTracker};
+    use crate::service::COMMIT_PROTOCOL_VERSION;
     use crate::snapshot_provenance::{
         SnapshotInstallOrigin, SnapshotInstallProvenance, SnapshotVerificationState,
     };
     use crate::status::SyncStatusManager;
     use bitcoincore_rpc::bitcoin::hashes::Hash;
-    use bitcoincore_rpc::bitcoin::{BlockHash, ScriptBuf};
+    use bitcoincore_rpc::bitcoin::{BlockHash, Network, ScriptBuf, Transaction, Txid};
     use jsonrpc_core::ErrorCode as JsonErrorCode;
     use std::sync::Arc;
     use std::time::{SystemTime, UNIX_EPOCH};
     use usdb_util::{
         BALANCE_HISTORY_SERVICE_NAME, ConsensusQueryContext, ConsensusRpcErrorCode,
         ConsensusRpcErrorData, ConsensusStateReference, ToUSDBScriptHash, USDBScriptHash,
+        compute_balance_delta_merkle_root, compute_balance_delta_root_v2,
+        compute_balance_history_block_commit, hash_balance_delta_leaf, verify_balance_delta_proof,
+        verify_balance_state_proof,
     };
 
     fn make_test_server(tag: &str) -> BalanceHistoryRpcServer {
+        make_test_server_with_config(tag, BalanceHistoryConfig::default())
+    }
+
+    fn make_test_server_with_config(
+        tag: &str,
+        mut config: BalanceHistoryConfig,
+    ) -> BalanceHistoryRpcServer {
         let nanos = SystemTime::now()
             .duration_since(UNIX_EPOCH)
             .unwrap()
@@ -1258,7 +2180,6 @@ mod tests {
         let root_dir = std::env::temp_dir().join(format!("balance_history_rpc_{}_{}", tag, nanos));
         std::fs::create_dir_all(&root_dir).unwrap();
 
-        let mut config = BalanceHistoryConfig::default();
         config.root_dir = root_dir;
         let config = Arc::new(config);
         let db =
@@ -1478,10 +2399,29 @@ mod tests {
             Some(build_consensus_snapshot_id(
                 &server
                     .build_consensus_snapshot_identity(12, &format!("{:x}", commit.btc_block_hash))
+                    .unwrap()
             ))
         );
     }
 
+    #[test]
+    fn test_consensus_snapshot_identity_requires_active_state_root() {
+        let mut config = BalanceHistoryConfig::default();
+        config.btc.network = Network::Regtest;
+        let server = make_test_server_with_config("snapshot_identity_state_root", config);
+
+        let err = server
+            .build_consensus_snapshot_identity(50, &"09".repeat(32))
+            .unwrap_err();
+        assert!(err.contains("Missing balance state root at height 50"));
+
+        let server = make_test_server("snapshot_identity_state_root_inactive");
+        let identity = server
+            .build_consensus_snapshot_identity(50, &"09".repeat(32))
+            .unwrap();
+        assert_eq!(identity.balance_state_root, None);
+    }
+
     #[test]
     fn test_get_state_ref_at_height_returns_history_not_available_when_commit_missing() {
         let server = make_test_server("state_ref_at_height_history_not_available");
@@ -1553,7 +2493,9 @@ mod tests {
         assert_eq!(
             data.actual_state.snapshot_id,
             Some(build_consensus_snapshot_id(
-                &server.build_consensus_snapshot_identity(12, &"09".repeat(32))
+                &server
+                    .build_consensus_snapshot_identity(12, &"09".repeat(32))
+                    .unwrap()
             ))
         );
     }
@@ -1668,6 +2610,160 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_get_address_transactions_returns_history_not_available_when_index_disabled() {
+        let server = make_test_server("address_transactions_disabled");
+        seed_stable_commit(&server, 12, 9);
+
+        let err = server
+            .get_address_transactions(GetAddressTransactionsParams {
+                script_hash: make_script_hash(1),
+                block_range: None,
+                page: 0,
+                page_size: 10,
+            })
+            .unwrap_err();
+        match err.code {
+            JsonErrorCode::ServerError(code) => {
+                assert_eq!(code, ConsensusRpcErrorCode::HistoryNotAvailable.code())
+            }
+            _ => panic!("unexpected error code: {:?}", err.code),
+        }
+        let data = decode_consensus_error_data(&err);
+        assert_eq!(data.upstream_stable_height, Some(12));
+        assert!(data.detail.unwrap().contains("not enabled"));
+    }
+
+    #[test]
+    fn test_get_address_transactions_returns_height_not_synced_for_future_range() {
+        let mut config = BalanceHistoryConfig::default();
+        config.sync.address_transactions = true;
+        let server = make_test_server_with_config("address_transactions_future_range", config);
+        seed_stable_commit(&server, 12, 9);
+
+        let err = server
+            .get_address_transactions(GetAddressTransactionsParams {
+                script_hash: make_script_hash(1),
+                block_range: Some(10..20),
+                page: 0,
+                page_size: 10,
+            })
+            .unwrap_err();
+        match err.code {
+            JsonErrorCode::ServerError(code) => {
+                assert_eq!(code, ConsensusRpcErrorCode::HeightNotSynced.code())
+            }
+            _ => panic!("unexpected error code: {:?}", err.code),
+        }
+        let data = decode_consensus_error_data(&err);
+        assert_eq!(data.requested_height, Some(19));
+        assert_eq!(data.upstream_stable_height, Some(12));
+    }
+
+    #[test]
+    fn test_balance_queries_below_retention_floor_return_state_not_retained() {
+        let server = make_test_server("state_not_retained");
+        let script_hash = make_script_hash(1);
+
+        let commit = BlockCommitEntry {
+            block_height: 12,
+            btc_block_hash: BlockHash::from_slice(&[9u8; 32]).unwrap(),
+            balance_delta_root: [10u8; 32],
+            block_commit: [11u8; 32],
+        };
+        let entries = vec![
+            BalanceHistoryEntry {
+                script_hash,
+                block_height: 5,
+                delta: 100,
+                balance: 100,
+            },
+            BalanceHistoryEntry {
+                script_hash,
+                block_height: 8,
+                delta: 20,
+                balance: 120,
+            },
+        ];
+        server
+            .db
+            .update_address_history_with_block_commits_async(&entries, 12, &[commit])
+            .unwrap();
+        server
+            .db
+            .prune_balance_history_before_height(10, &|| false)
+            .unwrap();
+
+        let assert_not_retained = |err: JsonError, requested_height: u32| {
+            match err.code {
+                JsonErrorCode::ServerError(code) => {
+                    assert_eq!(code, ConsensusRpcErrorCode::StateNotRetained.code())
+                }
+                _ => panic!("unexpected error code: {:?}", err.code),
+            }
+            assert_eq!(
+                err.message,
+                ConsensusRpcErrorCode::StateNotRetained.as_str()
+            );
+            let data = decode_consensus_error_data(&err);
+            assert_eq!(data.requested_height, Some(requested_height));
+        };
+
+        let err = server
+            .get_address_balance(GetBalanceParams {
+                script_hash,
+                block_height: Some(9),
+                block_range: None,
+            })
+            .unwrap_err();
+        assert_not_retained(err, 9);
+
+        let err = server
+            .get_address_balance(GetBalanceParams {
+                script_hash,
+                block_height: None,
+                block_range: Some(9..12),
+            })
+            .unwrap_err();
+        assert_not_retained(err, 9);
+
+        let err = server
+            .get_address_balance_delta(GetBalanceParams {
+                script_hash,
+                block_height: Some(8),
+                block_range: None,
+            })
+            .unwrap_err();
+        assert_not_retained(err, 8);
+
+        let err = server
+            .get_address_balance_summary(GetAddressBalanceSummaryParams {
+                script_hash,
+                block_range: 9..12,
+            })
+            .unwrap_err();
+        assert_not_retained(err, 9);
+
+        // At and above the floor the retained anchor still answers.
+        let balances = server
+            .get_address_balance(GetBalanceParams {
+                script_hash,
+                block_height: Some(10),
+                block_range: None,
+            })
+            .unwrap();
+        assert_eq!(balances[0].block_height, 8);
+        assert_eq!(balances[0].balance, 120);
+
+        let summary = server
+            .get_address_balance_summary(GetAddressBalanceSummaryParams {
+                script_hash,
+                block_range: 10..12,
+            })
+            .unwrap();
+        assert_eq!(summary.start_balance, 120);
+    }
+
     #[test]
     fn test_get_readiness_defaults_to_not_ready_before_rpc_alive() {
         let server = make_test_server("readiness_defaults");
@@ -1815,9 +2911,12 @@ mod tests {
                 manifest_version: Some("balance-history-snapshot-manifest:v1".to_string()),
                 signature_scheme: Some("ed25519".to_string()),
                 signing_key_id: Some("trusted-signer".to_string()),
+                accepted_signing_key_ids: vec!["trusted-signer".to_string()],
+                signature_threshold: Some(1),
                 snapshot_file_sha256: Some("aa".repeat(32)),
                 snapshot_id: Some("bb".repeat(32)),
                 installed_block_height: 12,
+                base_block_height: None,
             })
             .unwrap();
 
@@ -2273,6 +3372,154 @@ mod tests {
         assert!(too_many_buckets.message.contains("exceeds maximum"));
     }
 
+    const TEST_WALLET_XPUB: &str = "xpub6CatWdiZiodmUeTDp8LT5or8nmbKNcuyvz7WyksVFkKB4RHwCD3XyuvPEbvqAQY3rAPshWcMLoP2fMFMKHPJ4ZeZXYVUhLv1VMrjPC7PW6V";
+
+    #[test]
+    fn test_descriptor_aggregate_rpcs_scan_gap_limit_and_merge_wallet_scripts() {
+        let server = make_test_server("descriptor_aggregates");
+        let descriptor = format!("wpkh([73c5da0a/84h/0h/0h]{}/<0;1>/*)", TEST_WALLET_XPUB);
+        let branches = WalletDescriptor::parse(&descriptor).unwrap();
+        let script_hash_of = |branch: usize, index: u32| {
+            branches[branch]
+                .derive_script(index)
+                .unwrap()
+                .to_usdb_script_hash()
+        };
+        let receive_0 = script_hash_of(0, 0);
+        let receive_2 = script_hash_of(0, 2);
+        let receive_6 = script_hash_of(0, 6);
+        let change_0 = script_hash_of(1, 0);
+
+        // receive/0 pays 30 to change/0 with a fee of 10 at height 12, and
+        // receive/6 is beyond a gap limit of 3.
+        seed_balance_entries(
+            &server,
+            &[
+                BalanceHistoryEntry {
+                    script_hash: receive_0,
+                    block_height: 10,
+                    delta: 100,
+                    balance: 100,
+                },
+                BalanceHistoryEntry {
+                    script_hash: receive_0,
+                    block_height: 12,
+                    delta: -40,
+                    balance: 60,
+                },
+                BalanceHistoryEntry {
+                    script_hash: change_0,
+                    block_height: 12,
+                    delta: 30,
+                    balance: 30,
+                },
+                BalanceHistoryEntry {
+                    script_hash: receive_2,
+                    block_height: 15,
+                    delta: 50,
+                    balance: 50,
+                },
+                BalanceHistoryEntry {
+                    script_hash: receive_6,
+                    block_height: 16,
+                    delta: 999,
+                    balance: 999,
+                },
+            ],
+        );
+        seed_stable_commit(&server, 30, 52);
+
+        let ret = server
+            .get_descriptor_balance_summary(GetDescriptorBalanceSummaryParams {
+                descriptors: vec![descriptor.clone()],
+                gap_limit: Some(3),
+                block_range: 9..20,
+            })
+            .unwrap();
+        assert_eq!(ret.scanned_script_count, 10);
+        assert_eq!(ret.scripts.len(), 3);
+        assert_eq!(ret.scripts[0].index, Some(0));
+        assert_eq!(
+            ret.scripts[0].address.as_deref(),
+            Some("bc1qcr8te4kr609gcawutmrza0j4xv80jy8z306fyu")
+        );
+        assert_eq!(ret.scripts[0].end_balance, 60);
+        assert_eq!(ret.scripts[1].script_hash, receive_2.to_string());
+        assert_eq!(ret.scripts[2].script_hash, change_0.to_string());
+        assert_eq!(ret.summary.start_balance, 0);
+        assert_eq!(ret.summary.end_balance, 140);
+        assert_eq!(ret.summary.change_count, 3);
+        assert_eq!(ret.summary.total_inflow, 150);
+        assert_eq!(ret.summary.total_outflow, 10);
+        assert_eq!(ret.summary.net_delta, 140);
+        assert_eq!(ret.summary.peak_balance, 140);
+        assert_eq!(ret.summary.peak_height, 15);
+
+        let timeseries = server
+            .get_descriptor_balance_timeseries(GetDescriptorBalanceBucketsParams {
+                descriptors: vec![descriptor.clone()],
+                gap_limit: Some(3),
+                block_range: 9..20,
+                bucket_size: 5,
+            })
+            .unwrap();
+        assert_eq!(timeseries.len(), 3);
+        assert_eq!(timeseries[0].balance, 90);
+        assert_eq!(timeseries[0].net_delta, 90);
+        assert_eq!(timeseries[0].change_count, 2);
+        assert_eq!(timeseries[1].balance, 140);
+        assert_eq!(timeseries[2].balance, 140);
+
+        let flow = server
+            .get_descriptor_flow_buckets(GetDescriptorBalanceBucketsParams {
+                descriptors: vec![descriptor],
+                gap_limit: Some(7),
+                block_range: 9..20,
+                bucket_size: 5,
+            })
+            .unwrap();
+        assert_eq!(flow[0].inflow, 100);
+        assert_eq!(flow[0].outflow, 10);
+        assert_eq!(flow[1].inflow, 1_049);
+        assert_eq!(flow[1].change_count, 2);
+    }
+
+    #[test]
+    fn test_descriptor_aggregate_rpcs_reject_invalid_descriptors() {
+        let server = make_test_server("descriptor_aggregate_params");
+        seed_stable_commit(&server, 30, 53);
+
+        let cases = [
+            (vec![], None, "descriptors length"),
+            (
+                vec![format!("pkh({}/0/*)", TEST_WALLET_XPUB)],
+                None,
+                "Unsupported descriptor",
+            ),
+            (
+                vec![format!("wpkh({}/0/*)#qqqqqqqq", TEST_WALLET_XPUB)],
+                None,
+                "checksum",
+            ),
+            (
+                vec![format!("wpkh({}/0/*)", TEST_WALLET_XPUB)],
+                Some(0),
+                "gap_limit",
+            ),
+        ];
+        for (descriptors, gap_limit, message) in cases {
+            let err = server
+                .get_descriptor_balance_summary(GetDescriptorBalanceSummaryParams {
+                    descriptors,
+                    gap_limit,
+                    block_range: 0..10,
+                })
+                .unwrap_err();
+            assert_eq!(err.code, ErrorCode::InvalidParams);
+            assert!(err.message.contains(message), "{}", err.message);
+        }
+    }
+
     #[test]
     fn test_get_live_utxo_success() {
         use bitcoincore_rpc::bitcoin::OutPoint;
@@ -2293,6 +3540,67 @@ mod tests {
         assert_eq!(loaded.value, 12345);
     }
 
+    #[test]
+    fn test_get_address_utxos_pages_live_utxos() {
+        use bitcoincore_rpc::bitcoin::OutPoint;
+        use bitcoincore_rpc::bitcoin::Txid;
+
+        let server = make_test_server("get_address_utxos");
+        let script_hash = USDBScriptHash::from_byte_array([3u8; 32]);
+        for seed in 1..=3u8 {
+            let outpoint = OutPoint {
+                txid: Txid::from_slice(&[seed; 32]).unwrap(),
+                vout: 0,
+            };
+            server
+                .db
+                .put_utxo(&outpoint, &script_hash, seed as u64 * 1000)
+                .unwrap();
+        }
+        server
+            .db
+            .put_utxo(
+                &OutPoint {
+                    txid: Txid::from_slice(&[9u8; 32]).unwrap(),
+                    vout: 0,
+                },
+                &USDBScriptHash::from_byte_array([4u8; 32]),
+                5000,
+            )
+            .unwrap();
+
+        let params = |page| GetAddressUtxosParams {
+            script_hash,
+            page,
+            page_size: 2,
+        };
+        let first = server.get_address_utxos(params(0)).unwrap();
+        assert!(first.has_more);
+        assert_eq!(
+            first
+                .items
+                .iter()
+                .map(|item| item.value)
+                .collect::<Vec<_>>(),
+            vec![1000, 2000]
+        );
+        assert_eq!(first.items[0].script_hash, format!("{:x}", script_hash));
+
+        let second = server.get_address_utxos(params(1)).unwrap();
+        assert!(!second.has_more);
+        assert_eq!(second.items.len(), 1);
+        assert_eq!(second.items[0].value, 3000);
+
+        let err = server
+            .get_address_utxos(GetAddressUtxosParams {
+                script_hash,
+                page: 0,
+                page_size: 0,
+            })
+            .unwrap_err();
+        assert_eq!(err.code, ErrorCode::InvalidParams);
+    }
+
     #[test]
     fn test_resolve_script_hashes_returns_addresses_and_missing_rows() {
         let server = make_test_server("resolve_script_hashes");
@@ -2373,4 +3681,414 @@ mod tests {
         assert_eq!(err.code, ErrorCode::InvalidParams);
         assert!(err.message.contains("exceeds maximum"));
     }
+
+    // Persist one Merkle-rule block through the regular block-state batch so the
+    // block balance index is written the same way the indexer writes it.
+    fn seed_merkle_block(
+        server: &BalanceHistoryRpcServer,
+        block_height: u32,
+        entries: &[BalanceHistoryEntry],
+        prev_block_commit: [u8; 32],
+    ) -> BlockCommitEntry {
+        let btc_block_hash = BlockHash::from_slice(&[block_height as u8; 32]).unwrap();
+        let leaf_hashes: Vec<[u8; 32]> = entries
+            .iter()
+            .map(|entry| {
+                hash_balance_delta_leaf(&BalanceDeltaLeaf {
+                    script_hash: entry.script_hash,
+                    delta: entry.delta,
+                    balance: entry.balance,
+                })
+            })
+            .collect();
+        let merkle_root = compute_balance_delta_merkle_root(&leaf_hashes);
+        let balance_delta_root = compute_balance_delta_root_v2(
+            block_height,
+            &btc_block_hash,
+            leaf_hashes.len() as u32,
+            &merkle_root,
+        );
+        let commit = BlockCommitEntry {
+            block_height,
+            btc_block_hash,
+            balance_delta_root,
+            block_commit: compute_balance_history_block_commit(
+                block_height,
+                &btc_block_hash,
+                &balance_delta_root,
+                &prev_block_commit,
+            ),
+        };
+        server
+            .db
+            .update_block_state_batch_async(BlockStateUpdateBatch {
+                new_utxos: &[],
+                remove_utxos: &[],
+                entries_list: entries,
+                block_height,
+                block_commits: std::slice::from_ref(&commit),
+                script_registry_entries: &[],
+                undo_bundles: &[],
+                utxo_set_hash: None,
+                address_transactions: None,
+                utxo_history: None,
+            })
+            .unwrap();
+        commit
+    }
+
+    #[test]
+    fn test_get_address_balance_proof_verifies_against_block_commit() {
+        let mut config = BalanceHistoryConfig::default();
+        config.btc.network = Network::Regtest;
+        let server = make_test_server_with_config("balance_proof", config);
+
+        let first = seed_merkle_block(
+            &server,
+            1,
+            &[
+                BalanceHistoryEntry {
+                    script_hash: make_script_hash(1),
+                    block_height: 1,
+                    delta: 10,
+                    balance: 10,
+                },
+                BalanceHistoryEntry {
+                    script_hash: make_script_hash(2),
+                    block_height: 1,
+                    delta: 20,
+                    balance: 20,
+                },
+                BalanceHistoryEntry {
+                    script_hash: make_script_hash(3),
+                    block_height: 1,
+                    delta: 30,
+                    balance: 30,
+                },
+            ],
+            [0u8; 32],
+        );
+        let second = seed_merkle_block(
+            &server,
+            2,
+            &[BalanceHistoryEntry {
+                script_hash: make_script_hash(1),
+                block_height: 2,
+                delta: -4,
+                balance: 6,
+            }],
+            first.block_commit,
+        );
+
+        let proof = server
+            .get_address_balance_proof(GetAddressBalanceProofParams {
+                script_hash: make_script_hash(2),
+                block_height: 2,
+            })
+            .unwrap();
+        assert_eq!(proof.block_height, 1);
+        assert_eq!(proof.leaf_index, 1);
+        assert_eq!(proof.leaf_count, 3);
+        assert_eq!(proof.leaf.balance, 20);
+        assert_eq!(proof.block_commit, encode_hex(&first.block_commit));
+        verify_balance_delta_proof(&proof).unwrap();
+
+        let proof = server
+            .get_address_balance_proof(GetAddressBalanceProofParams {
+                script_hash: make_script_hash(1),
+                block_height: 2,
+            })
+            .unwrap();
+        assert_eq!(proof.block_height, 2);
+        assert_eq!(proof.leaf.balance, 6);
+        assert_eq!(proof.prev_block_commit, encode_hex(&first.block_commit));
+        assert_eq!(proof.block_commit, encode_hex(&second.block_commit));
+        verify_balance_delta_proof(&proof).unwrap();
+
+        let err = server
+            .get_address_balance_proof(GetAddressBalanceProofParams {
+                script_hash: make_script_hash(9),
+                block_height: 2,
+            })
+            .unwrap_err();
+        assert_eq!(
+            err.code,
+            JsonErrorCode::ServerError(ConsensusRpcErrorCode::NoRecord.code())
+        );
+    }
+
+    #[test]
+    fn test_get_address_balance_proof_rejects_heights_covered_by_snapshot_install() {
+        let mut config = BalanceHistoryConfig::default();
+        config.btc.network = Network::Regtest;
+        let server = make_test_server_with_config("balance_proof_snapshot_install", config);
+
+        let first = seed_merkle_block(
+            &server,
+            1,
+            &[BalanceHistoryEntry {
+                script_hash: make_script_hash(1),
+                block_height: 1,
+                delta: 10,
+                balance: 10,
+            }],
+            [0u8; 32],
+        );
+        seed_merkle_block(
+            &server,
+            2,
+            &[BalanceHistoryEntry {
+                script_hash: make_script_hash(2),
+                block_height: 2,
+                delta: 20,
+                balance: 20,
+            }],
+            first.block_commit,
+        );
+        server
+            .db
+            .put_snapshot_install_provenance(&SnapshotInstallProvenance {
+                origin: SnapshotInstallOrigin::SnapshotInstall,
+                trust_mode: crate::config::SnapshotTrustMode::Dev,
+                verification_state: SnapshotVerificationState::ManifestMissing,
+                manifest_present: false,
+                manifest_verified: false,
+                signature_present: false,
+                signature_verified: false,
+                manifest_version: None,
+                signature_scheme: None,
+                signing_key_id: None,
+                accepted_signing_key_ids: Vec::new(),
+                signature_threshold: None,
+                snapshot_file_sha256: None,
+                snapshot_id: None,
+                installed_block_height: 1,
+                base_block_height: None,
+            })
+            .unwrap();
+
+        let err = server
+            .get_address_balance_proof(GetAddressBalanceProofParams {
+                script_hash: make_script_hash(1),
+                block_height: 2,
+            })
+            .unwrap_err();
+        assert_eq!(
+            err.code,
+            JsonErrorCode::ServerError(ConsensusRpcErrorCode::HistoryNotAvailable.code())
+        );
+        let data = decode_consensus_error_data(&err);
+        assert!(data.detail.unwrap().contains("snapshot install height 1"));
+
+        let proof = server
+            .get_address_balance_proof(GetAddressBalanceProofParams {
+                script_hash: make_script_hash(2),
+                block_height: 2,
+            })
+            .unwrap();
+        assert_eq!(proof.block_height, 2);
+        verify_balance_delta_proof(&proof).unwrap();
+    }
+
+    #[test]
+    fn test_get_address_balance_proof_rejects_flat_rule_blocks() {
+        let server = make_test_server("balance_proof_flat");
+        seed_balance_entries(
+            &server,
+            &[BalanceHistoryEntry {
+                script_hash: make_script_hash(1),
+                block_height: 12,
+                delta: 10,
+                balance: 10,
+            }],
+        );
+        seed_stable_commit(&server, 12, 3);
+
+        let err = server
+            .get_address_balance_proof(GetAddressBalanceProofParams {
+                script_hash: make_script_hash(1),
+                block_height: 12,
+            })
+            .unwrap_err();
+        assert_eq!(
+            err.code,
+            JsonErrorCode::ServerError(ConsensusRpcErrorCode::HistoryNotAvailable.code())
+        );
+        let data = decode_consensus_error_data(&err);
+        assert!(data.detail.unwrap().contains(COMMIT_PROTOCOL_VERSION));
+    }
+
+    #[test]
+    fn test_get_address_balance_state_proof_covers_present_and_absent_scripts() {
+        let mut config = BalanceHistoryConfig::default();
+        config.btc.network = Network::Regtest;
+        let server = make_test_server_with_config("balance_state_proof", config);
+
+        let first = seed_merkle_block(
+            &server,
+            1,
+            &[
+                BalanceHistoryEntry {
+                    script_hash: make_script_hash(1),
+                    block_height: 1,
+                    delta: 10,
+                    balance: 10,
+                },
+                BalanceHistoryEntry {
+                    script_hash: make_script_hash(2),
+                    block_height: 1,
+                    delta: 20,
+                    balance: 20,
+                },
+            ],
+            [0u8; 32],
+        );
+        seed_merkle_block(
+            &server,
+            2,
+            &[BalanceHistoryEntry {
+                script_hash: make_script_hash(1),
+                block_height: 2,
+                delta: -10,
+                balance: 0,
+            }],
+            first.block_commit,
+        );
+
+        let state_ref = server
+            .get_state_ref_at_height(GetStateRefAtHeightParams {
+                block_height: 2,
+                context: None,
+            })
+            .unwrap();
+        let balance_state_root = state_ref
+            .consensus_identity
+            .balance_state_root
+            .clone()
+            .unwrap();
+
+        let proof = server
+            .get_address_balance_state_proof(GetBalanceStateProofParams {
+                script_hash: make_script_hash(2),
+                block_height: 2,
+            })
+            .unwrap();
+        assert_eq!(proof.balance, 20);
+        assert_eq!(proof.balance_state_root, balance_state_root);
+        verify_balance_state_proof(&proof).unwrap();
+
+        // Drained and never-seen scripts both prove an empty leaf.
+        for byte in [1u8, 9u8] {
+            let proof = server
+                .get_address_balance_state_proof(GetBalanceStateProofParams {
+                    script_hash: make_script_hash(byte),
+                    block_height: 2,
+                })
+                .unwrap();
+            assert_eq!(proof.balance, 0);
+            assert_eq!(proof.balance_state_root, balance_state_root);
+            verify_balance_state_proof(&proof).unwrap();
+        }
+
+        let err = server
+            .get_address_balance_state_proof(GetBalanceStateProofParams {
+                script_hash: make_script_hash(2),
+                block_height: 1,
+            })
+            .unwrap_err();
+        assert_eq!(
+            err.code,
+            JsonErrorCode::ServerError(ConsensusRpcErrorCode::HistoryNotAvailable.code())
+        );
+    }
+
+    #[test]
+    fn test_get_address_balance_state_proof_requires_activation() {
+        let server = make_test_server("balance_state_proof_inactive");
+        seed_balance_entries(
+            &server,
+            &[BalanceHistoryEntry {
+                script_hash: make_script_hash(1),
+                block_height: 12,
+                delta: 10,
+                balance: 10,
+            }],
+        );
+        seed_stable_commit(&server, 12, 3);
+
+        let err = server
+            .get_address_balance_state_proof(GetBalanceStateProofParams {
+                script_hash: make_script_hash(1),
+                block_height: 12,
+            })
+            .unwrap_err();
+        assert_eq!(
+            err.code,
+            JsonErrorCode::ServerError(ConsensusRpcErrorCode::HistoryNotAvailable.code())
+        );
+        assert!(
+            server
+                .get_snapshot_info()
+                .unwrap()
+                .balance_state_root
+                .is_none()
+        );
+    }
+
+    struct EmptyMempool;
+
+    impl MempoolSource for EmptyMempool {
+        fn get_raw_mempool(&self) -> Result<Vec<Txid>, String> {
+            Ok(Vec::new())
+        }
+
+        fn check_mempool_entry(&self, txid: &Txid) -> Result<(), String> {
+            Err(format!("Transaction {} not in mempool", txid))
+        }
+
+        fn get_transaction(&self, txid: &Txid) -> Result<Transaction, String> {
+            Err(format!("Transaction {} not found", txid))
+        }
+    }
+
+    #[test]
+    fn test_get_address_pending_balance_requires_mempool_tracker() {
+        let server = make_test_server("pending_balance");
+        let script_hash = make_script_hash(3);
+
+        let err = server
+            .get_address_pending_balance(GetAddressPendingBalanceParams { script_hash })
+            .unwrap_err();
+        assert_eq!(err.code, JsonErrorCode::InvalidRequest);
+
+        seed_balance_entries(
+            &server,
+            &[BalanceHistoryEntry {
+                script_hash,
+                block_height: 10,
+                delta: 50,
+                balance: 50,
+            }],
+        );
+        seed_stable_commit(&server, 12, 4);
+
+        let tracker = Arc::new(MempoolTracker::new(
+            server.config.clone(),
+            Arc::new(EmptyMempool),
+            server.db.clone(),
+        ));
+        tracker.refresh().unwrap();
+        let server = server.with_mempool_tracker(tracker);
+
+        let ret = server
+            .get_address_pending_balance(GetAddressPendingBalanceParams { script_hash })
+            .unwrap();
+        assert_eq!(ret.policy, PENDING_BALANCE_POLICY);
+        assert_eq!(ret.stable_height, 12);
+        assert_eq!(ret.confirmed_balance, 50);
+        assert_eq!(ret.pending_balance, 50);
+        assert_eq!(ret.pending_tx_count, 0);
+        assert_eq!(ret.mempool_tx_count, 0);
+        assert!(ret.mempool_refreshed_at.is_some());
+    }
 }
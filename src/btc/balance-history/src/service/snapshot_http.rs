@@ -0,0 +1,931 @@
+use crate::db::SnapshotHash;
+use crate::index::{
+    SNAPSHOT_CHUNK_SIZE, SnapshotChunkList, SnapshotData, SnapshotManifest,
+    manifest_path_for_snapshot_file, signature_path_for_manifest_file,
+};
+use axum::body::Body;
+use axum::extract::{Path as RoutePath, Request, State};
+use axum::response::{IntoResponse, Response};
+use axum::routing::get;
+use axum::serve::Listener;
+use axum::{Json, Router, serve};
+use reqwest::{Client, StatusCode};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::io::{IoSlice, Read, Seek, SeekFrom, Write};
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+use std::time::{Duration, SystemTime};
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::{OwnedSemaphorePermit, Semaphore, watch};
+use tokio::task::JoinHandle;
+use tower_http::services::ServeFile;
+
+// Snapshot distribution over plain HTTP.
+//
+// The server exposes `${root}/snapshots` read-only:
+//   GET /snapshots                 JSON list of snapshot DB files and their sidecars
+//   GET /snapshots/<name>          one snapshot DB or sidecar file, single `Range` supported
+//   GET /snapshots/<name>/chunks   per-chunk SHA256 list of one snapshot DB file
+// `SnapshotFetcher` downloads a snapshot chunk by chunk, verifies every chunk and resumes
+// from a `.part` file, then leaves trust decisions to `SnapshotInstaller`.
+
+const MAX_CONNECTIONS: usize = 32;
+const CLOSE_TIMEOUT: Duration = Duration::from_secs(10);
+const PARTIAL_FILE_EXTENSION: &str = "part";
+
+/// One downloadable snapshot DB file as listed by `GET /snapshots`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SnapshotFileEntry {
+    pub file_name: String,
+    pub file_size: u64,
+    /// Target height from the manifest, `None` without a manifest.
+    pub block_height: Option<u32>,
+    /// Base height for delta snapshots.
+    pub base_block_height: Option<u32>,
+    pub manifest_file: Option<String>,
+    pub signature_file: Option<String>,
+}
+
+/// Snapshot files and sidecars the server is willing to serve: plain basenames only.
+pub fn is_servable_snapshot_file_name(name: &str) -> bool {
+    !name.is_empty()
+        && !name.starts_with('.')
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '_' | '-'))
+        && (name.ends_with(".db") || name.ends_with(".manifest.json") || name.ends_with(".sig"))
+}
+
+fn file_name_of(path: &Path) -> Option<String> {
+    path.file_name()
+        .and_then(|value| value.to_str())
+        .map(|value| value.to_string())
+}
+
+/// Serves files from the snapshot directory and caches computed chunk lists.
+pub struct SnapshotHttpHandler {
+    snapshot_dir: PathBuf,
+    chunk_cache: Mutex<HashMap<String, (u64, Option<SystemTime>, SnapshotChunkList)>>,
+}
+
+pub type SnapshotHttpHandlerRef = Arc<SnapshotHttpHandler>;
+
+impl SnapshotHttpHandler {
+    pub fn new(snapshot_dir: PathBuf) -> Self {
+        Self {
+            snapshot_dir,
+            chunk_cache: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Lists snapshot DB files together with whatever sidecars sit next to them.
+    pub fn list_snapshots(&self) -> Result<Vec<SnapshotFileEntry>, String> {
+        let dir = match std::fs::read_dir(&self.snapshot_dir) {
+            Ok(dir) => dir,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => {
+                let msg = format!(
+                    "Failed to read snapshot directory {}: {}",
+                    self.snapshot_dir.display(),
+                    e
+                );
+                error!("{}", msg);
+                return Err(msg);
+            }
+        };
+
+        let mut entries = Vec::new();
+        for item in dir.flatten() {
+            let path = item.path();
+            let Some(file_name) = file_name_of(&path) else {
+                continue;
+            };
+            if !file_name.ends_with(".db") || !is_servable_snapshot_file_name(&file_name) {
+                continue;
+            }
+            let Ok(metadata) = item.metadata() else {
+                continue;
+            };
+            if !metadata.is_file() {
+                continue;
+            }
+
+            let manifest_path = manifest_path_for_snapshot_file(&path);
+            let manifest = if manifest_path.is_file() {
+                match SnapshotManifest::load(&manifest_path) {
+                    Ok(manifest) => Some(manifest),
+                    Err(e) => {
+                        warn!("Skipping snapshot {} with bad manifest: {}", file_name, e);
+                        continue;
+                    }
+                }
+            } else {
+                None
+            };
+            let signature_path = signature_path_for_manifest_file(&manifest_path);
+            entries.push(SnapshotFileEntry {
+                file_name,
+                file_size: metadata.len(),
+                block_height: manifest
+                    .as_ref()
+                    .map(|manifest| manifest.state_ref.block_height),
+                base_block_height: manifest
+                    .as_ref()
+                    .and_then(|manifest| manifest.base.as_ref())
+                    .map(|base| base.state_ref.block_height),
+                manifest_file: manifest.as_ref().and_then(|_| file_name_of(&manifest_path)),
+                signature_file: if manifest.is_some() && signature_path.is_file() {
+                    file_name_of(&signature_path)
+                } else {
+                    None
+                },
+            });
+        }
+
+        entries.sort_by(|a, b| a.file_name.cmp(&b.file_name));
+        Ok(entries)
+    }
+
+    /// Chunk list from the manifest when it matches the file, otherwise computed and cached.
+    pub fn get_chunk_list(&self, file_name: &str) -> Result<Option<SnapshotChunkList>, String> {
+        if !file_name.ends_with(".db") || !is_servable_snapshot_file_name(file_name) {
+            return Ok(None);
+        }
+        let path = self.snapshot_dir.join(file_name);
+        let Ok(metadata) = std::fs::metadata(&path) else {
+            return Ok(None);
+        };
+        let file_size = metadata.len();
+        let modified = metadata.modified().ok();
+
+        let manifest_path = manifest_path_for_snapshot_file(&path);
+        if manifest_path.is_file() {
+            let manifest = SnapshotManifest::load(&manifest_path)?;
+            let chunks = manifest
+                .chunks
+                .filter(|chunks| chunks.file_size == file_size);
+            if chunks.is_some() {
+                return Ok(chunks);
+            }
+        }
+
+        let cached = self
+            .chunk_cache
+            .lock()
+            .unwrap()
+            .get(file_name)
+            .filter(|(size, time, _)| *size == file_size && *time == modified)
+            .map(|(_, _, chunks)| chunks.clone());
+        if cached.is_some() {
+            return Ok(cached);
+        }
+        let chunks = SnapshotChunkList::calc(&path, SNAPSHOT_CHUNK_SIZE)?;
+        self.chunk_cache
+            .lock()
+            .unwrap()
+            .insert(file_name.to_string(), (file_size, modified, chunks.clone()));
+        Ok(Some(chunks))
+    }
+}
+
+pub struct SnapshotHttpServer {
+    addr: SocketAddr,
+    shutdown_tx: watch::Sender<bool>,
+    task: Mutex<Option<JoinHandle<()>>>,
+}
+
+impl SnapshotHttpServer {
+    /// Binds `addr` and starts serving on the current tokio runtime.
+    pub fn start(addr: SocketAddr, handler: SnapshotHttpHandlerRef) -> Result<Self, String> {
+        let listener = std::net::TcpListener::bind(addr)
+            .and_then(|listener| {
+                listener.set_nonblocking(true)?;
+                Ok(listener)
+            })
+            .and_then(TcpListener::from_std)
+            .map_err(|e| {
+                let msg = format!("Unable to start snapshot HTTP server on {}: {}", addr, e);
+                error!("{}", msg);
+                msg
+            })?;
+        let addr = listener.local_addr().map_err(|e| {
+            let msg = format!("Failed to get snapshot HTTP server address: {}", e);
+            error!("{}", msg);
+            msg
+        })?;
+
+        let listener = LimitedTcpListener {
+            listener,
+            permits: Arc::new(Semaphore::new(MAX_CONNECTIONS)),
+        };
+        let app = Router::new()
+            .route("/snapshots", get(list_snapshots))
+            .route("/snapshots/{name}", get(get_snapshot_file))
+            .route("/snapshots/{name}/chunks", get(get_snapshot_chunks))
+            .with_state(handler);
+
+        let (shutdown_tx, mut shutdown_rx) = watch::channel(false);
+        let task = tokio::spawn(async move {
+            let ret = serve(listener, app)
+                .with_graceful_shutdown(async move {
+                    let _ = shutdown_rx.changed().await;
+                })
+                .await;
+            if let Err(e) = ret {
+                error!("Snapshot HTTP server exited with error: {}", e);
+            }
+        });
+        info!(
+            "Snapshot HTTP server listening on {}, max_connections={}",
+            addr, MAX_CONNECTIONS
+        );
+
+        Ok(Self {
+            addr,
+            shutdown_tx,
+            task: Mutex::new(Some(task)),
+        })
+    }
+
+    pub fn local_addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    pub fn get_listen_url(&self) -> String {
+        format!("http://{}", self.addr)
+    }
+
+    /// Stops accepting connections and waits up to `CLOSE_TIMEOUT` for in-flight transfers.
+    pub async fn close(&self) {
+        let _ = self.shutdown_tx.send(true);
+        let Some(mut task) = self.task.lock().unwrap().take() else {
+            return;
+        };
+
+        match tokio::time::timeout(CLOSE_TIMEOUT, &mut task).await {
+            Ok(Ok(())) => {}
+            Ok(Err(e)) => warn!("Snapshot HTTP server task exited abnormally: {}", e),
+            Err(_) => {
+                warn!(
+                    "Snapshot HTTP server did not drain within {:?}, aborting",
+                    CLOSE_TIMEOUT
+                );
+                task.abort();
+            }
+        }
+        info!("Snapshot HTTP server closed.");
+    }
+}
+
+/// TCP listener that holds one semaphore permit per open connection, so at most
+/// `MAX_CONNECTIONS` peers are served at once and further peers wait in the backlog.
+struct LimitedTcpListener {
+    listener: TcpListener,
+    permits: Arc<Semaphore>,
+}
+
+impl Listener for LimitedTcpListener {
+    type Io = LimitedTcpStream;
+    type Addr = SocketAddr;
+
+    async fn accept(&mut self) -> (Self::Io, Self::Addr) {
+        let permit = self
+            .permits
+            .clone()
+            .acquire_owned()
+            .await
+            .expect("snapshot HTTP connection semaphore is never closed");
+        // The tokio listener already logs and backs off on accept errors.
+        let (stream, addr) = Listener::accept(&mut self.listener).await;
+        let stream = LimitedTcpStream {
+            stream,
+            _permit: permit,
+        };
+        (stream, addr)
+    }
+
+    fn local_addr(&self) -> std::io::Result<Self::Addr> {
+        self.listener.local_addr()
+    }
+}
+
+/// Connection stream that releases its permit when the connection is dropped.
+struct LimitedTcpStream {
+    stream: TcpStream,
+    _permit: OwnedSemaphorePermit,
+}
+
+impl AsyncRead for LimitedTcpStream {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<std::io::Result<()>> {
+        Pin::new(&mut self.stream).poll_read(cx, buf)
+    }
+}
+
+impl AsyncWrite for LimitedTcpStream {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<std::io::Result<usize>> {
+        Pin::new(&mut self.stream).poll_write(cx, buf)
+    }
+
+    fn poll_write_vectored(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &[IoSlice<'_>],
+    ) -> Poll<std::io::Result<usize>> {
+        Pin::new(&mut self.stream).poll_write_vectored(cx, bufs)
+    }
+
+    fn is_write_vectored(&self) -> bool {
+        self.stream.is_write_vectored()
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        Pin::new(&mut self.stream).poll_flush(cx)
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        Pin::new(&mut self.stream).poll_shutdown(cx)
+    }
+}
+
+async fn list_snapshots(State(handler): State<SnapshotHttpHandlerRef>) -> Response {
+    match tokio::task::spawn_blocking(move || handler.list_snapshots()).await {
+        Ok(Ok(entries)) => Json(entries).into_response(),
+        Ok(Err(_)) => StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+        Err(e) => {
+            error!("Snapshot list task failed: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR.into_response()
+        }
+    }
+}
+
+async fn get_snapshot_chunks(
+    State(handler): State<SnapshotHttpHandlerRef>,
+    RoutePath(file_name): RoutePath<String>,
+) -> Response {
+    match tokio::task::spawn_blocking(move || handler.get_chunk_list(&file_name)).await {
+        Ok(Ok(Some(chunks))) => Json(chunks).into_response(),
+        Ok(Ok(None)) => StatusCode::NOT_FOUND.into_response(),
+        Ok(Err(_)) => StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+        Err(e) => {
+            error!("Snapshot chunk list task failed: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR.into_response()
+        }
+    }
+}
+
+/// Serves one snapshot DB or sidecar file; `ServeFile` handles `HEAD`, `Range` and `416`.
+async fn get_snapshot_file(
+    State(handler): State<SnapshotHttpHandlerRef>,
+    RoutePath(file_name): RoutePath<String>,
+    request: Request,
+) -> Response {
+    if !is_servable_snapshot_file_name(&file_name) {
+        return StatusCode::NOT_FOUND.into_response();
+    }
+
+    let path = handler.snapshot_dir.join(&file_name);
+    match ServeFile::new(&path).try_call(request).await {
+        Ok(response) => response.map(Body::new),
+        Err(e) => {
+            error!("Failed to serve snapshot file {}: {}", path.display(), e);
+            StatusCode::INTERNAL_SERVER_ERROR.into_response()
+        }
+    }
+}
+
+/// Downloads snapshots from a peer's snapshot HTTP server into the local snapshot directory.
+pub struct SnapshotFetcher {
+    base_url: String,
+    snapshot_dir: PathBuf,
+    client: Client,
+    // Snapshots without a manifest cannot be checked against any expected state, so they
+    // are refused unless explicitly allowed.
+    allow_missing_manifest: bool,
+}
+
+impl SnapshotFetcher {
+    pub fn new(peer_url: &str, snapshot_dir: PathBuf) -> Result<Self, String> {
+        let client = Client::builder().build().map_err(|e| {
+            let msg = format!("Failed to build HTTP client: {}", e);
+            error!("{}", msg);
+            msg
+        })?;
+
+        Ok(Self {
+            base_url: format!("{}/snapshots", peer_url.trim_end_matches('/')),
+            snapshot_dir,
+            client,
+            allow_missing_manifest: false,
+        })
+    }
+
+    pub fn with_allow_missing_manifest(mut self, allow: bool) -> Self {
+        self.allow_missing_manifest = allow;
+        self
+    }
+
+    pub async fn list_snapshots(&self) -> Result<Vec<SnapshotFileEntry>, String> {
+        let body = self
+            .get_bytes(&self.base_url)
+            .await?
+            .ok_or_else(|| format!("Snapshot list not found at {}", self.base_url))?;
+        serde_json::from_slice(&body).map_err(|e| {
+            let msg = format!(
+                "Failed to parse snapshot list from {}: {}",
+                self.base_url, e
+            );
+            error!("{}", msg);
+            msg
+        })
+    }
+
+    /// Looks `file_name` up in the peer list, downloads it with its sidecars and returns the
+    /// local paths ready for `SnapshotInstaller`.
+    pub async fn fetch(&self, file_name: &str) -> Result<SnapshotData, String> {
+        let entry = self
+            .list_snapshots()
+            .await?
+            .into_iter()
+            .find(|entry| entry.file_name == file_name)
+            .ok_or_else(|| {
+                let msg = format!("Snapshot {} is not offered by {}", file_name, self.base_url);
+                error!("{}", msg);
+                msg
+            })?;
+        self.fetch_entry(&entry).await
+    }
+
+    pub async fn fetch_entry(&self, entry: &SnapshotFileEntry) -> Result<SnapshotData, String> {
+        for name in std::iter::once(&entry.file_name)
+            .chain(entry.manifest_file.iter())
+            .chain(entry.signature_file.iter())
+        {
+            if !is_servable_snapshot_file_name(name) {
+                let msg = format!("Refusing unsafe snapshot file name from peer: {:?}", name);
+                error!("{}", msg);
+                return Err(msg);
+            }
+        }
+        std::fs::create_dir_all(&self.snapshot_dir).map_err(|e| {
+            let msg = format!(
+                "Failed to create snapshot directory {}: {}",
+                self.snapshot_dir.display(),
+                e
+            );
+            error!("{}", msg);
+            msg
+        })?;
+
+        let (manifest, manifest_bytes) = match &entry.manifest_file {
+            Some(manifest_file) => {
+                let bytes = self.get_sidecar(manifest_file).await?;
+                let manifest: SnapshotManifest = serde_json::from_slice(&bytes).map_err(|e| {
+                    let msg = format!("Failed to parse peer manifest {}: {}", manifest_file, e);
+                    error!("{}", msg);
+                    msg
+                })?;
+                if manifest.file_name != entry.file_name {
+                    let msg = format!(
+                        "Peer manifest {} describes {} instead of {}",
+                        manifest_file, manifest.file_name, entry.file_name
+                    );
+                    error!("{}", msg);
+                    return Err(msg);
+                }
+                (Some(manifest), Some(bytes))
+            }
+            None if self.allow_missing_manifest => {
+                warn!(
+                    "Peer {} offers no manifest for snapshot {}: the download is only checked against the peer's own chunk list",
+                    self.base_url, entry.file_name
+                );
+                (None, None)
+            }
+            None => {
+                let msg = format!(
+                    "Peer {} offers no manifest for snapshot {}; refusing to download it without one",
+                    self.base_url, entry.file_name
+                );
+                error!("{}", msg);
+                return Err(msg);
+            }
+        };
+        let signature_bytes = match &entry.signature_file {
+            Some(signature_file) => Some(self.get_sidecar(signature_file).await?),
+            None => None,
+        };
+
+        let file = self.snapshot_dir.join(&entry.file_name);
+        let reused = file.exists()
+            && match &manifest {
+                Some(manifest) => SnapshotHash::calc_hash(&file)? == manifest.file_sha256,
+                None => false,
+            };
+        if reused {
+            info!(
+                "Snapshot {} already downloaded and verified",
+                file.display()
+            );
+        } else {
+            if file.exists() {
+                let msg = format!(
+                    "Snapshot file {} already exists and does not match the peer manifest",
+                    file.display()
+                );
+                error!("{}", msg);
+                return Err(msg);
+            }
+
+            let chunks = match manifest
+                .as_ref()
+                .and_then(|manifest| manifest.chunks.clone())
+            {
+                Some(chunks) => chunks,
+                None => self.get_chunk_list(&entry.file_name).await?,
+            };
+            chunks.validate()?;
+            self.download_chunks(&entry.file_name, &chunks, manifest.as_ref())
+                .await?;
+        }
+
+        let manifest_file = match (&entry.manifest_file, manifest_bytes) {
+            (Some(name), Some(bytes)) => {
+                let path = self.snapshot_dir.join(name);
+                write_file(&path, &bytes)?;
+                Some(path)
+            }
+            _ => None,
+        };
+        if let (Some(name), Some(bytes)) = (&entry.signature_file, signature_bytes) {
+            write_file(&self.snapshot_dir.join(name), &bytes)?;
+        }
+
+        Ok(SnapshotData {
+            file,
+            manifest_file,
+        })
+    }
+
+    async fn download_chunks(
+        &self,
+        file_name: &str,
+        chunks: &SnapshotChunkList,
+        manifest: Option<&SnapshotManifest>,
+    ) -> Result<(), String> {
+        let file = self.snapshot_dir.join(file_name);
+        let mut partial_file = file.clone().into_os_string();
+        partial_file.push(".");
+        partial_file.push(PARTIAL_FILE_EXTENSION);
+        let partial_file = PathBuf::from(partial_file);
+
+        let mut part = std::fs::OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .read(true)
+            .write(true)
+            .open(&partial_file)
+            .map_err(|e| {
+                let msg = format!(
+                    "Failed to open partial snapshot file {}: {}",
+                    partial_file.display(),
+                    e
+                );
+                error!("{}", msg);
+                msg
+            })?;
+        let first_missing = resume_partial_file(&mut part, chunks)?;
+        if first_missing > 0 {
+            info!(
+                "Resuming snapshot download of {} at chunk {}/{}",
+                file_name,
+                first_missing,
+                chunks.sha256.len()
+            );
+        }
+
+        let url = format!("{}/{}", self.base_url, file_name);
+        for index in first_missing..chunks.sha256.len() {
+            let (start, end) = chunks.chunk_range(index);
+            let response = self
+                .client
+                .get(&url)
+                .header(
+                    reqwest::header::RANGE,
+                    format!("bytes={}-{}", start, end - 1),
+                )
+                .send()
+                .await
+                .map_err(|e| {
+                    let msg = format!("Failed to request chunk {} of {}: {}", index, url, e);
+                    error!("{}", msg);
+                    msg
+                })?;
+            let status = response.status();
+            let whole_file = start == 0 && end == chunks.file_size;
+            if status != StatusCode::PARTIAL_CONTENT && !(status == StatusCode::OK && whole_file) {
+                let msg = format!(
+                    "Unexpected status {} for chunk {} of {}",
+                    status, index, url
+                );
+                error!("{}", msg);
+                return Err(msg);
+            }
+            let data = response.bytes().await.map_err(|e| {
+                let msg = format!("Failed to read chunk {} of {}: {}", index, url, e);
+                error!("{}", msg);
+                msg
+            })?;
+            if data.len() as u64 != end - start
+                || format!("{:x}", Sha256::digest(&data)) != chunks.sha256[index]
+            {
+                let msg = format!("Chunk {} of {} failed hash verification", index, url);
+                error!("{}", msg);
+                return Err(msg);
+            }
+            part.write_all(&data).map_err(|e| {
+                let msg = format!(
+                    "Failed to write partial snapshot file {}: {}",
+                    partial_file.display(),
+                    e
+                );
+                error!("{}", msg);
+                msg
+            })?;
+            debug!(
+                "Downloaded chunk {}/{} of {}",
+                index + 1,
+                chunks.sha256.len(),
+                file_name
+            );
+        }
+        part.sync_all().map_err(|e| {
+            let msg = format!(
+                "Failed to sync partial snapshot file {}: {}",
+                partial_file.display(),
+                e
+            );
+            error!("{}", msg);
+            msg
+        })?;
+        drop(part);
+
+        if let Some(manifest) = manifest {
+            let file_hash = SnapshotHash::calc_hash(&partial_file)?;
+            if file_hash != manifest.file_sha256 {
+                // Every chunk matched, so the chunk list itself disagrees with the manifest.
+                let _ = std::fs::remove_file(&partial_file);
+                let msg = format!(
+                    "Downloaded snapshot {} hash mismatch: expected {}, got {}",
+                    file_name, manifest.file_sha256, file_hash
+                );
+                error!("{}", msg);
+                return Err(msg);
+            }
+        }
+        std::fs::rename(&partial_file, &file).map_err(|e| {
+            let msg = format!(
+                "Failed to move downloaded snapshot {} into place: {}",
+                file.display(),
+                e
+            );
+            error!("{}", msg);
+            msg
+        })?;
+        info!("Snapshot downloaded to {}", file.display());
+
+        Ok(())
+    }
+
+    async fn get_chunk_list(&self, file_name: &str) -> Result<SnapshotChunkList, String> {
+        let url = format!("{}/{}/chunks", self.base_url, file_name);
+        let body = self
+            .get_bytes(&url)
+            .await?
+            .ok_or_else(|| format!("Snapshot chunk list not found at {}", url))?;
+        serde_json::from_slice(&body).map_err(|e| {
+            let msg = format!("Failed to parse snapshot chunk list from {}: {}", url, e);
+            error!("{}", msg);
+            msg
+        })
+    }
+
+    async fn get_sidecar(&self, name: &str) -> Result<Vec<u8>, String> {
+        let url = format!("{}/{}", self.base_url, name);
+        self.get_bytes(&url)
+            .await?
+            .ok_or_else(|| format!("Snapshot sidecar not found at {}", url))
+    }
+
+    // `None` on 404, so callers can tell a missing file from a failed request.
+    async fn get_bytes(&self, url: &str) -> Result<Option<Vec<u8>>, String> {
+        let response = self.client.get(url).send().await.map_err(|e| {
+            let msg = format!("Failed to request {}: {}", url, e);
+            error!("{}", msg);
+            msg
+        })?;
+        if response.status() == StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        if !response.status().is_success() {
+            let msg = format!("Unexpected status {} from {}", response.status(), url);
+            error!("{}", msg);
+            return Err(msg);
+        }
+        let body = response.bytes().await.map_err(|e| {
+            let msg = format!("Failed to read response from {}: {}", url, e);
+            error!("{}", msg);
+            msg
+        })?;
+        Ok(Some(body.to_vec()))
+    }
+}
+
+/// Keeps the verified prefix of a partial download and returns the first chunk still needed.
+/// The file is truncated at the first chunk that is short or does not match its hash.
+fn resume_partial_file(
+    part: &mut std::fs::File,
+    chunks: &SnapshotChunkList,
+) -> Result<usize, String> {
+    let map_io_err = |e: std::io::Error| {
+        let msg = format!("Failed to resume partial snapshot file: {}", e);
+        error!("{}", msg);
+        msg
+    };
+
+    let existing = part.metadata().map_err(map_io_err)?.len();
+    part.seek(SeekFrom::Start(0)).map_err(map_io_err)?;
+    let mut verified = 0;
+    let mut buffer = Vec::new();
+    for (index, expected) in chunks.sha256.iter().enumerate() {
+        let (start, end) = chunks.chunk_range(index);
+        if end > existing {
+            break;
+        }
+        buffer.resize((end - start) as usize, 0);
+        part.read_exact(&mut buffer).map_err(map_io_err)?;
+        if &format!("{:x}", Sha256::digest(&buffer)) != expected {
+            break;
+        }
+        verified = index + 1;
+    }
+
+    let verified_len = if verified == 0 {
+        0
+    } else {
+        chunks.chunk_range(verified - 1).1
+    };
+    part.set_len(verified_len).map_err(map_io_err)?;
+    part.seek(SeekFrom::Start(verified_len))
+        .map_err(map_io_err)?;
+    Ok(verified)
+}
+
+fn write_file(path: &Path, data: &[u8]) -> Result<(), String> {
+    std::fs::write(path, data).map_err(|e| {
+        let msg = format!("Failed to write {}: {}", path.display(), e);
+        error!("{}", msg);
+        msg
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::UNIX_EPOCH;
+
+    fn temp_dir(tag: &str) -> PathBuf {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_nanos();
+        let dir =
+            std::env::temp_dir().join(format!("balance_history_snapshot_http_{}_{}", tag, nanos));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[tokio::test]
+    async fn test_snapshot_server_serves_ranges() {
+        let server_dir = temp_dir("range");
+        let data: Vec<u8> = (0..100u8).collect();
+        std::fs::write(server_dir.join("snapshot_9.db"), &data).unwrap();
+
+        let handler = Arc::new(SnapshotHttpHandler::new(server_dir.clone()));
+        let server = SnapshotHttpServer::start("127.0.0.1:0".parse().unwrap(), handler).unwrap();
+        let url = format!("{}/snapshots/snapshot_9.db", server.get_listen_url());
+        let client = Client::new();
+
+        let response = client
+            .get(&url)
+            .header(reqwest::header::RANGE, "bytes=90-")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::PARTIAL_CONTENT);
+        assert_eq!(
+            response.headers()[reqwest::header::CONTENT_RANGE],
+            "bytes 90-99/100"
+        );
+        assert_eq!(response.bytes().await.unwrap().as_ref(), &data[90..]);
+
+        let response = client
+            .get(&url)
+            .header(reqwest::header::RANGE, "bytes=100-")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::RANGE_NOT_SATISFIABLE);
+
+        let response = client.head(&url).send().await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()[reqwest::header::CONTENT_LENGTH], "100");
+
+        let response = client
+            .get(format!(
+                "{}/snapshots/balance_history.db.part",
+                server.get_listen_url()
+            ))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+        server.close().await;
+    }
+
+    #[test]
+    fn test_snapshot_file_name_filter() {
+        assert!(is_servable_snapshot_file_name("snapshot_100.db"));
+        assert!(is_servable_snapshot_file_name("snapshot_100.manifest.json"));
+        assert!(is_servable_snapshot_file_name("snapshot_100.sig"));
+        assert!(!is_servable_snapshot_file_name("../balance_history.db"));
+        assert!(!is_servable_snapshot_file_name(".snapshot_100.db"));
+        assert!(!is_servable_snapshot_file_name("snapshot_100.db.part"));
+        assert!(!is_servable_snapshot_file_name("snapshot%2F100.db"));
+    }
+
+    #[tokio::test]
+    async fn test_fetch_snapshot_resumes_and_verifies_chunks() {
+        let server_dir = temp_dir("server");
+        let client_dir = temp_dir("client");
+        let data: Vec<u8> = (0..(SNAPSHOT_CHUNK_SIZE * 2 + 1234))
+            .map(|i| (i % 251) as u8)
+            .collect();
+        std::fs::write(server_dir.join("snapshot_7.db"), &data).unwrap();
+
+        let handler = Arc::new(SnapshotHttpHandler::new(server_dir.clone()));
+        let server =
+            SnapshotHttpServer::start("127.0.0.1:0".parse().unwrap(), handler.clone()).unwrap();
+        let fetcher = SnapshotFetcher::new(&server.get_listen_url(), client_dir.clone()).unwrap();
+
+        let entries = fetcher.list_snapshots().await.unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].file_size, data.len() as u64);
+        assert_eq!(entries[0].manifest_file, None);
+
+        // Without a manifest the fetch is refused unless explicitly allowed.
+        let err = fetcher.fetch("snapshot_7.db").await.unwrap_err();
+        assert!(err.contains("no manifest"));
+        assert!(!client_dir.join("snapshot_7.db").exists());
+        let fetcher = fetcher.with_allow_missing_manifest(true);
+
+        // A partial download whose second chunk is corrupt keeps only the first chunk.
+        let mut partial = data[..(SNAPSHOT_CHUNK_SIZE as usize * 2 + 10)].to_vec();
+        partial[SNAPSHOT_CHUNK_SIZE as usize] ^= 0xff;
+        let partial_path = client_dir.join("snapshot_7.db.part");
+        std::fs::write(&partial_path, &partial).unwrap();
+        let chunks = handler.get_chunk_list("snapshot_7.db").unwrap().unwrap();
+        assert_eq!(chunks.sha256.len(), 3);
+        let mut part = std::fs::OpenOptions::new()
+            .read(true)
+            .write(true)
+            .open(&partial_path)
+            .unwrap();
+        assert_eq!(resume_partial_file(&mut part, &chunks).unwrap(), 1);
+        assert_eq!(part.metadata().unwrap().len(), SNAPSHOT_CHUNK_SIZE);
+        drop(part);
+
+        let fetched = fetcher.fetch("snapshot_7.db").await.unwrap();
+        assert_eq!(fetched.file, client_dir.join("snapshot_7.db"));
+        assert_eq!(fetched.manifest_file, None);
+        assert_eq!(std::fs::read(&fetched.file).unwrap(), data);
+        assert!(!partial_path.exists());
+        assert!(fetcher.fetch("snapshot_8.db").await.is_err());
+
+        server.close().await;
+    }
+}
@@ -1,17 +1,19 @@
 use crate::config::BalanceHistoryConfig;
-use crate::db::BalanceHistoryDB;
+use crate::db::{BalanceHistoryDB, BlockCommitEntry};
 use crate::service::{
     BALANCE_HISTORY_API_VERSION, BALANCE_HISTORY_SEMANTICS_VERSION, BALANCE_HISTORY_STABLE_LAG,
-    HistoricalSnapshotStateRef,
+    BlockCommitInfo, HistoricalSnapshotStateRef,
 };
+use bitcoincore_rpc::bitcoin::Network;
 use usdb_util::{
+    BALANCE_HISTORY_COMMIT_PROTOCOL_VERSION_V1, BalanceDeltaRootRule,
     CONSENSUS_SNAPSHOT_ID_HASH_ALGO, CONSENSUS_SNAPSHOT_ID_VERSION, CONSENSUS_SOURCE_CHAIN_BTC,
     ConsensusSnapshotIdentity, USDB_INDEX_FORMULA_VERSION, USDB_INDEX_PROTOCOL_VERSION,
-    build_consensus_snapshot_id,
+    build_consensus_snapshot_id, is_balance_state_root_active,
 };
 
 /// Public version string of the first balance-history block commit protocol.
-pub const COMMIT_PROTOCOL_VERSION: &str = "1.0.0";
+pub const COMMIT_PROTOCOL_VERSION: &str = BALANCE_HISTORY_COMMIT_PROTOCOL_VERSION_V1;
 /// Hash algorithm used by both balance delta roots and rolling block commits.
 pub const COMMIT_HASH_ALGO: &str = "sha256";
 
@@ -25,11 +27,35 @@ pub fn encode_commit_hex(bytes: &[u8]) -> String {
     output
 }
 
+/// Commit protocol version of the block committed at `block_height`.
+///
+/// The version follows the balance delta root rule, which is activated per network.
+pub fn commit_protocol_version_at_height(network: Network, block_height: u32) -> &'static str {
+    BalanceDeltaRootRule::at_height(network, block_height).commit_protocol_version()
+}
+
+/// Converts one persisted block commit into its RPC representation.
+pub fn build_block_commit_info(network: Network, entry: &BlockCommitEntry) -> BlockCommitInfo {
+    BlockCommitInfo {
+        block_height: entry.block_height,
+        btc_block_hash: format!("{:x}", entry.btc_block_hash),
+        balance_delta_root: encode_commit_hex(&entry.balance_delta_root),
+        block_commit: encode_commit_hex(&entry.block_commit),
+        commit_protocol_version: commit_protocol_version_at_height(network, entry.block_height)
+            .to_string(),
+        commit_hash_algo: COMMIT_HASH_ALGO.to_string(),
+    }
+}
+
 /// Builds the canonical consensus snapshot identity for one exact committed BTC height.
+///
+/// `balance_state_root` must be the root stored at `stable_height`, or `None` before
+/// the state root is activated for the network.
 pub fn build_consensus_snapshot_identity(
     config: &BalanceHistoryConfig,
     stable_height: u32,
     stable_block_hash: &str,
+    balance_state_root: Option<String>,
 ) -> ConsensusSnapshotIdentity {
     ConsensusSnapshotIdentity {
         source_chain: CONSENSUS_SOURCE_CHAIN_BTC.to_string(),
@@ -41,6 +67,7 @@ pub fn build_consensus_snapshot_identity(
         balance_history_semantics_version: BALANCE_HISTORY_SEMANTICS_VERSION.to_string(),
         usdb_index_formula_version: USDB_INDEX_FORMULA_VERSION.to_string(),
         usdb_index_protocol_version: USDB_INDEX_PROTOCOL_VERSION.to_string(),
+        balance_state_root,
     }
 }
 
@@ -55,9 +82,24 @@ pub fn build_historical_state_ref_at_height(
         return Ok(None);
     };
 
+    // Heights below an installed snapshot have commits but no state root; their identity
+    // cannot be rebuilt locally.
+    let balance_state_root = if is_balance_state_root_active(config.btc.network(), block_height) {
+        match db.get_balance_state_root(block_height)? {
+            Some(root) => Some(encode_commit_hex(&root)),
+            None => return Ok(None),
+        }
+    } else {
+        None
+    };
+
     let stable_block_hash = format!("{:x}", commit.btc_block_hash);
-    let consensus_identity =
-        build_consensus_snapshot_identity(config, block_height, &stable_block_hash);
+    let consensus_identity = build_consensus_snapshot_identity(
+        config,
+        block_height,
+        &stable_block_hash,
+        balance_state_root,
+    );
     let snapshot_id = build_consensus_snapshot_id(&consensus_identity);
 
     Ok(Some(HistoricalSnapshotStateRef {
@@ -68,7 +110,11 @@ pub fn build_historical_state_ref_at_height(
         snapshot_id,
         snapshot_id_hash_algo: CONSENSUS_SNAPSHOT_ID_HASH_ALGO.to_string(),
         snapshot_id_version: CONSENSUS_SNAPSHOT_ID_VERSION.to_string(),
-        commit_protocol_version: COMMIT_PROTOCOL_VERSION.to_string(),
+        commit_protocol_version: commit_protocol_version_at_height(
+            config.btc.network(),
+            block_height,
+        )
+        .to_string(),
         commit_hash_algo: COMMIT_HASH_ALGO.to_string(),
     }))
 }
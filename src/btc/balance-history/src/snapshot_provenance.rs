@@ -44,12 +44,21 @@ pub struct SnapshotInstallProvenance {
     pub signature_scheme: Option<String>,
     /// Signer identifier recorded in the manifest, if any.
     pub signing_key_id: Option<String>,
+    /// Trusted signers whose signatures counted towards the threshold, in sidecar order.
+    #[serde(default)]
+    pub accepted_signing_key_ids: Vec<String>,
+    /// Number of distinct trusted signers the install required, for signed installs.
+    #[serde(default)]
+    pub signature_threshold: Option<u32>,
     /// Snapshot DB file hash from the manifest, if any.
     pub snapshot_file_sha256: Option<String>,
     /// Expected installed snapshot id from the manifest, if any.
     pub snapshot_id: Option<String>,
     /// Installed BTC block height of the snapshot DB.
     pub installed_block_height: u32,
+    /// Base height a delta snapshot was applied on top of, None for full snapshot installs.
+    #[serde(default)]
+    pub base_block_height: Option<u32>,
 }
 
 impl SnapshotInstallProvenance {
@@ -77,10 +77,16 @@ pub fn generate_snapshot_key_files(
         public_key_base64: base64::engine::general_purpose::STANDARD.encode(public_key.to_bytes()),
     };
     let trusted_keys_file = SnapshotTrustedKeySet {
+        threshold: 1,
         keys: vec![SnapshotTrustedPublicKey {
             key_id: key_id.to_string(),
             public_key_base64: public_key_file.public_key_base64.clone(),
+            valid_from_height: None,
+            valid_until_height: None,
+            valid_from_time: None,
+            valid_until_time: None,
         }],
+        revoked_keys: Vec::new(),
     };
 
     write_json_file(&signing_key_path, &signing_key_file)?;
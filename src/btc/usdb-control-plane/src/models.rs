@@ -57,6 +57,15 @@ pub struct BtcMintPrepareActivePassSummary {
     pub prev: Vec<String>,
 }
 
+/// Non-consensus mempool-adjusted owner balance read from balance-history.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BtcMintPreparePendingBalanceSummary {
+    pub stable_height: u32,
+    pub confirmed_balance: u64,
+    pub pending_balance: u64,
+    pub pending_tx_count: u32,
+}
+
 #[derive(Debug, Clone, Serialize)]
 pub struct BtcMintPrepareResponse {
     pub eligible: bool,
@@ -71,6 +80,7 @@ pub struct BtcMintPrepareResponse {
     pub prev: Vec<String>,
     pub suggested_prev: Vec<String>,
     pub active_pass: Option<BtcMintPrepareActivePassSummary>,
+    pub pending_balance: Option<BtcMintPreparePendingBalanceSummary>,
     pub inscription_payload: Value,
     pub inscription_payload_json: String,
     pub prepare_request: Value,
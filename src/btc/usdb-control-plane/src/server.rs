@@ -2,12 +2,12 @@ use crate::config::ControlPlaneConfig;
 use crate::models::{
     ApiError, AppEntry, ArtifactSummary, BalanceHistoryServiceSummary, BootstrapStepSummary,
     BootstrapSummary, BtcMintExecuteRequest, BtcMintExecuteResponse,
-    BtcMintPrepareActivePassSummary, BtcMintPrepareRequest, BtcMintPrepareResponse,
-    BtcMintPrepareRuntimeSummary, BtcNodeServiceSummary, BtcWorldSimDevSignerResponse,
-    BtcWorldSimIdentitiesResponse, BtcWorldSimIdentity, CapabilitiesSummary,
-    EthwAddressStatusResponse, EthwDevIdentityResponse, EthwServiceSummary, ExplorerLinks,
-    OrdServiceSummary, OverviewResponse, ServiceProbe, ServiceRpcRequest, ServicesSummary,
-    UsdbIndexerServiceSummary,
+    BtcMintPrepareActivePassSummary, BtcMintPreparePendingBalanceSummary, BtcMintPrepareRequest,
+    BtcMintPrepareResponse, BtcMintPrepareRuntimeSummary, BtcNodeServiceSummary,
+    BtcWorldSimDevSignerResponse, BtcWorldSimIdentitiesResponse, BtcWorldSimIdentity,
+    CapabilitiesSummary, EthwAddressStatusResponse, EthwDevIdentityResponse, EthwServiceSummary,
+    ExplorerLinks, OrdServiceSummary, OverviewResponse, ServiceProbe, ServiceRpcRequest,
+    ServicesSummary, UsdbIndexerServiceSummary,
 };
 use crate::rpc_client::{RpcClient, decode_hex_quantity};
 use axum::Json;
@@ -85,6 +85,10 @@ struct OrdMintJsonInscription {
     id: String,
 }
 
+// Mirrors usdb-indexer's ENERGY_BALANCE_THRESHOLD (0.001 BTC), below which a pass
+// stops growing energy.
+const MINT_OWNER_BALANCE_THRESHOLD_SATS: u64 = 100_000;
+
 const BALANCE_HISTORY_PROXY_METHODS: &[&str] = &[
     "get_network_type",
     "get_block_height",
@@ -97,6 +101,12 @@ const BALANCE_HISTORY_PROXY_METHODS: &[&str] = &[
     "get_address_balance_summary",
     "get_address_balance_timeseries",
     "get_address_flow_buckets",
+    "get_address_utxos",
+    "get_address_transactions",
+    "get_address_pending_balance",
+    "get_descriptor_balance_summary",
+    "get_descriptor_balance_timeseries",
+    "get_descriptor_flow_buckets",
     "resolve_script_hashes",
 ];
 
@@ -779,7 +789,35 @@ async fn prepare_btc_mint_context(
         );
     }
 
+    // The pending view is optional on balance-history, so it only feeds warnings.
+    let pending_balance = if balance_history_ready {
+        fetch_owner_pending_balance_summary(state, &owner_script_hash)
+            .await
+            .unwrap_or_else(|error| {
+                warn!(
+                    "Pending balance is unavailable for owner {}: {}",
+                    owner_address, error
+                );
+                None
+            })
+    } else {
+        None
+    };
+
     let mut warnings = Vec::new();
+    if let Some(pending) = pending_balance.as_ref()
+        && pending.pending_balance < MINT_OWNER_BALANCE_THRESHOLD_SATS
+    {
+        warnings.push(format!(
+            "Owner {} has {} sat after {} unconfirmed transaction(s), below the {} sat energy balance threshold (confirmed {} sat at height {}).",
+            owner_address,
+            pending.pending_balance,
+            pending.pending_tx_count,
+            MINT_OWNER_BALANCE_THRESHOLD_SATS,
+            pending.confirmed_balance,
+            pending.stable_height
+        ));
+    }
     let suggested_prev = active_pass
         .as_ref()
         .map(|item| vec![item.inscription_id.clone()])
@@ -870,6 +908,7 @@ async fn prepare_btc_mint_context(
             prev,
             suggested_prev,
             active_pass,
+            pending_balance,
             inscription_payload,
             inscription_payload_json,
             prepare_request,
@@ -1173,6 +1212,9 @@ async fn normalize_balance_history_request(
             | "get_address_balance_summary"
             | "get_address_balance_timeseries"
             | "get_address_flow_buckets"
+            | "get_address_utxos"
+            | "get_address_transactions"
+            | "get_address_pending_balance"
     ) {
         return Ok(request);
     }
@@ -1412,7 +1454,10 @@ fn normalize_balance_history_params(
         | "get_address_balance_delta"
         | "get_address_balance_summary"
         | "get_address_balance_timeseries"
-        | "get_address_flow_buckets" => {
+        | "get_address_flow_buckets"
+        | "get_address_utxos"
+        | "get_address_transactions"
+        | "get_address_pending_balance" => {
             let candidate = first
                 .get("script_hash")
                 .and_then(Value::as_str)
@@ -1808,6 +1853,29 @@ async fn fetch_owner_active_pass_summary(
     })
 }
 
+async fn fetch_owner_pending_balance_summary(
+    state: &AppState,
+    owner_script_hash: &str,
+) -> Result<Option<BtcMintPreparePendingBalanceSummary>, String> {
+    let response = state
+        .rpc_client
+        .balance_history_proxy(
+            &state.config.rpc.balance_history_url,
+            "get_address_pending_balance",
+            json!([{
+                "script_hash": owner_script_hash,
+            }]),
+        )
+        .await?;
+
+    serde_json::from_value(response).map_err(|error| {
+        format!(
+            "Failed to decode pending balance for owner {}: {}",
+            owner_script_hash, error
+        )
+    })
+}
+
 async fn build_services_summary(state: &AppState) -> ServicesSummary {
     let btc_node = probe_btc_node(state).await;
     let balance_history = probe_balance_history(state).await;
@@ -2375,6 +2443,9 @@ mod tests {
             "get_address_balance_summary",
             "get_address_balance_timeseries",
             "get_address_flow_buckets",
+            "get_address_utxos",
+            "get_address_transactions",
+            "get_address_pending_balance",
         ] {
             assert!(BALANCE_HISTORY_PROXY_METHODS.contains(&method));
             let normalized = normalize_balance_history_params(
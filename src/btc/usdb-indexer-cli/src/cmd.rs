@@ -29,6 +29,12 @@ pub enum Commands {
         block_height: Option<u32>,
     },
 
+    /// Get the persisted pass mutation stream and commit metadata at target height.
+    PassBlockMutations {
+        #[arg(long)]
+        block_height: Option<u32>,
+    },
+
     /// Get indexer sync status.
     SyncStatus {
         /// Keep polling sync status.
@@ -142,6 +148,79 @@ pub enum Commands {
         #[arg(long)]
         scope: Option<String>,
 
+        /// Ranking key: energy | effective_energy | level.
+        #[arg(long)]
+        order_by: Option<String>,
+
+        /// Fill level and difficulty factor fields (implied by --order-by level).
+        #[arg(long, default_value_t = false)]
+        include_level: bool,
+
+        #[arg(long, default_value_t = 0)]
+        page: usize,
+
+        #[arg(long, default_value_t = 100)]
+        page_size: usize,
+    },
+
+    /// Get UIP-0004 effective energy view of one pass.
+    PassEffectiveEnergy {
+        #[arg(long)]
+        inscription_id: String,
+
+        #[arg(long)]
+        block_height: Option<u32>,
+    },
+
+    /// Get UIP-0005 level view of one pass.
+    PassLevel {
+        #[arg(long)]
+        inscription_id: String,
+
+        #[arg(long)]
+        block_height: Option<u32>,
+    },
+
+    /// Get collab passes resolved to one Leader at target height.
+    LeaderCollabSet {
+        #[arg(long)]
+        leader_pass_id: String,
+
+        #[arg(long)]
+        at_height: Option<u32>,
+    },
+
+    /// Get deterministic validator candidate set and its commitment.
+    ValidatorCandidateSet {
+        #[arg(long)]
+        block_height: Option<u32>,
+
+        #[arg(long)]
+        max_candidates: Option<u32>,
+
+        #[arg(long)]
+        min_effective_energy: Option<u64>,
+    },
+
+    /// Verify candidate-set fields of a block body payload against the indexer.
+    VerifyValidatorCandidateSet {
+        /// JSON object string with block_height, system_state_id, selection_rule,
+        /// max_candidates, min_effective_energy, candidates and candidate_set_commitment.
+        #[arg(long)]
+        payload: String,
+    },
+
+    /// Get UIP-0006 economic state view at target height.
+    EconomicStateView {
+        #[arg(long)]
+        block_height: Option<u32>,
+    },
+
+    /// Get UIP-0006 economic audit view with per-pass profiles.
+    EconomicStateAuditView {
+        #[arg(long)]
+        block_height: Option<u32>,
+
         #[arg(long, default_value_t = 0)]
         page: usize,
 
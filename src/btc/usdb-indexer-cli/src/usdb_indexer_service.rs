@@ -49,6 +49,18 @@ impl UsdbIndexerService {
                     .await?;
                 print_pretty_json(&result)?;
             }
+            Commands::PassBlockMutations { block_height } => {
+                let result = self
+                    .client
+                    .call(
+                        "get_pass_block_mutations",
+                        json!([{
+                            "block_height": block_height,
+                        }]),
+                    )
+                    .await?;
+                print_pretty_json(&result)?;
+            }
             Commands::SyncStatus { watch, interval_ms } => {
                 if watch {
                     self.watch_sync_status(interval_ms).await?;
@@ -190,6 +202,8 @@ impl UsdbIndexerService {
             Commands::PassEnergyLeaderboard {
                 at_height,
                 scope,
+                order_by,
+                include_level,
                 page,
                 page_size,
             } => {
@@ -200,6 +214,124 @@ impl UsdbIndexerService {
                         json!([{
                             "at_height": at_height,
                             "scope": scope,
+                            "order_by": order_by,
+                            "include_level": include_level,
+                            "page": page,
+                            "page_size": page_size,
+                        }]),
+                    )
+                    .await?;
+                print_pretty_json(&result)?;
+            }
+            Commands::PassEffectiveEnergy {
+                inscription_id,
+                block_height,
+            } => {
+                let result = self
+                    .client
+                    .call(
+                        "get_pass_effective_energy",
+                        json!([{
+                            "inscription_id": inscription_id,
+                            "block_height": block_height,
+                        }]),
+                    )
+                    .await?;
+                print_pretty_json(&result)?;
+            }
+            Commands::PassLevel {
+                inscription_id,
+                block_height,
+            } => {
+                let result = self
+                    .client
+                    .call(
+                        "get_pass_level",
+                        json!([{
+                            "inscription_id": inscription_id,
+                            "block_height": block_height,
+                        }]),
+                    )
+                    .await?;
+                print_pretty_json(&result)?;
+            }
+            Commands::LeaderCollabSet {
+                leader_pass_id,
+                at_height,
+            } => {
+                let result = self
+                    .client
+                    .call(
+                        "get_leader_collab_set_at_height",
+                        json!([{
+                            "leader_pass_id": leader_pass_id,
+                            "at_height": at_height,
+                        }]),
+                    )
+                    .await?;
+                print_pretty_json(&result)?;
+            }
+            Commands::ValidatorCandidateSet {
+                block_height,
+                max_candidates,
+                min_effective_energy,
+            } => {
+                let result = self
+                    .client
+                    .call(
+                        "get_validator_candidate_set",
+                        json!([{
+                            "block_height": block_height,
+                            "max_candidates": max_candidates,
+                            "min_effective_energy": min_effective_energy,
+                        }]),
+                    )
+                    .await?;
+                print_pretty_json(&result)?;
+            }
+            Commands::VerifyValidatorCandidateSet { payload } => {
+                let parsed_payload: Value = serde_json::from_str(&payload).map_err(|e| {
+                    format!(
+                        "Invalid JSON in --payload: error={}, payload={}",
+                        e, payload
+                    )
+                })?;
+                if !parsed_payload.is_object() {
+                    return Err(format!(
+                        "Invalid --payload: expected JSON object, payload={}",
+                        payload
+                    ));
+                }
+
+                let result = self
+                    .client
+                    .call("verify_validator_candidate_set", json!([parsed_payload]))
+                    .await?;
+                print_pretty_json(&result)?;
+            }
+            Commands::EconomicStateView { block_height } => {
+                let result = self
+                    .client
+                    .call(
+                        "get_economic_state_view",
+                        json!([{
+                            "block_height": block_height,
+                        }]),
+                    )
+                    .await?;
+                print_pretty_json(&result)?;
+            }
+            Commands::EconomicStateAuditView {
+                block_height,
+                page,
+                page_size,
+            } => {
+                let result = self
+                    .client
+                    .call(
+                        "get_economic_state_audit_view",
+                        json!([{
+                            "block_height": block_height,
                             "page": page,
                             "page_size": page_size,
                         }]),
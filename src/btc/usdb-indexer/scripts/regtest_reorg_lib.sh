@@ -1164,6 +1164,7 @@ max_sync_block_height = 4294967295
 
 [rpc_server]
 port = ${BH_RPC_PORT}
+ws_port = 0
 EOF
 }
 
@@ -1201,6 +1202,7 @@ regtest_create_usdb_indexer_config() {
     "inscription_source_shadow_fail_fast": false,
     "rpc_server_port": ${USDB_RPC_PORT},
     "rpc_server_enabled": true,
+    "ws_server_enabled": false,
     "monitor_ord_enabled": false
   }
 }
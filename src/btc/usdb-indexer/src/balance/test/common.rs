@@ -63,6 +63,8 @@ pub(super) fn make_pass(
         prev: Vec::new(),
         invalid_code: None,
         invalid_reason: None,
+        leader_pass_id: None,
+        leader_btc_addr: None,
         owner,
         state: MinerPassState::Active,
     }
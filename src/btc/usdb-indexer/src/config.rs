@@ -1,7 +1,12 @@
+use balance_history::SnapshotConfig;
+use bitcoincore_rpc::bitcoin::Network;
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
-use usdb_util::{BTCConfig, BalanceHistoryConfig, OrdConfig, USDB_INDEXER_SERVICE_HTTP_PORT};
+use usdb_util::{
+    ActivationRegistry, ActiveVersionSet, BTCConfig, BalanceHistoryConfig, OrdConfig,
+    USDB_INDEXER_SERVICE_HTTP_PORT, USDB_INDEXER_SERVICE_WS_PORT, btc_activation_network_id,
+};
 
 fn default_genesis_block_height() -> u32 {
     900000
@@ -35,6 +40,10 @@ fn default_inscription_fixture_file() -> Option<String> {
     None
 }
 
+fn default_activation_registry_file() -> Option<String> {
+    None
+}
+
 fn default_inscription_source_shadow_compare() -> bool {
     false
 }
@@ -55,6 +64,14 @@ fn default_rpc_server_enabled() -> bool {
     true
 }
 
+fn default_ws_server_port() -> u16 {
+    USDB_INDEXER_SERVICE_WS_PORT
+}
+
+fn default_ws_server_enabled() -> bool {
+    false
+}
+
 fn default_pass_energy_leaderboard_cache_enabled() -> bool {
     true
 }
@@ -118,6 +135,14 @@ pub struct USDBConfig {
     #[serde(default = "default_rpc_server_enabled")]
     pub rpc_server_enabled: bool,
 
+    // WebSocket subscription server listen port; shares rpc_server_host.
+    #[serde(default = "default_ws_server_port")]
+    pub ws_server_port: u16,
+
+    // Enable or disable WebSocket subscription server startup, disabled by default.
+    #[serde(default = "default_ws_server_enabled")]
+    pub ws_server_enabled: bool,
+
     // Enable in-memory cache for latest-height pass energy leaderboard queries.
     #[serde(default = "default_pass_energy_leaderboard_cache_enabled")]
     pub pass_energy_leaderboard_cache_enabled: bool,
@@ -125,6 +150,12 @@ pub struct USDBConfig {
     // Maximum number of top-ranked leaderboard rows cached in memory.
     #[serde(default = "default_pass_energy_leaderboard_cache_top_k")]
     pub pass_energy_leaderboard_cache_top_k: usize,
+
+    // Optional UIP-0008 activation registry JSON file overriding the built-in registry.
+    // Intended for regtest/local rehearsals; relative paths are resolved from the root directory.
+    // On public networks the file must match the built-in registry exactly.
+    #[serde(default = "default_activation_registry_file")]
+    pub activation_registry_file: Option<String>,
 }
 
 impl Default for USDBConfig {
@@ -143,8 +174,11 @@ impl Default for USDBConfig {
             rpc_server_host: default_rpc_server_host(),
             rpc_server_port: default_rpc_server_port(),
             rpc_server_enabled: default_rpc_server_enabled(),
+            ws_server_port: default_ws_server_port(),
+            ws_server_enabled: default_ws_server_enabled(),
             pass_energy_leaderboard_cache_enabled: default_pass_energy_leaderboard_cache_enabled(),
             pass_energy_leaderboard_cache_top_k: default_pass_energy_leaderboard_cache_top_k(),
+            activation_registry_file: default_activation_registry_file(),
         }
     }
 }
@@ -165,6 +199,10 @@ pub struct IndexerConfig {
 
     // USDB indexer behavior and performance tuning settings.
     pub usdb: USDBConfig,
+
+    // State snapshot signing and install trust settings, shared in shape with balance-history.
+    #[serde(default)]
+    pub snapshot: SnapshotConfig,
 }
 
 impl Default for IndexerConfig {
@@ -175,6 +213,7 @@ impl Default for IndexerConfig {
             ordinals: OrdConfig::default(),
             balance_history: BalanceHistoryConfig::default(),
             usdb: USDBConfig::default(),
+            snapshot: SnapshotConfig::default(),
         }
     }
 }
@@ -182,6 +221,7 @@ impl Default for IndexerConfig {
 pub struct ConfigManager {
     root_dir: PathBuf,
     config: IndexerConfig,
+    activation_registry: ActivationRegistry,
 }
 
 impl ConfigManager {
@@ -218,9 +258,11 @@ impl ConfigManager {
                 serde_json::to_string_pretty(&default_config).unwrap()
             );
 
+            let activation_registry = Self::load_activation_registry(&root_dir, &default_config)?;
             return Ok(Self {
                 root_dir,
                 config: default_config,
+                activation_registry,
             });
         }
 
@@ -244,7 +286,71 @@ impl ConfigManager {
             msg
         })?;
 
-        Ok(Self { root_dir, config })
+        let activation_registry = Self::load_activation_registry(&root_dir, &config)?;
+        Ok(Self {
+            root_dir,
+            config,
+            activation_registry,
+        })
+    }
+
+    fn load_activation_registry(
+        root_dir: &Path,
+        config: &IndexerConfig,
+    ) -> Result<ActivationRegistry, String> {
+        let registry = match &config.usdb.activation_registry_file {
+            Some(file) => {
+                let path = PathBuf::from(file);
+                let path = if path.is_absolute() {
+                    path
+                } else {
+                    root_dir.join(path)
+                };
+                let registry = ActivationRegistry::load_from_file(&path)?;
+                Self::ensure_registry_override_allowed(config, &path, &registry)?;
+                registry
+            }
+            None => ActivationRegistry::builtin()?,
+        };
+
+        info!(
+            "Activation registry loaded: module=config, source={}, activation_registry_id={}",
+            config
+                .usdb
+                .activation_registry_file
+                .as_deref()
+                .unwrap_or("builtin"),
+            registry.activation_registry_id()
+        );
+        Ok(registry)
+    }
+
+    // Public networks share one activation matrix, so an override there may only restate
+    // the built-in registry; a diverging file would silently fork the node.
+    fn ensure_registry_override_allowed(
+        config: &IndexerConfig,
+        path: &Path,
+        registry: &ActivationRegistry,
+    ) -> Result<(), String> {
+        let network = config.bitcoin.network();
+        if network == Network::Regtest {
+            return Ok(());
+        }
+
+        let builtin = ActivationRegistry::builtin()?;
+        if registry.activation_registry_id() != builtin.activation_registry_id() {
+            let msg = format!(
+                "Activation registry override {} is not allowed on public network {}: activation_registry_id {} differs from the built-in {}",
+                path.display(),
+                btc_activation_network_id(network),
+                registry.activation_registry_id(),
+                builtin.activation_registry_id()
+            );
+            error!("{}", msg);
+            return Err(msg);
+        }
+
+        Ok(())
     }
 
     pub fn root_dir(&self) -> &PathBuf {
@@ -264,9 +370,119 @@ impl ConfigManager {
         dir
     }
 
+    // Relative paths in config are resolved from the root directory.
+    pub fn resolve_root_path(&self, path: &Path) -> PathBuf {
+        if path.is_absolute() {
+            path.to_path_buf()
+        } else {
+            self.root_dir.join(path)
+        }
+    }
+
+    pub fn snapshot_dir(&self) -> PathBuf {
+        self.root_dir.join("snapshots")
+    }
+
+    pub fn snapshot_signing_key_path(&self) -> Option<PathBuf> {
+        self.config
+            .snapshot
+            .signing_key_file
+            .as_deref()
+            .map(|path| self.resolve_root_path(path))
+    }
+
+    pub fn snapshot_trusted_keys_path(&self) -> Option<PathBuf> {
+        self.config
+            .snapshot
+            .trusted_keys_file
+            .as_deref()
+            .map(|path| self.resolve_root_path(path))
+    }
+
     pub fn config(&self) -> &IndexerConfig {
         &self.config
     }
+
+    pub fn activation_registry(&self) -> &ActivationRegistry {
+        &self.activation_registry
+    }
+
+    // Resolve the UIP-0008 active version set of the configured BTC network at btc_height.
+    // Historical callers must pass the target height, never the current synced head.
+    pub fn active_version_set_at(&self, btc_height: u32) -> Result<ActiveVersionSet, String> {
+        self.activation_registry
+            .lookup_btc_active_version_set(&self.activation_network_id(), btc_height)
+    }
+
+    // UIP-0008 network_id of the configured BTC network, for example `btc-regtest`.
+    pub fn activation_network_id(&self) -> String {
+        btc_activation_network_id(self.config.bitcoin.network())
+    }
 }
 
 pub type ConfigManagerRef = Arc<ConfigManager>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    fn write_config_with_registry(
+        tag: &str,
+        network: Network,
+        records: &[usdb_util::ActivationRecord],
+    ) -> PathBuf {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_nanos();
+        let root_dir =
+            std::env::temp_dir().join(format!("usdb_indexer_config_test_{}_{}", tag, nanos));
+        std::fs::create_dir_all(&root_dir).unwrap();
+
+        std::fs::write(
+            root_dir.join("activation-registry.json"),
+            serde_json::to_string_pretty(&serde_json::json!({ "records": records })).unwrap(),
+        )
+        .unwrap();
+
+        let mut config = IndexerConfig::default();
+        config.bitcoin.network = network;
+        config.usdb.activation_registry_file = Some("activation-registry.json".to_string());
+        std::fs::write(
+            root_dir.join("config.json"),
+            serde_json::to_string_pretty(&config).unwrap(),
+        )
+        .unwrap();
+        root_dir
+    }
+
+    #[test]
+    fn test_registry_override_rejected_on_public_network_unless_builtin() {
+        let builtin = ActivationRegistry::builtin().unwrap();
+        let mut changed = builtin.records().to_vec();
+        changed.retain(|r| r.network_id != "btc-testnet4");
+
+        let root_dir = write_config_with_registry("public_changed", Network::Bitcoin, &changed);
+        let err = ConfigManager::load(Some(root_dir.clone())).err().unwrap();
+        assert!(err.contains("is not allowed on public network btc-mainnet"));
+        std::fs::remove_dir_all(root_dir).unwrap();
+
+        let root_dir =
+            write_config_with_registry("public_builtin", Network::Bitcoin, builtin.records());
+        let config = ConfigManager::load(Some(root_dir.clone())).unwrap();
+        assert_eq!(
+            config.activation_registry().activation_registry_id(),
+            builtin.activation_registry_id()
+        );
+        std::fs::remove_dir_all(root_dir).unwrap();
+
+        let root_dir = write_config_with_registry("regtest_changed", Network::Regtest, &changed);
+        let config = ConfigManager::load(Some(root_dir.clone())).unwrap();
+        assert_ne!(
+            config.activation_registry().activation_registry_id(),
+            builtin.activation_registry_id()
+        );
+        std::fs::remove_dir_all(root_dir).unwrap();
+    }
+}
@@ -0,0 +1,335 @@
+use super::content::MinerPassState;
+use super::energy::PassEnergyManagerRef;
+use super::energy_formula::{COLLAB_WEIGHT_BPS, calc_collab_contribution};
+use crate::storage::{CollabMinerPassInfo, MinerPassStorageRef};
+use bitcoincore_rpc::bitcoin::Network;
+use ord::InscriptionId;
+use std::collections::{HashMap, HashSet};
+use usdb_util::{USDBScriptHash, address_string_to_script_hash};
+
+// UIP-0004 derived view. Everything here is computed on read from the pass history
+// and the raw energy ledger; nothing is ever written back into the raw ledger, so
+// Leader remints cannot inherit collab contributions through prev chains.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LeaderRefKind {
+    PassId,
+    BtcAddr,
+}
+
+impl LeaderRefKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LeaderRefKind::PassId => "leader_pass_id",
+            LeaderRefKind::BtcAddr => "leader_btc_addr",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CollabContribution {
+    pub collab_pass_id: InscriptionId,
+    pub collab_owner: USDBScriptHash,
+    pub collab_raw_energy: u64,
+    pub collab_weight_bps: u64,
+    pub collab_contribution: u64,
+    pub leader_ref_kind: LeaderRefKind,
+    pub leader_ref_value: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PassEffectiveEnergy {
+    pub inscription_id: InscriptionId,
+    pub block_height: u32,
+    pub state: MinerPassState,
+    pub is_collab: bool,
+    pub raw_energy: u64,
+    pub collab_contribution_total: u64,
+    pub effective_energy: u64,
+    pub collabs: Vec<CollabContribution>,
+}
+
+// Collab contributions of every Leader at one height, resolved from a single scan of
+// the active collab set. Used when many passes are ranked at the same height.
+#[derive(Debug, Clone, Default)]
+pub struct CollabContributionTotals {
+    active_collab_pass_ids: HashSet<InscriptionId>,
+    leader_totals: HashMap<InscriptionId, u64>,
+    leader_collab_counts: HashMap<InscriptionId, u32>,
+}
+
+impl CollabContributionTotals {
+    // Same rule as get_pass_effective_energy_at_height: only an active standard pass
+    // carries effective energy.
+    pub fn effective_energy(
+        &self,
+        inscription_id: &InscriptionId,
+        state: &MinerPassState,
+        raw_energy: u64,
+    ) -> u64 {
+        if *state != MinerPassState::Active || self.active_collab_pass_ids.contains(inscription_id)
+        {
+            return 0;
+        }
+
+        raw_energy.saturating_add(self.collab_contribution_total(inscription_id))
+    }
+
+    pub fn is_active_collab_pass(&self, inscription_id: &InscriptionId) -> bool {
+        self.active_collab_pass_ids.contains(inscription_id)
+    }
+
+    pub fn collab_contribution_total(&self, leader_inscription_id: &InscriptionId) -> u64 {
+        self.leader_totals
+            .get(leader_inscription_id)
+            .copied()
+            .unwrap_or(0)
+    }
+
+    pub fn collab_breakdown_count(&self, leader_inscription_id: &InscriptionId) -> u32 {
+        self.leader_collab_counts
+            .get(leader_inscription_id)
+            .copied()
+            .unwrap_or(0)
+    }
+}
+
+pub struct PassCollabResolver {
+    storage: MinerPassStorageRef,
+    energy_manager: PassEnergyManagerRef,
+    network: Network,
+}
+
+impl PassCollabResolver {
+    pub fn new(
+        storage: MinerPassStorageRef,
+        energy_manager: PassEnergyManagerRef,
+        network: Network,
+    ) -> Self {
+        Self {
+            storage,
+            energy_manager,
+            network,
+        }
+    }
+
+    fn pass_state_at_height(
+        &self,
+        inscription_id: &InscriptionId,
+        block_height: u32,
+    ) -> Result<Option<MinerPassState>, String> {
+        let event = self
+            .storage
+            .get_last_pass_history_at_or_before_height(inscription_id, block_height)?;
+        Ok(event.map(|e| e.state))
+    }
+
+    // A Leader must be a standard pass whose history state at block_height is Active.
+    pub fn is_active_standard_pass_at_height(
+        &self,
+        inscription_id: &InscriptionId,
+        block_height: u32,
+    ) -> Result<bool, String> {
+        let Some(pass) = self.storage.get_pass_by_inscription_id(inscription_id)? else {
+            return Ok(false);
+        };
+        if pass.is_collab() {
+            return Ok(false);
+        }
+
+        Ok(
+            self.pass_state_at_height(inscription_id, block_height)?
+                == Some(MinerPassState::Active),
+        )
+    }
+
+    // Resolve the Leader of one collab pass at block_height.
+    // leader_pass_id never follows Leader remints, while leader_btc_addr resolves to
+    // the current active standard pass of that address. A duplicate active owner is
+    // an index inconsistency and is returned as an error rather than resolved.
+    pub fn resolve_leader_at_height(
+        &self,
+        collab: &CollabMinerPassInfo,
+        block_height: u32,
+    ) -> Result<Option<InscriptionId>, String> {
+        if let Some(leader_pass_id) = &collab.leader_pass_id {
+            if self.is_active_standard_pass_at_height(leader_pass_id, block_height)? {
+                return Ok(Some(*leader_pass_id));
+            }
+            return Ok(None);
+        }
+
+        let Some(leader_btc_addr) = &collab.leader_btc_addr else {
+            return Ok(None);
+        };
+
+        let leader_owner = match address_string_to_script_hash(leader_btc_addr, &self.network) {
+            Ok(owner) => owner,
+            Err(e) => {
+                debug!(
+                    "Collab leader address does not resolve on current network: module=pass_collab, collab_pass_id={}, leader_btc_addr={}, error={}",
+                    collab.inscription_id, leader_btc_addr, e
+                );
+                return Ok(None);
+            }
+        };
+
+        let Some(active_pass) = self
+            .storage
+            .get_owner_active_pass_from_history_at_height(&leader_owner, block_height)?
+        else {
+            return Ok(None);
+        };
+
+        if self.is_active_standard_pass_at_height(&active_pass.inscription_id, block_height)? {
+            Ok(Some(active_pass.inscription_id))
+        } else {
+            Ok(None)
+        }
+    }
+
+    fn raw_energy_at_height(
+        &self,
+        inscription_id: &InscriptionId,
+        block_height: u32,
+    ) -> Result<u64, String> {
+        let record = self
+            .energy_manager
+            .get_pass_energy_record_at_or_before(inscription_id, block_height)?;
+        Ok(record
+            .map(|r| {
+                self.energy_manager
+                    .project_energy_record_no_balance_change(&r, block_height)
+                    .energy
+            })
+            .unwrap_or(0))
+    }
+
+    fn collect_collab_contributions(
+        &self,
+        leader_pass_id: &InscriptionId,
+        block_height: u32,
+    ) -> Result<Vec<CollabContribution>, String> {
+        let collabs = self
+            .storage
+            .get_active_collab_passes_from_history_at_height(block_height)?;
+
+        let mut contributions = Vec::new();
+        for collab in collabs {
+            let resolved = self.resolve_leader_at_height(&collab, block_height)?;
+            if resolved.as_ref() != Some(leader_pass_id) {
+                continue;
+            }
+
+            let (leader_ref_kind, leader_ref_value) = match &collab.leader_pass_id {
+                Some(id) => (LeaderRefKind::PassId, id.to_string()),
+                None => (
+                    LeaderRefKind::BtcAddr,
+                    collab.leader_btc_addr.clone().unwrap_or_default(),
+                ),
+            };
+            let collab_raw_energy =
+                self.raw_energy_at_height(&collab.inscription_id, block_height)?;
+            contributions.push(CollabContribution {
+                collab_pass_id: collab.inscription_id,
+                collab_owner: collab.owner,
+                collab_raw_energy,
+                collab_weight_bps: COLLAB_WEIGHT_BPS,
+                collab_contribution: calc_collab_contribution(collab_raw_energy),
+                leader_ref_kind,
+                leader_ref_value,
+            });
+        }
+
+        Ok(contributions)
+    }
+
+    // Resolve every active collab pass at block_height once and sum the contributions
+    // per Leader.
+    pub fn get_collab_contribution_totals_at_height(
+        &self,
+        block_height: u32,
+    ) -> Result<CollabContributionTotals, String> {
+        let collabs = self
+            .storage
+            .get_active_collab_passes_from_history_at_height(block_height)?;
+
+        let mut totals = CollabContributionTotals::default();
+        for collab in collabs {
+            totals.active_collab_pass_ids.insert(collab.inscription_id);
+            let Some(leader_pass_id) = self.resolve_leader_at_height(&collab, block_height)? else {
+                continue;
+            };
+
+            let collab_raw_energy =
+                self.raw_energy_at_height(&collab.inscription_id, block_height)?;
+            let total = totals.leader_totals.entry(leader_pass_id).or_insert(0);
+            *total = total.saturating_add(calc_collab_contribution(collab_raw_energy));
+            *totals
+                .leader_collab_counts
+                .entry(leader_pass_id)
+                .or_insert(0) += 1;
+        }
+
+        Ok(totals)
+    }
+
+    // Get the collab passes contributing to leader_pass_id at block_height.
+    // Returns an empty set if the pass is not an active standard pass at that height.
+    pub fn get_leader_collab_set_at_height(
+        &self,
+        leader_pass_id: &InscriptionId,
+        block_height: u32,
+    ) -> Result<Vec<CollabContribution>, String> {
+        if !self.is_active_standard_pass_at_height(leader_pass_id, block_height)? {
+            return Ok(Vec::new());
+        }
+
+        self.collect_collab_contributions(leader_pass_id, block_height)
+    }
+
+    // Get the effective energy view of one pass at block_height, None if the pass
+    // does not exist yet at that height.
+    pub fn get_pass_effective_energy_at_height(
+        &self,
+        inscription_id: &InscriptionId,
+        block_height: u32,
+    ) -> Result<Option<PassEffectiveEnergy>, String> {
+        let Some(pass) = self.storage.get_pass_by_inscription_id(inscription_id)? else {
+            return Ok(None);
+        };
+        let Some(state) = self.pass_state_at_height(inscription_id, block_height)? else {
+            return Ok(None);
+        };
+
+        let raw_energy = self.raw_energy_at_height(inscription_id, block_height)?;
+        let is_collab = pass.is_collab();
+
+        // Collab passes never enter the candidate set on their own, and only an
+        // active standard pass can carry effective energy.
+        let collabs = if !is_collab && state == MinerPassState::Active {
+            self.collect_collab_contributions(inscription_id, block_height)?
+        } else {
+            Vec::new()
+        };
+        let collab_contribution_total = collabs
+            .iter()
+            .fold(0u64, |acc, c| acc.saturating_add(c.collab_contribution));
+        let effective_energy = if !is_collab && state == MinerPassState::Active {
+            raw_energy.saturating_add(collab_contribution_total)
+        } else {
+            0
+        };
+
+        Ok(Some(PassEffectiveEnergy {
+            inscription_id: *inscription_id,
+            block_height,
+            state,
+            is_collab,
+            raw_energy,
+            collab_contribution_total,
+            effective_energy,
+            collabs,
+        }))
+    }
+}
@@ -3,8 +3,10 @@ use std::str::FromStr;
 use crate::btc::{ContentBody, OrdClient};
 use crate::config::ConfigManager;
 use crate::inscription::InscriptionOperation;
+use bitcoincore_rpc::bitcoin::Address;
 use ord::InscriptionId;
 use serde::{Deserialize, Serialize};
+use usdb_util::{INSCRIPTION_SCHEMA_VERSION_V1, INSCRIPTION_SCHEMA_VERSION_V2};
 
 // check content type at first
 const VALID_CONTENT_TYPES: [&str; 3] = [
@@ -24,6 +26,15 @@ const VALID_CONTENT_TYPES: [&str; 3] = [
     "old_inscription_id_b"
   ]
 }
+
+Collab pass (UIP-0001 v2), exactly one of leader_pass_id / leader_btc_addr,
+and no eth_main / eth_collab:
+{
+  "p": "usdb",
+  "op": "mint",
+  "leader_pass_id": "leader_inscription_id",
+  "prev": []
+}
 */
 
 #[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
@@ -94,6 +105,8 @@ pub enum MintValidationErrorCode {
     InvalidEthCollab,
     InvalidPrevId,
     AmbiguousRevealInput,
+    InvalidLeaderPassId,
+    InvalidLeaderBtcAddr,
 }
 
 impl MintValidationErrorCode {
@@ -104,6 +117,8 @@ impl MintValidationErrorCode {
             MintValidationErrorCode::InvalidEthCollab => "INVALID_ETH_COLLAB",
             MintValidationErrorCode::InvalidPrevId => "INVALID_PREV_ID",
             MintValidationErrorCode::AmbiguousRevealInput => "AMBIGUOUS_REVEAL_INPUT",
+            MintValidationErrorCode::InvalidLeaderPassId => "INVALID_LEADER_PASS_ID",
+            MintValidationErrorCode::InvalidLeaderBtcAddr => "INVALID_LEADER_BTC_ADDR",
         }
     }
 }
@@ -123,12 +138,62 @@ pub enum ParsedMintContent {
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct USDBMint {
+    // Empty for collab passes, which bind to a Leader instead of an ETH identity.
+    #[serde(default)]
     pub eth_main: String,
     pub eth_collab: Option<String>,
     pub prev: Vec<String>,
+
+    // UIP-0004 Leader binding, only present on collab passes.
+    #[serde(default)]
+    pub leader_pass_id: Option<String>,
+    #[serde(default)]
+    pub leader_btc_addr: Option<String>,
+}
+
+// Mint payload as parsed before inscription schema v2. eth_main is required here so a
+// pre-activation payload without it fails with the original serde reason, which is
+// part of the committed error_reason.
+#[derive(Debug, Clone, Deserialize)]
+struct USDBMintV1 {
+    eth_main: String,
+    eth_collab: Option<String>,
+    prev: Vec<String>,
+}
+
+impl From<USDBMintV1> for USDBMint {
+    fn from(mint: USDBMintV1) -> Self {
+        Self {
+            eth_main: mint.eth_main,
+            eth_collab: mint.eth_collab,
+            prev: mint.prev,
+            leader_pass_id: None,
+            leader_btc_addr: None,
+        }
+    }
 }
 
 impl USDBMint {
+    pub fn is_collab(&self) -> bool {
+        self.leader_pass_id.is_some() || self.leader_btc_addr.is_some()
+    }
+
+    pub fn leader_pass_inscription_id(&self) -> Result<Option<InscriptionId>, String> {
+        match &self.leader_pass_id {
+            Some(leader_pass_id) => {
+                InscriptionId::from_str(leader_pass_id)
+                    .map(Some)
+                    .map_err(|e| {
+                        format!(
+                            "Failed to parse leader_pass_id {} in USDBMint: {}",
+                            leader_pass_id, e
+                        )
+                    })
+            }
+            None => Ok(None),
+        }
+    }
+
     pub fn prev_inscription_ids(&self) -> Result<Vec<InscriptionId>, String> {
         self.prev
             .iter()
@@ -144,6 +209,42 @@ impl USDBMint {
     }
 }
 
+// Pass inscription schema active at the mint height, resolved through UIP-0008.
+// v2 adds the UIP-0004 collab pass; under v1 the leader fields are unknown keys.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InscriptionSchemaVersion {
+    V1,
+    V2,
+}
+
+impl InscriptionSchemaVersion {
+    pub fn from_version_value(value: &str) -> Result<Self, String> {
+        match value {
+            INSCRIPTION_SCHEMA_VERSION_V1 => Ok(Self::V1),
+            INSCRIPTION_SCHEMA_VERSION_V2 => Ok(Self::V2),
+            _ => {
+                let msg = format!(
+                    "VERSION_NOT_SUPPORTED: family=inscription_schema_version, version={}",
+                    value
+                );
+                error!("{}", msg);
+                Err(msg)
+            }
+        }
+    }
+
+    pub fn version_value(&self) -> &'static str {
+        match self {
+            Self::V1 => INSCRIPTION_SCHEMA_VERSION_V1,
+            Self::V2 => INSCRIPTION_SCHEMA_VERSION_V2,
+        }
+    }
+
+    pub fn supports_collab_pass(&self) -> bool {
+        matches!(self, Self::V2)
+    }
+}
+
 // TODO: define different types of USDB inscriptions
 #[derive(Debug, Clone)]
 pub enum USDBInscription {
@@ -197,6 +298,7 @@ impl InscriptionContentLoader {
         ord_client: &OrdClient,
         inscription_id: &InscriptionId,
         content_type: Option<&str>,
+        schema: InscriptionSchemaVersion,
         _config: &ConfigManager,
     ) -> Result<Option<(String, USDBInscription)>, String> {
         let content = Self::load_content_data(ord_client, inscription_id, content_type).await?;
@@ -220,7 +322,7 @@ impl InscriptionContentLoader {
         };
 
         // Parse the content into USDBInscription
-        let ret = Self::parse_content(inscription_id, &value)?;
+        let ret = Self::parse_content(inscription_id, &value, schema)?;
         match ret {
             Some(usdb_inscription) => Ok(Some((content, usdb_inscription))),
             None => Ok(None),
@@ -265,8 +367,9 @@ impl InscriptionContentLoader {
     pub fn parse_content_str(
         inscription_id: &InscriptionId,
         content: &str,
+        schema: InscriptionSchemaVersion,
     ) -> Result<Option<USDBInscription>, String> {
-        match Self::classify_mint_content_str(inscription_id, content)? {
+        match Self::classify_mint_content_str(inscription_id, content, schema)? {
             ParsedMintContent::Valid(v) => Ok(Some(v)),
             ParsedMintContent::NotUsdbMint | ParsedMintContent::Invalid(_) => Ok(None),
         }
@@ -275,6 +378,7 @@ impl InscriptionContentLoader {
     pub fn classify_mint_content_str(
         inscription_id: &InscriptionId,
         content: &str,
+        schema: InscriptionSchemaVersion,
     ) -> Result<ParsedMintContent, String> {
         let value = match serde_json::from_str::<serde_json::Value>(content) {
             Ok(v) => v,
@@ -287,14 +391,15 @@ impl InscriptionContentLoader {
             }
         };
 
-        Self::classify_mint_content(inscription_id, &value)
+        Self::classify_mint_content(inscription_id, &value, schema)
     }
 
     pub fn parse_content(
         inscription_id: &InscriptionId,
         content: &serde_json::Value,
+        schema: InscriptionSchemaVersion,
     ) -> Result<Option<USDBInscription>, String> {
-        match Self::classify_mint_content(inscription_id, content)? {
+        match Self::classify_mint_content(inscription_id, content, schema)? {
             ParsedMintContent::Valid(v) => Ok(Some(v)),
             ParsedMintContent::NotUsdbMint | ParsedMintContent::Invalid(_) => Ok(None),
         }
@@ -303,12 +408,20 @@ impl InscriptionContentLoader {
     pub fn classify_mint_content(
         inscription_id: &InscriptionId,
         content: &serde_json::Value,
+        schema: InscriptionSchemaVersion,
     ) -> Result<ParsedMintContent, String> {
         if !content.is_object() {
             return Ok(ParsedMintContent::NotUsdbMint);
         }
 
-        let content = content.as_object().unwrap();
+        let mut content = content.as_object().unwrap().clone();
+        if !schema.supports_collab_pass() {
+            // Before the collab pass activates, leader fields are ignored like any other
+            // unknown key so pre-activation mints classify exactly as they always did.
+            content.remove("leader_pass_id");
+            content.remove("leader_btc_addr");
+        }
+        let content = &content;
 
         // First check protocol field 'p' is equal to 'usdb'
         let p_field = content.get("p");
@@ -327,19 +440,44 @@ impl InscriptionContentLoader {
             return Ok(ParsedMintContent::NotUsdbMint);
         }
 
-        let mint_inscription: USDBMint =
-            match serde_json::from_value(serde_json::Value::Object(content.clone())) {
-                Ok(mint) => mint,
-                Err(e) => {
-                    return Ok(ParsedMintContent::Invalid(MintValidationError {
-                        code: MintValidationErrorCode::InvalidSchema,
-                        reason: format!(
-                            "Failed to parse USDB mint payload for inscription {}: {}",
-                            inscription_id, e
-                        ),
-                    }));
-                }
-            };
+        let payload = serde_json::Value::Object(content.clone());
+        let parsed = if schema.supports_collab_pass() {
+            serde_json::from_value::<USDBMint>(payload)
+        } else {
+            serde_json::from_value::<USDBMintV1>(payload).map(USDBMint::from)
+        };
+        let mint_inscription: USDBMint = match parsed {
+            Ok(mint) => mint,
+            Err(e) => {
+                return Ok(ParsedMintContent::Invalid(MintValidationError {
+                    code: MintValidationErrorCode::InvalidSchema,
+                    reason: format!(
+                        "Failed to parse USDB mint payload for inscription {}: {}",
+                        inscription_id, e
+                    ),
+                }));
+            }
+        };
+
+        if mint_inscription.is_collab() {
+            return Ok(Self::validate_collab_mint(
+                inscription_id,
+                content,
+                mint_inscription,
+            ));
+        }
+
+        // Under v2 eth_main defaults to empty for collab passes, so a standard mint
+        // must be checked for it explicitly.
+        if schema.supports_collab_pass() && !content.contains_key("eth_main") {
+            return Ok(ParsedMintContent::Invalid(MintValidationError {
+                code: MintValidationErrorCode::InvalidSchema,
+                reason: format!(
+                    "Missing eth_main in standard USDB mint payload for inscription {}",
+                    inscription_id
+                ),
+            }));
+        }
 
         if !Self::is_valid_eth_address(&mint_inscription.eth_main) {
             return Ok(ParsedMintContent::Invalid(MintValidationError {
@@ -374,6 +512,63 @@ impl InscriptionContentLoader {
             mint_inscription,
         )))
     }
+
+    // Collab pass schema from UIP-0001 v2: exactly one Leader binding field and no
+    // ETH identity fields. The leader_btc_addr network is checked at resolution time,
+    // where an address for another network simply never resolves to a Leader.
+    fn validate_collab_mint(
+        inscription_id: &InscriptionId,
+        content: &serde_json::Map<String, serde_json::Value>,
+        mint_inscription: USDBMint,
+    ) -> ParsedMintContent {
+        if mint_inscription.leader_pass_id.is_some() && mint_inscription.leader_btc_addr.is_some() {
+            return ParsedMintContent::Invalid(MintValidationError {
+                code: MintValidationErrorCode::InvalidSchema,
+                reason: format!(
+                    "Collab pass must specify only one of leader_pass_id and leader_btc_addr for inscription {}",
+                    inscription_id
+                ),
+            });
+        }
+
+        if content.contains_key("eth_main") || content.contains_key("eth_collab") {
+            return ParsedMintContent::Invalid(MintValidationError {
+                code: MintValidationErrorCode::InvalidSchema,
+                reason: format!(
+                    "Collab pass must not contain eth_main or eth_collab for inscription {}",
+                    inscription_id
+                ),
+            });
+        }
+
+        if let Err(e) = mint_inscription.leader_pass_inscription_id() {
+            return ParsedMintContent::Invalid(MintValidationError {
+                code: MintValidationErrorCode::InvalidLeaderPassId,
+                reason: e,
+            });
+        }
+
+        if let Some(leader_btc_addr) = &mint_inscription.leader_btc_addr {
+            if let Err(e) = Address::from_str(leader_btc_addr) {
+                return ParsedMintContent::Invalid(MintValidationError {
+                    code: MintValidationErrorCode::InvalidLeaderBtcAddr,
+                    reason: format!(
+                        "Invalid leader_btc_addr format for inscription {}: {}, error={}",
+                        inscription_id, leader_btc_addr, e
+                    ),
+                });
+            }
+        }
+
+        if let Err(e) = mint_inscription.prev_inscription_ids() {
+            return ParsedMintContent::Invalid(MintValidationError {
+                code: MintValidationErrorCode::InvalidPrevId,
+                reason: e,
+            });
+        }
+
+        ParsedMintContent::Valid(USDBInscription::Mint(mint_inscription))
+    }
 }
 
 #[cfg(test)]
@@ -392,8 +587,12 @@ mod tests {
         let inscription_id = test_inscription_id(1, 0);
         let content = r#"{"p":"usdb","op":"mint","eth_main":"0x1111111111111111111111111111111111111111","eth_collab":"0x2222222222222222222222222222222222222222","prev":["1111111111111111111111111111111111111111111111111111111111111111i0"]}"#;
 
-        let result =
-            InscriptionContentLoader::classify_mint_content_str(&inscription_id, content).unwrap();
+        let result = InscriptionContentLoader::classify_mint_content_str(
+            &inscription_id,
+            content,
+            InscriptionSchemaVersion::V2,
+        )
+        .unwrap();
         assert!(matches!(result, ParsedMintContent::Valid(_)));
     }
 
@@ -402,8 +601,12 @@ mod tests {
         let inscription_id = test_inscription_id(2, 0);
         let content = r#"{"p":"usdb","op":"mint","eth_main":"0x123","prev":[]}"#;
 
-        let result =
-            InscriptionContentLoader::classify_mint_content_str(&inscription_id, content).unwrap();
+        let result = InscriptionContentLoader::classify_mint_content_str(
+            &inscription_id,
+            content,
+            InscriptionSchemaVersion::V2,
+        )
+        .unwrap();
         match result {
             ParsedMintContent::Invalid(err) => {
                 assert_eq!(err.code, MintValidationErrorCode::InvalidEthMain)
@@ -417,8 +620,12 @@ mod tests {
         let inscription_id = test_inscription_id(3, 0);
         let content = r#"{"p":"usdb","op":"mint","eth_main":"0x1111111111111111111111111111111111111111","eth_collab":"0xabc","prev":[]}"#;
 
-        let result =
-            InscriptionContentLoader::classify_mint_content_str(&inscription_id, content).unwrap();
+        let result = InscriptionContentLoader::classify_mint_content_str(
+            &inscription_id,
+            content,
+            InscriptionSchemaVersion::V2,
+        )
+        .unwrap();
         match result {
             ParsedMintContent::Invalid(err) => {
                 assert_eq!(err.code, MintValidationErrorCode::InvalidEthCollab)
@@ -432,8 +639,12 @@ mod tests {
         let inscription_id = test_inscription_id(4, 0);
         let content = r#"{"p":"usdb","op":"mint","eth_main":"0x1111111111111111111111111111111111111111","prev":["bad-prev-id"]}"#;
 
-        let result =
-            InscriptionContentLoader::classify_mint_content_str(&inscription_id, content).unwrap();
+        let result = InscriptionContentLoader::classify_mint_content_str(
+            &inscription_id,
+            content,
+            InscriptionSchemaVersion::V2,
+        )
+        .unwrap();
         match result {
             ParsedMintContent::Invalid(err) => {
                 assert_eq!(err.code, MintValidationErrorCode::InvalidPrevId)
@@ -441,4 +652,229 @@ mod tests {
             _ => panic!("expected invalid mint content"),
         }
     }
+
+    #[test]
+    fn test_classify_mint_content_str_valid_collab_with_leader_pass_id() {
+        let inscription_id = test_inscription_id(5, 0);
+        let content = r#"{"p":"usdb","op":"mint","leader_pass_id":"1111111111111111111111111111111111111111111111111111111111111111i0","prev":[]}"#;
+
+        let result = InscriptionContentLoader::classify_mint_content_str(
+            &inscription_id,
+            content,
+            InscriptionSchemaVersion::V2,
+        )
+        .unwrap();
+        match result {
+            ParsedMintContent::Valid(USDBInscription::Mint(mint)) => {
+                assert!(mint.is_collab());
+                assert!(mint.eth_main.is_empty());
+                assert!(mint.leader_pass_inscription_id().unwrap().is_some());
+            }
+            _ => panic!("expected valid collab mint content"),
+        }
+    }
+
+    #[test]
+    fn test_classify_mint_content_str_valid_collab_with_leader_btc_addr() {
+        let inscription_id = test_inscription_id(6, 0);
+        let content = r#"{"p":"usdb","op":"mint","leader_btc_addr":"bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4","prev":[]}"#;
+
+        let result = InscriptionContentLoader::classify_mint_content_str(
+            &inscription_id,
+            content,
+            InscriptionSchemaVersion::V2,
+        )
+        .unwrap();
+        assert!(matches!(result, ParsedMintContent::Valid(_)));
+    }
+
+    #[test]
+    fn test_classify_mint_content_str_collab_rejects_eth_main() {
+        let inscription_id = test_inscription_id(7, 0);
+        let content = r#"{"p":"usdb","op":"mint","eth_main":"0x1111111111111111111111111111111111111111","leader_btc_addr":"bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4","prev":[]}"#;
+
+        let result = InscriptionContentLoader::classify_mint_content_str(
+            &inscription_id,
+            content,
+            InscriptionSchemaVersion::V2,
+        )
+        .unwrap();
+        match result {
+            ParsedMintContent::Invalid(err) => {
+                assert_eq!(err.code, MintValidationErrorCode::InvalidSchema)
+            }
+            _ => panic!("expected invalid mint content"),
+        }
+    }
+
+    #[test]
+    fn test_classify_mint_content_str_collab_rejects_both_leader_fields() {
+        let inscription_id = test_inscription_id(8, 0);
+        let content = r#"{"p":"usdb","op":"mint","leader_pass_id":"1111111111111111111111111111111111111111111111111111111111111111i0","leader_btc_addr":"bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4","prev":[]}"#;
+
+        let result = InscriptionContentLoader::classify_mint_content_str(
+            &inscription_id,
+            content,
+            InscriptionSchemaVersion::V2,
+        )
+        .unwrap();
+        match result {
+            ParsedMintContent::Invalid(err) => {
+                assert_eq!(err.code, MintValidationErrorCode::InvalidSchema)
+            }
+            _ => panic!("expected invalid mint content"),
+        }
+    }
+
+    #[test]
+    fn test_classify_mint_content_str_invalid_leader_fields() {
+        let inscription_id = test_inscription_id(9, 0);
+        let content = r#"{"p":"usdb","op":"mint","leader_pass_id":"bad-leader-id","prev":[]}"#;
+        let result = InscriptionContentLoader::classify_mint_content_str(
+            &inscription_id,
+            content,
+            InscriptionSchemaVersion::V2,
+        )
+        .unwrap();
+        match result {
+            ParsedMintContent::Invalid(err) => {
+                assert_eq!(err.code, MintValidationErrorCode::InvalidLeaderPassId)
+            }
+            _ => panic!("expected invalid mint content"),
+        }
+
+        let content = r#"{"p":"usdb","op":"mint","leader_btc_addr":"not-an-address","prev":[]}"#;
+        let result = InscriptionContentLoader::classify_mint_content_str(
+            &inscription_id,
+            content,
+            InscriptionSchemaVersion::V2,
+        )
+        .unwrap();
+        match result {
+            ParsedMintContent::Invalid(err) => {
+                assert_eq!(err.code, MintValidationErrorCode::InvalidLeaderBtcAddr)
+            }
+            _ => panic!("expected invalid mint content"),
+        }
+    }
+
+    #[test]
+    fn test_classify_mint_content_str_standard_requires_eth_main() {
+        let inscription_id = test_inscription_id(10, 0);
+        let content = r#"{"p":"usdb","op":"mint","prev":[]}"#;
+
+        let result = InscriptionContentLoader::classify_mint_content_str(
+            &inscription_id,
+            content,
+            InscriptionSchemaVersion::V2,
+        )
+        .unwrap();
+        match result {
+            ParsedMintContent::Invalid(err) => {
+                assert_eq!(err.code, MintValidationErrorCode::InvalidSchema)
+            }
+            _ => panic!("expected invalid mint content"),
+        }
+    }
+
+    #[test]
+    fn test_classify_collab_mint_follows_inscription_schema_activation() {
+        // Builtin regtest registry activates inscription schema v2 at height 1.
+        let registry = usdb_util::ActivationRegistry::builtin().unwrap();
+        let schema_at = |height| {
+            let value = registry
+                .lookup_btc_version(
+                    "btc-regtest",
+                    usdb_util::INSCRIPTION_SCHEMA_VERSION_FAMILY,
+                    height,
+                )
+                .unwrap();
+            InscriptionSchemaVersion::from_version_value(&value).unwrap()
+        };
+        assert_eq!(schema_at(0), InscriptionSchemaVersion::V1);
+        assert_eq!(schema_at(1), InscriptionSchemaVersion::V2);
+
+        let inscription_id = test_inscription_id(11, 0);
+        let collab = r#"{"p":"usdb","op":"mint","leader_pass_id":"1111111111111111111111111111111111111111111111111111111111111111i0","prev":[]}"#;
+
+        // Below activation a collab payload is a standard mint missing eth_main.
+        let result = InscriptionContentLoader::classify_mint_content_str(
+            &inscription_id,
+            collab,
+            schema_at(0),
+        )
+        .unwrap();
+        match result {
+            ParsedMintContent::Invalid(err) => {
+                assert_eq!(err.code, MintValidationErrorCode::InvalidSchema);
+                assert!(err.reason.contains("missing field `eth_main`"));
+            }
+            _ => panic!("expected invalid mint content"),
+        }
+
+        let result = InscriptionContentLoader::classify_mint_content_str(
+            &inscription_id,
+            collab,
+            schema_at(1),
+        )
+        .unwrap();
+        match result {
+            ParsedMintContent::Valid(USDBInscription::Mint(mint)) => assert!(mint.is_collab()),
+            _ => panic!("expected valid collab mint content"),
+        }
+    }
+
+    #[test]
+    fn test_classify_v1_missing_eth_main_keeps_serde_reason() {
+        let inscription_id = test_inscription_id(13, 0);
+        let content = r#"{"p":"usdb","op":"mint","prev":[]}"#;
+
+        let result = InscriptionContentLoader::classify_mint_content_str(
+            &inscription_id,
+            content,
+            InscriptionSchemaVersion::V1,
+        )
+        .unwrap();
+        match result {
+            ParsedMintContent::Invalid(err) => {
+                assert_eq!(err.code, MintValidationErrorCode::InvalidSchema);
+                assert_eq!(
+                    err.reason,
+                    format!(
+                        "Failed to parse USDB mint payload for inscription {}: missing field `eth_main`",
+                        inscription_id
+                    )
+                );
+            }
+            _ => panic!("expected invalid mint content"),
+        }
+    }
+
+    #[test]
+    fn test_classify_mint_ignores_leader_fields_before_collab_activation() {
+        let inscription_id = test_inscription_id(12, 0);
+        let content = r#"{"p":"usdb","op":"mint","eth_main":"0x1111111111111111111111111111111111111111","leader_btc_addr":"bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4","leader_pass_id":42,"prev":[]}"#;
+
+        let result = InscriptionContentLoader::classify_mint_content_str(
+            &inscription_id,
+            content,
+            InscriptionSchemaVersion::V1,
+        )
+        .unwrap();
+        match result {
+            ParsedMintContent::Valid(USDBInscription::Mint(mint)) => {
+                assert!(!mint.is_collab());
+                assert_eq!(mint.eth_main, "0x1111111111111111111111111111111111111111");
+            }
+            _ => panic!("expected valid standard mint content"),
+        }
+
+        let result = InscriptionContentLoader::classify_mint_content_str(
+            &inscription_id,
+            content,
+            InscriptionSchemaVersion::V2,
+        )
+        .unwrap();
+        assert!(matches!(result, ParsedMintContent::Invalid(_)));
+    }
 }
@@ -1,5 +1,5 @@
 use super::content::MinerPassState;
-use super::energy_formula::{calc_growth_delta, calc_penalty_from_delta};
+use super::energy_formula::EnergyFormulaSchedule;
 use crate::config::ConfigManagerRef;
 use crate::storage::{PassEnergyRecord, PassEnergyStorage, PassEnergyValue};
 use balance_history::{AddressBalance, RpcClient as BalanceHistoryRpcClient};
@@ -8,7 +8,7 @@ use std::future::Future;
 use std::ops::Range;
 use std::pin::Pin;
 use std::sync::Arc;
-use usdb_util::USDBScriptHash;
+use usdb_util::{ENERGY_FORMULA_VERSION_FAMILY, USDBScriptHash};
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct PassEnergyResult {
@@ -74,30 +74,10 @@ impl BalanceProvider for RpcBalanceProvider {
     }
 }
 
-fn calc_incremental_growth(
-    owner_balance: u64,
-    active_block_height: u32,
-    from_block_height: u32,
-    to_block_height: u32,
-) -> u64 {
-    if to_block_height <= from_block_height {
-        return 0;
-    }
-
-    let growth_at_to = calc_growth_delta(
-        owner_balance,
-        to_block_height.saturating_sub(active_block_height),
-    );
-    let growth_at_from = calc_growth_delta(
-        owner_balance,
-        from_block_height.saturating_sub(active_block_height),
-    );
-    growth_at_to.saturating_sub(growth_at_from)
-}
-
 pub struct PassEnergyManager {
     config: ConfigManagerRef,
     storage: PassEnergyStorage,
+    formula_schedule: EnergyFormulaSchedule,
     balance_provider: Arc<dyn BalanceProvider>,
     #[cfg(test)]
     force_strict_settle_consistency_for_test: std::sync::atomic::AtomicBool,
@@ -110,21 +90,33 @@ impl PassEnergyManager {
             &config.config().balance_history.rpc_url,
         )?);
 
-        Ok(Self::new_with_deps(config, storage, balance_provider))
+        Self::new_with_deps(config, storage, balance_provider)
     }
 
     pub(crate) fn new_with_deps(
         config: ConfigManagerRef,
         storage: PassEnergyStorage,
         balance_provider: Arc<dyn BalanceProvider>,
-    ) -> Self {
-        Self {
+    ) -> Result<Self, String> {
+        let formula_schedule =
+            EnergyFormulaSchedule::new(config.activation_registry().btc_activation_points(
+                &config.activation_network_id(),
+                ENERGY_FORMULA_VERSION_FAMILY,
+            ))?;
+        info!(
+            "Energy formula schedule loaded: module=pass_energy, network_id={}, schedule={}",
+            config.activation_network_id(),
+            formula_schedule.describe()
+        );
+
+        Ok(Self {
             config,
             storage,
+            formula_schedule,
             balance_provider,
             #[cfg(test)]
             force_strict_settle_consistency_for_test: std::sync::atomic::AtomicBool::new(false),
-        }
+        })
     }
 
     fn strict_settle_consistency_enabled(&self) -> bool {
@@ -434,17 +426,12 @@ impl PassEnergyManager {
             };
         }
 
-        let growth_at_query = calc_growth_delta(
+        let incremental_growth = self.formula_schedule.calc_incremental_growth(
             record.owner_balance,
-            query_block_height.saturating_sub(record.active_block_height),
+            record.active_block_height,
+            record.block_height,
+            query_block_height,
         );
-        let growth_at_record = calc_growth_delta(
-            record.owner_balance,
-            record
-                .block_height
-                .saturating_sub(record.active_block_height),
-        );
-        let incremental_growth = growth_at_query.saturating_sub(growth_at_record);
 
         PassEnergyResult {
             energy: record.energy.saturating_add(incremental_growth),
@@ -564,7 +551,7 @@ impl PassEnergyManager {
         for balance_record in balances {
             // Calculate energy bonus between last_record.block_height and balance_record.block_height base on last_record.owner_balance
             // The R is related to the H, H = current block height - miner certificate's activation block height. The larger the H, the larger the R, but the R has an upper limit.
-            let energy_delta = calc_incremental_growth(
+            let energy_delta = self.formula_schedule.calc_incremental_growth(
                 last_record.owner_balance,
                 last_record.active_block_height,
                 last_record.block_height,
@@ -574,19 +561,25 @@ impl PassEnergyManager {
             let mut new_energy = last_record.energy.saturating_add(energy_delta);
 
             // Keep active height for non-negative deltas.
-            // Only negative delta starts a new growth window after penalty is applied.
+            // Negative deltas apply the penalty and active height rule of the formula
+            // active at the balance change height.
             let active_block_height = if balance_record.delta < 0 {
-                balance_record.block_height
+                let (penalty, active_block_height) = self
+                    .formula_schedule
+                    .formula_at(balance_record.block_height)
+                    .calc_balance_decrease(
+                        last_record.active_block_height,
+                        balance_record.block_height,
+                        last_record.owner_balance,
+                        balance_record.balance,
+                        balance_record.delta,
+                    );
+                new_energy = new_energy.saturating_sub(penalty);
+                active_block_height
             } else {
                 last_record.active_block_height
             };
 
-            // Apply protocol-defined punishment on negative balance delta.
-            if balance_record.delta < 0 {
-                let energy_delta = calc_penalty_from_delta(balance_record.delta);
-                new_energy = new_energy.saturating_sub(energy_delta);
-            }
-
             let new_energy = PassEnergyRecord {
                 inscription_id: inscription_id.clone(),
                 block_height: balance_record.block_height,
@@ -604,7 +597,7 @@ impl PassEnergyManager {
         let ret = if last_record.block_height < block_height {
             // No balance changes in between, just calculate energy up to block_height
             // This record should not save to storage, as there is no balance change record at this height
-            let energy_delta = calc_incremental_growth(
+            let energy_delta = self.formula_schedule.calc_incremental_growth(
                 last_record.owner_balance,
                 last_record.active_block_height,
                 last_record.block_height,
@@ -745,7 +738,7 @@ impl PassEnergyManager {
             return Ok(false);
         }
 
-        let growth_delta = calc_incremental_growth(
+        let growth_delta = self.formula_schedule.calc_incremental_growth(
             last_record.owner_balance,
             last_record.active_block_height,
             last_record.block_height,
@@ -754,14 +747,21 @@ impl PassEnergyManager {
         let mut next_energy = last_record.energy.saturating_add(growth_delta);
 
         let next_active_height = if owner_delta < 0 {
-            block_height
+            let (penalty, next_active_height) = self
+                .formula_schedule
+                .formula_at(block_height)
+                .calc_balance_decrease(
+                    last_record.active_block_height,
+                    block_height,
+                    last_record.owner_balance,
+                    owner_balance,
+                    owner_delta,
+                );
+            next_energy = next_energy.saturating_sub(penalty);
+            next_active_height
         } else {
             last_record.active_block_height
         };
-        if owner_delta < 0 {
-            let penalty = calc_penalty_from_delta(owner_delta);
-            next_energy = next_energy.saturating_sub(penalty);
-        }
 
         let record = PassEnergyRecord {
             inscription_id: inscription_id.clone(),
@@ -840,6 +840,14 @@ impl PassEnergyManager {
         Ok(())
     }
 
+    // Raw energy a consumed prev pass passes on to a new pass minted at block_height.
+    // The discount follows the formula active at the mint height, per prev.
+    pub fn calc_inheritable_energy(&self, energy: u64, block_height: u32) -> u64 {
+        self.formula_schedule
+            .formula_at(block_height)
+            .calc_inheritable_energy(energy)
+    }
+
     // Clear energy to zero on consumed
     pub fn on_pass_consumed(
         &self,
@@ -869,13 +877,20 @@ pub type PassEnergyManagerRef = std::sync::Arc<PassEnergyManager>;
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::config::ConfigManager;
+    use crate::config::{ConfigManager, IndexerConfig};
+    use crate::index::energy_formula::{
+        calc_active_block_height_after_loss_v2, calc_growth_delta, calc_growth_delta_v2,
+        calc_penalty_from_delta, calc_penalty_v2,
+    };
     use bitcoincore_rpc::bitcoin::hashes::Hash;
-    use bitcoincore_rpc::bitcoin::{ScriptBuf, Txid};
+    use bitcoincore_rpc::bitcoin::{Network, ScriptBuf, Txid};
     use std::path::PathBuf;
     use std::sync::Arc;
     use std::time::{SystemTime, UNIX_EPOCH};
-    use usdb_util::ToUSDBScriptHash;
+    use usdb_util::{
+        ENERGY_FORMULA_VERSION_V1, ENERGY_FORMULA_VERSION_V2, ToUSDBScriptHash,
+        btc_activation_network_id,
+    };
 
     struct TestBalanceProvider {
         at_height: Vec<AddressBalance>,
@@ -1184,6 +1199,113 @@ mod tests {
         std::fs::remove_dir_all(root_dir).unwrap();
     }
 
+    #[test]
+    fn test_energy_formula_v2_from_genesis() {
+        // Energy formula v2 cannot supersede v1 history, so a registry that activates it
+        // later is rejected at load time, while a v2-only network uses the unit based
+        // penalty, age decay and inheritance from genesis.
+        let root_dir = test_root_dir("formula_v2_genesis");
+        std::fs::create_dir_all(&root_dir).unwrap();
+
+        let mut indexer_config = IndexerConfig::default();
+        indexer_config.bitcoin.network = Network::Regtest;
+        indexer_config.usdb.activation_registry_file = Some("activation-registry.json".to_string());
+        std::fs::write(
+            root_dir.join("config.json"),
+            serde_json::to_string_pretty(&indexer_config).unwrap(),
+        )
+        .unwrap();
+
+        let network_id = btc_activation_network_id(Network::Regtest);
+        let write_registry = |energy_records: Vec<usdb_util::ActivationRecord>| {
+            let mut records: Vec<usdb_util::ActivationRecord> =
+                usdb_util::ActivationRegistry::builtin()
+                    .unwrap()
+                    .records()
+                    .iter()
+                    .filter(|r| {
+                        !(r.network_id == network_id
+                            && r.version_family == ENERGY_FORMULA_VERSION_FAMILY)
+                    })
+                    .cloned()
+                    .collect();
+            records.extend(energy_records);
+            std::fs::write(
+                root_dir.join("activation-registry.json"),
+                serde_json::to_string_pretty(&serde_json::json!({ "records": records })).unwrap(),
+            )
+            .unwrap();
+        };
+        let energy_record =
+            |value: &str, height: u64, supersedes: Option<&str>| usdb_util::ActivationRecord {
+                uip: "UIP-0003".to_string(),
+                version_family: ENERGY_FORMULA_VERSION_FAMILY.to_string(),
+                version_value: value.to_string(),
+                chain: usdb_util::ActivationChain::BTC,
+                network_type: "regtest".to_string(),
+                network_id: network_id.clone(),
+                activation_anchor: usdb_util::ActivationAnchor::BtcHeight,
+                activation_value: height,
+                status: usdb_util::ActivationStatus::Active,
+                supersedes: supersedes.map(|v| v.to_string()),
+                notes: None,
+            };
+
+        write_registry(vec![
+            energy_record(ENERGY_FORMULA_VERSION_V1, 0, None),
+            energy_record(
+                ENERGY_FORMULA_VERSION_V2,
+                150,
+                Some(ENERGY_FORMULA_VERSION_V1),
+            ),
+        ]);
+        let err = ConfigManager::load(Some(root_dir.clone())).err().unwrap();
+        assert!(err.contains("ENERGY_SCALE_TRANSITION_UNSUPPORTED"));
+
+        write_registry(vec![energy_record(ENERGY_FORMULA_VERSION_V2, 0, None)]);
+        let config = Arc::new(ConfigManager::load(Some(root_dir.clone())).unwrap());
+        let manager = PassEnergyManager::new(config).unwrap();
+
+        let inscription_id = test_inscription_id(11, 0);
+        let owner = test_script_hash(11);
+        manager
+            .storage
+            .insert_pass_energy_record(&PassEnergyRecord {
+                inscription_id,
+                block_height: 100,
+                state: MinerPassState::Active,
+                active_block_height: 100,
+                owner_address: owner,
+                owner_balance: 400_000,
+                owner_delta: 0,
+                energy: 10_000,
+            })
+            .unwrap();
+
+        assert!(
+            manager
+                .apply_active_balance_change(&inscription_id, &owner, 200, 250_000, -150_000)
+                .unwrap()
+        );
+        let record = manager
+            .get_pass_energy_record_exact(&inscription_id, 200)
+            .unwrap()
+            .unwrap();
+        let expected_energy = 10_000u64
+            .saturating_add(calc_growth_delta_v2(400_000, 100))
+            .saturating_sub(calc_penalty_v2(400_000, 250_000, 100));
+        assert_eq!(record.energy, expected_energy);
+        assert_eq!(
+            record.active_block_height,
+            calc_active_block_height_after_loss_v2(100, 200, 400_000, 250_000)
+        );
+
+        assert_eq!(manager.calc_inheritable_energy(10_000, 0), 9_500);
+        assert_eq!(manager.calc_inheritable_energy(10_000, 200), 9_500);
+
+        std::fs::remove_dir_all(root_dir).unwrap();
+    }
+
     #[test]
     fn test_apply_active_balance_change_same_height_conflict_relaxed_mode_skips() {
         // Relaxed mode should skip same-height conflicting updates without mutating existing record.
@@ -1240,7 +1362,7 @@ mod tests {
             at_height: vec![],
             at_range: vec![],
         });
-        let manager = PassEnergyManager::new_with_deps(config, storage, provider);
+        let manager = PassEnergyManager::new_with_deps(config, storage, provider).unwrap();
 
         let inscription_id = test_inscription_id(17, 0);
         let owner = test_script_hash(17);
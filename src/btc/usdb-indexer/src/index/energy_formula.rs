@@ -1,6 +1,9 @@
+use usdb_util::{ENERGY_FORMULA_VERSION_V1, ENERGY_FORMULA_VERSION_V2};
+
 // Energy formula constants are centralized here for consistency between
 // production logic and tests. We intentionally keep them as code constants
-// for now (not runtime config) to reduce protocol drift risk.
+// for now (not runtime config) to reduce protocol drift risk. Which formula
+// applies at a height is decided by the UIP-0008 activation registry.
 
 // 0.001 BTC threshold in satoshi.
 pub const ENERGY_BALANCE_THRESHOLD: u64 = 100_000;
@@ -12,6 +15,21 @@ pub const ENERGY_GROWTH_MULTIPLIER: u64 = 10_000;
 // Current protocol multiplier = 10_000 * 6 * 24 * 30.
 pub const ENERGY_PENALTY_MULTIPLIER: u64 = 43_200_000;
 
+// UIP-0003 v2: growth and penalty are measured in discrete 0.001 BTC balance units.
+pub const ENERGY_UNIT_SATS: u64 = 100_000;
+pub const ENERGY_PER_UNIT_BLOCK: u64 = 1;
+
+// UIP-0003 v2 penalty multiplier lambda = 3 / 2.
+pub const PENALTY_LAMBDA_NUM: u64 = 3;
+pub const PENALTY_LAMBDA_DEN: u64 = 2;
+
+// UIP-0003 v2: each prev loses 5% of its raw energy when inherited.
+pub const INHERIT_DISCOUNT_BPS: u64 = 500;
+
+// UIP-0004: collab raw energy is counted at 50% into its Leader's effective energy.
+pub const COLLAB_WEIGHT_BPS: u64 = 5_000;
+pub const BPS_DENOMINATOR: u64 = 10_000;
+
 fn saturating_u128_to_u64(value: u128) -> u64 {
     if value > u64::MAX as u128 {
         u64::MAX
@@ -43,6 +61,238 @@ pub fn calc_penalty_from_delta(delta: i64) -> u64 {
     saturating_u128_to_u64(raw)
 }
 
+// UIP-0003 v2 growth: balance_units * ENERGY_PER_UNIT_BLOCK * r.
+pub fn calc_growth_delta_v2(owner_balance: u64, r: u32) -> u64 {
+    let units = owner_balance / ENERGY_UNIT_SATS;
+    let raw = (units as u128)
+        .saturating_mul(ENERGY_PER_UNIT_BLOCK as u128)
+        .saturating_mul(r as u128);
+    saturating_u128_to_u64(raw)
+}
+
+// UIP-0003 v2 penalty: floor(lost_units * age_before * lambda).
+// Units are snapshotted before and after the change, never derived from the sat delta.
+pub fn calc_penalty_v2(balance_before: u64, balance_after: u64, age_before: u32) -> u64 {
+    let units_before = balance_before / ENERGY_UNIT_SATS;
+    let units_after = balance_after / ENERGY_UNIT_SATS;
+    let lost_units = units_before.saturating_sub(units_after);
+
+    let raw = (lost_units as u128)
+        .saturating_mul(age_before as u128)
+        .saturating_mul(ENERGY_PER_UNIT_BLOCK as u128)
+        .saturating_mul(PENALTY_LAMBDA_NUM as u128)
+        / PENALTY_LAMBDA_DEN as u128;
+    saturating_u128_to_u64(raw)
+}
+
+// UIP-0003 v2 age decay: the remaining units keep floor(age * units_after / units_before).
+pub fn calc_active_block_height_after_loss_v2(
+    active_block_height: u32,
+    block_height: u32,
+    balance_before: u64,
+    balance_after: u64,
+) -> u32 {
+    let units_before = balance_before / ENERGY_UNIT_SATS;
+    let units_after = balance_after / ENERGY_UNIT_SATS;
+    if units_before == 0 || units_after >= units_before {
+        return active_block_height;
+    }
+    if units_after == 0 {
+        return block_height;
+    }
+
+    let age_before = block_height.saturating_sub(active_block_height);
+    let remaining_age = (age_before as u128) * (units_after as u128) / (units_before as u128);
+    block_height.saturating_sub(remaining_age as u32)
+}
+
+// UIP-0003 v2 inheritance: floor(energy * (1 - INHERIT_DISCOUNT_BPS / BPS_DENOMINATOR)).
+pub fn calc_inheritable_energy_v2(energy: u64) -> u64 {
+    let raw = (energy as u128).saturating_mul((BPS_DENOMINATOR - INHERIT_DISCOUNT_BPS) as u128)
+        / BPS_DENOMINATOR as u128;
+    saturating_u128_to_u64(raw)
+}
+
+// The two versions use different energy scales: v1 counts satoshi * ENERGY_GROWTH_MULTIPLIER
+// per block, v2 counts 0.001 BTC units per block, about 1e9 times smaller. Stored energy is
+// never rescaled, so the activation registry only accepts v2 from genesis on networks
+// without v1 history and a single network never mixes the two scales.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EnergyFormulaVersion {
+    V1,
+    V2,
+}
+
+impl EnergyFormulaVersion {
+    pub fn from_version_value(value: &str) -> Result<Self, String> {
+        match value {
+            ENERGY_FORMULA_VERSION_V1 => Ok(Self::V1),
+            ENERGY_FORMULA_VERSION_V2 => Ok(Self::V2),
+            _ => {
+                let msg = format!(
+                    "VERSION_NOT_SUPPORTED: family=energy_formula_version, version={}",
+                    value
+                );
+                error!("{}", msg);
+                Err(msg)
+            }
+        }
+    }
+
+    pub fn version_value(&self) -> &'static str {
+        match self {
+            Self::V1 => ENERGY_FORMULA_VERSION_V1,
+            Self::V2 => ENERGY_FORMULA_VERSION_V2,
+        }
+    }
+
+    // Growth accrued over settled heights (from_block_height, to_block_height].
+    pub fn calc_incremental_growth(
+        &self,
+        owner_balance: u64,
+        active_block_height: u32,
+        from_block_height: u32,
+        to_block_height: u32,
+    ) -> u64 {
+        if to_block_height <= from_block_height {
+            return 0;
+        }
+
+        match self {
+            Self::V1 => {
+                let growth_at_to = calc_growth_delta(
+                    owner_balance,
+                    to_block_height.saturating_sub(active_block_height),
+                );
+                let growth_at_from = calc_growth_delta(
+                    owner_balance,
+                    from_block_height.saturating_sub(active_block_height),
+                );
+                growth_at_to.saturating_sub(growth_at_from)
+            }
+            Self::V2 => calc_growth_delta_v2(owner_balance, to_block_height - from_block_height),
+        }
+    }
+
+    // Penalty and next active_block_height for a negative owner balance change at block_height.
+    pub fn calc_balance_decrease(
+        &self,
+        active_block_height: u32,
+        block_height: u32,
+        balance_before: u64,
+        balance_after: u64,
+        owner_delta: i64,
+    ) -> (u64, u32) {
+        match self {
+            // v1 restarts the growth window on any decrease and uses the fixed-window penalty.
+            Self::V1 => (calc_penalty_from_delta(owner_delta), block_height),
+            Self::V2 => (
+                calc_penalty_v2(
+                    balance_before,
+                    balance_after,
+                    block_height.saturating_sub(active_block_height),
+                ),
+                calc_active_block_height_after_loss_v2(
+                    active_block_height,
+                    block_height,
+                    balance_before,
+                    balance_after,
+                ),
+            ),
+        }
+    }
+
+    // Raw energy one prev pass passes on to the new pass.
+    pub fn calc_inheritable_energy(&self, energy: u64) -> u64 {
+        match self {
+            Self::V1 => energy,
+            Self::V2 => calc_inheritable_energy_v2(energy),
+        }
+    }
+}
+
+// Energy formula versions of one network ordered by activation height. The first
+// entry must start at height 0 so every height has exactly one formula.
+#[derive(Debug, Clone)]
+pub struct EnergyFormulaSchedule {
+    segments: Vec<(u32, EnergyFormulaVersion)>,
+}
+
+impl EnergyFormulaSchedule {
+    pub fn new(activation_points: Vec<(u32, String)>) -> Result<Self, String> {
+        let mut segments = Vec::with_capacity(activation_points.len());
+        for (height, value) in activation_points {
+            segments.push((height, EnergyFormulaVersion::from_version_value(&value)?));
+        }
+
+        if segments.first().map(|(h, _)| *h) != Some(0) {
+            let msg = format!(
+                "ACTIVATION_RECORD_NOT_FOUND: energy_formula_version must be active from height 0, segments={:?}",
+                segments
+            );
+            error!("{}", msg);
+            return Err(msg);
+        }
+
+        Ok(Self { segments })
+    }
+
+    // Human readable "height:version" list for logs.
+    pub fn describe(&self) -> String {
+        self.segments
+            .iter()
+            .map(|(h, v)| format!("{}:{}", h, v.version_value()))
+            .collect::<Vec<_>>()
+            .join(",")
+    }
+
+    pub fn formula_at(&self, block_height: u32) -> EnergyFormulaVersion {
+        self.segments
+            .iter()
+            .rev()
+            .find(|(h, _)| *h <= block_height)
+            .map(|(_, v)| *v)
+            .unwrap_or(EnergyFormulaVersion::V1)
+    }
+
+    // Growth over (from_block_height, to_block_height], where each block accrues
+    // under the formula active at that block's own height.
+    pub fn calc_incremental_growth(
+        &self,
+        owner_balance: u64,
+        active_block_height: u32,
+        from_block_height: u32,
+        to_block_height: u32,
+    ) -> u64 {
+        let mut growth = 0u64;
+        for (index, (start, formula)) in self.segments.iter().enumerate() {
+            // Segment covers block heights [start, next_start).
+            let lo = from_block_height.max(start.saturating_sub(1));
+            let hi = match self.segments.get(index + 1) {
+                Some((next_start, _)) => to_block_height.min(next_start.saturating_sub(1)),
+                None => to_block_height,
+            };
+            if hi <= lo {
+                continue;
+            }
+            growth = growth.saturating_add(formula.calc_incremental_growth(
+                owner_balance,
+                active_block_height,
+                lo,
+                hi,
+            ));
+        }
+        growth
+    }
+}
+
+// Calculate one collab pass contribution to its resolved Leader, rounded down.
+pub fn calc_collab_contribution(collab_raw_energy: u64) -> u64 {
+    let raw = (collab_raw_energy as u128).saturating_mul(COLLAB_WEIGHT_BPS as u128)
+        / BPS_DENOMINATOR as u128;
+    saturating_u128_to_u64(raw)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -69,4 +319,99 @@ mod tests {
         let value = calc_penalty_from_delta(i64::MIN);
         assert_eq!(value, u64::MAX);
     }
+
+    #[test]
+    fn test_growth_delta_v2_uses_balance_units() {
+        assert_eq!(calc_growth_delta_v2(99_999, 100), 0);
+        assert_eq!(calc_growth_delta_v2(100_000, 144), 144);
+        assert_eq!(calc_growth_delta_v2(199_999, 144), 144);
+        assert_eq!(calc_growth_delta_v2(200_000, 1_008), 2_016);
+        assert_eq!(calc_growth_delta_v2(100_000_000, 1), 1_000);
+    }
+
+    #[test]
+    fn test_penalty_v2_uses_unit_snapshots() {
+        // Unit boundary cases from UIP-0003.
+        assert_eq!(calc_penalty_v2(199_999, 100_000, 100), 0);
+        assert_eq!(calc_penalty_v2(100_001, 99_999, 100), 150);
+        assert_eq!(calc_penalty_v2(250_000, 150_000, 100), 150);
+        assert_eq!(calc_penalty_v2(99_999, 0, 100), 0);
+        // floor(1 * 3 * 3 / 2)
+        assert_eq!(calc_penalty_v2(100_000, 0, 3), 4);
+    }
+
+    #[test]
+    fn test_active_block_height_after_loss_v2_keeps_proportional_age() {
+        // 4 units aged 100 blocks, 1 unit left: remaining age floor(100 * 1 / 4) = 25.
+        assert_eq!(
+            calc_active_block_height_after_loss_v2(100, 200, 400_000, 100_000),
+            175
+        );
+        // Remaining age rounds down: floor(10 * 2 / 3) = 6.
+        assert_eq!(
+            calc_active_block_height_after_loss_v2(90, 100, 300_000, 200_000),
+            94
+        );
+        assert_eq!(
+            calc_active_block_height_after_loss_v2(100, 200, 400_000, 0),
+            200
+        );
+        // No lost unit keeps the age untouched.
+        assert_eq!(
+            calc_active_block_height_after_loss_v2(100, 200, 199_999, 100_000),
+            100
+        );
+    }
+
+    #[test]
+    fn test_inheritable_energy_v2_discounts_and_rounds_down() {
+        assert_eq!(calc_inheritable_energy_v2(0), 0);
+        assert_eq!(calc_inheritable_energy_v2(19), 18);
+        assert_eq!(calc_inheritable_energy_v2(10_000), 9_500);
+        assert_eq!(EnergyFormulaVersion::V1.calc_inheritable_energy(19), 19);
+    }
+
+    #[test]
+    fn test_schedule_splits_growth_at_activation_height() {
+        let schedule = EnergyFormulaSchedule::new(vec![
+            (0, ENERGY_FORMULA_VERSION_V1.to_string()),
+            (200, ENERGY_FORMULA_VERSION_V2.to_string()),
+        ])
+        .unwrap();
+        assert_eq!(schedule.formula_at(199), EnergyFormulaVersion::V1);
+        assert_eq!(schedule.formula_at(200), EnergyFormulaVersion::V2);
+
+        // Blocks 191..=199 accrue under v1 and blocks 200..=210 under v2.
+        assert_eq!(
+            schedule.calc_incremental_growth(300_000, 100, 190, 210),
+            calc_growth_delta(300_000, 9) + calc_growth_delta_v2(300_000, 11)
+        );
+        assert_eq!(
+            schedule.calc_incremental_growth(300_000, 100, 150, 199),
+            calc_growth_delta(300_000, 49)
+        );
+        assert_eq!(
+            schedule.calc_incremental_growth(300_000, 100, 199, 220),
+            calc_growth_delta_v2(300_000, 21)
+        );
+    }
+
+    #[test]
+    fn test_schedule_requires_formula_from_genesis_and_known_versions() {
+        assert!(EnergyFormulaSchedule::new(vec![]).is_err());
+        assert!(
+            EnergyFormulaSchedule::new(vec![(10, ENERGY_FORMULA_VERSION_V1.to_string())]).is_err()
+        );
+        let err = EnergyFormulaSchedule::new(vec![(0, "energy:v99".to_string())]).unwrap_err();
+        assert!(err.contains("VERSION_NOT_SUPPORTED"));
+    }
+
+    #[test]
+    fn test_collab_contribution_rounds_down_and_handles_u64_max() {
+        assert_eq!(calc_collab_contribution(0), 0);
+        assert_eq!(calc_collab_contribution(1), 0);
+        assert_eq!(calc_collab_contribution(3), 1);
+        assert_eq!(calc_collab_contribution(1_000), 500);
+        assert_eq!(calc_collab_contribution(u64::MAX), u64::MAX / 2);
+    }
 }
@@ -1,16 +1,19 @@
-use super::MintValidationErrorCode;
+use super::collab::PassCollabResolver;
 use super::energy::{PassEnergyManager, PassEnergyManagerRef};
 use super::pass::{
     InvalidPassMintInscriptionInfo, MinerPassManager, MinerPassManagerRef, PassMintInscriptionInfo,
 };
 use super::pass_commit::{PassBlockCommitEntry, PassBlockMutationCollector};
+use super::protocol_version::resolve_active_version_set;
 use super::transfer::{InscriptionTransferTracker, TransferTrackSeed};
+use super::{InscriptionSchemaVersion, MintValidationErrorCode};
 use crate::balance::BalanceMonitor;
 use crate::config::ConfigManagerRef;
 use crate::inscription::{
     BitcoindInscriptionSource, CompareInscriptionSource, FixtureInscriptionSource,
     InscriptionNewItem, InscriptionSource, InscriptionTransferItem, OrdInscriptionSource,
 };
+use crate::service::{PassBlockCommitEvent, SUBSCRIBE_PASS_BLOCK_COMMITS};
 use crate::status::StatusManagerRef;
 use crate::storage::{MinePassStorageSavePointGuard, MinerPassStorage, MinerPassStorageRef};
 use balance_history::{
@@ -20,8 +23,10 @@ use bitcoincore_rpc::bitcoin::{Block, Txid};
 use std::collections::HashMap;
 use std::sync::Arc;
 use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
-use std::time::Instant;
-use usdb_util::{BTCRpcClient, BTCRpcClientRef};
+use std::time::{Duration, Instant};
+use usdb_util::{
+    BTCRpcClient, BTCRpcClientRef, SubscriptionHubRef, ZmqBlockSubscriber, ZmqBlockSubscriberRef,
+};
 
 #[path = "indexer/block_events.rs"]
 mod block_events;
@@ -29,16 +34,23 @@ mod block_events;
 mod traits;
 
 use block_events::{BlockEventExecutor, BlockEventPlanner, BlockProcessEvent};
-use traits::RpcBlockHintProvider;
 pub(crate) use traits::{
     BalanceHistoryCommitApi, BlockHintProvider, IndexStatusApi, TransferTrackerApi,
 };
+use traits::{RpcBlockHintProvider, ZmqBlockHintProvider};
 
 const REORG_RECOVERY_ENERGY_FAILURE_ENV: &str =
     "USDB_INDEXER_INJECT_REORG_RECOVERY_ENERGY_FAILURES";
 const REORG_RECOVERY_TRANSFER_RELOAD_FAILURE_ENV: &str =
     "USDB_INDEXER_INJECT_REORG_RECOVERY_TRANSFER_RELOAD_FAILURES";
 
+// Idle poll interval for the balance-history stable height.
+const NEW_BLOCK_POLL_INTERVAL: Duration = Duration::from_secs(5);
+// After a bitcoind ZMQ notification the block still has to be indexed by balance-history,
+// so poll the upstream snapshot more often for a while instead of waiting a full interval.
+const ZMQ_FAST_POLL_INTERVAL: Duration = Duration::from_millis(200);
+const ZMQ_FAST_POLL_WINDOW: Duration = Duration::from_secs(30);
+
 #[derive(Default)]
 struct ReorgRecoveryFaultInjector {
     // Runtime-only failure budget for the first phase of resumable recovery.
@@ -152,6 +164,12 @@ pub struct InscriptionIndexer {
 
     reorg_recovery_fault_injector: ReorgRecoveryFaultInjector,
 
+    // Optional WebSocket fan-out for durable pass block commits and rollbacks.
+    subscription_hub: Option<SubscriptionHubRef>,
+
+    // Optional bitcoind ZMQ block subscriber that wakes the idle loop early.
+    block_notifier: Option<ZmqBlockSubscriberRef>,
+
     // Shutdown signal
     should_stop: Arc<AtomicBool>,
 }
@@ -205,8 +223,18 @@ impl InscriptionIndexer {
         )?);
         let inscription_source =
             Self::build_inscription_source(config.clone(), btc_client.clone())?;
-        let block_hint_provider: Arc<dyn BlockHintProvider> =
-            Arc::new(RpcBlockHintProvider::new(btc_client.clone()));
+        let block_notifier = config.config().bitcoin.zmq_block_url.as_ref().map(|url| {
+            let notifier = Arc::new(ZmqBlockSubscriber::new(url));
+            notifier.start();
+            notifier
+        });
+        let block_hint_provider: Arc<dyn BlockHintProvider> = match &block_notifier {
+            Some(notifier) => Arc::new(ZmqBlockHintProvider::new(
+                btc_client.clone(),
+                notifier.clone(),
+            )),
+            None => Arc::new(RpcBlockHintProvider::new(btc_client.clone())),
+        };
         let balance_history_client: Arc<dyn BalanceHistoryCommitApi> = Arc::new(
             BalanceHistoryRpcClient::new(&config.config().balance_history.rpc_url)?,
         );
@@ -248,6 +276,8 @@ impl InscriptionIndexer {
             balance_monitor,
             status,
             reorg_recovery_fault_injector,
+            subscription_hub: None,
+            block_notifier,
 
             should_stop: Arc::new(AtomicBool::new(false)),
         };
@@ -280,10 +310,17 @@ impl InscriptionIndexer {
             balance_history_client,
             status,
             reorg_recovery_fault_injector: ReorgRecoveryFaultInjector::default(),
+            subscription_hub: None,
+            block_notifier: None,
             should_stop: Arc::new(AtomicBool::new(false)),
         }
     }
 
+    pub fn with_subscription_hub(mut self, hub: SubscriptionHubRef) -> Self {
+        self.subscription_hub = Some(hub);
+        self
+    }
+
     fn create_inscription_source_by_name(
         source_name: &str,
         config: ConfigManagerRef,
@@ -359,6 +396,10 @@ impl InscriptionIndexer {
         if !prev_value {
             info!("Shutdown signal sent to InscriptionIndexer");
         }
+
+        if let Some(block_notifier) = &self.block_notifier {
+            block_notifier.close();
+        }
     }
 
     pub fn miner_pass_storage(&self) -> &MinerPassStorageRef {
@@ -369,6 +410,14 @@ impl InscriptionIndexer {
         &self.pass_energy_manager
     }
 
+    pub fn pass_collab_resolver(&self) -> PassCollabResolver {
+        PassCollabResolver::new(
+            self.miner_pass_storage.clone(),
+            self.pass_energy_manager.clone(),
+            self.config.config().bitcoin.network(),
+        )
+    }
+
     fn check_shutdown(&self) -> bool {
         self.should_stop.load(Ordering::SeqCst)
     }
@@ -467,7 +516,8 @@ impl InscriptionIndexer {
                 && local_anchor.latest_block_commit == upstream_block_commit
                 && local_anchor.commit_protocol_version
                     == upstream_snapshot.commit_protocol_version
-                && local_anchor.commit_hash_algo == upstream_snapshot.commit_hash_algo,
+                && local_anchor.commit_hash_algo == upstream_snapshot.commit_hash_algo
+                && local_anchor.balance_state_root == upstream_snapshot.balance_state_root,
         )
     }
 
@@ -481,6 +531,8 @@ impl InscriptionIndexer {
             && local_anchor.stable_lag == upstream_state_ref.consensus_identity.stable_lag
             && local_anchor.commit_protocol_version == upstream_state_ref.commit_protocol_version
             && local_anchor.commit_hash_algo == upstream_state_ref.commit_hash_algo
+            && local_anchor.balance_state_root
+                == upstream_state_ref.consensus_identity.balance_state_root
     }
 
     async fn detect_upstream_reorg_target(
@@ -676,6 +728,10 @@ impl InscriptionIndexer {
             error!("{}", msg);
             return Err(msg);
         }
+        self.publish_pass_block_commit_event(&PassBlockCommitEvent::Rollback {
+            from_height: current_height,
+            to_height: rollback_target,
+        });
 
         self.resume_pending_upstream_reorg_recovery(genesis_block_height)
             .await?;
@@ -908,6 +964,12 @@ impl InscriptionIndexer {
 
     async fn wait_for_new_blocks(&self, last_synced_height: u32) -> Result<u32, String> {
         let genesis_block_height = self.config.config().usdb.genesis_block_height;
+        let mut seen_notification = self
+            .block_notifier
+            .as_ref()
+            .map(|notifier| notifier.notification_sequence())
+            .unwrap_or(0);
+        let mut fast_poll_deadline: Option<Instant> = None;
         loop {
             let msg = format!(
                 "Waiting for new blocks... Last synced height: {}",
@@ -948,7 +1010,21 @@ impl InscriptionIndexer {
             }
 
             // Sleep for a while before checking again
-            tokio::time::sleep(std::time::Duration::from_secs(5)).await;
+            match &self.block_notifier {
+                Some(_) if fast_poll_deadline.is_some_and(|deadline| Instant::now() < deadline) => {
+                    tokio::time::sleep(ZMQ_FAST_POLL_INTERVAL).await;
+                }
+                Some(notifier) => {
+                    let sequence = notifier
+                        .wait_for_notification_async(seen_notification, NEW_BLOCK_POLL_INTERVAL)
+                        .await;
+                    if sequence != seen_notification {
+                        seen_notification = sequence;
+                        fast_poll_deadline = Some(Instant::now() + ZMQ_FAST_POLL_WINDOW);
+                    }
+                }
+                None => tokio::time::sleep(NEW_BLOCK_POLL_INTERVAL).await,
+            }
 
             // Check for shutdown signal while waiting
             if self.check_shutdown() {
@@ -1124,7 +1200,7 @@ impl InscriptionIndexer {
             self.status.update_index_status(None, None, Some(msg));
 
             // Any error in sync_block should abort this block and keep previous committed height intact.
-            self.sync_block(height).await?;
+            let (commit_entry, mutation_collector) = self.sync_block(height).await?;
 
             // Persist synced height before committing savepoint so crash-recovery starts from durable progress.
             let update_synced_height_begin = Instant::now();
@@ -1164,6 +1240,12 @@ impl InscriptionIndexer {
             let commit_savepoint_elapsed_ms = commit_savepoint_begin.elapsed().as_millis();
             let sync_single_block_elapsed_ms = sync_single_block_begin.elapsed().as_millis();
 
+            // Subscribers only ever see commits that survived the savepoint.
+            self.publish_pass_block_commit_event(&PassBlockCommitEvent::PassBlockCommit {
+                commit: (&commit_entry).into(),
+                mutations: mutation_collector.mutations().to_vec(),
+            });
+
             current_height = height;
             self.status
                 .update_index_status(Some(current_height), None, None);
@@ -1212,11 +1294,23 @@ impl InscriptionIndexer {
             .has_active_block_mutation_collection()
     }
 
-    async fn sync_block(&self, height: u32) -> Result<(), String> {
+    // Returns the persisted pass block commit and the mutations it commits to.
+    async fn sync_block(
+        &self,
+        height: u32,
+    ) -> Result<(PassBlockCommitEntry, PassBlockMutationCollector), String> {
         info!("Processing inscriptions at block height {}", height);
         let sync_block_begin = Instant::now();
         let mut energy_finalized = false;
 
+        // Versions are resolved per block so an activation height takes effect exactly at
+        // that block, and replays after a reorg re-select them on the new canonical branch.
+        let active_versions = resolve_active_version_set(&self.config, height)?;
+        debug!(
+            "Active version set resolved: module=indexer, block_height={}, energy_formula_version={}, commit_protocol_version={}",
+            height, active_versions.energy_formula_version, active_versions.commit_protocol_version
+        );
+
         // Mark energy sync as pending first so crashes can be detected and repaired on restart.
         self.pass_energy_manager.begin_block_sync(height)?;
         let mutation_collection_guard =
@@ -1233,8 +1327,11 @@ impl InscriptionIndexer {
 
         // Collect mint events and transfer events first, then apply in tx order.
         let process_inscriptions_begin = Instant::now();
+        let inscription_schema = InscriptionSchemaVersion::from_version_value(
+            &active_versions.inscription_schema_version,
+        )?;
         let collected_mints = self
-            .collect_block_inscription_mints(height, Some(block_hint.clone()))
+            .collect_block_inscription_mints(height, Some(block_hint.clone()), inscription_schema)
             .await?;
         let process_inscriptions_elapsed_ms = process_inscriptions_begin.elapsed().as_millis();
 
@@ -1355,15 +1452,18 @@ impl InscriptionIndexer {
                 return Err(msg);
             }
         };
-        if let Err(e) = self
+        let commit_entry = match self
             .persist_pass_block_commit(height, &mutation_collector)
             .await
         {
-            let msg = self
-                .recover_failed_block_sync(height, true, energy_finalized, e)
-                .await;
-            return Err(msg);
-        }
+            Ok(entry) => entry,
+            Err(e) => {
+                let msg = self
+                    .recover_failed_block_sync(height, true, energy_finalized, e)
+                    .await;
+                return Err(msg);
+            }
+        };
         // Commit transfer tracker staged state only after energy metadata finalize succeeds.
         if let Err(e) = self.transfer_tracker.commit_staged_block(height).await {
             let msg = self
@@ -1399,7 +1499,20 @@ impl InscriptionIndexer {
             total_elapsed_ms
         );
 
-        Ok(())
+        Ok((commit_entry, mutation_collector))
+    }
+
+    // Subscription delivery is best effort and must never fail indexing.
+    fn publish_pass_block_commit_event(&self, event: &PassBlockCommitEvent) {
+        let Some(hub) = &self.subscription_hub else {
+            return;
+        };
+        if let Err(e) = hub.publish(SUBSCRIBE_PASS_BLOCK_COMMITS, event) {
+            warn!(
+                "Failed to publish pass block commit event: module=indexer, error={}",
+                e
+            );
+        }
     }
 
     fn merge_block_failure_with_recovery<E: std::fmt::Display>(
@@ -1484,7 +1597,7 @@ impl InscriptionIndexer {
         &self,
         block_height: u32,
         collector: &super::pass_commit::PassBlockMutationCollector,
-    ) -> Result<(), String> {
+    ) -> Result<PassBlockCommitEntry, String> {
         // Pass commit v1 always fetches the upstream anchor at the same local block height.
         // The downstream build_commit_entry path rejects any height mismatch explicitly.
         let upstream_commit = self
@@ -1517,12 +1630,14 @@ impl InscriptionIndexer {
         });
 
         let entry = collector.build_commit_entry(&upstream_commit, prev_local_commit.as_ref())?;
-        self.miner_pass_storage.upsert_pass_block_commit(&entry)
+        self.miner_pass_storage
+            .upsert_pass_block_commit_with_mutations(&entry, collector.mutations())?;
+        Ok(entry)
     }
 
     #[cfg(test)]
     pub(crate) async fn sync_block_for_test(&self, height: u32) -> Result<(), String> {
-        self.sync_block(height).await
+        self.sync_block(height).await.map(|_| ())
     }
 
     fn build_block_tx_position_map(block: &Block) -> HashMap<Txid, usize> {
@@ -1565,10 +1680,11 @@ impl InscriptionIndexer {
         &self,
         block_height: u32,
         block_hint: Option<Arc<Block>>,
+        inscription_schema: InscriptionSchemaVersion,
     ) -> Result<CollectedMintItems, String> {
         let discovered_batch = self
             .inscription_source
-            .load_block_mint_batch(block_height, block_hint)
+            .load_block_mint_batch(block_height, block_hint, inscription_schema)
             .await?;
         if discovered_batch.valid_mints.is_empty() && discovered_batch.invalid_mints.is_empty() {
             info!("No inscriptions found at block height {}", block_height);
@@ -1734,6 +1850,15 @@ impl InscriptionIndexer {
                 error!("{}", msg);
                 msg
             })?,
+            leader_pass_id: mint_content.leader_pass_inscription_id().map_err(|e| {
+                let msg = format!(
+                    "Failed to parse leader_pass_id for inscription {}: {}",
+                    item.inscription_id, e
+                );
+                error!("{}", msg);
+                msg
+            })?,
+            leader_btc_addr: mint_content.leader_btc_addr.clone(),
         };
         self.miner_pass_manager.on_mint_pass(&mint_info).await?;
 
@@ -258,6 +258,8 @@ mod tests {
                 eth_main: "0x1111111111111111111111111111111111111111".to_string(),
                 eth_collab: None,
                 prev: Vec::new(),
+                leader_pass_id: None,
+                leader_btc_addr: None,
             }),
             op: crate::inscription::InscriptionOperation::Inscribe,
             commit_txid: Txid::from_slice(&[owner_tag; 32]).unwrap(),
@@ -14,7 +14,7 @@ use ordinals::SatPoint;
 use std::future::Future;
 use std::pin::Pin;
 use std::sync::Arc;
-use usdb_util::{BTCRpcClientRef, USDBScriptHash};
+use usdb_util::{BTCRpcClientRef, USDBScriptHash, ZmqBlockSubscriberRef};
 
 pub(crate) type TransferTrackerFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
 pub(crate) type BalanceHistoryFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
@@ -40,6 +40,34 @@ impl BlockHintProvider for RpcBlockHintProvider {
     }
 }
 
+// Serves the block pushed over bitcoind ZMQ when it is still canonical at the height,
+// so the tip block is not downloaded a second time through `getblock`.
+pub(super) struct ZmqBlockHintProvider {
+    btc_client: BTCRpcClientRef,
+    block_notifier: ZmqBlockSubscriberRef,
+}
+
+impl ZmqBlockHintProvider {
+    pub(super) fn new(btc_client: BTCRpcClientRef, block_notifier: ZmqBlockSubscriberRef) -> Self {
+        Self {
+            btc_client,
+            block_notifier,
+        }
+    }
+}
+
+impl BlockHintProvider for ZmqBlockHintProvider {
+    fn load_block_hint(&self, block_height: u32) -> Result<Option<Arc<Block>>, String> {
+        let block_hash = self.btc_client.get_block_hash(block_height)?;
+        if let Some(block) = self.block_notifier.get_block(&block_hash) {
+            return Ok(Some(block));
+        }
+
+        let block = self.btc_client.get_block_by_hash(&block_hash)?;
+        Ok(Some(Arc::new(block)))
+    }
+}
+
 pub(crate) trait TransferTrackerApi: Send + Sync {
     fn init<'a>(&'a self) -> TransferTrackerFuture<'a, Result<(), String>>;
 
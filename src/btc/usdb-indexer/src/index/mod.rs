@@ -1,13 +1,19 @@
+mod collab;
 mod content;
 mod energy;
 pub(crate) mod energy_formula;
 mod indexer;
 mod pass;
 mod pass_commit;
+mod protocol_version;
+mod snapshot;
 #[cfg(test)]
 mod test;
 mod transfer;
 
+pub use collab::*;
 pub use content::*;
 pub use indexer::*;
 pub(crate) use pass_commit::*;
+pub(crate) use protocol_version::*;
+pub use snapshot::*;
@@ -24,6 +24,10 @@ pub struct PassMintInscriptionInfo {
     pub eth_main: String,
     pub eth_collab: Option<String>,
     pub prev: Vec<InscriptionId>,
+
+    // UIP-0004 Leader binding, only set on collab passes
+    pub leader_pass_id: Option<InscriptionId>,
+    pub leader_btc_addr: Option<String>,
 }
 
 pub struct InvalidPassMintInscriptionInfo {
@@ -141,6 +145,8 @@ impl MinerPassManager {
             prev: mint_info.prev.clone(),
             invalid_code: None,
             invalid_reason: None,
+            leader_pass_id: mint_info.leader_pass_id.clone(),
+            leader_btc_addr: mint_info.leader_btc_addr.clone(),
 
             state: MinerPassState::Active,
             owner: mint_info.mint_owner.clone(),
@@ -156,6 +162,8 @@ impl MinerPassManager {
             eth_main: info.eth_main.clone(),
             eth_collab: info.eth_collab.clone(),
             prev: info.prev.iter().map(|v| v.to_string()).collect(),
+            leader_pass_id: info.leader_pass_id.as_ref().map(|v| v.to_string()),
+            leader_btc_addr: info.leader_btc_addr.clone(),
         });
 
         info!(
@@ -199,11 +207,15 @@ impl MinerPassManager {
                     continue;
                 }
 
-                // Consume the previous pass and inherit energy
+                // Consume the previous pass and inherit energy.
+                // Each prev is discounted on its own before summing, so rounding stays per prev.
                 let energy = self
                     .consume_pass(prev_inscription_id, mint_info.mint_block_height)
                     .await?;
-                inherited_energy = inherited_energy.saturating_add(energy);
+                let inheritable = self
+                    .energy_manager
+                    .calc_inheritable_energy(energy, mint_info.mint_block_height);
+                inherited_energy = inherited_energy.saturating_add(inheritable);
             } else {
                 warn!(
                     "Previous Miner Pass {} not found for new mint pass {}",
@@ -241,6 +253,8 @@ impl MinerPassManager {
             prev: Vec::new(),
             invalid_code: Some(invalid_info.error_code.clone()),
             invalid_reason: Some(invalid_info.error_reason.clone()),
+            leader_pass_id: None,
+            leader_btc_addr: None,
             owner: invalid_info.mint_owner,
             state: MinerPassState::Invalid,
         };
@@ -619,6 +633,8 @@ mod tests {
             prev: Vec::new(),
             invalid_code: None,
             invalid_reason: None,
+            leader_pass_id: None,
+            leader_btc_addr: None,
             owner,
             state: MinerPassState::Active,
         };
@@ -2,7 +2,8 @@ use balance_history::BlockCommitInfo as BalanceHistoryBlockCommitInfo;
 use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
 
-pub const PASS_COMMIT_PROTOCOL_VERSION: &str = "1.0.0";
+// Commit format v1; selected per height through the UIP-0008 commit_protocol_version family.
+pub const PASS_COMMIT_PROTOCOL_VERSION: &str = usdb_util::COMMIT_PROTOCOL_VERSION_V1;
 pub const PASS_COMMIT_HASH_ALGO: &str = "sha256";
 
 fn encode_hex(bytes: &[u8]) -> String {
@@ -25,6 +26,11 @@ pub enum PassBlockMutation {
         eth_main: String,
         eth_collab: Option<String>,
         prev: Vec<String>,
+        // Omitted for standard passes so pre-UIP-0004 mutation roots stay unchanged.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        leader_pass_id: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        leader_btc_addr: Option<String>,
     },
     InvalidMint {
         inscription_id: String,
@@ -199,6 +205,8 @@ mod tests {
             eth_main: "0x1".to_string(),
             eth_collab: None,
             prev: Vec::new(),
+            leader_pass_id: None,
+            leader_btc_addr: None,
         });
 
         let upstream = BalanceHistoryBlockCommitInfo {
@@ -0,0 +1,105 @@
+use super::pass_commit::PASS_COMMIT_PROTOCOL_VERSION;
+use crate::config::ConfigManager;
+use usdb_util::{
+    ActiveVersionSet, COMMIT_PROTOCOL_VERSION_FAMILY, EFFECTIVE_ENERGY_FORMULA_VERSION_FAMILY,
+    EFFECTIVE_ENERGY_FORMULA_VERSION_V1, ENERGY_FORMULA_VERSION_FAMILY, ENERGY_FORMULA_VERSION_V1,
+    ENERGY_FORMULA_VERSION_V2, INSCRIPTION_SCHEMA_VERSION_FAMILY, INSCRIPTION_SCHEMA_VERSION_V1,
+    INSCRIPTION_SCHEMA_VERSION_V2, LEVEL_FORMULA_VERSION_FAMILY, LEVEL_FORMULA_VERSION_V1,
+    PASS_STATE_MACHINE_VERSION_FAMILY, PASS_STATE_MACHINE_VERSION_V1,
+};
+
+// UIP-0008 lookup on the indexer side. The registry only says which version is active
+// at a height; this build must also implement it. A height whose active version is not
+// implemented here fails closed instead of being interpreted with the nearest version.
+// A family with no active version, such as the level formula before energy formula v2,
+// has nothing to check.
+fn ensure_active_version_set_supported(
+    version_set: &ActiveVersionSet,
+    btc_height: u32,
+) -> Result<(), String> {
+    let checks: [(&str, Option<&str>, &[&str]); 6] = [
+        (
+            INSCRIPTION_SCHEMA_VERSION_FAMILY,
+            Some(version_set.inscription_schema_version.as_str()),
+            &[INSCRIPTION_SCHEMA_VERSION_V1, INSCRIPTION_SCHEMA_VERSION_V2],
+        ),
+        (
+            PASS_STATE_MACHINE_VERSION_FAMILY,
+            Some(version_set.pass_state_machine_version.as_str()),
+            &[PASS_STATE_MACHINE_VERSION_V1],
+        ),
+        (
+            ENERGY_FORMULA_VERSION_FAMILY,
+            Some(version_set.energy_formula_version.as_str()),
+            &[ENERGY_FORMULA_VERSION_V1, ENERGY_FORMULA_VERSION_V2],
+        ),
+        (
+            EFFECTIVE_ENERGY_FORMULA_VERSION_FAMILY,
+            Some(version_set.effective_energy_formula_version.as_str()),
+            &[EFFECTIVE_ENERGY_FORMULA_VERSION_V1],
+        ),
+        (
+            LEVEL_FORMULA_VERSION_FAMILY,
+            version_set.level_formula_version.as_deref(),
+            &[LEVEL_FORMULA_VERSION_V1],
+        ),
+        (
+            COMMIT_PROTOCOL_VERSION_FAMILY,
+            Some(version_set.commit_protocol_version.as_str()),
+            &[PASS_COMMIT_PROTOCOL_VERSION],
+        ),
+    ];
+
+    for (family, value, supported) in checks {
+        let Some(value) = value else {
+            continue;
+        };
+        if !supported.contains(&value) {
+            let msg = format!(
+                "VERSION_NOT_SUPPORTED: family={}, version={}, btc_height={}, supported={:?}",
+                family, value, btc_height, supported
+            );
+            error!("{}", msg);
+            return Err(msg);
+        }
+    }
+
+    Ok(())
+}
+
+// Resolve the active version set at btc_height and make sure this build implements it.
+pub(crate) fn resolve_active_version_set(
+    config: &ConfigManager,
+    btc_height: u32,
+) -> Result<ActiveVersionSet, String> {
+    let version_set = config.active_version_set_at(btc_height)?;
+    ensure_active_version_set_supported(&version_set, btc_height)?;
+    Ok(version_set)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builtin_v1_version_set_is_supported() {
+        let registry = usdb_util::ActivationRegistry::builtin().unwrap();
+        let version_set = registry
+            .lookup_btc_active_version_set("btc-regtest", 0)
+            .unwrap();
+        ensure_active_version_set_supported(&version_set, 0).unwrap();
+    }
+
+    #[test]
+    fn test_unknown_version_fails_closed() {
+        let registry = usdb_util::ActivationRegistry::builtin().unwrap();
+        let mut version_set = registry
+            .lookup_btc_active_version_set("btc-regtest", 0)
+            .unwrap();
+        version_set.energy_formula_version = "uip-0003-pass-energy-formula:v99".to_string();
+
+        let err = ensure_active_version_set_supported(&version_set, 10).unwrap_err();
+        assert!(err.contains("VERSION_NOT_SUPPORTED"));
+        assert!(err.contains(ENERGY_FORMULA_VERSION_FAMILY));
+    }
+}
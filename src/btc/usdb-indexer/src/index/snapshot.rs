@@ -0,0 +1,926 @@
+use crate::config::{ConfigManager, ConfigManagerRef};
+use crate::service::rpc::{
+    IndexerSnapshotInfo, IndexerSnapshotInfoSeed, LocalStateCommitInfo, LocalStateCommitInfoSeed,
+};
+use crate::storage::{MinerPassStorage, PassEnergyStorage, StateSnapshotDB, StateSnapshotMeta};
+use balance_history::{
+    SNAPSHOT_SIGNATURE_SCHEME_ED25519, SnapshotHash, SnapshotSigningKeyFile, SnapshotTrustMode,
+    SnapshotTrustedKeySet, load_signature_set_file, manifest_path_for_snapshot_file,
+    save_signature_file, signature_path_for_manifest_file,
+};
+use ed25519_dalek::Signer;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+use usdb_util::{LocalStateActiveBalanceSnapshot, LocalStatePassCommitIdentity};
+
+/// Version tag of the usdb-indexer state snapshot manifest sidecar format.
+pub const STATE_SNAPSHOT_MANIFEST_VERSION: &str = "usdb-indexer-snapshot-manifest:v1";
+
+pub fn state_snapshot_file_name(block_height: u32) -> String {
+    format!("usdb_indexer_snapshot_{}.db", block_height)
+}
+
+// Rebuild the upstream snapshot anchor and local state commit of the current durable state,
+// following the same rules as get_snapshot_info / get_local_state_commit_info.
+pub fn build_current_state_commit(
+    config: &ConfigManager,
+    storage: &MinerPassStorage,
+) -> Result<(IndexerSnapshotInfo, LocalStateCommitInfo), String> {
+    let anchor = storage
+        .get_balance_history_snapshot_anchor()?
+        .ok_or_else(|| {
+            let msg = "No balance-history snapshot anchor has been adopted yet".to_string();
+            error!("{}", msg);
+            msg
+        })?;
+    let synced_height = storage
+        .get_synced_btc_block_height()?
+        .unwrap_or(anchor.stable_height);
+
+    let snapshot_info = IndexerSnapshotInfo::from(IndexerSnapshotInfoSeed {
+        network: config.config().bitcoin.network().to_string(),
+        local_synced_block_height: synced_height,
+        balance_history_stable_height: anchor.stable_height,
+        stable_block_hash: anchor.stable_block_hash,
+        latest_block_commit: anchor.latest_block_commit,
+        stable_lag: anchor.stable_lag,
+        commit_protocol_version: anchor.commit_protocol_version,
+        commit_hash_algo: anchor.commit_hash_algo,
+        balance_state_root: anchor.balance_state_root,
+    });
+
+    let latest_pass_block_commit = storage
+        .get_latest_pass_block_commit_at_or_before(synced_height)?
+        .map(|entry| LocalStatePassCommitIdentity {
+            block_height: entry.block_height,
+            block_commit: entry.block_commit,
+            commit_protocol_version: entry.commit_protocol_version,
+            commit_hash_algo: entry.commit_hash_algo,
+        });
+
+    let genesis_block_height = config.config().usdb.genesis_block_height;
+    let latest_active_balance_snapshot = if synced_height < genesis_block_height {
+        None
+    } else {
+        storage.assert_balance_snapshot_consistency(synced_height, genesis_block_height)?;
+        let snapshot = storage
+            .get_active_balance_snapshot(synced_height)?
+            .ok_or_else(|| {
+                let msg = format!(
+                    "Missing active balance snapshot at height {} while building local state commit",
+                    synced_height
+                );
+                error!("{}", msg);
+                msg
+            })?;
+        Some(LocalStateActiveBalanceSnapshot {
+            block_height: snapshot.block_height,
+            total_balance: snapshot.total_balance,
+            active_address_count: snapshot.active_address_count,
+        })
+    };
+
+    let local_state_commit_info = LocalStateCommitInfo::from(LocalStateCommitInfoSeed {
+        local_synced_block_height: synced_height,
+        upstream_snapshot_id: snapshot_info.snapshot_id.clone(),
+        latest_pass_block_commit,
+        latest_active_balance_snapshot,
+    });
+
+    Ok((snapshot_info, local_state_commit_info))
+}
+
+#[derive(Clone, Debug)]
+pub struct StateSnapshotData {
+    /// Path to the snapshot DB file to be installed.
+    pub file: PathBuf,
+
+    /// Optional sidecar manifest file describing the expected installed state.
+    pub manifest_file: Option<PathBuf>,
+}
+
+/// Files written by one successful `StateSnapshotCreator::run`.
+#[derive(Clone, Debug)]
+pub struct CreatedStateSnapshot {
+    pub file: PathBuf,
+    pub manifest_file: PathBuf,
+    pub signature_file: Option<PathBuf>,
+    pub meta: StateSnapshotMeta,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct StateSnapshotManifest {
+    /// Version tag of the external sidecar manifest schema.
+    pub manifest_version: String,
+    /// Snapshot DB file basename expected by this manifest.
+    pub file_name: String,
+    /// Canonical SHA256 of the snapshot DB file.
+    pub file_sha256: String,
+    /// Local synced block height captured by the snapshot.
+    pub block_height: u32,
+    /// Balance-history snapshot anchor adopted by the snapshotted state.
+    pub snapshot_info: IndexerSnapshotInfo,
+    /// Local state commit expected after installation.
+    pub local_state_commit_info: LocalStateCommitInfo,
+    /// Detached signature scheme used for the optional sidecar signature file.
+    #[serde(default)]
+    pub signature_scheme: Option<String>,
+    /// Logical signer identifier that must match a trusted install key.
+    #[serde(default)]
+    pub signing_key_id: Option<String>,
+    /// Unix timestamp when this manifest was generated.
+    #[serde(default)]
+    pub generated_at: Option<u64>,
+}
+
+impl StateSnapshotManifest {
+    pub fn build(
+        file_name: String,
+        file_sha256: String,
+        snapshot_info: IndexerSnapshotInfo,
+        local_state_commit_info: LocalStateCommitInfo,
+        signing_key_id: Option<String>,
+    ) -> Self {
+        Self {
+            manifest_version: STATE_SNAPSHOT_MANIFEST_VERSION.to_string(),
+            file_name,
+            file_sha256,
+            block_height: local_state_commit_info.local_synced_block_height,
+            snapshot_info,
+            local_state_commit_info,
+            signature_scheme: signing_key_id
+                .as_ref()
+                .map(|_| SNAPSHOT_SIGNATURE_SCHEME_ED25519.to_string()),
+            signing_key_id,
+            generated_at: Some(
+                SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .unwrap_or_default()
+                    .as_secs(),
+            ),
+        }
+    }
+
+    /// Returns the canonical JSON bytes covered by the detached signature.
+    pub fn canonical_bytes(&self) -> Result<Vec<u8>, String> {
+        serde_json::to_vec(self).map_err(|e| {
+            let msg = format!(
+                "Failed to serialize canonical state snapshot manifest {} for signing: {}",
+                self.file_name, e
+            );
+            error!("{}", msg);
+            msg
+        })
+    }
+
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let data = std::fs::read_to_string(path).map_err(|e| {
+            let msg = format!(
+                "Failed to read state snapshot manifest {}: {}",
+                path.display(),
+                e
+            );
+            error!("{}", msg);
+            msg
+        })?;
+        let manifest: StateSnapshotManifest = serde_json::from_str(&data).map_err(|e| {
+            let msg = format!(
+                "Failed to parse state snapshot manifest {} as JSON: {}",
+                path.display(),
+                e
+            );
+            error!("{}", msg);
+            msg
+        })?;
+        if manifest.manifest_version != STATE_SNAPSHOT_MANIFEST_VERSION {
+            let msg = format!(
+                "Unsupported state snapshot manifest version {} in {} (expected {})",
+                manifest.manifest_version,
+                path.display(),
+                STATE_SNAPSHOT_MANIFEST_VERSION
+            );
+            error!("{}", msg);
+            return Err(msg);
+        }
+        Ok(manifest)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        let data = serde_json::to_string_pretty(self).map_err(|e| {
+            let msg = format!(
+                "Failed to serialize state snapshot manifest {}: {}",
+                path.display(),
+                e
+            );
+            error!("{}", msg);
+            msg
+        })?;
+        std::fs::write(path, data).map_err(|e| {
+            let msg = format!(
+                "Failed to write state snapshot manifest {}: {}",
+                path.display(),
+                e
+            );
+            error!("{}", msg);
+            msg
+        })
+    }
+}
+
+pub struct StateSnapshotCreator {
+    config: ConfigManagerRef,
+}
+
+impl StateSnapshotCreator {
+    pub fn new(config: ConfigManagerRef) -> Self {
+        Self { config }
+    }
+
+    // Snapshot the durable state at the local synced height. miner_passes only holds the
+    // current pass view, so historical heights cannot be exported and target_block_height
+    // must match the synced height when given.
+    pub fn run(&self, target_block_height: Option<u32>) -> Result<CreatedStateSnapshot, String> {
+        let data_dir = self.config.data_dir();
+        let pass_storage = MinerPassStorage::new(&data_dir)?;
+        let energy_storage = PassEnergyStorage::new(&data_dir)?;
+
+        let synced_height = pass_storage.get_synced_btc_block_height()?.ok_or_else(|| {
+            let msg = "Cannot create state snapshot before any block has been synced".to_string();
+            error!("{}", msg);
+            msg
+        })?;
+        if let Some(target_block_height) = target_block_height {
+            if target_block_height != synced_height {
+                let msg = format!(
+                    "Historical state snapshots are not supported: target block height {} must match local synced height {}",
+                    target_block_height, synced_height
+                );
+                error!("{}", msg);
+                return Err(msg);
+            }
+        }
+
+        let energy_synced_height = energy_storage.get_synced_block_height()?;
+        let energy_pending_height = energy_storage.get_pending_block_height()?;
+        if energy_pending_height.is_some()
+            || energy_synced_height.is_some_and(|height| height != synced_height)
+        {
+            let msg = format!(
+                "Pass energy store is not settled at local synced height {}: energy_synced_height={:?}, energy_pending_height={:?}",
+                synced_height, energy_synced_height, energy_pending_height
+            );
+            error!("{}", msg);
+            return Err(msg);
+        }
+
+        let (snapshot_info, local_state_commit_info) =
+            build_current_state_commit(&self.config, &pass_storage)?;
+
+        let snapshot_dir = self.config.snapshot_dir();
+        std::fs::create_dir_all(&snapshot_dir).map_err(|e| {
+            let msg = format!(
+                "Failed to create snapshot directory {}: {}",
+                snapshot_dir.display(),
+                e
+            );
+            error!("{}", msg);
+            msg
+        })?;
+        let file_name = state_snapshot_file_name(synced_height);
+        let db_path = snapshot_dir.join(&file_name);
+        let meta =
+            StateSnapshotDB::create(&db_path, &pass_storage, &energy_storage, synced_height)?;
+        info!(
+            "State snapshot database created: file={}, meta={:?}",
+            db_path.display(),
+            meta
+        );
+
+        let file_hash = SnapshotHash::calc_hash(&db_path)?;
+        let signing_key = self
+            .config
+            .snapshot_signing_key_path()
+            .map(|path| SnapshotSigningKeyFile::load(&path))
+            .transpose()?;
+        let manifest = StateSnapshotManifest::build(
+            file_name,
+            file_hash,
+            snapshot_info,
+            local_state_commit_info,
+            signing_key.as_ref().map(|key| key.key_id.clone()),
+        );
+        let manifest_path = manifest_path_for_snapshot_file(&db_path);
+        manifest.save(&manifest_path)?;
+
+        let signature_path = match signing_key {
+            Some(signing_key) => {
+                let signature_path = signature_path_for_manifest_file(&manifest_path);
+                let signature = signing_key
+                    .to_signing_key()?
+                    .sign(&manifest.canonical_bytes()?);
+                save_signature_file(&signature_path, &signature)?;
+                Some(signature_path)
+            }
+            None => None,
+        };
+
+        Ok(CreatedStateSnapshot {
+            file: db_path,
+            manifest_file: manifest_path,
+            signature_file: signature_path,
+            meta,
+        })
+    }
+}
+
+pub struct StateSnapshotInstaller {
+    config: ConfigManagerRef,
+}
+
+impl StateSnapshotInstaller {
+    pub fn new(config: ConfigManagerRef) -> Self {
+        Self { config }
+    }
+
+    pub fn install(&self, data: StateSnapshotData) -> Result<StateSnapshotMeta, String> {
+        info!("Starting state snapshot installation from {:?}", data);
+
+        let trust_mode = self.config.config().snapshot.trust_mode.clone();
+        let manifest = data
+            .manifest_file
+            .as_ref()
+            .map(|path| StateSnapshotManifest::load(path))
+            .transpose()?;
+
+        match manifest.as_ref() {
+            Some(manifest) => {
+                if matches!(trust_mode, SnapshotTrustMode::Signed) {
+                    self.verify_manifest_signature(&data, manifest)?;
+                }
+                self.verify_snapshot_file(&data, manifest)?;
+            }
+            None => {
+                if !matches!(trust_mode, SnapshotTrustMode::Dev) {
+                    let msg = format!(
+                        "State snapshot install requires a manifest in {:?} trust mode, but none was provided for {}",
+                        trust_mode,
+                        data.file.display()
+                    );
+                    error!("{}", msg);
+                    return Err(msg);
+                }
+                warn!(
+                    "Installing state snapshot {} without manifest verification",
+                    data.file.display()
+                );
+            }
+        }
+
+        let snapshot_db = StateSnapshotDB::open(&data.file)?;
+        let meta = snapshot_db.get_meta()?;
+        if let Some(manifest) = manifest.as_ref() {
+            if manifest.block_height != meta.block_height {
+                let msg = format!(
+                    "State snapshot manifest block height mismatch: manifest expects {}, snapshot meta reports {}",
+                    manifest.block_height, meta.block_height
+                );
+                error!("{}", msg);
+                return Err(msg);
+            }
+        }
+
+        // Install into a sibling staging directory first, then switch the live data directory.
+        let live_data_dir = self.config.data_dir();
+        let staging_dir = Self::sibling_dir(&live_data_dir, "snapshot_install_staging")?;
+        std::fs::create_dir_all(&staging_dir).map_err(|e| {
+            let msg = format!(
+                "Failed to create staging directory {}: {}",
+                staging_dir.display(),
+                e
+            );
+            error!("{}", msg);
+            msg
+        })?;
+
+        let ret = snapshot_db
+            .install_into(&staging_dir)
+            .and_then(|_| self.validate_staged_state(&staging_dir, &meta, manifest.as_ref()));
+        if let Err(e) = ret {
+            let _ = std::fs::remove_dir_all(&staging_dir);
+            return Err(e);
+        }
+
+        self.swap_staging_dir_into_place(&live_data_dir, &staging_dir)?;
+        info!(
+            "Completed state snapshot installation at block height {}",
+            meta.block_height
+        );
+
+        Ok(meta)
+    }
+
+    fn verify_manifest_signature(
+        &self,
+        data: &StateSnapshotData,
+        manifest: &StateSnapshotManifest,
+    ) -> Result<(), String> {
+        let signature_scheme = manifest.signature_scheme.as_deref().ok_or_else(|| {
+            let msg = format!(
+                "Signed state snapshot install requires manifest.signature_scheme for {}",
+                data.file.display()
+            );
+            error!("{}", msg);
+            msg
+        })?;
+        if signature_scheme != SNAPSHOT_SIGNATURE_SCHEME_ED25519 {
+            let msg = format!(
+                "Unsupported state snapshot signature scheme {} for {} (expected {})",
+                signature_scheme,
+                data.file.display(),
+                SNAPSHOT_SIGNATURE_SCHEME_ED25519
+            );
+            error!("{}", msg);
+            return Err(msg);
+        }
+        // The manifest path is always present here because a manifest was loaded from it.
+        let manifest_file = data.manifest_file.as_ref().unwrap();
+        let signature_path = signature_path_for_manifest_file(manifest_file);
+        if !signature_path.exists() {
+            let msg = format!(
+                "Signed state snapshot install requires signature sidecar {}, but it does not exist",
+                signature_path.display()
+            );
+            error!("{}", msg);
+            return Err(msg);
+        }
+
+        let trusted_keys_path = self.config.snapshot_trusted_keys_path().ok_or_else(|| {
+            let msg = format!(
+                "Signed state snapshot install requires snapshot.trusted_keys_file in config for {}",
+                data.file.display()
+            );
+            error!("{}", msg);
+            msg
+        })?;
+        let trusted_keys = SnapshotTrustedKeySet::load(&trusted_keys_path)?;
+        let signatures =
+            load_signature_set_file(&signature_path, manifest.signing_key_id.as_deref())?;
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        let report = trusted_keys.verify_signatures(
+            &manifest.canonical_bytes()?,
+            manifest.block_height,
+            manifest.generated_at,
+            now,
+            &signatures,
+        );
+        for signer in &report.rejected_signers {
+            warn!(
+                "State snapshot signer {} rejected for {}: {}",
+                signer.key_id,
+                manifest_file.display(),
+                signer.reason
+            );
+        }
+        report.ensure_satisfied(&format!(
+            "state snapshot manifest {}",
+            manifest_file.display()
+        ))?;
+        info!(
+            "State snapshot manifest {} accepted with {}/{} trusted signers: {}",
+            manifest_file.display(),
+            report.accepted_signers.len(),
+            report.threshold,
+            report.accepted_signers.join(", ")
+        );
+        Ok(())
+    }
+
+    fn verify_snapshot_file(
+        &self,
+        data: &StateSnapshotData,
+        manifest: &StateSnapshotManifest,
+    ) -> Result<(), String> {
+        let file_name = data
+            .file
+            .file_name()
+            .and_then(|value| value.to_str())
+            .ok_or_else(|| {
+                let msg = format!(
+                    "Failed to resolve state snapshot file name from path {}",
+                    data.file.display()
+                );
+                error!("{}", msg);
+                msg
+            })?;
+        if manifest.file_name != file_name {
+            let msg = format!(
+                "State snapshot manifest file_name mismatch: manifest expects {}, actual file is {}",
+                manifest.file_name, file_name
+            );
+            error!("{}", msg);
+            return Err(msg);
+        }
+
+        let file_hash = SnapshotHash::calc_hash(&data.file)?;
+        if !file_hash.eq_ignore_ascii_case(&manifest.file_sha256) {
+            let msg = format!(
+                "State snapshot file hash mismatch: expected {}, got {}",
+                manifest.file_sha256, file_hash
+            );
+            error!("{}", msg);
+            return Err(msg);
+        }
+
+        Ok(())
+    }
+
+    // Recompute the local state commit from the staged stores before they replace live data.
+    fn validate_staged_state(
+        &self,
+        staging_dir: &Path,
+        meta: &StateSnapshotMeta,
+        manifest: Option<&StateSnapshotManifest>,
+    ) -> Result<(), String> {
+        let staged_storage = MinerPassStorage::new(staging_dir)?;
+        let staged_height = staged_storage.get_synced_btc_block_height()?;
+        if staged_height != Some(meta.block_height) {
+            let msg = format!(
+                "Staged state snapshot synced height mismatch: expected {}, got {:?}",
+                meta.block_height, staged_height
+            );
+            error!("{}", msg);
+            return Err(msg);
+        }
+
+        let Some(manifest) = manifest else {
+            return Ok(());
+        };
+        let (snapshot_info, local_state_commit_info) =
+            build_current_state_commit(&self.config, &staged_storage)?;
+        if snapshot_info.snapshot_id != manifest.snapshot_info.snapshot_id {
+            let msg = format!(
+                "Staged state snapshot upstream snapshot_id mismatch at height {}: expected {}, got {}",
+                meta.block_height, manifest.snapshot_info.snapshot_id, snapshot_info.snapshot_id
+            );
+            error!("{}", msg);
+            return Err(msg);
+        }
+        if local_state_commit_info.local_state_commit
+            != manifest.local_state_commit_info.local_state_commit
+        {
+            let msg = format!(
+                "Staged state snapshot local_state_commit mismatch at height {}: expected {}, got {}",
+                meta.block_height,
+                manifest.local_state_commit_info.local_state_commit,
+                local_state_commit_info.local_state_commit
+            );
+            error!("{}", msg);
+            return Err(msg);
+        }
+
+        info!(
+            "Staged state snapshot matches manifest: block_height={}, local_state_commit={}",
+            meta.block_height, local_state_commit_info.local_state_commit
+        );
+        Ok(())
+    }
+
+    fn sibling_dir(dir: &Path, prefix: &str) -> Result<PathBuf, String> {
+        let parent = dir.parent().ok_or_else(|| {
+            let msg = format!("Data directory {} has no parent", dir.display());
+            error!("{}", msg);
+            msg
+        })?;
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_nanos();
+        Ok(parent.join(format!("{}_{}", prefix, nanos)))
+    }
+
+    fn swap_staging_dir_into_place(
+        &self,
+        live_data_dir: &Path,
+        staging_dir: &Path,
+    ) -> Result<(), String> {
+        let backup_dir = Self::sibling_dir(live_data_dir, "data_backup_snapshot_install")?;
+        std::fs::rename(live_data_dir, &backup_dir).map_err(|e| {
+            let msg = format!(
+                "Failed to move live data directory {} to backup {}: {}",
+                live_data_dir.display(),
+                backup_dir.display(),
+                e
+            );
+            error!("{}", msg);
+            msg
+        })?;
+
+        if let Err(e) = std::fs::rename(staging_dir, live_data_dir) {
+            let _ = std::fs::rename(&backup_dir, live_data_dir);
+            let msg = format!(
+                "Failed to promote staged data directory {} to live {}: {}",
+                staging_dir.display(),
+                live_data_dir.display(),
+                e
+            );
+            error!("{}", msg);
+            return Err(msg);
+        }
+
+        info!(
+            "Preserved previous live data directory after state snapshot install: {}",
+            backup_dir.display()
+        );
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::IndexerConfig;
+    use crate::index::{MinerPassState, PassBlockCommitEntry};
+    use crate::storage::PassEnergyRecord;
+    use bitcoincore_rpc::bitcoin::hashes::Hash;
+    use bitcoincore_rpc::bitcoin::{ScriptBuf, Txid};
+    use ord::InscriptionId;
+    use std::sync::Arc;
+    use usdb_util::ToUSDBScriptHash;
+
+    fn test_root_dir(tag: &str) -> PathBuf {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_nanos();
+        let dir = std::env::temp_dir().join(format!("usdb_state_snapshot_{tag}_{nanos}"));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn load_config(root_dir: &Path, config_file: &IndexerConfig) -> ConfigManagerRef {
+        std::fs::write(
+            root_dir.join("config.json"),
+            serde_json::to_vec_pretty(config_file).unwrap(),
+        )
+        .unwrap();
+        Arc::new(ConfigManager::load(Some(root_dir.to_path_buf())).unwrap())
+    }
+
+    fn base_config() -> IndexerConfig {
+        let mut config_file = IndexerConfig::default();
+        config_file.usdb.genesis_block_height = 100;
+        config_file
+    }
+
+    fn energy_record(block_height: u32, energy: u64) -> PassEnergyRecord {
+        PassEnergyRecord {
+            inscription_id: InscriptionId {
+                txid: Txid::from_slice(&[9u8; 32]).unwrap(),
+                index: 0,
+            },
+            block_height,
+            state: MinerPassState::Active,
+            active_block_height: 100,
+            owner_address: ScriptBuf::from(vec![1u8; 32]).to_usdb_script_hash(),
+            owner_balance: 50_000,
+            owner_delta: 0,
+            energy,
+        }
+    }
+
+    // Populate a synced state at height 120 anchored to one balance-history snapshot.
+    fn seed_state(config: &ConfigManager) {
+        let data_dir = config.data_dir();
+        let storage = MinerPassStorage::new(&data_dir).unwrap();
+        storage
+            .upsert_balance_history_snapshot_anchor(&balance_history::SnapshotInfo {
+                stable_height: 120,
+                stable_block_hash: Some("aa".repeat(32)),
+                latest_block_commit: Some("bb".repeat(32)),
+                stable_lag: balance_history::BALANCE_HISTORY_STABLE_LAG,
+                balance_history_api_version: balance_history::BALANCE_HISTORY_API_VERSION
+                    .to_string(),
+                balance_history_semantics_version:
+                    balance_history::BALANCE_HISTORY_SEMANTICS_VERSION.to_string(),
+                commit_protocol_version: "1.0.0".to_string(),
+                commit_hash_algo: "sha256".to_string(),
+                balance_state_root: None,
+            })
+            .unwrap();
+        storage
+            .upsert_pass_block_commit(&PassBlockCommitEntry {
+                block_height: 110,
+                balance_history_block_height: 110,
+                balance_history_block_commit: "cc".repeat(32),
+                mutation_root: "dd".repeat(32),
+                block_commit: "ee".repeat(32),
+                commit_protocol_version: "1.0.0".to_string(),
+                commit_hash_algo: "sha256".to_string(),
+            })
+            .unwrap();
+        storage
+            .upsert_active_balance_snapshot(120, 5_000, 2)
+            .unwrap();
+        storage.update_synced_btc_block_height(120).unwrap();
+
+        let energy_storage = PassEnergyStorage::new(&data_dir).unwrap();
+        energy_storage
+            .insert_pass_energy_record(&energy_record(110, 700))
+            .unwrap();
+        energy_storage
+            .insert_pass_energy_record(&energy_record(120, 900))
+            .unwrap();
+        energy_storage.finalize_block_sync(120).unwrap();
+    }
+
+    fn copy_snapshot_files(created: &CreatedStateSnapshot, target_root: &Path) -> PathBuf {
+        let snapshot_dir = target_root.join("snapshots");
+        std::fs::create_dir_all(&snapshot_dir).unwrap();
+        let mut sources = vec![created.file.clone(), created.manifest_file.clone()];
+        sources.extend(created.signature_file.clone());
+        for source in sources {
+            std::fs::copy(&source, snapshot_dir.join(source.file_name().unwrap())).unwrap();
+        }
+        snapshot_dir.join(created.file.file_name().unwrap())
+    }
+
+    #[test]
+    fn test_state_snapshot_round_trip_restores_local_state_commit() {
+        let source_root = test_root_dir("round_trip_source");
+        let source_config = load_config(&source_root, &base_config());
+        seed_state(&source_config);
+
+        let created = StateSnapshotCreator::new(source_config.clone())
+            .run(Some(120))
+            .unwrap();
+        assert_eq!(created.meta.block_height, 120);
+        assert_eq!(created.meta.pass_block_commit_count, 1);
+        assert_eq!(created.meta.pass_energy_record_count, 2);
+        assert!(created.signature_file.is_none());
+        let manifest = StateSnapshotManifest::load(&created.manifest_file).unwrap();
+        let (_, source_local_state) = build_current_state_commit(
+            &source_config,
+            &MinerPassStorage::new(&source_config.data_dir()).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(
+            manifest.local_state_commit_info.local_state_commit,
+            source_local_state.local_state_commit
+        );
+
+        let target_root = test_root_dir("round_trip_target");
+        let mut target_config_file = base_config();
+        target_config_file.snapshot.trust_mode = SnapshotTrustMode::Manifest;
+        let target_config = load_config(&target_root, &target_config_file);
+        let file = copy_snapshot_files(&created, &target_root);
+        let meta = StateSnapshotInstaller::new(target_config.clone())
+            .install(StateSnapshotData {
+                file: file.clone(),
+                manifest_file: Some(manifest_path_for_snapshot_file(&file)),
+            })
+            .unwrap();
+        assert_eq!(meta, created.meta);
+
+        let data_dir = target_config.data_dir();
+        let storage = MinerPassStorage::new(&data_dir).unwrap();
+        let (snapshot_info, local_state) =
+            build_current_state_commit(&target_config, &storage).unwrap();
+        assert_eq!(
+            snapshot_info.snapshot_id,
+            manifest.snapshot_info.snapshot_id
+        );
+        assert_eq!(
+            local_state.local_state_commit,
+            manifest.local_state_commit_info.local_state_commit
+        );
+        let energy_storage = PassEnergyStorage::new(&data_dir).unwrap();
+        assert_eq!(energy_storage.get_synced_block_height().unwrap(), Some(120));
+        let record = energy_storage
+            .get_pass_energy_record(&energy_record(120, 0).inscription_id, 120)
+            .unwrap()
+            .unwrap();
+        assert_eq!(record.energy, 900);
+
+        drop(storage);
+        drop(energy_storage);
+        std::fs::remove_dir_all(source_root).unwrap();
+        std::fs::remove_dir_all(target_root).unwrap();
+    }
+
+    #[test]
+    fn test_state_snapshot_install_rejects_manifest_mismatch_before_swap() {
+        let source_root = test_root_dir("mismatch_source");
+        let source_config = load_config(&source_root, &base_config());
+        seed_state(&source_config);
+        let created = StateSnapshotCreator::new(source_config).run(None).unwrap();
+
+        let target_root = test_root_dir("mismatch_target");
+        let target_config = load_config(&target_root, &base_config());
+        let file = copy_snapshot_files(&created, &target_root);
+        let manifest_file = manifest_path_for_snapshot_file(&file);
+        let mut manifest = StateSnapshotManifest::load(&manifest_file).unwrap();
+        manifest.local_state_commit_info.local_state_commit = "00".repeat(32);
+        manifest.save(&manifest_file).unwrap();
+
+        let live_marker = target_config.data_dir().join("live.marker");
+        std::fs::write(&live_marker, b"live").unwrap();
+        let err = StateSnapshotInstaller::new(target_config.clone())
+            .install(StateSnapshotData {
+                file,
+                manifest_file: Some(manifest_file),
+            })
+            .unwrap_err();
+        assert!(err.contains("local_state_commit mismatch"), "{}", err);
+        assert!(live_marker.exists());
+
+        std::fs::remove_dir_all(source_root).unwrap();
+        std::fs::remove_dir_all(target_root).unwrap();
+    }
+
+    #[test]
+    fn test_state_snapshot_signed_mode_requires_trusted_signature() {
+        let key_dir = test_root_dir("signed_keys");
+        let keys =
+            balance_history::tool::generate_snapshot_key_files(&key_dir, "test-signer", false)
+                .unwrap();
+
+        let source_root = test_root_dir("signed_source");
+        let mut source_config_file = base_config();
+        source_config_file.snapshot.signing_key_file = Some(keys.signing_key_file.clone());
+        let source_config = load_config(&source_root, &source_config_file);
+        seed_state(&source_config);
+        let created = StateSnapshotCreator::new(source_config).run(None).unwrap();
+        assert!(created.signature_file.is_some());
+
+        let target_root = test_root_dir("signed_target");
+        let mut target_config_file = base_config();
+        target_config_file.snapshot.trust_mode = SnapshotTrustMode::Signed;
+        target_config_file.snapshot.trusted_keys_file = Some(keys.trusted_keys_file.clone());
+        let target_config = load_config(&target_root, &target_config_file);
+        let file = copy_snapshot_files(&created, &target_root);
+        let manifest_file = manifest_path_for_snapshot_file(&file);
+
+        // A missing sidecar must fail in signed mode, then succeed once it is restored.
+        let signature_file = signature_path_for_manifest_file(&manifest_file);
+        let signature = std::fs::read(&signature_file).unwrap();
+        std::fs::remove_file(&signature_file).unwrap();
+        let err = StateSnapshotInstaller::new(target_config.clone())
+            .install(StateSnapshotData {
+                file: file.clone(),
+                manifest_file: Some(manifest_file.clone()),
+            })
+            .unwrap_err();
+        assert!(err.contains("signature sidecar"), "{}", err);
+
+        std::fs::write(&signature_file, &signature).unwrap();
+
+        // A manifest re-signed with a backdated generated_at is still refused once the
+        // installer's clock is past the key's validity window.
+        let manifest_bytes = std::fs::read(&manifest_file).unwrap();
+        let trusted_keys_bytes = std::fs::read(&keys.trusted_keys_file).unwrap();
+        let mut backdated = StateSnapshotManifest::load(&manifest_file).unwrap();
+        backdated.generated_at = Some(1_000);
+        backdated.save(&manifest_file).unwrap();
+        let signing_key = SnapshotSigningKeyFile::load(&keys.signing_key_file)
+            .unwrap()
+            .to_signing_key()
+            .unwrap();
+        save_signature_file(
+            &signature_file,
+            &signing_key.sign(&backdated.canonical_bytes().unwrap()),
+        )
+        .unwrap();
+        let mut trusted_keys = SnapshotTrustedKeySet::load(&keys.trusted_keys_file).unwrap();
+        trusted_keys.keys[0].valid_until_time = Some(2_000);
+        std::fs::write(
+            &keys.trusted_keys_file,
+            serde_json::to_vec_pretty(&trusted_keys).unwrap(),
+        )
+        .unwrap();
+        let err = StateSnapshotInstaller::new(target_config.clone())
+            .install(StateSnapshotData {
+                file: file.clone(),
+                manifest_file: Some(manifest_file.clone()),
+            })
+            .unwrap_err();
+        assert!(err.contains("threshold not met"), "{}", err);
+
+        std::fs::write(&manifest_file, manifest_bytes).unwrap();
+        std::fs::write(&signature_file, &signature).unwrap();
+        std::fs::write(&keys.trusted_keys_file, trusted_keys_bytes).unwrap();
+        StateSnapshotInstaller::new(target_config)
+            .install(StateSnapshotData {
+                file,
+                manifest_file: Some(manifest_file),
+            })
+            .unwrap();
+
+        std::fs::remove_dir_all(key_dir).unwrap();
+        std::fs::remove_dir_all(source_root).unwrap();
+        std::fs::remove_dir_all(target_root).unwrap();
+    }
+}
@@ -148,7 +148,7 @@ async fn setup_manager_with_timeline(
     let mut timelines = HashMap::new();
     timelines.insert(owner, timeline_points.clone());
     let provider = Arc::new(TimelineBalanceProvider::new(timelines));
-    let manager = PassEnergyManager::new_with_deps(config, storage, provider);
+    let manager = PassEnergyManager::new_with_deps(config, storage, provider).unwrap();
 
     let inscription_id = test_inscription_id(9, 0);
     manager
@@ -4,7 +4,6 @@ use super::common::{
 };
 use crate::balance::{BalanceMonitor, MockBalanceBackend, MockResponse, SerialBalanceLoader};
 use crate::config::{ConfigManager, IndexerConfig};
-use crate::index::MintValidationErrorCode;
 use crate::index::content::{MinerPassState, USDBInscription, USDBMint};
 use crate::index::energy::PassEnergyManager;
 use crate::index::energy_formula::{calc_growth_delta, calc_penalty_from_delta};
@@ -14,6 +13,7 @@ use crate::index::{
     BalanceHistoryCommitApi, BlockHintProvider, IndexStatusApi, InscriptionIndexer,
     PassBlockCommitEntry, TransferTrackerApi,
 };
+use crate::index::{InscriptionSchemaVersion, MintValidationErrorCode};
 use crate::inscription::{
     DiscoveredInscription, DiscoveredInvalidMint, DiscoveredMint, DiscoveredMintBatch,
     InscriptionSource, InscriptionTransferItem,
@@ -75,6 +75,7 @@ impl MockStatus {
                 .to_string(),
             commit_protocol_version: "1.0.0".to_string(),
             commit_hash_algo: "sha256".to_string(),
+            balance_state_root: None,
         };
         Self {
             latest_height: AtomicU32::new(latest_height),
@@ -160,6 +161,7 @@ impl MockBalanceHistoryCommitProvider {
                 .to_string(),
             usdb_index_formula_version: usdb_util::USDB_INDEX_FORMULA_VERSION.to_string(),
             usdb_index_protocol_version: usdb_util::USDB_INDEX_PROTOCOL_VERSION.to_string(),
+            balance_state_root: None,
         };
         balance_history::HistoricalSnapshotStateRef {
             block_height,
@@ -453,6 +455,7 @@ impl InscriptionSource for MockInscriptionSource {
         &'a self,
         block_height: u32,
         _block_hint: Option<Arc<Block>>,
+        _schema: InscriptionSchemaVersion,
     ) -> Pin<Box<dyn Future<Output = Result<Vec<DiscoveredMint>, String>> + Send + 'a>> {
         Box::pin(async move {
             Ok(self
@@ -467,6 +470,7 @@ impl InscriptionSource for MockInscriptionSource {
         &'a self,
         block_height: u32,
         _block_hint: Option<Arc<Block>>,
+        _schema: InscriptionSchemaVersion,
     ) -> Pin<Box<dyn Future<Output = Result<DiscoveredMintBatch, String>> + Send + 'a>> {
         Box::pin(async move {
             Ok(DiscoveredMintBatch {
@@ -502,6 +506,8 @@ fn make_active_pass(
         prev: Vec::new(),
         invalid_code: None,
         invalid_reason: None,
+        leader_pass_id: None,
+        leader_btc_addr: None,
         owner,
         state: MinerPassState::Active,
     }
@@ -517,6 +523,8 @@ fn make_discovered_mint(
         eth_main: "0x1111111111111111111111111111111111111111".to_string(),
         eth_collab: None,
         prev: prev_strings,
+        leader_pass_id: None,
+        leader_btc_addr: None,
     });
 
     DiscoveredMint {
@@ -578,11 +586,9 @@ fn build_indexer_fixture_with_runtime_deps_at_root(
     let balance_monitor = BalanceMonitor::new_with_loader(storage.clone(), loader, 1024, 1024);
 
     let energy_storage = PassEnergyStorage::new(&config.data_dir()).unwrap();
-    let pass_energy_manager = Arc::new(PassEnergyManager::new_with_deps(
-        config.clone(),
-        energy_storage,
-        energy_provider,
-    ));
+    let pass_energy_manager = Arc::new(
+        PassEnergyManager::new_with_deps(config.clone(), energy_storage, energy_provider).unwrap(),
+    );
     let miner_pass_manager = Arc::new(
         MinerPassManager::new(config.clone(), storage.clone(), pass_energy_manager.clone())
             .unwrap(),
@@ -738,6 +744,7 @@ fn snapshot_from_commit(commit: &balance_history::BlockCommitInfo) -> BalanceHis
             .to_string(),
         commit_protocol_version: commit.commit_protocol_version.clone(),
         commit_hash_algo: commit.commit_hash_algo.clone(),
+        balance_state_root: None,
     }
 }
 
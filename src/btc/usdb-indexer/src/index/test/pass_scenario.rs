@@ -56,6 +56,8 @@ impl ScenarioRunner {
                         eth_main: "0x1111111111111111111111111111111111111111".to_string(),
                         eth_collab: None,
                         prev,
+                        leader_pass_id: None,
+                        leader_btc_addr: None,
                         inscription_id,
                     };
                     self.tx_seed = self.tx_seed.wrapping_add(1);
@@ -91,11 +93,9 @@ fn setup_manager_with_mock(
     let config = Arc::new(ConfigManager::load(Some(root_dir.clone())).unwrap());
     let pass_storage = Arc::new(MinerPassStorage::new(&config.data_dir()).unwrap());
     let energy_storage = PassEnergyStorage::new(&config.data_dir()).unwrap();
-    let energy_manager = Arc::new(PassEnergyManager::new_with_deps(
-        config.clone(),
-        energy_storage,
-        mock_provider,
-    ));
+    let energy_manager = Arc::new(
+        PassEnergyManager::new_with_deps(config.clone(), energy_storage, mock_provider).unwrap(),
+    );
     let manager =
         MinerPassManager::new(config, pass_storage.clone(), energy_manager.clone()).unwrap();
 
@@ -1,6 +1,6 @@
 use crate::index::{
-    InscriptionContentLoader, MintValidationError, MintValidationErrorCode, ParsedMintContent,
-    USDBInscription,
+    InscriptionContentLoader, InscriptionSchemaVersion, MintValidationError,
+    MintValidationErrorCode, ParsedMintContent, USDBInscription,
 };
 use bitcoincore_rpc::bitcoin::Block;
 use ord::InscriptionId;
@@ -60,16 +60,19 @@ pub trait InscriptionSource: Send + Sync {
         block_hint: Option<Arc<Block>>,
     ) -> InscriptionSourceFuture<'a, Result<Vec<DiscoveredInscription>, String>>;
 
+    // `schema` is the inscription schema active at `block_height`; callers resolve it
+    // through the activation registry so sources stay network agnostic.
     fn load_block_mint_batch<'a>(
         &'a self,
         block_height: u32,
         block_hint: Option<Arc<Block>>,
+        schema: InscriptionSchemaVersion,
     ) -> InscriptionSourceFuture<'a, Result<DiscoveredMintBatch, String>> {
         Box::pin(async move {
             let inscriptions = self
                 .load_block_inscriptions(block_height, block_hint)
                 .await?;
-            classify_usdb_mints_from_inscriptions(inscriptions)
+            classify_usdb_mints_from_inscriptions(inscriptions, schema)
         })
     }
 
@@ -77,9 +80,12 @@ pub trait InscriptionSource: Send + Sync {
         &'a self,
         block_height: u32,
         block_hint: Option<Arc<Block>>,
+        schema: InscriptionSchemaVersion,
     ) -> InscriptionSourceFuture<'a, Result<Vec<DiscoveredMint>, String>> {
         Box::pin(async move {
-            let batch = self.load_block_mint_batch(block_height, block_hint).await?;
+            let batch = self
+                .load_block_mint_batch(block_height, block_hint, schema)
+                .await?;
             Ok(batch.valid_mints)
         })
     }
@@ -87,8 +93,9 @@ pub trait InscriptionSource: Send + Sync {
 
 pub fn map_usdb_mints_from_inscriptions(
     inscriptions: Vec<DiscoveredInscription>,
+    schema: InscriptionSchemaVersion,
 ) -> Result<Vec<DiscoveredMint>, String> {
-    let batch = classify_usdb_mints_from_inscriptions(inscriptions)?;
+    let batch = classify_usdb_mints_from_inscriptions(inscriptions, schema)?;
     Ok(batch.valid_mints)
 }
 
@@ -111,6 +118,7 @@ fn to_invalid_mint(
 
 pub fn classify_usdb_mints_from_inscriptions(
     inscriptions: Vec<DiscoveredInscription>,
+    schema: InscriptionSchemaVersion,
 ) -> Result<DiscoveredMintBatch, String> {
     let mut batch = DiscoveredMintBatch::default();
     for inscription in inscriptions {
@@ -122,6 +130,7 @@ pub fn classify_usdb_mints_from_inscriptions(
         match InscriptionContentLoader::classify_mint_content_str(
             &inscription.inscription_id,
             &content_string,
+            schema,
         )? {
             ParsedMintContent::NotUsdbMint => {}
             ParsedMintContent::Valid(content) => {
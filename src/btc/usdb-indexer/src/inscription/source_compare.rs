@@ -2,6 +2,7 @@ use super::{
     DiscoveredInscription, DiscoveredMint, DiscoveredMintBatch, InscriptionSource,
     InscriptionSourceFuture, classify_usdb_mints_from_inscriptions,
 };
+use crate::index::InscriptionSchemaVersion;
 use bitcoincore_rpc::bitcoin::Block;
 use std::collections::BTreeMap;
 use std::sync::Arc;
@@ -257,9 +258,12 @@ impl InscriptionSource for CompareInscriptionSource {
         &'a self,
         block_height: u32,
         block_hint: Option<Arc<Block>>,
+        schema: InscriptionSchemaVersion,
     ) -> InscriptionSourceFuture<'a, Result<Vec<DiscoveredMint>, String>> {
         Box::pin(async move {
-            let batch = self.load_block_mint_batch(block_height, block_hint).await?;
+            let batch = self
+                .load_block_mint_batch(block_height, block_hint, schema)
+                .await?;
             Ok(batch.valid_mints)
         })
     }
@@ -268,6 +272,7 @@ impl InscriptionSource for CompareInscriptionSource {
         &'a self,
         block_height: u32,
         block_hint: Option<Arc<Block>>,
+        schema: InscriptionSchemaVersion,
     ) -> InscriptionSourceFuture<'a, Result<DiscoveredMintBatch, String>> {
         Box::pin(async move {
             let primary_inscriptions = self
@@ -287,9 +292,11 @@ impl InscriptionSource for CompareInscriptionSource {
                 )?;
             }
 
-            let primary_batch = classify_usdb_mints_from_inscriptions(primary_inscriptions)?;
+            let primary_batch =
+                classify_usdb_mints_from_inscriptions(primary_inscriptions, schema)?;
             if self.target == CompareTarget::UsdbMint {
-                let shadow_batch = classify_usdb_mints_from_inscriptions(shadow_inscriptions)?;
+                let shadow_batch =
+                    classify_usdb_mints_from_inscriptions(shadow_inscriptions, schema)?;
                 self.compare_block_mints(
                     block_height,
                     &primary_batch.valid_mints,
@@ -1,5 +1,6 @@
 use crate::btc::OrdClient;
 use crate::config::ConfigManager;
+use crate::index::InscriptionSchemaVersion;
 use crate::inscription::{
     BitcoindInscriptionSource, CompareInscriptionSource, CompareTarget, InscriptionSource,
     OrdInscriptionSource,
@@ -212,7 +213,7 @@ async fn run_compare_ord_and_bitcoind_with_options(options: CompareRangeOptions)
                 .unwrap_or_else(|e| panic!("Compare failed at block {}: {}", height, e))
                 .len(),
             CompareTarget::UsdbMint => compare_source
-                .load_block_mints(height, Some(block))
+                .load_block_mints(height, Some(block), InscriptionSchemaVersion::V2)
                 .await
                 .unwrap_or_else(|e| panic!("Compare failed at block {}: {}", height, e))
                 .len(),
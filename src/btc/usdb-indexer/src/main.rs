@@ -14,12 +14,12 @@ mod storage;
 #[macro_use]
 extern crate log;
 
-use clap::Parser;
-use config::ConfigManager;
+use clap::{Parser, Subcommand};
+use config::{ConfigManager, ConfigManagerRef};
 use index::InscriptionIndexer;
 use std::path::PathBuf;
 use std::sync::Arc;
-use usdb_util::LogConfig;
+use usdb_util::{LogConfig, SubscriptionHub, WsSubscriptionServer};
 
 #[derive(Parser, Debug)]
 #[command(name = "usdb-indexer")]
@@ -27,6 +27,9 @@ use usdb_util::LogConfig;
 #[command(version = "0.1.0")]
 #[command(about = "USDB Indexer", long_about = None)]
 struct UsdbIndexerCli {
+    #[command(subcommand)]
+    command: Option<UsdbIndexerCommands>,
+
     /// Override service root directory (default: ~/.usdb/usdb-indexer)
     #[arg(long)]
     root_dir: Option<PathBuf>,
@@ -36,6 +39,98 @@ struct UsdbIndexerCli {
     skip_process_lock: bool,
 }
 
+#[derive(Subcommand, Debug, Clone)]
+#[command(rename_all = "kebab-case")]
+enum UsdbIndexerCommands {
+    /// Create a signed state snapshot of the current local synced height under ${root}/snapshots/
+    CreateSnapshot {
+        /// Expected snapshot height; must match the local synced height when given
+        #[arg(short, long)]
+        block_height: Option<u32>,
+    },
+
+    /// Install a state snapshot, replacing the current data directory
+    InstallSnapshot {
+        /// Snapshot file to install; relative paths are resolved against ${root}/snapshots/
+        #[arg(short, long)]
+        file: PathBuf,
+
+        /// Optional sidecar manifest file describing the expected installed state.
+        /// If omitted, the installer will look for `<snapshot>.manifest.json` next to the snapshot DB.
+        #[arg(long)]
+        manifest: Option<PathBuf>,
+    },
+}
+
+fn run_snapshot_command(config: ConfigManagerRef, command: UsdbIndexerCommands) {
+    match command {
+        UsdbIndexerCommands::CreateSnapshot { block_height } => {
+            let creator = index::StateSnapshotCreator::new(config);
+            match creator.run(block_height) {
+                Ok(created) => {
+                    println!(
+                        "State snapshot created at block height {}: {}",
+                        created.meta.block_height,
+                        created.file.display()
+                    );
+                    println!("Snapshot manifest: {}", created.manifest_file.display());
+                    if let Some(signature_file) = created.signature_file {
+                        println!("Snapshot signature: {}", signature_file.display());
+                    }
+                }
+                Err(e) => {
+                    error!("Failed to create state snapshot: {}", e);
+                    println!("Failed to create state snapshot: {}", e);
+                    std::process::exit(1);
+                }
+            }
+        }
+        UsdbIndexerCommands::InstallSnapshot { file, manifest } => {
+            let resolve = |path: PathBuf| {
+                if path.is_relative() {
+                    config.snapshot_dir().join(path)
+                } else {
+                    path
+                }
+            };
+            let file = resolve(file);
+            let manifest_file = match manifest {
+                Some(manifest) => Some(resolve(manifest)),
+                None => {
+                    let auto_manifest = balance_history::manifest_path_for_snapshot_file(&file);
+                    auto_manifest.exists().then_some(auto_manifest)
+                }
+            };
+            if !file.exists() {
+                error!("State snapshot file does not exist: {}", file.display());
+                println!("State snapshot file does not exist: {}", file.display());
+                std::process::exit(1);
+            }
+
+            let installer = index::StateSnapshotInstaller::new(config);
+            match installer.install(index::StateSnapshotData {
+                file,
+                manifest_file,
+            }) {
+                Ok(meta) => {
+                    println!(
+                        "State snapshot installed at block height {}: passes={}, pass_block_commits={}, energy_records={}",
+                        meta.block_height,
+                        meta.miner_pass_count,
+                        meta.pass_block_commit_count,
+                        meta.pass_energy_record_count
+                    );
+                }
+                Err(e) => {
+                    error!("Failed to install state snapshot: {}", e);
+                    println!("Failed to install state snapshot: {}", e);
+                    std::process::exit(1);
+                }
+            }
+        }
+    }
+}
+
 #[tokio::main]
 async fn main() {
     let cli = UsdbIndexerCli::parse();
@@ -53,10 +148,14 @@ async fn main() {
         .root_dir
         .unwrap_or_else(|| usdb_util::get_service_dir(usdb_util::USDB_INDEXER_SERVICE_NAME));
 
-    // Init file logging
-    let config = LogConfig::new(usdb_util::USDB_INDEXER_SERVICE_NAME)
+    // Init file logging, snapshot commands log to their own file
+    let mut config = LogConfig::new(usdb_util::USDB_INDEXER_SERVICE_NAME)
         .with_service_root_dir(root_dir.clone())
         .enable_console(false);
+    if cli.command.is_some() {
+        let file_name = format!("{}_snapshot", usdb_util::USDB_INDEXER_SERVICE_NAME);
+        config = config.with_file_name(&file_name);
+    }
     usdb_util::init_log(config);
 
     let output = output::IndexOutput::new();
@@ -75,6 +174,11 @@ async fn main() {
     };
     let config = Arc::new(config);
 
+    if let Some(command) = cli.command {
+        run_snapshot_command(config, command);
+        return;
+    }
+
     let status_manager = status::StatusManager::new(config.clone(), output.clone())
         .map_err(|e| {
             error!("Failed to initialize status manager: {}", e);
@@ -86,13 +190,23 @@ async fn main() {
 
     status_manager.run_monitor();
 
+    let subscription_hub = SubscriptionHub::new(&[service::SUBSCRIBE_PASS_BLOCK_COMMITS])
+        .map_err(|e| {
+            error!("Failed to create subscription hub: {}", e);
+            println!("Failed to create subscription hub: {}", e);
+            std::process::exit(1);
+        })
+        .unwrap();
+    let subscription_hub = Arc::new(subscription_hub);
+
     let indexer = InscriptionIndexer::new(config.clone(), status_manager.clone())
         .map_err(|e| {
             error!("Failed to initialize indexer: {}", e);
             println!("Failed to initialize indexer: {}", e);
             std::process::exit(1);
         })
-        .unwrap();
+        .unwrap()
+        .with_subscription_hub(subscription_hub.clone());
     let indexer = Arc::new(indexer);
     let (shutdown_tx, mut shutdown_rx) = tokio::sync::watch::channel(());
 
@@ -115,6 +229,30 @@ async fn main() {
         None
     };
 
+    let ws_server = if config.config().usdb.ws_server_enabled {
+        let ret = match format!(
+            "{}:{}",
+            config.config().usdb.rpc_server_host,
+            config.config().usdb.ws_server_port
+        )
+        .parse::<std::net::SocketAddr>()
+        {
+            Ok(addr) => WsSubscriptionServer::start(addr, subscription_hub.clone()).await,
+            Err(e) => Err(format!("Failed to parse WebSocket server address: {}", e)),
+        };
+        match ret {
+            Ok(server) => Some(server),
+            Err(e) => {
+                error!("Failed to start usdb-indexer WebSocket server: {}", e);
+                println!("Failed to start usdb-indexer WebSocket server: {}", e);
+                std::process::exit(1);
+            }
+        }
+    } else {
+        info!("USDB indexer WebSocket server is disabled by config");
+        None
+    };
+
     // Create a Future to wait for Ctrl+C (SIGINT) signal
     use tokio::signal;
     let sigint = signal::ctrl_c();
@@ -162,6 +300,9 @@ async fn main() {
     if let Some(server) = &rpc_server {
         server.close().await;
     }
+    if let Some(server) = &ws_server {
+        server.close().await;
+    }
 
     output.println("Indexer has shut down gracefully.");
 
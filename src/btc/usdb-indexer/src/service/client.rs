@@ -115,6 +115,28 @@ impl RpcClient {
         .await
     }
 
+    /// Returns the persisted pass mutation stream of one block together with its commit metadata.
+    ///
+    /// # Arguments
+    /// * `block_height` - Optional query height. `None` resolves to current local synced height.
+    ///
+    /// # Returns
+    /// * `Ok(Some(PassBlockMutationsInfo))` when a local pass block commit row exists at the height.
+    /// * `Ok(None)` when the height is resolved successfully but no local pass commit row exists.
+    /// * `Err(String)` if the RPC call fails, including commits persisted without a mutation stream.
+    pub async fn get_pass_block_mutations(
+        &self,
+        block_height: Option<u32>,
+    ) -> Result<Option<PassBlockMutationsInfo>, String> {
+        self.rpc_call::<Option<PassBlockMutationsInfo>>(
+            "get_pass_block_mutations",
+            json!([{
+                "block_height": block_height,
+            }]),
+        )
+        .await
+    }
+
     /// Returns the current locally durable core-state commit anchored to the current upstream snapshot.
     pub async fn get_local_state_commit_info(
         &self,
@@ -451,6 +473,8 @@ impl RpcClient {
     /// # Arguments
     /// * `at_height` - Optional query height. `None` resolves to current local synced height.
     /// * `scope` - Optional leaderboard scope: `active`, `active_dormant`, or `all`.
+    /// * `order_by` - Optional ranking key: `energy` (default), `effective_energy` or `level`.
+    /// * `include_level` - Whether to fill level fields; implied by `order_by=level`.
     /// * `page` - Zero-based page index.
     /// * `page_size` - Number of rows per page.
     ///
@@ -461,6 +485,8 @@ impl RpcClient {
         &self,
         at_height: Option<u32>,
         scope: Option<&str>,
+        order_by: Option<&str>,
+        include_level: Option<bool>,
         page: usize,
         page_size: usize,
     ) -> Result<PassEnergyLeaderboardPage, String> {
@@ -469,6 +495,8 @@ impl RpcClient {
             json!([{
                 "at_height": at_height,
                 "scope": scope,
+                "order_by": order_by,
+                "include_level": include_level,
                 "page": page,
                 "page_size": page_size,
             }]),
@@ -476,6 +504,178 @@ impl RpcClient {
         .await
     }
 
+    /// Returns the UIP-0004 effective energy view of one pass.
+    ///
+    /// # Arguments
+    /// * `inscription_id` - Target inscription id.
+    /// * `block_height` - Optional query height. `None` resolves to current local synced height.
+    ///
+    /// # Returns
+    /// * `Ok(PassEffectiveEnergySnapshot)` on success.
+    /// * `Err(String)` if the pass is not found or request fails.
+    pub async fn get_pass_effective_energy(
+        &self,
+        inscription_id: &str,
+        block_height: Option<u32>,
+    ) -> Result<PassEffectiveEnergySnapshot, String> {
+        self.rpc_call::<PassEffectiveEnergySnapshot>(
+            "get_pass_effective_energy",
+            json!([GetPassEffectiveEnergyParams {
+                inscription_id: inscription_id.to_string(),
+                block_height,
+                context: None,
+            }]),
+        )
+        .await
+    }
+
+    /// Returns the UIP-0005 level view of one pass.
+    ///
+    /// # Arguments
+    /// * `inscription_id` - Target inscription id.
+    /// * `block_height` - Optional query height. `None` resolves to current local synced height.
+    ///
+    /// # Returns
+    /// * `Ok(PassLevelSnapshot)` on success.
+    /// * `Err(String)` if the pass is not found or request fails.
+    pub async fn get_pass_level(
+        &self,
+        inscription_id: &str,
+        block_height: Option<u32>,
+    ) -> Result<PassLevelSnapshot, String> {
+        self.rpc_call::<PassLevelSnapshot>(
+            "get_pass_level",
+            json!([GetPassLevelParams {
+                inscription_id: inscription_id.to_string(),
+                block_height,
+                context: None,
+            }]),
+        )
+        .await
+    }
+
+    /// Returns collab passes resolved to one Leader at a target height.
+    ///
+    /// # Arguments
+    /// * `leader_pass_id` - Leader pass inscription id.
+    /// * `at_height` - Optional query height. `None` resolves to current local synced height.
+    ///
+    /// # Returns
+    /// * `Ok(LeaderCollabSet)` on success.
+    /// * `Err(String)` if request fails.
+    pub async fn get_leader_collab_set_at_height(
+        &self,
+        leader_pass_id: &str,
+        at_height: Option<u32>,
+    ) -> Result<LeaderCollabSet, String> {
+        self.rpc_call::<LeaderCollabSet>(
+            "get_leader_collab_set_at_height",
+            json!([{
+                "leader_pass_id": leader_pass_id,
+                "at_height": at_height,
+            }]),
+        )
+        .await
+    }
+
+    /// Returns the deterministic validator candidate set and its commitment.
+    ///
+    /// # Arguments
+    /// * `block_height` - Optional query height. `None` resolves to current local synced height.
+    /// * `max_candidates` - Optional candidate limit. `None` uses the server default.
+    /// * `min_effective_energy` - Optional minimum effective energy. `None` means 0.
+    ///
+    /// # Returns
+    /// * `Ok(ValidatorCandidateSet)` on success.
+    /// * `Err(String)` if parameters are invalid or request fails.
+    pub async fn get_validator_candidate_set(
+        &self,
+        block_height: Option<u32>,
+        max_candidates: Option<u32>,
+        min_effective_energy: Option<u64>,
+    ) -> Result<ValidatorCandidateSet, String> {
+        self.rpc_call::<ValidatorCandidateSet>(
+            "get_validator_candidate_set",
+            json!([GetValidatorCandidateSetParams {
+                block_height,
+                max_candidates,
+                min_effective_energy,
+                context: None,
+            }]),
+        )
+        .await
+    }
+
+    /// Verifies the candidate-set fields of a block body payload against local state.
+    ///
+    /// # Arguments
+    /// * `params` - Candidate-set fields carried by the payload.
+    ///
+    /// # Returns
+    /// * `Ok(ValidatorCandidateSetVerification)` with `valid=false` and mismatch codes
+    ///   when the payload does not match.
+    /// * `Err(String)` if the height is not available or request fails.
+    pub async fn verify_validator_candidate_set(
+        &self,
+        params: VerifyValidatorCandidateSetParams,
+    ) -> Result<ValidatorCandidateSetVerification, String> {
+        self.rpc_call::<ValidatorCandidateSetVerification>(
+            "verify_validator_candidate_set",
+            json!([params]),
+        )
+        .await
+    }
+
+    /// Returns the UIP-0006 economic state view at a target height.
+    ///
+    /// # Arguments
+    /// * `block_height` - Optional query height. `None` resolves to current local synced height.
+    ///
+    /// # Returns
+    /// * `Ok(EconomicStateView)` on success.
+    /// * `Err(String)` if the height is not available or request fails.
+    pub async fn get_economic_state_view(
+        &self,
+        block_height: Option<u32>,
+    ) -> Result<EconomicStateView, String> {
+        self.rpc_call::<EconomicStateView>(
+            "get_economic_state_view",
+            json!([GetEconomicStateViewParams {
+                block_height,
+                context: None,
+            }]),
+        )
+        .await
+    }
+
+    /// Returns one page of the UIP-0006 economic audit view.
+    ///
+    /// # Arguments
+    /// * `block_height` - Optional query height. `None` resolves to current local synced height.
+    /// * `page` - Zero-based page index.
+    /// * `page_size` - Page size.
+    ///
+    /// # Returns
+    /// * `Ok(EconomicStateAuditView)` on success.
+    /// * `Err(String)` if pagination is invalid or request fails.
+    pub async fn get_economic_state_audit_view(
+        &self,
+        block_height: Option<u32>,
+        page: usize,
+        page_size: usize,
+    ) -> Result<EconomicStateAuditView, String> {
+        self.rpc_call::<EconomicStateAuditView>(
+            "get_economic_state_audit_view",
+            json!([GetEconomicStateAuditViewParams {
+                block_height,
+                page,
+                page_size,
+                context: None,
+            }]),
+        )
+        .await
+    }
+
     /// Returns active-balance snapshot exactly at `block_height`.
     ///
     /// # Arguments
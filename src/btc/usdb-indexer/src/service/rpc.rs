@@ -1,13 +1,15 @@
+use crate::index::{PassBlockCommitEntry, PassBlockMutation};
 use jsonrpc_core::Result as JsonResult;
 use jsonrpc_derive::rpc;
 use serde::{Deserialize, Serialize};
 use usdb_util::{
-    CONSENSUS_SNAPSHOT_ID_HASH_ALGO, CONSENSUS_SNAPSHOT_ID_VERSION, ConsensusQueryContext,
-    ConsensusSnapshotIdentity, ConsensusStateReference, LOCAL_STATE_COMMIT_HASH_ALGO,
-    LOCAL_STATE_COMMIT_VERSION, LocalStateActiveBalanceSnapshot, LocalStateCommitIdentity,
-    LocalStatePassCommitIdentity, SYSTEM_STATE_ID_HASH_ALGO, SYSTEM_STATE_ID_VERSION,
-    SystemStateIdentity, USDB_INDEX_FORMULA_VERSION as UTIL_USDB_INDEX_FORMULA_VERSION,
-    USDB_INDEX_PROTOCOL_VERSION as UTIL_USDB_INDEX_PROTOCOL_VERSION,
+    ActiveVersionSet, CONSENSUS_SNAPSHOT_ID_HASH_ALGO, CONSENSUS_SNAPSHOT_ID_VERSION,
+    ConsensusQueryContext, ConsensusSnapshotIdentity, ConsensusStateReference,
+    LOCAL_STATE_COMMIT_HASH_ALGO, LOCAL_STATE_COMMIT_VERSION, LocalStateActiveBalanceSnapshot,
+    LocalStateCommitIdentity, LocalStatePassCommitIdentity, SYSTEM_STATE_ID_HASH_ALGO,
+    SYSTEM_STATE_ID_VERSION, SystemStateIdentity,
+    USDB_INDEX_FORMULA_VERSION as UTIL_USDB_INDEX_FORMULA_VERSION,
+    USDB_INDEX_PROTOCOL_VERSION as UTIL_USDB_INDEX_PROTOCOL_VERSION, ValidatorCandidateIdentity,
 };
 
 /// Business error code returned when the requested height is above local durable sync progress.
@@ -26,6 +28,8 @@ pub const ERR_INVALID_PAGINATION: i64 = -32015;
 pub const ERR_INVALID_HEIGHT_RANGE: i64 = -32016;
 /// Business error code returned when internal state invariants are violated during RPC resolution.
 pub const ERR_INTERNAL_INVARIANT_BROKEN: i64 = -32017;
+/// Business error code returned when a pass block commit exists but its mutation stream was not retained.
+pub const ERR_PASS_BLOCK_MUTATIONS_NOT_AVAILABLE: i64 = -32018;
 
 pub const USDB_INDEX_FORMULA_VERSION: &str = UTIL_USDB_INDEX_FORMULA_VERSION;
 pub const USDB_INDEX_PROTOCOL_VERSION: &str = UTIL_USDB_INDEX_PROTOCOL_VERSION;
@@ -41,6 +45,10 @@ pub const LOCAL_STATE_VERSION: &str = LOCAL_STATE_COMMIT_VERSION;
 pub const SYSTEM_STATE_HASH_ALGO: &str = SYSTEM_STATE_ID_HASH_ALGO;
 /// Version tag of the system-state id derivation rule exposed by the RPC layer.
 pub const SYSTEM_STATE_VERSION: &str = SYSTEM_STATE_ID_VERSION;
+/// View version of the UIP-0006 economic state view.
+pub const ECONOMIC_STATE_VIEW_VERSION: &str = "uip-0006-usdb-economic-state-view:v1";
+/// Item order of `get_economic_state_audit_view`.
+pub const ECONOMIC_STATE_AUDIT_SORT: &str = "pass_id_asc";
 
 /// Service metadata returned by `get_rpc_info`.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -53,6 +61,12 @@ pub struct RpcInfo {
     pub network: String,
     /// Advertised capability list supported by this server instance.
     pub features: Vec<String>,
+    /// Canonical id of the UIP-0008 activation registry loaded by this node.
+    pub activation_registry_id: String,
+    /// Active version set at the current local synced height, `None` before the first synced block.
+    pub active_version_set: Option<ActiveVersionSet>,
+    /// Canonical id of `active_version_set`.
+    pub active_version_set_id: Option<String>,
 }
 
 /// Runtime synchronization status of the indexer.
@@ -115,6 +129,7 @@ pub struct IndexerSnapshotInfoSeed {
     pub stable_lag: u32,
     pub commit_protocol_version: String,
     pub commit_hash_algo: String,
+    pub balance_state_root: Option<String>,
 }
 
 impl From<IndexerSnapshotInfoSeed> for IndexerSnapshotInfo {
@@ -130,6 +145,7 @@ impl From<IndexerSnapshotInfoSeed> for IndexerSnapshotInfo {
                 .to_string(),
             usdb_index_formula_version: USDB_INDEX_FORMULA_VERSION.to_string(),
             usdb_index_protocol_version: USDB_INDEX_PROTOCOL_VERSION.to_string(),
+            balance_state_root: seed.balance_state_root,
         };
         let snapshot_id = usdb_util::build_consensus_snapshot_id(&consensus_identity);
 
@@ -208,6 +224,70 @@ pub struct PassBlockCommitInfo {
     pub commit_hash_algo: String,
 }
 
+impl From<&PassBlockCommitEntry> for PassBlockCommitInfo {
+    fn from(entry: &PassBlockCommitEntry) -> Self {
+        Self {
+            block_height: entry.block_height,
+            balance_history_block_height: entry.balance_history_block_height,
+            balance_history_block_commit: entry.balance_history_block_commit.clone(),
+            mutation_root: entry.mutation_root.clone(),
+            block_commit: entry.block_commit.clone(),
+            commit_protocol_version: entry.commit_protocol_version.clone(),
+            commit_hash_algo: entry.commit_hash_algo.clone(),
+        }
+    }
+}
+
+/// Parameters for `get_pass_block_mutations`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GetPassBlockMutationsParams {
+    /// Optional query height; `None` resolves to the current local synced height.
+    pub block_height: Option<u32>,
+}
+
+/// Persisted pass mutation stream of one block plus the inputs needed to recompute its commit.
+///
+/// `mutation_root` is recomputed from `block_height` and `mutations`; `block_commit` is then
+/// recomputed from `previous_block_commit` (or `"genesis"` when absent), the upstream anchor
+/// in `commit`, and that `mutation_root`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PassBlockMutationsInfo {
+    /// Local pass block commit whose `mutation_root` hashes `mutations`.
+    pub commit: PassBlockCommitInfo,
+    /// Local pass block commit at `block_height - 1`, `None` when this is the first commit.
+    pub previous_block_commit: Option<String>,
+    /// Number of entries in `mutations`.
+    pub mutation_count: u32,
+    /// Ordered pass mutations exactly as hashed into `mutation_root`.
+    pub mutations: Vec<PassBlockMutation>,
+}
+
+/// WebSocket subscription method streaming `PassBlockCommitEvent` notifications.
+pub const SUBSCRIBE_PASS_BLOCK_COMMITS: &str = "subscribe_pass_block_commits";
+
+/// Event pushed to `subscribe_pass_block_commits` subscribers.
+///
+/// Commits are published only after the block savepoint is durable. Subscribers
+/// that miss events (reconnect or lag) must resync through `get_pass_block_commit`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum PassBlockCommitEvent {
+    /// One local pass block commit with the mutation stream hashed into `mutation_root`.
+    PassBlockCommit {
+        /// Committed local pass block commit.
+        commit: PassBlockCommitInfo,
+        /// Ordered pass mutations of this block, in `mutation_root` order.
+        mutations: Vec<PassBlockMutation>,
+    },
+    /// Local pass state was rolled back after upstream anchor drift.
+    Rollback {
+        /// Local synced height before the rollback.
+        from_height: u32,
+        /// Height the local pass state was rolled back to.
+        to_height: u32,
+    },
+}
+
 /// Locally durable core-state commit anchored to one upstream snapshot.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LocalStateCommitInfo {
@@ -365,27 +445,42 @@ pub struct HistoricalStateRefInfo {
     pub local_state_commit_info: LocalStateCommitInfo,
     /// Historical top-level system-state id at `block_height`.
     pub system_state_info: SystemStateInfo,
+    /// UIP-0008 versions active at `block_height`, looked up by that height rather than the head.
+    pub active_version_set: ActiveVersionSet,
+    /// Canonical id of `active_version_set`.
+    pub active_version_set_id: String,
+    /// Canonical id of the activation registry used for the lookup.
+    pub activation_registry_id: String,
 }
 
 /// Normalized inputs required to derive one `HistoricalStateRefInfo`.
 ///
 /// Keeping this seed explicit makes it easier to audit which three exact
-/// historical sub-views are bundled into one validator-facing state ref.
+/// historical sub-views, plus the version set active at that height, are
+/// bundled into one validator-facing state ref.
 #[derive(Debug, Clone)]
 pub struct HistoricalStateRefInfoSeed {
     pub block_height: u32,
     pub snapshot_info: IndexerSnapshotInfo,
     pub local_state_commit_info: LocalStateCommitInfo,
     pub system_state_info: SystemStateInfo,
+    pub active_version_set: ActiveVersionSet,
+    pub activation_registry_id: String,
 }
 
 impl From<HistoricalStateRefInfoSeed> for HistoricalStateRefInfo {
     fn from(seed: HistoricalStateRefInfoSeed) -> Self {
+        let active_version_set_id =
+            usdb_util::build_active_version_set_id(&seed.active_version_set);
+
         Self {
             block_height: seed.block_height,
             snapshot_info: seed.snapshot_info,
             local_state_commit_info: seed.local_state_commit_info,
             system_state_info: seed.system_state_info,
+            active_version_set: seed.active_version_set,
+            active_version_set_id,
+            activation_registry_id: seed.activation_registry_id,
         }
     }
 }
@@ -501,6 +596,10 @@ pub struct PassSnapshot {
     pub invalid_code: Option<String>,
     /// Human-readable invalid reason.
     pub invalid_reason: Option<String>,
+    /// Fixed Leader pass declared by a collab pass (UIP-0004).
+    pub leader_pass_id: Option<String>,
+    /// Leader BTC address declared by a collab pass (UIP-0004).
+    pub leader_btc_addr: Option<String>,
     /// Owner script hash at resolved height.
     pub owner: String,
     /// Pass state at resolved height.
@@ -835,6 +934,13 @@ pub struct GetPassEnergyLeaderboardParams {
     /// - `active_dormant`: include active + dormant passes.
     /// - `all`: include all pass states.
     pub scope: Option<String>,
+    /// Optional ranking key:
+    /// - `energy`: raw energy descending (default).
+    /// - `effective_energy`: UIP-0004 effective energy descending, then raw energy descending.
+    /// - `level`: UIP-0005 level descending, then effective energy descending.
+    pub order_by: Option<String>,
+    /// Whether to fill `level` and `difficulty_factor_bps`; implied by `order_by=level`.
+    pub include_level: Option<bool>,
     /// Zero-based page index.
     pub page: usize,
     /// Number of rows per page.
@@ -852,8 +958,15 @@ pub struct PassEnergyLeaderboardItem {
     pub record_block_height: u32,
     /// Pass state in the latest energy record.
     pub state: String,
-    /// Energy value used for ranking.
+    /// Raw energy at resolved height, the ranking key for `order_by=energy`.
     pub energy: u64,
+    /// UIP-0004 effective energy at resolved height, `None` for `order_by=energy`
+    /// unless levels are requested.
+    pub effective_energy: Option<u64>,
+    /// UIP-0005 level derived from `effective_energy`, `None` unless requested.
+    pub level: Option<u32>,
+    /// UIP-0005 difficulty factor in bps derived from `level`, `None` unless requested.
+    pub difficulty_factor_bps: Option<u32>,
 }
 
 /// Paged pass energy leaderboard response.
@@ -867,6 +980,376 @@ pub struct PassEnergyLeaderboardPage {
     pub items: Vec<PassEnergyLeaderboardItem>,
 }
 
+/// Parameters for `get_pass_effective_energy`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GetPassEffectiveEnergyParams {
+    /// Target inscription id.
+    pub inscription_id: String,
+    /// Optional query height; `None` resolves to the current local synced height.
+    pub block_height: Option<u32>,
+    /// Optional consensus selectors pinned by downstream validators.
+    ///
+    /// When present, the service validates the historical state reference at
+    /// the resolved height before returning the effective energy view.
+    pub context: Option<ConsensusQueryContext>,
+}
+
+/// One collab pass contribution to its resolved Leader (UIP-0004).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CollabContributionItem {
+    /// Collab pass inscription id.
+    pub collab_pass_id: String,
+    /// Collab pass owner script hash at query height.
+    pub collab_owner: String,
+    /// Raw energy of the collab pass at query height.
+    pub collab_raw_energy: u64,
+    /// Weight in basis points applied to the collab raw energy.
+    pub collab_weight_bps: u64,
+    /// Contribution counted into the Leader effective energy, rounded down.
+    pub collab_contribution: u64,
+    /// Leader binding declared by the collab pass, `leader_pass_id` or `leader_btc_addr`.
+    pub leader_ref_kind: String,
+    /// Declared Leader inscription id or BTC address.
+    pub leader_ref_value: String,
+}
+
+/// Effective energy view of one pass (UIP-0004).
+///
+/// This is derived on read and never written back into the raw energy ledger.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PassEffectiveEnergySnapshot {
+    /// Pass inscription id.
+    pub inscription_id: String,
+    /// Height used by this query after resolution.
+    pub query_block_height: u32,
+    /// Pass state at query height.
+    pub state: String,
+    /// Whether the pass is a collab pass bound to a Leader.
+    pub is_collab: bool,
+    /// Raw energy of the pass at query height, as returned by `get_pass_energy`.
+    pub raw_energy: u64,
+    /// Sum of all collab contributions resolved to this pass.
+    pub collab_contribution_total: u64,
+    /// `raw_energy + collab_contribution_total` for an active standard pass, otherwise 0.
+    pub effective_energy: u64,
+    /// Collab contributions resolved to this pass at query height.
+    pub collabs: Vec<CollabContributionItem>,
+}
+
+/// Parameters for `get_pass_level`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GetPassLevelParams {
+    /// Target inscription id.
+    pub inscription_id: String,
+    /// Optional query height; `None` resolves to the current local synced height.
+    pub block_height: Option<u32>,
+    /// Optional consensus selectors pinned by downstream validators.
+    ///
+    /// When present, the service validates the historical state reference at
+    /// the resolved height before returning the level view.
+    pub context: Option<ConsensusQueryContext>,
+}
+
+/// Level view of one pass (UIP-0005).
+///
+/// Recomputed on read from the effective energy view; nothing here is persisted.
+/// `real_difficulty` depends on the ETHW base difficulty and is left to the ETHW side.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PassLevelSnapshot {
+    /// Pass inscription id.
+    pub inscription_id: String,
+    /// Height used by this query after resolution.
+    pub query_block_height: u32,
+    /// Pass state at query height.
+    pub state: String,
+    /// Whether the pass is a collab pass bound to a Leader.
+    pub is_collab: bool,
+    /// UIP-0004 effective energy used as the only level input.
+    pub effective_energy: u64,
+    /// Highest level whose threshold `effective_energy` reaches.
+    pub level: u32,
+    /// Difficulty factor in bps, never below 5000.
+    pub difficulty_factor_bps: u32,
+    /// Minimum effective energy of the next level, `None` at the maximum level.
+    pub next_level_threshold: Option<u64>,
+    /// Level formula version active at query height.
+    pub level_formula_version: String,
+}
+
+/// Parameters for `get_leader_collab_set_at_height`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GetLeaderCollabSetAtHeightParams {
+    /// Leader pass inscription id.
+    pub leader_pass_id: String,
+    /// Optional query height; `None` resolves to the current local synced height.
+    pub at_height: Option<u32>,
+    /// Optional consensus selectors pinned by downstream validators.
+    ///
+    /// When present, the service validates the historical state reference at
+    /// the resolved height before returning the collab set.
+    pub context: Option<ConsensusQueryContext>,
+}
+
+/// Collab passes resolved to one Leader at a target height.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LeaderCollabSet {
+    /// Leader pass inscription id.
+    pub leader_pass_id: String,
+    /// Final query height resolved by the server.
+    pub resolved_height: u32,
+    /// Whether the Leader is an active standard pass at resolved height.
+    pub leader_active: bool,
+    /// Sum of all collab contributions in `items`.
+    pub total_contribution: u64,
+    /// Collab contributions ordered by collab inscription id.
+    pub items: Vec<CollabContributionItem>,
+}
+
+/// Parameters for `get_validator_candidate_set`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GetValidatorCandidateSetParams {
+    /// Optional query height; `None` resolves to the current local synced height.
+    pub block_height: Option<u32>,
+    /// Optional maximum number of candidates kept after ordering.
+    /// Defaults to 100 and must not exceed 1000.
+    pub max_candidates: Option<u32>,
+    /// Optional minimum effective energy required to enter the set. Defaults to 0.
+    pub min_effective_energy: Option<u64>,
+    /// Optional consensus selectors pinned by downstream validators.
+    ///
+    /// When present, the service validates the historical state reference at
+    /// the resolved height before building the candidate set.
+    pub context: Option<ConsensusQueryContext>,
+}
+
+/// One pass in a validator candidate set.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ValidatorCandidateItem {
+    /// Zero-based position in selection order; rank 0 is the winner.
+    pub rank: u32,
+    /// Candidate pass inscription id.
+    pub inscription_id: String,
+    /// Candidate owner script hash at query height.
+    pub owner: String,
+    /// Raw energy of the pass at query height.
+    pub raw_energy: u64,
+    /// Sum of collab contributions resolved to this pass.
+    pub collab_contribution_total: u64,
+    /// UIP-0004 effective energy used for ordering.
+    pub effective_energy: u64,
+    /// UIP-0005 level derived from `effective_energy`, `None` before energy formula v2.
+    pub level: Option<u32>,
+    /// UIP-0005 difficulty factor in bps derived from `level`, `None` before energy formula v2.
+    pub difficulty_factor_bps: Option<u32>,
+}
+
+/// Deterministic validator candidate set at one height.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ValidatorCandidateSet {
+    /// Final query height resolved by the server.
+    pub block_height: u32,
+    /// System-state id at `block_height` bound into the commitment.
+    pub system_state_id: String,
+    /// Ordering rule applied to `candidates`.
+    pub selection_rule: String,
+    /// Effective maximum number of candidates.
+    pub max_candidates: u32,
+    /// Effective minimum effective energy.
+    pub min_effective_energy: u64,
+    /// Number of eligible passes before truncation to `max_candidates`.
+    pub total_eligible: u64,
+    /// Inscription id of the first candidate, `None` for an empty set.
+    pub winner: Option<String>,
+    /// Candidates in selection order.
+    pub candidates: Vec<ValidatorCandidateItem>,
+    /// Version of the commitment derivation rule.
+    pub commitment_version: String,
+    /// Hash algorithm of the commitment.
+    pub commitment_hash_algo: String,
+    /// Canonical sha256 commitment over the selection parameters and ordered candidates.
+    pub candidate_set_commitment: String,
+}
+
+/// Parameters for `verify_validator_candidate_set`.
+///
+/// These are the candidate-set fields a block body payload carries.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VerifyValidatorCandidateSetParams {
+    /// BTC height the payload claims the candidate set was derived at.
+    pub block_height: u32,
+    /// System-state id claimed by the payload.
+    pub system_state_id: String,
+    /// Ordering rule claimed by the payload.
+    pub selection_rule: String,
+    /// Maximum number of candidates claimed by the payload.
+    pub max_candidates: u32,
+    /// Minimum effective energy claimed by the payload.
+    pub min_effective_energy: u64,
+    /// Candidates in the order carried by the payload.
+    pub candidates: Vec<ValidatorCandidateIdentity>,
+    /// Candidate-set commitment carried by the payload.
+    pub candidate_set_commitment: String,
+    /// Optional consensus selectors pinned by downstream validators.
+    pub context: Option<ConsensusQueryContext>,
+}
+
+/// Result of `verify_validator_candidate_set`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ValidatorCandidateSetVerification {
+    /// Whether the payload matches the candidate set recomputed by the service.
+    pub valid: bool,
+    /// BTC height used for recomputation.
+    pub block_height: u32,
+    /// Mismatch codes, empty when `valid` is true:
+    /// `SELECTION_RULE_MISMATCH`, `COMMITMENT_NOT_SELF_CONSISTENT`,
+    /// `SYSTEM_STATE_ID_MISMATCH`, `CANDIDATE_SET_MISMATCH`, `COMMITMENT_MISMATCH`.
+    pub mismatches: Vec<String>,
+    /// System-state id of the service at `block_height`.
+    pub expected_system_state_id: String,
+    /// Commitment of the candidate set recomputed with the payload parameters.
+    pub expected_candidate_set_commitment: String,
+    /// Winner of the recomputed candidate set.
+    pub expected_winner: Option<String>,
+}
+
+/// UIP-0006 `external_state` that binds one economic view to a replayable BTC history.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct EconomicExternalState {
+    /// BTC height the view was read at.
+    pub btc_height: u32,
+    /// Upstream balance-history consensus snapshot id.
+    pub snapshot_id: String,
+    /// Top-level system-state id consumed by downstream chains.
+    pub system_state_id: String,
+    /// Stable BTC block hash at `btc_height`.
+    pub stable_block_hash: String,
+    /// usdb-indexer local durable state commit.
+    pub local_state_commit: String,
+    /// balance-history query semantics version.
+    pub balance_history_semantics_version: String,
+    /// usdb-index public protocol version.
+    pub usdb_index_protocol_version: String,
+    /// usdb-index formula version.
+    pub usdb_index_formula_version: String,
+}
+
+impl From<&HistoricalStateRefInfo> for EconomicExternalState {
+    fn from(state_ref: &HistoricalStateRefInfo) -> Self {
+        let identity = &state_ref.snapshot_info.consensus_identity;
+        Self {
+            btc_height: state_ref.block_height,
+            snapshot_id: state_ref.snapshot_info.snapshot_id.clone(),
+            system_state_id: state_ref.system_state_info.system_state_id.clone(),
+            stable_block_hash: state_ref.snapshot_info.stable_block_hash.clone(),
+            local_state_commit: state_ref.local_state_commit_info.local_state_commit.clone(),
+            balance_history_semantics_version: identity.balance_history_semantics_version.clone(),
+            usdb_index_protocol_version: identity.usdb_index_protocol_version.clone(),
+            usdb_index_formula_version: identity.usdb_index_formula_version.clone(),
+        }
+    }
+}
+
+/// Aggregate totals of the UIP-0006 economic state view.
+///
+/// Energy totals are canonical decimal strings as required by UIP-0006.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct EconomicStateTotals {
+    /// Number of active passes, standard and collab.
+    pub active_pass_count: u64,
+    /// Number of active standard passes.
+    pub active_standard_pass_count: u64,
+    /// Number of active collab passes.
+    pub active_collab_pass_count: u64,
+    /// Sum of raw energy over all active passes.
+    pub total_raw_energy: String,
+    /// Sum of collab contributions resolved to active Leaders.
+    pub total_collab_contribution: String,
+    /// Sum of effective energy over active standard passes.
+    pub total_effective_energy: String,
+    /// Latest active-balance snapshot at or before the view height, as bound into
+    /// `local_state_commit`.
+    pub active_balance_snapshot: Option<LocalStateActiveBalanceSnapshot>,
+}
+
+/// Parameters for `get_economic_state_view`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GetEconomicStateViewParams {
+    /// Optional query height; `None` resolves to the current local synced height.
+    pub block_height: Option<u32>,
+    /// Optional consensus selectors pinned by downstream validators.
+    pub context: Option<ConsensusQueryContext>,
+}
+
+/// UIP-0006 economic state view at one height.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EconomicStateView {
+    /// View version, currently `uip-0006-usdb-economic-state-view:v1`.
+    pub view_version: String,
+    /// Historical state the totals were read from.
+    pub external_state: EconomicExternalState,
+    /// Aggregate totals at `external_state.btc_height`.
+    pub totals: EconomicStateTotals,
+}
+
+/// Parameters for `get_economic_state_audit_view`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GetEconomicStateAuditViewParams {
+    /// Optional query height; `None` resolves to the current local synced height.
+    pub block_height: Option<u32>,
+    /// Zero-based page index.
+    pub page: usize,
+    /// Page size, must be in `1..=1000`.
+    pub page_size: usize,
+    /// Optional consensus selectors pinned by downstream validators.
+    pub context: Option<ConsensusQueryContext>,
+}
+
+/// Per-pass economic profile listed by the audit view.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct PassEconomicProfile {
+    /// Pass inscription id.
+    pub pass_id: String,
+    /// Canonical owner script hash at the view height.
+    pub owner_script_hash: String,
+    /// Pass state at the view height.
+    pub state: String,
+    /// `standard` or `collab`.
+    pub pass_kind: String,
+    /// Raw energy of the pass itself.
+    pub raw_energy: String,
+    /// Collab contribution received as a Leader.
+    pub collab_contribution: String,
+    /// `raw_energy + collab_contribution`, `0` for collab passes.
+    pub effective_energy: String,
+    /// UIP-0005 level derived from `effective_energy`, `None` before energy formula v2.
+    pub level: Option<u32>,
+    /// UIP-0005 difficulty factor in bps derived from `level`, `None` before energy formula v2.
+    pub difficulty_factor_bps: Option<u32>,
+    /// Number of collab passes contributing to this pass as a Leader.
+    pub collab_breakdown_count: u32,
+}
+
+/// UIP-0006 audit view: the economic state view plus the per-pass profiles behind it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EconomicStateAuditView {
+    /// View version, currently `uip-0006-usdb-economic-state-view:v1`.
+    pub view_version: String,
+    /// Historical state the profiles were read from.
+    pub external_state: EconomicExternalState,
+    /// Aggregate totals over all profiles, not only this page.
+    pub totals: EconomicStateTotals,
+    /// Item order, currently `pass_id_asc`.
+    pub sort: String,
+    /// Requested page index.
+    pub page: usize,
+    /// Requested page size.
+    pub page_size: usize,
+    /// Total number of profiles across all pages.
+    pub total: u64,
+    /// Profiles on this page.
+    pub items: Vec<PassEconomicProfile>,
+}
+
 /// Parameters for `get_active_balance_snapshot`.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GetActiveBalanceSnapshotParams {
@@ -975,6 +1458,16 @@ pub trait UsdbIndexerRpc {
         params: GetPassBlockCommitParams,
     ) -> JsonResult<Option<PassBlockCommitInfo>>;
 
+    /// Returns the persisted pass mutation stream behind one pass block commit.
+    ///
+    /// Returns `None` when no commit exists at the height and business error
+    /// `PASS_BLOCK_MUTATIONS_NOT_AVAILABLE` when the commit predates mutation persistence.
+    #[rpc(name = "get_pass_block_mutations")]
+    fn get_pass_block_mutations(
+        &self,
+        params: GetPassBlockMutationsParams,
+    ) -> JsonResult<Option<PassBlockMutationsInfo>>;
+
     /// Returns the current locally durable core-state commit.
     ///
     /// Returns shared consensus error `SNAPSHOT_NOT_READY` when the node does
@@ -1069,6 +1562,52 @@ pub trait UsdbIndexerRpc {
         params: GetPassEnergyLeaderboardParams,
     ) -> JsonResult<PassEnergyLeaderboardPage>;
 
+    /// Returns the UIP-0004 effective energy view of one pass.
+    #[rpc(name = "get_pass_effective_energy")]
+    fn get_pass_effective_energy(
+        &self,
+        params: GetPassEffectiveEnergyParams,
+    ) -> JsonResult<PassEffectiveEnergySnapshot>;
+
+    /// Returns the UIP-0005 level view of one pass.
+    #[rpc(name = "get_pass_level")]
+    fn get_pass_level(&self, params: GetPassLevelParams) -> JsonResult<PassLevelSnapshot>;
+
+    /// Returns collab passes resolved to one Leader at a target height.
+    #[rpc(name = "get_leader_collab_set_at_height")]
+    fn get_leader_collab_set_at_height(
+        &self,
+        params: GetLeaderCollabSetAtHeightParams,
+    ) -> JsonResult<LeaderCollabSet>;
+
+    /// Returns the deterministic validator candidate set and its commitment at a target height.
+    #[rpc(name = "get_validator_candidate_set")]
+    fn get_validator_candidate_set(
+        &self,
+        params: GetValidatorCandidateSetParams,
+    ) -> JsonResult<ValidatorCandidateSet>;
+
+    /// Recomputes the validator candidate set of a block body payload and compares it.
+    #[rpc(name = "verify_validator_candidate_set")]
+    fn verify_validator_candidate_set(
+        &self,
+        params: VerifyValidatorCandidateSetParams,
+    ) -> JsonResult<ValidatorCandidateSetVerification>;
+
+    /// Returns the UIP-0006 economic state view bound to one historical state.
+    #[rpc(name = "get_economic_state_view")]
+    fn get_economic_state_view(
+        &self,
+        params: GetEconomicStateViewParams,
+    ) -> JsonResult<EconomicStateView>;
+
+    /// Returns the UIP-0006 economic state view with paged per-pass profiles.
+    #[rpc(name = "get_economic_state_audit_view")]
+    fn get_economic_state_audit_view(
+        &self,
+        params: GetEconomicStateAuditViewParams,
+    ) -> JsonResult<EconomicStateAuditView>;
+
     /// Returns invalid passes with optional code filter.
     #[rpc(name = "get_invalid_passes")]
     fn get_invalid_passes(&self, params: GetInvalidPassesParams) -> JsonResult<InvalidPassesPage>;
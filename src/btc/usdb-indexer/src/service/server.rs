@@ -1,7 +1,10 @@
 use super::rpc::*;
 use crate::config::ConfigManagerRef;
-use crate::index::{InscriptionIndexer, MinerPassState};
+use crate::index::{
+    CollabContribution, InscriptionIndexer, MinerPassState, resolve_active_version_set,
+};
 use crate::status::StatusManagerRef;
+use crate::storage::{ActiveMinerPassInfo, StoredPassBlockCommitEntry};
 use jsonrpc_core::IoHandler;
 use jsonrpc_core::{Error as JsonError, ErrorCode, Result as JsonResult};
 use jsonrpc_http_server::{AccessControlAllowOrigin, DomainsValidation, ServerBuilder};
@@ -12,9 +15,13 @@ use std::sync::{Arc, Mutex};
 use std::time::Instant;
 use tokio::sync::watch;
 use usdb_util::{
-    CONSENSUS_SOURCE_CHAIN_BTC, ConsensusQueryContext, ConsensusRpcErrorCode,
-    ConsensusRpcErrorData, ConsensusStateReference, LocalStateActiveBalanceSnapshot,
-    LocalStatePassCommitIdentity, USDB_INDEXER_SERVICE_NAME, build_consensus_snapshot_id,
+    ActiveVersionSet, CONSENSUS_SOURCE_CHAIN_BTC, ConsensusQueryContext, ConsensusRpcErrorCode,
+    ConsensusRpcErrorData, ConsensusStateReference, ENERGY_FORMULA_VERSION_V2,
+    LocalStateActiveBalanceSnapshot, LocalStatePassCommitIdentity, USDB_INDEXER_SERVICE_NAME,
+    VALIDATOR_CANDIDATE_SELECTION_RULE_V1, VALIDATOR_CANDIDATE_SET_COMMITMENT_HASH_ALGO,
+    VALIDATOR_CANDIDATE_SET_COMMITMENT_VERSION, ValidatorCandidateIdentity,
+    ValidatorCandidateSetIdentity, build_consensus_snapshot_id,
+    build_validator_candidate_set_commitment,
 };
 use usdb_util::{USDBScriptHash, parse_script_hash_any};
 
@@ -27,12 +34,27 @@ fn encode_hex(bytes: &[u8]) -> String {
     output
 }
 
+fn build_pass_block_commit_info(entry: StoredPassBlockCommitEntry) -> PassBlockCommitInfo {
+    PassBlockCommitInfo {
+        block_height: entry.block_height,
+        balance_history_block_height: entry.balance_history_block_height,
+        balance_history_block_commit: entry.balance_history_block_commit,
+        mutation_root: entry.mutation_root,
+        block_commit: entry.block_commit,
+        commit_protocol_version: entry.commit_protocol_version,
+        commit_hash_algo: entry.commit_hash_algo,
+    }
+}
+
 const MAX_RPC_PAGE_SIZE: usize = 1_000;
+const DEFAULT_VALIDATOR_CANDIDATE_SET_SIZE: u32 = 100;
 
 #[derive(Clone, Debug)]
 struct PassEnergyLeaderboardCacheEntry {
     resolved_height: u32,
     scope: String,
+    order_by: String,
+    include_level: bool,
     top_k: usize,
     total: u64,
     items: Vec<PassEnergyLeaderboardItem>,
@@ -74,6 +96,23 @@ impl PassEnergyLeaderboardScope {
     }
 }
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum PassEnergyLeaderboardOrder {
+    Energy,
+    EffectiveEnergy,
+    Level,
+}
+
+impl PassEnergyLeaderboardOrder {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Energy => "energy",
+            Self::EffectiveEnergy => "effective_energy",
+            Self::Level => "level",
+        }
+    }
+}
+
 #[derive(Clone)]
 pub struct UsdbIndexerRpcServer {
     config: ConfigManagerRef,
@@ -606,6 +645,7 @@ impl UsdbIndexerRpcServer {
             stable_lag: anchor.stable_lag,
             commit_protocol_version: anchor.commit_protocol_version,
             commit_hash_algo: anchor.commit_hash_algo,
+            balance_state_root: anchor.balance_state_root,
         })))
     }
 
@@ -648,6 +688,7 @@ impl UsdbIndexerRpcServer {
             stable_lag: anchor.stable_lag,
             commit_protocol_version: anchor.commit_protocol_version,
             commit_hash_algo: anchor.commit_hash_algo,
+            balance_state_root: anchor.balance_state_root,
         }))
     }
 
@@ -769,6 +810,59 @@ impl UsdbIndexerRpcServer {
         ))
     }
 
+    // UIP-0008 versions active at block_height. Historical callers must pass the target
+    // height so older heights are never interpreted with the current head's versions.
+    fn active_version_set_at(&self, block_height: u32) -> Result<ActiveVersionSet, JsonError> {
+        resolve_active_version_set(&self.config, block_height).map_err(Self::to_internal_error)
+    }
+
+    // UIP-0005 level thresholds are calibrated on the UIP-0003 v2 energy scale. The
+    // activation registry only lets a level formula activate where energy formula v2 is
+    // active, so levels exist exactly at heights with an active level formula.
+    fn level_formula_applicable(&self, block_height: u32) -> Result<bool, JsonError> {
+        let active_version_set = self.active_version_set_at(block_height)?;
+        Ok(active_version_set.level_formula_version.is_some())
+    }
+
+    // Fail closed for queries whose answer is a level. Returns the active level formula.
+    fn ensure_level_formula_applicable(&self, block_height: u32) -> Result<String, JsonError> {
+        let active_version_set = self.active_version_set_at(block_height)?;
+        if let Some(level_formula_version) = active_version_set.level_formula_version {
+            return Ok(level_formula_version);
+        }
+
+        let (current_snapshot, current_local_state, current_system_state) = self
+            .current_state_for_error_payload()
+            .unwrap_or((None, None, None));
+        Err(Self::to_consensus_error(
+            ConsensusRpcErrorCode::VersionMismatch,
+            self.build_consensus_error_data(
+                Some(block_height),
+                current_snapshot.as_ref(),
+                current_local_state.as_ref(),
+                current_system_state.as_ref(),
+                Some(format!(
+                    "No level formula is active at height {}, level formulas require energy formula {} and the active energy formula is {}",
+                    block_height, ENERGY_FORMULA_VERSION_V2, active_version_set.energy_formula_version
+                )),
+            ),
+        ))
+    }
+
+    fn derive_level_fields(
+        effective_energy: u64,
+        levels_enabled: bool,
+    ) -> (Option<u32>, Option<u32>) {
+        if !levels_enabled {
+            return (None, None);
+        }
+        let level = usdb_util::calc_level(effective_energy);
+        (
+            Some(level),
+            Some(usdb_util::calc_difficulty_factor_bps(level)),
+        )
+    }
+
     fn build_historical_state_ref_info(
         &self,
         block_height: u32,
@@ -778,12 +872,19 @@ impl UsdbIndexerRpcServer {
             self.build_local_state_commit_info_at_height(&snapshot_info, false)?;
         let system_state_info =
             self.build_system_state_info_from_local_state(&local_state_commit_info);
+        let active_version_set = self.active_version_set_at(block_height)?;
 
         Ok(HistoricalStateRefInfo::from(HistoricalStateRefInfoSeed {
             block_height,
             snapshot_info,
             local_state_commit_info,
             system_state_info,
+            active_version_set,
+            activation_registry_id: self
+                .config
+                .activation_registry()
+                .activation_registry_id()
+                .to_string(),
         }))
     }
 
@@ -1194,6 +1295,22 @@ impl UsdbIndexerRpcServer {
         }
     }
 
+    fn parse_leaderboard_order(
+        &self,
+        value: Option<&str>,
+    ) -> Result<PassEnergyLeaderboardOrder, JsonError> {
+        let normalized = value.unwrap_or("energy").trim().to_ascii_lowercase();
+        match normalized.as_str() {
+            "energy" => Ok(PassEnergyLeaderboardOrder::Energy),
+            "effective_energy" => Ok(PassEnergyLeaderboardOrder::EffectiveEnergy),
+            "level" => Ok(PassEnergyLeaderboardOrder::Level),
+            _ => Err(Self::to_invalid_params(format!(
+                "Invalid leaderboard order_by {}, expected energy, effective_energy or level",
+                normalized
+            ))),
+        }
+    }
+
     fn parse_optional_pass_states(
         &self,
         values: Option<Vec<String>>,
@@ -1263,6 +1380,8 @@ impl UsdbIndexerRpcServer {
             prev: pass.prev.into_iter().map(|v| v.to_string()).collect(),
             invalid_code: pass.invalid_code,
             invalid_reason: pass.invalid_reason,
+            leader_pass_id: pass.leader_pass_id.map(|v| v.to_string()),
+            leader_btc_addr: pass.leader_btc_addr,
             owner: history.owner.to_string(),
             state: history.state.as_str().to_string(),
             satpoint: history.satpoint.to_string(),
@@ -1272,6 +1391,35 @@ impl UsdbIndexerRpcServer {
         }))
     }
 
+    fn to_collab_contribution_item(item: CollabContribution) -> CollabContributionItem {
+        CollabContributionItem {
+            collab_pass_id: item.collab_pass_id.to_string(),
+            collab_owner: item.collab_owner.to_string(),
+            collab_raw_energy: item.collab_raw_energy,
+            collab_weight_bps: item.collab_weight_bps,
+            collab_contribution: item.collab_contribution,
+            leader_ref_kind: item.leader_ref_kind.as_str().to_string(),
+            leader_ref_value: item.leader_ref_value,
+        }
+    }
+
+    // Leader resolution by BTC address relies on the unique active owner invariant,
+    // so a duplicate is surfaced as a business error instead of a partial result.
+    fn map_collab_resolution_error(message: String, resolved_height: u32) -> JsonError {
+        if message.contains("Duplicate active owner detected") {
+            Self::to_business_error(
+                ERR_DUPLICATE_ACTIVE_OWNER,
+                "DUPLICATE_ACTIVE_OWNER",
+                json!({
+                    "resolved_height": resolved_height,
+                    "reason": message
+                }),
+            )
+        } else {
+            Self::to_internal_error(message)
+        }
+    }
+
     fn leaderboard_cache_settings(&self) -> (bool, usize) {
         let cfg = &self.config.config().usdb;
         (
@@ -1313,6 +1461,8 @@ impl UsdbIndexerRpcServer {
         &self,
         resolved_height: u32,
         scope: PassEnergyLeaderboardScope,
+        order: PassEnergyLeaderboardOrder,
+        include_level: bool,
         top_k: usize,
         page: usize,
         page_size: usize,
@@ -1324,6 +1474,8 @@ impl UsdbIndexerRpcServer {
         };
         if entry.resolved_height != resolved_height
             || entry.scope != scope.as_str()
+            || entry.order_by != order.as_str()
+            || entry.include_level != include_level
             || entry.top_k != top_k
         {
             return Ok(None);
@@ -1366,6 +1518,8 @@ impl UsdbIndexerRpcServer {
         &self,
         resolved_height: u32,
         scope: PassEnergyLeaderboardScope,
+        order: PassEnergyLeaderboardOrder,
+        include_level: bool,
         top_k: usize,
         total: u64,
         ranked: &[PassEnergyLeaderboardItem],
@@ -1379,6 +1533,8 @@ impl UsdbIndexerRpcServer {
         cache.latest = Some(PassEnergyLeaderboardCacheEntry {
             resolved_height,
             scope: scope.as_str().to_string(),
+            order_by: order.as_str().to_string(),
+            include_level,
             top_k,
             total,
             items: cached_items,
@@ -1389,6 +1545,8 @@ impl UsdbIndexerRpcServer {
         &self,
         resolved_height: u32,
         scope: PassEnergyLeaderboardScope,
+        order: PassEnergyLeaderboardOrder,
+        include_level: bool,
     ) -> Result<(u64, Vec<PassEnergyLeaderboardItem>), JsonError> {
         let build_start = Instant::now();
         let states = scope.states();
@@ -1426,6 +1584,19 @@ impl UsdbIndexerRpcServer {
             rows.extend(page_rows);
         }
 
+        // The collab totals scan touches every collab pass, so plain raw energy rankings
+        // skip it and leave the derived fields empty.
+        let collab_totals = if include_level || order != PassEnergyLeaderboardOrder::Energy {
+            Some(
+                self.indexer
+                    .pass_collab_resolver()
+                    .get_collab_contribution_totals_at_height(resolved_height)
+                    .map_err(|e| Self::map_collab_resolution_error(e, resolved_height))?,
+            )
+        } else {
+            None
+        };
+
         let mut ranked = Vec::with_capacity(rows.len());
         for row in rows {
             let Some(record) = self
@@ -1445,27 +1616,56 @@ impl UsdbIndexerRpcServer {
                 .pass_energy_manager()
                 .project_energy_record_no_balance_change(&record, resolved_height);
 
+            let effective_energy = collab_totals.as_ref().map(|totals| {
+                totals.effective_energy(&row.inscription_id, &projected.state, projected.energy)
+            });
+            let (level, difficulty_factor_bps) = match effective_energy {
+                Some(effective_energy) => {
+                    Self::derive_level_fields(effective_energy, include_level)
+                }
+                None => (None, None),
+            };
+
             ranked.push(PassEnergyLeaderboardItem {
                 inscription_id: row.inscription_id.to_string(),
                 owner: row.owner.to_string(),
                 record_block_height: record.block_height,
                 state: projected.state.as_str().to_string(),
                 energy: projected.energy,
+                effective_energy,
+                level,
+                difficulty_factor_bps,
             });
         }
 
-        ranked.sort_by(|a, b| {
-            b.energy
-                .cmp(&a.energy)
-                .then_with(|| b.record_block_height.cmp(&a.record_block_height))
-                .then_with(|| a.inscription_id.cmp(&b.inscription_id))
-        });
+        match order {
+            PassEnergyLeaderboardOrder::Energy => ranked.sort_by(|a, b| {
+                b.energy
+                    .cmp(&a.energy)
+                    .then_with(|| b.record_block_height.cmp(&a.record_block_height))
+                    .then_with(|| a.inscription_id.cmp(&b.inscription_id))
+            }),
+            PassEnergyLeaderboardOrder::EffectiveEnergy => ranked.sort_by(|a, b| {
+                b.effective_energy
+                    .cmp(&a.effective_energy)
+                    .then_with(|| b.energy.cmp(&a.energy))
+                    .then_with(|| a.inscription_id.cmp(&b.inscription_id))
+            }),
+            PassEnergyLeaderboardOrder::Level => ranked.sort_by(|a, b| {
+                b.level
+                    .cmp(&a.level)
+                    .then_with(|| b.effective_energy.cmp(&a.effective_energy))
+                    .then_with(|| b.energy.cmp(&a.energy))
+                    .then_with(|| a.inscription_id.cmp(&b.inscription_id))
+            }),
+        }
 
         let total = ranked.len() as u64;
         let elapsed_ms = build_start.elapsed().as_millis();
         info!(
-            "Pass energy leaderboard dataset built: module=rpc_server, scope={}, resolved_height={}, pass_count={}, ranked_count={}, missing_energy_count={}, elapsed_ms={}",
+            "Pass energy leaderboard dataset built: module=rpc_server, scope={}, order_by={}, resolved_height={}, pass_count={}, ranked_count={}, missing_energy_count={}, elapsed_ms={}",
             scope.as_str(),
+            order.as_str(),
             resolved_height,
             total_passes,
             total,
@@ -1475,10 +1675,264 @@ impl UsdbIndexerRpcServer {
 
         Ok((total, ranked))
     }
+
+    fn load_history_passes_at_height_by_states(
+        &self,
+        block_height: u32,
+        states: &[MinerPassState],
+    ) -> Result<Vec<ActiveMinerPassInfo>, JsonError> {
+        let storage = self.indexer.miner_pass_storage();
+        let total_passes = storage
+            .get_pass_count_from_history_at_height_by_states(block_height, states)
+            .map_err(Self::to_internal_error)?;
+        let load_page_size = self.config.config().usdb.active_address_page_size.max(1);
+        let mut rows = Vec::new();
+        let mut page = 0;
+        while (rows.len() as u64) < total_passes {
+            let page_rows = storage
+                .get_passes_by_page_from_history_at_height_by_states(
+                    page,
+                    load_page_size,
+                    block_height,
+                    states,
+                )
+                .map_err(Self::to_internal_error)?;
+            if page_rows.is_empty() {
+                break;
+            }
+            rows.extend(page_rows);
+            page += 1;
+        }
+        Ok(rows)
+    }
+
+    fn validate_max_candidates(&self, max_candidates: u32) -> Result<(), JsonError> {
+        if max_candidates == 0 || max_candidates as usize > MAX_RPC_PAGE_SIZE {
+            return Err(Self::to_invalid_params(format!(
+                "Invalid max_candidates {}, expected 1..={}",
+                max_candidates, MAX_RPC_PAGE_SIZE
+            )));
+        }
+        Ok(())
+    }
+
+    // Candidate set ordering follows VALIDATOR_CANDIDATE_SELECTION_RULE_V1: only active
+    // standard passes are eligible, ranked by effective energy desc then pass id asc.
+    // Record heights are deliberately not a tie-breaker so ETHW validators can reproduce
+    // the order from the committed candidate fields alone.
+    fn build_validator_candidate_set(
+        &self,
+        block_height: u32,
+        max_candidates: u32,
+        min_effective_energy: u64,
+    ) -> Result<ValidatorCandidateSet, JsonError> {
+        let system_state_id = self
+            .build_historical_state_ref_info(block_height)?
+            .system_state_info
+            .system_state_id;
+
+        let rows =
+            self.load_history_passes_at_height_by_states(block_height, &[MinerPassState::Active])?;
+        let levels_enabled = self.level_formula_applicable(block_height)?;
+
+        let collab_totals = self
+            .indexer
+            .pass_collab_resolver()
+            .get_collab_contribution_totals_at_height(block_height)
+            .map_err(|e| Self::map_collab_resolution_error(e, block_height))?;
+
+        let mut candidates = Vec::with_capacity(rows.len());
+        for row in rows {
+            if collab_totals.is_active_collab_pass(&row.inscription_id) {
+                continue;
+            }
+
+            let Some(record) = self
+                .indexer
+                .pass_energy_manager()
+                .get_pass_energy_record_at_or_before(&row.inscription_id, block_height)
+                .map_err(Self::to_internal_error)?
+            else {
+                warn!(
+                    "Missing energy record when building validator candidate set: inscription_id={}, block_height={}",
+                    row.inscription_id, block_height
+                );
+                continue;
+            };
+            let projected = self
+                .indexer
+                .pass_energy_manager()
+                .project_energy_record_no_balance_change(&record, block_height);
+
+            let effective_energy = collab_totals.effective_energy(
+                &row.inscription_id,
+                &projected.state,
+                projected.energy,
+            );
+            if effective_energy < min_effective_energy {
+                continue;
+            }
+
+            let (level, difficulty_factor_bps) =
+                Self::derive_level_fields(effective_energy, levels_enabled);
+            candidates.push(ValidatorCandidateItem {
+                rank: 0,
+                inscription_id: row.inscription_id.to_string(),
+                owner: row.owner.to_string(),
+                raw_energy: projected.energy,
+                collab_contribution_total: collab_totals
+                    .collab_contribution_total(&row.inscription_id),
+                effective_energy,
+                level,
+                difficulty_factor_bps,
+            });
+        }
+
+        candidates.sort_by(|a, b| {
+            b.effective_energy
+                .cmp(&a.effective_energy)
+                .then_with(|| a.inscription_id.cmp(&b.inscription_id))
+        });
+        let total_eligible = candidates.len() as u64;
+        candidates.truncate(max_candidates as usize);
+        for (rank, candidate) in candidates.iter_mut().enumerate() {
+            candidate.rank = rank as u32;
+        }
+
+        let identity = ValidatorCandidateSetIdentity {
+            system_state_id: system_state_id.clone(),
+            block_height,
+            selection_rule: VALIDATOR_CANDIDATE_SELECTION_RULE_V1.to_string(),
+            max_candidates,
+            min_effective_energy,
+            candidates: candidates
+                .iter()
+                .map(|candidate| ValidatorCandidateIdentity {
+                    inscription_id: candidate.inscription_id.clone(),
+                    owner: candidate.owner.clone(),
+                    effective_energy: candidate.effective_energy,
+                })
+                .collect(),
+        };
+
+        Ok(ValidatorCandidateSet {
+            block_height,
+            system_state_id,
+            selection_rule: identity.selection_rule.clone(),
+            max_candidates,
+            min_effective_energy,
+            total_eligible,
+            winner: candidates
+                .first()
+                .map(|candidate| candidate.inscription_id.clone()),
+            candidates,
+            commitment_version: VALIDATOR_CANDIDATE_SET_COMMITMENT_VERSION.to_string(),
+            commitment_hash_algo: VALIDATOR_CANDIDATE_SET_COMMITMENT_HASH_ALGO.to_string(),
+            candidate_set_commitment: build_validator_candidate_set_commitment(&identity),
+        })
+    }
+
+    // UIP-0006 profiles of every active pass at the state ref height, in pass_id order.
+    // Totals are folded from the same profiles so the aggregate view and the audit view
+    // of one height can never disagree.
+    fn build_economic_state(
+        &self,
+        state_ref: &HistoricalStateRefInfo,
+    ) -> Result<(EconomicStateTotals, Vec<PassEconomicProfile>), JsonError> {
+        let block_height = state_ref.block_height;
+        let rows =
+            self.load_history_passes_at_height_by_states(block_height, &[MinerPassState::Active])?;
+        let levels_enabled = self.level_formula_applicable(block_height)?;
+        let collab_totals = self
+            .indexer
+            .pass_collab_resolver()
+            .get_collab_contribution_totals_at_height(block_height)
+            .map_err(|e| Self::map_collab_resolution_error(e, block_height))?;
+
+        let mut active_collab_pass_count = 0u64;
+        let mut total_raw_energy = 0u128;
+        let mut total_collab_contribution = 0u128;
+        let mut total_effective_energy = 0u128;
+        let mut profiles = Vec::with_capacity(rows.len());
+        for row in rows {
+            let raw_energy = match self
+                .indexer
+                .pass_energy_manager()
+                .get_pass_energy_record_at_or_before(&row.inscription_id, block_height)
+                .map_err(Self::to_internal_error)?
+            {
+                Some(record) => {
+                    self.indexer
+                        .pass_energy_manager()
+                        .project_energy_record_no_balance_change(&record, block_height)
+                        .energy
+                }
+                None => 0,
+            };
+
+            let is_collab = collab_totals.is_active_collab_pass(&row.inscription_id);
+            let (collab_contribution, collab_breakdown_count) = if is_collab {
+                active_collab_pass_count += 1;
+                (0, 0)
+            } else {
+                (
+                    collab_totals.collab_contribution_total(&row.inscription_id),
+                    collab_totals.collab_breakdown_count(&row.inscription_id),
+                )
+            };
+            let effective_energy = collab_totals.effective_energy(
+                &row.inscription_id,
+                &MinerPassState::Active,
+                raw_energy,
+            );
+            total_raw_energy += raw_energy as u128;
+            total_collab_contribution += collab_contribution as u128;
+            total_effective_energy += effective_energy as u128;
+
+            let (level, difficulty_factor_bps) =
+                Self::derive_level_fields(effective_energy, levels_enabled);
+            profiles.push(PassEconomicProfile {
+                pass_id: row.inscription_id.to_string(),
+                owner_script_hash: row.owner.to_string(),
+                state: MinerPassState::Active.as_str().to_string(),
+                pass_kind: if is_collab { "collab" } else { "standard" }.to_string(),
+                raw_energy: raw_energy.to_string(),
+                collab_contribution: collab_contribution.to_string(),
+                effective_energy: effective_energy.to_string(),
+                level,
+                difficulty_factor_bps,
+                collab_breakdown_count,
+            });
+        }
+        profiles.sort_by(|a, b| a.pass_id.cmp(&b.pass_id));
+
+        let active_pass_count = profiles.len() as u64;
+        let totals = EconomicStateTotals {
+            active_pass_count,
+            active_standard_pass_count: active_pass_count - active_collab_pass_count,
+            active_collab_pass_count,
+            total_raw_energy: total_raw_energy.to_string(),
+            total_collab_contribution: total_collab_contribution.to_string(),
+            total_effective_energy: total_effective_energy.to_string(),
+            active_balance_snapshot: state_ref
+                .local_state_commit_info
+                .latest_active_balance_snapshot
+                .clone(),
+        };
+        Ok((totals, profiles))
+    }
 }
 
 impl UsdbIndexerRpc for UsdbIndexerRpcServer {
     fn get_rpc_info(&self) -> JsonResult<RpcInfo> {
+        let active_version_set = match self.synced_height()? {
+            Some(height) => Some(self.active_version_set_at(height)?),
+            None => None,
+        };
+        let active_version_set_id = active_version_set
+            .as_ref()
+            .map(usdb_util::build_active_version_set_id);
+
         Ok(RpcInfo {
             service: "usdb-indexer".to_string(),
             api_version: "1.0.0".to_string(),
@@ -1486,6 +1940,7 @@ impl UsdbIndexerRpc for UsdbIndexerRpcServer {
             features: vec![
                 "snapshot_info".to_string(),
                 "pass_block_commit".to_string(),
+                "pass_block_mutations".to_string(),
                 "local_state_commit_info".to_string(),
                 "system_state_info".to_string(),
                 "readiness".to_string(),
@@ -1499,11 +1954,35 @@ impl UsdbIndexerRpc for UsdbIndexerRpcServer {
                 "energy_snapshot".to_string(),
                 "energy_range".to_string(),
                 "pass_energy_leaderboard".to_string(),
+                "pass_effective_energy".to_string(),
+                "pass_level".to_string(),
+                "leader_collab_set_at_height".to_string(),
+                "validator_candidate_set".to_string(),
+                "verify_validator_candidate_set".to_string(),
+                "economic_state_view".to_string(),
+                "economic_state_audit_view".to_string(),
                 "invalid_passes".to_string(),
                 "active_balance_snapshot".to_string(),
                 "latest_active_balance_snapshot".to_string(),
+                "activation_registry".to_string(),
                 "stop".to_string(),
-            ],
+            ]
+            .into_iter()
+            .chain(
+                self.config
+                    .config()
+                    .usdb
+                    .ws_server_enabled
+                    .then(|| SUBSCRIBE_PASS_BLOCK_COMMITS.to_string()),
+            )
+            .collect(),
+            activation_registry_id: self
+                .config
+                .activation_registry()
+                .activation_registry_id()
+                .to_string(),
+            active_version_set,
+            active_version_set_id,
         })
     }
 
@@ -1543,14 +2022,48 @@ impl UsdbIndexerRpc for UsdbIndexerRpcServer {
             .get_pass_block_commit(resolved_height)
             .map_err(Self::to_internal_error)?;
 
-        Ok(entry.map(|entry| PassBlockCommitInfo {
-            block_height: entry.block_height,
-            balance_history_block_height: entry.balance_history_block_height,
-            balance_history_block_commit: entry.balance_history_block_commit,
-            mutation_root: entry.mutation_root,
-            block_commit: entry.block_commit,
-            commit_protocol_version: entry.commit_protocol_version,
-            commit_hash_algo: entry.commit_hash_algo,
+        Ok(entry.map(build_pass_block_commit_info))
+    }
+
+    fn get_pass_block_mutations(
+        &self,
+        params: GetPassBlockMutationsParams,
+    ) -> JsonResult<Option<PassBlockMutationsInfo>> {
+        let resolved_height = self.resolve_height(params.block_height)?;
+        let storage = self.indexer.miner_pass_storage();
+        let Some(entry) = storage
+            .get_pass_block_commit(resolved_height)
+            .map_err(Self::to_internal_error)?
+        else {
+            return Ok(None);
+        };
+
+        let mutations = storage
+            .get_pass_block_mutations(resolved_height)
+            .map_err(Self::to_internal_error)?
+            .ok_or_else(|| {
+                Self::to_business_error(
+                    ERR_PASS_BLOCK_MUTATIONS_NOT_AVAILABLE,
+                    "PASS_BLOCK_MUTATIONS_NOT_AVAILABLE",
+                    json!({
+                        "resolved_height": resolved_height,
+                        "mutation_root": entry.mutation_root,
+                    }),
+                )
+            })?;
+        let previous_block_commit = match resolved_height.checked_sub(1) {
+            Some(prev_height) => storage
+                .get_pass_block_commit(prev_height)
+                .map_err(Self::to_internal_error)?
+                .map(|prev| prev.block_commit),
+            None => None,
+        };
+
+        Ok(Some(PassBlockMutationsInfo {
+            commit: build_pass_block_commit_info(entry),
+            previous_block_commit,
+            mutation_count: mutations.len() as u32,
+            mutations,
         }))
     }
 
@@ -1968,6 +2481,12 @@ impl UsdbIndexerRpc for UsdbIndexerRpcServer {
 
         let resolved_height = self.resolve_height(params.at_height)?;
         let scope = self.parse_leaderboard_scope(params.scope.as_deref())?;
+        let order = self.parse_leaderboard_order(params.order_by.as_deref())?;
+        let include_level =
+            params.include_level.unwrap_or(false) || order == PassEnergyLeaderboardOrder::Level;
+        if include_level {
+            self.ensure_level_formula_applicable(resolved_height)?;
+        }
         let call_start = Instant::now();
         let (cache_enabled, cache_top_k) = self.leaderboard_cache_settings();
         let offset = Self::pagination_offset(params.page, params.page_size)?;
@@ -1978,13 +2497,16 @@ impl UsdbIndexerRpc for UsdbIndexerRpcServer {
                 if let Some(cached_page) = self.try_get_cached_leaderboard_page(
                     resolved_height,
                     scope,
+                    order,
+                    include_level,
                     cache_top_k,
                     params.page,
                     params.page_size,
                 )? {
                     info!(
-                        "Pass energy leaderboard top-k overflow served from cache metadata: module=rpc_server, scope={}, resolved_height={}, top_k={}, page={}, page_size={}, elapsed_ms={}",
+                        "Pass energy leaderboard top-k overflow served from cache metadata: module=rpc_server, scope={}, order_by={}, resolved_height={}, top_k={}, page={}, page_size={}, elapsed_ms={}",
                         scope.as_str(),
+                        order.as_str(),
                         resolved_height,
                         cache_top_k,
                         params.page,
@@ -1996,8 +2518,9 @@ impl UsdbIndexerRpc for UsdbIndexerRpcServer {
             }
 
             info!(
-                "Pass energy leaderboard top-k overflow returned empty: module=rpc_server, scope={}, resolved_height={}, top_k={}, at_height={:?}, page={}, page_size={}, elapsed_ms={}",
+                "Pass energy leaderboard top-k overflow returned empty: module=rpc_server, scope={}, order_by={}, resolved_height={}, top_k={}, at_height={:?}, page={}, page_size={}, elapsed_ms={}",
                 scope.as_str(),
+                order.as_str(),
                 resolved_height,
                 cache_top_k,
                 params.at_height,
@@ -2016,13 +2539,16 @@ impl UsdbIndexerRpc for UsdbIndexerRpcServer {
             if let Some(cached_page) = self.try_get_cached_leaderboard_page(
                 resolved_height,
                 scope,
+                order,
+                include_level,
                 cache_top_k,
                 params.page,
                 params.page_size,
             )? {
                 info!(
-                    "Pass energy leaderboard served from cache: module=rpc_server, scope={}, resolved_height={}, page={}, page_size={}, total={}, elapsed_ms={}",
+                    "Pass energy leaderboard served from cache: module=rpc_server, scope={}, order_by={}, resolved_height={}, page={}, page_size={}, total={}, elapsed_ms={}",
                     scope.as_str(),
+                    order.as_str(),
                     resolved_height,
                     params.page,
                     params.page_size,
@@ -2033,8 +2559,12 @@ impl UsdbIndexerRpc for UsdbIndexerRpcServer {
             }
         }
 
-        let (raw_total, ranked) =
-            self.build_pass_energy_leaderboard_dataset(resolved_height, scope)?;
+        let (raw_total, ranked) = self.build_pass_energy_leaderboard_dataset(
+            resolved_height,
+            scope,
+            order,
+            include_level,
+        )?;
         let capped_total = raw_total.min(cache_top_k as u64);
         let capped_len = capped_total as usize;
         let capped_ranked = if ranked.len() > capped_len {
@@ -2053,13 +2583,16 @@ impl UsdbIndexerRpc for UsdbIndexerRpcServer {
             self.update_leaderboard_cache(
                 resolved_height,
                 scope,
+                order,
+                include_level,
                 cache_top_k,
                 capped_total,
                 capped_ranked,
             );
             info!(
-                "Pass energy leaderboard cache refreshed: module=rpc_server, scope={}, resolved_height={}, top_k={}, raw_total={}, capped_total={}, page={}, page_size={}, elapsed_ms={}",
+                "Pass energy leaderboard cache refreshed: module=rpc_server, scope={}, order_by={}, resolved_height={}, top_k={}, raw_total={}, capped_total={}, page={}, page_size={}, elapsed_ms={}",
                 scope.as_str(),
+                order.as_str(),
                 resolved_height,
                 cache_top_k,
                 raw_total,
@@ -2070,8 +2603,9 @@ impl UsdbIndexerRpc for UsdbIndexerRpcServer {
             );
         } else {
             info!(
-                "Pass energy leaderboard served without cache: module=rpc_server, scope={}, resolved_height={}, at_height={:?}, top_k={}, raw_total={}, capped_total={}, page={}, page_size={}, elapsed_ms={}",
+                "Pass energy leaderboard served without cache: module=rpc_server, scope={}, order_by={}, resolved_height={}, at_height={:?}, top_k={}, raw_total={}, capped_total={}, page={}, page_size={}, elapsed_ms={}",
                 scope.as_str(),
+                order.as_str(),
                 resolved_height,
                 params.at_height,
                 cache_top_k,
@@ -2090,67 +2624,313 @@ impl UsdbIndexerRpc for UsdbIndexerRpcServer {
         })
     }
 
-    fn get_invalid_passes(&self, params: GetInvalidPassesParams) -> JsonResult<InvalidPassesPage> {
-        self.validate_pagination(params.page, params.page_size)?;
+    fn get_pass_effective_energy(
+        &self,
+        params: GetPassEffectiveEnergyParams,
+    ) -> JsonResult<PassEffectiveEnergySnapshot> {
+        let inscription_id = self.parse_inscription_id(&params.inscription_id)?;
+        let query_height =
+            self.resolve_height_for_contextual_query(params.block_height, params.context.as_ref())?;
+        self.ensure_history_height_retained(query_height, "historical state")?;
 
-        let resolved_to_height = self.resolve_height_range(params.from_height, params.to_height)?;
-        let storage = self.indexer.miner_pass_storage();
-        let total = storage
-            .get_invalid_pass_count_in_height_range(
-                params.from_height,
-                resolved_to_height,
-                params.error_code.as_deref(),
-            )
-            .map_err(Self::to_internal_error)?;
-        let rows = storage
-            .get_invalid_passes_by_page_in_height_range(
-                params.from_height,
-                resolved_to_height,
-                params.error_code.as_deref(),
-                params.page,
-                params.page_size,
-            )
-            .map_err(Self::to_internal_error)?;
+        let view = self
+            .indexer
+            .pass_collab_resolver()
+            .get_pass_effective_energy_at_height(&inscription_id, query_height)
+            .map_err(|e| Self::map_collab_resolution_error(e, query_height))?;
+        let Some(view) = view else {
+            return Err(Self::to_business_error(
+                ERR_PASS_NOT_FOUND,
+                "PASS_NOT_FOUND",
+                json!({
+                    "inscription_id": params.inscription_id,
+                    "query_block_height": query_height
+                }),
+            ));
+        };
 
-        Ok(InvalidPassesPage {
-            resolved_height: resolved_to_height,
-            total,
-            items: rows
+        Ok(PassEffectiveEnergySnapshot {
+            inscription_id: view.inscription_id.to_string(),
+            query_block_height: view.block_height,
+            state: view.state.as_str().to_string(),
+            is_collab: view.is_collab,
+            raw_energy: view.raw_energy,
+            collab_contribution_total: view.collab_contribution_total,
+            effective_energy: view.effective_energy,
+            collabs: view
+                .collabs
                 .into_iter()
-                .map(|item| InvalidPassItem {
-                    inscription_id: item.inscription_id.to_string(),
-                    inscription_number: item.inscription_number,
-                    mint_txid: item.mint_txid.to_string(),
-                    mint_block_height: item.mint_block_height,
-                    mint_owner: item.mint_owner.to_string(),
-                    eth_main: item.eth_main,
-                    eth_collab: item.eth_collab,
-                    prev: item.prev.into_iter().map(|v| v.to_string()).collect(),
-                    invalid_code: item.invalid_code,
-                    invalid_reason: item.invalid_reason,
-                    owner: item.owner.to_string(),
-                    state: item.state.as_str().to_string(),
-                    satpoint: item.satpoint.to_string(),
-                })
+                .map(Self::to_collab_contribution_item)
                 .collect(),
         })
     }
 
-    fn get_active_balance_snapshot(
-        &self,
-        params: GetActiveBalanceSnapshotParams,
-    ) -> JsonResult<RpcActiveBalanceSnapshot> {
-        let upstream_snapshot = self.upstream_snapshot_info()?;
-        let local_state = upstream_snapshot.as_ref().and_then(|snapshot| {
-            self.build_local_state_commit_info_from_snapshot(snapshot)
-                .ok()
-        });
-        let system_state = local_state
-            .as_ref()
-            .map(|local_state| self.build_system_state_info_from_local_state(local_state));
-        let requested_height =
-            self.resolve_height_with_consensus_error(Some(params.block_height))?;
-        let active_balance_snapshot = self
+    fn get_pass_level(&self, params: GetPassLevelParams) -> JsonResult<PassLevelSnapshot> {
+        let inscription_id = self.parse_inscription_id(&params.inscription_id)?;
+        let query_height =
+            self.resolve_height_for_contextual_query(params.block_height, params.context.as_ref())?;
+        self.ensure_history_height_retained(query_height, "historical state")?;
+        let level_formula_version = self.ensure_level_formula_applicable(query_height)?;
+
+        let view = self
+            .indexer
+            .pass_collab_resolver()
+            .get_pass_effective_energy_at_height(&inscription_id, query_height)
+            .map_err(|e| Self::map_collab_resolution_error(e, query_height))?;
+        let Some(view) = view else {
+            return Err(Self::to_business_error(
+                ERR_PASS_NOT_FOUND,
+                "PASS_NOT_FOUND",
+                json!({
+                    "inscription_id": params.inscription_id,
+                    "query_block_height": query_height
+                }),
+            ));
+        };
+
+        let level = usdb_util::calc_level(view.effective_energy);
+        Ok(PassLevelSnapshot {
+            inscription_id: view.inscription_id.to_string(),
+            query_block_height: view.block_height,
+            state: view.state.as_str().to_string(),
+            is_collab: view.is_collab,
+            effective_energy: view.effective_energy,
+            level,
+            difficulty_factor_bps: usdb_util::calc_difficulty_factor_bps(level),
+            next_level_threshold: usdb_util::level_threshold(level + 1),
+            level_formula_version,
+        })
+    }
+
+    fn get_leader_collab_set_at_height(
+        &self,
+        params: GetLeaderCollabSetAtHeightParams,
+    ) -> JsonResult<LeaderCollabSet> {
+        let leader_pass_id = self.parse_inscription_id(&params.leader_pass_id)?;
+        let resolved_height =
+            self.resolve_height_for_contextual_query(params.at_height, params.context.as_ref())?;
+        self.ensure_history_height_retained(resolved_height, "historical state")?;
+
+        let resolver = self.indexer.pass_collab_resolver();
+        let leader_active = resolver
+            .is_active_standard_pass_at_height(&leader_pass_id, resolved_height)
+            .map_err(Self::to_internal_error)?;
+        let items = resolver
+            .get_leader_collab_set_at_height(&leader_pass_id, resolved_height)
+            .map_err(|e| Self::map_collab_resolution_error(e, resolved_height))?;
+        let total_contribution = items.iter().fold(0u64, |acc, item| {
+            acc.saturating_add(item.collab_contribution)
+        });
+
+        Ok(LeaderCollabSet {
+            leader_pass_id: leader_pass_id.to_string(),
+            resolved_height,
+            leader_active,
+            total_contribution,
+            items: items
+                .into_iter()
+                .map(Self::to_collab_contribution_item)
+                .collect(),
+        })
+    }
+
+    fn get_validator_candidate_set(
+        &self,
+        params: GetValidatorCandidateSetParams,
+    ) -> JsonResult<ValidatorCandidateSet> {
+        let max_candidates = params
+            .max_candidates
+            .unwrap_or(DEFAULT_VALIDATOR_CANDIDATE_SET_SIZE);
+        self.validate_max_candidates(max_candidates)?;
+        let block_height =
+            self.resolve_height_for_contextual_query(params.block_height, params.context.as_ref())?;
+        self.ensure_history_height_retained(block_height, "historical state")?;
+
+        self.build_validator_candidate_set(
+            block_height,
+            max_candidates,
+            params.min_effective_energy.unwrap_or(0),
+        )
+    }
+
+    fn verify_validator_candidate_set(
+        &self,
+        params: VerifyValidatorCandidateSetParams,
+    ) -> JsonResult<ValidatorCandidateSetVerification> {
+        self.validate_max_candidates(params.max_candidates)?;
+        let block_height = self.resolve_height_for_contextual_query(
+            Some(params.block_height),
+            params.context.as_ref(),
+        )?;
+        self.ensure_history_height_retained(block_height, "historical state")?;
+
+        let expected = self.build_validator_candidate_set(
+            block_height,
+            params.max_candidates,
+            params.min_effective_energy,
+        )?;
+
+        let provided_commitment =
+            build_validator_candidate_set_commitment(&ValidatorCandidateSetIdentity {
+                system_state_id: params.system_state_id.clone(),
+                block_height: params.block_height,
+                selection_rule: params.selection_rule.clone(),
+                max_candidates: params.max_candidates,
+                min_effective_energy: params.min_effective_energy,
+                candidates: params.candidates.clone(),
+            });
+        let expected_candidates = expected
+            .candidates
+            .iter()
+            .map(|candidate| ValidatorCandidateIdentity {
+                inscription_id: candidate.inscription_id.clone(),
+                owner: candidate.owner.clone(),
+                effective_energy: candidate.effective_energy,
+            })
+            .collect::<Vec<_>>();
+
+        let mut mismatches = Vec::new();
+        if params.selection_rule != expected.selection_rule {
+            mismatches.push("SELECTION_RULE_MISMATCH".to_string());
+        }
+        if params.candidate_set_commitment != provided_commitment {
+            mismatches.push("COMMITMENT_NOT_SELF_CONSISTENT".to_string());
+        }
+        if params.system_state_id != expected.system_state_id {
+            mismatches.push("SYSTEM_STATE_ID_MISMATCH".to_string());
+        }
+        if params.candidates != expected_candidates {
+            mismatches.push("CANDIDATE_SET_MISMATCH".to_string());
+        }
+        if params.candidate_set_commitment != expected.candidate_set_commitment {
+            mismatches.push("COMMITMENT_MISMATCH".to_string());
+        }
+
+        if !mismatches.is_empty() {
+            warn!(
+                "Validator candidate set verification failed: module=rpc_server, block_height={}, mismatches={:?}",
+                block_height, mismatches
+            );
+        }
+
+        Ok(ValidatorCandidateSetVerification {
+            valid: mismatches.is_empty(),
+            block_height,
+            mismatches,
+            expected_system_state_id: expected.system_state_id,
+            expected_candidate_set_commitment: expected.candidate_set_commitment,
+            expected_winner: expected.winner,
+        })
+    }
+
+    fn get_economic_state_view(
+        &self,
+        params: GetEconomicStateViewParams,
+    ) -> JsonResult<EconomicStateView> {
+        let block_height =
+            self.resolve_height_for_contextual_query(params.block_height, params.context.as_ref())?;
+        self.ensure_history_height_retained(block_height, "historical state")?;
+        let state_ref = self.build_historical_state_ref_info(block_height)?;
+        let (totals, _) = self.build_economic_state(&state_ref)?;
+
+        Ok(EconomicStateView {
+            view_version: ECONOMIC_STATE_VIEW_VERSION.to_string(),
+            external_state: EconomicExternalState::from(&state_ref),
+            totals,
+        })
+    }
+
+    fn get_economic_state_audit_view(
+        &self,
+        params: GetEconomicStateAuditViewParams,
+    ) -> JsonResult<EconomicStateAuditView> {
+        self.validate_pagination(params.page, params.page_size)?;
+        let block_height =
+            self.resolve_height_for_contextual_query(params.block_height, params.context.as_ref())?;
+        self.ensure_history_height_retained(block_height, "historical state")?;
+        let state_ref = self.build_historical_state_ref_info(block_height)?;
+        let (totals, profiles) = self.build_economic_state(&state_ref)?;
+
+        let total = profiles.len() as u64;
+        let items = profiles
+            .into_iter()
+            .skip(params.page * params.page_size)
+            .take(params.page_size)
+            .collect();
+
+        Ok(EconomicStateAuditView {
+            view_version: ECONOMIC_STATE_VIEW_VERSION.to_string(),
+            external_state: EconomicExternalState::from(&state_ref),
+            totals,
+            sort: ECONOMIC_STATE_AUDIT_SORT.to_string(),
+            page: params.page,
+            page_size: params.page_size,
+            total,
+            items,
+        })
+    }
+
+    fn get_invalid_passes(&self, params: GetInvalidPassesParams) -> JsonResult<InvalidPassesPage> {
+        self.validate_pagination(params.page, params.page_size)?;
+
+        let resolved_to_height = self.resolve_height_range(params.from_height, params.to_height)?;
+        let storage = self.indexer.miner_pass_storage();
+        let total = storage
+            .get_invalid_pass_count_in_height_range(
+                params.from_height,
+                resolved_to_height,
+                params.error_code.as_deref(),
+            )
+            .map_err(Self::to_internal_error)?;
+        let rows = storage
+            .get_invalid_passes_by_page_in_height_range(
+                params.from_height,
+                resolved_to_height,
+                params.error_code.as_deref(),
+                params.page,
+                params.page_size,
+            )
+            .map_err(Self::to_internal_error)?;
+
+        Ok(InvalidPassesPage {
+            resolved_height: resolved_to_height,
+            total,
+            items: rows
+                .into_iter()
+                .map(|item| InvalidPassItem {
+                    inscription_id: item.inscription_id.to_string(),
+                    inscription_number: item.inscription_number,
+                    mint_txid: item.mint_txid.to_string(),
+                    mint_block_height: item.mint_block_height,
+                    mint_owner: item.mint_owner.to_string(),
+                    eth_main: item.eth_main,
+                    eth_collab: item.eth_collab,
+                    prev: item.prev.into_iter().map(|v| v.to_string()).collect(),
+                    invalid_code: item.invalid_code,
+                    invalid_reason: item.invalid_reason,
+                    owner: item.owner.to_string(),
+                    state: item.state.as_str().to_string(),
+                    satpoint: item.satpoint.to_string(),
+                })
+                .collect(),
+        })
+    }
+
+    fn get_active_balance_snapshot(
+        &self,
+        params: GetActiveBalanceSnapshotParams,
+    ) -> JsonResult<RpcActiveBalanceSnapshot> {
+        let upstream_snapshot = self.upstream_snapshot_info()?;
+        let local_state = upstream_snapshot.as_ref().and_then(|snapshot| {
+            self.build_local_state_commit_info_from_snapshot(snapshot)
+                .ok()
+        });
+        let system_state = local_state
+            .as_ref()
+            .map(|local_state| self.build_system_state_info_from_local_state(local_state));
+        let requested_height =
+            self.resolve_height_with_consensus_error(Some(params.block_height))?;
+        let active_balance_snapshot = self
             .indexer
             .miner_pass_storage()
             .get_active_balance_snapshot(requested_height)
@@ -2219,12 +2999,15 @@ mod tests {
     use super::*;
     use crate::config::{ConfigManager, IndexerConfig};
     use crate::index::energy_formula::calc_growth_delta;
-    use crate::index::{InscriptionIndexer, MinerPassState, PassBlockCommitEntry};
+    use crate::index::{
+        InscriptionIndexer, MinerPassState, PassBlockCommitEntry, PassBlockMutation,
+        PassBlockMutationCollector,
+    };
     use crate::output::IndexOutput;
     use crate::status::StatusManager;
     use crate::storage::{MinerPassInfo, PassEnergyRecord};
     use bitcoincore_rpc::bitcoin::hashes::Hash;
-    use bitcoincore_rpc::bitcoin::{OutPoint, ScriptBuf, Txid};
+    use bitcoincore_rpc::bitcoin::{Network, OutPoint, ScriptBuf, Txid};
     use ord::InscriptionId;
     use ordinals::SatPoint;
     use std::path::PathBuf;
@@ -2283,6 +3066,8 @@ mod tests {
             prev: Vec::new(),
             invalid_code: None,
             invalid_reason: None,
+            leader_pass_id: None,
+            leader_btc_addr: None,
             owner,
             state: MinerPassState::Active,
         }
@@ -2341,30 +3126,7 @@ mod tests {
         // checks would classify every query as pruned before the fixture data
         // is even inserted.
         config_file.usdb.genesis_block_height = 0;
-        std::fs::write(
-            root_dir.join("config.json"),
-            serde_json::to_vec_pretty(&config_file).unwrap(),
-        )
-        .unwrap();
-        let config = Arc::new(ConfigManager::load(Some(root_dir.clone())).unwrap());
-        let output = Arc::new(IndexOutput::new());
-        let status = Arc::new(StatusManager::new(config.clone(), output).unwrap());
-        let indexer = Arc::new(InscriptionIndexer::new(config.clone(), status.clone()).unwrap());
-
-        indexer
-            .miner_pass_storage()
-            .update_synced_btc_block_height(synced_height)
-            .unwrap();
-
-        let (shutdown_tx, _shutdown_rx) = tokio::sync::watch::channel(());
-        let server = UsdbIndexerRpcServer::new(
-            config,
-            status,
-            indexer,
-            "127.0.0.1:0".parse().unwrap(),
-            shutdown_tx,
-        );
-        (server, root_dir)
+        build_server_in(root_dir, &config_file, synced_height)
     }
 
     fn build_server_with_genesis(
@@ -2375,9 +3137,28 @@ mod tests {
         let root_dir = test_root_dir(tag);
         let mut config_file = IndexerConfig::default();
         config_file.usdb.genesis_block_height = genesis_block_height;
+        build_server_in(root_dir, &config_file, synced_height)
+    }
+
+    // Same as build_server, but with a registry that switches the configured network to
+    // energy formula v2 at energy_v2_height.
+    // The builtin regtest registry runs energy formula v2 and the level formula from genesis.
+    fn build_regtest_server(tag: &str, synced_height: u32) -> (UsdbIndexerRpcServer, PathBuf) {
+        let root_dir = test_root_dir(tag);
+        let mut config_file = IndexerConfig::default();
+        config_file.usdb.genesis_block_height = 0;
+        config_file.bitcoin.network = Network::Regtest;
+        build_server_in(root_dir, &config_file, synced_height)
+    }
+
+    fn build_server_in(
+        root_dir: PathBuf,
+        config_file: &IndexerConfig,
+        synced_height: u32,
+    ) -> (UsdbIndexerRpcServer, PathBuf) {
         std::fs::write(
             root_dir.join("config.json"),
-            serde_json::to_vec_pretty(&config_file).unwrap(),
+            serde_json::to_vec_pretty(config_file).unwrap(),
         )
         .unwrap();
         let config = Arc::new(ConfigManager::load(Some(root_dir.clone())).unwrap());
@@ -2412,6 +3193,7 @@ mod tests {
                 .to_string(),
             commit_protocol_version: "1.0.0".to_string(),
             commit_hash_algo: "sha256".to_string(),
+            balance_state_root: None,
         }
     }
 
@@ -2481,6 +3263,7 @@ mod tests {
                     balance_history::BALANCE_HISTORY_SEMANTICS_VERSION.to_string(),
                 commit_protocol_version: "1.0.0".to_string(),
                 commit_hash_algo: "sha256".to_string(),
+                balance_state_root: None,
             })
             .unwrap();
 
@@ -2682,6 +3465,7 @@ mod tests {
                     balance_history::BALANCE_HISTORY_SEMANTICS_VERSION.to_string(),
                 commit_protocol_version: "1.0.0".to_string(),
                 commit_hash_algo: "sha256".to_string(),
+                balance_state_root: None,
             })
             .unwrap();
 
@@ -2700,6 +3484,7 @@ mod tests {
                     balance_history::BALANCE_HISTORY_SEMANTICS_VERSION.to_string(),
                 commit_protocol_version: "1.0.0".to_string(),
                 commit_hash_algo: "sha256".to_string(),
+                balance_state_root: None,
             })
             .unwrap();
         server_b
@@ -2740,6 +3525,7 @@ mod tests {
                     balance_history::BALANCE_HISTORY_SEMANTICS_VERSION.to_string(),
                 commit_protocol_version: "1.0.0".to_string(),
                 commit_hash_algo: "sha256".to_string(),
+                balance_state_root: None,
             })
             .unwrap();
 
@@ -2790,6 +3576,92 @@ mod tests {
         std::fs::remove_dir_all(root_dir).unwrap();
     }
 
+    #[test]
+    fn test_get_pass_block_mutations_recomputes_commit() {
+        let (server, root_dir) = build_server("pass_block_mutations", 141);
+        let storage = server.indexer.miner_pass_storage();
+        let upstream_commit = |block_height: u32| balance_history::BlockCommitInfo {
+            block_height,
+            btc_block_hash: "11".repeat(32),
+            balance_delta_root: "22".repeat(32),
+            block_commit: format!("{:064x}", block_height),
+            commit_protocol_version: "1.0.0".to_string(),
+            commit_hash_algo: "sha256".to_string(),
+        };
+        let mutation = PassBlockMutation::StateTransition {
+            inscription_id: "txidi0".to_string(),
+            from_state: "active".to_string(),
+            to_state: "dormant".to_string(),
+            owner: "owner".to_string(),
+            satpoint: "txid:0:0".to_string(),
+        };
+
+        // Height 139 predates mutation persistence; 140 carries its stream.
+        let legacy_entry = PassBlockMutationCollector::new(139)
+            .build_commit_entry(&upstream_commit(139), None)
+            .unwrap();
+        storage.upsert_pass_block_commit(&legacy_entry).unwrap();
+        let mut collector = PassBlockMutationCollector::new(140);
+        collector.push(mutation.clone());
+        let entry = collector
+            .build_commit_entry(&upstream_commit(140), Some(&legacy_entry))
+            .unwrap();
+        storage
+            .upsert_pass_block_commit_with_mutations(&entry, collector.mutations())
+            .unwrap();
+
+        let info = server
+            .get_pass_block_mutations(GetPassBlockMutationsParams {
+                block_height: Some(140),
+            })
+            .unwrap()
+            .unwrap();
+        assert_eq!(info.mutation_count, 1);
+        assert_eq!(info.mutations, vec![mutation]);
+        assert_eq!(
+            info.previous_block_commit.as_deref(),
+            Some(legacy_entry.block_commit.as_str())
+        );
+
+        // Everything needed to recompute the commit comes from the response itself.
+        let mut recomputed = PassBlockMutationCollector::new(info.commit.block_height);
+        for mutation in info.mutations {
+            recomputed.push(mutation);
+        }
+        let previous = PassBlockCommitEntry {
+            block_commit: info.previous_block_commit.unwrap(),
+            ..legacy_entry.clone()
+        };
+        let recomputed = recomputed
+            .build_commit_entry(
+                &upstream_commit(info.commit.balance_history_block_height),
+                Some(&previous),
+            )
+            .unwrap();
+        assert_eq!(recomputed.mutation_root, info.commit.mutation_root);
+        assert_eq!(recomputed.block_commit, info.commit.block_commit);
+
+        let err = server
+            .get_pass_block_mutations(GetPassBlockMutationsParams {
+                block_height: Some(139),
+            })
+            .unwrap_err();
+        assert_eq!(
+            err.code,
+            ErrorCode::ServerError(ERR_PASS_BLOCK_MUTATIONS_NOT_AVAILABLE)
+        );
+
+        let missing = server
+            .get_pass_block_mutations(GetPassBlockMutationsParams {
+                block_height: Some(141),
+            })
+            .unwrap();
+        assert!(missing.is_none());
+
+        drop(server);
+        std::fs::remove_dir_all(root_dir).unwrap();
+    }
+
     #[test]
     fn test_get_local_state_commit_info_success() {
         let (server, root_dir) = build_server_with_genesis("local_state_commit_success", 120, 100);
@@ -2807,6 +3679,7 @@ mod tests {
                     balance_history::BALANCE_HISTORY_SEMANTICS_VERSION.to_string(),
                 commit_protocol_version: "1.0.0".to_string(),
                 commit_hash_algo: "sha256".to_string(),
+                balance_state_root: None,
             })
             .unwrap();
         server
@@ -2885,6 +3758,7 @@ mod tests {
                     balance_history::BALANCE_HISTORY_SEMANTICS_VERSION.to_string(),
                 commit_protocol_version: "1.0.0".to_string(),
                 commit_hash_algo: "sha256".to_string(),
+                balance_state_root: None,
             })
             .unwrap();
         server
@@ -2946,6 +3820,7 @@ mod tests {
                     balance_history::BALANCE_HISTORY_SEMANTICS_VERSION.to_string(),
                 commit_protocol_version: "1.0.0".to_string(),
                 commit_hash_algo: "sha256".to_string(),
+                balance_state_root: None,
             })
             .unwrap();
         server
@@ -2962,6 +3837,7 @@ mod tests {
                     balance_history::BALANCE_HISTORY_SEMANTICS_VERSION.to_string(),
                 commit_protocol_version: "1.0.0".to_string(),
                 commit_hash_algo: "sha256".to_string(),
+                balance_state_root: None,
             })
             .unwrap();
         server
@@ -3034,6 +3910,18 @@ mod tests {
             state_ref.system_state_info.system_state_id,
             build_system_state_id(&state_ref.system_state_info.system_state_identity)
         );
+        assert_eq!(
+            state_ref.active_version_set.energy_formula_version,
+            usdb_util::ENERGY_FORMULA_VERSION_V1
+        );
+        assert_eq!(
+            state_ref.active_version_set_id,
+            usdb_util::build_active_version_set_id(&state_ref.active_version_set)
+        );
+        assert_eq!(
+            state_ref.activation_registry_id,
+            server.config.activation_registry().activation_registry_id()
+        );
 
         drop(server);
         std::fs::remove_dir_all(root_dir).unwrap();
@@ -3458,6 +4346,7 @@ mod tests {
                     balance_history::BALANCE_HISTORY_SEMANTICS_VERSION.to_string(),
                 commit_protocol_version: "1.0.0".to_string(),
                 commit_hash_algo: "sha256".to_string(),
+                balance_state_root: None,
             })
             .unwrap();
         server
@@ -4153,6 +5042,8 @@ mod tests {
             .get_pass_energy_leaderboard(GetPassEnergyLeaderboardParams {
                 at_height: None,
                 scope: None,
+                order_by: None,
+                include_level: None,
                 page: 0,
                 page_size: 10,
             })
@@ -4179,6 +5070,8 @@ mod tests {
             .get_pass_energy_leaderboard(GetPassEnergyLeaderboardParams {
                 at_height: None,
                 scope: None,
+                order_by: None,
+                include_level: None,
                 page: 0,
                 page_size: 10,
             })
@@ -4239,33 +5132,624 @@ mod tests {
         std::fs::remove_dir_all(root_dir).unwrap();
     }
 
+    fn make_collab_pass(
+        ins_tag: u8,
+        owner_tag: u8,
+        mint_height: u32,
+        leader_pass_id: Option<InscriptionId>,
+        leader_btc_addr: Option<&str>,
+    ) -> MinerPassInfo {
+        let mut pass = make_active_pass(ins_tag, owner_tag, mint_height);
+        pass.eth_main = String::new();
+        pass.leader_pass_id = leader_pass_id;
+        pass.leader_btc_addr = leader_btc_addr.map(|v| v.to_string());
+        pass
+    }
+
     #[test]
-    fn test_get_pass_energy_rejects_mismatched_context_height() {
-        let (server, root_dir) = build_server("energy_context_height_mismatch", 130);
+    fn test_get_pass_effective_energy_adds_resolved_collab_contributions() {
+        let (server, root_dir) = build_server("effective_energy", 130);
         let storage = server.indexer.miner_pass_storage();
+        let leader_addr = "bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4";
+
+        let mut leader = make_active_pass(40, 40, 100);
+        leader.owner =
+            usdb_util::address_string_to_script_hash(leader_addr, &Network::Bitcoin).unwrap();
+        leader.mint_owner = leader.owner;
+        storage.add_new_mint_pass_at_height(&leader, 100).unwrap();
+        let collab_by_id = make_collab_pass(41, 41, 101, Some(leader.inscription_id), None);
+        storage
+            .add_new_mint_pass_at_height(&collab_by_id, 101)
+            .unwrap();
+        let collab_by_addr = make_collab_pass(42, 42, 102, None, Some(leader_addr));
+        storage
+            .add_new_mint_pass_at_height(&collab_by_addr, 102)
+            .unwrap();
+        seed_energy_record(&server, &leader, 120, 500);
+        seed_energy_record(&server, &collab_by_id, 120, 1_000);
+        seed_energy_record(&server, &collab_by_addr, 120, 301);
 
-        let pass = make_active_pass(23, 123, 100);
-        storage.add_new_mint_pass_at_height(&pass, 100).unwrap();
-        seed_energy_record(&server, &pass, 120, 500);
-        seed_state_ref_context(&server, 120);
-
-        let err = server
-            .get_pass_energy(GetPassEnergyParams {
-                inscription_id: pass.inscription_id.to_string(),
+        let view = server
+            .get_pass_effective_energy(GetPassEffectiveEnergyParams {
+                inscription_id: leader.inscription_id.to_string(),
                 block_height: Some(120),
-                context: Some(ConsensusQueryContext {
-                    requested_height: Some(121),
-                    expected_state: ConsensusStateReference::default(),
-                }),
-                mode: Some("exact".to_string()),
+                context: None,
             })
-            .unwrap_err();
-
-        assert_eq!(err.code, ErrorCode::InvalidParams);
+            .unwrap();
+        assert!(!view.is_collab);
+        assert_eq!(view.raw_energy, 500);
+        assert_eq!(view.collab_contribution_total, 650);
+        assert_eq!(view.effective_energy, 1_150);
+        assert_eq!(view.collabs.len(), 2);
+
+        let collab_view = server
+            .get_pass_effective_energy(GetPassEffectiveEnergyParams {
+                inscription_id: collab_by_id.inscription_id.to_string(),
+                block_height: Some(120),
+                context: None,
+            })
+            .unwrap();
+        assert!(collab_view.is_collab);
+        assert_eq!(collab_view.raw_energy, 1_000);
+        assert_eq!(collab_view.effective_energy, 0);
 
-        drop(server);
-        std::fs::remove_dir_all(root_dir).unwrap();
-    }
+        let set = server
+            .get_leader_collab_set_at_height(GetLeaderCollabSetAtHeightParams {
+                leader_pass_id: leader.inscription_id.to_string(),
+                at_height: Some(120),
+                context: None,
+            })
+            .unwrap();
+        assert!(set.leader_active);
+        assert_eq!(set.total_contribution, 650);
+        let by_id = set
+            .items
+            .iter()
+            .find(|item| item.collab_pass_id == collab_by_id.inscription_id.to_string())
+            .unwrap();
+        assert_eq!(by_id.leader_ref_kind, "leader_pass_id");
+        assert_eq!(by_id.collab_weight_bps, 5_000);
+        assert_eq!(by_id.collab_contribution, 500);
+
+        // Raw energy ledger must stay untouched by the derived view.
+        let raw = server
+            .get_pass_energy(GetPassEnergyParams {
+                inscription_id: leader.inscription_id.to_string(),
+                block_height: Some(120),
+                context: None,
+                mode: Some("exact".to_string()),
+            })
+            .unwrap();
+        assert_eq!(raw.energy, 500);
+
+        storage
+            .update_state_at_height(
+                &leader.inscription_id,
+                MinerPassState::Dormant,
+                MinerPassState::Active,
+                125,
+            )
+            .unwrap();
+        let set = server
+            .get_leader_collab_set_at_height(GetLeaderCollabSetAtHeightParams {
+                leader_pass_id: leader.inscription_id.to_string(),
+                at_height: Some(125),
+                context: None,
+            })
+            .unwrap();
+        assert!(!set.leader_active);
+        assert!(set.items.is_empty());
+        let view = server
+            .get_pass_effective_energy(GetPassEffectiveEnergyParams {
+                inscription_id: leader.inscription_id.to_string(),
+                block_height: Some(125),
+                context: None,
+            })
+            .unwrap();
+        assert_eq!(view.effective_energy, 0);
+
+        let err = server
+            .get_pass_effective_energy(GetPassEffectiveEnergyParams {
+                inscription_id: test_inscription_id(99, 0).to_string(),
+                block_height: Some(120),
+                context: None,
+            })
+            .unwrap_err();
+        assert_eq!(err.message, "PASS_NOT_FOUND");
+
+        drop(server);
+        std::fs::remove_dir_all(root_dir).unwrap();
+    }
+
+    #[test]
+    fn test_get_pass_level_and_level_leaderboard_use_effective_energy() {
+        let (server, root_dir) = build_regtest_server("pass_level", 130);
+        let storage = server.indexer.miner_pass_storage();
+
+        let leader = make_active_pass(50, 50, 100);
+        storage.add_new_mint_pass_at_height(&leader, 100).unwrap();
+        let solo = make_active_pass(51, 51, 100);
+        storage.add_new_mint_pass_at_height(&solo, 100).unwrap();
+        let collab = make_collab_pass(52, 52, 101, Some(leader.inscription_id), None);
+        storage.add_new_mint_pass_at_height(&collab, 101).unwrap();
+        seed_energy_record(&server, &leader, 120, 800_000);
+        seed_energy_record(&server, &solo, 120, 900_000);
+        seed_energy_record(&server, &collab, 120, 500_000);
+
+        let level = server
+            .get_pass_level(GetPassLevelParams {
+                inscription_id: leader.inscription_id.to_string(),
+                block_height: Some(120),
+                context: None,
+            })
+            .unwrap();
+        assert_eq!(level.effective_energy, 1_050_000);
+        assert_eq!(level.level, 1);
+        assert_eq!(level.difficulty_factor_bps, 9_900);
+        assert_eq!(level.next_level_threshold, Some(2_180_000));
+        assert_eq!(
+            level.level_formula_version,
+            usdb_util::LEVEL_FORMULA_VERSION_V1
+        );
+
+        let collab_level = server
+            .get_pass_level(GetPassLevelParams {
+                inscription_id: collab.inscription_id.to_string(),
+                block_height: Some(120),
+                context: None,
+            })
+            .unwrap();
+        assert!(collab_level.is_collab);
+        assert_eq!(collab_level.effective_energy, 0);
+        assert_eq!(collab_level.level, 0);
+        assert_eq!(collab_level.difficulty_factor_bps, 10_000);
+
+        let by_energy = server
+            .get_pass_energy_leaderboard(GetPassEnergyLeaderboardParams {
+                at_height: Some(120),
+                scope: None,
+                order_by: None,
+                include_level: None,
+                page: 0,
+                page_size: 10,
+            })
+            .unwrap();
+        let ids = by_energy
+            .items
+            .iter()
+            .map(|item| item.inscription_id.clone())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            ids,
+            vec![
+                solo.inscription_id.to_string(),
+                leader.inscription_id.to_string(),
+                collab.inscription_id.to_string(),
+            ]
+        );
+        // Raw energy ranking skips the collab scan and leaves derived fields empty.
+        assert!(
+            by_energy
+                .items
+                .iter()
+                .all(|item| item.effective_energy.is_none() && item.level.is_none())
+        );
+
+        let by_effective = server
+            .get_pass_energy_leaderboard(GetPassEnergyLeaderboardParams {
+                at_height: Some(120),
+                scope: None,
+                order_by: Some("effective_energy".to_string()),
+                include_level: None,
+                page: 0,
+                page_size: 10,
+            })
+            .unwrap();
+        assert_eq!(
+            by_effective.items[0].inscription_id,
+            leader.inscription_id.to_string()
+        );
+        assert_eq!(by_effective.items[0].effective_energy, Some(1_050_000));
+        assert_eq!(by_effective.items[0].level, None);
+
+        let by_level = server
+            .get_pass_energy_leaderboard(GetPassEnergyLeaderboardParams {
+                at_height: Some(120),
+                scope: None,
+                order_by: Some("level".to_string()),
+                include_level: None,
+                page: 0,
+                page_size: 10,
+            })
+            .unwrap();
+        let ids = by_level
+            .items
+            .iter()
+            .map(|item| item.inscription_id.clone())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            ids,
+            vec![
+                leader.inscription_id.to_string(),
+                solo.inscription_id.to_string(),
+                collab.inscription_id.to_string(),
+            ]
+        );
+        assert_eq!(by_level.items[0].level, Some(1));
+        assert_eq!(by_level.items[0].effective_energy, Some(1_050_000));
+        assert_eq!(by_level.items[2].effective_energy, Some(0));
+
+        let err = server
+            .get_pass_energy_leaderboard(GetPassEnergyLeaderboardParams {
+                at_height: Some(120),
+                scope: None,
+                order_by: Some("bad_order".to_string()),
+                include_level: None,
+                page: 0,
+                page_size: 10,
+            })
+            .unwrap_err();
+        assert_eq!(err.code, ErrorCode::InvalidParams);
+
+        drop(server);
+        std::fs::remove_dir_all(root_dir).unwrap();
+    }
+
+    #[test]
+    fn test_get_pass_level_fails_closed_without_level_formula() {
+        let (server, root_dir) = build_server("pass_level_pre_v2", 130);
+        let storage = server.indexer.miner_pass_storage();
+
+        let pass = make_active_pass(53, 53, 100);
+        storage.add_new_mint_pass_at_height(&pass, 100).unwrap();
+        seed_energy_record(&server, &pass, 105, 800_000);
+
+        let err = server
+            .get_pass_level(GetPassLevelParams {
+                inscription_id: pass.inscription_id.to_string(),
+                block_height: Some(105),
+                context: None,
+            })
+            .unwrap_err();
+        match err.code {
+            ErrorCode::ServerError(code) => {
+                assert_eq!(code, ConsensusRpcErrorCode::VersionMismatch.code())
+            }
+            _ => panic!("unexpected error code: {:?}", err.code),
+        }
+        let data = decode_consensus_error_data(&err);
+        assert_eq!(data.requested_height, Some(105));
+
+        let err = server
+            .get_pass_energy_leaderboard(GetPassEnergyLeaderboardParams {
+                at_height: Some(105),
+                scope: None,
+                order_by: Some("level".to_string()),
+                include_level: None,
+                page: 0,
+                page_size: 10,
+            })
+            .unwrap_err();
+        assert_eq!(
+            err.code,
+            ErrorCode::ServerError(ConsensusRpcErrorCode::VersionMismatch.code())
+        );
+
+        let by_energy = server
+            .get_pass_energy_leaderboard(GetPassEnergyLeaderboardParams {
+                at_height: Some(105),
+                scope: None,
+                order_by: None,
+                include_level: None,
+                page: 0,
+                page_size: 10,
+            })
+            .unwrap();
+        assert_eq!(by_energy.items.len(), 1);
+        assert_eq!(by_energy.items[0].energy, 800_000);
+
+        drop(server);
+        std::fs::remove_dir_all(root_dir).unwrap();
+    }
+
+    #[test]
+    fn test_validator_candidate_set_ordering_and_verification() {
+        let (server, root_dir) = build_server("validator_candidate_set", 130);
+        let storage = server.indexer.miner_pass_storage();
+
+        let leader = make_active_pass(60, 60, 100);
+        storage.add_new_mint_pass_at_height(&leader, 100).unwrap();
+        let solo_a = make_active_pass(61, 61, 100);
+        storage.add_new_mint_pass_at_height(&solo_a, 100).unwrap();
+        let solo_b = make_active_pass(62, 62, 100);
+        storage.add_new_mint_pass_at_height(&solo_b, 100).unwrap();
+        let collab = make_collab_pass(63, 63, 101, Some(leader.inscription_id), None);
+        storage.add_new_mint_pass_at_height(&collab, 101).unwrap();
+        seed_energy_record(&server, &leader, 120, 800_000);
+        seed_energy_record(&server, &solo_a, 120, 900_000);
+        seed_energy_record(&server, &solo_b, 120, 900_000);
+        seed_energy_record(&server, &collab, 120, 500_000);
+        seed_state_ref_context(&server, 120);
+
+        let set = server
+            .get_validator_candidate_set(GetValidatorCandidateSetParams {
+                block_height: Some(120),
+                max_candidates: Some(2),
+                min_effective_energy: None,
+                context: None,
+            })
+            .unwrap();
+        let mut tied = vec![
+            solo_a.inscription_id.to_string(),
+            solo_b.inscription_id.to_string(),
+        ];
+        tied.sort();
+        assert_eq!(set.total_eligible, 3);
+        assert_eq!(set.winner, Some(leader.inscription_id.to_string()));
+        assert_eq!(set.candidates.len(), 2);
+        assert_eq!(set.candidates[0].effective_energy, 1_050_000);
+        assert_eq!(set.candidates[0].collab_contribution_total, 250_000);
+        assert_eq!(set.candidates[1].rank, 1);
+        assert_eq!(set.candidates[1].inscription_id, tied[0]);
+        assert_eq!(
+            set.system_state_id,
+            server
+                .build_historical_state_ref_info(120)
+                .unwrap()
+                .system_state_info
+                .system_state_id
+        );
+
+        let filtered = server
+            .get_validator_candidate_set(GetValidatorCandidateSetParams {
+                block_height: Some(120),
+                max_candidates: None,
+                min_effective_energy: Some(1_000_000),
+                context: None,
+            })
+            .unwrap();
+        assert_eq!(filtered.total_eligible, 1);
+        assert_eq!(
+            filtered.max_candidates,
+            DEFAULT_VALIDATOR_CANDIDATE_SET_SIZE
+        );
+
+        let payload = VerifyValidatorCandidateSetParams {
+            block_height: set.block_height,
+            system_state_id: set.system_state_id.clone(),
+            selection_rule: set.selection_rule.clone(),
+            max_candidates: set.max_candidates,
+            min_effective_energy: set.min_effective_energy,
+            candidates: set
+                .candidates
+                .iter()
+                .map(|candidate| ValidatorCandidateIdentity {
+                    inscription_id: candidate.inscription_id.clone(),
+                    owner: candidate.owner.clone(),
+                    effective_energy: candidate.effective_energy,
+                })
+                .collect(),
+            candidate_set_commitment: set.candidate_set_commitment.clone(),
+            context: None,
+        };
+        let verification = server
+            .verify_validator_candidate_set(payload.clone())
+            .unwrap();
+        assert!(verification.valid);
+        assert!(verification.mismatches.is_empty());
+        assert_eq!(
+            verification.expected_winner,
+            Some(leader.inscription_id.to_string())
+        );
+
+        let mut reordered = payload.clone();
+        reordered.candidates.reverse();
+        let verification = server.verify_validator_candidate_set(reordered).unwrap();
+        assert!(!verification.valid);
+        assert_eq!(
+            verification.mismatches,
+            vec![
+                "COMMITMENT_NOT_SELF_CONSISTENT".to_string(),
+                "CANDIDATE_SET_MISMATCH".to_string(),
+            ]
+        );
+
+        let mut wrong_state = payload.clone();
+        wrong_state.system_state_id = "ee".repeat(32);
+        let verification = server.verify_validator_candidate_set(wrong_state).unwrap();
+        assert!(
+            verification
+                .mismatches
+                .contains(&"SYSTEM_STATE_ID_MISMATCH".to_string())
+        );
+
+        let err = server
+            .get_validator_candidate_set(GetValidatorCandidateSetParams {
+                block_height: Some(120),
+                max_candidates: Some(0),
+                min_effective_energy: None,
+                context: None,
+            })
+            .unwrap_err();
+        assert_eq!(err.code, ErrorCode::InvalidParams);
+
+        drop(server);
+        std::fs::remove_dir_all(root_dir).unwrap();
+    }
+
+    #[test]
+    fn test_economic_state_view_totals_match_audit_profiles() {
+        let (server, root_dir) = build_server("economic_state_view", 130);
+        let storage = server.indexer.miner_pass_storage();
+
+        let leader = make_active_pass(70, 70, 100);
+        storage.add_new_mint_pass_at_height(&leader, 100).unwrap();
+        let solo = make_active_pass(71, 71, 100);
+        storage.add_new_mint_pass_at_height(&solo, 100).unwrap();
+        let collab = make_collab_pass(72, 72, 101, Some(leader.inscription_id), None);
+        storage.add_new_mint_pass_at_height(&collab, 101).unwrap();
+        seed_energy_record(&server, &leader, 120, 800_000);
+        seed_energy_record(&server, &solo, 120, 900_000);
+        seed_energy_record(&server, &collab, 120, 500_000);
+        seed_state_ref_context(&server, 120);
+
+        let view = server
+            .get_economic_state_view(GetEconomicStateViewParams {
+                block_height: Some(120),
+                context: None,
+            })
+            .unwrap();
+        let state_ref = server.build_historical_state_ref_info(120).unwrap();
+        assert_eq!(view.view_version, ECONOMIC_STATE_VIEW_VERSION);
+        assert_eq!(view.external_state.btc_height, 120);
+        assert_eq!(
+            view.external_state.system_state_id,
+            state_ref.system_state_info.system_state_id
+        );
+        assert_eq!(
+            view.external_state.local_state_commit,
+            state_ref.local_state_commit_info.local_state_commit
+        );
+        assert_eq!(view.totals.active_pass_count, 3);
+        assert_eq!(view.totals.active_standard_pass_count, 2);
+        assert_eq!(view.totals.active_collab_pass_count, 1);
+        assert_eq!(view.totals.total_raw_energy, "2200000");
+        assert_eq!(view.totals.total_collab_contribution, "250000");
+        assert_eq!(view.totals.total_effective_energy, "1950000");
+        assert_eq!(
+            view.totals.active_balance_snapshot,
+            Some(LocalStateActiveBalanceSnapshot {
+                block_height: 120,
+                total_balance: 5_000,
+                active_address_count: 2,
+            })
+        );
+
+        let mut profiles = Vec::new();
+        for page in 0..2 {
+            let audit = server
+                .get_economic_state_audit_view(GetEconomicStateAuditViewParams {
+                    block_height: Some(120),
+                    page,
+                    page_size: 2,
+                    context: None,
+                })
+                .unwrap();
+            assert_eq!(audit.total, 3);
+            assert_eq!(audit.totals, view.totals);
+            assert_eq!(audit.external_state, view.external_state);
+            profiles.extend(audit.items);
+        }
+        assert_eq!(profiles.len(), 3);
+        assert!(profiles.windows(2).all(|w| w[0].pass_id < w[1].pass_id));
+
+        let leader_profile = profiles
+            .iter()
+            .find(|p| p.pass_id == leader.inscription_id.to_string())
+            .unwrap();
+        assert_eq!(leader_profile.pass_kind, "standard");
+        assert_eq!(leader_profile.collab_contribution, "250000");
+        assert_eq!(leader_profile.effective_energy, "1050000");
+        assert_eq!(leader_profile.collab_breakdown_count, 1);
+        // Energy formula v1 is active here, so no level is derived.
+        assert_eq!(leader_profile.level, None);
+        assert_eq!(leader_profile.difficulty_factor_bps, None);
+
+        let collab_profile = profiles
+            .iter()
+            .find(|p| p.pass_id == collab.inscription_id.to_string())
+            .unwrap();
+        assert_eq!(collab_profile.pass_kind, "collab");
+        assert_eq!(collab_profile.raw_energy, "500000");
+        assert_eq!(collab_profile.effective_energy, "0");
+
+        let err = server
+            .get_economic_state_audit_view(GetEconomicStateAuditViewParams {
+                block_height: Some(120),
+                page: 0,
+                page_size: 0,
+                context: None,
+            })
+            .unwrap_err();
+        assert_eq!(err.code, ErrorCode::ServerError(ERR_INVALID_PAGINATION));
+
+        drop(server);
+        std::fs::remove_dir_all(root_dir).unwrap();
+    }
+
+    #[test]
+    fn test_economic_state_views_return_state_not_retained_below_history_floor() {
+        let (server, root_dir) = build_server_with_genesis("economic_state_not_retained", 130, 121);
+        let storage = server.indexer.miner_pass_storage();
+
+        let pass = make_active_pass(73, 73, 100);
+        storage.add_new_mint_pass_at_height(&pass, 100).unwrap();
+        seed_energy_record(&server, &pass, 120, 500);
+        seed_state_ref_context(&server, 120);
+
+        let view_err = server
+            .get_economic_state_view(GetEconomicStateViewParams {
+                block_height: Some(120),
+                context: None,
+            })
+            .unwrap_err();
+        let audit_err = server
+            .get_economic_state_audit_view(GetEconomicStateAuditViewParams {
+                block_height: Some(120),
+                page: 0,
+                page_size: 10,
+                context: None,
+            })
+            .unwrap_err();
+
+        for err in [view_err, audit_err] {
+            match err.code {
+                ErrorCode::ServerError(code) => {
+                    assert_eq!(code, ConsensusRpcErrorCode::StateNotRetained.code())
+                }
+                _ => panic!("unexpected error code: {:?}", err.code),
+            }
+            let data = decode_consensus_error_data(&err);
+            assert_eq!(data.requested_height, Some(120));
+            assert!(
+                data.detail
+                    .as_deref()
+                    .unwrap_or_default()
+                    .contains("historical state retention floor 121")
+            );
+        }
+
+        drop(server);
+        std::fs::remove_dir_all(root_dir).unwrap();
+    }
+
+    #[test]
+    fn test_get_pass_energy_rejects_mismatched_context_height() {
+        let (server, root_dir) = build_server("energy_context_height_mismatch", 130);
+        let storage = server.indexer.miner_pass_storage();
+
+        let pass = make_active_pass(23, 123, 100);
+        storage.add_new_mint_pass_at_height(&pass, 100).unwrap();
+        seed_energy_record(&server, &pass, 120, 500);
+        seed_state_ref_context(&server, 120);
+
+        let err = server
+            .get_pass_energy(GetPassEnergyParams {
+                inscription_id: pass.inscription_id.to_string(),
+                block_height: Some(120),
+                context: Some(ConsensusQueryContext {
+                    requested_height: Some(121),
+                    expected_state: ConsensusStateReference::default(),
+                }),
+                mode: Some("exact".to_string()),
+            })
+            .unwrap_err();
+
+        assert_eq!(err.code, ErrorCode::InvalidParams);
+
+        drop(server);
+        std::fs::remove_dir_all(root_dir).unwrap();
+    }
 
     #[test]
     fn test_get_pass_energy_returns_system_state_mismatch_with_context() {
@@ -4307,6 +5791,55 @@ mod tests {
         std::fs::remove_dir_all(root_dir).unwrap();
     }
 
+    #[test]
+    fn test_get_leader_collab_set_returns_system_state_mismatch_with_context() {
+        let (server, root_dir) = build_server("collab_set_context_system_mismatch", 130);
+        let storage = server.indexer.miner_pass_storage();
+
+        let leader = make_active_pass(29, 129, 100);
+        storage.add_new_mint_pass_at_height(&leader, 100).unwrap();
+        seed_energy_record(&server, &leader, 120, 500);
+        seed_state_ref_context(&server, 120);
+
+        let err = server
+            .get_leader_collab_set_at_height(GetLeaderCollabSetAtHeightParams {
+                leader_pass_id: leader.inscription_id.to_string(),
+                at_height: Some(120),
+                context: Some(ConsensusQueryContext {
+                    requested_height: Some(120),
+                    expected_state: ConsensusStateReference {
+                        system_state_id: Some("dd".repeat(32)),
+                        ..Default::default()
+                    },
+                }),
+            })
+            .unwrap_err();
+
+        match err.code {
+            ErrorCode::ServerError(code) => {
+                assert_eq!(code, ConsensusRpcErrorCode::SystemStateIdMismatch.code())
+            }
+            _ => panic!("unexpected error code: {:?}", err.code),
+        }
+        let data = decode_consensus_error_data(&err);
+        assert_eq!(data.requested_height, Some(120));
+
+        let err = server
+            .get_leader_collab_set_at_height(GetLeaderCollabSetAtHeightParams {
+                leader_pass_id: leader.inscription_id.to_string(),
+                at_height: Some(120),
+                context: Some(ConsensusQueryContext {
+                    requested_height: Some(121),
+                    expected_state: ConsensusStateReference::default(),
+                }),
+            })
+            .unwrap_err();
+        assert_eq!(err.code, ErrorCode::InvalidParams);
+
+        drop(server);
+        std::fs::remove_dir_all(root_dir).unwrap();
+    }
+
     #[test]
     fn test_get_pass_energy_returns_snapshot_not_ready_when_context_consensus_not_ready() {
         let (server, root_dir) = build_server("energy_context_not_ready", 130);
@@ -4461,6 +5994,8 @@ mod tests {
             .get_pass_energy_leaderboard(GetPassEnergyLeaderboardParams {
                 at_height: Some(120),
                 scope: None,
+                order_by: None,
+                include_level: None,
                 page: 0,
                 page_size: 10,
             })
@@ -4496,6 +6031,8 @@ mod tests {
             .get_pass_energy_leaderboard(GetPassEnergyLeaderboardParams {
                 at_height: None,
                 scope: None,
+                order_by: None,
+                include_level: None,
                 page: 100,
                 page_size: 20,
             })
@@ -4536,6 +6073,8 @@ mod tests {
             .get_pass_energy_leaderboard(GetPassEnergyLeaderboardParams {
                 at_height: None,
                 scope: None,
+                order_by: None,
+                include_level: None,
                 page: 0,
                 page_size: 2,
             })
@@ -4551,6 +6090,8 @@ mod tests {
             .get_pass_energy_leaderboard(GetPassEnergyLeaderboardParams {
                 at_height: None,
                 scope: None,
+                order_by: None,
+                include_level: None,
                 page: 1,
                 page_size: 2,
             })
@@ -4566,6 +6107,8 @@ mod tests {
             .get_pass_energy_leaderboard(GetPassEnergyLeaderboardParams {
                 at_height: Some(120),
                 scope: None,
+                order_by: None,
+                include_level: None,
                 page: 1,
                 page_size: 2,
             })
@@ -4596,6 +6139,8 @@ mod tests {
             .get_pass_energy_leaderboard(GetPassEnergyLeaderboardParams {
                 at_height: None,
                 scope: None,
+                order_by: None,
+                include_level: None,
                 page: 0,
                 page_size: 2,
             })
@@ -4610,6 +6155,8 @@ mod tests {
             .get_pass_energy_leaderboard(GetPassEnergyLeaderboardParams {
                 at_height: None,
                 scope: None,
+                order_by: None,
+                include_level: None,
                 page: 1,
                 page_size: 2,
             })
@@ -4663,6 +6210,8 @@ mod tests {
             .get_pass_energy_leaderboard(GetPassEnergyLeaderboardParams {
                 at_height: None,
                 scope: Some("active".to_string()),
+                order_by: None,
+                include_level: None,
                 page: 0,
                 page_size: 10,
             })
@@ -4679,6 +6228,8 @@ mod tests {
             .get_pass_energy_leaderboard(GetPassEnergyLeaderboardParams {
                 at_height: None,
                 scope: Some("active_dormant".to_string()),
+                order_by: None,
+                include_level: None,
                 page: 0,
                 page_size: 10,
             })
@@ -4698,6 +6249,8 @@ mod tests {
             .get_pass_energy_leaderboard(GetPassEnergyLeaderboardParams {
                 at_height: None,
                 scope: Some("all".to_string()),
+                order_by: None,
+                include_level: None,
                 page: 0,
                 page_size: 10,
             })
@@ -4720,6 +6273,8 @@ mod tests {
             .get_pass_energy_leaderboard(GetPassEnergyLeaderboardParams {
                 at_height: None,
                 scope: Some("bad_scope".to_string()),
+                order_by: None,
+                include_level: None,
                 page: 0,
                 page_size: 10,
             })
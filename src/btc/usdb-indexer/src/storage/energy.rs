@@ -684,6 +684,58 @@ impl PassEnergyStorage {
         Ok(())
     }
 
+    // Visit every pass energy row in key order as raw key/value bytes, used by snapshot export.
+    // Returns the number of rows visited.
+    pub fn for_each_raw_pass_energy_record(
+        &self,
+        mut visit: impl FnMut(&[u8], &[u8]) -> Result<(), String>,
+    ) -> Result<u64, String> {
+        let cf = self.db.cf_handle(PASS_ENERGY_CF).ok_or_else(|| {
+            let msg = format!("Column family {} not found", PASS_ENERGY_CF);
+            error!("{}", msg);
+            msg
+        })?;
+
+        let mut count = 0u64;
+        for item in self.db.iterator_cf(cf, rocksdb::IteratorMode::Start) {
+            let (key_bytes, value_bytes) = item.map_err(|e| {
+                let msg = format!("Failed to iterate pass energy records: {}", e);
+                error!("{}", msg);
+                msg
+            })?;
+            visit(&key_bytes, &value_bytes)?;
+            count += 1;
+        }
+
+        Ok(count)
+    }
+
+    // Insert one raw row produced by for_each_raw_pass_energy_record. Both key and value
+    // are decoded first so a corrupted snapshot row is rejected instead of persisted.
+    pub fn insert_raw_pass_energy_record(&self, key: &[u8], value: &[u8]) -> Result<(), String> {
+        let key = Self::parse_energy_key(key)?;
+        let (value, _): (PassEnergyValue, usize) =
+            bincode::serde::decode_from_slice(value, bincode::config::standard()).map_err(|e| {
+                let msg = format!(
+                    "Failed to deserialize raw PassEnergyValue for {} at height {}: {}",
+                    key.inscription_id, key.block_height, e
+                );
+                error!("{}", msg);
+                msg
+            })?;
+
+        self.insert_pass_energy_record(&PassEnergyRecord {
+            inscription_id: key.inscription_id,
+            block_height: key.block_height,
+            state: value.state,
+            active_block_height: value.active_block_height,
+            owner_address: value.owner_address,
+            owner_balance: value.owner_balance,
+            owner_delta: value.owner_delta,
+            energy: value.energy,
+        })
+    }
+
     pub fn get_max_record_block_height(&self) -> Result<Option<u32>, String> {
         // Fast path: read cached max height from metadata.
         if let Some(height) = self.get_meta_u32(META_KEY_MAX_RECORD_BLOCK_HEIGHT)? {
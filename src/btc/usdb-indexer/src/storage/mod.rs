@@ -1,5 +1,7 @@
 mod energy;
 mod pass;
+mod snapshot;
 
 pub use energy::*;
 pub use pass::*;
+pub use snapshot::*;
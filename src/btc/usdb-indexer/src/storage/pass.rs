@@ -1,4 +1,4 @@
-use crate::index::{MinerPassState, PassBlockCommitEntry};
+use crate::index::{MinerPassState, PassBlockCommitEntry, PassBlockMutation};
 use balance_history::SnapshotInfo as BalanceHistorySnapshotInfo;
 use bitcoincore_rpc::bitcoin::Txid;
 use ord::InscriptionId;
@@ -19,6 +19,8 @@ const BALANCE_HISTORY_SNAPSHOT_COMMIT_PROTOCOL_VERSION_KEY: &str =
     "balance_history_snapshot_commit_protocol_version";
 const BALANCE_HISTORY_SNAPSHOT_COMMIT_HASH_ALGO_KEY: &str =
     "balance_history_snapshot_commit_hash_algo";
+const BALANCE_HISTORY_SNAPSHOT_BALANCE_STATE_ROOT_KEY: &str =
+    "balance_history_snapshot_balance_state_root";
 const UPSTREAM_REORG_RECOVERY_PENDING_HEIGHT_KEY: &str = "upstream_reorg_recovery_pending_height";
 
 // Default savepoint name for miner pass operations
@@ -48,12 +50,22 @@ pub struct MinerPassInfo {
     pub invalid_code: Option<String>,
     pub invalid_reason: Option<String>,
 
+    // UIP-0004 Leader binding, at most one of them is set and only on collab passes
+    pub leader_pass_id: Option<InscriptionId>,
+    pub leader_btc_addr: Option<String>,
+
     // Current owner address of the pass, when the pass is transferred,
     // the owner changes and state changed to Dormant by default
     pub owner: USDBScriptHash,
     pub state: MinerPassState,
 }
 
+impl MinerPassInfo {
+    pub fn is_collab(&self) -> bool {
+        self.leader_pass_id.is_some() || self.leader_btc_addr.is_some()
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct ValidMinerPassInfo {
     pub inscription_id: InscriptionId,
@@ -67,6 +79,14 @@ pub struct ActiveMinerPassInfo {
     pub owner: USDBScriptHash,
 }
 
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CollabMinerPassInfo {
+    pub inscription_id: InscriptionId,
+    pub owner: USDBScriptHash,
+    pub leader_pass_id: Option<InscriptionId>,
+    pub leader_btc_addr: Option<String>,
+}
+
 pub struct MinerPassSnapshotInfo {
     pub pass: MinerPassInfo,
     pub latest_event_height: u32,
@@ -108,6 +128,8 @@ pub struct BalanceHistorySnapshotAnchor {
     pub stable_lag: u32,
     pub commit_protocol_version: String,
     pub commit_hash_algo: String,
+    // Upstream full balance state root, only present once activated for the network.
+    pub balance_state_root: Option<String>,
 }
 
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -257,6 +279,17 @@ impl MinerPassStorage {
 
         Self::ensure_column_exists(&conn, "miner_passes", "invalid_code", "TEXT")?;
         Self::ensure_column_exists(&conn, "miner_passes", "invalid_reason", "TEXT")?;
+        Self::ensure_column_exists(&conn, "miner_passes", "leader_pass_id", "TEXT")?;
+        Self::ensure_column_exists(&conn, "miner_passes", "leader_btc_addr", "TEXT")?;
+        // NULL for rows committed before mutation streams were persisted.
+        Self::ensure_column_exists(&conn, "pass_block_commits", "mutations_json", "TEXT")?;
+        // NULL before the upstream balance state root is activated.
+        Self::ensure_column_exists(
+            &conn,
+            "balance_history_snapshot_history",
+            "balance_state_root",
+            "TEXT",
+        )?;
 
         let mut stmt = conn
             .prepare(
@@ -609,12 +642,9 @@ impl MinerPassStorage {
         let conn = self.conn.lock().unwrap();
         Self::upsert_balance_history_snapshot_history_with_conn(
             &conn,
-            snapshot.stable_height,
+            snapshot,
             &stable_block_hash,
             &latest_block_commit,
-            snapshot.stable_lag,
-            &snapshot.commit_protocol_version,
-            &snapshot.commit_hash_algo,
         )
     }
 
@@ -669,6 +699,17 @@ impl MinerPassStorage {
             BALANCE_HISTORY_SNAPSHOT_COMMIT_HASH_ALGO_KEY,
             &snapshot.commit_hash_algo,
         )?;
+        match snapshot.balance_state_root.as_deref() {
+            Some(balance_state_root) => Self::upsert_text_state_with_conn(
+                conn,
+                BALANCE_HISTORY_SNAPSHOT_BALANCE_STATE_ROOT_KEY,
+                balance_state_root,
+            )?,
+            None => Self::delete_text_state_with_conn(
+                conn,
+                BALANCE_HISTORY_SNAPSHOT_BALANCE_STATE_ROOT_KEY,
+            )?,
+        }
         Self::upsert_numeric_state_with_conn(
             conn,
             BALANCE_HISTORY_SNAPSHOT_HEIGHT_KEY,
@@ -676,12 +717,9 @@ impl MinerPassStorage {
         )?;
         Self::upsert_balance_history_snapshot_history_with_conn(
             conn,
-            snapshot.stable_height,
+            snapshot,
             &stable_block_hash,
             &latest_block_commit,
-            snapshot.stable_lag,
-            &snapshot.commit_protocol_version,
-            &snapshot.commit_hash_algo,
         )?;
 
         Ok(())
@@ -689,13 +727,11 @@ impl MinerPassStorage {
 
     fn upsert_balance_history_snapshot_history_with_conn(
         conn: &Connection,
-        block_height: u32,
+        snapshot: &BalanceHistorySnapshotInfo,
         stable_block_hash: &str,
         latest_block_commit: &str,
-        stable_lag: u32,
-        commit_protocol_version: &str,
-        commit_hash_algo: &str,
     ) -> Result<(), String> {
+        let block_height = snapshot.stable_height;
         conn.execute(
             "
             INSERT INTO balance_history_snapshot_history (
@@ -704,23 +740,26 @@ impl MinerPassStorage {
                 latest_block_commit,
                 stable_lag,
                 commit_protocol_version,
-                commit_hash_algo
+                commit_hash_algo,
+                balance_state_root
             )
-            VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)
             ON CONFLICT(block_height) DO UPDATE SET
                 stable_block_hash = excluded.stable_block_hash,
                 latest_block_commit = excluded.latest_block_commit,
                 stable_lag = excluded.stable_lag,
                 commit_protocol_version = excluded.commit_protocol_version,
-                commit_hash_algo = excluded.commit_hash_algo;
+                commit_hash_algo = excluded.commit_hash_algo,
+                balance_state_root = excluded.balance_state_root;
             ",
             rusqlite::params![
                 block_height as i64,
                 stable_block_hash,
                 latest_block_commit,
-                stable_lag as i64,
-                commit_protocol_version,
-                commit_hash_algo,
+                snapshot.stable_lag as i64,
+                snapshot.commit_protocol_version,
+                snapshot.commit_hash_algo,
+                snapshot.balance_state_root,
             ],
         )
         .map_err(|e| {
@@ -768,6 +807,7 @@ impl MinerPassStorage {
             BALANCE_HISTORY_SNAPSHOT_COMMIT_PROTOCOL_VERSION_KEY,
         )?;
         Self::delete_text_state_with_conn(conn, BALANCE_HISTORY_SNAPSHOT_COMMIT_HASH_ALGO_KEY)?;
+        Self::delete_text_state_with_conn(conn, BALANCE_HISTORY_SNAPSHOT_BALANCE_STATE_ROOT_KEY)?;
         Ok(())
     }
 
@@ -900,6 +940,8 @@ impl MinerPassStorage {
                     h.new_state AS state,
                     m.invalid_code,
                     m.invalid_reason,
+                    m.leader_pass_id,
+                    m.leader_btc_addr,
                     m.created_at
                 FROM miner_passes m
                 INNER JOIN latest l ON l.inscription_id = m.inscription_id
@@ -1017,6 +1059,8 @@ impl MinerPassStorage {
                     state,
                     invalid_code,
                     invalid_reason,
+                    leader_pass_id,
+                    leader_btc_addr,
                     created_at
                 )
                 SELECT
@@ -1033,6 +1077,8 @@ impl MinerPassStorage {
                     state,
                     invalid_code,
                     invalid_reason,
+                    leader_pass_id,
+                    leader_btc_addr,
                     created_at
                 FROM rollback_surviving_passes
                 ORDER BY mint_block_height ASC, inscription_id ASC;
@@ -1196,6 +1242,8 @@ impl MinerPassStorage {
                 error!("{}", msg);
                 msg
             })?;
+        let balance_state_root =
+            self.get_text_state(BALANCE_HISTORY_SNAPSHOT_BALANCE_STATE_ROOT_KEY)?;
 
         Ok(Some(BalanceHistorySnapshotAnchor {
             stable_height: stable_height as u32,
@@ -1204,6 +1252,7 @@ impl MinerPassStorage {
             stable_lag: stable_lag as u32,
             commit_protocol_version,
             commit_hash_algo,
+            balance_state_root,
         }))
     }
 
@@ -1221,7 +1270,8 @@ impl MinerPassStorage {
                     latest_block_commit,
                     stable_lag,
                     commit_protocol_version,
-                    commit_hash_algo
+                    commit_hash_algo,
+                    balance_state_root
                 FROM balance_history_snapshot_history
                 WHERE block_height = ?1
                 ",
@@ -1244,6 +1294,7 @@ impl MinerPassStorage {
                     row.get::<usize, i64>(3)?,
                     row.get::<usize, String>(4)?,
                     row.get::<usize, String>(5)?,
+                    row.get::<usize, Option<String>>(6)?,
                 ))
             })
             .optional()
@@ -1262,6 +1313,7 @@ impl MinerPassStorage {
             stable_lag,
             commit_protocol_version,
             commit_hash_algo,
+            balance_state_root,
         )) = row
         else {
             return Ok(None);
@@ -1291,6 +1343,7 @@ impl MinerPassStorage {
             stable_lag: stable_lag as u32,
             commit_protocol_version,
             commit_hash_algo,
+            balance_state_root,
         }))
     }
 
@@ -1379,6 +1432,33 @@ impl MinerPassStorage {
     }
 
     pub fn upsert_pass_block_commit(&self, entry: &PassBlockCommitEntry) -> Result<(), String> {
+        self.upsert_pass_block_commit_row(entry, None)
+    }
+
+    // Persist the commit row together with the exact mutation stream hashed into
+    // mutation_root, so auditors can recompute mutation_root and block_commit offline.
+    pub fn upsert_pass_block_commit_with_mutations(
+        &self,
+        entry: &PassBlockCommitEntry,
+        mutations: &[PassBlockMutation],
+    ) -> Result<(), String> {
+        let mutations_json = serde_json::to_string(mutations).map_err(|e| {
+            let msg = format!(
+                "Failed to serialize pass block mutations at height {}: {}",
+                entry.block_height, e
+            );
+            error!("{}", msg);
+            msg
+        })?;
+
+        self.upsert_pass_block_commit_row(entry, Some(mutations_json))
+    }
+
+    fn upsert_pass_block_commit_row(
+        &self,
+        entry: &PassBlockCommitEntry,
+        mutations_json: Option<String>,
+    ) -> Result<(), String> {
         let conn = self.conn.lock().unwrap();
         conn.execute(
             "
@@ -1389,16 +1469,18 @@ impl MinerPassStorage {
                 mutation_root,
                 block_commit,
                 commit_protocol_version,
-                commit_hash_algo
+                commit_hash_algo,
+                mutations_json
             )
-            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)
             ON CONFLICT(block_height) DO UPDATE SET
                 balance_history_block_height = excluded.balance_history_block_height,
                 balance_history_block_commit = excluded.balance_history_block_commit,
                 mutation_root = excluded.mutation_root,
                 block_commit = excluded.block_commit,
                 commit_protocol_version = excluded.commit_protocol_version,
-                commit_hash_algo = excluded.commit_hash_algo;
+                commit_hash_algo = excluded.commit_hash_algo,
+                mutations_json = excluded.mutations_json;
             ",
             rusqlite::params![
                 entry.block_height as i64,
@@ -1408,6 +1490,7 @@ impl MinerPassStorage {
                 entry.block_commit,
                 entry.commit_protocol_version,
                 entry.commit_hash_algo,
+                mutations_json,
             ],
         )
         .map_err(|e| {
@@ -1422,6 +1505,45 @@ impl MinerPassStorage {
         Ok(())
     }
 
+    // Get the mutation stream persisted with the commit at block_height.
+    // Returns None when no commit exists or the row predates mutation persistence.
+    pub fn get_pass_block_mutations(
+        &self,
+        block_height: u32,
+    ) -> Result<Option<Vec<PassBlockMutation>>, String> {
+        let conn = self.conn.lock().unwrap();
+        let mutations_json: Option<String> = conn
+            .query_row(
+                "SELECT mutations_json FROM pass_block_commits WHERE block_height = ?1",
+                rusqlite::params![block_height as i64],
+                |row| row.get(0),
+            )
+            .optional()
+            .map_err(|e| {
+                let msg = format!(
+                    "Failed to load pass block mutations at height {}: {}",
+                    block_height, e
+                );
+                error!("{}", msg);
+                msg
+            })?
+            .flatten();
+
+        let Some(mutations_json) = mutations_json else {
+            return Ok(None);
+        };
+        let mutations = serde_json::from_str(&mutations_json).map_err(|e| {
+            let msg = format!(
+                "Failed to parse pass block mutations at height {}: {}",
+                block_height, e
+            );
+            error!("{}", msg);
+            msg
+        })?;
+
+        Ok(Some(mutations))
+    }
+
     pub fn get_pass_block_commit(
         &self,
         block_height: u32,
@@ -1561,6 +1683,26 @@ impl MinerPassStorage {
         Ok(height.map(|h| h as u32))
     }
 
+    // Write a compacted, transactionally consistent copy of the whole database to path.
+    // The target file must not exist yet.
+    pub fn export_to_file(&self, path: &Path) -> Result<(), String> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "VACUUM INTO ?1",
+            rusqlite::params![path.to_string_lossy().to_string()],
+        )
+        .map_err(|e| {
+            let msg = format!(
+                "Failed to export MinerPassStorage database {:?} to {:?}: {}",
+                self.db_path, path, e
+            );
+            error!("{}", msg);
+            msg
+        })?;
+
+        Ok(())
+    }
+
     // Defensive guard for historical reads: fail fast if local state contains
     // any record beyond the target height, which usually indicates incomplete rollback.
     pub fn assert_no_data_after_block_height(&self, block_height: u32) -> Result<(), String> {
@@ -2144,8 +2286,10 @@ impl MinerPassStorage {
                 owner,
                 state,
                 invalid_code,
-                invalid_reason
-            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13);
+                invalid_reason,
+                leader_pass_id,
+                leader_btc_addr
+            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15);
             ",
             rusqlite::params![
                 pass_info.inscription_id.to_string(),
@@ -2161,6 +2305,8 @@ impl MinerPassStorage {
                 pass_info.state.as_str(),
                 pass_info.invalid_code,
                 pass_info.invalid_reason,
+                pass_info.leader_pass_id.as_ref().map(|id| id.to_string()),
+                pass_info.leader_btc_addr,
             ],
         )
         .map_err(|e| {
@@ -2606,6 +2752,26 @@ impl MinerPassStorage {
                 })?
         };
 
+        // Leader columns were appended by migration, so read them by name instead of
+        // by position to stay independent of the physical column order.
+        let leader_pass_id = row
+            .get::<_, Option<String>>("leader_pass_id")
+            .map_err(|e| {
+                let msg = format!(
+                    "Failed to get leader_pass_id field from miner pass row: {}",
+                    e
+                );
+                error!("{}", msg);
+                msg
+            })?
+            .map(|s| s.parse::<InscriptionId>())
+            .transpose()
+            .map_err(|e| {
+                let msg = format!("Failed to parse leader_pass_id from string: {}", e);
+                error!("{}", msg);
+                msg
+            })?;
+
         Ok(MinerPassInfo {
             inscription_id: row
                 .get::<_, String>(0)
@@ -2709,6 +2875,15 @@ impl MinerPassStorage {
                 error!("{}", msg);
                 msg
             })?,
+            leader_pass_id,
+            leader_btc_addr: row.get("leader_btc_addr").map_err(|e| {
+                let msg = format!(
+                    "Failed to get leader_btc_addr field from miner pass row: {}",
+                    e
+                );
+                error!("{}", msg);
+                msg
+            })?,
 
             owner: row
                 .get::<_, String>(9)
@@ -3800,7 +3975,9 @@ impl MinerPassStorage {
                 h.new_state AS state,
                 m.invalid_code,
                 m.invalid_reason,
-                h.block_height AS latest_event_height
+                h.block_height AS latest_event_height,
+                m.leader_pass_id,
+                m.leader_btc_addr
             FROM miner_pass_state_history h
             INNER JOIN latest l ON h.id = l.max_id
             INNER JOIN miner_passes m ON m.inscription_id = h.inscription_id
@@ -3985,7 +4162,9 @@ impl MinerPassStorage {
                 h.new_state AS state,
                 m.invalid_code,
                 m.invalid_reason,
-                h.block_height AS latest_event_height
+                h.block_height AS latest_event_height,
+                m.leader_pass_id,
+                m.leader_btc_addr
             FROM miner_pass_state_history h
             INNER JOIN latest l ON h.id = l.max_id
             INNER JOIN miner_passes m ON m.inscription_id = h.inscription_id
@@ -4170,6 +4349,139 @@ impl MinerPassStorage {
         Ok(results.into_iter().next())
     }
 
+    // Get all collab passes whose latest history state at block_height is active,
+    // together with their UIP-0004 Leader binding.
+    pub fn get_active_collab_passes_from_history_at_height(
+        &self,
+        block_height: u32,
+    ) -> Result<Vec<CollabMinerPassInfo>, String> {
+        let conn = self.conn.lock().unwrap();
+
+        let mut stmt = conn
+            .prepare(
+                "
+            WITH latest AS (
+                SELECT
+                    inscription_id,
+                    MAX(id) AS max_id
+                FROM miner_pass_state_history
+                WHERE block_height <= ?1
+                GROUP BY inscription_id
+            )
+            SELECT
+                h.inscription_id,
+                h.new_owner,
+                m.leader_pass_id,
+                m.leader_btc_addr
+            FROM miner_pass_state_history h
+            INNER JOIN latest l ON h.id = l.max_id
+            INNER JOIN miner_passes m ON m.inscription_id = h.inscription_id
+            WHERE h.new_state = ?2
+              AND (m.leader_pass_id IS NOT NULL OR m.leader_btc_addr IS NOT NULL)
+            ORDER BY h.inscription_id ASC;
+            ",
+            )
+            .map_err(|e| {
+                let msg = format!(
+                    "Failed to prepare statement to get active collab passes from history: {}",
+                    e
+                );
+                error!("{}", msg);
+                msg
+            })?;
+
+        let mut rows = stmt
+            .query(rusqlite::params![
+                block_height as i64,
+                MinerPassState::Active.as_str()
+            ])
+            .map_err(|e| {
+                let msg = format!(
+                    "Failed to query active collab passes from history: block_height={}, error={}",
+                    block_height, e
+                );
+                error!("{}", msg);
+                msg
+            })?;
+
+        let mut results = Vec::<CollabMinerPassInfo>::new();
+        while let Some(row) = rows.next().map_err(|e| {
+            let msg = format!("Failed to read active collab pass row from history: {}", e);
+            error!("{}", msg);
+            msg
+        })? {
+            let inscription_id = row
+                .get::<_, String>(0)
+                .map_err(|e| {
+                    let msg = format!(
+                        "Failed to get inscription_id from active collab pass row: {}",
+                        e
+                    );
+                    error!("{}", msg);
+                    msg
+                })?
+                .parse::<InscriptionId>()
+                .map_err(|e| {
+                    let msg = format!(
+                        "Failed to parse inscription_id from active collab pass row: {}",
+                        e
+                    );
+                    error!("{}", msg);
+                    msg
+                })?;
+            let owner = row
+                .get::<_, String>(1)
+                .map_err(|e| {
+                    let msg = format!("Failed to get owner from active collab pass row: {}", e);
+                    error!("{}", msg);
+                    msg
+                })?
+                .parse::<USDBScriptHash>()
+                .map_err(|e| {
+                    let msg = format!("Failed to parse owner from active collab pass row: {}", e);
+                    error!("{}", msg);
+                    msg
+                })?;
+            let leader_pass_id = row
+                .get::<_, Option<String>>(2)
+                .map_err(|e| {
+                    let msg = format!(
+                        "Failed to get leader_pass_id from active collab pass row: {}",
+                        e
+                    );
+                    error!("{}", msg);
+                    msg
+                })?
+                .map(|s| s.parse::<InscriptionId>())
+                .transpose()
+                .map_err(|e| {
+                    let msg = format!(
+                        "Failed to parse leader_pass_id from active collab pass row: {}",
+                        e
+                    );
+                    error!("{}", msg);
+                    msg
+                })?;
+            let leader_btc_addr = row.get::<_, Option<String>>(3).map_err(|e| {
+                let msg = format!(
+                    "Failed to get leader_btc_addr from active collab pass row: {}",
+                    e
+                );
+                error!("{}", msg);
+                msg
+            })?;
+
+            results.push(CollabMinerPassInfo {
+                inscription_id,
+                owner,
+                leader_pass_id,
+                leader_btc_addr,
+            });
+        }
+
+        Ok(results)
+    }
+
     pub fn get_invalid_pass_count_in_height_range(
         &self,
         from_height: u32,
@@ -4444,6 +4756,8 @@ mod tests {
             prev: vec![inscription_id(ins_tag.wrapping_add(2), 0)],
             invalid_code: None,
             invalid_reason: None,
+            leader_pass_id: None,
+            leader_btc_addr: None,
             owner,
             state,
         }
@@ -4573,6 +4887,75 @@ mod tests {
         std::fs::remove_dir_all(&dir).unwrap();
     }
 
+    #[test]
+    fn test_pass_storage_collab_leader_fields_and_active_collab_query() {
+        let dir = test_data_dir("collab_leader");
+        let storage = MinerPassStorage::new(&dir).unwrap();
+
+        let leader = make_pass(20, 0, script_hash(20), MinerPassState::Active, 100);
+        storage.add_new_mint_pass_at_height(&leader, 100).unwrap();
+
+        let mut collab_by_id = make_pass(21, 0, script_hash(21), MinerPassState::Active, 101);
+        collab_by_id.eth_main = String::new();
+        collab_by_id.eth_collab = None;
+        collab_by_id.leader_pass_id = Some(leader.inscription_id);
+        storage
+            .add_new_mint_pass_at_height(&collab_by_id, 101)
+            .unwrap();
+
+        let mut collab_by_addr = make_pass(22, 0, script_hash(22), MinerPassState::Active, 102);
+        collab_by_addr.eth_main = String::new();
+        collab_by_addr.eth_collab = None;
+        collab_by_addr.leader_btc_addr =
+            Some("bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4".to_string());
+        storage
+            .add_new_mint_pass_at_height(&collab_by_addr, 102)
+            .unwrap();
+
+        let loaded = storage
+            .get_pass_by_inscription_id(&collab_by_id.inscription_id)
+            .unwrap()
+            .unwrap();
+        assert!(loaded.is_collab());
+        assert_eq!(loaded.leader_pass_id, Some(leader.inscription_id));
+        assert_eq!(loaded.leader_btc_addr, None);
+        let loaded = storage
+            .get_pass_by_inscription_id(&leader.inscription_id)
+            .unwrap()
+            .unwrap();
+        assert!(!loaded.is_collab());
+
+        let collabs = storage
+            .get_active_collab_passes_from_history_at_height(101)
+            .unwrap();
+        assert_eq!(collabs.len(), 1);
+        assert_eq!(collabs[0].inscription_id, collab_by_id.inscription_id);
+
+        storage
+            .update_state_at_height(
+                &collab_by_id.inscription_id,
+                MinerPassState::Dormant,
+                MinerPassState::Active,
+                103,
+            )
+            .unwrap();
+        let collabs = storage
+            .get_active_collab_passes_from_history_at_height(102)
+            .unwrap();
+        assert_eq!(collabs.len(), 2);
+        let collabs = storage
+            .get_active_collab_passes_from_history_at_height(103)
+            .unwrap();
+        assert_eq!(collabs.len(), 1);
+        assert_eq!(collabs[0].inscription_id, collab_by_addr.inscription_id);
+        assert_eq!(
+            collabs[0].leader_btc_addr.as_deref(),
+            Some("bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4")
+        );
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
     #[test]
     fn test_pass_storage_paging_and_active_lookup() {
         let dir = test_data_dir("paging");
@@ -5834,6 +6217,7 @@ mod tests {
                     balance_history::BALANCE_HISTORY_SEMANTICS_VERSION.to_string(),
                 commit_protocol_version: "1.0.0".to_string(),
                 commit_hash_algo: "sha256".to_string(),
+                balance_state_root: None,
             })
             .unwrap();
         storage.update_synced_btc_block_height(100).unwrap();
@@ -5947,6 +6331,7 @@ mod tests {
                     balance_history::BALANCE_HISTORY_SEMANTICS_VERSION.to_string(),
                 commit_protocol_version: "1.0.0".to_string(),
                 commit_hash_algo: "sha256".to_string(),
+                balance_state_root: None,
             })
             .unwrap();
 
@@ -5981,6 +6366,7 @@ mod tests {
                     balance_history::BALANCE_HISTORY_SEMANTICS_VERSION.to_string(),
                 commit_protocol_version: "1.0.0".to_string(),
                 commit_hash_algo: "sha256".to_string(),
+                balance_state_root: None,
             })
             .unwrap();
 
@@ -6011,6 +6397,7 @@ mod tests {
                     balance_history::BALANCE_HISTORY_SEMANTICS_VERSION.to_string(),
                 commit_protocol_version: "1.0.0".to_string(),
                 commit_hash_algo: "sha256".to_string(),
+                balance_state_root: None,
             })
             .unwrap();
         storage
@@ -6025,6 +6412,7 @@ mod tests {
                     balance_history::BALANCE_HISTORY_SEMANTICS_VERSION.to_string(),
                 commit_protocol_version: "1.0.0".to_string(),
                 commit_hash_algo: "sha256".to_string(),
+                balance_state_root: Some("55".repeat(32)),
             })
             .unwrap();
 
@@ -6035,6 +6423,7 @@ mod tests {
         assert_eq!(anchor_120.stable_height, 120);
         assert_eq!(anchor_120.stable_block_hash, "11".repeat(32));
         assert_eq!(anchor_120.latest_block_commit, "22".repeat(32));
+        assert_eq!(anchor_120.balance_state_root, None);
 
         let anchor_130 = storage
             .get_balance_history_snapshot_anchor_at_height(130)
@@ -6043,6 +6432,15 @@ mod tests {
         assert_eq!(anchor_130.stable_height, 130);
         assert_eq!(anchor_130.stable_block_hash, "33".repeat(32));
         assert_eq!(anchor_130.latest_block_commit, "44".repeat(32));
+        assert_eq!(anchor_130.balance_state_root, Some("55".repeat(32)));
+        assert_eq!(
+            storage
+                .get_balance_history_snapshot_anchor()
+                .unwrap()
+                .unwrap()
+                .balance_state_root,
+            Some("55".repeat(32))
+        );
 
         assert!(
             storage
@@ -6083,6 +6481,70 @@ mod tests {
         std::fs::remove_dir_all(&dir).unwrap();
     }
 
+    #[test]
+    fn test_pass_block_mutations_round_trip_and_rollback() {
+        let dir = test_data_dir("pass_block_mutations_round_trip");
+        let storage = MinerPassStorage::new(&dir).unwrap();
+
+        let make_entry = |block_height: u32| PassBlockCommitEntry {
+            block_height,
+            balance_history_block_height: block_height,
+            balance_history_block_commit: "ab".repeat(32),
+            mutation_root: "cd".repeat(32),
+            block_commit: "ef".repeat(32),
+            commit_protocol_version: "1.0.0".to_string(),
+            commit_hash_algo: "sha256".to_string(),
+        };
+        let mutations = vec![
+            PassBlockMutation::StateTransition {
+                inscription_id: "txidi0".to_string(),
+                from_state: "active".to_string(),
+                to_state: "dormant".to_string(),
+                owner: "owner".to_string(),
+                satpoint: "txid:0:0".to_string(),
+            },
+            PassBlockMutation::SatpointUpdate {
+                inscription_id: "txidi1".to_string(),
+                state: "active".to_string(),
+                owner: "owner".to_string(),
+                from_satpoint: "txid:1:0".to_string(),
+                to_satpoint: "txid:2:0".to_string(),
+            },
+        ];
+
+        storage.upsert_pass_block_commit(&make_entry(100)).unwrap();
+        storage
+            .upsert_pass_block_commit_with_mutations(&make_entry(101), &mutations)
+            .unwrap();
+        storage
+            .upsert_pass_block_commit_with_mutations(&make_entry(102), &[])
+            .unwrap();
+
+        // Legacy rows and missing rows both have no retained stream.
+        assert!(storage.get_pass_block_mutations(100).unwrap().is_none());
+        assert!(storage.get_pass_block_mutations(99).unwrap().is_none());
+        assert_eq!(
+            storage.get_pass_block_mutations(101).unwrap().unwrap(),
+            mutations
+        );
+        assert!(
+            storage
+                .get_pass_block_mutations(102)
+                .unwrap()
+                .unwrap()
+                .is_empty()
+        );
+
+        storage.rollback_to_block_height(101, None).unwrap();
+        assert!(storage.get_pass_block_mutations(102).unwrap().is_none());
+        assert_eq!(
+            storage.get_pass_block_mutations(101).unwrap().unwrap(),
+            mutations
+        );
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
     #[test]
     fn test_get_latest_pass_block_commit_at_or_before_returns_nearest_entry() {
         let dir = test_data_dir("latest_pass_block_commit_at_or_before");
@@ -6214,6 +6676,7 @@ mod tests {
                     balance_history::BALANCE_HISTORY_SEMANTICS_VERSION.to_string(),
                 commit_protocol_version: "1.0.0".to_string(),
                 commit_hash_algo: "sha256".to_string(),
+                balance_state_root: None,
             })
             .unwrap();
 
@@ -0,0 +1,336 @@
+use super::energy::PassEnergyStorage;
+use super::pass::MinerPassStorage;
+use rusqlite::{Connection, OptionalExtension};
+use std::path::{Path, PathBuf};
+
+// Tables appended to the exported miner_pass.db copy. They only exist inside snapshot
+// files and are dropped again when the snapshot is staged for install.
+const SNAPSHOT_META_TABLE: &str = "usdb_snapshot_meta";
+const SNAPSHOT_PASS_ENERGY_TABLE: &str = "usdb_snapshot_pass_energy";
+
+const META_KEY_BLOCK_HEIGHT: &str = "block_height";
+const META_KEY_MINER_PASS_COUNT: &str = "miner_pass_count";
+const META_KEY_PASS_HISTORY_COUNT: &str = "pass_history_count";
+const META_KEY_PASS_BLOCK_COMMIT_COUNT: &str = "pass_block_commit_count";
+const META_KEY_ACTIVE_BALANCE_SNAPSHOT_COUNT: &str = "active_balance_snapshot_count";
+const META_KEY_PASS_ENERGY_RECORD_COUNT: &str = "pass_energy_record_count";
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StateSnapshotMeta {
+    // Local synced block height captured by the snapshot.
+    pub block_height: u32,
+    pub miner_pass_count: u64,
+    pub pass_history_count: u64,
+    pub pass_block_commit_count: u64,
+    pub active_balance_snapshot_count: u64,
+    pub pass_energy_record_count: u64,
+}
+
+// Single-file SQLite snapshot of the usdb-indexer durable state: a VACUUM INTO copy of
+// miner_pass.db plus the raw pass energy rows exported from the energy RocksDB.
+pub struct StateSnapshotDB {
+    file: PathBuf,
+    conn: Connection,
+}
+
+impl StateSnapshotDB {
+    // Export the live stores into a new snapshot file at path.
+    pub fn create(
+        path: &Path,
+        pass_storage: &MinerPassStorage,
+        energy_storage: &PassEnergyStorage,
+        block_height: u32,
+    ) -> Result<StateSnapshotMeta, String> {
+        if path.exists() {
+            let msg = format!("Snapshot file {} already exists", path.display());
+            error!("{}", msg);
+            return Err(msg);
+        }
+
+        pass_storage.export_to_file(path)?;
+        let mut snapshot = Self::open(path)?;
+        snapshot.create_snapshot_tables()?;
+
+        let tx = snapshot.conn.transaction().map_err(|e| {
+            let msg = format!(
+                "Failed to start snapshot energy export transaction {}: {}",
+                path.display(),
+                e
+            );
+            error!("{}", msg);
+            msg
+        })?;
+        let pass_energy_record_count = {
+            let mut stmt = tx
+                .prepare(&format!(
+                    "INSERT INTO {} (key, value) VALUES (?1, ?2)",
+                    SNAPSHOT_PASS_ENERGY_TABLE
+                ))
+                .map_err(|e| {
+                    let msg = format!("Failed to prepare snapshot energy insert: {}", e);
+                    error!("{}", msg);
+                    msg
+                })?;
+            energy_storage.for_each_raw_pass_energy_record(|key, value| {
+                stmt.execute(rusqlite::params![key, value])
+                    .map(|_| ())
+                    .map_err(|e| {
+                        let msg = format!("Failed to write snapshot energy row: {}", e);
+                        error!("{}", msg);
+                        msg
+                    })
+            })?
+        };
+        tx.commit().map_err(|e| {
+            let msg = format!(
+                "Failed to commit snapshot energy export {}: {}",
+                path.display(),
+                e
+            );
+            error!("{}", msg);
+            msg
+        })?;
+
+        let meta = StateSnapshotMeta {
+            block_height,
+            miner_pass_count: snapshot.count_rows("miner_passes")?,
+            pass_history_count: snapshot.count_rows("miner_pass_state_history")?,
+            pass_block_commit_count: snapshot.count_rows("pass_block_commits")?,
+            active_balance_snapshot_count: snapshot.count_rows("active_balance_snapshots")?,
+            pass_energy_record_count,
+        };
+        snapshot.update_meta(&meta)?;
+
+        Ok(meta)
+    }
+
+    pub fn open(path: &Path) -> Result<Self, String> {
+        let conn = Connection::open(path).map_err(|e| {
+            let msg = format!("Failed to open snapshot database {}: {}", path.display(), e);
+            error!("{}", msg);
+            msg
+        })?;
+
+        Ok(Self {
+            file: path.to_path_buf(),
+            conn,
+        })
+    }
+
+    fn create_snapshot_tables(&self) -> Result<(), String> {
+        self.conn
+            .execute_batch(&format!(
+                "
+                CREATE TABLE {} (
+                    name TEXT PRIMARY KEY,
+                    value INTEGER NOT NULL
+                );
+
+                CREATE TABLE {} (
+                    key BLOB PRIMARY KEY,
+                    value BLOB NOT NULL
+                );
+                ",
+                SNAPSHOT_META_TABLE, SNAPSHOT_PASS_ENERGY_TABLE
+            ))
+            .map_err(|e| {
+                let msg = format!(
+                    "Failed to create snapshot tables in {}: {}",
+                    self.file.display(),
+                    e
+                );
+                error!("{}", msg);
+                msg
+            })
+    }
+
+    fn count_rows(&self, table: &str) -> Result<u64, String> {
+        self.conn
+            .query_row(&format!("SELECT COUNT(*) FROM {}", table), [], |row| {
+                row.get::<_, i64>(0)
+            })
+            .map(|count| count as u64)
+            .map_err(|e| {
+                let msg = format!(
+                    "Failed to count rows of {} in snapshot {}: {}",
+                    table,
+                    self.file.display(),
+                    e
+                );
+                error!("{}", msg);
+                msg
+            })
+    }
+
+    fn update_meta(&self, meta: &StateSnapshotMeta) -> Result<(), String> {
+        let entries = [
+            (META_KEY_BLOCK_HEIGHT, meta.block_height as i64),
+            (META_KEY_MINER_PASS_COUNT, meta.miner_pass_count as i64),
+            (META_KEY_PASS_HISTORY_COUNT, meta.pass_history_count as i64),
+            (
+                META_KEY_PASS_BLOCK_COMMIT_COUNT,
+                meta.pass_block_commit_count as i64,
+            ),
+            (
+                META_KEY_ACTIVE_BALANCE_SNAPSHOT_COUNT,
+                meta.active_balance_snapshot_count as i64,
+            ),
+            (
+                META_KEY_PASS_ENERGY_RECORD_COUNT,
+                meta.pass_energy_record_count as i64,
+            ),
+        ];
+        for (name, value) in entries {
+            self.conn
+                .execute(
+                    &format!(
+                        "INSERT OR REPLACE INTO {} (name, value) VALUES (?1, ?2)",
+                        SNAPSHOT_META_TABLE
+                    ),
+                    rusqlite::params![name, value],
+                )
+                .map_err(|e| {
+                    let msg = format!(
+                        "Failed to write snapshot meta {} in {}: {}",
+                        name,
+                        self.file.display(),
+                        e
+                    );
+                    error!("{}", msg);
+                    msg
+                })?;
+        }
+
+        Ok(())
+    }
+
+    fn get_meta_value(&self, name: &str) -> Result<u64, String> {
+        let value: Option<i64> = self
+            .conn
+            .query_row(
+                &format!("SELECT value FROM {} WHERE name = ?1", SNAPSHOT_META_TABLE),
+                rusqlite::params![name],
+                |row| row.get(0),
+            )
+            .optional()
+            .map_err(|e| {
+                let msg = format!(
+                    "Failed to read snapshot meta {} from {}: {}",
+                    name,
+                    self.file.display(),
+                    e
+                );
+                error!("{}", msg);
+                msg
+            })?;
+
+        value.map(|value| value as u64).ok_or_else(|| {
+            let msg = format!("Missing snapshot meta {} in {}", name, self.file.display());
+            error!("{}", msg);
+            msg
+        })
+    }
+
+    pub fn get_meta(&self) -> Result<StateSnapshotMeta, String> {
+        Ok(StateSnapshotMeta {
+            block_height: self.get_meta_value(META_KEY_BLOCK_HEIGHT)? as u32,
+            miner_pass_count: self.get_meta_value(META_KEY_MINER_PASS_COUNT)?,
+            pass_history_count: self.get_meta_value(META_KEY_PASS_HISTORY_COUNT)?,
+            pass_block_commit_count: self.get_meta_value(META_KEY_PASS_BLOCK_COMMIT_COUNT)?,
+            active_balance_snapshot_count: self
+                .get_meta_value(META_KEY_ACTIVE_BALANCE_SNAPSHOT_COUNT)?,
+            pass_energy_record_count: self.get_meta_value(META_KEY_PASS_ENERGY_RECORD_COUNT)?,
+        })
+    }
+
+    // Materialize the snapshot into an empty data directory: copy the pass database,
+    // replay the energy rows into a fresh energy RocksDB and strip the snapshot-only
+    // tables. The caller opens the staged stores afterwards for validation.
+    pub fn install_into(&self, data_dir: &Path) -> Result<StateSnapshotMeta, String> {
+        let meta = self.get_meta()?;
+
+        let pass_db_path = data_dir.join(crate::constants::MINER_PASS_DB_FILE);
+        std::fs::copy(&self.file, &pass_db_path).map_err(|e| {
+            let msg = format!(
+                "Failed to copy snapshot {} to {}: {}",
+                self.file.display(),
+                pass_db_path.display(),
+                e
+            );
+            error!("{}", msg);
+            msg
+        })?;
+
+        let energy_storage = PassEnergyStorage::new(data_dir)?;
+        let mut stmt = self
+            .conn
+            .prepare(&format!(
+                "SELECT key, value FROM {} ORDER BY key",
+                SNAPSHOT_PASS_ENERGY_TABLE
+            ))
+            .map_err(|e| {
+                let msg = format!("Failed to prepare snapshot energy scan: {}", e);
+                error!("{}", msg);
+                msg
+            })?;
+        let mut rows = stmt.query([]).map_err(|e| {
+            let msg = format!("Failed to scan snapshot energy rows: {}", e);
+            error!("{}", msg);
+            msg
+        })?;
+        let mut imported = 0u64;
+        while let Some(row) = rows.next().map_err(|e| {
+            let msg = format!("Failed to read snapshot energy row: {}", e);
+            error!("{}", msg);
+            msg
+        })? {
+            let key: Vec<u8> = row.get(0).map_err(|e| {
+                let msg = format!("Failed to read snapshot energy key: {}", e);
+                error!("{}", msg);
+                msg
+            })?;
+            let value: Vec<u8> = row.get(1).map_err(|e| {
+                let msg = format!("Failed to read snapshot energy value: {}", e);
+                error!("{}", msg);
+                msg
+            })?;
+            energy_storage.insert_raw_pass_energy_record(&key, &value)?;
+            imported += 1;
+        }
+        if imported != meta.pass_energy_record_count {
+            let msg = format!(
+                "Snapshot energy row count mismatch: meta reports {}, imported {}",
+                meta.pass_energy_record_count, imported
+            );
+            error!("{}", msg);
+            return Err(msg);
+        }
+        energy_storage.finalize_block_sync(meta.block_height)?;
+
+        let staged = Connection::open(&pass_db_path).map_err(|e| {
+            let msg = format!(
+                "Failed to open staged pass database {}: {}",
+                pass_db_path.display(),
+                e
+            );
+            error!("{}", msg);
+            msg
+        })?;
+        staged
+            .execute_batch(&format!(
+                "DROP TABLE {}; DROP TABLE {}; VACUUM;",
+                SNAPSHOT_META_TABLE, SNAPSHOT_PASS_ENERGY_TABLE
+            ))
+            .map_err(|e| {
+                let msg = format!(
+                    "Failed to drop snapshot tables from staged pass database {}: {}",
+                    pass_db_path.display(),
+                    e
+                );
+                error!("{}", msg);
+                msg
+            })?;
+
+        Ok(meta)
+    }
+}
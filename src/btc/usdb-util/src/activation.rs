@@ -0,0 +1,917 @@
+use crate::balance_proof::{
+    BALANCE_HISTORY_COMMIT_PROTOCOL_VERSION_V1, BALANCE_HISTORY_COMMIT_PROTOCOL_VERSION_V2,
+};
+use crate::types::{encode_hex, update_string_component};
+use bitcoincore_rpc::bitcoin::Network;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// Version tag of the `activation_registry_id` derivation rule.
+pub const ACTIVATION_REGISTRY_ID_VERSION: &str = "usdb-activation-registry:v1";
+/// Version tag of the `active_version_set_id` derivation rule.
+pub const ACTIVE_VERSION_SET_ID_VERSION: &str = "usdb-active-version-set:v1";
+
+pub const INSCRIPTION_SCHEMA_VERSION_FAMILY: &str = "inscription_schema_version";
+pub const PASS_STATE_MACHINE_VERSION_FAMILY: &str = "pass_state_machine_version";
+pub const ENERGY_FORMULA_VERSION_FAMILY: &str = "energy_formula_version";
+pub const EFFECTIVE_ENERGY_FORMULA_VERSION_FAMILY: &str = "effective_energy_formula_version";
+pub const LEVEL_FORMULA_VERSION_FAMILY: &str = "level_formula_version";
+pub const COMMIT_PROTOCOL_VERSION_FAMILY: &str = "commit_protocol_version";
+/// balance-history block commit rule; values are `BALANCE_HISTORY_COMMIT_PROTOCOL_VERSION_*`.
+pub const BALANCE_HISTORY_COMMIT_PROTOCOL_VERSION_FAMILY: &str =
+    "balance_history_commit_protocol_version";
+
+pub const INSCRIPTION_SCHEMA_VERSION_V1: &str = "uip-0001-miner-pass-inscription:v1";
+pub const INSCRIPTION_SCHEMA_VERSION_V2: &str = "uip-0001-miner-pass-inscription:v2";
+pub const PASS_STATE_MACHINE_VERSION_V1: &str = "uip-0002-pass-state-machine:v1";
+pub const ENERGY_FORMULA_VERSION_V1: &str = "uip-0003-pass-energy-formula:v1";
+pub const ENERGY_FORMULA_VERSION_V2: &str = "uip-0003-pass-energy-formula:v2";
+pub const EFFECTIVE_ENERGY_FORMULA_VERSION_V1: &str = "uip-0004-collab-leader-effective-energy:v1";
+pub const LEVEL_FORMULA_VERSION_V1: &str = "uip-0005-level-and-real-difficulty:v1";
+pub const COMMIT_PROTOCOL_VERSION_V1: &str = "1.0.0";
+
+// Machine-readable UIP-0008 activation matrix shipped with this build.
+const BUILTIN_ACTIVATION_REGISTRY_JSON: &str = include_str!("../activation-registry.json");
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum ActivationChain {
+    BTC,
+    ETHW,
+    CrossChain,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ActivationAnchor {
+    BtcHeight,
+    EthwBlock,
+    Governance,
+    Manual,
+    Hybrid,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum ActivationStatus {
+    Planned,
+    Active,
+    Deferred,
+    Superseded,
+}
+
+impl ActivationChain {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ActivationChain::BTC => "BTC",
+            ActivationChain::ETHW => "ETHW",
+            ActivationChain::CrossChain => "CrossChain",
+        }
+    }
+}
+
+impl ActivationAnchor {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ActivationAnchor::BtcHeight => "btc_height",
+            ActivationAnchor::EthwBlock => "ethw_block",
+            ActivationAnchor::Governance => "governance",
+            ActivationAnchor::Manual => "manual",
+            ActivationAnchor::Hybrid => "hybrid",
+        }
+    }
+}
+
+impl ActivationStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ActivationStatus::Planned => "Planned",
+            ActivationStatus::Active => "Active",
+            ActivationStatus::Deferred => "Deferred",
+            ActivationStatus::Superseded => "Superseded",
+        }
+    }
+}
+
+/// One row of the UIP-0008 activation matrix.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ActivationRecord {
+    /// UIP that defines this version, for example `UIP-0003`.
+    pub uip: String,
+    /// Version family name, for example `energy_formula_version`.
+    pub version_family: String,
+    /// Concrete version value of the family.
+    pub version_value: String,
+    /// Chain whose anchor drives this activation.
+    pub chain: ActivationChain,
+    /// Network type, for example `mainnet` or `regtest`.
+    pub network_type: String,
+    /// Concrete network id, for example `btc-regtest`.
+    pub network_id: String,
+    /// Anchor kind that `activation_value` is measured in.
+    pub activation_anchor: ActivationAnchor,
+    /// First anchor value at which `version_value` applies.
+    pub activation_value: u64,
+    /// Lifecycle status; only `Active` records take part in lookups.
+    pub status: ActivationStatus,
+    /// Version value replaced by this record, if any.
+    #[serde(default)]
+    pub supersedes: Option<String>,
+    /// Human-readable notes; excluded from `activation_registry_id`.
+    #[serde(default)]
+    pub notes: Option<String>,
+}
+
+/// Version set resolved for one BTC network and height.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ActiveVersionSet {
+    /// Pass inscription JSON schema version.
+    pub inscription_schema_version: String,
+    /// Pass state machine version.
+    pub pass_state_machine_version: String,
+    /// Raw energy, penalty and inheritance formula version.
+    pub energy_formula_version: String,
+    /// Collab contribution and Leader effective energy formula version.
+    pub effective_energy_formula_version: String,
+    /// Effective energy to level and difficulty factor formula version. `None` where no
+    /// level formula is active yet, since levels require energy formula v2.
+    pub level_formula_version: Option<String>,
+    /// Local pass block commit encoding version.
+    pub commit_protocol_version: String,
+}
+
+impl ActiveVersionSet {
+    // Family/value pairs in canonical order (sorted by family name). Families that are
+    // not active at the height are omitted.
+    fn canonical_entries(&self) -> Vec<(&'static str, &str)> {
+        let mut entries = vec![
+            (
+                INSCRIPTION_SCHEMA_VERSION_FAMILY,
+                self.inscription_schema_version.as_str(),
+            ),
+            (
+                PASS_STATE_MACHINE_VERSION_FAMILY,
+                self.pass_state_machine_version.as_str(),
+            ),
+            (
+                ENERGY_FORMULA_VERSION_FAMILY,
+                self.energy_formula_version.as_str(),
+            ),
+            (
+                EFFECTIVE_ENERGY_FORMULA_VERSION_FAMILY,
+                self.effective_energy_formula_version.as_str(),
+            ),
+            (
+                COMMIT_PROTOCOL_VERSION_FAMILY,
+                self.commit_protocol_version.as_str(),
+            ),
+        ];
+        if let Some(level_formula_version) = &self.level_formula_version {
+            entries.push((LEVEL_FORMULA_VERSION_FAMILY, level_formula_version.as_str()));
+        }
+        entries.sort_by(|a, b| a.0.cmp(b.0));
+        entries
+    }
+}
+
+/// Builds the canonical `active_version_set_id` for one resolved version set.
+pub fn build_active_version_set_id(version_set: &ActiveVersionSet) -> String {
+    let entries = version_set.canonical_entries();
+
+    let mut hasher = Sha256::new();
+    update_string_component(&mut hasher, ACTIVE_VERSION_SET_ID_VERSION);
+    hasher.update((entries.len() as u32).to_be_bytes());
+    for (family, value) in entries {
+        update_string_component(&mut hasher, family);
+        update_string_component(&mut hasher, value);
+    }
+    encode_hex(&hasher.finalize())
+}
+
+/// Maps a BTC network to the UIP-0008 `network_id` used by activation records.
+pub fn btc_activation_network_id(network: Network) -> String {
+    match network {
+        Network::Bitcoin => "btc-mainnet".to_string(),
+        other => format!("btc-{}", other),
+    }
+}
+
+fn is_public_network_type(network_type: &str) -> bool {
+    matches!(network_type, "mainnet" | "testnet" | "signet")
+}
+
+/// Validated activation matrix.
+///
+/// Lookups only consider `Active` records. Networks that are not listed never
+/// resolve to any version, and two active records for the same family, network
+/// and activation value are rejected at load time rather than picked arbitrarily.
+#[derive(Debug, Clone)]
+pub struct ActivationRegistry {
+    records: Vec<ActivationRecord>,
+    registry_id: String,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ActivationRegistryFile {
+    records: Vec<ActivationRecord>,
+}
+
+impl ActivationRegistry {
+    pub fn new(records: Vec<ActivationRecord>) -> Result<Self, String> {
+        Self::validate_records(&records)?;
+        let registry_id = Self::build_registry_id(&records);
+        Ok(Self {
+            records,
+            registry_id,
+        })
+    }
+
+    /// Registry compiled into this build from `activation-registry.json`.
+    pub fn builtin() -> Result<Self, String> {
+        Self::from_json_str(BUILTIN_ACTIVATION_REGISTRY_JSON)
+    }
+
+    pub fn from_json_str(data: &str) -> Result<Self, String> {
+        let file: ActivationRegistryFile = serde_json::from_str(data).map_err(|e| {
+            let msg = format!("Failed to parse activation registry: {}", e);
+            error!("{}", msg);
+            msg
+        })?;
+        Self::new(file.records)
+    }
+
+    pub fn load_from_file(path: &Path) -> Result<Self, String> {
+        let data = std::fs::read_to_string(path).map_err(|e| {
+            let msg = format!(
+                "Failed to read activation registry file {}: {}",
+                path.display(),
+                e
+            );
+            error!("{}", msg);
+            msg
+        })?;
+        Self::from_json_str(&data)
+    }
+
+    pub fn records(&self) -> &[ActivationRecord] {
+        &self.records
+    }
+
+    /// Canonical sha256 id of all records, excluding notes.
+    pub fn activation_registry_id(&self) -> &str {
+        &self.registry_id
+    }
+
+    fn validate_records(records: &[ActivationRecord]) -> Result<(), String> {
+        // (chain, network_id, family) -> active records ordered by activation value.
+        let mut active: BTreeMap<(ActivationChain, &str, &str), Vec<&ActivationRecord>> =
+            BTreeMap::new();
+
+        for record in records {
+            if record.version_family.is_empty()
+                || record.version_value.is_empty()
+                || record.network_id.is_empty()
+            {
+                let msg = format!(
+                    "Invalid activation record: empty family, value or network_id, uip={}",
+                    record.uip
+                );
+                error!("{}", msg);
+                return Err(msg);
+            }
+
+            if record.activation_anchor == ActivationAnchor::Manual
+                && is_public_network_type(&record.network_type)
+            {
+                let msg = format!(
+                    "Invalid activation record: public network must not use manual activation, uip={}, family={}, network_id={}",
+                    record.uip, record.version_family, record.network_id
+                );
+                error!("{}", msg);
+                return Err(msg);
+            }
+
+            if record.status == ActivationStatus::Active {
+                active
+                    .entry((
+                        record.chain,
+                        record.network_id.as_str(),
+                        record.version_family.as_str(),
+                    ))
+                    .or_default()
+                    .push(record);
+            }
+        }
+
+        for ((chain, network_id, family), mut items) in active {
+            items.sort_by_key(|r| r.activation_value);
+            for pair in items.windows(2) {
+                let (prev, next) = (pair[0], pair[1]);
+                if prev.activation_value == next.activation_value {
+                    let msg = format!(
+                        "ACTIVATION_RECORD_CONFLICT: chain={}, network_id={}, family={}, activation_value={}, versions=[{}, {}]",
+                        chain.as_str(),
+                        network_id,
+                        family,
+                        next.activation_value,
+                        prev.version_value,
+                        next.version_value
+                    );
+                    error!("{}", msg);
+                    return Err(msg);
+                }
+
+                if next.supersedes.as_deref() != Some(prev.version_value.as_str()) {
+                    let msg = format!(
+                        "Invalid activation record: later version must supersede the previous one, chain={}, network_id={}, family={}, version={}, expected_supersedes={}, actual_supersedes={:?}",
+                        chain.as_str(),
+                        network_id,
+                        family,
+                        next.version_value,
+                        prev.version_value,
+                        next.supersedes
+                    );
+                    error!("{}", msg);
+                    return Err(msg);
+                }
+            }
+
+            if family == ENERGY_FORMULA_VERSION_FAMILY {
+                Self::validate_energy_formula_scale(chain, network_id, &items)?;
+            }
+            if family == BALANCE_HISTORY_COMMIT_PROTOCOL_VERSION_FAMILY {
+                Self::validate_balance_history_commit_protocol(chain, network_id, &items)?;
+            }
+        }
+
+        Self::validate_level_formula_activation(records)?;
+        Ok(())
+    }
+
+    // UIP-0005 thresholds are calibrated on the energy formula v2 scale, so a level
+    // formula must not become active on a network before energy formula v2 does.
+    fn validate_level_formula_activation(records: &[ActivationRecord]) -> Result<(), String> {
+        let is_active = |r: &ActivationRecord| {
+            r.status == ActivationStatus::Active
+                && r.activation_anchor == ActivationAnchor::BtcHeight
+        };
+
+        for level in records
+            .iter()
+            .filter(|r| is_active(r) && r.version_family == LEVEL_FORMULA_VERSION_FAMILY)
+        {
+            let energy = records
+                .iter()
+                .filter(|r| {
+                    is_active(r)
+                        && r.chain == level.chain
+                        && r.network_id == level.network_id
+                        && r.version_family == ENERGY_FORMULA_VERSION_FAMILY
+                        && r.activation_value <= level.activation_value
+                })
+                .max_by_key(|r| r.activation_value)
+                .map(|r| r.version_value.as_str());
+
+            if energy != Some(ENERGY_FORMULA_VERSION_V2) {
+                let msg = format!(
+                    "Invalid activation record: level formula must not activate before energy formula {}, chain={}, network_id={}, level_version={}, activation_value={}, energy_version={:?}",
+                    ENERGY_FORMULA_VERSION_V2,
+                    level.chain.as_str(),
+                    level.network_id,
+                    level.version_value,
+                    level.activation_value,
+                    energy
+                );
+                error!("{}", msg);
+                return Err(msg);
+            }
+        }
+
+        Ok(())
+    }
+
+    // Energy formula v1 is sat scaled and v2 counts 0.001 BTC units, about 1e9 apart.
+    // Stored energy is carried across activation heights as is and inherited through
+    // prev chains, so v2 may only start from genesis on a network without v1 history.
+    fn validate_energy_formula_scale(
+        chain: ActivationChain,
+        network_id: &str,
+        items: &[&ActivationRecord],
+    ) -> Result<(), String> {
+        let has_v1 = items
+            .iter()
+            .any(|r| r.version_value == ENERGY_FORMULA_VERSION_V1);
+        for record in items {
+            if has_v1 && record.version_value == ENERGY_FORMULA_VERSION_V2 {
+                let msg = format!(
+                    "ENERGY_SCALE_TRANSITION_UNSUPPORTED: chain={}, network_id={}, activation_value={}, energy formula {} must not supersede {} because stored energy is not rescaled",
+                    chain.as_str(),
+                    network_id,
+                    record.activation_value,
+                    ENERGY_FORMULA_VERSION_V2,
+                    ENERGY_FORMULA_VERSION_V1
+                );
+                error!("{}", msg);
+                return Err(msg);
+            }
+        }
+        Ok(())
+    }
+
+    // balance-history only knows the flat and Merkle delta root rules. Blocks are never
+    // recommitted, and proofs are only indexed from the Merkle activation onwards, so
+    // the Merkle rule must not be superseded once active.
+    fn validate_balance_history_commit_protocol(
+        chain: ActivationChain,
+        network_id: &str,
+        items: &[&ActivationRecord],
+    ) -> Result<(), String> {
+        for record in items {
+            if record.version_value != BALANCE_HISTORY_COMMIT_PROTOCOL_VERSION_V1
+                && record.version_value != BALANCE_HISTORY_COMMIT_PROTOCOL_VERSION_V2
+            {
+                let msg = format!(
+                    "Invalid activation record: unsupported balance-history commit protocol {}, chain={}, network_id={}, activation_value={}",
+                    record.version_value,
+                    chain.as_str(),
+                    network_id,
+                    record.activation_value
+                );
+                error!("{}", msg);
+                return Err(msg);
+            }
+        }
+
+        for pair in items.windows(2) {
+            if pair[0].version_value == BALANCE_HISTORY_COMMIT_PROTOCOL_VERSION_V2 {
+                let msg = format!(
+                    "Invalid activation record: balance-history commit protocol {} must not be superseded, chain={}, network_id={}, activation_value={}, version={}",
+                    BALANCE_HISTORY_COMMIT_PROTOCOL_VERSION_V2,
+                    chain.as_str(),
+                    network_id,
+                    pair[1].activation_value,
+                    pair[1].version_value
+                );
+                error!("{}", msg);
+                return Err(msg);
+            }
+        }
+        Ok(())
+    }
+
+    fn build_registry_id(records: &[ActivationRecord]) -> String {
+        let mut sorted: Vec<&ActivationRecord> = records.iter().collect();
+        sorted.sort_by(|a, b| {
+            (
+                a.chain,
+                &a.network_id,
+                &a.version_family,
+                a.activation_value,
+                &a.version_value,
+                a.status,
+            )
+                .cmp(&(
+                    b.chain,
+                    &b.network_id,
+                    &b.version_family,
+                    b.activation_value,
+                    &b.version_value,
+                    b.status,
+                ))
+        });
+
+        let mut hasher = Sha256::new();
+        update_string_component(&mut hasher, ACTIVATION_REGISTRY_ID_VERSION);
+        hasher.update((sorted.len() as u32).to_be_bytes());
+        for record in sorted {
+            update_string_component(&mut hasher, &record.uip);
+            update_string_component(&mut hasher, &record.version_family);
+            update_string_component(&mut hasher, &record.version_value);
+            update_string_component(&mut hasher, record.chain.as_str());
+            update_string_component(&mut hasher, &record.network_type);
+            update_string_component(&mut hasher, &record.network_id);
+            update_string_component(&mut hasher, record.activation_anchor.as_str());
+            hasher.update(record.activation_value.to_be_bytes());
+            update_string_component(&mut hasher, record.status.as_str());
+            hasher.update([if record.supersedes.is_some() { 1 } else { 0 }]);
+            if let Some(supersedes) = &record.supersedes {
+                update_string_component(&mut hasher, supersedes);
+            }
+        }
+        encode_hex(&hasher.finalize())
+    }
+
+    /// Resolves the active value of one BTC-side version family at `btc_height`.
+    ///
+    /// Historical queries must pass the target height here, never the current head.
+    pub fn lookup_btc_version(
+        &self,
+        network_id: &str,
+        version_family: &str,
+        btc_height: u32,
+    ) -> Result<String, String> {
+        self.find_btc_version(network_id, version_family, btc_height)?
+            .ok_or_else(|| {
+                let msg = format!(
+                    "ACTIVATION_RECORD_NOT_FOUND: network_id={}, family={}, btc_height={}",
+                    network_id, version_family, btc_height
+                );
+                error!("{}", msg);
+                msg
+            })
+    }
+
+    /// Like `lookup_btc_version`, but a family with no active record at `btc_height`
+    /// resolves to `None` instead of an error.
+    pub fn find_btc_version(
+        &self,
+        network_id: &str,
+        version_family: &str,
+        btc_height: u32,
+    ) -> Result<Option<String>, String> {
+        let mut best: Option<&ActivationRecord> = None;
+        for record in &self.records {
+            if record.status != ActivationStatus::Active
+                || record.chain != ActivationChain::BTC
+                || record.activation_anchor != ActivationAnchor::BtcHeight
+                || record.network_id != network_id
+                || record.version_family != version_family
+                || record.activation_value > btc_height as u64
+            {
+                continue;
+            }
+
+            match best {
+                Some(current) if current.activation_value == record.activation_value => {
+                    let msg = format!(
+                        "ACTIVATION_RECORD_CONFLICT: network_id={}, family={}, btc_height={}, versions=[{}, {}]",
+                        network_id,
+                        version_family,
+                        btc_height,
+                        current.version_value,
+                        record.version_value
+                    );
+                    error!("{}", msg);
+                    return Err(msg);
+                }
+                Some(current) if current.activation_value > record.activation_value => {}
+                _ => best = Some(record),
+            }
+        }
+
+        Ok(best.map(|r| r.version_value.clone()))
+    }
+
+    /// Lists the BTC heights at which `version_family` changes value on one network,
+    /// ordered by height. Used by formulas that must split work across activation heights.
+    pub fn btc_activation_points(
+        &self,
+        network_id: &str,
+        version_family: &str,
+    ) -> Vec<(u32, String)> {
+        let mut points: Vec<(u32, String)> = self
+            .records
+            .iter()
+            .filter(|r| {
+                r.status == ActivationStatus::Active
+                    && r.chain == ActivationChain::BTC
+                    && r.activation_anchor == ActivationAnchor::BtcHeight
+                    && r.network_id == network_id
+                    && r.version_family == version_family
+            })
+            .filter_map(|r| {
+                u32::try_from(r.activation_value)
+                    .ok()
+                    .map(|h| (h, r.version_value.clone()))
+            })
+            .collect();
+        points.sort_by_key(|(h, _)| *h);
+        points
+    }
+
+    /// Resolves the full BTC-side active version set at `btc_height`.
+    pub fn lookup_btc_active_version_set(
+        &self,
+        network_id: &str,
+        btc_height: u32,
+    ) -> Result<ActiveVersionSet, String> {
+        Ok(ActiveVersionSet {
+            inscription_schema_version: self.lookup_btc_version(
+                network_id,
+                INSCRIPTION_SCHEMA_VERSION_FAMILY,
+                btc_height,
+            )?,
+            pass_state_machine_version: self.lookup_btc_version(
+                network_id,
+                PASS_STATE_MACHINE_VERSION_FAMILY,
+                btc_height,
+            )?,
+            energy_formula_version: self.lookup_btc_version(
+                network_id,
+                ENERGY_FORMULA_VERSION_FAMILY,
+                btc_height,
+            )?,
+            effective_energy_formula_version: self.lookup_btc_version(
+                network_id,
+                EFFECTIVE_ENERGY_FORMULA_VERSION_FAMILY,
+                btc_height,
+            )?,
+            level_formula_version: self.find_btc_version(
+                network_id,
+                LEVEL_FORMULA_VERSION_FAMILY,
+                btc_height,
+            )?,
+            commit_protocol_version: self.lookup_btc_version(
+                network_id,
+                COMMIT_PROTOCOL_VERSION_FAMILY,
+                btc_height,
+            )?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn make_record(family: &str, value: &str, height: u64) -> ActivationRecord {
+        ActivationRecord {
+            uip: "UIP-0003".to_string(),
+            version_family: family.to_string(),
+            version_value: value.to_string(),
+            chain: ActivationChain::BTC,
+            network_type: "regtest".to_string(),
+            network_id: "btc-regtest".to_string(),
+            activation_anchor: ActivationAnchor::BtcHeight,
+            activation_value: height,
+            status: ActivationStatus::Active,
+            supersedes: None,
+            notes: None,
+        }
+    }
+
+    #[test]
+    fn test_builtin_registry_resolves_from_genesis() {
+        let registry = ActivationRegistry::builtin().unwrap();
+        assert_eq!(registry.activation_registry_id().len(), 64);
+
+        for network in [
+            Network::Bitcoin,
+            Network::Testnet,
+            Network::Testnet4,
+            Network::Signet,
+            Network::Regtest,
+        ] {
+            let network_id = btc_activation_network_id(network);
+            let set = registry
+                .lookup_btc_active_version_set(&network_id, 0)
+                .unwrap();
+            assert_eq!(
+                set.inscription_schema_version,
+                INSCRIPTION_SCHEMA_VERSION_V1
+            );
+            assert_eq!(
+                set.pass_state_machine_version,
+                PASS_STATE_MACHINE_VERSION_V1
+            );
+            assert_eq!(
+                set.effective_energy_formula_version,
+                EFFECTIVE_ENERGY_FORMULA_VERSION_V1
+            );
+            assert_eq!(set.commit_protocol_version, COMMIT_PROTOCOL_VERSION_V1);
+
+            // Only regtest runs energy formula v2, and levels follow it.
+            if network == Network::Regtest {
+                assert_eq!(set.energy_formula_version, ENERGY_FORMULA_VERSION_V2);
+                assert_eq!(
+                    set.level_formula_version.as_deref(),
+                    Some(LEVEL_FORMULA_VERSION_V1)
+                );
+            } else {
+                assert_eq!(set.energy_formula_version, ENERGY_FORMULA_VERSION_V1);
+                assert_eq!(set.level_formula_version, None);
+            }
+
+            // balance-history commits with the Merkle delta root only on regtest.
+            let balance_history_commit_protocol = registry
+                .lookup_btc_version(
+                    &network_id,
+                    BALANCE_HISTORY_COMMIT_PROTOCOL_VERSION_FAMILY,
+                    0,
+                )
+                .unwrap();
+            if network == Network::Regtest {
+                assert_eq!(
+                    balance_history_commit_protocol,
+                    BALANCE_HISTORY_COMMIT_PROTOCOL_VERSION_V2
+                );
+            } else {
+                assert_eq!(
+                    balance_history_commit_protocol,
+                    BALANCE_HISTORY_COMMIT_PROTOCOL_VERSION_V1
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_lookup_selects_version_by_height() {
+        let mut v2 = make_record(ENERGY_FORMULA_VERSION_FAMILY, "energy:v2", 200);
+        v2.supersedes = Some("energy:v1".to_string());
+        let registry = ActivationRegistry::new(vec![
+            make_record(ENERGY_FORMULA_VERSION_FAMILY, "energy:v1", 0),
+            v2,
+        ])
+        .unwrap();
+
+        let lookup = |h| {
+            registry
+                .lookup_btc_version("btc-regtest", ENERGY_FORMULA_VERSION_FAMILY, h)
+                .unwrap()
+        };
+        assert_eq!(lookup(0), "energy:v1");
+        assert_eq!(lookup(199), "energy:v1");
+        assert_eq!(lookup(200), "energy:v2");
+        assert_eq!(lookup(201), "energy:v2");
+
+        assert_eq!(
+            registry.btc_activation_points("btc-regtest", ENERGY_FORMULA_VERSION_FAMILY),
+            vec![(0, "energy:v1".to_string()), (200, "energy:v2".to_string())]
+        );
+        assert!(
+            registry
+                .btc_activation_points("btc-mainnet", ENERGY_FORMULA_VERSION_FAMILY)
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_lookup_fails_closed_for_unlisted_network_and_planned_records() {
+        let mut planned = make_record(ENERGY_FORMULA_VERSION_FAMILY, "energy:v1", 0);
+        planned.status = ActivationStatus::Planned;
+        let registry = ActivationRegistry::new(vec![planned]).unwrap();
+
+        let err = registry
+            .lookup_btc_version("btc-regtest", ENERGY_FORMULA_VERSION_FAMILY, 10)
+            .unwrap_err();
+        assert!(err.contains("ACTIVATION_RECORD_NOT_FOUND"));
+
+        let registry = ActivationRegistry::builtin().unwrap();
+        let err = registry
+            .lookup_btc_version("btc-unknown", ENERGY_FORMULA_VERSION_FAMILY, 10)
+            .unwrap_err();
+        assert!(err.contains("ACTIVATION_RECORD_NOT_FOUND"));
+    }
+
+    #[test]
+    fn test_registry_rejects_conflicts_and_missing_supersedes() {
+        let err = ActivationRegistry::new(vec![
+            make_record(ENERGY_FORMULA_VERSION_FAMILY, "energy:v1", 0),
+            make_record(ENERGY_FORMULA_VERSION_FAMILY, "energy:v2", 0),
+        ])
+        .unwrap_err();
+        assert!(err.contains("ACTIVATION_RECORD_CONFLICT"));
+
+        let err = ActivationRegistry::new(vec![
+            make_record(ENERGY_FORMULA_VERSION_FAMILY, "energy:v1", 0),
+            make_record(ENERGY_FORMULA_VERSION_FAMILY, "energy:v2", 100),
+        ])
+        .unwrap_err();
+        assert!(err.contains("supersede"));
+    }
+
+    #[test]
+    fn test_registry_rejects_energy_v2_over_v1_history() {
+        let mut v2 = make_record(
+            ENERGY_FORMULA_VERSION_FAMILY,
+            ENERGY_FORMULA_VERSION_V2,
+            150,
+        );
+        v2.supersedes = Some(ENERGY_FORMULA_VERSION_V1.to_string());
+        let err = ActivationRegistry::new(vec![
+            make_record(ENERGY_FORMULA_VERSION_FAMILY, ENERGY_FORMULA_VERSION_V1, 0),
+            v2,
+        ])
+        .unwrap_err();
+        assert!(err.contains("ENERGY_SCALE_TRANSITION_UNSUPPORTED"));
+
+        // A network without v1 history may run v2 from genesis.
+        let registry = ActivationRegistry::new(vec![make_record(
+            ENERGY_FORMULA_VERSION_FAMILY,
+            ENERGY_FORMULA_VERSION_V2,
+            0,
+        )])
+        .unwrap();
+        assert_eq!(
+            registry
+                .lookup_btc_version("btc-regtest", ENERGY_FORMULA_VERSION_FAMILY, 10)
+                .unwrap(),
+            ENERGY_FORMULA_VERSION_V2
+        );
+    }
+
+    #[test]
+    fn test_registry_rejects_level_formula_before_energy_v2() {
+        let level = make_record(LEVEL_FORMULA_VERSION_FAMILY, LEVEL_FORMULA_VERSION_V1, 0);
+        let err = ActivationRegistry::new(vec![
+            make_record(ENERGY_FORMULA_VERSION_FAMILY, ENERGY_FORMULA_VERSION_V1, 0),
+            level.clone(),
+        ])
+        .unwrap_err();
+        assert!(err.contains("level formula must not activate before"));
+
+        let err = ActivationRegistry::new(vec![level.clone()]).unwrap_err();
+        assert!(err.contains("level formula must not activate before"));
+
+        let registry = ActivationRegistry::new(vec![
+            make_record(ENERGY_FORMULA_VERSION_FAMILY, ENERGY_FORMULA_VERSION_V2, 0),
+            level,
+        ])
+        .unwrap();
+        assert_eq!(
+            registry
+                .find_btc_version("btc-regtest", LEVEL_FORMULA_VERSION_FAMILY, 10)
+                .unwrap()
+                .as_deref(),
+            Some(LEVEL_FORMULA_VERSION_V1)
+        );
+        assert_eq!(
+            registry
+                .find_btc_version("btc-mainnet", LEVEL_FORMULA_VERSION_FAMILY, 10)
+                .unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn test_registry_rejects_balance_history_commit_protocol_downgrade() {
+        let family = BALANCE_HISTORY_COMMIT_PROTOCOL_VERSION_FAMILY;
+        let mut v2 = make_record(family, BALANCE_HISTORY_COMMIT_PROTOCOL_VERSION_V2, 100);
+        v2.supersedes = Some(BALANCE_HISTORY_COMMIT_PROTOCOL_VERSION_V1.to_string());
+        let registry = ActivationRegistry::new(vec![
+            make_record(family, BALANCE_HISTORY_COMMIT_PROTOCOL_VERSION_V1, 0),
+            v2.clone(),
+        ])
+        .unwrap();
+        assert_eq!(
+            registry.btc_activation_points("btc-regtest", family),
+            vec![
+                (0, BALANCE_HISTORY_COMMIT_PROTOCOL_VERSION_V1.to_string()),
+                (100, BALANCE_HISTORY_COMMIT_PROTOCOL_VERSION_V2.to_string())
+            ]
+        );
+
+        let mut v1 = make_record(family, BALANCE_HISTORY_COMMIT_PROTOCOL_VERSION_V1, 200);
+        v1.supersedes = Some(BALANCE_HISTORY_COMMIT_PROTOCOL_VERSION_V2.to_string());
+        let err = ActivationRegistry::new(vec![
+            make_record(family, BALANCE_HISTORY_COMMIT_PROTOCOL_VERSION_V1, 0),
+            v2,
+            v1,
+        ])
+        .unwrap_err();
+        assert!(err.contains("must not be superseded"));
+
+        let err = ActivationRegistry::new(vec![make_record(family, "3.0.0", 0)]).unwrap_err();
+        assert!(err.contains("unsupported balance-history commit protocol"));
+    }
+
+    #[test]
+    fn test_registry_rejects_manual_activation_on_public_network() {
+        let mut record = make_record(ENERGY_FORMULA_VERSION_FAMILY, "energy:v1", 0);
+        record.network_type = "mainnet".to_string();
+        record.network_id = "btc-mainnet".to_string();
+        record.activation_anchor = ActivationAnchor::Manual;
+        assert!(ActivationRegistry::new(vec![record.clone()]).is_err());
+
+        record.network_type = "local".to_string();
+        record.network_id = "btc-local".to_string();
+        assert!(ActivationRegistry::new(vec![record]).is_ok());
+    }
+
+    #[test]
+    fn test_ids_are_stable_and_ignore_notes() {
+        let mut record = make_record(ENERGY_FORMULA_VERSION_FAMILY, "energy:v1", 0);
+        let a = ActivationRegistry::new(vec![record.clone()]).unwrap();
+        record.notes = Some("changed".to_string());
+        let b = ActivationRegistry::new(vec![record.clone()]).unwrap();
+        assert_eq!(a.activation_registry_id(), b.activation_registry_id());
+
+        record.activation_value = 1;
+        let c = ActivationRegistry::new(vec![record]).unwrap();
+        assert_ne!(a.activation_registry_id(), c.activation_registry_id());
+
+        let set = ActivationRegistry::builtin()
+            .unwrap()
+            .lookup_btc_active_version_set("btc-regtest", 100)
+            .unwrap();
+        let id = build_active_version_set_id(&set);
+        assert_eq!(id, build_active_version_set_id(&set.clone()));
+        assert_eq!(id.len(), 64);
+
+        let mut changed = set;
+        changed.energy_formula_version = "energy:v2".to_string();
+        assert_ne!(id, build_active_version_set_id(&changed));
+    }
+}
@@ -0,0 +1,518 @@
+use crate::activation::{
+    ActivationRegistry, BALANCE_HISTORY_COMMIT_PROTOCOL_VERSION_FAMILY, btc_activation_network_id,
+};
+use crate::hash::USDBScriptHash;
+use crate::types::encode_hex;
+use bitcoincore_rpc::bitcoin::hex::FromHex;
+use bitcoincore_rpc::bitcoin::{BlockHash, Network};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::str::FromStr;
+use std::sync::LazyLock;
+
+/// Commit protocol version of the flat balance delta root rule.
+pub const BALANCE_HISTORY_COMMIT_PROTOCOL_VERSION_V1: &str = "1.0.0";
+/// Commit protocol version of the Merkle balance delta root rule.
+pub const BALANCE_HISTORY_COMMIT_PROTOCOL_VERSION_V2: &str = "2.0.0";
+
+const BLOCK_DELTA_ROOT_V2_TAG: &[u8] = b"balance-history:block-delta-root:v2";
+const DELTA_LEAF_TAG: &[u8] = b"balance-history:delta-leaf:v2";
+const DELTA_NODE_TAG: &[u8] = b"balance-history:delta-node:v2";
+const BLOCK_COMMIT_TAG: &[u8] = b"balance-history:block-commit:v1";
+
+// Merkle root of a block without any balance entries.
+const EMPTY_MERKLE_ROOT: [u8; 32] = [0u8; 32];
+
+// balance-history takes no registry override, so every node resolves the delta root
+// rule from the registry compiled into its build.
+static BUILTIN_ACTIVATION_REGISTRY: LazyLock<ActivationRegistry> = LazyLock::new(|| {
+    ActivationRegistry::builtin().expect("Built-in activation registry must be valid")
+});
+
+/// Rule used to derive the per-block balance delta root.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BalanceDeltaRootRule {
+    /// sha256 over the concatenated entries; no inclusion proofs.
+    FlatV1,
+    /// Merkle tree over the entries sorted by script hash.
+    MerkleV2,
+}
+
+impl BalanceDeltaRootRule {
+    /// Resolves the rule that committed `block_height` on `network` from the built-in
+    /// UIP-0008 activation registry.
+    pub fn at_height(network: Network, block_height: u32) -> Self {
+        Self::resolve(&BUILTIN_ACTIVATION_REGISTRY, network, block_height)
+    }
+
+    /// Resolves the rule from `registry`; heights without an active record stay flat.
+    pub fn resolve(registry: &ActivationRegistry, network: Network, block_height: u32) -> Self {
+        let version = registry.find_btc_version(
+            &btc_activation_network_id(network),
+            BALANCE_HISTORY_COMMIT_PROTOCOL_VERSION_FAMILY,
+            block_height,
+        );
+        match version {
+            Ok(Some(version)) if version == BALANCE_HISTORY_COMMIT_PROTOCOL_VERSION_V2 => {
+                Self::MerkleV2
+            }
+            _ => Self::FlatV1,
+        }
+    }
+
+    pub fn commit_protocol_version(&self) -> &'static str {
+        match self {
+            Self::FlatV1 => BALANCE_HISTORY_COMMIT_PROTOCOL_VERSION_V1,
+            Self::MerkleV2 => BALANCE_HISTORY_COMMIT_PROTOCOL_VERSION_V2,
+        }
+    }
+}
+
+/// First BTC height committed with the Merkle delta root on `network`, taken from the
+/// `balance_history_commit_protocol_version` records of the built-in activation registry.
+///
+/// Public networks stay on the flat rule until an activation record is agreed: nodes
+/// that already committed blocks under v1 must all switch at the same height,
+/// otherwise their rolling block commits diverge. Regtest uses the Merkle rule from
+/// genesis, so regtest DBs committed under the flat rule must be resynced.
+pub fn balance_delta_merkle_activation_height(network: Network) -> Option<u32> {
+    BUILTIN_ACTIVATION_REGISTRY
+        .btc_activation_points(
+            &btc_activation_network_id(network),
+            BALANCE_HISTORY_COMMIT_PROTOCOL_VERSION_FAMILY,
+        )
+        .into_iter()
+        .find(|(_, version)| version == BALANCE_HISTORY_COMMIT_PROTOCOL_VERSION_V2)
+        .map(|(height, _)| height)
+}
+
+/// One Merkle leaf: the balance-history entry of a script at the committed block.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BalanceDeltaLeaf {
+    /// Script hash in balance-history's canonical internal format.
+    pub script_hash: USDBScriptHash,
+    /// Signed balance delta applied by the block, in satoshi.
+    pub delta: i64,
+    /// Balance after the block is applied, in satoshi.
+    pub balance: u64,
+}
+
+pub fn hash_balance_delta_leaf(leaf: &BalanceDeltaLeaf) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update(DELTA_LEAF_TAG);
+    hasher.update(leaf.script_hash.as_ref() as &[u8]);
+    hasher.update(leaf.delta.to_be_bytes());
+    hasher.update(leaf.balance.to_be_bytes());
+    hasher.finalize().into()
+}
+
+fn hash_balance_delta_node(left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update(DELTA_NODE_TAG);
+    hasher.update(left);
+    hasher.update(right);
+    hasher.finalize().into()
+}
+
+// Hashes one tree level into the next. An odd trailing node is promoted unchanged
+// instead of being paired with itself, so no two leaf lists share a root.
+fn next_merkle_level(level: &[[u8; 32]]) -> Vec<[u8; 32]> {
+    level
+        .chunks(2)
+        .map(|pair| match pair {
+            [left, right] => hash_balance_delta_node(left, right),
+            [single] => *single,
+            _ => unreachable!(),
+        })
+        .collect()
+}
+
+/// Computes the Merkle root over leaf hashes already sorted by script hash.
+pub fn compute_balance_delta_merkle_root(leaf_hashes: &[[u8; 32]]) -> [u8; 32] {
+    if leaf_hashes.is_empty() {
+        return EMPTY_MERKLE_ROOT;
+    }
+
+    let mut level = leaf_hashes.to_vec();
+    while level.len() > 1 {
+        level = next_merkle_level(&level);
+    }
+    level[0]
+}
+
+/// Collects the sibling hashes of one leaf, ordered from the leaf level upwards.
+///
+/// Levels where the node is promoted without a sibling contribute nothing.
+pub fn build_balance_delta_merkle_path(
+    leaf_hashes: &[[u8; 32]],
+    leaf_index: usize,
+) -> Option<Vec<[u8; 32]>> {
+    if leaf_index >= leaf_hashes.len() {
+        return None;
+    }
+
+    let mut siblings = Vec::new();
+    let mut index = leaf_index;
+    let mut level = leaf_hashes.to_vec();
+    while level.len() > 1 {
+        let sibling_index = index ^ 1;
+        if sibling_index < level.len() {
+            siblings.push(level[sibling_index]);
+        }
+        index /= 2;
+        level = next_merkle_level(&level);
+    }
+    Some(siblings)
+}
+
+/// Folds one leaf hash and its sibling path back into the Merkle root.
+///
+/// Returns `None` when the path length does not match the tree shape implied by
+/// `leaf_index` and `leaf_count`.
+pub fn compute_balance_delta_merkle_root_from_path(
+    leaf_hash: &[u8; 32],
+    leaf_index: u32,
+    leaf_count: u32,
+    siblings: &[[u8; 32]],
+) -> Option<[u8; 32]> {
+    if leaf_index >= leaf_count {
+        return None;
+    }
+
+    let mut current = *leaf_hash;
+    let mut index = leaf_index;
+    let mut count = leaf_count;
+    let mut siblings = siblings.iter();
+    while count > 1 {
+        if index % 2 == 1 {
+            current = hash_balance_delta_node(siblings.next()?, &current);
+        } else if index + 1 < count {
+            current = hash_balance_delta_node(&current, siblings.next()?);
+        }
+        index /= 2;
+        count = count.div_ceil(2);
+    }
+
+    if siblings.next().is_some() {
+        return None;
+    }
+    Some(current)
+}
+
+/// Binds the Merkle root to its block and leaf count under the v2 delta root rule.
+pub fn compute_balance_delta_root_v2(
+    block_height: u32,
+    block_hash: &BlockHash,
+    leaf_count: u32,
+    merkle_root: &[u8; 32],
+) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update(BLOCK_DELTA_ROOT_V2_TAG);
+    hasher.update(block_height.to_be_bytes());
+    hasher.update(block_hash.as_ref() as &[u8]);
+    hasher.update(leaf_count.to_be_bytes());
+    hasher.update(merkle_root);
+    hasher.finalize().into()
+}
+
+/// Links one block's delta root to the previous committed block.
+pub fn compute_balance_history_block_commit(
+    block_height: u32,
+    block_hash: &BlockHash,
+    balance_delta_root: &[u8; 32],
+    prev_block_commit: &[u8; 32],
+) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update(BLOCK_COMMIT_TAG);
+    hasher.update(block_height.to_be_bytes());
+    hasher.update(block_hash.as_ref() as &[u8]);
+    hasher.update(balance_delta_root);
+    hasher.update(prev_block_commit);
+    hasher.finalize().into()
+}
+
+/// Inclusion proof of one balance-history entry in a block commit.
+///
+/// Hashes are lowercase hex; `btc_block_hash` uses the usual BTC display order.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BalanceDeltaProof {
+    /// BTC height of the block that wrote `leaf`.
+    pub block_height: u32,
+    /// BTC block hash bound into the block commit.
+    pub btc_block_hash: String,
+    /// Proven balance-history entry.
+    pub leaf: BalanceDeltaLeaf,
+    /// Position of `leaf` among the block's entries sorted by script hash.
+    pub leaf_index: u32,
+    /// Number of entries written by the block.
+    pub leaf_count: u32,
+    /// Sibling hashes from the leaf level upwards.
+    pub siblings: Vec<String>,
+    /// Delta root stored in the block commit.
+    pub balance_delta_root: String,
+    /// Block commit of the previous height, all zeros at genesis.
+    pub prev_block_commit: String,
+    /// Block commit at `block_height`.
+    pub block_commit: String,
+    /// Commit protocol version of the block, always the Merkle rule.
+    pub commit_protocol_version: String,
+}
+
+/// Builds the proof of `leaves[leaf_index]` for one Merkle-rule block.
+///
+/// `leaves` must hold every entry written by the block, sorted by script hash.
+/// The returned `block_commit` is recomputed from the inputs, so callers serving
+/// proofs from stored data should compare it with the persisted commit.
+pub fn build_balance_delta_proof(
+    block_height: u32,
+    block_hash: &BlockHash,
+    leaves: &[BalanceDeltaLeaf],
+    leaf_index: usize,
+    prev_block_commit: &[u8; 32],
+) -> Option<BalanceDeltaProof> {
+    let leaf_hashes: Vec<[u8; 32]> = leaves.iter().map(hash_balance_delta_leaf).collect();
+    let siblings = build_balance_delta_merkle_path(&leaf_hashes, leaf_index)?;
+    let merkle_root = compute_balance_delta_merkle_root(&leaf_hashes);
+    let leaf_count = leaves.len() as u32;
+    let balance_delta_root =
+        compute_balance_delta_root_v2(block_height, block_hash, leaf_count, &merkle_root);
+    let block_commit = compute_balance_history_block_commit(
+        block_height,
+        block_hash,
+        &balance_delta_root,
+        prev_block_commit,
+    );
+
+    Some(BalanceDeltaProof {
+        block_height,
+        btc_block_hash: format!("{:x}", block_hash),
+        leaf: leaves[leaf_index],
+        leaf_index: leaf_index as u32,
+        leaf_count,
+        siblings: siblings.iter().map(|hash| encode_hex(hash)).collect(),
+        balance_delta_root: encode_hex(&balance_delta_root),
+        prev_block_commit: encode_hex(prev_block_commit),
+        block_commit: encode_hex(&block_commit),
+        commit_protocol_version: BALANCE_HISTORY_COMMIT_PROTOCOL_VERSION_V2.to_string(),
+    })
+}
+
+pub(crate) fn decode_proof_hash(name: &str, value: &str) -> Result<[u8; 32], String> {
+    <[u8; 32]>::from_hex(value).map_err(|e| {
+        let msg = format!("Invalid {} in balance proof: {}", name, e);
+        error!("{}", msg);
+        msg
+    })
+}
+
+/// Checks that `proof.leaf` is committed by `proof.block_commit`.
+///
+/// This only proves internal consistency. Callers must still compare
+/// `block_commit` with a commit they trust for `block_height`, for example the
+/// `latest_block_commit` of a consensus-checked state ref at that height.
+pub fn verify_balance_delta_proof(proof: &BalanceDeltaProof) -> Result<(), String> {
+    if proof.commit_protocol_version != BALANCE_HISTORY_COMMIT_PROTOCOL_VERSION_V2 {
+        let msg = format!(
+            "Unsupported balance proof commit protocol version {}",
+            proof.commit_protocol_version
+        );
+        error!("{}", msg);
+        return Err(msg);
+    }
+
+    let block_hash = BlockHash::from_str(&proof.btc_block_hash).map_err(|e| {
+        let msg = format!("Invalid btc_block_hash in balance proof: {}", e);
+        error!("{}", msg);
+        msg
+    })?;
+    let siblings = proof
+        .siblings
+        .iter()
+        .map(|value| decode_proof_hash("sibling", value))
+        .collect::<Result<Vec<_>, _>>()?;
+    let balance_delta_root = decode_proof_hash("balance_delta_root", &proof.balance_delta_root)?;
+    let prev_block_commit = decode_proof_hash("prev_block_commit", &proof.prev_block_commit)?;
+    let block_commit = decode_proof_hash("block_commit", &proof.block_commit)?;
+
+    let leaf_hash = hash_balance_delta_leaf(&proof.leaf);
+    let merkle_root = compute_balance_delta_merkle_root_from_path(
+        &leaf_hash,
+        proof.leaf_index,
+        proof.leaf_count,
+        &siblings,
+    )
+    .ok_or_else(|| {
+        let msg = format!(
+            "Balance proof path does not match leaf_index {} and leaf_count {}",
+            proof.leaf_index, proof.leaf_count
+        );
+        error!("{}", msg);
+        msg
+    })?;
+
+    let expected_delta_root = compute_balance_delta_root_v2(
+        proof.block_height,
+        &block_hash,
+        proof.leaf_count,
+        &merkle_root,
+    );
+    if expected_delta_root != balance_delta_root {
+        let msg = format!(
+            "Balance proof delta root mismatch at height {}: expected {}, got {}",
+            proof.block_height,
+            encode_hex(&expected_delta_root),
+            proof.balance_delta_root
+        );
+        error!("{}", msg);
+        return Err(msg);
+    }
+
+    let expected_commit = compute_balance_history_block_commit(
+        proof.block_height,
+        &block_hash,
+        &balance_delta_root,
+        &prev_block_commit,
+    );
+    if expected_commit != block_commit {
+        let msg = format!(
+            "Balance proof block commit mismatch at height {}: expected {}, got {}",
+            proof.block_height,
+            encode_hex(&expected_commit),
+            proof.block_commit
+        );
+        error!("{}", msg);
+        return Err(msg);
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::activation::{
+        ActivationAnchor, ActivationChain, ActivationRecord, ActivationStatus,
+    };
+    use bitcoincore_rpc::bitcoin::hashes::Hash;
+
+    fn make_leaf(seed: u8) -> BalanceDeltaLeaf {
+        BalanceDeltaLeaf {
+            script_hash: USDBScriptHash::from_byte_array([seed; 32]),
+            delta: seed as i64 * 10,
+            balance: seed as u64 * 100,
+        }
+    }
+
+    fn build_proof(leaf_count: u8, leaf_index: usize) -> BalanceDeltaProof {
+        let leaves: Vec<_> = (1..=leaf_count).map(make_leaf).collect();
+        let block_hash = BlockHash::from_byte_array([7u8; 32]);
+        build_balance_delta_proof(9, &block_hash, &leaves, leaf_index, &[3u8; 32]).unwrap()
+    }
+
+    #[test]
+    fn test_balance_delta_proof_verifies_every_leaf_for_odd_and_even_trees() {
+        for leaf_count in 1..=9u8 {
+            for leaf_index in 0..leaf_count as usize {
+                let proof = build_proof(leaf_count, leaf_index);
+                assert!(
+                    verify_balance_delta_proof(&proof).is_ok(),
+                    "leaf_count={}, leaf_index={}",
+                    leaf_count,
+                    leaf_index
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_balance_delta_proof_rejects_tampered_leaf_and_shape() {
+        let proof = build_proof(5, 4);
+
+        let mut tampered = proof.clone();
+        tampered.leaf.balance += 1;
+        assert!(verify_balance_delta_proof(&tampered).is_err());
+
+        let mut tampered = proof.clone();
+        tampered.leaf_count = 6;
+        assert!(verify_balance_delta_proof(&tampered).is_err());
+
+        let mut tampered = proof.clone();
+        tampered.siblings.push(encode_hex(&[0u8; 32]));
+        assert!(verify_balance_delta_proof(&tampered).is_err());
+
+        let mut tampered = proof;
+        tampered.prev_block_commit = encode_hex(&[4u8; 32]);
+        assert!(verify_balance_delta_proof(&tampered).is_err());
+    }
+
+    #[test]
+    fn test_balance_delta_merkle_root_does_not_duplicate_odd_leaf() {
+        let leaves: Vec<_> = (1..=3u8)
+            .map(|seed| hash_balance_delta_leaf(&make_leaf(seed)))
+            .collect();
+        let mut duplicated = leaves.clone();
+        duplicated.push(leaves[2]);
+
+        assert_ne!(
+            compute_balance_delta_merkle_root(&leaves),
+            compute_balance_delta_merkle_root(&duplicated)
+        );
+        assert_eq!(compute_balance_delta_merkle_root(&[]), EMPTY_MERKLE_ROOT);
+    }
+
+    #[test]
+    fn test_balance_delta_root_rule_activation() {
+        assert_eq!(
+            BalanceDeltaRootRule::at_height(Network::Regtest, 0),
+            BalanceDeltaRootRule::MerkleV2
+        );
+        assert_eq!(
+            balance_delta_merkle_activation_height(Network::Regtest),
+            Some(0)
+        );
+        assert_eq!(
+            BalanceDeltaRootRule::at_height(Network::Bitcoin, u32::MAX),
+            BalanceDeltaRootRule::FlatV1
+        );
+        assert_eq!(
+            balance_delta_merkle_activation_height(Network::Bitcoin),
+            None
+        );
+
+        // A later activation keeps earlier heights on the flat rule.
+        let record = |value: &str, height: u64, supersedes: Option<&str>| ActivationRecord {
+            uip: "UIP-0008".to_string(),
+            version_family: BALANCE_HISTORY_COMMIT_PROTOCOL_VERSION_FAMILY.to_string(),
+            version_value: value.to_string(),
+            chain: ActivationChain::BTC,
+            network_type: "mainnet".to_string(),
+            network_id: "btc-mainnet".to_string(),
+            activation_anchor: ActivationAnchor::BtcHeight,
+            activation_value: height,
+            status: ActivationStatus::Active,
+            supersedes: supersedes.map(|value| value.to_string()),
+            notes: None,
+        };
+        let registry = ActivationRegistry::new(vec![
+            record(BALANCE_HISTORY_COMMIT_PROTOCOL_VERSION_V1, 0, None),
+            record(
+                BALANCE_HISTORY_COMMIT_PROTOCOL_VERSION_V2,
+                900_000,
+                Some(BALANCE_HISTORY_COMMIT_PROTOCOL_VERSION_V1),
+            ),
+        ])
+        .unwrap();
+        assert_eq!(
+            BalanceDeltaRootRule::resolve(&registry, Network::Bitcoin, 899_999),
+            BalanceDeltaRootRule::FlatV1
+        );
+        assert_eq!(
+            BalanceDeltaRootRule::resolve(&registry, Network::Bitcoin, 900_000),
+            BalanceDeltaRootRule::MerkleV2
+        );
+        assert_eq!(
+            BalanceDeltaRootRule::resolve(&registry, Network::Regtest, 0),
+            BalanceDeltaRootRule::FlatV1
+        );
+    }
+}
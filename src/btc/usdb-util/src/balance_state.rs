@@ -0,0 +1,337 @@
+use crate::balance_proof::decode_proof_hash;
+use crate::hash::USDBScriptHash;
+use crate::types::encode_hex;
+use bitcoincore_rpc::bitcoin::Network;
+use bitcoincore_rpc::bitcoin::hashes::Hash;
+use bitcoincore_rpc::bitcoin::hex::FromHex;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+/// Number of levels below the root of the balance state tree, one per script hash bit.
+pub const BALANCE_STATE_TREE_DEPTH: usize = USDBScriptHash::LEN * 8;
+
+/// Hash of an empty subtree at any level, including the leaf of a zero balance.
+pub const BALANCE_STATE_EMPTY_HASH: [u8; 32] = [0u8; 32];
+
+const STATE_LEAF_TAG: &[u8] = b"balance-history:state-leaf:v1";
+const STATE_NODE_TAG: &[u8] = b"balance-history:state-node:v1";
+
+/// First BTC height whose balance state root is published on `network`.
+///
+/// The root is part of the consensus snapshot identity, so public networks stay
+/// without it until an activation height is agreed. Nodes maintain the tree from
+/// genesis (or from an installed snapshot) whenever an activation height exists.
+pub fn balance_state_root_activation_height(network: Network) -> Option<u32> {
+    match network {
+        Network::Regtest => Some(0),
+        _ => None,
+    }
+}
+
+/// Whether the state at `block_height` on `network` carries a balance state root.
+pub fn is_balance_state_root_active(network: Network, block_height: u32) -> bool {
+    matches!(
+        balance_state_root_activation_height(network),
+        Some(activation_height) if block_height >= activation_height
+    )
+}
+
+/// Leaf hash of one script balance; zero balances are absent from the tree.
+pub fn hash_balance_state_leaf(script_hash: &USDBScriptHash, balance: u64) -> [u8; 32] {
+    if balance == 0 {
+        return BALANCE_STATE_EMPTY_HASH;
+    }
+
+    let mut hasher = Sha256::new();
+    hasher.update(STATE_LEAF_TAG);
+    hasher.update(script_hash.as_ref() as &[u8]);
+    hasher.update(balance.to_be_bytes());
+    hasher.finalize().into()
+}
+
+/// Hash of one inner node. A node with two empty children stays empty, so empty
+/// subtrees of every height share `BALANCE_STATE_EMPTY_HASH`.
+pub fn hash_balance_state_node(left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+    if *left == BALANCE_STATE_EMPTY_HASH && *right == BALANCE_STATE_EMPTY_HASH {
+        return BALANCE_STATE_EMPTY_HASH;
+    }
+
+    let mut hasher = Sha256::new();
+    hasher.update(STATE_NODE_TAG);
+    hasher.update(left);
+    hasher.update(right);
+    hasher.finalize().into()
+}
+
+/// Bit of `script_hash` that selects the child below `depth`, most significant bit first.
+pub fn balance_state_key_bit(script_hash: &USDBScriptHash, depth: usize) -> bool {
+    let bytes: &[u8] = script_hash.as_ref();
+    (bytes[depth / 8] >> (7 - depth % 8)) & 1 == 1
+}
+
+/// Root of the subtree at `depth` holding `leaves`.
+///
+/// `leaves` must be sorted by script hash, share their first `depth` bits and
+/// carry non-zero balances.
+pub fn compute_balance_state_subtree_root(
+    depth: usize,
+    leaves: &[(USDBScriptHash, u64)],
+) -> [u8; 32] {
+    if leaves.is_empty() {
+        return BALANCE_STATE_EMPTY_HASH;
+    }
+    if depth == BALANCE_STATE_TREE_DEPTH {
+        let (script_hash, balance) = &leaves[0];
+        return hash_balance_state_leaf(script_hash, *balance);
+    }
+
+    let split =
+        leaves.partition_point(|(script_hash, _)| !balance_state_key_bit(script_hash, depth));
+    hash_balance_state_node(
+        &compute_balance_state_subtree_root(depth + 1, &leaves[..split]),
+        &compute_balance_state_subtree_root(depth + 1, &leaves[split..]),
+    )
+}
+
+/// Siblings on the path of `script_hash` inside the subtree at `depth`, from the
+/// leaf level upwards. The result has `BALANCE_STATE_TREE_DEPTH - depth` entries.
+///
+/// `leaves` follows the same rules as `compute_balance_state_subtree_root`; the
+/// script itself may be absent, which yields a non-membership path.
+pub fn build_balance_state_subtree_siblings(
+    depth: usize,
+    leaves: &[(USDBScriptHash, u64)],
+    script_hash: &USDBScriptHash,
+) -> Vec<[u8; 32]> {
+    let mut siblings = Vec::with_capacity(BALANCE_STATE_TREE_DEPTH - depth);
+    let mut current = leaves;
+    for level_depth in depth..BALANCE_STATE_TREE_DEPTH {
+        let split = current.partition_point(|(key, _)| !balance_state_key_bit(key, level_depth));
+        let (left, right) = current.split_at(split);
+        if balance_state_key_bit(script_hash, level_depth) {
+            siblings.push(compute_balance_state_subtree_root(level_depth + 1, left));
+            current = right;
+        } else {
+            siblings.push(compute_balance_state_subtree_root(level_depth + 1, right));
+            current = left;
+        }
+    }
+
+    siblings.reverse();
+    siblings
+}
+
+/// Recomputes the tree root from a leaf and its full sibling path.
+pub fn compute_balance_state_root_from_siblings(
+    script_hash: &USDBScriptHash,
+    balance: u64,
+    siblings: &[[u8; 32]],
+) -> Option<[u8; 32]> {
+    if siblings.len() != BALANCE_STATE_TREE_DEPTH {
+        return None;
+    }
+
+    let mut hash = hash_balance_state_leaf(script_hash, balance);
+    for (level, sibling) in siblings.iter().enumerate() {
+        let depth = BALANCE_STATE_TREE_DEPTH - 1 - level;
+        hash = if balance_state_key_bit(script_hash, depth) {
+            hash_balance_state_node(sibling, &hash)
+        } else {
+            hash_balance_state_node(&hash, sibling)
+        };
+    }
+
+    Some(hash)
+}
+
+/// Membership proof of a script balance, or non-membership proof when `balance` is 0.
+///
+/// Empty siblings are elided: bit `i` of `sibling_bitmap` (most significant bit of
+/// the first byte is level 0, the leaf level) is set when the sibling at level `i`
+/// is non-empty, and `siblings` lists only those hashes from the leaf level upwards.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BalanceStateProof {
+    /// BTC height whose full balance state is proven.
+    pub block_height: u32,
+    /// Script hash in balance-history's canonical internal format.
+    pub script_hash: USDBScriptHash,
+    /// Balance of the script at `block_height`, in satoshi.
+    pub balance: u64,
+    /// 256-bit map of non-empty siblings, lowercase hex.
+    pub sibling_bitmap: String,
+    /// Non-empty sibling hashes from the leaf level upwards, lowercase hex.
+    pub siblings: Vec<String>,
+    /// Balance state root at `block_height`, lowercase hex.
+    pub balance_state_root: String,
+}
+
+/// Builds a compact proof from the full sibling path of `script_hash`.
+pub fn build_balance_state_proof(
+    block_height: u32,
+    script_hash: &USDBScriptHash,
+    balance: u64,
+    siblings: &[[u8; 32]],
+    balance_state_root: &[u8; 32],
+) -> BalanceStateProof {
+    let mut bitmap = [0u8; BALANCE_STATE_TREE_DEPTH / 8];
+    let mut non_empty = Vec::new();
+    for (level, sibling) in siblings.iter().enumerate() {
+        if *sibling != BALANCE_STATE_EMPTY_HASH {
+            bitmap[level / 8] |= 1 << (7 - level % 8);
+            non_empty.push(encode_hex(sibling));
+        }
+    }
+
+    BalanceStateProof {
+        block_height,
+        script_hash: *script_hash,
+        balance,
+        sibling_bitmap: encode_hex(&bitmap),
+        siblings: non_empty,
+        balance_state_root: encode_hex(balance_state_root),
+    }
+}
+
+/// Checks that `proof.balance` is the balance of `proof.script_hash` under
+/// `proof.balance_state_root`.
+///
+/// As with delta proofs, callers must still compare `balance_state_root` with
+/// the root of a consensus-checked state ref at `block_height`.
+pub fn verify_balance_state_proof(proof: &BalanceStateProof) -> Result<(), String> {
+    let bitmap =
+        <[u8; BALANCE_STATE_TREE_DEPTH / 8]>::from_hex(&proof.sibling_bitmap).map_err(|e| {
+            let msg = format!("Invalid sibling_bitmap in balance state proof: {}", e);
+            error!("{}", msg);
+            msg
+        })?;
+    let balance_state_root = decode_proof_hash("balance_state_root", &proof.balance_state_root)?;
+
+    let mut encoded = proof.siblings.iter();
+    let mut siblings = Vec::with_capacity(BALANCE_STATE_TREE_DEPTH);
+    for level in 0..BALANCE_STATE_TREE_DEPTH {
+        if bitmap[level / 8] & (1 << (7 - level % 8)) == 0 {
+            siblings.push(BALANCE_STATE_EMPTY_HASH);
+            continue;
+        }
+        let value = encoded.next().ok_or_else(|| {
+            let msg = format!(
+                "Balance state proof lists {} siblings, fewer than its bitmap requires",
+                proof.siblings.len()
+            );
+            error!("{}", msg);
+            msg
+        })?;
+        siblings.push(decode_proof_hash("sibling", value)?);
+    }
+    if encoded.next().is_some() {
+        let msg = format!(
+            "Balance state proof lists {} siblings, more than its bitmap requires",
+            proof.siblings.len()
+        );
+        error!("{}", msg);
+        return Err(msg);
+    }
+
+    let expected_root =
+        compute_balance_state_root_from_siblings(&proof.script_hash, proof.balance, &siblings)
+            .unwrap();
+    if expected_root != balance_state_root {
+        let msg = format!(
+            "Balance state proof root mismatch for {} at height {}: expected {}, got {}",
+            proof.script_hash,
+            proof.block_height,
+            proof.balance_state_root,
+            encode_hex(&expected_root)
+        );
+        error!("{}", msg);
+        return Err(msg);
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn script_hash(first: u8, last: u8) -> USDBScriptHash {
+        let mut bytes = [0u8; 32];
+        bytes[0] = first;
+        bytes[31] = last;
+        USDBScriptHash::from_byte_array(bytes)
+    }
+
+    fn sample_leaves() -> Vec<(USDBScriptHash, u64)> {
+        let mut leaves = vec![
+            (script_hash(0x00, 1), 10),
+            (script_hash(0x00, 2), 20),
+            (script_hash(0x80, 0), 30),
+            (script_hash(0xff, 7), 40),
+        ];
+        leaves.sort();
+        leaves
+    }
+
+    #[test]
+    fn test_balance_state_proofs_verify_membership_and_absence() {
+        let leaves = sample_leaves();
+        let root = compute_balance_state_subtree_root(0, &leaves);
+        assert_ne!(root, BALANCE_STATE_EMPTY_HASH);
+
+        for (key, balance) in &leaves {
+            let siblings = build_balance_state_subtree_siblings(0, &leaves, key);
+            let proof = build_balance_state_proof(9, key, *balance, &siblings, &root);
+            verify_balance_state_proof(&proof).unwrap();
+        }
+
+        let absent = script_hash(0x00, 3);
+        let siblings = build_balance_state_subtree_siblings(0, &leaves, &absent);
+        let proof = build_balance_state_proof(9, &absent, 0, &siblings, &root);
+        verify_balance_state_proof(&proof).unwrap();
+
+        let mut forged = proof.clone();
+        forged.balance = 1;
+        assert!(verify_balance_state_proof(&forged).is_err());
+
+        let siblings = build_balance_state_subtree_siblings(0, &leaves, &leaves[0].0);
+        let mut forged = build_balance_state_proof(9, &leaves[0].0, 0, &siblings, &root);
+        assert!(verify_balance_state_proof(&forged).is_err());
+        forged.balance = leaves[0].1;
+        forged.siblings.pop();
+        assert!(verify_balance_state_proof(&forged).is_err());
+    }
+
+    #[test]
+    fn test_balance_state_root_ignores_zero_balances_and_splits_by_prefix() {
+        let leaves = sample_leaves();
+        assert_eq!(
+            compute_balance_state_subtree_root(0, &[]),
+            BALANCE_STATE_EMPTY_HASH
+        );
+
+        // The root over a prefix split matches the root of the whole set.
+        let split = leaves.partition_point(|(key, _)| !balance_state_key_bit(key, 0));
+        let left = compute_balance_state_subtree_root(1, &leaves[..split]);
+        let right = compute_balance_state_subtree_root(1, &leaves[split..]);
+        assert_eq!(
+            hash_balance_state_node(&left, &right),
+            compute_balance_state_subtree_root(0, &leaves)
+        );
+
+        // Removing a leaf equals setting its balance to zero.
+        let mut without_last = leaves.clone();
+        let (last_key, _) = without_last.pop().unwrap();
+        let siblings = build_balance_state_subtree_siblings(0, &without_last, &last_key);
+        assert_eq!(
+            compute_balance_state_root_from_siblings(&last_key, 0, &siblings).unwrap(),
+            compute_balance_state_subtree_root(0, &without_last)
+        );
+    }
+
+    #[test]
+    fn test_balance_state_root_activation() {
+        assert!(is_balance_state_root_active(Network::Regtest, 0));
+        assert!(!is_balance_state_root_active(Network::Bitcoin, 900_000));
+        assert_eq!(balance_state_root_activation_height(Network::Testnet), None);
+    }
+}
@@ -1,5 +1,7 @@
 mod electrs;
 mod rpc;
+mod zmq;
 
 pub use electrs::*;
 pub use rpc::*;
+pub use zmq::*;
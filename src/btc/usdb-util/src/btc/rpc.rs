@@ -1,6 +1,74 @@
-use bitcoincore_rpc::bitcoin::{Amount, Block, BlockHash, OutPoint, ScriptBuf, Transaction, Txid};
-use bitcoincore_rpc::{Auth, Client, RpcApi};
+use bitcoincore_rpc::bitcoin::block::Header;
+use bitcoincore_rpc::bitcoin::{
+    Amount, Block, BlockHash, OutPoint, ScriptBuf, Transaction, TxOut, Txid,
+};
+use bitcoincore_rpc::json::{
+    GetMempoolEntryResult, HashOrHeight, ScanTxOutRequest, TxOutSetHashType,
+};
+use bitcoincore_rpc::{Auth, Client, RpcApi, jsonrpc};
+use serde::Deserialize;
+use std::collections::HashMap;
 use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+// `scantxoutset` walks the whole UTXO set, which takes minutes on mainnet.
+const SCAN_TX_OUT_SET_TIMEOUT: Duration = Duration::from_secs(30 * 60);
+
+/// Confirmed balances of a list of scripts, taken from bitcoind's UTXO set at `block_height`.
+#[derive(Debug, Clone)]
+pub struct ScriptBalanceScan {
+    pub block_height: u32,
+    pub block_hash: BlockHash,
+    // Balances in satoshis, in the order of the requested scripts.
+    pub balances: Vec<u64>,
+}
+
+/// Outputs created and spent by one block, as returned by `getblock <hash> 3`.
+#[derive(Debug, Clone, Default)]
+pub struct BlockTxOutFlow {
+    pub created: Vec<TxOut>,
+    pub spent: Vec<TxOut>,
+}
+
+#[derive(Deserialize)]
+struct VerboseBlock {
+    tx: Vec<VerboseTx>,
+}
+
+#[derive(Deserialize)]
+struct VerboseTx {
+    vin: Vec<VerboseTxIn>,
+    vout: Vec<VerboseTxOut>,
+}
+
+#[derive(Deserialize)]
+struct VerboseTxIn {
+    // Missing for coinbase inputs.
+    #[serde(default)]
+    prevout: Option<VerboseTxOut>,
+}
+
+#[derive(Deserialize)]
+struct VerboseTxOut {
+    #[serde(with = "bitcoincore_rpc::bitcoin::amount::serde::as_btc")]
+    value: Amount,
+    #[serde(rename = "scriptPubKey")]
+    script_pub_key: VerboseScriptPubKey,
+}
+
+#[derive(Deserialize)]
+struct VerboseScriptPubKey {
+    hex: ScriptBuf,
+}
+
+impl From<VerboseTxOut> for TxOut {
+    fn from(value: VerboseTxOut) -> Self {
+        TxOut {
+            value: value.value,
+            script_pubkey: value.script_pub_key.hex,
+        }
+    }
+}
 
 struct ClientConfig {
     rpc_url: String,
@@ -123,6 +191,18 @@ impl BTCRpcClient {
         })
     }
 
+    pub fn get_block_header(&self, block_hash: &BlockHash) -> Result<Header, String> {
+        self.client()?
+            .get_block_header(block_hash)
+            .map_err(|error| {
+                self.on_error(&error);
+
+                let msg = format!("get_block_header failed: {}", error);
+                error!("{}", msg);
+                msg
+            })
+    }
+
     pub fn get_block(&self, block_height: u32) -> Result<Block, String> {
         // First get the block hash for the given height
         let hash = self
@@ -231,6 +311,170 @@ impl BTCRpcClient {
         })
     }
 
+    // Get bitcoind's MuHash of the UTXO set after the block at the given height, in the
+    // same hex form as `gettxoutsetinfo muhash`. Historical heights need -coinstatsindex.
+    pub fn get_utxo_set_muhash(&self, block_height: u32) -> Result<String, String> {
+        let ret = self
+            .client()?
+            .get_tx_out_set_info(
+                Some(TxOutSetHashType::Muhash),
+                Some(HashOrHeight::Height(block_height as u64)),
+                None,
+            )
+            .map_err(|e| {
+                self.on_error(&e);
+
+                let msg = format!(
+                    "Failed to get UTXO set muhash at height {}: {}",
+                    block_height, e
+                );
+                error!("{}", msg);
+                msg
+            })?;
+
+        if ret.height != block_height as u64 {
+            let msg = format!(
+                "UTXO set info height mismatch: expected {}, got {}",
+                block_height, ret.height
+            );
+            error!("{}", msg);
+            return Err(msg);
+        }
+
+        let muhash = ret.muhash.ok_or_else(|| {
+            let msg = format!("Missing muhash in UTXO set info at height {}", block_height);
+            error!("{}", msg);
+            msg
+        })?;
+
+        Ok(muhash.to_string())
+    }
+
+    // A client with a long timeout for RPCs that scan the whole UTXO set.
+    fn long_running_client(&self) -> Result<Client, String> {
+        let (user, pass) = self.config.auth.clone().get_user_pass().map_err(|e| {
+            let msg = format!("Failed to load BTC RPC credentials: {}", e);
+            error!("{}", msg);
+            msg
+        })?;
+
+        let mut builder = jsonrpc::simple_http::Builder::new()
+            .timeout(SCAN_TX_OUT_SET_TIMEOUT)
+            .url(&self.config.rpc_url)
+            .map_err(|e| {
+                let msg = format!("Invalid BTC RPC url {}: {}", self.config.rpc_url, e);
+                error!("{}", msg);
+                msg
+            })?;
+        if let Some(user) = user {
+            builder = builder.auth(user, pass);
+        }
+
+        Ok(Client::from_jsonrpc(jsonrpc::Client::with_transport(
+            builder.build(),
+        )))
+    }
+
+    // Sum the confirmed UTXOs of each script with `scantxoutset` at bitcoind's current tip.
+    // All scripts are scanned in one pass, so callers should batch them.
+    pub fn scan_script_balances(&self, scripts: &[ScriptBuf]) -> Result<ScriptBalanceScan, String> {
+        let requests: Vec<ScanTxOutRequest> = scripts
+            .iter()
+            .map(|script| ScanTxOutRequest::Single(format!("raw({})", script.to_hex_string())))
+            .collect();
+
+        let ret = self
+            .long_running_client()?
+            .scan_tx_out_set_blocking(&requests)
+            .map_err(|e| {
+                self.on_error(&e);
+
+                let msg = format!("scantxoutset failed for {} scripts: {}", scripts.len(), e);
+                error!("{}", msg);
+                msg
+            })?;
+
+        if ret.success == Some(false) {
+            let msg = "scantxoutset did not complete".to_string();
+            error!("{}", msg);
+            return Err(msg);
+        }
+        let (Some(block_height), Some(block_hash)) = (ret.height, ret.best_block_hash) else {
+            let msg = "scantxoutset result is missing height or bestblock, bitcoind is too old"
+                .to_string();
+            error!("{}", msg);
+            return Err(msg);
+        };
+
+        let mut totals: HashMap<&ScriptBuf, u64> = HashMap::with_capacity(scripts.len());
+        for utxo in &ret.unspents {
+            *totals.entry(&utxo.script_pub_key).or_default() += utxo.amount.to_sat();
+        }
+
+        Ok(ScriptBalanceScan {
+            block_height: block_height as u32,
+            block_hash,
+            balances: scripts
+                .iter()
+                .map(|script| totals.get(script).copied().unwrap_or(0))
+                .collect(),
+        })
+    }
+
+    // Outputs created and spent by the block, using `getblock` verbosity 3 for the
+    // prevouts. Needs bitcoind 23+ and the block's undo data (not pruned).
+    pub fn get_block_tx_out_flow(&self, block_hash: &BlockHash) -> Result<BlockTxOutFlow, String> {
+        let block: VerboseBlock = self
+            .client()?
+            .call(
+                "getblock",
+                &[
+                    serde_json::json!(block_hash.to_string()),
+                    serde_json::json!(3),
+                ],
+            )
+            .map_err(|e| {
+                self.on_error(&e);
+
+                let msg = format!("getblock {} with prevouts failed: {}", block_hash, e);
+                error!("{}", msg);
+                msg
+            })?;
+
+        let mut flow = BlockTxOutFlow::default();
+        for tx in block.tx {
+            flow.spent.extend(
+                tx.vin
+                    .into_iter()
+                    .filter_map(|vin| vin.prevout)
+                    .map(TxOut::from),
+            );
+            flow.created.extend(tx.vout.into_iter().map(TxOut::from));
+        }
+
+        Ok(flow)
+    }
+
+    pub fn get_raw_mempool(&self) -> Result<Vec<Txid>, String> {
+        self.client()?.get_raw_mempool().map_err(|e| {
+            self.on_error(&e);
+
+            let msg = format!("getrawmempool failed: {}", e);
+            error!("{}", msg);
+            msg
+        })
+    }
+
+    pub fn get_mempool_entry(&self, txid: &Txid) -> Result<GetMempoolEntryResult, String> {
+        self.client()?.get_mempool_entry(txid).map_err(|e| {
+            self.on_error(&e);
+
+            let msg = format!("getmempoolentry failed for {}: {}", txid, e);
+            warn!("{}", msg);
+            msg
+        })
+    }
+
     pub fn get_transactions(&self, txids: &[Txid]) -> Result<Vec<Transaction>, String> {
         use rayon::prelude::*;
 
@@ -0,0 +1,335 @@
+use bitcoincore_rpc::bitcoin::consensus::deserialize;
+use bitcoincore_rpc::bitcoin::hashes::Hash;
+use bitcoincore_rpc::bitcoin::{Block, BlockHash};
+use std::collections::VecDeque;
+use std::sync::{Arc, Condvar, Mutex};
+use std::time::Duration;
+use tokio::sync::watch;
+use tokio::task::JoinHandle;
+use zeromq::{Socket, SocketRecv, SubSocket};
+
+// Push-based block notifications from bitcoind's ZMQ interface.
+//
+// bitcoind publishes multipart messages `[topic, body, sequence]` where the body is
+// the serialized block for `rawblock` and the block hash in display (reversed) byte
+// order for `hashblock`. Notifications are only a latency optimization: consumers
+// keep their polling loops and treat every notification as "check the chain now".
+// Missed messages (bitcoind restarts, socket drops) are therefore harmless.
+
+pub const ZMQ_TOPIC_RAWBLOCK: &str = "rawblock";
+pub const ZMQ_TOPIC_HASHBLOCK: &str = "hashblock";
+
+/// Number of recent `rawblock` bodies kept for block hint lookups.
+pub const ZMQ_RECENT_BLOCK_CAPACITY: usize = 8;
+
+const ZMQ_RECONNECT_INTERVAL: Duration = Duration::from_secs(3);
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ZmqBlockEvent {
+    RawBlock(Block),
+    HashBlock(BlockHash),
+}
+
+/// Parses one bitcoind ZMQ multipart message. The trailing sequence frame is optional.
+pub fn parse_zmq_block_message(frames: &[&[u8]]) -> Result<ZmqBlockEvent, String> {
+    if frames.len() < 2 {
+        let msg = format!(
+            "Invalid ZMQ block message: expected at least 2 frames, got {}",
+            frames.len()
+        );
+        error!("{}", msg);
+        return Err(msg);
+    }
+
+    let topic = frames[0];
+    let body = frames[1];
+    if topic == ZMQ_TOPIC_RAWBLOCK.as_bytes() {
+        let block: Block = deserialize(body).map_err(|e| {
+            let msg = format!("Failed to decode ZMQ rawblock body: {}", e);
+            error!("{}", msg);
+            msg
+        })?;
+        Ok(ZmqBlockEvent::RawBlock(block))
+    } else if topic == ZMQ_TOPIC_HASHBLOCK.as_bytes() {
+        let mut bytes: [u8; 32] = body.try_into().map_err(|_| {
+            let msg = format!(
+                "Invalid ZMQ hashblock body length: expected 32, got {}",
+                body.len()
+            );
+            error!("{}", msg);
+            msg
+        })?;
+        bytes.reverse();
+        Ok(ZmqBlockEvent::HashBlock(BlockHash::from_byte_array(bytes)))
+    } else {
+        let msg = format!("Unexpected ZMQ topic: {}", String::from_utf8_lossy(topic));
+        warn!("{}", msg);
+        Err(msg)
+    }
+}
+
+/// Subscribes to bitcoind `rawblock`/`hashblock` notifications.
+///
+/// Every accepted message bumps a notification sequence that blocking and async
+/// waiters can sleep on, and `rawblock` bodies are cached for block hint lookups.
+pub struct ZmqBlockSubscriber {
+    url: String,
+    recent_blocks: Mutex<VecDeque<Arc<Block>>>,
+    sequence: Mutex<u64>,
+    sequence_cond: Condvar,
+    sequence_tx: watch::Sender<u64>,
+    task: Mutex<Option<JoinHandle<()>>>,
+}
+
+pub type ZmqBlockSubscriberRef = Arc<ZmqBlockSubscriber>;
+
+impl ZmqBlockSubscriber {
+    pub fn new(url: &str) -> Self {
+        let (sequence_tx, _) = watch::channel(0);
+        Self {
+            url: url.to_string(),
+            recent_blocks: Mutex::new(VecDeque::with_capacity(ZMQ_RECENT_BLOCK_CAPACITY)),
+            sequence: Mutex::new(0),
+            sequence_cond: Condvar::new(),
+            sequence_tx,
+            task: Mutex::new(None),
+        }
+    }
+
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// Spawns the receive loop on the current tokio runtime. Calling it twice is a no-op.
+    pub fn start(self: &Arc<Self>) {
+        let mut task = self.task.lock().unwrap();
+        if task.is_some() {
+            return;
+        }
+
+        let subscriber = self.clone();
+        *task = Some(tokio::spawn(async move {
+            subscriber.run_loop().await;
+        }));
+        info!("ZMQ block subscriber started: url={}", self.url);
+    }
+
+    pub fn close(&self) {
+        if let Some(task) = self.task.lock().unwrap().take() {
+            task.abort();
+            info!("ZMQ block subscriber stopped: url={}", self.url);
+        }
+    }
+
+    async fn run_loop(&self) {
+        loop {
+            if let Err(e) = self.subscribe_once().await {
+                warn!(
+                    "ZMQ block subscription interrupted, retrying in {:?}: url={}, error={}",
+                    ZMQ_RECONNECT_INTERVAL, self.url, e
+                );
+            }
+
+            tokio::time::sleep(ZMQ_RECONNECT_INTERVAL).await;
+        }
+    }
+
+    async fn subscribe_once(&self) -> Result<(), String> {
+        let mut socket = SubSocket::new();
+        socket
+            .connect(&self.url)
+            .await
+            .map_err(|e| format!("Failed to connect ZMQ socket {}: {}", self.url, e))?;
+        for topic in [ZMQ_TOPIC_RAWBLOCK, ZMQ_TOPIC_HASHBLOCK] {
+            socket
+                .subscribe(topic)
+                .await
+                .map_err(|e| format!("Failed to subscribe ZMQ topic {}: {}", topic, e))?;
+        }
+        info!(
+            "Subscribed to bitcoind ZMQ block notifications: url={}",
+            self.url
+        );
+
+        loop {
+            let message = socket
+                .recv()
+                .await
+                .map_err(|e| format!("Failed to receive ZMQ message: {}", e))?;
+            let frames = message.into_vec();
+            let frames: Vec<&[u8]> = frames.iter().map(|frame| frame.as_ref()).collect();
+
+            // A malformed message is logged by the parser and must not tear down the socket.
+            if let Ok(event) = parse_zmq_block_message(&frames) {
+                self.on_event(event);
+            }
+        }
+    }
+
+    /// Applies one parsed notification. Also lets embedders and tests inject events.
+    pub fn on_event(&self, event: ZmqBlockEvent) {
+        match event {
+            ZmqBlockEvent::RawBlock(block) => {
+                let block_hash = block.block_hash();
+                debug!("ZMQ rawblock received: hash={}", block_hash);
+
+                let mut recent_blocks = self.recent_blocks.lock().unwrap();
+                if !recent_blocks
+                    .iter()
+                    .any(|cached| cached.block_hash() == block_hash)
+                {
+                    if recent_blocks.len() >= ZMQ_RECENT_BLOCK_CAPACITY {
+                        recent_blocks.pop_front();
+                    }
+                    recent_blocks.push_back(Arc::new(block));
+                }
+            }
+            ZmqBlockEvent::HashBlock(block_hash) => {
+                debug!("ZMQ hashblock received: hash={}", block_hash);
+            }
+        }
+
+        let mut sequence = self.sequence.lock().unwrap();
+        *sequence += 1;
+        self.sequence_tx.send_replace(*sequence);
+        self.sequence_cond.notify_all();
+    }
+
+    /// Returns a cached `rawblock` body by hash, if it is still in the recent window.
+    pub fn get_block(&self, block_hash: &BlockHash) -> Option<Arc<Block>> {
+        self.recent_blocks
+            .lock()
+            .unwrap()
+            .iter()
+            .find(|block| block.block_hash() == *block_hash)
+            .cloned()
+    }
+
+    pub fn notification_sequence(&self) -> u64 {
+        *self.sequence.lock().unwrap()
+    }
+
+    /// Blocks until the sequence moves past `seen_sequence` or `timeout` elapses,
+    /// and returns the latest sequence.
+    pub fn wait_for_notification(&self, seen_sequence: u64, timeout: Duration) -> u64 {
+        let sequence = self.sequence.lock().unwrap();
+        let (sequence, _) = self
+            .sequence_cond
+            .wait_timeout_while(sequence, timeout, |sequence| *sequence == seen_sequence)
+            .unwrap();
+        *sequence
+    }
+
+    /// Async variant of `wait_for_notification`.
+    pub async fn wait_for_notification_async(&self, seen_sequence: u64, timeout: Duration) -> u64 {
+        let mut rx = self.sequence_tx.subscribe();
+        if *rx.borrow_and_update() == seen_sequence {
+            let _ = tokio::time::timeout(timeout, rx.changed()).await;
+        }
+        *rx.borrow()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bitcoincore_rpc::bitcoin::Network;
+    use bitcoincore_rpc::bitcoin::blockdata::constants::genesis_block;
+    use bitcoincore_rpc::bitcoin::consensus::serialize;
+
+    #[test]
+    fn test_parse_zmq_block_message() {
+        let block = genesis_block(Network::Regtest);
+        let body = serialize(&block);
+        let sequence = 7u32.to_le_bytes();
+
+        let event =
+            parse_zmq_block_message(&[ZMQ_TOPIC_RAWBLOCK.as_bytes(), &body, &sequence]).unwrap();
+        assert_eq!(event, ZmqBlockEvent::RawBlock(block.clone()));
+
+        // hashblock bodies use display byte order, the same as `getbestblockhash`.
+        let mut display_bytes = block.block_hash().to_byte_array();
+        display_bytes.reverse();
+        let event =
+            parse_zmq_block_message(&[ZMQ_TOPIC_HASHBLOCK.as_bytes(), &display_bytes]).unwrap();
+        assert_eq!(event, ZmqBlockEvent::HashBlock(block.block_hash()));
+
+        assert!(parse_zmq_block_message(&[ZMQ_TOPIC_HASHBLOCK.as_bytes(), &[0u8; 31]]).is_err());
+        assert!(parse_zmq_block_message(&[b"rawtx", &body]).is_err());
+        assert!(parse_zmq_block_message(&[ZMQ_TOPIC_RAWBLOCK.as_bytes()]).is_err());
+    }
+
+    #[test]
+    fn test_zmq_subscriber_caches_blocks_and_wakes_waiters() {
+        let subscriber = Arc::new(ZmqBlockSubscriber::new("tcp://127.0.0.1:28332"));
+        let block = genesis_block(Network::Regtest);
+        let block_hash = block.block_hash();
+        assert!(subscriber.get_block(&block_hash).is_none());
+
+        let seen = subscriber.notification_sequence();
+        let waiter = {
+            let subscriber = subscriber.clone();
+            std::thread::spawn(move || {
+                subscriber.wait_for_notification(seen, Duration::from_secs(5))
+            })
+        };
+        subscriber.on_event(ZmqBlockEvent::RawBlock(block.clone()));
+        assert_eq!(waiter.join().unwrap(), seen + 1);
+        assert_eq!(subscriber.get_block(&block_hash).unwrap().as_ref(), &block);
+
+        // Duplicate bodies still notify but are cached once.
+        subscriber.on_event(ZmqBlockEvent::RawBlock(block.clone()));
+        assert_eq!(subscriber.notification_sequence(), seen + 2);
+        assert_eq!(subscriber.recent_blocks.lock().unwrap().len(), 1);
+
+        // A timed-out wait returns the unchanged sequence.
+        let current = subscriber.notification_sequence();
+        assert_eq!(
+            subscriber.wait_for_notification(current, Duration::from_millis(10)),
+            current
+        );
+    }
+
+    #[test]
+    fn test_zmq_subscriber_evicts_oldest_block() {
+        let subscriber = ZmqBlockSubscriber::new("tcp://127.0.0.1:28332");
+        let mut hashes = Vec::new();
+        for nonce in 0..(ZMQ_RECENT_BLOCK_CAPACITY as u32 + 1) {
+            let mut block = genesis_block(Network::Regtest);
+            block.header.nonce = nonce;
+            hashes.push(block.block_hash());
+            subscriber.on_event(ZmqBlockEvent::RawBlock(block));
+        }
+
+        assert!(subscriber.get_block(&hashes[0]).is_none());
+        for hash in &hashes[1..] {
+            assert!(subscriber.get_block(hash).is_some());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_zmq_subscriber_async_wait() {
+        let subscriber = Arc::new(ZmqBlockSubscriber::new("tcp://127.0.0.1:28332"));
+        let seen = subscriber.notification_sequence();
+
+        let waiter = {
+            let subscriber = subscriber.clone();
+            tokio::spawn(async move {
+                subscriber
+                    .wait_for_notification_async(seen, Duration::from_secs(5))
+                    .await
+            })
+        };
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        subscriber.on_event(ZmqBlockEvent::HashBlock(BlockHash::all_zeros()));
+        assert_eq!(waiter.await.unwrap(), seen + 1);
+
+        // A notification that already happened is returned without waiting.
+        assert_eq!(
+            subscriber
+                .wait_for_notification_async(seen, Duration::from_secs(5))
+                .await,
+            seen + 1
+        );
+    }
+}
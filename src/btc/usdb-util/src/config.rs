@@ -30,6 +30,11 @@ pub struct BTCConfig {
 
     #[serde(default)]
     pub block_magic: Option<u32>,
+
+    /// bitcoind ZMQ endpoint publishing `rawblock`/`hashblock`, e.g. `tcp://127.0.0.1:28332`.
+    /// When set, new blocks wake the sync loops immediately; polling stays as the fallback.
+    #[serde(default)]
+    pub zmq_block_url: Option<String>,
 }
 
 impl BTCConfig {
@@ -107,6 +112,7 @@ impl Default for BTCConfig {
             rpc_url: None,
             auth: None,
             block_magic: None,
+            zmq_block_url: None,
         }
     }
 }
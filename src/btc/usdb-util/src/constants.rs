@@ -17,7 +17,9 @@ pub const USDB_TESTNET4_PORT_BASE: u16 = 28_400;
 
 // Shared per-service offsets from each network base.
 pub const PORT_OFFSET_BALANCE_HISTORY_RPC: u16 = 10;
+pub const PORT_OFFSET_BALANCE_HISTORY_WS: u16 = 11;
 pub const PORT_OFFSET_USDB_INDEXER_RPC: u16 = 20;
+pub const PORT_OFFSET_USDB_INDEXER_WS: u16 = 21;
 pub const PORT_OFFSET_ORD_HTTP: u16 = 30;
 pub const PORT_OFFSET_BITCOIND_RPC: u16 = 32;
 pub const PORT_OFFSET_BITCOIND_P2P: u16 = 33;
@@ -25,7 +27,9 @@ pub const PORT_OFFSET_CONTROL_PLANE_HTTP: u16 = 40;
 
 // Mainnet service ports (USDB-managed local services) plus standard bitcoind defaults.
 pub const BALANCE_HISTORY_SERVICE_HTTP_PORT: u16 = 28_010; // base 28000 + offset 10
+pub const BALANCE_HISTORY_SERVICE_WS_PORT: u16 = 28_011; // base 28000 + offset 11
 pub const USDB_INDEXER_SERVICE_HTTP_PORT: u16 = 28_020; // base 28000 + offset 20
+pub const USDB_INDEXER_SERVICE_WS_PORT: u16 = 28_021; // base 28000 + offset 21
 pub const ORD_SERVICE_HTTP_PORT: u16 = 28_030; // base 28000 + offset 30
 pub const USDB_CONTROL_PLANE_HTTP_PORT: u16 = 28_040; // base 28000 + offset 40
 pub const BITCOIND_MAINNET_RPC_PORT: u16 = 8332;
@@ -33,7 +37,9 @@ pub const BITCOIND_MAINNET_P2P_PORT: u16 = 8333;
 
 // Regtest default ports (explicit values for quick lookup).
 pub const REGTEST_BALANCE_HISTORY_SERVICE_HTTP_PORT: u16 = 28_110; // base 28100 + offset 10
+pub const REGTEST_BALANCE_HISTORY_SERVICE_WS_PORT: u16 = 28_111; // base 28100 + offset 11
 pub const REGTEST_USDB_INDEXER_SERVICE_HTTP_PORT: u16 = 28_120; // base 28100 + offset 20
+pub const REGTEST_USDB_INDEXER_SERVICE_WS_PORT: u16 = 28_121; // base 28100 + offset 21
 pub const REGTEST_ORD_SERVICE_HTTP_PORT: u16 = 28_130; // base 28100 + offset 30
 pub const REGTEST_USDB_CONTROL_PLANE_HTTP_PORT: u16 = 28_140; // base 28100 + offset 40
 pub const BITCOIND_REGTEST_RPC_PORT: u16 = 28_132; // base 28100 + offset 32
@@ -0,0 +1,383 @@
+use bitcoincore_rpc::bitcoin::bip32::{ChildNumber, Xpub};
+use bitcoincore_rpc::bitcoin::secp256k1::{Secp256k1, VerifyOnly};
+use bitcoincore_rpc::bitcoin::{NetworkKind, ScriptBuf};
+use std::fmt;
+use std::str::FromStr;
+use std::sync::LazyLock;
+
+static SECP: LazyLock<Secp256k1<VerifyOnly>> = LazyLock::new(Secp256k1::verification_only);
+
+// Descriptor checksum alphabets and generator from BIP-380.
+const DESCRIPTOR_INPUT_CHARSET: &str = "0123456789()[],'/*abcdefgh@:$%{}IJKLMNOPQRSTUVWXYZ&+-.;<=>?!^_|~ijklmnopqrstuvwxyzABCDEFGH`#\"\\ ";
+const DESCRIPTOR_CHECKSUM_CHARSET: &[u8] = b"qpzry9x8gf2tvdw0s3jn54khce6mua7l";
+const DESCRIPTOR_CHECKSUM_GENERATOR: [u64; 5] = [
+    0xf5dee51989,
+    0xa9fdca3312,
+    0x1bab10e32d,
+    0x3706b1677a,
+    0x644d626ffd,
+];
+
+fn descriptor_polymod(symbols: &[u64]) -> u64 {
+    let mut chk = 1u64;
+    for value in symbols {
+        let top = chk >> 35;
+        chk = ((chk & 0x7ffffffff) << 5) ^ value;
+        for (i, generator) in DESCRIPTOR_CHECKSUM_GENERATOR.iter().enumerate() {
+            if (top >> i) & 1 == 1 {
+                chk ^= generator;
+            }
+        }
+    }
+    chk
+}
+
+/// Computes the 8-character BIP-380 checksum of a descriptor without its `#` suffix.
+pub fn descriptor_checksum(descriptor: &str) -> Result<String, String> {
+    let mut symbols = Vec::with_capacity(descriptor.len() * 4 / 3 + 9);
+    let mut groups = Vec::with_capacity(3);
+    for ch in descriptor.chars() {
+        let value = DESCRIPTOR_INPUT_CHARSET
+            .find(ch)
+            .ok_or_else(|| format!("Invalid character {:?} in descriptor", ch))?
+            as u64;
+        symbols.push(value & 31);
+        groups.push(value >> 5);
+        if groups.len() == 3 {
+            symbols.push(groups[0] * 9 + groups[1] * 3 + groups[2]);
+            groups.clear();
+        }
+    }
+    match groups.len() {
+        1 => symbols.push(groups[0]),
+        2 => symbols.push(groups[0] * 3 + groups[1]),
+        _ => {}
+    }
+    symbols.extend([0u64; 8]);
+
+    let checksum = descriptor_polymod(&symbols) ^ 1;
+    Ok((0..8)
+        .map(|i| DESCRIPTOR_CHECKSUM_CHARSET[((checksum >> (5 * (7 - i))) & 31) as usize] as char)
+        .collect())
+}
+
+/// Output script template of a supported single-key descriptor.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DescriptorScriptType {
+    Wpkh,
+    ShWpkh,
+    Tr,
+}
+
+/// One single-key output descriptor over an extended public key.
+///
+/// Supports `wpkh(KEY)`, `sh(wpkh(KEY))` and key-path only `tr(KEY)`, where KEY is an
+/// xpub/tpub with an optional `[fingerprint/path]` origin, unhardened derivation steps
+/// and an optional trailing `*`.
+#[derive(Debug, Clone)]
+pub struct WalletDescriptor {
+    script_type: DescriptorScriptType,
+    xpub: Xpub,
+    path: Vec<u32>,
+    ranged: bool,
+    // Key derived along `path`, so ranged scripts only need one more step.
+    base: Xpub,
+}
+
+impl WalletDescriptor {
+    /// Parses one descriptor, verifying its `#checksum` suffix when present.
+    ///
+    /// A `<a;b>` multipath step, as exported by most wallets for the receive and change
+    /// chains, expands into one descriptor per branch.
+    pub fn parse(descriptor: &str) -> Result<Vec<Self>, String> {
+        let descriptor = descriptor.trim();
+        let body = match descriptor.split_once('#') {
+            Some((body, checksum)) => {
+                let expected = descriptor_checksum(body)?;
+                if checksum != expected {
+                    return Err(format!(
+                        "Invalid descriptor checksum {}, expected {}",
+                        checksum, expected
+                    ));
+                }
+                body
+            }
+            None => descriptor,
+        };
+
+        let (script_type, key) = if let Some(inner) = Self::strip_call(body, "sh") {
+            let key = Self::strip_call(inner, "wpkh").ok_or_else(|| {
+                format!(
+                    "Unsupported descriptor {}: only sh(wpkh(...)) is supported",
+                    body
+                )
+            })?;
+            (DescriptorScriptType::ShWpkh, key)
+        } else if let Some(key) = Self::strip_call(body, "wpkh") {
+            (DescriptorScriptType::Wpkh, key)
+        } else if let Some(key) = Self::strip_call(body, "tr") {
+            if key.contains(',') {
+                return Err(format!(
+                    "Unsupported descriptor {}: taproot script trees are not supported",
+                    body
+                ));
+            }
+            (DescriptorScriptType::Tr, key)
+        } else {
+            return Err(format!(
+                "Unsupported descriptor {}: expected wpkh, sh(wpkh) or tr",
+                body
+            ));
+        };
+
+        let key = match key.strip_prefix('[') {
+            Some(rest) => {
+                let (origin, key) = rest
+                    .split_once(']')
+                    .ok_or_else(|| format!("Unterminated key origin in descriptor {}", body))?;
+                let fingerprint = origin.split('/').next().unwrap_or_default();
+                if fingerprint.len() != 8 || !fingerprint.chars().all(|c| c.is_ascii_hexdigit()) {
+                    return Err(format!("Invalid key origin fingerprint {}", fingerprint));
+                }
+                key
+            }
+            None => key,
+        };
+
+        let mut parts = key.split('/');
+        let xpub_str = parts.next().unwrap_or_default();
+        let xpub = Xpub::from_str(xpub_str)
+            .map_err(|e| format!("Invalid extended public key {}: {}", xpub_str, e))?;
+
+        let steps: Vec<&str> = parts.collect();
+        let ranged = steps.last() == Some(&"*");
+        let steps = if ranged {
+            &steps[..steps.len() - 1]
+        } else {
+            &steps[..]
+        };
+
+        let mut branches: Vec<Vec<u32>> = vec![Vec::new()];
+        let mut has_multipath = false;
+        for step in steps {
+            if let Some(multipath) = step.strip_prefix('<').and_then(|s| s.strip_suffix('>')) {
+                if has_multipath {
+                    return Err(format!(
+                        "Descriptor {} has more than one multipath step",
+                        body
+                    ));
+                }
+                has_multipath = true;
+
+                let indexes = multipath
+                    .split(';')
+                    .map(Self::parse_step)
+                    .collect::<Result<Vec<u32>, String>>()?;
+                if indexes.len() < 2 {
+                    return Err(format!("Invalid multipath step {}", step));
+                }
+                branches = indexes
+                    .into_iter()
+                    .map(|index| {
+                        let mut path = branches[0].clone();
+                        path.push(index);
+                        path
+                    })
+                    .collect();
+            } else {
+                let index = Self::parse_step(step)?;
+                for path in &mut branches {
+                    path.push(index);
+                }
+            }
+        }
+
+        branches
+            .into_iter()
+            .map(|path| {
+                let base = Self::derive_xpub(&xpub, &path)?;
+                Ok(Self {
+                    script_type,
+                    xpub,
+                    path,
+                    ranged,
+                    base,
+                })
+            })
+            .collect()
+    }
+
+    fn strip_call<'a>(value: &'a str, name: &str) -> Option<&'a str> {
+        value
+            .strip_prefix(name)
+            .and_then(|rest| rest.strip_prefix('('))
+            .and_then(|rest| rest.strip_suffix(')'))
+    }
+
+    fn parse_step(step: &str) -> Result<u32, String> {
+        if step.ends_with('\'') || step.ends_with('h') || step.ends_with('H') {
+            return Err(format!(
+                "Hardened step {} cannot be derived from an extended public key",
+                step
+            ));
+        }
+
+        let index = step
+            .parse::<u32>()
+            .map_err(|e| format!("Invalid derivation step {}: {}", step, e))?;
+        if index >= 1 << 31 {
+            return Err(format!("Derivation step {} is out of range", step));
+        }
+        Ok(index)
+    }
+
+    fn derive_xpub(xpub: &Xpub, path: &[u32]) -> Result<Xpub, String> {
+        let path = path
+            .iter()
+            .map(|index| ChildNumber::from_normal_idx(*index))
+            .collect::<Result<Vec<ChildNumber>, _>>()
+            .map_err(|e| format!("Invalid derivation path: {}", e))?;
+        xpub.derive_pub(&SECP, &path)
+            .map_err(|e| format!("Failed to derive public key: {}", e))
+    }
+
+    pub fn script_type(&self) -> DescriptorScriptType {
+        self.script_type
+    }
+
+    /// Whether the descriptor ends with `*` and describes one script per index.
+    pub fn is_ranged(&self) -> bool {
+        self.ranged
+    }
+
+    pub fn network_kind(&self) -> NetworkKind {
+        self.xpub.network
+    }
+
+    /// Derives the output script at `index`; `index` is ignored for non-ranged descriptors.
+    pub fn derive_script(&self, index: u32) -> Result<ScriptBuf, String> {
+        let key = if self.ranged {
+            Self::derive_xpub(&self.base, &[index])?
+        } else {
+            self.base
+        };
+
+        let script = match self.script_type {
+            DescriptorScriptType::Wpkh => ScriptBuf::new_p2wpkh(&key.to_pub().wpubkey_hash()),
+            DescriptorScriptType::ShWpkh => {
+                let redeem_script = ScriptBuf::new_p2wpkh(&key.to_pub().wpubkey_hash());
+                ScriptBuf::new_p2sh(&redeem_script.script_hash())
+            }
+            DescriptorScriptType::Tr => ScriptBuf::new_p2tr(&SECP, key.to_x_only_pub(), None),
+        };
+        Ok(script)
+    }
+}
+
+impl fmt::Display for WalletDescriptor {
+    // Canonical form without key origin or checksum, one branch per multipath step.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut key = self.xpub.to_string();
+        for index in &self.path {
+            key.push_str(&format!("/{}", index));
+        }
+        if self.ranged {
+            key.push_str("/*");
+        }
+
+        match self.script_type {
+            DescriptorScriptType::Wpkh => write!(f, "wpkh({})", key),
+            DescriptorScriptType::ShWpkh => write!(f, "sh(wpkh({}))", key),
+            DescriptorScriptType::Tr => write!(f, "tr({})", key),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bitcoincore_rpc::bitcoin::{Address, Network};
+
+    // BIP-84 / BIP-86 account 0 keys of the "abandon ... about" test mnemonic.
+    const BIP84_ACCOUNT_XPUB: &str = "xpub6CatWdiZiodmUeTDp8LT5or8nmbKNcuyvz7WyksVFkKB4RHwCD3XyuvPEbvqAQY3rAPshWcMLoP2fMFMKHPJ4ZeZXYVUhLv1VMrjPC7PW6V";
+    const BIP86_ACCOUNT_XPUB: &str = "xpub6BgBgsespWvERF3LHQu6CnqdvfEvtMcQjYrcRzx53QJjSxarj2afYWcLteoGVky7D3UKDP9QyrLprQ3VCECoY49yfdDEHGCtMMj92pReUsQ";
+
+    fn address_of(script: &ScriptBuf) -> String {
+        Address::from_script(script, Network::Bitcoin)
+            .unwrap()
+            .to_string()
+    }
+
+    #[test]
+    fn test_descriptor_checksum_matches_bip380() {
+        assert_eq!(descriptor_checksum("raw(deadbeef)").unwrap(), "89f8spxm");
+
+        let descriptor = format!("wpkh({}/0/*)", BIP84_ACCOUNT_XPUB);
+        let checksum = descriptor_checksum(&descriptor).unwrap();
+        let parsed = WalletDescriptor::parse(&format!("{}#{}", descriptor, checksum)).unwrap();
+        assert_eq!(parsed.len(), 1);
+        assert_eq!(parsed[0].to_string(), descriptor);
+
+        let err = WalletDescriptor::parse(&format!("{}#qqqqqqqq", descriptor)).unwrap_err();
+        assert!(err.contains("checksum"));
+    }
+
+    #[test]
+    fn test_wpkh_multipath_derives_bip84_vectors() {
+        let descriptors = WalletDescriptor::parse(&format!(
+            "wpkh([73c5da0a/84h/0h/0h]{}/<0;1>/*)",
+            BIP84_ACCOUNT_XPUB
+        ))
+        .unwrap();
+        assert_eq!(descriptors.len(), 2);
+        assert!(descriptors.iter().all(|d| d.is_ranged()));
+        assert_eq!(descriptors[0].network_kind(), NetworkKind::Main);
+
+        let receive = &descriptors[0];
+        assert_eq!(
+            address_of(&receive.derive_script(0).unwrap()),
+            "bc1qcr8te4kr609gcawutmrza0j4xv80jy8z306fyu"
+        );
+        assert_eq!(
+            address_of(&receive.derive_script(1).unwrap()),
+            "bc1qnjg0jd8228aq7egyzacy8cys3knf9xvrerkf9g"
+        );
+        assert_eq!(
+            address_of(&descriptors[1].derive_script(0).unwrap()),
+            "bc1q8c6fshw2dlwun7ekn9qwf37cu2rn755upcp6el"
+        );
+    }
+
+    #[test]
+    fn test_tr_and_sh_wpkh_scripts() {
+        let tr = WalletDescriptor::parse(&format!("tr({}/0/*)", BIP86_ACCOUNT_XPUB)).unwrap();
+        assert_eq!(
+            address_of(&tr[0].derive_script(0).unwrap()),
+            "bc1p5cyxnuxmeuwuvkwfem96lqzszd02n6xdcjrs20cac6yqjjwudpxqkedrcr"
+        );
+
+        let sh = WalletDescriptor::parse(&format!("sh(wpkh({}/0/0))", BIP84_ACCOUNT_XPUB)).unwrap();
+        assert!(!sh[0].is_ranged());
+        assert_eq!(
+            sh[0].derive_script(7).unwrap(),
+            ScriptBuf::from_hex("a914a6b5888fddc8fa193dd353d10e5cd5a8eeab064e87").unwrap()
+        );
+    }
+
+    #[test]
+    fn test_rejects_unsupported_descriptors() {
+        for descriptor in [
+            format!("pkh({}/0/*)", BIP84_ACCOUNT_XPUB),
+            format!("wpkh({}/0h/*)", BIP84_ACCOUNT_XPUB),
+            format!("wpkh({}/<0;1>/<2;3>/*)", BIP84_ACCOUNT_XPUB),
+            format!("tr({},pk({}))", BIP86_ACCOUNT_XPUB, BIP84_ACCOUNT_XPUB),
+            "wpkh(not-a-key/0/*)".to_string(),
+        ] {
+            assert!(
+                WalletDescriptor::parse(&descriptor).is_err(),
+                "{}",
+                descriptor
+            );
+        }
+    }
+}
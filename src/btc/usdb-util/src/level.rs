@@ -0,0 +1,193 @@
+// UIP-0005 level and difficulty factor rules.
+//
+// Every value here is derived from `effective_energy` with integer arithmetic only, so
+// BTC-side indexers and ETHW-side validators get identical results on any platform.
+
+/// Base energy of the level curve, in UIP-0003 unit-block energy.
+pub const LEVEL_E0: u64 = 1_000_000;
+/// Numerator of the level curve ratio `q = 1.18`.
+pub const LEVEL_Q_NUM: u64 = 118;
+/// Denominator of the level curve ratio `q = 1.18`.
+pub const LEVEL_Q_DEN: u64 = 100;
+/// Highest level defined by UIP-0005 v1.
+pub const MAX_LEVEL: u32 = 50;
+/// Difficulty discount granted per level, in bps.
+pub const LEVEL_DISCOUNT_BPS: u32 = 100;
+/// Upper bound of the total difficulty discount, in bps.
+pub const MAX_DIFFICULTY_DISCOUNT_BPS: u32 = 5_000;
+/// Lower bound of `difficulty_factor_bps`.
+pub const MIN_DIFFICULTY_FACTOR_BPS: u32 = 5_000;
+/// Basis point denominator used by difficulty factors.
+pub const DIFFICULTY_BPS_DENOMINATOR: u32 = 10_000;
+
+// level_threshold(L) = ceil(LEVEL_E0 * sum(q^i, i = 0..L-1)), precomputed with exact
+// rational arithmetic. Runtime code must not recompute it with floats, log or pow.
+const LEVEL_THRESHOLDS: [u64; MAX_LEVEL as usize + 1] = [
+    0,
+    1_000_000,
+    2_180_000,
+    3_572_400,
+    5_215_432,
+    7_154_210,
+    9_441_968,
+    12_141_522,
+    15_326_996,
+    19_085_855,
+    23_521_309,
+    28_755_145,
+    34_931_071,
+    42_218_663,
+    50_818_023,
+    60_965_267,
+    72_939_014,
+    87_068_037,
+    103_740_283,
+    123_413_534,
+    146_627_971,
+    174_021_005,
+    206_344_786,
+    244_486_847,
+    289_494_480,
+    342_603_486,
+    405_272_113,
+    479_221_094,
+    566_480_891,
+    669_447_451,
+    790_947_992,
+    934_318_630,
+    1_103_495_984,
+    1_303_125_261,
+    1_538_687_807,
+    1_816_651_613,
+    2_144_648_903,
+    2_531_685_705,
+    2_988_389_132,
+    3_527_299_176,
+    4_163_213_027,
+    4_913_591_372,
+    5_799_037_819,
+    6_843_864_626,
+    8_076_760_259,
+    9_531_577_106,
+    11_248_260_984,
+    13_273_947_962,
+    15_664_258_595,
+    18_484_825_142,
+    21_813_093_667,
+];
+
+/// Minimum `effective_energy` required to reach `level`, `None` above `MAX_LEVEL`.
+pub fn level_threshold(level: u32) -> Option<u64> {
+    LEVEL_THRESHOLDS.get(level as usize).copied()
+}
+
+/// Maps `effective_energy` to the highest level whose threshold it reaches.
+pub fn calc_level(effective_energy: u64) -> u32 {
+    // Thresholds are strictly increasing and start at 0, so the partition point is >= 1.
+    let reached = LEVEL_THRESHOLDS.partition_point(|threshold| *threshold <= effective_energy);
+    (reached - 1) as u32
+}
+
+/// Difficulty factor in bps for `level`, never below `MIN_DIFFICULTY_FACTOR_BPS`.
+pub fn calc_difficulty_factor_bps(level: u32) -> u32 {
+    let discount = level
+        .saturating_mul(LEVEL_DISCOUNT_BPS)
+        .min(MAX_DIFFICULTY_DISCOUNT_BPS);
+    DIFFICULTY_BPS_DENOMINATOR - discount
+}
+
+/// ETHW-side real difficulty, `ceil(base_difficulty * factor_bps / 10_000)`.
+///
+/// `base_difficulty` comes from the ETHW mining policy and is never read by the USDB
+/// indexer. A zero base difficulty is an invalid input.
+pub fn calc_real_difficulty(
+    base_difficulty: u128,
+    difficulty_factor_bps: u32,
+) -> Result<u128, String> {
+    if base_difficulty == 0 {
+        let msg = "Invalid base_difficulty: must be positive".to_string();
+        error!("{}", msg);
+        return Err(msg);
+    }
+    if !(MIN_DIFFICULTY_FACTOR_BPS..=DIFFICULTY_BPS_DENOMINATOR).contains(&difficulty_factor_bps) {
+        let msg = format!(
+            "Invalid difficulty_factor_bps: value={}, expected {}..={}",
+            difficulty_factor_bps, MIN_DIFFICULTY_FACTOR_BPS, DIFFICULTY_BPS_DENOMINATOR
+        );
+        error!("{}", msg);
+        return Err(msg);
+    }
+
+    let denominator = DIFFICULTY_BPS_DENOMINATOR as u128;
+    let product = base_difficulty
+        .checked_mul(difficulty_factor_bps as u128)
+        .ok_or_else(|| {
+            let msg = format!(
+                "Real difficulty overflow: base_difficulty={}, difficulty_factor_bps={}",
+                base_difficulty, difficulty_factor_bps
+            );
+            error!("{}", msg);
+            msg
+        })?;
+    Ok(product.div_ceil(denominator))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_level_thresholds_follow_ratio() {
+        // threshold(L + 1) = ceil(E0 + q * S(L)); with S(L) in [threshold(L) - 1, threshold(L)]
+        // the next threshold must sit inside the matching integer bounds.
+        for level in 1..MAX_LEVEL as usize {
+            let current = LEVEL_THRESHOLDS[level] as u128;
+            let next = LEVEL_THRESHOLDS[level + 1] as u128;
+            let e0 = LEVEL_E0 as u128;
+            let low = e0 + ((current - 1) * LEVEL_Q_NUM as u128) / LEVEL_Q_DEN as u128;
+            let high = e0 + (current * LEVEL_Q_NUM as u128).div_ceil(LEVEL_Q_DEN as u128);
+            assert!(low <= next && next <= high, "level={}", level + 1);
+        }
+    }
+
+    #[test]
+    fn test_calc_level_boundaries() {
+        assert_eq!(calc_level(0), 0);
+        for level in 1..=MAX_LEVEL {
+            let threshold = level_threshold(level).unwrap();
+            assert_eq!(calc_level(threshold - 1), level - 1);
+            assert_eq!(calc_level(threshold), level);
+        }
+        assert_eq!(calc_level(u64::MAX), MAX_LEVEL);
+        assert_eq!(level_threshold(MAX_LEVEL + 1), None);
+
+        // UIP-0005 sample table.
+        assert_eq!(calc_level(4_320_000), 3);
+        assert_eq!(calc_level(25_920_000), 10);
+        assert_eq!(calc_level(52_560_000), 14);
+        assert_eq!(calc_level(210_000_000), 22);
+        assert_eq!(calc_level(525_600_000), 27);
+        assert_eq!(calc_level(5_256_000_000), 41);
+    }
+
+    #[test]
+    fn test_difficulty_factor_and_real_difficulty() {
+        assert_eq!(calc_difficulty_factor_bps(0), 10_000);
+        assert_eq!(calc_difficulty_factor_bps(1), 9_900);
+        assert_eq!(
+            calc_difficulty_factor_bps(MAX_LEVEL),
+            MIN_DIFFICULTY_FACTOR_BPS
+        );
+        assert_eq!(
+            calc_difficulty_factor_bps(u32::MAX),
+            MIN_DIFFICULTY_FACTOR_BPS
+        );
+
+        assert_eq!(calc_real_difficulty(101, 9_900).unwrap(), 100);
+        assert_eq!(calc_real_difficulty(1, 5_000).unwrap(), 1);
+        assert_eq!(calc_real_difficulty(20_000, 5_000).unwrap(), 10_000);
+        assert!(calc_real_difficulty(0, 9_900).is_err());
+        assert!(calc_real_difficulty(100, 4_999).is_err());
+        assert!(calc_real_difficulty(u128::MAX, 9_900).is_err());
+    }
+}
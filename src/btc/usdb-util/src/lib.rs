@@ -1,21 +1,35 @@
+mod activation;
+mod balance_proof;
+mod balance_state;
 mod btc;
 mod config;
 mod constants;
+mod descriptor;
 mod dirs;
 mod hash;
+mod level;
 mod lock;
 mod log_util;
 mod mem;
+mod muhash;
+mod subscription;
 mod types;
 
+pub use activation::*;
+pub use balance_proof::*;
+pub use balance_state::*;
 pub use btc::*;
 pub use config::*;
 pub use constants::*;
+pub use descriptor::*;
 pub use dirs::*;
 pub use hash::*;
+pub use level::*;
 pub use lock::*;
 pub use log_util::*;
 pub use mem::*;
+pub use muhash::*;
+pub use subscription::*;
 pub use types::*;
 
 pub use named_lock::{NamedLock, NamedLockGuard};
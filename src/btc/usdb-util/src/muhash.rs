@@ -0,0 +1,342 @@
+use bitcoincore_rpc::bitcoin::hashes::Hash;
+use bitcoincore_rpc::bitcoin::{OutPoint, Script};
+use num_bigint::BigUint;
+use rayon::prelude::*;
+use sha2::{Digest, Sha256};
+use std::sync::OnceLock;
+
+/// Byte length of one Num3072 element.
+pub const MUHASH_NUM3072_LEN: usize = 384;
+
+/// Byte length of a serialized MuHash3072 accumulator: numerator then denominator.
+pub const MUHASH_STATE_LEN: usize = MUHASH_NUM3072_LEN * 2;
+
+// bitcoind excludes scripts above MAX_SCRIPT_SIZE from the UTXO set as unspendable.
+const MAX_SCRIPT_SIZE: usize = 10_000;
+
+// The MuHash3072 modulus is the largest 3072-bit safe prime, 2^3072 - 1103717.
+const MUHASH_PRIME_DIFF: u32 = 1_103_717;
+
+fn muhash_prime() -> &'static BigUint {
+    static PRIME: OnceLock<BigUint> = OnceLock::new();
+    PRIME.get_or_init(|| (BigUint::from(1u32) << 3072) - BigUint::from(MUHASH_PRIME_DIFF))
+}
+
+/// Rolling multiset hash compatible with bitcoind's `MuHash3072`.
+///
+/// Elements are 32-byte digests expanded to Num3072 with ChaCha20. Insertions are
+/// multiplied into the numerator and removals into the denominator, so the division
+/// is deferred to `finalize`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MuHash3072 {
+    numerator: BigUint,
+    denominator: BigUint,
+}
+
+impl Default for MuHash3072 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MuHash3072 {
+    /// Hash of the empty set.
+    pub fn new() -> Self {
+        Self {
+            numerator: BigUint::from(1u32),
+            denominator: BigUint::from(1u32),
+        }
+    }
+
+    /// Build the accumulator delta of inserting and removing the given digests.
+    ///
+    /// Products are computed in parallel, which makes this the preferred way to fold a
+    /// whole block into a running state with `combine`.
+    pub fn from_digests(inserted: &[[u8; 32]], removed: &[[u8; 32]]) -> Self {
+        Self {
+            numerator: product_of_digests(inserted),
+            denominator: product_of_digests(removed),
+        }
+    }
+
+    pub fn insert_digest(&mut self, digest: &[u8; 32]) {
+        self.numerator = mul_mod(&self.numerator, &digest_to_num3072(digest));
+    }
+
+    pub fn remove_digest(&mut self, digest: &[u8; 32]) {
+        self.denominator = mul_mod(&self.denominator, &digest_to_num3072(digest));
+    }
+
+    /// Insert arbitrary data, hashed with SHA256 first like bitcoind's `Insert`.
+    pub fn insert(&mut self, data: &[u8]) {
+        self.insert_digest(&Sha256::digest(data).into());
+    }
+
+    /// Remove arbitrary data, hashed with SHA256 first like bitcoind's `Remove`.
+    pub fn remove(&mut self, data: &[u8]) {
+        self.remove_digest(&Sha256::digest(data).into());
+    }
+
+    /// Merge another accumulator into this one (set union / difference).
+    pub fn combine(&mut self, other: &MuHash3072) {
+        self.numerator = mul_mod(&self.numerator, &other.numerator);
+        self.denominator = mul_mod(&self.denominator, &other.denominator);
+    }
+
+    /// Finalize to the 32-byte set hash, in the byte order bitcoind stores its uint256.
+    ///
+    /// This performs one modular inversion, so callers should keep the accumulator
+    /// form around and only finalize on demand.
+    pub fn finalize(&self) -> [u8; 32] {
+        let prime = muhash_prime();
+        let exponent = prime - BigUint::from(2u32);
+        let inverse = self.denominator.modpow(&exponent, prime);
+        let value = mul_mod(&self.numerator, &inverse);
+        Sha256::digest(num3072_to_bytes(&value)).into()
+    }
+
+    pub fn to_bytes(&self) -> [u8; MUHASH_STATE_LEN] {
+        let mut bytes = [0u8; MUHASH_STATE_LEN];
+        bytes[..MUHASH_NUM3072_LEN].copy_from_slice(&num3072_to_bytes(&self.numerator));
+        bytes[MUHASH_NUM3072_LEN..].copy_from_slice(&num3072_to_bytes(&self.denominator));
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, String> {
+        if bytes.len() != MUHASH_STATE_LEN {
+            let msg = format!(
+                "Invalid MuHash3072 state length: expected {}, got {}",
+                MUHASH_STATE_LEN,
+                bytes.len()
+            );
+            error!("{}", msg);
+            return Err(msg);
+        }
+
+        Ok(Self {
+            numerator: BigUint::from_bytes_le(&bytes[..MUHASH_NUM3072_LEN]),
+            denominator: BigUint::from_bytes_le(&bytes[MUHASH_NUM3072_LEN..]),
+        })
+    }
+}
+
+/// Hex form of a finalized UTXO set hash, byte-reversed like bitcoind's uint256 output.
+pub fn utxo_set_hash_to_hex(hash: &[u8; 32]) -> String {
+    let mut reversed = *hash;
+    reversed.reverse();
+    crate::encode_hex(&reversed)
+}
+
+/// Whether bitcoind keeps an output with this script in its UTXO set.
+pub fn is_muhash_tracked_script(script: &Script) -> bool {
+    !script.is_op_return() && script.len() <= MAX_SCRIPT_SIZE
+}
+
+/// SHA256 of bitcoind's `TxOutSer` encoding of one coin, the element its coinstats
+/// index inserts into MuHash for every UTXO.
+pub fn hash_utxo_for_muhash(
+    outpoint: &OutPoint,
+    block_height: u32,
+    is_coinbase: bool,
+    value: u64,
+    script: &Script,
+) -> [u8; 32] {
+    let script_bytes = script.as_bytes();
+    let mut hasher = Sha256::new();
+    hasher.update(outpoint.txid.as_byte_array());
+    hasher.update(outpoint.vout.to_le_bytes());
+    hasher.update(((block_height << 1) | is_coinbase as u32).to_le_bytes());
+    hasher.update(value.to_le_bytes());
+    hasher.update(encode_compact_size(script_bytes.len() as u64));
+    hasher.update(script_bytes);
+    hasher.finalize().into()
+}
+
+fn encode_compact_size(n: u64) -> Vec<u8> {
+    match n {
+        0..=0xFC => vec![n as u8],
+        0xFD..=0xFFFF => {
+            let mut bytes = vec![0xFD];
+            bytes.extend_from_slice(&(n as u16).to_le_bytes());
+            bytes
+        }
+        0x1_0000..=0xFFFF_FFFF => {
+            let mut bytes = vec![0xFE];
+            bytes.extend_from_slice(&(n as u32).to_le_bytes());
+            bytes
+        }
+        _ => {
+            let mut bytes = vec![0xFF];
+            bytes.extend_from_slice(&n.to_le_bytes());
+            bytes
+        }
+    }
+}
+
+fn mul_mod(left: &BigUint, right: &BigUint) -> BigUint {
+    (left * right) % muhash_prime()
+}
+
+fn product_of_digests(digests: &[[u8; 32]]) -> BigUint {
+    digests
+        .par_iter()
+        .map(digest_to_num3072)
+        .reduce(|| BigUint::from(1u32), |left, right| mul_mod(&left, &right))
+}
+
+fn num3072_to_bytes(value: &BigUint) -> [u8; MUHASH_NUM3072_LEN] {
+    let mut bytes = [0u8; MUHASH_NUM3072_LEN];
+    let le = value.to_bytes_le();
+    bytes[..le.len()].copy_from_slice(&le);
+    bytes
+}
+
+// ToNum3072: the ChaCha20 keystream keyed by the digest (zero nonce, counter from 0),
+// read as a little-endian 3072-bit number.
+fn digest_to_num3072(digest: &[u8; 32]) -> BigUint {
+    let mut key = [0u32; 8];
+    for (i, word) in key.iter_mut().enumerate() {
+        *word = u32::from_le_bytes(digest[i * 4..i * 4 + 4].try_into().unwrap());
+    }
+
+    let mut bytes = [0u8; MUHASH_NUM3072_LEN];
+    for (counter, chunk) in bytes.chunks_mut(64).enumerate() {
+        chunk.copy_from_slice(&chacha20_block(&key, counter as u32));
+    }
+    BigUint::from_bytes_le(&bytes)
+}
+
+fn chacha20_block(key: &[u32; 8], counter: u32) -> [u8; 64] {
+    let mut input = [0u32; 16];
+    input[..4].copy_from_slice(&[0x6170_7865, 0x3320_646e, 0x7962_2d32, 0x6b20_6574]);
+    input[4..12].copy_from_slice(key);
+    input[12] = counter;
+
+    let mut state = input;
+    for _ in 0..10 {
+        quarter_round(&mut state, 0, 4, 8, 12);
+        quarter_round(&mut state, 1, 5, 9, 13);
+        quarter_round(&mut state, 2, 6, 10, 14);
+        quarter_round(&mut state, 3, 7, 11, 15);
+        quarter_round(&mut state, 0, 5, 10, 15);
+        quarter_round(&mut state, 1, 6, 11, 12);
+        quarter_round(&mut state, 2, 7, 8, 13);
+        quarter_round(&mut state, 3, 4, 9, 14);
+    }
+
+    let mut out = [0u8; 64];
+    for i in 0..16 {
+        out[i * 4..i * 4 + 4].copy_from_slice(&state[i].wrapping_add(input[i]).to_le_bytes());
+    }
+    out
+}
+
+fn quarter_round(state: &mut [u32; 16], a: usize, b: usize, c: usize, d: usize) {
+    state[a] = state[a].wrapping_add(state[b]);
+    state[d] = (state[d] ^ state[a]).rotate_left(16);
+    state[c] = state[c].wrapping_add(state[d]);
+    state[b] = (state[b] ^ state[c]).rotate_left(12);
+    state[a] = state[a].wrapping_add(state[b]);
+    state[d] = (state[d] ^ state[a]).rotate_left(8);
+    state[c] = state[c].wrapping_add(state[d]);
+    state[b] = (state[b] ^ state[c]).rotate_left(7);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn from_int(i: u8) -> MuHash3072 {
+        let mut data = [0u8; 32];
+        data[0] = i;
+        let mut muhash = MuHash3072::new();
+        muhash.insert(&data);
+        muhash
+    }
+
+    #[test]
+    fn test_chacha20_matches_rfc7539_keystream() {
+        // RFC 7539 appendix A.1, test vector #1: all-zero key and nonce, counter 0.
+        let block = chacha20_block(&[0u32; 8], 0);
+        assert_eq!(
+            crate::encode_hex(&block[..16]),
+            "76b8e0ada0f13d90405d6ae55386bd28"
+        );
+    }
+
+    #[test]
+    fn test_muhash_matches_bitcoind_vector() {
+        let mut acc = from_int(0);
+        acc.combine(&from_int(1));
+        let mut divisor = from_int(2);
+        std::mem::swap(&mut divisor.numerator, &mut divisor.denominator);
+        acc.combine(&divisor);
+
+        assert_eq!(
+            utxo_set_hash_to_hex(&acc.finalize()),
+            "10d312b100cbd32ada024a6646e40d3482fcff103668d2625f10002a607d5863"
+        );
+    }
+
+    #[test]
+    fn test_muhash_insert_remove_is_order_independent() {
+        let digests: Vec<[u8; 32]> = (0u8..8).map(|i| [i; 32]).collect();
+
+        let mut forward = MuHash3072::new();
+        for digest in &digests {
+            forward.insert_digest(digest);
+        }
+        forward.remove_digest(&digests[3]);
+
+        let mut batched = MuHash3072::from_digests(&digests[4..], &[]);
+        batched.combine(&MuHash3072::from_digests(&digests[..4], &digests[3..4]));
+        assert_eq!(forward.finalize(), batched.finalize());
+
+        let mut without = MuHash3072::new();
+        for (i, digest) in digests.iter().enumerate().rev() {
+            if i != 3 {
+                without.insert_digest(digest);
+            }
+        }
+        assert_eq!(forward.finalize(), without.finalize());
+        assert_ne!(forward.finalize(), MuHash3072::new().finalize());
+    }
+
+    #[test]
+    fn test_muhash_state_round_trip() {
+        let mut muhash = MuHash3072::from_digests(&[[1u8; 32], [2u8; 32]], &[[3u8; 32]]);
+        muhash.insert(b"coin");
+
+        let restored = MuHash3072::from_bytes(&muhash.to_bytes()).unwrap();
+        assert_eq!(restored, muhash);
+        assert_eq!(restored.finalize(), muhash.finalize());
+        assert!(MuHash3072::from_bytes(&[0u8; 10]).is_err());
+    }
+
+    #[test]
+    fn test_hash_utxo_for_muhash_commits_to_coin_fields() {
+        let outpoint = OutPoint {
+            txid: bitcoincore_rpc::bitcoin::Txid::from_byte_array([9u8; 32]),
+            vout: 1,
+        };
+        let script = bitcoincore_rpc::bitcoin::ScriptBuf::from(vec![0x51]);
+        let base = hash_utxo_for_muhash(&outpoint, 100, false, 5000, &script);
+
+        assert_ne!(
+            base,
+            hash_utxo_for_muhash(&outpoint, 100, true, 5000, &script)
+        );
+        assert_ne!(
+            base,
+            hash_utxo_for_muhash(&outpoint, 101, false, 5000, &script)
+        );
+        assert_ne!(
+            base,
+            hash_utxo_for_muhash(&outpoint, 100, false, 5001, &script)
+        );
+        assert!(is_muhash_tracked_script(&script));
+        assert!(!is_muhash_tracked_script(
+            &bitcoincore_rpc::bitcoin::ScriptBuf::from(vec![0x6a])
+        ));
+    }
+}
@@ -0,0 +1,301 @@
+use jsonrpsee::core::SubscriptionResult;
+use jsonrpsee::server::{RandomStringIdProvider, Server, ServerConfig, ServerHandle};
+use jsonrpsee::{PendingSubscriptionSink, RpcModule, SubscriptionMessage};
+use serde::Serialize;
+use serde_json::value::RawValue;
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use tokio::sync::broadcast;
+
+// WebSocket JSON-RPC subscriptions shared by balance-history and usdb-indexer,
+// served by the jsonrpsee pubsub server.
+//
+// Wire format is the jsonrpsee convention:
+// - `subscribe_<topic>` returns a subscription id.
+// - Notifications reuse the subscribe method name and carry
+//   `{"subscription": <id>, "result": <event>}`.
+// - `unsubscribe_<topic>` with `[<id>]` returns whether the id was active.
+//
+// The hub never replays history. Services publish only durable events, and a
+// subscriber that falls behind the channel capacity is closed with a
+// `SUBSCRIPTION_LAGGED` error notification and must resync through the HTTP
+// JSON-RPC API.
+
+/// Number of events buffered per topic before slow subscribers start lagging.
+pub const SUBSCRIPTION_CHANNEL_CAPACITY: usize = 1_024;
+/// Maximum number of live subscriptions on one WebSocket connection.
+pub const MAX_SUBSCRIPTIONS_PER_CONNECTION: u32 = 64;
+/// Error message prefix of the notification that closes a lagging subscription.
+pub const SUBSCRIPTION_LAGGED: &str = "SUBSCRIPTION_LAGGED";
+
+const SUBSCRIBE_METHOD_PREFIX: &str = "subscribe_";
+const UNSUBSCRIBE_METHOD_PREFIX: &str = "unsubscribe_";
+const MAX_WS_MESSAGE_SIZE: u32 = 1024 * 1024;
+const SUBSCRIPTION_ID_LEN: usize = 16;
+
+struct SubscriptionTopic {
+    unsubscribe_method: &'static str,
+    sender: broadcast::Sender<Box<RawValue>>,
+}
+
+/// Fan-out point between a service and its WebSocket subscribers.
+///
+/// Topics are keyed by their `subscribe_*` method name and fixed at construction.
+pub struct SubscriptionHub {
+    topics: HashMap<&'static str, SubscriptionTopic>,
+}
+
+pub type SubscriptionHubRef = Arc<SubscriptionHub>;
+
+impl SubscriptionHub {
+    pub fn new(subscribe_methods: &[&'static str]) -> Result<Self, String> {
+        let mut topics = HashMap::new();
+        for method in subscribe_methods {
+            let Some(topic) = method
+                .strip_prefix(SUBSCRIBE_METHOD_PREFIX)
+                .filter(|topic| !topic.is_empty())
+            else {
+                let msg = format!(
+                    "Invalid subscription method {}: expected {}<topic>",
+                    method, SUBSCRIBE_METHOD_PREFIX
+                );
+                error!("{}", msg);
+                return Err(msg);
+            };
+
+            // jsonrpsee only registers `&'static str` method names. Hubs are built once per
+            // process, so leaking the derived unsubscribe names is bounded.
+            let unsubscribe_method: &'static str =
+                Box::leak(format!("{}{}", UNSUBSCRIBE_METHOD_PREFIX, topic).into_boxed_str());
+            let (sender, _) = broadcast::channel(SUBSCRIPTION_CHANNEL_CAPACITY);
+            topics.insert(
+                *method,
+                SubscriptionTopic {
+                    unsubscribe_method,
+                    sender,
+                },
+            );
+        }
+
+        Ok(Self { topics })
+    }
+
+    /// Publishes one event to every subscriber of `subscribe_method`.
+    ///
+    /// Returns the number of subscribers that received the event; zero
+    /// subscribers is not an error.
+    pub fn publish<T: Serialize>(
+        &self,
+        subscribe_method: &str,
+        event: &T,
+    ) -> Result<usize, String> {
+        let topic = self.topics.get(subscribe_method).ok_or_else(|| {
+            let msg = format!("Unknown subscription method {}", subscribe_method);
+            error!("{}", msg);
+            msg
+        })?;
+        let value = serde_json::value::to_raw_value(event).map_err(|e| {
+            let msg = format!(
+                "Failed to serialize subscription event: method={}, error={}",
+                subscribe_method, e
+            );
+            error!("{}", msg);
+            msg
+        })?;
+
+        Ok(topic.sender.send(value).unwrap_or(0))
+    }
+
+    pub fn subscriber_count(&self, subscribe_method: &str) -> usize {
+        self.topics
+            .get(subscribe_method)
+            .map(|topic| topic.sender.receiver_count())
+            .unwrap_or(0)
+    }
+
+    fn build_rpc_module(&self) -> Result<RpcModule<()>, String> {
+        let mut module = RpcModule::new(());
+        for (&subscribe_method, topic) in &self.topics {
+            let sender = topic.sender.clone();
+            module
+                .register_subscription(
+                    subscribe_method,
+                    subscribe_method,
+                    topic.unsubscribe_method,
+                    move |_params, pending, _ctx, _extensions| {
+                        // Subscribe before accepting, so no event published after the
+                        // subscription id is returned can be missed.
+                        let receiver = sender.subscribe();
+                        forward_events(pending, receiver)
+                    },
+                )
+                .map_err(|e| {
+                    let msg = format!(
+                        "Failed to register subscription method {}: {}",
+                        subscribe_method, e
+                    );
+                    error!("{}", msg);
+                    msg
+                })?;
+        }
+
+        Ok(module)
+    }
+}
+
+async fn forward_events(
+    pending: PendingSubscriptionSink,
+    mut receiver: broadcast::Receiver<Box<RawValue>>,
+) -> SubscriptionResult {
+    let sink = pending.accept().await?;
+    loop {
+        tokio::select! {
+            _ = sink.closed() => return Ok(()),
+            ret = receiver.recv() => match ret {
+                Ok(event) => sink.send(SubscriptionMessage::from(event)).await?,
+                Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                    debug!(
+                        "Closing lagging subscription: subscription_id={:?}, skipped={}",
+                        sink.subscription_id(),
+                        skipped
+                    );
+                    let msg = format!("{}: skipped {} events", SUBSCRIPTION_LAGGED, skipped);
+                    return Err(msg.into());
+                }
+                Err(broadcast::error::RecvError::Closed) => return Ok(()),
+            },
+        }
+    }
+}
+
+/// WebSocket listener serving the topics of one `SubscriptionHub`.
+pub struct WsSubscriptionServer {
+    addr: SocketAddr,
+    handle: ServerHandle,
+}
+
+impl WsSubscriptionServer {
+    /// Binds `addr` and starts serving on the current tokio runtime.
+    pub async fn start(addr: SocketAddr, hub: SubscriptionHubRef) -> Result<Self, String> {
+        let module = hub.build_rpc_module()?;
+        let server_config = ServerConfig::builder()
+            .ws_only()
+            .max_request_body_size(MAX_WS_MESSAGE_SIZE)
+            .max_response_body_size(MAX_WS_MESSAGE_SIZE)
+            .max_subscriptions_per_connection(MAX_SUBSCRIPTIONS_PER_CONNECTION)
+            .set_id_provider(RandomStringIdProvider::new(SUBSCRIPTION_ID_LEN))
+            .build();
+        let server = Server::builder()
+            .set_config(server_config)
+            .build(addr)
+            .await
+            .map_err(|e| {
+                let msg = format!("Unable to start WebSocket server on {}: {}", addr, e);
+                error!("{}", msg);
+                msg
+            })?;
+        let addr = server.local_addr().map_err(|e| {
+            let msg = format!("Failed to get WebSocket server address: {}", e);
+            error!("{}", msg);
+            msg
+        })?;
+
+        let handle = server.start(module);
+        info!("WebSocket subscription server listening on {}", addr);
+
+        Ok(Self { addr, handle })
+    }
+
+    pub fn local_addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    pub fn get_listen_url(&self) -> String {
+        format!("ws://{}", self.addr)
+    }
+
+    /// Stops accepting connections and closes every open connection.
+    pub async fn close(&self) {
+        if self.handle.stop().is_ok() {
+            self.handle.clone().stopped().await;
+            info!("WebSocket subscription server closed.");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use jsonrpsee::core::client::{ClientT, SubscriptionClientT};
+    use jsonrpsee::rpc_params;
+    use jsonrpsee::ws_client::WsClientBuilder;
+    use serde_json::{Value, json};
+    use std::time::Duration;
+
+    async fn wait_for_subscriber_count(hub: &SubscriptionHub, method: &str, expected: usize) {
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while hub.subscriber_count(method) != expected {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .unwrap();
+    }
+
+    #[test]
+    fn test_subscription_hub_rejects_invalid_topics_and_unknown_publish() {
+        assert!(SubscriptionHub::new(&["new_block"]).is_err());
+        assert!(SubscriptionHub::new(&["subscribe_"]).is_err());
+
+        let hub = SubscriptionHub::new(&["subscribe_new_block"]).unwrap();
+        assert_eq!(hub.publish("subscribe_new_block", &json!({})).unwrap(), 0);
+        assert!(hub.publish("subscribe_other", &json!({})).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_ws_subscription_roundtrip() {
+        let hub = Arc::new(SubscriptionHub::new(&["subscribe_new_block"]).unwrap());
+        let server = WsSubscriptionServer::start("127.0.0.1:0".parse().unwrap(), hub.clone())
+            .await
+            .unwrap();
+        let client = WsClientBuilder::default()
+            .build(server.get_listen_url())
+            .await
+            .unwrap();
+
+        let mut subscription = client
+            .subscribe::<Value, _>(
+                "subscribe_new_block",
+                rpc_params![],
+                "unsubscribe_new_block",
+            )
+            .await
+            .unwrap();
+        assert_eq!(hub.subscriber_count("subscribe_new_block"), 1);
+
+        hub.publish("subscribe_new_block", &json!({"block_height": 7}))
+            .unwrap();
+        let event = tokio::time::timeout(Duration::from_secs(5), subscription.next())
+            .await
+            .unwrap()
+            .unwrap()
+            .unwrap();
+        assert_eq!(event["block_height"], 7);
+
+        assert!(
+            client
+                .request::<Value, _>("subscribe_missing", rpc_params![])
+                .await
+                .is_err()
+        );
+
+        subscription.unsubscribe().await.unwrap();
+        wait_for_subscriber_count(&hub, "subscribe_new_block", 0).await;
+
+        server.close().await;
+        tokio::time::timeout(Duration::from_secs(5), client.on_disconnect())
+            .await
+            .unwrap();
+    }
+}
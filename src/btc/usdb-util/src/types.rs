@@ -79,6 +79,13 @@ pub const LOCAL_STATE_COMMIT_VERSION: &str = "usdb-local-state:v1";
 pub const SYSTEM_STATE_ID_HASH_ALGO: &str = "sha256";
 /// Version tag of the canonical BTC-side system-state id serialization rule.
 pub const SYSTEM_STATE_ID_VERSION: &str = "btc-system-state:v1";
+/// Hash algorithm used by validator candidate-set commitments.
+pub const VALIDATOR_CANDIDATE_SET_COMMITMENT_HASH_ALGO: &str = "sha256";
+/// Version tag of the validator candidate-set commitment derivation rule.
+pub const VALIDATOR_CANDIDATE_SET_COMMITMENT_VERSION: &str = "usdb-validator-candidate-set:v1";
+/// Candidate ordering rule: effective energy descending, then pass id ascending.
+pub const VALIDATOR_CANDIDATE_SELECTION_RULE_V1: &str =
+    "uip-0006:effective-energy-desc-pass-id-asc:v1";
 /// Version of the usdb-index derived-state formula set that participates in
 /// BTC-side consensus snapshot identity calculation.
 pub const USDB_INDEX_FORMULA_VERSION: &str = "pass-energy-formula:v1";
@@ -109,8 +116,8 @@ pub const CONSENSUS_RPC_ERR_SYSTEM_STATE_ID_MISMATCH: i64 = -32046;
 /// Shared JSON-RPC error code returned when the query is valid and within the
 /// durable range, but no record exists for the requested object/key.
 pub const CONSENSUS_RPC_ERR_NO_RECORD: i64 = -32047;
-/// Shared JSON-RPC error code reserved for heights that have fallen below the
-/// node's explicit retention floor.
+/// Shared JSON-RPC error code returned for heights that have fallen below the
+/// node's explicit retention floor, e.g. a pruned balance-history node.
 pub const CONSENSUS_RPC_ERR_STATE_NOT_RETAINED: i64 = -32048;
 /// Shared JSON-RPC error code returned when historical data should exist
 /// logically, but this node cannot currently reconstruct it.
@@ -278,6 +285,12 @@ pub struct ConsensusSnapshotIdentity {
     pub usdb_index_formula_version: String,
     /// Version of the usdb-index external protocol contract.
     pub usdb_index_protocol_version: String,
+    /// Full balance state root at `stable_height`, once activated for `network`.
+    ///
+    /// Only hashed into the snapshot id when present, so ids of heights without a
+    /// state root are unchanged.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub balance_state_root: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
@@ -324,7 +337,7 @@ pub struct SystemStateIdentity {
     pub local_state_commit: String,
 }
 
-fn encode_hex(bytes: &[u8]) -> String {
+pub(crate) fn encode_hex(bytes: &[u8]) -> String {
     let mut output = String::with_capacity(bytes.len() * 2);
     for byte in bytes {
         use std::fmt::Write;
@@ -333,7 +346,7 @@ fn encode_hex(bytes: &[u8]) -> String {
     output
 }
 
-fn update_string_component(hasher: &mut Sha256, value: &str) {
+pub(crate) fn update_string_component(hasher: &mut Sha256, value: &str) {
     hasher.update((value.len() as u32).to_be_bytes());
     hasher.update(value.as_bytes());
 }
@@ -354,6 +367,10 @@ pub fn build_consensus_snapshot_id(identity: &ConsensusSnapshotIdentity) -> Stri
     update_string_component(&mut hasher, &identity.balance_history_semantics_version);
     update_string_component(&mut hasher, &identity.usdb_index_formula_version);
     update_string_component(&mut hasher, &identity.usdb_index_protocol_version);
+    if let Some(balance_state_root) = &identity.balance_state_root {
+        update_optional_marker(&mut hasher, true);
+        update_string_component(&mut hasher, balance_state_root);
+    }
     encode_hex(&hasher.finalize())
 }
 
@@ -434,6 +451,56 @@ impl OutPointCodec {
     }
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ValidatorCandidateIdentity {
+    /// Candidate pass inscription id.
+    pub inscription_id: String,
+    /// Candidate owner script hash at the committed height.
+    pub owner: String,
+    /// UIP-0004 effective energy used for ordering.
+    pub effective_energy: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ValidatorCandidateSetIdentity {
+    /// System-state id of the BTC-side state the candidate set was derived from.
+    pub system_state_id: String,
+    /// BTC height of the candidate set.
+    pub block_height: u32,
+    /// Ordering rule applied to `candidates`.
+    pub selection_rule: String,
+    /// Maximum number of candidates kept after ordering.
+    pub max_candidates: u32,
+    /// Minimum effective energy required to enter the set.
+    pub min_effective_energy: u64,
+    /// Candidates in selection order; the first entry is the winner.
+    pub candidates: Vec<ValidatorCandidateIdentity>,
+}
+
+/// Builds the canonical commitment of one ordered validator candidate set.
+///
+/// The commitment binds the candidate order and selection parameters to one
+/// `system_state_id`, so a block body payload can be checked against the exact
+/// BTC-side state it claims to be derived from.
+pub fn build_validator_candidate_set_commitment(
+    identity: &ValidatorCandidateSetIdentity,
+) -> String {
+    let mut hasher = Sha256::new();
+    update_string_component(&mut hasher, VALIDATOR_CANDIDATE_SET_COMMITMENT_VERSION);
+    update_string_component(&mut hasher, &identity.system_state_id);
+    hasher.update(identity.block_height.to_be_bytes());
+    update_string_component(&mut hasher, &identity.selection_rule);
+    hasher.update(identity.max_candidates.to_be_bytes());
+    hasher.update(identity.min_effective_energy.to_be_bytes());
+    hasher.update((identity.candidates.len() as u32).to_be_bytes());
+    for candidate in &identity.candidates {
+        update_string_component(&mut hasher, &candidate.inscription_id);
+        update_string_component(&mut hasher, &candidate.owner);
+        hasher.update(candidate.effective_energy.to_be_bytes());
+    }
+    encode_hex(&hasher.finalize())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -450,12 +517,19 @@ mod tests {
             balance_history_semantics_version: "balance-snapshot-at-or-before:v1".to_string(),
             usdb_index_formula_version: "pass-energy-formula:v1".to_string(),
             usdb_index_protocol_version: "1.0.0".to_string(),
+            balance_state_root: None,
         };
 
         let a = build_consensus_snapshot_id(&identity);
         let b = build_consensus_snapshot_id(&identity);
         assert_eq!(a, b);
         assert_eq!(a.len(), 64);
+
+        let with_root = ConsensusSnapshotIdentity {
+            balance_state_root: Some("bb".repeat(32)),
+            ..identity
+        };
+        assert_ne!(build_consensus_snapshot_id(&with_root), a);
     }
 
     #[test]
@@ -509,6 +583,43 @@ mod tests {
         assert_ne!(base_id, changed_id);
     }
 
+    #[test]
+    fn test_build_validator_candidate_set_commitment_binds_order_and_state() {
+        let candidate = |id: &str, energy: u64| ValidatorCandidateIdentity {
+            inscription_id: id.to_string(),
+            owner: "dd".repeat(32),
+            effective_energy: energy,
+        };
+        let base = ValidatorCandidateSetIdentity {
+            system_state_id: "aa".repeat(32),
+            block_height: 120,
+            selection_rule: VALIDATOR_CANDIDATE_SELECTION_RULE_V1.to_string(),
+            max_candidates: 10,
+            min_effective_energy: 0,
+            candidates: vec![candidate("a", 200), candidate("b", 100)],
+        };
+
+        let base_commitment = build_validator_candidate_set_commitment(&base);
+        assert_eq!(base_commitment.len(), 64);
+        assert_eq!(
+            base_commitment,
+            build_validator_candidate_set_commitment(&base.clone())
+        );
+
+        let mut reordered = base.clone();
+        reordered.candidates.reverse();
+        let mut other_state = base.clone();
+        other_state.system_state_id = "bb".repeat(32);
+        let mut other_limit = base.clone();
+        other_limit.max_candidates = 11;
+        for changed in [reordered, other_state, other_limit] {
+            assert_ne!(
+                base_commitment,
+                build_validator_candidate_set_commitment(&changed)
+            );
+        }
+    }
+
     #[test]
     fn test_consensus_rpc_error_code_contract_is_stable() {
         assert_eq!(
@@ -343,6 +343,13 @@ export interface BtcMintPrepareActivePassSummary {
   prev: string[]
 }
 
+export interface BtcMintPreparePendingBalanceSummary {
+  stable_height: number
+  confirmed_balance: number
+  pending_balance: number
+  pending_tx_count: number
+}
+
 export interface BtcMintPrepareResponse {
   eligible: boolean
   prepare_mode: string
@@ -356,6 +363,7 @@ export interface BtcMintPrepareResponse {
   prev: string[]
   suggested_prev: string[]
   active_pass?: BtcMintPrepareActivePassSummary | null
+  pending_balance?: BtcMintPreparePendingBalanceSummary | null
   inscription_payload: Record<string, unknown>
   inscription_payload_json: string
   prepare_request: Record<string, unknown>